@@ -0,0 +1,171 @@
+"use strict";
+
+const $ = (id) => document.getElementById(id);
+const HIDDEN_FIELDS = ["category", "key"];
+
+let currentCategory = null;
+let editing = null; // {category, key} of the item open in the editor
+
+// serve-ui prints a URL carrying the API token in its fragment; keep it for
+// the session and take it out of the address bar.
+const TOKEN_KEY = "fmemory-token";
+const fragmentToken = new URLSearchParams(location.hash.slice(1)).get("token");
+if (fragmentToken) {
+  sessionStorage.setItem(TOKEN_KEY, fragmentToken);
+  history.replaceState(null, "", location.pathname + location.search);
+}
+
+async function api(path, options = {}) {
+  const token = sessionStorage.getItem(TOKEN_KEY) || "";
+  const response = await fetch(path, {
+    ...options,
+    headers: { "Content-Type": "application/json", Authorization: `Bearer ${token}` },
+  });
+  if (response.status === 401) {
+    const entered = prompt("API token (printed by fmemory serve-ui):");
+    if (entered) {
+      sessionStorage.setItem(TOKEN_KEY, entered.trim());
+      return api(path, options);
+    }
+  }
+  if (response.status === 204) return null;
+  const body = await response.json();
+  if (!response.ok) throw new Error(body.error || response.statusText);
+  return body;
+}
+
+function el(tag, className, text) {
+  const node = document.createElement(tag);
+  if (className) node.className = className;
+  if (text !== undefined) node.textContent = text;
+  return node;
+}
+
+function preview(item) {
+  return Object.entries(item)
+    .filter(([k, v]) => !HIDDEN_FIELDS.includes(k) && v !== null && k !== "created_at")
+    .map(([k, v]) => `${k}: ${typeof v === "string" ? v : JSON.stringify(v)}`)
+    .join(" · ");
+}
+
+function setStatus(message, isError = false) {
+  $("status").textContent = message;
+  $("status").className = isError ? "error" : "";
+}
+
+async function loadCategories() {
+  const categories = await api("/api/categories");
+  const list = $("categories");
+  list.replaceChildren();
+  for (const category of categories) {
+    const li = el("li", category.name === currentCategory ? "active" : "", category.name);
+    if (category.description) li.title = category.description;
+    li.onclick = () => showCategory(category.name);
+    list.append(li);
+  }
+}
+
+function renderItems(title, items) {
+  $("list-title").textContent = title;
+  const list = $("items");
+  list.replaceChildren();
+  if (items.length === 0) {
+    list.append(el("li", "meta", "No items"));
+    return;
+  }
+  for (const item of items) {
+    const li = el("li");
+    li.append(
+      el("div", "key", `${item.category}/${item.key}`),
+      el("div", "preview", preview(item)),
+      el("div", "meta", item.created_at || ""),
+    );
+    li.onclick = () => openItem(item.category, item.key);
+    list.append(li);
+  }
+}
+
+async function showCategory(name) {
+  currentCategory = name;
+  const items = await api(`/api/categories/${encodeURIComponent(name)}/items`);
+  renderItems(name, items);
+  loadCategories();
+}
+
+async function runSearch(query) {
+  currentCategory = null;
+  const items = await api(`/api/search?q=${encodeURIComponent(query)}`);
+  renderItems(`Search: ${query}`, items);
+  loadCategories();
+}
+
+function itemPath(category, key) {
+  return `/api/items/${encodeURIComponent(category)}/${encodeURIComponent(key)}`;
+}
+
+async function openItem(category, key) {
+  const item = await api(itemPath(category, key));
+  const attributes = Object.fromEntries(
+    Object.entries(item).filter(([k]) => !HIDDEN_FIELDS.includes(k)),
+  );
+  editing = { category, key };
+  $("editor-title").textContent = `${category}/${key}`;
+  $("edit-category").value = category;
+  $("edit-key").value = key;
+  $("edit-body").value = JSON.stringify(attributes, null, 2);
+  $("delete").hidden = false;
+  $("editor").hidden = false;
+  setStatus("");
+}
+
+async function saveItem() {
+  const category = $("edit-category").value.trim();
+  const key = $("edit-key").value.trim();
+  let body;
+  try {
+    body = JSON.parse($("edit-body").value || "{}");
+  } catch (e) {
+    setStatus(`Invalid JSON: ${e.message}`, true);
+    return;
+  }
+  try {
+    await api(itemPath(category, key), { method: "PUT", body: JSON.stringify(body) });
+    // Renaming: remove the item under its old address.
+    if (editing && (editing.category !== category || editing.key !== key)) {
+      await api(itemPath(editing.category, editing.key), { method: "DELETE" });
+    }
+    setStatus("Saved");
+    await showCategory(category);
+    await openItem(category, key);
+  } catch (e) {
+    setStatus(e.message, true);
+  }
+}
+
+async function deleteItem() {
+  if (!editing || !confirm(`Delete ${editing.category}/${editing.key}?`)) return;
+  try {
+    await api(itemPath(editing.category, editing.key), { method: "DELETE" });
+    $("editor").hidden = true;
+    await showCategory(editing.category);
+    editing = null;
+  } catch (e) {
+    setStatus(e.message, true);
+  }
+}
+
+$("search-form").onsubmit = (event) => {
+  event.preventDefault();
+  const query = $("search").value.trim();
+  if (query) runSearch(query).catch((e) => setStatus(e.message, true));
+};
+$("save").onclick = saveItem;
+$("delete").onclick = deleteItem;
+$("close").onclick = () => {
+  $("editor").hidden = true;
+  editing = null;
+};
+
+loadCategories().catch((e) => {
+  $("list-title").textContent = `Failed to load categories: ${e.message}`;
+});
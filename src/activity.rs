@@ -0,0 +1,261 @@
+//! Per-category read and write counts.
+//!
+//! The backend counts the reads (`get`, queries, index lookups) and writes
+//! (puts and deletes) served for each category in [`ActivityCounters`], and
+//! persists them into the reserved `category_activity` partition of the
+//! namespace: one item per category, keyed by its name, holding running
+//! `reads` and `writes` totals and when each last happened. Counts are
+//! flushed at most every [`ACTIVITY_FLUSH_INTERVAL`] while the process runs
+//! and when a CLI command finishes or the MCP server drains.
+//!
+//! Bookkeeping (access times, recall logs) and the reserved partitions are
+//! not counted, and maintenance commands such as `stats` and `prune` turn
+//! counting off, so the totals reflect what categories are used for.
+//! [`activity_report`] lines them up with the defined categories for
+//! `fmemory stats --activity`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::backend::MemoryBackend;
+use crate::error::MemoryError;
+
+/// Reserved partition holding the persisted counts.
+pub const ACTIVITY_CATEGORY: &str = "_category_activity";
+
+/// Longest time counts stay in memory while the process is busy.
+pub const ACTIVITY_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Maximum number of activity items read by [`activity_report`].
+const ACTIVITY_SCAN_LIMIT: usize = 10_000;
+
+/// Kind of access counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// Accesses to one category not persisted yet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PendingActivity {
+    pub reads: u64,
+    pub writes: u64,
+    pub last_read: Option<DateTime<Utc>>,
+    pub last_write: Option<DateTime<Utc>>,
+}
+
+impl PendingActivity {
+    fn absorb(&mut self, other: PendingActivity) {
+        self.reads += other.reads;
+        self.writes += other.writes;
+        self.last_read = self.last_read.max(other.last_read);
+        self.last_write = self.last_write.max(other.last_write);
+    }
+}
+
+/// Counts not persisted yet, per table and category, shared between clones.
+#[derive(Debug, Clone)]
+pub struct ActivityCounters {
+    pending: Arc<Mutex<HashMap<(String, String), PendingActivity>>>,
+    last_flush: Arc<Mutex<Instant>>,
+}
+
+impl Default for ActivityCounters {
+    fn default() -> Self {
+        Self {
+            pending: Arc::default(),
+            last_flush: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+}
+
+impl ActivityCounters {
+    /// Count one `access` to `category` of `table`.
+    pub fn record(&self, table: &str, category: &str, access: Access) {
+        let now = Utc::now();
+        let mut pending = self.pending.lock().unwrap();
+        let entry = pending
+            .entry((table.to_string(), category.to_string()))
+            .or_default();
+        match access {
+            Access::Read => {
+                entry.reads += 1;
+                entry.last_read = Some(now);
+            }
+            Access::Write => {
+                entry.writes += 1;
+                entry.last_write = Some(now);
+            }
+        }
+    }
+
+    /// Take every pending count, to be persisted.
+    pub fn take(&self) -> HashMap<(String, String), PendingActivity> {
+        *self.last_flush.lock().unwrap() = Instant::now();
+        std::mem::take(&mut *self.pending.lock().unwrap())
+    }
+
+    /// Put back counts that could not be persisted.
+    pub fn restore(&self, counts: HashMap<(String, String), PendingActivity>) {
+        let mut pending = self.pending.lock().unwrap();
+        for (id, counts) in counts {
+            pending.entry(id).or_default().absorb(counts);
+        }
+    }
+
+    /// Whether counts are waiting and the last flush was at least
+    /// [`ACTIVITY_FLUSH_INTERVAL`] ago. Restarts the interval when it is, so
+    /// concurrent callers flush once.
+    pub fn flush_due(&self) -> bool {
+        let mut last_flush = self.last_flush.lock().unwrap();
+        if last_flush.elapsed() < ACTIVITY_FLUSH_INTERVAL || self.pending.lock().unwrap().is_empty()
+        {
+            return false;
+        }
+        *last_flush = Instant::now();
+        true
+    }
+}
+
+/// The persisted item for `category`: `existing` plus `pending`.
+pub fn merge_activity(
+    existing: Option<&Value>,
+    category: &str,
+    pending: &PendingActivity,
+    now: DateTime<Utc>,
+) -> Value {
+    let count = |name: &str| existing.and_then(|item| item[name].as_u64()).unwrap_or(0);
+    let latest = |name: &str, new: Option<DateTime<Utc>>| {
+        let old = existing
+            .and_then(|item| item[name].as_str())
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| at.with_timezone(&Utc));
+        old.max(new).map(|at| at.to_rfc3339())
+    };
+    let created_at = existing
+        .and_then(|item| item["created_at"].as_str())
+        .map(String::from)
+        .unwrap_or_else(|| now.to_rfc3339());
+    json!({
+        "category": ACTIVITY_CATEGORY,
+        "key": category,
+        "reads": count("reads") + pending.reads,
+        "writes": count("writes") + pending.writes,
+        "last_read_at": latest("last_read_at", pending.last_read),
+        "last_write_at": latest("last_write_at", pending.last_write),
+        "created_at": created_at,
+        "updated_at": now.to_rfc3339(),
+    })
+}
+
+/// Persisted use of one category.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CategoryActivity {
+    pub category: String,
+    pub reads: u64,
+    pub writes: u64,
+    pub last_read_at: Option<String>,
+    pub last_write_at: Option<String>,
+    /// When counting started for the category, if it has been used.
+    pub since: Option<String>,
+}
+
+/// Persisted activity of `categories`, plus any other category with
+/// recorded activity, busiest first. Categories never used report zeros.
+pub async fn activity_report(
+    backend: &MemoryBackend,
+    categories: &[String],
+) -> Result<Vec<CategoryActivity>, MemoryError> {
+    let mut report: BTreeMap<String, CategoryActivity> = categories
+        .iter()
+        .map(|category| {
+            (
+                category.clone(),
+                CategoryActivity {
+                    category: category.clone(),
+                    reads: 0,
+                    writes: 0,
+                    last_read_at: None,
+                    last_write_at: None,
+                    since: None,
+                },
+            )
+        })
+        .collect();
+    for item in backend
+        .query(ACTIVITY_CATEGORY, None, ACTIVITY_SCAN_LIMIT)
+        .await?
+    {
+        let Some(category) = item["key"].as_str() else {
+            continue;
+        };
+        let text = |name: &str| item[name].as_str().map(String::from);
+        report.insert(
+            category.to_string(),
+            CategoryActivity {
+                category: category.to_string(),
+                reads: item["reads"].as_u64().unwrap_or(0),
+                writes: item["writes"].as_u64().unwrap_or(0),
+                last_read_at: text("last_read_at"),
+                last_write_at: text("last_write_at"),
+                since: text("created_at"),
+            },
+        );
+    }
+    let mut report: Vec<CategoryActivity> = report.into_values().collect();
+    report.sort_by(|a, b| {
+        (b.reads + b.writes)
+            .cmp(&(a.reads + a.writes))
+            .then_with(|| a.category.cmp(&b.category))
+    });
+    Ok(report)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+
+    #[tokio::test]
+    async fn test_activity_counted_and_persisted() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        backend
+            .put_item(json!({"category": "contacts", "key": "toby", "name": "Toby"}))
+            .await
+            .unwrap();
+        backend.get_item("contacts", "toby").await.unwrap();
+        backend.query("contacts", None, 10).await.unwrap();
+        backend.query("decisions", None, 10).await.unwrap();
+        backend
+            .clone()
+            .with_activity_counting(false)
+            .query("contacts", None, 10)
+            .await
+            .unwrap();
+        backend.flush_activity().await.unwrap();
+
+        // Flushing again adds to the persisted totals.
+        backend.delete_item("contacts", "toby").await.unwrap();
+        backend.flush_activity().await.unwrap();
+
+        let categories = ["contacts".to_string(), "notes".to_string()];
+        let report = activity_report(&backend, &categories).await.unwrap();
+        let names: Vec<&str> = report.iter().map(|a| a.category.as_str()).collect();
+        assert_eq!(names, ["contacts", "decisions", "notes"]);
+        assert_eq!((report[0].reads, report[0].writes), (2, 2));
+        assert!(report[0].last_read_at.is_some() && report[0].since.is_some());
+        assert_eq!((report[1].reads, report[1].writes), (1, 0));
+        assert_eq!((report[2].reads, report[2].writes), (0, 0));
+        assert!(report[2].since.is_none());
+    }
+}
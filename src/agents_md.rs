@@ -0,0 +1,190 @@
+//! Agent instruction files (`AGENTS.md`, `CLAUDE.md`) generated from memory.
+//!
+//! [`generate_agents_md`] renders the live items of [`AGENTS_MD_SECTIONS`]
+//! (preferences, project knowledge, decisions, and tools), best
+//! [`decay_score`](crate::score::decay_score) first, one Markdown section
+//! each. Every section sits
+//! between `<!-- fmemory:begin NAME -->` and `<!-- fmemory:end NAME -->`
+//! markers: regenerating an existing file replaces only what is between
+//! them, so text written around the sections survives, and a section whose
+//! markers were removed is appended again at the end. A section missing only
+//! its end marker is refused rather than guessed at.
+
+use serde_json::Value;
+
+use crate::backend::MemoryBackend;
+use crate::chunk::is_chunk;
+use crate::context::item_summary;
+use crate::error::MemoryError;
+use crate::score::sort_by_score;
+use crate::ttl::is_expired;
+
+/// Generated sections, as category and heading, in file order.
+pub const AGENTS_MD_SECTIONS: &[(&str, &str)] = &[
+    ("preferences", "Preferences"),
+    ("project", "Project"),
+    ("decisions", "Decisions"),
+    ("tools", "Tools"),
+];
+
+/// Maximum number of items listed per section.
+pub const AGENTS_MD_SECTION_LIMIT: usize = 50;
+
+/// Maximum number of items read per category.
+const AGENTS_MD_SCAN_LIMIT: usize = 10_000;
+
+/// Opening of a new file, above the generated sections.
+const AGENTS_MD_HEADER: &str = "# Agent instructions\n\n\
+<!-- Sections between fmemory markers are regenerated by `fmemory generate agents-md`; \
+edit outside them. -->\n";
+
+fn begin_marker(section: &str) -> String {
+    format!("<!-- fmemory:begin {section} -->")
+}
+
+fn end_marker(section: &str) -> String {
+    format!("<!-- fmemory:end {section} -->")
+}
+
+/// The file to write: `existing` with its generated sections replaced, or a
+/// new file if there is none.
+pub async fn generate_agents_md(
+    backend: &MemoryBackend,
+    existing: Option<&str>,
+) -> Result<String, MemoryError> {
+    let mut sections = Vec::new();
+    for (category, heading) in AGENTS_MD_SECTIONS {
+        let mut items: Vec<Value> = backend
+            .query(category, None, AGENTS_MD_SCAN_LIMIT)
+            .await?
+            .into_iter()
+            .filter(|item| !is_chunk(item) && !is_expired(item))
+            .collect();
+        sort_by_score(&mut items, chrono::Utc::now());
+        items.truncate(AGENTS_MD_SECTION_LIMIT);
+        sections.push((*category, render_section(category, heading, &items)));
+    }
+    merge_sections(existing, &sections)
+}
+
+/// One section with its markers, listing `items` in order.
+pub fn render_section(section: &str, heading: &str, items: &[Value]) -> String {
+    let mut out = format!("{}\n## {heading}\n\n", begin_marker(section));
+    if items.is_empty() {
+        out.push_str("_Nothing stored yet._\n");
+    }
+    for item in items {
+        let key = item["key"].as_str().unwrap_or("?");
+        let summary = item_summary(item);
+        if summary.is_empty() {
+            out.push_str(&format!("- **{key}**\n"));
+        } else {
+            out.push_str(&format!("- **{key}**: {summary}\n"));
+        }
+    }
+    out.push_str(&end_marker(section));
+    out.push('\n');
+    out
+}
+
+/// Put rendered `sections` (name and text) into `existing`: each replaces
+/// the text between its markers, or is appended if the markers are missing.
+/// A begin marker with no end marker after it is an error, since the extent
+/// of the generated text, and so what is safe to replace, is unknown.
+pub fn merge_sections(
+    existing: Option<&str>,
+    sections: &[(&str, String)],
+) -> Result<String, MemoryError> {
+    let mut out = existing.unwrap_or(AGENTS_MD_HEADER).to_string();
+    for (section, rendered) in sections {
+        let (begin, end) = (begin_marker(section), end_marker(section));
+        match out.find(&begin) {
+            Some(start) => {
+                let end_at = out[start..].find(&end).ok_or_else(|| {
+                    MemoryError::InvalidParams(format!(
+                        "`{begin}` has no matching `{end}`; restore the end marker \
+                         or remove the section"
+                    ))
+                })?;
+                let end_at = start + end_at + end.len();
+                let end_at = if out[end_at..].starts_with('\n') {
+                    end_at + 1
+                } else {
+                    end_at
+                };
+                out.replace_range(start..end_at, rendered);
+            }
+            None => {
+                if !out.is_empty() && !out.ends_with("\n\n") {
+                    out.push_str(if out.ends_with('\n') { "\n" } else { "\n\n" });
+                }
+                out.push_str(rendered);
+            }
+        }
+    }
+    Ok(out)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_generate_agents_md_keeps_manual_edits() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        backend
+            .put_item(json!({"category": "preferences", "key": "editor", "value": "neovim"}))
+            .await
+            .unwrap();
+        backend
+            .put_item(json!({"category": "tools", "key": "ci", "name": "GitHub Actions"}))
+            .await
+            .unwrap();
+
+        let fresh = generate_agents_md(&backend, None).await.unwrap();
+        assert!(fresh.starts_with("# Agent instructions"));
+        assert!(fresh.contains("## Preferences\n\n- **editor**: value: neovim\n"));
+        assert!(fresh.contains("## Project\n\n_Nothing stored yet._\n"));
+        assert!(fresh.contains("- **ci**: name: GitHub Actions\n"));
+
+        let edited = fresh.replace(
+            "# Agent instructions\n",
+            "# Agent instructions\n\nRun `make lint`.\n",
+        ) + "\n## Notes\n\nHand-written.\n";
+        backend
+            .put_item(json!({"category": "preferences", "key": "shell", "value": "fish"}))
+            .await
+            .unwrap();
+        let regenerated = generate_agents_md(&backend, Some(&edited)).await.unwrap();
+        assert!(regenerated.contains("Run `make lint`."));
+        assert!(regenerated.ends_with("## Notes\n\nHand-written.\n"));
+        assert!(regenerated.contains("- **shell**: value: fish\n"));
+        assert_eq!(regenerated.matches(&begin_marker("preferences")).count(), 1);
+        assert_eq!(
+            generate_agents_md(&backend, Some(&regenerated))
+                .await
+                .unwrap(),
+            regenerated
+        );
+
+        let merged = merge_sections(Some("Manual only"), &[("tools", "T\n".to_string())]);
+        assert_eq!(merged.unwrap(), "Manual only\n\nT\n");
+    }
+
+    #[test]
+    fn test_merge_sections_rejects_unterminated_section() {
+        let existing = format!(
+            "{}\nold\n\n## Notes\n\nHand-written.\n",
+            begin_marker("tools")
+        );
+        let err = merge_sections(Some(&existing), &[("tools", "T\n".to_string())]).unwrap_err();
+        assert!(matches!(err, MemoryError::InvalidParams(_)));
+        assert!(err.to_string().contains(&end_marker("tools")));
+    }
+}
@@ -0,0 +1,254 @@
+//! Alternative names for schema attributes.
+//!
+//! An attribute can carry aliases (`team` ← `org`, `group`) so the same fact
+//! is extracted the same way however it is phrased. Aliases are listed next
+//! to their attribute in the parse and resolve prompts, and an alias that
+//! still shows up in any written item is renamed to its attribute by
+//! [`MemoryBackend::put_item`] ([`AttributeAliases::canonicalize`]).
+//!
+//! FerridynDB partition schemas have no room for aliases, so each category's
+//! aliases are kept in one record in the reserved [`ALIASES_CATEGORY`]
+//! partition, written when the schema is created. Predefined categories
+//! without a record use the aliases in [`PREDEFINED_SCHEMAS`].
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::backend::MemoryBackend;
+use crate::error::MemoryError;
+use crate::schema::{AttributeDef, PREDEFINED_SCHEMAS};
+
+/// Partition holding one alias record per category.
+pub const ALIASES_CATEGORY: &str = "_attribute_aliases";
+
+/// Maximum number of alias records read.
+pub const ALIASES_SCAN_LIMIT: usize = 10_000;
+
+/// Attribute aliases of each category, mapping every alias to its attribute.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttributeAliases {
+    by_category: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl AttributeAliases {
+    /// The aliases built into [`PREDEFINED_SCHEMAS`].
+    pub fn predefined() -> Self {
+        let mut aliases = Self::default();
+        for category in PREDEFINED_SCHEMAS {
+            for (alias, attribute) in category.aliases {
+                aliases.insert(category.name, alias, attribute);
+            }
+        }
+        aliases
+    }
+
+    /// Record `alias` as another name for `attribute` in `category`.
+    pub fn insert(&mut self, category: &str, alias: &str, attribute: &str) {
+        self.by_category
+            .entry(category.to_string())
+            .or_default()
+            .insert(alias.to_string(), attribute.to_string());
+    }
+
+    /// Replace the aliases of `category` with `aliases` (alias → attribute).
+    pub fn set_category(&mut self, category: &str, aliases: BTreeMap<String, String>) {
+        self.by_category.insert(category.to_string(), aliases);
+    }
+
+    /// Aliases of `attribute` in `category`, sorted.
+    pub fn aliases_of(&self, category: &str, attribute: &str) -> Vec<&str> {
+        self.by_category
+            .get(category)
+            .map(|aliases| {
+                aliases
+                    .iter()
+                    .filter(|(_, target)| target.as_str() == attribute)
+                    .map(|(alias, _)| alias.as_str())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Rename alias attributes in `doc` to the attribute they stand for.
+    ///
+    /// A null alias is dropped. An alias whose value differs from a non-null
+    /// value under the attribute or another of its aliases is an error,
+    /// naming both, rather than one of the values being lost; `doc` is then
+    /// left unchanged.
+    pub fn canonicalize(&self, category: &str, doc: &mut Value) -> Result<(), MemoryError> {
+        let (Some(aliases), Some(obj)) = (self.by_category.get(category), doc.as_object_mut())
+        else {
+            return Ok(());
+        };
+        // The first non-null value found for each attribute, and its name.
+        let mut seen: BTreeMap<&str, (&str, &Value)> = BTreeMap::new();
+        for (alias, attribute) in aliases {
+            let Some(value) = obj.get(alias).filter(|value| !value.is_null()) else {
+                continue;
+            };
+            let first = obj
+                .get(attribute)
+                .filter(|existing| !existing.is_null())
+                .map(|existing| (attribute.as_str(), existing))
+                .or_else(|| seen.get(attribute.as_str()).copied());
+            match first {
+                Some((name, existing)) if existing != value => {
+                    return Err(MemoryError::InvalidParams(format!(
+                        "'{alias}' and '{name}' both stand for '{attribute}' in \
+                         '{category}' but have different values"
+                    )));
+                }
+                Some(_) => {}
+                None => {
+                    seen.insert(attribute, (alias, value));
+                }
+            }
+        }
+        for (alias, attribute) in aliases {
+            let Some(value) = obj.remove(alias) else {
+                continue;
+            };
+            if obj.get(attribute).is_none_or(Value::is_null) && !value.is_null() {
+                obj.insert(attribute.clone(), value);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Map every alias declared in `attributes` to its attribute.
+///
+/// Aliases are trimmed and empty ones ignored. An alias that is also an
+/// attribute name, or that two attributes claim, is an error.
+pub fn alias_map(attributes: &[AttributeDef]) -> Result<BTreeMap<String, String>, MemoryError> {
+    let mut map = BTreeMap::new();
+    for attribute in attributes {
+        for alias in &attribute.aliases {
+            let alias = alias.trim();
+            if alias.is_empty() || alias == attribute.name {
+                continue;
+            }
+            if attributes.iter().any(|a| a.name == alias) {
+                return Err(MemoryError::InvalidParams(format!(
+                    "alias '{alias}' of '{}' is also an attribute",
+                    attribute.name
+                )));
+            }
+            if let Some(other) = map.insert(alias.to_string(), attribute.name.clone())
+                && other != attribute.name
+            {
+                return Err(MemoryError::InvalidParams(format!(
+                    "alias '{alias}' is claimed by both '{other}' and '{}'",
+                    attribute.name
+                )));
+            }
+        }
+    }
+    Ok(map)
+}
+
+/// Store the aliases of `category`'s attributes, replacing any previous ones.
+pub async fn save_aliases(
+    backend: &MemoryBackend,
+    category: &str,
+    attributes: &[AttributeDef],
+) -> Result<(), MemoryError> {
+    let aliases = alias_map(attributes)?;
+    backend
+        .put_system_item(serde_json::json!({
+            "category": ALIASES_CATEGORY,
+            "key": category,
+            "aliases": aliases,
+        }))
+        .await
+}
+
+/// Aliases of every category: the predefined ones, replaced per category by
+/// stored records.
+pub async fn load_aliases(backend: &MemoryBackend) -> Result<AttributeAliases, MemoryError> {
+    let mut aliases = AttributeAliases::predefined();
+    let records = backend
+        .query(ALIASES_CATEGORY, None, ALIASES_SCAN_LIMIT)
+        .await?;
+    for record in records {
+        let Some(category) = record["key"].as_str() else {
+            continue;
+        };
+        let map = record["aliases"]
+            .as_object()
+            .map(|obj| {
+                obj.iter()
+                    .filter_map(|(alias, attribute)| {
+                        Some((alias.clone(), attribute.as_str()?.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        aliases.set_category(category, map);
+    }
+    Ok(aliases)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    fn attr(name: &str, aliases: &[&str]) -> AttributeDef {
+        AttributeDef {
+            name: name.into(),
+            attr_type: "STRING".into(),
+            required: false,
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let aliases = AttributeAliases::predefined();
+        assert_eq!(aliases.aliases_of("contacts", "team"), ["group", "org"]);
+
+        let mut doc = json!({"name": "Toby", "org": "infra", "title": "SRE", "role": null});
+        aliases.canonicalize("contacts", &mut doc).unwrap();
+        assert_eq!(doc, json!({"name": "Toby", "team": "infra", "role": "SRE"}));
+
+        // An alias repeating the attribute's value is dropped.
+        let mut doc = json!({"team": "infra", "org": "infra"});
+        aliases.canonicalize("contacts", &mut doc).unwrap();
+        assert_eq!(doc, json!({"team": "infra"}));
+
+        // One contradicting it is refused, leaving the document as it was.
+        let mut doc = json!({"team": "infra", "org": "platform"});
+        let err = aliases.canonicalize("contacts", &mut doc).unwrap_err();
+        assert!(err.to_string().contains("'org'"), "{err}");
+        assert_eq!(doc, json!({"team": "infra", "org": "platform"}));
+        let mut doc = json!({"org": "infra", "group": "platform"});
+        assert!(aliases.canonicalize("contacts", &mut doc).is_err());
+    }
+
+    #[test]
+    fn test_alias_map_rejects_clashes() {
+        let map = alias_map(&[attr("phone", &["mobile", " cell "])]).unwrap();
+        assert_eq!(map["cell"], "phone");
+        assert!(alias_map(&[attr("phone", &["email"]), attr("email", &[])]).is_err());
+        assert!(alias_map(&[attr("phone", &["number"]), attr("fax", &["number"])]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_saved_aliases_replace_predefined() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        save_aliases(&backend, "contacts", &[attr("phone", &["mobile"])])
+            .await
+            .unwrap();
+        let aliases = load_aliases(&backend).await.unwrap();
+        assert_eq!(aliases.aliases_of("contacts", "phone"), ["mobile"]);
+        assert!(aliases.aliases_of("contacts", "team").is_empty());
+        assert_eq!(aliases.aliases_of("tasks", "due_date"), ["deadline", "due"]);
+    }
+}
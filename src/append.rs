@@ -0,0 +1,144 @@
+//! Appending text to a memory's `content`.
+//!
+//! [`append_content`] adds an entry to the end of an item's `content`,
+//! behind a separator carrying the time it was appended, so running notes
+//! can grow without the caller reading and rewriting the whole item. Chunked
+//! content is reassembled first, and the result is chunked again when it
+//! outgrows [`CHUNK_THRESHOLD_CHARS`](crate::chunk::CHUNK_THRESHOLD_CHARS).
+//!
+//! ferridyn-server has no append or update request, so the append is a
+//! read-modify-write under [`MemoryBackend::update_item`]'s lock, held from
+//! reading the content to storing its chunks. Appends and updates made in
+//! this process are serialized and never lose each other's changes; a write
+//! from another process landing in between is overwritten.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{Map, Value};
+
+use crate::backend::MemoryBackend;
+use crate::chunk::hydrate_chunks;
+use crate::error::MemoryError;
+
+/// Attribute appended to.
+pub const CONTENT_ATTRIBUTE: &str = "content";
+
+/// Append `text` to the `content` of `category/key` as an entry stamped
+/// `now`. Returns the updated item with its full content, or `None` if it
+/// does not exist.
+pub async fn append_content(
+    backend: &MemoryBackend,
+    category: &str,
+    key: &str,
+    text: &str,
+    now: DateTime<Utc>,
+) -> Result<Option<Value>, MemoryError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(MemoryError::InvalidParams(
+            "nothing to append: text is empty".into(),
+        ));
+    }
+    let held = backend.lock_updates().await;
+    let Some(item) = backend.get_item(category, key).await? else {
+        return Ok(None);
+    };
+    let current = hydrate_chunks(backend, vec![item])
+        .await?
+        .into_iter()
+        .next()
+        .and_then(|item| item[CONTENT_ATTRIBUTE].as_str().map(String::from))
+        .unwrap_or_default();
+    let mut changes = Map::new();
+    changes.insert(
+        CONTENT_ATTRIBUTE.into(),
+        Value::String(with_entry(&current, text, now)),
+    );
+    backend
+        .update_item_locked(&held, category, key, &changes)
+        .await
+}
+
+/// `content` followed by `text` behind a separator stamped `now`.
+pub fn with_entry(content: &str, text: &str, now: DateTime<Utc>) -> String {
+    let separator = format!("--- {} ---", now.to_rfc3339_opts(SecondsFormat::Secs, true));
+    let content = content.trim_end();
+    if content.is_empty() {
+        format!("{separator}\n{text}")
+    } else {
+        format!("{content}\n\n{separator}\n{text}")
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use crate::chunk::CHUNK_THRESHOLD_CHARS;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_append_content() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        backend
+            .put_item(json!({"category": "project", "key": "api-notes",
+                "content": "REST only", "owner": "toby"}))
+            .await
+            .unwrap();
+        let now = "2026-03-04T09:30:00Z".parse::<DateTime<Utc>>().unwrap();
+
+        let item = append_content(
+            &backend,
+            "project",
+            "api-notes",
+            "also supports webhooks",
+            now,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            item[CONTENT_ATTRIBUTE],
+            "REST only\n\n--- 2026-03-04T09:30:00Z ---\nalso supports webhooks"
+        );
+        assert_eq!(item["owner"], "toby");
+        let stored = backend.get_item("project", "api-notes").await.unwrap();
+        assert_eq!(stored.unwrap()[CONTENT_ATTRIBUTE], item[CONTENT_ATTRIBUTE]);
+
+        // Outgrowing the threshold chunks the content.
+        let long = "word ".repeat(CHUNK_THRESHOLD_CHARS / 5);
+        let item = append_content(&backend, "project", "api-notes", &long, now)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(item["chunk_count"].as_u64().unwrap() >= 2);
+        let hydrated = hydrate_chunks(
+            &backend,
+            vec![
+                backend
+                    .get_item("project", "api-notes")
+                    .await
+                    .unwrap()
+                    .unwrap(),
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(hydrated[0][CONTENT_ATTRIBUTE], item[CONTENT_ATTRIBUTE]);
+
+        assert!(
+            append_content(&backend, "project", "missing", "x", now)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            append_content(&backend, "project", "api-notes", "  ", now)
+                .await
+                .is_err()
+        );
+    }
+}
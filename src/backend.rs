@@ -1,16 +1,79 @@
-//! Backend abstraction: server client (production) or direct FerridynDB handle (tests only).
+//! Backend abstraction: server client (production), in-process in-memory
+//! database (embedders' tests), or direct FerridynDB handle (this crate's tests).
+//!
+//! Reads, listings, and deletes against the server are idempotent and are
+//! retried with capped backoff when the connection fails transiently (reset,
+//! broken pipe, timeout), reconnecting first when the socket path is known.
+//! Puts and schema/index changes are never retried: a put whose response
+//! was lost may have been applied, and repeating it could clobber a newer
+//! write.
+//!
+//! Server calls are bounded by the backend's [`Timeouts`]: each read attempt
+//! by `read`, each write by `write`. A read that times out counts as a
+//! transient failure; a write that times out fails with
+//! [`MemoryError::Timeout`] and drops the connection, whose next response can
+//! no longer be matched to a request.
 
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use crate::SYSTEM_CATEGORIES;
+use crate::activity::{ACTIVITY_CATEGORY, Access, ActivityCounters, merge_activity};
+use crate::alias::{ALIASES_CATEGORY, AttributeAliases, load_aliases};
+use crate::capability::{Capabilities, upgrade_message};
+use crate::chunk::{chunk_content, is_chunk, store_chunks};
 use crate::error::MemoryError;
-use crate::schema::{PREDEFINED_SCHEMAS, SchemaManager};
+use crate::in_memory::InMemoryDb;
+use crate::keys::{nfc, normalize_category, normalize_key, slugify, validate_schema_category};
+use crate::links::{
+    LINKS_CATEGORY, LINKS_SCAN_LIMIT, Link, LinkDirection, link_key_prefix, normalize_item_ref,
+    parse_item_ref,
+};
+use crate::page::{PAGE_SCAN_LIMIT, Page, paginate};
+use crate::schema::{PREDEFINED_SCHEMAS, SCHEMA_CACHE_TTL, SchemaDefinition, SchemaManager};
+use crate::size::ItemSizeLimits;
+use crate::sort::UPDATED_AT_ATTRIBUTE;
+use crate::timeout::Timeouts;
+use crate::ttl::is_expired;
+use crate::validation::WriteValidation;
+use crate::version::{DataVersions, SCHEMAS_VERSION_KEY};
+use futures::stream::{self, StreamExt, TryStreamExt};
 use serde_json::Value;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, MutexGuard, mpsc, oneshot};
 
 #[cfg(test)]
 use ferridyn_core::api::FerridynDB;
 use ferridyn_server::FerridynClient;
 use ferridyn_server::client::{AttributeDefInput, IndexInfo, PartitionSchemaInfo};
+use ferridyn_server::error::ClientError;
+
+/// Maximum number of per-category operations kept in flight by
+/// multi-category operations (prune, key sampling, predefined-schema
+/// initialization). Requests to a ferridyn-server share one connection and
+/// are still sent one at a time; this overlaps the work between them, and
+/// reads routed to a replica, rather than the server round trips themselves.
+pub const MAX_CONCURRENT_CATEGORY_OPS: usize = 8;
+
+/// Attempts (first try included) for an idempotent server operation.
+const RETRY_ATTEMPTS: u32 = 3;
+
+/// Backoff before the first retry; doubled for each further retry.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(50);
+
+/// Cap on the backoff between retries.
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(1);
+
+/// Environment variable naming the socket of a read replica of the server.
+pub const READ_SOCKET_ENV: &str = "FERRIDYN_MEMORY_READ_SOCKET";
+
+/// Environment variable: how far the read replica may trail the primary,
+/// as a duration such as `500ms` or `2s`, or `0` to always read from it.
+pub const REPLICA_MAX_LAG_ENV: &str = "FMEMORY_REPLICA_MAX_LAG";
+
+/// Replica lag assumed when `FMEMORY_REPLICA_MAX_LAG` is unset.
+pub const DEFAULT_REPLICA_MAX_LAG: Duration = Duration::from_secs(2);
 
 /// Inner storage variant for [`MemoryBackend`].
 #[derive(Clone)]
@@ -18,18 +81,80 @@ enum BackendInner {
     #[cfg(test)]
     Direct(FerridynDB),
     Server(Arc<Mutex<FerridynClient>>),
+    InMemory(Arc<InMemoryDb>),
 }
 
 /// Unified backend for memory operations.
 ///
-/// Wraps either a server client (production) or direct FerridynDB handle (tests)
-/// along with the table name to operate on. The table name is determined by
+/// Wraps a server client (production), an in-memory database, or a direct
+/// FerridynDB handle (tests) along with the table name to operate on. The table name is determined by
 /// the namespace configuration.
 #[derive(Clone)]
 pub struct MemoryBackend {
     inner: BackendInner,
     /// The table name used for all operations (e.g. "memories" or "memories_myproject").
     pub table_name: String,
+    /// Socket to reconnect to before retrying a server operation.
+    socket_path: Option<Arc<Path>>,
+    timeouts: Timeouts,
+    item_size: ItemSizeLimits,
+    replica: Option<ReadReplica>,
+    mirror: Option<Mirror>,
+    capabilities: Capabilities,
+    validation: WriteValidation,
+    /// Schema listings consulted by `validation`, per table, shared between
+    /// clones.
+    schema_cache: Arc<std::sync::Mutex<HashMap<String, CachedListing>>>,
+    /// Attribute aliases applied by `put_item`, per table, shared between
+    /// clones.
+    alias_cache: Arc<std::sync::Mutex<HashMap<String, CachedAliases>>>,
+    /// Writes made through this backend and its clones, per category.
+    versions: DataVersions,
+    /// Reads and writes not persisted yet, shared between clones.
+    activity: ActivityCounters,
+    /// Whether this backend's reads and writes are counted in `activity`.
+    count_activity: bool,
+    /// Serializes [`update_item`](Self::update_item) calls, shared between
+    /// clones.
+    update_lock: Arc<Mutex<()>>,
+}
+
+/// A table's schema listing and when it was fetched.
+type CachedListing = (Instant, Vec<PartitionSchemaInfo>);
+
+/// A table's attribute aliases and when they were read.
+type CachedAliases = (Instant, AttributeAliases);
+
+/// Backend receiving a copy of every write (see [`MemoryBackend::with_mirror`]).
+#[derive(Clone)]
+struct Mirror {
+    backend: Arc<MemoryBackend>,
+    queue: mpsc::UnboundedSender<MirrorOp>,
+}
+
+/// A write to replay on the mirror, in the order it succeeded on the primary.
+enum MirrorOp {
+    Put {
+        table: String,
+        doc: Value,
+    },
+    Delete {
+        table: String,
+        category: String,
+        key: String,
+    },
+    /// Signalled once every earlier operation has been applied.
+    Flush(oneshot::Sender<()>),
+}
+
+/// Secondary server answering reads (see [`MemoryBackend::with_read_replica`]).
+#[derive(Clone)]
+struct ReadReplica {
+    client: Arc<Mutex<FerridynClient>>,
+    socket_path: Arc<Path>,
+    max_lag: Duration,
+    /// When this backend, or a clone of it, last wrote to the primary.
+    last_write: Arc<std::sync::Mutex<Option<Instant>>>,
 }
 
 impl MemoryBackend {
@@ -38,6 +163,416 @@ impl MemoryBackend {
         Self {
             inner: BackendInner::Server(client),
             table_name,
+            socket_path: None,
+            timeouts: Timeouts::default(),
+            item_size: ItemSizeLimits::default(),
+            replica: None,
+            mirror: None,
+            capabilities: Capabilities::default(),
+            validation: WriteValidation::default(),
+            schema_cache: Arc::default(),
+            alias_cache: Arc::default(),
+            versions: DataVersions::default(),
+            activity: ActivityCounters::default(),
+            count_activity: true,
+            update_lock: Arc::default(),
+        }
+    }
+
+    /// Reconnect to `path` when a server operation fails transiently, before
+    /// retrying it.
+    pub fn with_socket_path(mut self, path: &Path) -> Self {
+        self.socket_path = Some(Arc::from(path));
+        self
+    }
+
+    /// Limit server calls to `timeouts` instead of [`Timeouts::default`].
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Bound the size of items accepted by [`put_item`](Self::put_item)
+    /// with `limits` instead of [`ItemSizeLimits::default`].
+    pub fn with_item_size_limits(mut self, limits: ItemSizeLimits) -> Self {
+        self.item_size = limits;
+        self
+    }
+
+    /// Treat the server as supporting only `capabilities`, as probed by
+    /// [`probe_capabilities`](crate::capability::probe_capabilities).
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// What the server supports.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Check items passed to [`put_item`](Self::put_item) as `validation`
+    /// says instead of [`WriteValidation::default`].
+    pub fn with_write_validation(mut self, validation: WriteValidation) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Check `doc` against the schema of its category, as configured by
+    /// [`with_write_validation`](Self::with_write_validation).
+    async fn validate_write(&self, doc: &Value) -> Result<(), MemoryError> {
+        if self.validation == WriteValidation::off() {
+            return Ok(());
+        }
+        #[cfg(test)]
+        if matches!(self.inner, BackendInner::Direct(_)) {
+            return Ok(());
+        }
+        // Chunks carry a slice of their parent's content, not its attributes.
+        if is_chunk(doc) {
+            return Ok(());
+        }
+        let Some(category) = doc["category"].as_str() else {
+            return Ok(());
+        };
+        let cached = self
+            .schema_cache
+            .lock()
+            .unwrap()
+            .get(&self.table_name)
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < SCHEMA_CACHE_TTL)
+            .map(|(_, schemas)| schemas.clone());
+        let schemas = match cached {
+            Some(schemas) => schemas,
+            None => {
+                let schemas = self.list_schemas().await?;
+                self.schema_cache
+                    .lock()
+                    .unwrap()
+                    .insert(self.table_name.clone(), (Instant::now(), schemas.clone()));
+                schemas
+            }
+        };
+        match schemas.iter().find(|s| s.prefix == category) {
+            Some(schema) if self.validation.applies_to(schema) => {
+                self.validation.check(schema, doc)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes made to `category` of this backend's table, by this process.
+    /// Schema and index changes are counted under [`SCHEMAS_VERSION_KEY`].
+    pub fn data_version(&self, category: &str) -> u64 {
+        self.versions.version(&self.table_name, category)
+    }
+
+    /// [`data_version`](Self::data_version) of every category written so far.
+    pub fn data_versions(&self) -> BTreeMap<String, u64> {
+        self.versions.snapshot(&self.table_name)
+    }
+
+    fn bump_version(&self, category: &str) {
+        self.versions.bump(&self.table_name, category);
+    }
+
+    /// Count this backend's reads and writes in the
+    /// [category activity](crate::activity) when `enabled`, as by default.
+    /// Maintenance commands turn it off so their scans are not counted.
+    pub fn with_activity_counting(mut self, enabled: bool) -> Self {
+        self.count_activity = enabled;
+        self
+    }
+
+    /// Count an `access` to `category`, persisting the pending counts in the
+    /// background once [`ACTIVITY_FLUSH_INTERVAL`] has passed since the last
+    /// flush.
+    ///
+    /// [`ACTIVITY_FLUSH_INTERVAL`]: crate::activity::ACTIVITY_FLUSH_INTERVAL
+    fn count_access(&self, category: &str, access: Access) {
+        if !self.count_activity || SYSTEM_CATEGORIES.contains(&category) {
+            return;
+        }
+        self.activity.record(&self.table_name, category, access);
+        if self.activity.flush_due()
+            && let Ok(runtime) = tokio::runtime::Handle::try_current()
+        {
+            let backend = self.clone();
+            runtime.spawn(async move {
+                if let Err(e) = backend.flush_activity().await {
+                    tracing::warn!(error = %e, "failed to persist category activity");
+                }
+            });
+        }
+    }
+
+    /// Add the reads and writes counted since the last flush to the
+    /// persisted [category activity](crate::activity) of their tables.
+    /// Counts that could not be persisted are kept for the next flush.
+    pub async fn flush_activity(&self) -> Result<(), MemoryError> {
+        let pending = self.activity.take();
+        let now = chrono::Utc::now();
+        let mut unsaved = HashMap::new();
+        let mut first_error = None;
+        for ((table, category), counts) in pending {
+            let mut backend = self.clone();
+            backend.table_name = table.clone();
+            let saved = async {
+                let existing = backend.read_item(ACTIVITY_CATEGORY, &category).await?;
+                let doc = merge_activity(existing.as_ref(), &category, &counts, now);
+                backend.write_item(doc).await
+            }
+            .await;
+            if let Err(e) = saved {
+                first_error.get_or_insert(e);
+                unsaved.insert((table, category), counts);
+            }
+        }
+        self.activity.restore(unsaved);
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Forget the cached schema listing of this backend's table.
+    fn invalidate_schema_cache(&self) {
+        self.schema_cache.lock().unwrap().remove(&self.table_name);
+    }
+
+    fn require_schemas(&self) -> Result<(), MemoryError> {
+        if self.capabilities.partition_schemas {
+            Ok(())
+        } else {
+            Err(MemoryError::Unsupported(upgrade_message(
+                "partition schemas",
+            )))
+        }
+    }
+
+    fn require_indexes(&self) -> Result<(), MemoryError> {
+        if self.capabilities.secondary_indexes {
+            Ok(())
+        } else {
+            Err(MemoryError::Unsupported(upgrade_message(
+                "secondary indexes",
+            )))
+        }
+    }
+
+    /// Copy every successful item write and delete to `mirror` as well.
+    ///
+    /// Copies are applied in the background, in order, and never fail the
+    /// write itself: a mirror that is down misses them (logged at `warn`)
+    /// until [`sync_mirror`](crate::mirror::sync_mirror) catches it up. Call
+    /// [`flush_mirror`](Self::flush_mirror) before exiting. Must be called
+    /// within a Tokio runtime.
+    pub fn with_mirror(mut self, mirror: MemoryBackend) -> Self {
+        let (queue, mut ops) = mpsc::unbounded_channel();
+        let target = mirror.clone();
+        tokio::spawn(async move {
+            while let Some(op) = ops.recv().await {
+                let (table, result) = match op {
+                    MirrorOp::Put { table, doc } => {
+                        let mut target = target.clone();
+                        target.table_name = table;
+                        let result = target.write_item(doc).await;
+                        (target.table_name, result)
+                    }
+                    MirrorOp::Delete {
+                        table,
+                        category,
+                        key,
+                    } => {
+                        let mut target = target.clone();
+                        target.table_name = table;
+                        let result = target.delete_item(&category, &key).await;
+                        (target.table_name, result)
+                    }
+                    MirrorOp::Flush(done) => {
+                        let _ = done.send(());
+                        continue;
+                    }
+                };
+                if let Err(e) = result {
+                    tracing::warn!(table = %table, error = %e, "mirror write failed; run `fmemory mirror-sync`");
+                }
+            }
+        });
+        self.mirror = Some(Mirror {
+            backend: Arc::new(mirror),
+            queue,
+        });
+        self
+    }
+
+    /// The mirror set by [`with_mirror`](Self::with_mirror), over this
+    /// backend's table.
+    pub fn mirror(&self) -> Option<MemoryBackend> {
+        self.mirror.as_ref().map(|mirror| {
+            let mut backend = MemoryBackend::clone(&mirror.backend);
+            backend.table_name = self.table_name.clone();
+            backend
+        })
+    }
+
+    /// Wait until every write made so far has been copied to the mirror.
+    pub async fn flush_mirror(&self) {
+        let Some(mirror) = &self.mirror else {
+            return;
+        };
+        let (done, flushed) = oneshot::channel();
+        if mirror.queue.send(MirrorOp::Flush(done)).is_ok() {
+            let _ = flushed.await;
+        }
+    }
+
+    fn replicate(&self, op: MirrorOp) {
+        if let Some(mirror) = &self.mirror {
+            // The worker only stops with the runtime.
+            let _ = mirror.queue.send(op);
+        }
+    }
+
+    /// Send item reads (get, query, list) to the server at `socket_path`
+    /// through `client`; writes and schema/index operations stay on the
+    /// primary. The replica may trail the primary by up to `max_lag`, so for
+    /// that long after a write, reads go to the primary as well.
+    pub fn with_read_replica(
+        mut self,
+        client: Arc<Mutex<FerridynClient>>,
+        socket_path: &Path,
+        max_lag: Duration,
+    ) -> Self {
+        self.replica = Some(ReadReplica {
+            client,
+            socket_path: Arc::from(socket_path),
+            max_lag,
+            last_write: Arc::default(),
+        });
+        self
+    }
+
+    /// The connection an item read should use: the replica unless there is
+    /// none or a recent write may not have reached it yet.
+    fn read_client<'a>(&'a self, primary: &'a Mutex<FerridynClient>) -> &'a Mutex<FerridynClient> {
+        match &self.replica {
+            Some(replica)
+                if replica_is_current(
+                    *replica.last_write.lock().unwrap(),
+                    replica.max_lag,
+                    Instant::now(),
+                ) =>
+            {
+                &replica.client
+            }
+            _ => primary,
+        }
+    }
+
+    /// Record a write to the primary, steering reads away from the replica
+    /// until it has caught up.
+    fn note_write(&self) {
+        if let Some(replica) = &self.replica {
+            *replica.last_write.lock().unwrap() = Some(Instant::now());
+        }
+    }
+
+    /// Run an idempotent operation on the server `client`, retrying
+    /// transient failures and timeouts. `call` locks the client for each
+    /// attempt and bounds the request itself with [`within`], so waiting for
+    /// the lock never counts as a timeout. A connection whose request timed
+    /// out is replaced even after the last attempt, since its reply may
+    /// still arrive. The outer `Err` is a timeout of the last attempt;
+    /// otherwise the last attempt's result is returned.
+    async fn retry_idempotent<T, F, Fut>(
+        &self,
+        client: &Mutex<FerridynClient>,
+        mut call: F,
+    ) -> Result<Result<T, ClientError>, MemoryError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<Result<T, ClientError>, MemoryError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let result = call().await;
+            let retry = match &result {
+                Ok(Err(err)) => is_transient(err),
+                Ok(Ok(_)) => false,
+                Err(_) => true,
+            };
+            if !retry || attempt >= RETRY_ATTEMPTS {
+                if result.is_err() {
+                    self.reconnect(client).await;
+                }
+                return result;
+            }
+            tracing::debug!(attempt, "retrying backend operation");
+            tokio::time::sleep(retry_delay(attempt)).await;
+            self.reconnect(client).await;
+            attempt += 1;
+        }
+    }
+
+    /// Run a write on the server `client`. `call` locks the client and
+    /// bounds the request with [`within`]; a timed-out connection is
+    /// replaced. The outer `Err` is the timeout.
+    async fn bounded_write<T>(
+        &self,
+        client: &Mutex<FerridynClient>,
+        call: impl Future<Output = Result<Result<T, ClientError>, MemoryError>>,
+    ) -> Result<Result<T, ClientError>, MemoryError> {
+        self.note_write();
+        let result = call.await;
+        if result.is_err() {
+            self.reconnect(client).await;
+        }
+        result
+    }
+
+    /// Replace the server connection with a fresh one, when the socket path
+    /// is known. Losing and regaining the server is logged at `warn`/`info`.
+    async fn reconnect(&self, client: &Mutex<FerridynClient>) {
+        let path = match &self.replica {
+            Some(replica) if std::ptr::eq(client, &*replica.client) => Some(&replica.socket_path),
+            _ => self.socket_path.as_ref(),
+        };
+        let Some(path) = path else {
+            return;
+        };
+        match FerridynClient::connect(path).await {
+            Ok(fresh) => {
+                *client.lock().await = fresh;
+                tracing::info!(socket = %path.display(), "reconnected to ferridyn-server");
+            }
+            Err(err) => tracing::warn!(
+                socket = %path.display(),
+                error = %err,
+                "ferridyn-server unreachable"
+            ),
+        }
+    }
+
+    /// Create a backend over a fresh in-memory database.
+    ///
+    /// Supports items, schemas, and indexes without a running ferridyn-server,
+    /// for tests in crates that embed this library. Clones share the same
+    /// data; nothing is persisted.
+    pub fn in_memory(table_name: String) -> Self {
+        Self {
+            inner: BackendInner::InMemory(Arc::new(InMemoryDb::default())),
+            table_name,
+            socket_path: None,
+            timeouts: Timeouts::default(),
+            item_size: ItemSizeLimits::default(),
+            replica: None,
+            mirror: None,
+            capabilities: Capabilities::default(),
+            validation: WriteValidation::default(),
+            schema_cache: Arc::default(),
+            alias_cache: Arc::default(),
+            versions: DataVersions::default(),
+            activity: ActivityCounters::default(),
+            count_activity: true,
+            update_lock: Arc::default(),
         }
     }
 
@@ -47,23 +582,230 @@ impl MemoryBackend {
         Self {
             inner: BackendInner::Direct(db),
             table_name,
+            socket_path: None,
+            timeouts: Timeouts::default(),
+            item_size: ItemSizeLimits::default(),
+            replica: None,
+            mirror: None,
+            capabilities: Capabilities::default(),
+            validation: WriteValidation::default(),
+            schema_cache: Arc::default(),
+            alias_cache: Arc::default(),
+            versions: DataVersions::default(),
+            activity: ActivityCounters::default(),
+            count_activity: true,
+            update_lock: Arc::default(),
         }
     }
 
+    #[tracing::instrument(
+        name = "backend.put_item",
+        level = "debug",
+        skip_all,
+        fields(
+            table = %self.table_name,
+            category = doc["category"].as_str(),
+            key = doc["key"].as_str(),
+        )
+    )]
     pub async fn put_item(&self, doc: Value) -> Result<(), MemoryError> {
-        match &self.inner {
+        let doc = self.checked_item(doc).await?;
+        let category = doc["category"].as_str().unwrap_or_default().to_string();
+        self.write_item(doc).await?;
+        self.bump_version(&category);
+        self.count_access(&category, Access::Write);
+        Ok(())
+    }
+
+    /// Write bookkeeping done by reads, such as access times and recall
+    /// logs, checked like [`put_item`](Self::put_item) but not counted as a
+    /// change to the data (see [`data_version`](Self::data_version)).
+    pub(crate) async fn put_bookkeeping_item(&self, doc: Value) -> Result<(), MemoryError> {
+        let doc = self.checked_item(doc).await?;
+        self.write_item(doc).await
+    }
+
+    /// `doc` with its address normalized, once it passed the checks of
+    /// [`put_item`](Self::put_item).
+    async fn checked_item(&self, doc: Value) -> Result<Value, MemoryError> {
+        let doc = normalize_address(doc)?;
+        if let Some(category) = doc["category"].as_str()
+            && SYSTEM_CATEGORIES.contains(&category)
+        {
+            return Err(MemoryError::InvalidParams(format!(
+                "category '{category}' is reserved for internal use"
+            )));
+        }
+        let mut doc = doc;
+        self.canonicalize_aliases(&mut doc).await?;
+        self.item_size
+            .check(&doc)
+            .map_err(MemoryError::InvalidParams)?;
+        self.validate_write(&doc).await?;
+        Ok(doc)
+    }
+
+    /// Rename attribute aliases in `doc` to their attribute (see
+    /// [`AttributeAliases::canonicalize`]), so items written by any path
+    /// use the names schemas and filters expect.
+    async fn canonicalize_aliases(&self, doc: &mut Value) -> Result<(), MemoryError> {
+        if is_chunk(doc) {
+            return Ok(());
+        }
+        let Some(category) = doc["category"].as_str().map(String::from) else {
+            return Ok(());
+        };
+        let cached = self
+            .alias_cache
+            .lock()
+            .unwrap()
+            .get(&self.table_name)
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < SCHEMA_CACHE_TTL)
+            .map(|(_, aliases)| aliases.clone());
+        let aliases = match cached {
+            Some(aliases) => aliases,
+            None => {
+                let aliases = load_aliases(self).await?;
+                self.alias_cache
+                    .lock()
+                    .unwrap()
+                    .insert(self.table_name.clone(), (Instant::now(), aliases.clone()));
+                aliases
+            }
+        };
+        aliases.canonicalize(&category, doc)
+    }
+
+    /// Merge `patch` into `category/key` as a JSON Merge Patch (RFC 7396),
+    /// leaving attributes it does not mention as they are: a `null` value
+    /// removes the attribute, an object is merged into an object attribute,
+    /// and any other value replaces it. `updated_at` is stamped. `category`
+    /// and `key` cannot be changed. A patched `content` is chunked again, and
+    /// chunks it no longer needs are deleted.
+    ///
+    /// ferridyn-server has no update request, so the item is read, patched,
+    /// and written back through [`put_item`](Self::put_item). Updates made
+    /// through this backend and its clones, and the other read-modify-writes
+    /// holding [`lock_updates`](Self::lock_updates), are serialized and never
+    /// lose each other's attributes; a write from another process landing in
+    /// between is overwritten. Returns the updated item with its full
+    /// content, or `None` if it does not exist.
+    pub async fn update_item(
+        &self,
+        category: &str,
+        key: &str,
+        patch: &serde_json::Map<String, Value>,
+    ) -> Result<Option<Value>, MemoryError> {
+        let held = self.update_lock.lock().await;
+        self.update_item_locked(&held, category, key, patch).await
+    }
+
+    /// Take the lock [`update_item`](Self::update_item) serializes on, for a
+    /// read-modify-write that cannot be written as one patch. While holding
+    /// it, update through [`update_item_locked`](Self::update_item_locked);
+    /// `update_item` would wait on it forever.
+    pub(crate) async fn lock_updates(&self) -> MutexGuard<'_, ()> {
+        self.update_lock.lock().await
+    }
+
+    /// [`update_item`](Self::update_item) for a caller already holding
+    /// [`lock_updates`](Self::lock_updates).
+    pub(crate) async fn update_item_locked(
+        &self,
+        _held: &MutexGuard<'_, ()>,
+        category: &str,
+        key: &str,
+        patch: &serde_json::Map<String, Value>,
+    ) -> Result<Option<Value>, MemoryError> {
+        if patch.contains_key("category") || patch.contains_key("key") {
+            return Err(MemoryError::InvalidParams(
+                "an update cannot change the category or key".into(),
+            ));
+        }
+        let Some(mut item) = self.get_item(category, key).await? else {
+            return Ok(None);
+        };
+        let Some(fields) = item.as_object_mut() else {
+            return Err(MemoryError::Internal(format!(
+                "item {category}/{key} is not an object"
+            )));
+        };
+        merge_patch(fields, patch);
+        fields.insert(
+            UPDATED_AT_ATTRIBUTE.into(),
+            Value::String(chrono::Utc::now().to_rfc3339()),
+        );
+        if !patch.contains_key("content") {
+            self.put_item(item.clone()).await?;
+            return Ok(Some(item));
+        }
+        let chunks = chunk_content(&mut item);
+        self.put_item(item.clone()).await?;
+        store_chunks(self, &item, &chunks).await?;
+        if !chunks.is_empty() {
+            // Return the full content, as a read will.
+            item["content"] = Value::String(chunks.join("\n\n"));
+        }
+        Ok(Some(item))
+    }
+
+    /// Write to one of the [`SYSTEM_CATEGORIES`], which [`put_item`] refuses.
+    ///
+    /// [`put_item`]: Self::put_item
+    pub(crate) async fn put_system_item(&self, doc: Value) -> Result<(), MemoryError> {
+        let doc = normalize_address(doc)?;
+        let category = doc["category"].as_str().unwrap_or_default().to_string();
+        self.write_item(doc).await?;
+        if category == ALIASES_CATEGORY {
+            self.alias_cache.lock().unwrap().remove(&self.table_name);
+        }
+        self.bump_version(&category);
+        Ok(())
+    }
+
+    async fn write_item(&self, doc: Value) -> Result<(), MemoryError> {
+        let mirrored = self.mirror.is_some().then(|| doc.clone());
+        let written = match &self.inner {
             #[cfg(test)]
             BackendInner::Direct(db) => db.put_item(&self.table_name, doc).map_err(mcp_core_err),
-            BackendInner::Server(client) => client
-                .lock()
-                .await
-                .put_item(&self.table_name, doc)
-                .await
+            BackendInner::InMemory(db) => db.put_item(&self.table_name, doc),
+            BackendInner::Server(client) => self
+                .bounded_write(client, async {
+                    let mut client = client.lock().await;
+                    within(
+                        self.timeouts.write,
+                        "write",
+                        client.put_item(&self.table_name, doc),
+                    )
+                    .await
+                })
+                .await?
                 .map_err(mcp_client_err),
+        };
+        written?;
+        if let Some(doc) = mirrored {
+            self.replicate(MirrorOp::Put {
+                table: self.table_name.clone(),
+                doc,
+            });
         }
+        Ok(())
     }
 
+    #[tracing::instrument(
+        name = "backend.get_item",
+        level = "debug",
+        skip(self),
+        fields(table = %self.table_name),
+    )]
     pub async fn get_item(&self, category: &str, key: &str) -> Result<Option<Value>, MemoryError> {
+        let item = self.read_item(category, key).await?;
+        self.count_access(&nfc(category), Access::Read);
+        Ok(item)
+    }
+
+    async fn read_item(&self, category: &str, key: &str) -> Result<Option<Value>, MemoryError> {
+        let (category, key) = (&nfc(category), &nfc(key));
         match &self.inner {
             #[cfg(test)]
             BackendInner::Direct(db) => db
@@ -72,60 +814,195 @@ impl MemoryBackend {
                 .sort_key(key)
                 .execute()
                 .map_err(mcp_core_err),
-            BackendInner::Server(client) => client
-                .lock()
-                .await
-                .get_item(
-                    &self.table_name,
-                    Value::String(category.to_string()),
-                    Some(Value::String(key.to_string())),
-                )
-                .await
-                .map_err(mcp_client_err),
+            BackendInner::Server(client) => {
+                let client = self.read_client(client);
+                self.retry_idempotent(client, || async move {
+                    let mut client = client.lock().await;
+                    within(
+                        self.timeouts.read,
+                        "read",
+                        client.get_item(
+                            &self.table_name,
+                            Value::String(category.to_string()),
+                            Some(Value::String(key.to_string())),
+                        ),
+                    )
+                    .await
+                })
+                .await?
+                .map_err(mcp_client_err)
+            }
+            BackendInner::InMemory(db) => Ok(db.get_item(&self.table_name, category, key)),
         }
     }
 
+    #[tracing::instrument(
+        name = "backend.query",
+        level = "debug",
+        skip(self),
+        fields(table = %self.table_name),
+    )]
     pub async fn query(
         &self,
         partition_key: &str,
         prefix: Option<&str>,
         limit: usize,
     ) -> Result<Vec<Value>, MemoryError> {
-        match &self.inner {
+        self.query_ordered(partition_key, prefix, limit, false)
+            .await
+    }
+
+    /// Like [`MemoryBackend::query`], scanning keys in descending order when
+    /// `descending`.
+    #[tracing::instrument(
+        name = "backend.query_ordered",
+        level = "debug",
+        skip(self),
+        fields(table = %self.table_name),
+    )]
+    pub async fn query_ordered(
+        &self,
+        partition_key: &str,
+        prefix: Option<&str>,
+        limit: usize,
+        descending: bool,
+    ) -> Result<Vec<Value>, MemoryError> {
+        let partition_key = &nfc(partition_key);
+        let prefix = prefix.map(nfc);
+        let prefix = prefix.as_deref();
+        let items = match &self.inner {
             #[cfg(test)]
             BackendInner::Direct(db) => {
                 let mut builder = db.query(&self.table_name).partition_key(partition_key);
                 if let Some(pfx) = prefix {
                     builder = builder.sort_key_begins_with(pfx);
                 }
-                let result = builder.limit(limit).execute().map_err(mcp_core_err)?;
-                Ok(result.items)
+                if !descending {
+                    builder = builder.limit(limit);
+                }
+                let mut items = builder.execute().map_err(mcp_core_err)?.items;
+                if descending {
+                    items.reverse();
+                    items.truncate(limit);
+                }
+                Ok(items)
             }
             BackendInner::Server(client) => {
+                let client = self.read_client(client);
                 use ferridyn_server::protocol::SortKeyCondition;
                 let cond = prefix.map(|pfx| SortKeyCondition::BeginsWith {
                     prefix: pfx.to_string(),
                 });
-                let result = client
-                    .lock()
-                    .await
-                    .query(
-                        &self.table_name,
-                        Value::String(partition_key.to_string()),
-                        cond,
-                        Some(limit),
-                        None,
-                        None,
-                    )
-                    .await
+                let cond = &cond;
+                let result = self
+                    .retry_idempotent(client, || async move {
+                        let mut client = client.lock().await;
+                        within(
+                            self.timeouts.read,
+                            "read",
+                            client.query(
+                                &self.table_name,
+                                Value::String(partition_key.to_string()),
+                                cond.clone(),
+                                Some(limit),
+                                Some(!descending),
+                                None,
+                            ),
+                        )
+                        .await
+                    })
+                    .await?
                     .map_err(mcp_client_err)?;
                 Ok(result.items)
             }
-        }
+            BackendInner::InMemory(db) => {
+                Ok(db.query(&self.table_name, partition_key, prefix, limit, descending))
+            }
+        }?;
+        self.count_access(partition_key, Access::Read);
+        Ok(items)
     }
 
+    /// Up to `limit` items of `partition_key` whose key sorts after `after`
+    /// (from the first key without it), in ascending key order. The server
+    /// starts its scan after `after` instead of reading the keys before it,
+    /// so paging through a partition reads each item once.
+    #[tracing::instrument(
+        name = "backend.query_after",
+        level = "debug",
+        skip(self),
+        fields(table = %self.table_name),
+    )]
+    pub async fn query_after(
+        &self,
+        partition_key: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<Value>, MemoryError> {
+        let partition_key = &nfc(partition_key);
+        let after = after.map(nfc);
+        let after = after.as_deref();
+        let items = match &self.inner {
+            #[cfg(test)]
+            BackendInner::Direct(db) => {
+                let items = db
+                    .query(&self.table_name)
+                    .partition_key(partition_key)
+                    .execute()
+                    .map_err(mcp_core_err)?
+                    .items;
+                Ok(items
+                    .into_iter()
+                    .filter(|item| {
+                        after.is_none_or(|after| item["key"].as_str().is_some_and(|k| k > after))
+                    })
+                    .take(limit)
+                    .collect())
+            }
+            BackendInner::Server(client) => {
+                let client = self.read_client(client);
+                // The exclusive start key names the last item already read.
+                let start =
+                    after.map(|after| serde_json::json!({"category": partition_key, "key": after}));
+                let start = &start;
+                let result = self
+                    .retry_idempotent(client, || async move {
+                        let mut client = client.lock().await;
+                        within(
+                            self.timeouts.read,
+                            "read",
+                            client.query(
+                                &self.table_name,
+                                Value::String(partition_key.to_string()),
+                                None,
+                                Some(limit),
+                                Some(true),
+                                start.clone(),
+                            ),
+                        )
+                        .await
+                    })
+                    .await?
+                    .map_err(mcp_client_err)?;
+                Ok(result.items)
+            }
+            BackendInner::InMemory(db) => {
+                Ok(db.query_after(&self.table_name, partition_key, after, limit))
+            }
+        }?;
+        self.count_access(partition_key, Access::Read);
+        Ok(items)
+    }
+
+    #[tracing::instrument(
+        name = "backend.delete_item",
+        level = "debug",
+        skip(self),
+        fields(table = %self.table_name),
+    )]
     pub async fn delete_item(&self, category: &str, key: &str) -> Result<(), MemoryError> {
-        match &self.inner {
+        let (category, key) = (&nfc(category), &nfc(key));
+        let deleted = match &self.inner {
             #[cfg(test)]
             BackendInner::Direct(db) => db
                 .delete_item(&self.table_name)
@@ -133,19 +1010,46 @@ impl MemoryBackend {
                 .sort_key(key)
                 .execute()
                 .map_err(mcp_core_err),
-            BackendInner::Server(client) => client
-                .lock()
-                .await
-                .delete_item(
-                    &self.table_name,
-                    Value::String(category.to_string()),
-                    Some(Value::String(key.to_string())),
-                )
-                .await
-                .map_err(mcp_client_err),
-        }
+            BackendInner::Server(client) => {
+                self.note_write();
+                self.retry_idempotent(client, || async move {
+                    let mut client = client.lock().await;
+                    within(
+                        self.timeouts.write,
+                        "write",
+                        client.delete_item(
+                            &self.table_name,
+                            Value::String(category.to_string()),
+                            Some(Value::String(key.to_string())),
+                        ),
+                    )
+                    .await
+                })
+                .await?
+                .map_err(mcp_client_err)
+            }
+            BackendInner::InMemory(db) => {
+                db.delete_item(&self.table_name, category, key);
+                Ok(())
+            }
+        };
+        deleted?;
+        self.bump_version(category);
+        self.count_access(category, Access::Write);
+        self.replicate(MirrorOp::Delete {
+            table: self.table_name.clone(),
+            category: category.clone(),
+            key: key.clone(),
+        });
+        Ok(())
     }
 
+    #[tracing::instrument(
+        name = "backend.list_partition_keys",
+        level = "debug",
+        skip(self),
+        fields(table = %self.table_name),
+    )]
     pub async fn list_partition_keys(&self, limit: usize) -> Result<Vec<Value>, MemoryError> {
         match &self.inner {
             #[cfg(test)]
@@ -154,20 +1058,60 @@ impl MemoryBackend {
                 .limit(limit)
                 .execute()
                 .map_err(mcp_core_err),
-            BackendInner::Server(client) => client
-                .lock()
-                .await
-                .list_partition_keys(&self.table_name, Some(limit))
-                .await
-                .map_err(mcp_client_err),
+            BackendInner::Server(client) => {
+                let client = self.read_client(client);
+                self.retry_idempotent(client, || async move {
+                    let mut client = client.lock().await;
+                    within(
+                        self.timeouts.read,
+                        "read",
+                        client.list_partition_keys(&self.table_name, Some(limit)),
+                    )
+                    .await
+                })
+                .await?
+                .map_err(mcp_client_err)
+            }
+            BackendInner::InMemory(db) => Ok(db.list_partition_keys(&self.table_name, limit)),
         }
     }
 
+    /// Every category holding items, with or without a schema, internal
+    /// ones included, in name order (up to [`PAGE_SCAN_LIMIT`]).
+    pub async fn list_categories(&self) -> Result<Vec<String>, MemoryError> {
+        let mut categories: Vec<String> = self
+            .list_partition_keys(PAGE_SCAN_LIMIT)
+            .await?
+            .into_iter()
+            .filter_map(|v| v.as_str().map(String::from))
+            .collect();
+        categories.sort();
+        Ok(categories)
+    }
+
+    /// Up to `limit` categories (partition keys) in name order, starting
+    /// after `cursor`, the last category of the previous page. Follow
+    /// [`Page::next_cursor`] until it is `None` to enumerate every category.
+    pub async fn list_partition_keys_page(
+        &self,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<Page, MemoryError> {
+        Ok(paginate(self.list_categories().await?, cursor, limit))
+    }
+
+    #[tracing::instrument(
+        name = "backend.list_sort_key_prefixes",
+        level = "debug",
+        skip(self),
+        fields(table = %self.table_name),
+    )]
     pub async fn list_sort_key_prefixes(
         &self,
         category: &str,
         limit: usize,
     ) -> Result<Vec<Value>, MemoryError> {
+        let category = &nfc(category);
         match &self.inner {
             #[cfg(test)]
             BackendInner::Direct(db) => db
@@ -176,16 +1120,27 @@ impl MemoryBackend {
                 .limit(limit)
                 .execute()
                 .map_err(mcp_core_err),
-            BackendInner::Server(client) => client
-                .lock()
-                .await
-                .list_sort_key_prefixes(
-                    &self.table_name,
-                    Value::String(category.to_string()),
-                    Some(limit),
-                )
-                .await
-                .map_err(mcp_client_err),
+            BackendInner::Server(client) => {
+                let client = self.read_client(client);
+                self.retry_idempotent(client, || async move {
+                    let mut client = client.lock().await;
+                    within(
+                        self.timeouts.read,
+                        "read",
+                        client.list_sort_key_prefixes(
+                            &self.table_name,
+                            Value::String(category.to_string()),
+                            Some(limit),
+                        ),
+                    )
+                    .await
+                })
+                .await?
+                .map_err(mcp_client_err)
+            }
+            BackendInner::InMemory(db) => {
+                Ok(db.list_sort_key_prefixes(&self.table_name, category, limit))
+            }
         }
     }
 
@@ -198,63 +1153,123 @@ impl MemoryBackend {
         attrs: &[AttributeDefInput],
         validate: bool,
     ) -> Result<(), MemoryError> {
-        match &self.inner {
+        let prefix = &validate_schema_category(prefix).map_err(MemoryError::InvalidParams)?;
+        if SYSTEM_CATEGORIES.contains(&prefix.as_str()) {
+            return Err(MemoryError::InvalidParams(format!(
+                "category '{prefix}' is reserved for internal use"
+            )));
+        }
+        self.require_schemas()?;
+        let created = match &self.inner {
             #[cfg(test)]
             BackendInner::Direct(_) => Err(MemoryError::Internal(
                 "schema operations not supported in direct mode".into(),
             )),
-            BackendInner::Server(client) => client
-                .lock()
-                .await
-                .create_schema(&self.table_name, prefix, description, attrs, validate)
-                .await
+            BackendInner::Server(client) => self
+                .bounded_write(client, async {
+                    let mut client = client.lock().await;
+                    within(
+                        self.timeouts.write,
+                        "write",
+                        client.create_schema(
+                            &self.table_name,
+                            prefix,
+                            description,
+                            attrs,
+                            validate,
+                        ),
+                    )
+                    .await
+                })
+                .await?
                 .map_err(|e| MemoryError::Schema(e.to_string())),
-        }
+            BackendInner::InMemory(db) => {
+                db.create_schema(&self.table_name, prefix, description, attrs, validate)
+            }
+        };
+        self.invalidate_schema_cache();
+        self.bump_version(SCHEMAS_VERSION_KEY);
+        created
     }
 
     pub async fn describe_schema(&self, prefix: &str) -> Result<PartitionSchemaInfo, MemoryError> {
+        self.require_schemas()?;
         match &self.inner {
             #[cfg(test)]
             BackendInner::Direct(_) => Err(MemoryError::Internal(
                 "schema operations not supported in direct mode".into(),
             )),
-            BackendInner::Server(client) => client
-                .lock()
-                .await
-                .describe_schema(&self.table_name, prefix)
-                .await
+            BackendInner::Server(client) => self
+                .retry_idempotent(client, || async move {
+                    let mut client = client.lock().await;
+                    within(
+                        self.timeouts.read,
+                        "read",
+                        client.describe_schema(&self.table_name, prefix),
+                    )
+                    .await
+                })
+                .await?
                 .map_err(|e| MemoryError::Schema(e.to_string())),
+            BackendInner::InMemory(db) => db.describe_schema(&self.table_name, prefix),
         }
     }
 
+    #[tracing::instrument(
+        name = "backend.list_schemas",
+        level = "debug",
+        skip(self),
+        fields(table = %self.table_name),
+    )]
     pub async fn list_schemas(&self) -> Result<Vec<PartitionSchemaInfo>, MemoryError> {
+        if !self.capabilities.partition_schemas {
+            return Ok(Vec::new());
+        }
         match &self.inner {
             #[cfg(test)]
             BackendInner::Direct(_) => Err(MemoryError::Internal(
                 "schema operations not supported in direct mode".into(),
             )),
-            BackendInner::Server(client) => client
-                .lock()
-                .await
-                .list_schemas(&self.table_name)
-                .await
+            BackendInner::Server(client) => self
+                .retry_idempotent(client, || async move {
+                    let mut client = client.lock().await;
+                    within(
+                        self.timeouts.read,
+                        "read",
+                        client.list_schemas(&self.table_name),
+                    )
+                    .await
+                })
+                .await?
                 .map_err(|e| MemoryError::Schema(e.to_string())),
+            BackendInner::InMemory(db) => Ok(db.list_schemas(&self.table_name)),
         }
     }
 
     pub async fn drop_schema(&self, prefix: &str) -> Result<(), MemoryError> {
-        match &self.inner {
+        self.require_schemas()?;
+        let dropped = match &self.inner {
             #[cfg(test)]
             BackendInner::Direct(_) => Err(MemoryError::Internal(
                 "schema operations not supported in direct mode".into(),
             )),
-            BackendInner::Server(client) => client
-                .lock()
-                .await
-                .drop_schema(&self.table_name, prefix)
-                .await
+            BackendInner::Server(client) => self
+                .bounded_write(client, async {
+                    let mut client = client.lock().await;
+                    within(
+                        self.timeouts.write,
+                        "write",
+                        client.drop_schema(&self.table_name, prefix),
+                    )
+                    .await
+                })
+                .await?
                 .map_err(|e| MemoryError::Schema(e.to_string())),
-        }
+            BackendInner::InMemory(db) => db.drop_schema(&self.table_name, prefix),
+        };
+        self.invalidate_schema_cache();
+        self.bump_version(SCHEMAS_VERSION_KEY);
+        dropped
     }
 
     // -- Secondary index operations --
@@ -266,112 +1281,407 @@ impl MemoryBackend {
         key_name: &str,
         key_type: &str,
     ) -> Result<(), MemoryError> {
-        match &self.inner {
+        self.require_indexes()?;
+        let created = match &self.inner {
             #[cfg(test)]
             BackendInner::Direct(_) => Err(MemoryError::Internal(
                 "index operations not supported in direct mode".into(),
             )),
-            BackendInner::Server(client) => client
-                .lock()
-                .await
-                .create_index(&self.table_name, name, partition_schema, key_name, key_type)
-                .await
+            BackendInner::Server(client) => self
+                .bounded_write(client, async {
+                    let mut client = client.lock().await;
+                    within(
+                        self.timeouts.write,
+                        "write",
+                        client.create_index(
+                            &self.table_name,
+                            name,
+                            partition_schema,
+                            key_name,
+                            key_type,
+                        ),
+                    )
+                    .await
+                })
+                .await?
                 .map_err(|e| MemoryError::Index(e.to_string())),
-        }
+            BackendInner::InMemory(db) => {
+                db.create_index(&self.table_name, name, partition_schema, key_name, key_type)
+            }
+        };
+        self.bump_version(SCHEMAS_VERSION_KEY);
+        created
     }
 
+    #[tracing::instrument(
+        name = "backend.list_indexes",
+        level = "debug",
+        skip(self),
+        fields(table = %self.table_name),
+    )]
     pub async fn list_indexes(&self) -> Result<Vec<IndexInfo>, MemoryError> {
+        if !self.capabilities.secondary_indexes {
+            return Ok(Vec::new());
+        }
         match &self.inner {
             #[cfg(test)]
             BackendInner::Direct(_) => Err(MemoryError::Internal(
                 "index operations not supported in direct mode".into(),
             )),
-            BackendInner::Server(client) => client
-                .lock()
-                .await
-                .list_indexes(&self.table_name)
-                .await
+            BackendInner::Server(client) => self
+                .retry_idempotent(client, || async move {
+                    let mut client = client.lock().await;
+                    within(
+                        self.timeouts.read,
+                        "read",
+                        client.list_indexes(&self.table_name),
+                    )
+                    .await
+                })
+                .await?
                 .map_err(|e| MemoryError::Index(e.to_string())),
+            BackendInner::InMemory(db) => Ok(db.list_indexes(&self.table_name)),
         }
     }
 
     pub async fn describe_index(&self, name: &str) -> Result<IndexInfo, MemoryError> {
+        self.require_indexes()?;
         match &self.inner {
             #[cfg(test)]
             BackendInner::Direct(_) => Err(MemoryError::Internal(
                 "index operations not supported in direct mode".into(),
             )),
-            BackendInner::Server(client) => client
-                .lock()
-                .await
-                .describe_index(&self.table_name, name)
-                .await
+            BackendInner::Server(client) => self
+                .retry_idempotent(client, || async move {
+                    let mut client = client.lock().await;
+                    within(
+                        self.timeouts.read,
+                        "read",
+                        client.describe_index(&self.table_name, name),
+                    )
+                    .await
+                })
+                .await?
                 .map_err(|e| MemoryError::Index(e.to_string())),
+            BackendInner::InMemory(db) => db.describe_index(&self.table_name, name),
         }
     }
 
     pub async fn drop_index(&self, name: &str) -> Result<(), MemoryError> {
-        match &self.inner {
+        self.require_indexes()?;
+        let dropped = match &self.inner {
             #[cfg(test)]
             BackendInner::Direct(_) => Err(MemoryError::Internal(
                 "index operations not supported in direct mode".into(),
             )),
-            BackendInner::Server(client) => client
-                .lock()
-                .await
-                .drop_index(&self.table_name, name)
-                .await
+            BackendInner::Server(client) => self
+                .bounded_write(client, async {
+                    let mut client = client.lock().await;
+                    within(
+                        self.timeouts.write,
+                        "write",
+                        client.drop_index(&self.table_name, name),
+                    )
+                    .await
+                })
+                .await?
                 .map_err(|e| MemoryError::Index(e.to_string())),
-        }
+            BackendInner::InMemory(db) => db.drop_index(&self.table_name, name),
+        };
+        self.bump_version(SCHEMAS_VERSION_KEY);
+        dropped
     }
 
     /// Create all predefined schemas and their indexes if they don't already exist.
     ///
-    /// Idempotent — skips categories that already have schemas.
+    /// Idempotent — skips categories that already have schemas, and does
+    /// nothing on a server without partition schemas.
     /// Called by `fmemory init` and auto-init on first `remember`.
     pub async fn ensure_predefined_schemas(&self) -> Result<(), MemoryError> {
+        if !self.capabilities.partition_schemas {
+            return Ok(());
+        }
         let sm = SchemaManager::new(self.clone());
-        for predefined in PREDEFINED_SCHEMAS {
-            if sm.has_schema(predefined.name).await? {
-                continue;
+        let pending: Vec<(String, SchemaDefinition)> = PREDEFINED_SCHEMAS
+            .iter()
+            .map(|p| (p.name.to_string(), p.to_definition()))
+            .collect();
+        stream::iter(pending)
+            .map(|(name, definition)| {
+                let sm = sm.clone();
+                async move {
+                    if sm.has_schema(&name).await? {
+                        return Ok(());
+                    }
+                    sm.create_schema_with_indexes(&name, &definition, false)
+                        .await
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_CATEGORY_OPS)
+            .try_collect::<Vec<()>>()
+            .await?;
+        Ok(())
+    }
+
+    /// Delete all expired items in the given categories.
+    ///
+    /// Up to [`MAX_CONCURRENT_CATEGORY_OPS`] categories are in flight at once;
+    /// see that constant for what this overlaps. Returns the total number of
+    /// items deleted.
+    pub async fn prune_expired(&self, categories: &[String]) -> Result<usize, MemoryError> {
+        let counts: Vec<usize> = stream::iter(categories.iter().cloned())
+            .map(|cat| {
+                let backend = self.clone();
+                async move {
+                    let items = backend.query(&cat, None, 1000).await?;
+                    let mut pruned = 0usize;
+                    for item in &items {
+                        if is_expired(item)
+                            && let Some(key) = item["key"].as_str()
+                        {
+                            backend.delete_item(&cat, key).await?;
+                            pruned += 1;
+                        }
+                    }
+                    Ok::<_, MemoryError>(pruned)
+                }
+            })
+            .buffer_unordered(MAX_CONCURRENT_CATEGORY_OPS)
+            .try_collect()
+            .await?;
+        Ok(counts.into_iter().sum())
+    }
+
+    /// Record a `relation` link from `source` to `target`, both `category/key`
+    /// references to stored items. Linking the same pair with the same
+    /// relation again only refreshes its `created_at`.
+    pub async fn link_items(
+        &self,
+        source: &str,
+        target: &str,
+        relation: &str,
+    ) -> Result<Link, MemoryError> {
+        let link = Link::new(source, target, relation)?;
+        for reference in [&link.source, &link.target] {
+            let (category, key) = parse_item_ref(reference)?;
+            if self.get_item(&category, &key).await?.is_none() {
+                return Err(MemoryError::InvalidParams(format!(
+                    "No memory found for {reference}"
+                )));
             }
-            let definition = predefined.to_definition();
-            sm.create_schema_with_indexes(predefined.name, &definition, false)
+        }
+        for record in link.to_records() {
+            self.put_system_item(record).await?;
+        }
+        Ok(link)
+    }
+
+    /// Links from and to `item_ref` (`category/key`), outgoing first.
+    pub async fn get_links(&self, item_ref: &str) -> Result<Vec<Link>, MemoryError> {
+        let item_ref = normalize_item_ref(item_ref)?;
+        let mut links = Vec::new();
+        for direction in [LinkDirection::Out, LinkDirection::In] {
+            let prefix = link_key_prefix(direction, &item_ref);
+            let records = self
+                .query(LINKS_CATEGORY, Some(&prefix), LINKS_SCAN_LIMIT)
                 .await?;
+            // Keys carry a truncated hash, so check the reference itself.
+            links.extend(records.iter().filter_map(Link::from_record).filter(
+                |link| match direction {
+                    LinkDirection::Out => link.source == item_ref,
+                    LinkDirection::In => link.target == item_ref,
+                },
+            ));
         }
-        Ok(())
+        Ok(links)
+    }
+
+    /// Remove the links from `source` to `target`, or only those of
+    /// `relation` if given. Returns the number of links removed.
+    pub async fn unlink_items(
+        &self,
+        source: &str,
+        target: &str,
+        relation: Option<&str>,
+    ) -> Result<usize, MemoryError> {
+        let target = normalize_item_ref(target)?;
+        let relation = relation.map(slugify);
+        let links: Vec<Link> = self
+            .get_links(source)
+            .await?
+            .into_iter()
+            .filter(|link| {
+                link.target == target && relation.as_ref().is_none_or(|r| &link.relation == r)
+            })
+            .collect();
+        for link in &links {
+            for record in link.to_records() {
+                let key = record["key"].as_str().unwrap_or_default();
+                self.delete_item(LINKS_CATEGORY, key).await?;
+            }
+        }
+        Ok(links.len())
     }
 
+    #[tracing::instrument(
+        name = "backend.query_index",
+        level = "debug",
+        skip(self, key_value),
+        fields(table = %self.table_name),
+    )]
     pub async fn query_index(
         &self,
         index_name: &str,
         key_value: Value,
         limit: Option<usize>,
     ) -> Result<Vec<Value>, MemoryError> {
-        match &self.inner {
+        self.require_indexes()?;
+        let items = match &self.inner {
             #[cfg(test)]
             BackendInner::Direct(_) => Err(MemoryError::Internal(
                 "index operations not supported in direct mode".into(),
             )),
             BackendInner::Server(client) => {
-                let result = client
-                    .lock()
-                    .await
-                    .query_index(&self.table_name, index_name, key_value, limit, None)
-                    .await
+                let client = self.read_client(client);
+                let key_value = &key_value;
+                let result = self
+                    .retry_idempotent(client, || async move {
+                        let mut client = client.lock().await;
+                        within(
+                            self.timeouts.read,
+                            "read",
+                            client.query_index(
+                                &self.table_name,
+                                index_name,
+                                key_value.clone(),
+                                limit,
+                                None,
+                            ),
+                        )
+                        .await
+                    })
+                    .await?
                     .map_err(|e| MemoryError::Index(e.to_string()))?;
                 Ok(result.items)
             }
+            BackendInner::InMemory(db) => {
+                db.query_index(&self.table_name, index_name, &key_value, limit)
+            }
+        }?;
+        let categories: std::collections::BTreeSet<&str> = items
+            .iter()
+            .filter_map(|item| item["category"].as_str())
+            .collect();
+        for category in categories {
+            self.count_access(category, Access::Read);
         }
+        Ok(items)
     }
 }
 
+/// Apply `patch` to `fields` as a JSON Merge Patch (RFC 7396).
+fn merge_patch(
+    fields: &mut serde_json::Map<String, Value>,
+    patch: &serde_json::Map<String, Value>,
+) {
+    for (name, value) in patch {
+        match (value, fields.get_mut(name)) {
+            (Value::Null, _) => {
+                fields.remove(name);
+            }
+            (Value::Object(nested), Some(Value::Object(existing))) => {
+                merge_patch(existing, nested);
+            }
+            (Value::Object(nested), _) => {
+                let mut created = serde_json::Map::new();
+                merge_patch(&mut created, nested);
+                fields.insert(name.clone(), Value::Object(created));
+            }
+            _ => {
+                fields.insert(name.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Validate an item's `category` and `key` and replace them with their
+/// normalized forms. [`SYSTEM_CATEGORIES`] pass as they are. Lookups only
+/// NFC-normalize, so an invalid address simply finds nothing.
+fn normalize_address(mut doc: Value) -> Result<Value, MemoryError> {
+    let (Some(category), Some(key)) = (doc["category"].as_str(), doc["key"].as_str()) else {
+        return Err(MemoryError::InvalidParams(
+            "item must have string 'category' and 'key' attributes".into(),
+        ));
+    };
+    let category = if SYSTEM_CATEGORIES.contains(&category) {
+        category.to_string()
+    } else {
+        normalize_category(category).map_err(MemoryError::InvalidParams)?
+    };
+    let key = normalize_key(key).map_err(MemoryError::InvalidParams)?;
+    doc["category"] = Value::String(category);
+    doc["key"] = Value::String(key);
+    Ok(doc)
+}
+
 #[cfg(test)]
 fn mcp_core_err(err: ferridyn_core::error::Error) -> MemoryError {
     MemoryError::Internal(format!("{err}"))
 }
 
-fn mcp_client_err(err: ferridyn_server::error::ClientError) -> MemoryError {
+/// Await one server `request`, bounded by `limit` when set. Only the request
+/// is timed; callers lock the client before building it. The outer `Err` is
+/// the timeout, naming the `kind` of request.
+async fn within<T>(
+    limit: Option<Duration>,
+    kind: &str,
+    request: impl Future<Output = Result<T, ClientError>>,
+) -> Result<Result<T, ClientError>, MemoryError> {
+    let Some(limit) = limit else {
+        return Ok(request.await);
+    };
+    tokio::time::timeout(limit, request)
+        .await
+        .map_err(|_| MemoryError::Timeout(format!("backend {kind} after {limit:?}")))
+}
+
+/// Whether `err` is a connection failure worth retrying: its source chain
+/// holds an I/O error such as a reset connection or a timeout.
+fn is_transient(err: &(dyn std::error::Error + 'static)) -> bool {
+    use std::io::ErrorKind;
+
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            return matches!(
+                io.kind(),
+                ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::BrokenPipe
+                    | ErrorKind::TimedOut
+                    | ErrorKind::Interrupted
+                    | ErrorKind::UnexpectedEof
+            );
+        }
+        source = err.source();
+    }
+    false
+}
+
+/// Whether a replica that trails the primary by up to `max_lag` has every
+/// write made at `last_write` by `now`.
+fn replica_is_current(last_write: Option<Instant>, max_lag: Duration, now: Instant) -> bool {
+    last_write.is_none_or(|at| now.duration_since(at) >= max_lag)
+}
+
+/// Backoff before retry number `attempt` (1-based).
+fn retry_delay(attempt: u32) -> Duration {
+    RETRY_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt - 1))
+        .min(RETRY_MAX_DELAY)
+}
+
+fn mcp_client_err(err: ClientError) -> MemoryError {
     MemoryError::Server(format!("{err}"))
 }
 
@@ -582,12 +1892,527 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_prune_expired_across_categories() {
+        use super::MemoryBackend;
+        let (db, _dir) = setup_test_db();
+        let backend = MemoryBackend::direct(db, TABLE_NAME.to_string());
+        let past = (chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            for cat in ["scratchpad", "notes", "events"] {
+                backend
+                    .put_item(json!({"category": cat, "key": "old", "expires_at": past}))
+                    .await
+                    .unwrap();
+                backend
+                    .put_item(json!({"category": cat, "key": "keep", "content": "ltm"}))
+                    .await
+                    .unwrap();
+            }
+            let categories: Vec<String> = ["scratchpad", "notes", "events"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+            let pruned = backend.prune_expired(&categories).await.unwrap();
+            assert_eq!(pruned, 3);
+            for cat in &categories {
+                let items = backend.query(cat, None, 10).await.unwrap();
+                assert_eq!(items.len(), 1);
+                assert_eq!(items[0]["key"], "keep");
+            }
+        });
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_schemas_and_indexes() {
+        use super::MemoryBackend;
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        backend.ensure_predefined_schemas().await.unwrap();
+        let schema = backend.describe_schema("contacts").await.unwrap();
+        assert!(!schema.attributes.is_empty());
+        assert!(backend.describe_schema("nope").await.is_err());
+
+        let index = backend
+            .list_indexes()
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|i| i.partition_schema == "contacts")
+            .unwrap();
+        let attr = index.index_key_name.clone();
+        backend
+            .put_item(json!({"category": "contacts", "key": "toby", attr.as_str(): "match"}))
+            .await
+            .unwrap();
+        backend
+            .put_item(json!({"category": "contacts", "key": "alice", attr.as_str(): "other"}))
+            .await
+            .unwrap();
+        let hits = backend
+            .query_index(&index.name, json!("match"), None)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0]["key"], "toby");
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_capabilities_degrade() {
+        use super::MemoryBackend;
+        use crate::capability::Capabilities;
+        use crate::error::MemoryError;
+        let backend =
+            MemoryBackend::in_memory(TABLE_NAME.to_string()).with_capabilities(Capabilities {
+                partition_schemas: false,
+                secondary_indexes: false,
+            });
+        backend.ensure_predefined_schemas().await.unwrap();
+        assert!(backend.list_schemas().await.unwrap().is_empty());
+        assert!(backend.list_indexes().await.unwrap().is_empty());
+        let err = backend
+            .create_index("contacts_email", "contacts", "email", "STRING")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MemoryError::Unsupported(_)));
+        assert!(err.to_string().contains("upgrade ferridyn-server"));
+        assert!(matches!(
+            backend.describe_schema("contacts").await,
+            Err(MemoryError::Unsupported(_))
+        ));
+
+        backend
+            .put_item(json!({"category": "contacts", "key": "toby", "name": "Toby"}))
+            .await
+            .unwrap();
+        assert!(
+            backend
+                .get_item("contacts", "toby")
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_items_and_namespaces() {
+        use super::MemoryBackend;
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for key in ["ownership#borrowing", "ownership#moves", "lifetimes#basics"] {
+            backend
+                .put_item(json!({"category": "rust", "key": key}))
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            backend
+                .query("rust", Some("ownership"), 10)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(backend.query("rust", None, 1).await.unwrap().len(), 1);
+        assert_eq!(
+            backend.list_sort_key_prefixes("rust", 10).await.unwrap(),
+            vec![json!("lifetimes"), json!("ownership")]
+        );
+        backend
+            .delete_item("rust", "lifetimes#basics")
+            .await
+            .unwrap();
+        assert!(
+            backend
+                .get_item("rust", "lifetimes#basics")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // Clones with another table name share storage but not tables.
+        let mut other = backend.clone();
+        other.table_name = "memories_other".to_string();
+        assert!(other.list_partition_keys(10).await.unwrap().is_empty());
+        assert_eq!(
+            backend.list_partition_keys(10).await.unwrap(),
+            vec![json!("rust")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_partition_keys_page() {
+        use super::MemoryBackend;
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for category in ["rust", "notes", "contacts"] {
+            backend
+                .put_item(json!({"category": category, "key": "k"}))
+                .await
+                .unwrap();
+        }
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = backend
+                .list_partition_keys_page(cursor.as_deref(), 2)
+                .await
+                .unwrap();
+            assert!(page.items.len() <= 2);
+            seen.extend(page.items);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(seen, vec!["contacts", "notes", "rust"]);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_validating_schema_rejects_missing_attribute() {
+        use super::MemoryBackend;
+        use ferridyn_server::client::AttributeDefInput;
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        let attrs = [AttributeDefInput {
+            name: "email".into(),
+            attr_type: "STRING".into(),
+            required: true,
+        }];
+        backend
+            .create_schema("people", None, &attrs, true)
+            .await
+            .unwrap();
+        assert!(
+            backend
+                .put_item(json!({"category": "people", "key": "a"}))
+                .await
+                .is_err()
+        );
+        backend
+            .put_item(json!({"category": "people", "key": "a", "email": "a@x"}))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_update_item_merges_patch() {
+        use super::MemoryBackend;
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        backend
+            .put_item(
+                json!({"category": "contacts", "key": "toby", "name": "Toby",
+                "phone": "555", "address": {"city": "Leeds", "zip": "LS1"}}),
+            )
+            .await
+            .unwrap();
+        let patch = json!({"role": "CTO", "phone": null, "address": {"zip": "LS2"}});
+        let updated = backend
+            .update_item("contacts", "toby", patch.as_object().unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        let stored = backend.get_item("contacts", "toby").await.unwrap().unwrap();
+        assert_eq!(stored, updated);
+        assert_eq!(stored["name"], "Toby");
+        assert_eq!(stored["role"], "CTO");
+        assert!(stored.get("phone").is_none());
+        assert_eq!(stored["address"], json!({"city": "Leeds", "zip": "LS2"}));
+        assert!(stored["updated_at"].is_string());
+
+        // Concurrent updates keep each other's attributes.
+        let patches: Vec<_> = (0..8)
+            .map(|i| json!({ format!("tag{i}"): i }).as_object().unwrap().clone())
+            .collect();
+        futures::future::try_join_all(
+            patches
+                .iter()
+                .map(|patch| backend.update_item("contacts", "toby", patch)),
+        )
+        .await
+        .unwrap();
+        let stored = backend.get_item("contacts", "toby").await.unwrap().unwrap();
+        assert!((0..8).all(|i| stored[format!("tag{i}")] == i));
+
+        let rename = json!({"key": "tobias"});
+        assert!(
+            backend
+                .update_item("contacts", "toby", rename.as_object().unwrap())
+                .await
+                .is_err()
+        );
+        assert!(
+            backend
+                .update_item("contacts", "missing", patch.as_object().unwrap())
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_item_rechunks_content() {
+        use super::MemoryBackend;
+        use crate::chunk::{CHUNK_THRESHOLD_CHARS, hydrate_chunks};
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        backend
+            .put_item(json!({"category": "notes", "key": "long", "content": "short"}))
+            .await
+            .unwrap();
+
+        let long = json!({"content": "word ".repeat(CHUNK_THRESHOLD_CHARS)});
+        let updated = backend
+            .update_item("notes", "long", long.as_object().unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(updated["chunk_count"].as_u64().unwrap() > 1);
+        let stored = backend.get_item("notes", "long").await.unwrap().unwrap();
+        let hydrated = hydrate_chunks(&backend, vec![stored]).await.unwrap();
+        assert_eq!(hydrated[0]["content"], updated["content"]);
+
+        // Patching other attributes leaves the chunks alone.
+        let tag = json!({"tag": "draft"});
+        backend
+            .update_item("notes", "long", tag.as_object().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            backend.query("notes", None, 100).await.unwrap().len(),
+            1 + updated["chunk_count"].as_u64().unwrap() as usize
+        );
+
+        let short = json!({"content": "short again"});
+        let updated = backend
+            .update_item("notes", "long", short.as_object().unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated["content"], "short again");
+        assert!(updated.get("chunk_count").is_none());
+        assert_eq!(backend.query("notes", None, 100).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_put_item_canonicalizes_aliases() {
+        use super::MemoryBackend;
+        use crate::alias::save_aliases;
+        use crate::schema::AttributeDef;
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        backend
+            .put_item(json!({"category": "contacts", "key": "toby", "org": "infra"}))
+            .await
+            .unwrap();
+        let item = backend.get_item("contacts", "toby").await.unwrap().unwrap();
+        assert_eq!(item["team"], "infra");
+        assert!(item.get("org").is_none());
+
+        let patch = json!({"group": "platform"});
+        let err = backend
+            .update_item("contacts", "toby", patch.as_object().unwrap())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("'group'"), "{err}");
+
+        // Aliases saved later apply to the next write.
+        let attribute = AttributeDef {
+            name: "phone".into(),
+            attr_type: "STRING".into(),
+            required: false,
+            aliases: vec!["mobile".into()],
+        };
+        save_aliases(&backend, "contacts", &[attribute])
+            .await
+            .unwrap();
+        backend
+            .put_item(json!({"category": "contacts", "key": "ada", "mobile": "555"}))
+            .await
+            .unwrap();
+        let item = backend.get_item("contacts", "ada").await.unwrap().unwrap();
+        assert_eq!(item["phone"], "555");
+    }
+
+    #[tokio::test]
+    async fn test_put_item_normalizes_and_validates_address() {
+        use super::MemoryBackend;
+        use crate::error::MemoryError;
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        let decomposed = "jose\u{301}";
+        backend
+            .put_item(json!({"category": "contacts", "key": decomposed}))
+            .await
+            .unwrap();
+        let item = backend
+            .get_item("contacts", "jos\u{e9}")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(item["key"], "jos\u{e9}");
+        assert!(
+            backend
+                .get_item("contacts", decomposed)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(
+            backend
+                .query("contacts", Some(decomposed), 10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        for bad in [
+            json!({"category": "contacts", "key": "party \u{1f389}"}),
+            json!({"category": "Contacts", "key": "toby"}),
+            json!({"category": "contacts"}),
+        ] {
+            let err = backend.put_item(bad).await.unwrap_err();
+            assert!(matches!(err, MemoryError::InvalidParams(_)), "{err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_link_get_and_unlink_items() {
+        use super::MemoryBackend;
+        use crate::error::MemoryError;
+        use crate::links::LINKS_CATEGORY;
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for (category, key) in [
+            ("decisions", "auth-method"),
+            ("contacts", "toby"),
+            ("project", "auth-service"),
+        ] {
+            backend
+                .put_item(json!({"category": category, "key": key}))
+                .await
+                .unwrap();
+        }
+
+        let err = backend
+            .link_items("decisions/auth-method", "contacts/nobody", "decided-by")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MemoryError::InvalidParams(_)), "{err}");
+
+        backend
+            .link_items("decisions/auth-method", "contacts/toby", "Decided by")
+            .await
+            .unwrap();
+        backend
+            .link_items("decisions/auth-method", "project/auth-service", "")
+            .await
+            .unwrap();
+        // Re-linking does not add a second link.
+        backend
+            .link_items("decisions/auth-method", "contacts/toby", "decided-by")
+            .await
+            .unwrap();
+
+        let links = backend.get_links("decisions/auth-method").await.unwrap();
+        assert_eq!(links.len(), 2);
+        let toby = backend.get_links("contacts/toby").await.unwrap();
+        assert_eq!(toby.len(), 1);
+        assert_eq!(toby[0].source, "decisions/auth-method");
+        assert_eq!(toby[0].relation, "decided-by");
+
+        let removed = backend
+            .unlink_items("decisions/auth-method", "contacts/toby", Some("owns"))
+            .await
+            .unwrap();
+        assert_eq!(removed, 0);
+        let removed = backend
+            .unlink_items("decisions/auth-method", "contacts/toby", None)
+            .await
+            .unwrap();
+        assert_eq!(removed, 1);
+        assert!(backend.get_links("contacts/toby").await.unwrap().is_empty());
+        assert_eq!(
+            backend.query(LINKS_CATEGORY, None, 10).await.unwrap().len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reserved_categories_rejected_on_write() {
+        use super::MemoryBackend;
+        use crate::error::MemoryError;
+        use crate::usage::USAGE_CATEGORY;
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for category in ["_trash", "_history", USAGE_CATEGORY] {
+            let err = backend
+                .put_item(json!({"category": category, "key": "x"}))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, MemoryError::InvalidParams(_)), "{err}");
+        }
+        backend
+            .put_system_item(json!({"category": USAGE_CATEGORY, "key": "x"}))
+            .await
+            .unwrap();
+
+        for category in ["_schema", "meeting_notes", USAGE_CATEGORY] {
+            let err = backend
+                .create_schema(category, None, &[], false)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, MemoryError::InvalidParams(_)), "{err}");
+        }
+        backend
+            .create_schema("meeting-notes", None, &[], false)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_oversized_items_rejected() {
+        use super::MemoryBackend;
+        use crate::error::MemoryError;
+        use crate::size::ItemSizeLimits;
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string()).with_item_size_limits(
+            ItemSizeLimits {
+                warn: Some(64),
+                max: Some(256),
+            },
+        );
+        backend
+            .put_item(json!({"category": "notes", "key": "small", "content": "x".repeat(100)}))
+            .await
+            .unwrap();
+        let err = backend
+            .put_item(json!({"category": "notes", "key": "big", "content": "x".repeat(300)}))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MemoryError::InvalidParams(_)), "{err}");
+        assert!(err.to_string().contains("notes/big"), "{err}");
+        assert!(backend.get_item("notes", "big").await.unwrap().is_none());
+    }
+
     #[test]
     fn test_resolve_table_name() {
-        use crate::resolve_table_name;
+        use crate::{
+            MAX_TABLE_NAME_LEN, resolve_table_name, table_name_in, validate_namespace,
+            validate_table_name,
+        };
         assert_eq!(resolve_table_name(None), "memories");
         assert_eq!(resolve_table_name(Some("myproject")), "memories_myproject");
         assert_eq!(resolve_table_name(Some("test")), "memories_test");
+        assert_eq!(table_name_in("acme", None), "acme");
+        assert_eq!(table_name_in("acme", Some("team")), "acme_team");
+
+        assert!(validate_table_name("acme-memories_2").is_ok());
+        assert!(validate_table_name(TABLE_NAME).is_ok());
+        assert!(validate_table_name("").is_err());
+        assert!(validate_table_name("2fast").is_err());
+        assert!(validate_table_name("has space").is_err());
+        assert!(validate_table_name(&"a".repeat(MAX_TABLE_NAME_LEN + 1)).is_err());
+        assert!(validate_table_name("memories_team").is_err());
+
+        assert!(validate_namespace("team-2_a").is_ok());
+        assert!(validate_namespace("").is_err());
+        assert!(validate_namespace("../etc").is_err());
+        assert!(validate_namespace("has space").is_err());
+        assert!(validate_namespace(&"a".repeat(MAX_TABLE_NAME_LEN - TABLE_NAME.len() - 1)).is_ok());
+        assert!(validate_namespace(&"a".repeat(MAX_TABLE_NAME_LEN - TABLE_NAME.len())).is_err());
     }
 
     #[test]
@@ -613,4 +2438,36 @@ mod tests {
             assert_eq!(items[0]["content"], "namespaced");
         });
     }
+
+    #[test]
+    fn test_transient_errors_and_backoff() {
+        use super::{RETRY_MAX_DELAY, is_transient, retry_delay};
+        use std::io::{Error, ErrorKind};
+        use std::time::Duration;
+
+        assert!(is_transient(&Error::from(ErrorKind::ConnectionReset)));
+        assert!(is_transient(&Error::from(ErrorKind::TimedOut)));
+        assert!(!is_transient(&Error::from(ErrorKind::PermissionDenied)));
+        // An I/O error wrapped in another error is found through `source`.
+        let wrapped = Error::other(Error::from(ErrorKind::BrokenPipe));
+        assert!(is_transient(wrapped.get_ref().unwrap()));
+        assert!(!is_transient(&super::MemoryError::Server("nope".into())));
+
+        assert_eq!(retry_delay(1), Duration::from_millis(50));
+        assert_eq!(retry_delay(2), Duration::from_millis(100));
+        assert_eq!(retry_delay(20), RETRY_MAX_DELAY);
+    }
+
+    #[test]
+    fn test_replica_is_current() {
+        use super::replica_is_current;
+        use std::time::{Duration, Instant};
+
+        let now = Instant::now();
+        let lag = Duration::from_secs(2);
+        assert!(replica_is_current(None, lag, now));
+        assert!(!replica_is_current(Some(now), lag, now));
+        assert!(replica_is_current(Some(now), lag, now + lag));
+        assert!(replica_is_current(Some(now), Duration::ZERO, now));
+    }
 }
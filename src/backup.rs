@@ -0,0 +1,179 @@
+//! Backup archives of a memory table and diffs between two of them.
+//!
+//! An archive is a JSON file holding every item of the table, grouped by
+//! category, as stored (expired items included). [`diff_backups`] compares
+//! two archives key by key, so `fmemory backup diff` can show what an agent
+//! added, removed, or changed between the two snapshots. Schemas and indexes
+//! are not archived.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::backend::MemoryBackend;
+use crate::error::MemoryError;
+
+/// Version of the archive format written by [`create_backup`].
+pub const BACKUP_VERSION: u32 = 1;
+
+/// Maximum number of categories, and of items per category, archived.
+pub const BACKUP_SCAN_LIMIT: usize = 100_000;
+
+/// A snapshot of one memory table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Backup {
+    pub version: u32,
+    pub table: String,
+    /// RFC 3339 time the snapshot was taken.
+    pub created_at: String,
+    /// Items by category, sorted by key.
+    pub items: BTreeMap<String, Vec<Value>>,
+}
+
+/// Snapshot every item of `backend`'s table.
+pub async fn create_backup(backend: &MemoryBackend) -> Result<Backup, MemoryError> {
+    let mut items = BTreeMap::new();
+    for category in backend.list_partition_keys(BACKUP_SCAN_LIMIT).await? {
+        let Some(category) = category.as_str() else {
+            continue;
+        };
+        let mut category_items = backend.query(category, None, BACKUP_SCAN_LIMIT).await?;
+        category_items.sort_by(|a, b| a["key"].as_str().cmp(&b["key"].as_str()));
+        items.insert(category.to_string(), category_items);
+    }
+    Ok(Backup {
+        version: BACKUP_VERSION,
+        table: backend.table_name.clone(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        items,
+    })
+}
+
+/// An item present in both snapshots with different contents.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangedItem {
+    pub key: String,
+    /// Attributes added, removed, or changed, sorted by name.
+    pub attributes: Vec<String>,
+}
+
+/// Differences within one category, keys sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CategoryDiff {
+    pub category: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<ChangedItem>,
+}
+
+/// What changed from `before` to `after`, per category. Categories without
+/// differences are left out.
+pub fn diff_backups(before: &Backup, after: &Backup) -> Vec<CategoryDiff> {
+    let categories: BTreeSet<&String> = before.items.keys().chain(after.items.keys()).collect();
+    categories
+        .into_iter()
+        .filter_map(|category| {
+            let old = by_key(before.items.get(category));
+            let new = by_key(after.items.get(category));
+            let mut diff = CategoryDiff {
+                category: category.clone(),
+                ..Default::default()
+            };
+            for (key, item) in &new {
+                match old.get(key) {
+                    None => diff.added.push(key.to_string()),
+                    Some(previous) if previous != item => diff.changed.push(ChangedItem {
+                        key: key.to_string(),
+                        attributes: changed_attributes(previous, item),
+                    }),
+                    Some(_) => {}
+                }
+            }
+            diff.removed = old
+                .keys()
+                .filter(|key| !new.contains_key(*key))
+                .map(|key| key.to_string())
+                .collect();
+            let empty = diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty();
+            (!empty).then_some(diff)
+        })
+        .collect()
+}
+
+fn by_key(items: Option<&Vec<Value>>) -> BTreeMap<&str, &Value> {
+    items
+        .into_iter()
+        .flatten()
+        .filter_map(|item| Some((item["key"].as_str()?, item)))
+        .collect()
+}
+
+fn changed_attributes(before: &Value, after: &Value) -> Vec<String> {
+    let empty = serde_json::Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+    let names: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    names
+        .into_iter()
+        .filter(|name| before.get(*name) != after.get(*name))
+        .cloned()
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_backup_and_diff() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for (key, email) in [("toby", "toby@old.dev"), ("ann", "ann@x.dev")] {
+            backend
+                .put_item(json!({"category": "contacts", "key": key, "email": email}))
+                .await
+                .unwrap();
+        }
+        let before = create_backup(&backend).await.unwrap();
+        assert_eq!(before.items["contacts"].len(), 2);
+
+        backend
+            .put_item(json!({"category": "contacts", "key": "toby", "email": "toby@new.dev", "role": "cto"}))
+            .await
+            .unwrap();
+        backend.delete_item("contacts", "ann").await.unwrap();
+        backend
+            .put_item(json!({"category": "notes", "key": "standup", "content": "moved to 10am"}))
+            .await
+            .unwrap();
+        let after = create_backup(&backend).await.unwrap();
+
+        let diff = diff_backups(&before, &after);
+        assert_eq!(
+            diff,
+            vec![
+                CategoryDiff {
+                    category: "contacts".into(),
+                    added: vec![],
+                    removed: vec!["ann".into()],
+                    changed: vec![ChangedItem {
+                        key: "toby".into(),
+                        attributes: vec!["email".into(), "role".into()],
+                    }],
+                },
+                CategoryDiff {
+                    category: "notes".into(),
+                    added: vec!["standup".into()],
+                    ..Default::default()
+                },
+            ]
+        );
+        assert!(diff_backups(&after, &after).is_empty());
+    }
+}
@@ -0,0 +1,326 @@
+//! Token-budget trimming of LLM prompt context.
+//!
+//! Query resolution describes every category schema, index, and a sample of
+//! keys to the LLM. With many categories that context grows past what is
+//! useful, so [`trim_to_budget`] ranks categories and keys by keyword overlap
+//! with the query and keeps the most relevant ones that fit the budget.
+
+use std::collections::BTreeSet;
+
+use crate::schema::{format_index_entry, format_schema_entry};
+use crate::{IndexInfo, PartitionSchemaInfo};
+
+/// Default token budget for the schema/index/key context of a resolve prompt.
+pub const DEFAULT_PROMPT_TOKEN_BUDGET: usize = 2000;
+
+/// Environment variable overriding [`DEFAULT_PROMPT_TOKEN_BUDGET`].
+pub const PROMPT_BUDGET_ENV: &str = "FMEMORY_PROMPT_BUDGET";
+
+/// Words too common to signal relevance.
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "at", "did", "do", "does", "for", "from", "how", "i", "in", "is",
+    "it", "me", "my", "of", "on", "or", "the", "to", "was", "we", "what", "when", "where", "which",
+    "who", "why", "with",
+];
+
+/// Schema, index, and key context that fits within a token budget.
+#[derive(Debug, Clone)]
+pub struct PromptContext {
+    /// Selected categories, most relevant first.
+    pub schemas: Vec<PartitionSchemaInfo>,
+    /// Indexes belonging to the selected categories.
+    pub indexes: Vec<IndexInfo>,
+    /// Selected keys per category, most relevant first.
+    pub category_keys: Vec<(String, Vec<String>)>,
+}
+
+/// Read the prompt budget from `FMEMORY_PROMPT_BUDGET`, falling back to the default.
+pub fn prompt_budget_from_env() -> usize {
+    std::env::var(PROMPT_BUDGET_ENV)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|&n: &usize| n > 0)
+        .unwrap_or(DEFAULT_PROMPT_TOKEN_BUDGET)
+}
+
+/// Rough token estimate (~4 characters per token).
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Split text into lowercase alphanumeric terms, dropping stop words and
+/// single characters (e.g. the "s" in "Toby's").
+///
+/// Keys like `doctor-appointment` split into `doctor` and `appointment`.
+pub(crate) fn terms(text: &str) -> BTreeSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() > 1)
+        .map(|w| w.to_lowercase())
+        .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// Count query terms that appear in `text`, matching on shared prefixes so
+/// that "meetings" matches "meeting".
+pub(crate) fn overlap(query_terms: &BTreeSet<String>, text: &str) -> usize {
+    let text_terms = terms(text);
+    query_terms
+        .iter()
+        .filter(|q| {
+            text_terms
+                .iter()
+                .any(|t| t.starts_with(q.as_str()) || (t.len() >= 3 && q.starts_with(t.as_str())))
+        })
+        .count()
+}
+
+/// Score a category's relevance to the query.
+///
+/// Name matches weigh most, then attribute and key matches, then description.
+fn score_category(
+    query_terms: &BTreeSet<String>,
+    schema: &PartitionSchemaInfo,
+    keys: &[String],
+) -> usize {
+    let attrs = schema
+        .attributes
+        .iter()
+        .map(|a| a.name.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    overlap(query_terms, &schema.prefix) * 4
+        + overlap(query_terms, &attrs) * 2
+        + overlap(query_terms, &keys.join(" ")) * 2
+        + overlap(query_terms, &schema.description)
+}
+
+/// Categories ordered by relevance to `query`, most relevant first, scored
+/// with their sample keys from `category_keys`. Ties keep their original
+/// order.
+pub fn rank_categories<'a>(
+    schemas: &'a [PartitionSchemaInfo],
+    category_keys: &[(String, Vec<String>)],
+    query: &str,
+) -> Vec<&'a PartitionSchemaInfo> {
+    let query_terms = terms(query);
+    let mut ranked: Vec<(usize, &PartitionSchemaInfo)> = schemas
+        .iter()
+        .map(|s| {
+            let keys = category_keys
+                .iter()
+                .find(|(c, _)| *c == s.prefix)
+                .map(|(_, k)| k.as_slice())
+                .unwrap_or_default();
+            (score_category(&query_terms, s, keys), s)
+        })
+        .collect();
+    ranked.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    ranked.into_iter().map(|(_, s)| s).collect()
+}
+
+/// Trim schemas, indexes, and keys so the rendered prompt context stays
+/// within `token_budget` estimated tokens.
+///
+/// Categories are added in relevance order (ties keep their original order)
+/// until the budget is spent; the most relevant category is always kept.
+/// Remaining budget is then filled with each selected category's keys, most
+/// relevant first. When everything fits, the input is returned unchanged.
+pub fn trim_to_budget(
+    schemas: &[PartitionSchemaInfo],
+    indexes: &[IndexInfo],
+    category_keys: &[(String, Vec<String>)],
+    query: &str,
+    token_budget: usize,
+) -> PromptContext {
+    let keys_for = |category: &str| -> &[String] {
+        category_keys
+            .iter()
+            .find(|(c, _)| c == category)
+            .map(|(_, k)| k.as_slice())
+            .unwrap_or_default()
+    };
+    let indexes_for = |category: &str| -> Vec<&IndexInfo> {
+        indexes
+            .iter()
+            .filter(|i| i.partition_schema == category)
+            .collect()
+    };
+    let category_cost = |schema: &PartitionSchemaInfo, keys: &[&str]| -> usize {
+        estimate_tokens(&format_schema_entry(schema, keys))
+            + indexes_for(&schema.prefix)
+                .iter()
+                .map(|i| estimate_tokens(&format_index_entry(i)))
+                .sum::<usize>()
+    };
+
+    let full_cost: usize = schemas
+        .iter()
+        .map(|s| {
+            let keys: Vec<&str> = keys_for(&s.prefix).iter().map(|k| k.as_str()).collect();
+            category_cost(s, &keys)
+        })
+        .sum();
+    if full_cost <= token_budget {
+        return PromptContext {
+            schemas: schemas.to_vec(),
+            indexes: indexes.to_vec(),
+            category_keys: category_keys.to_vec(),
+        };
+    }
+
+    let query_terms = terms(query);
+    let ranked = rank_categories(schemas, category_keys, query);
+
+    // Pass 1: categories without keys, most relevant first.
+    let mut used = 0;
+    let mut selected: Vec<&PartitionSchemaInfo> = Vec::new();
+    for schema in ranked {
+        let cost = category_cost(schema, &[]);
+        if selected.is_empty() || used + cost <= token_budget {
+            used += cost;
+            selected.push(schema);
+        }
+    }
+
+    // Pass 2: fill remaining budget with keys, most relevant first.
+    let mut selected_keys: Vec<(String, Vec<String>)> = Vec::new();
+    for schema in &selected {
+        let mut keys: Vec<&String> = keys_for(&schema.prefix).iter().collect();
+        keys.sort_by_key(|k| std::cmp::Reverse(overlap(&query_terms, k)));
+
+        let mut kept = Vec::new();
+        for key in keys {
+            // ", key" — or the key replacing "(empty)" for the first one.
+            let cost = estimate_tokens(key) + 1;
+            if used + cost > token_budget {
+                break;
+            }
+            used += cost;
+            kept.push(key.clone());
+        }
+        selected_keys.push((schema.prefix.clone(), kept));
+    }
+
+    PromptContext {
+        schemas: selected.iter().map(|s| (*s).clone()).collect(),
+        indexes: selected
+            .iter()
+            .flat_map(|s| indexes_for(&s.prefix))
+            .cloned()
+            .collect(),
+        category_keys: selected_keys,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AttributeInfo;
+
+    fn schema(prefix: &str, description: &str, attrs: &[&str]) -> PartitionSchemaInfo {
+        PartitionSchemaInfo {
+            prefix: prefix.into(),
+            description: description.into(),
+            attributes: attrs
+                .iter()
+                .map(|a| AttributeInfo {
+                    name: (*a).into(),
+                    attr_type: "STRING".into(),
+                    required: false,
+                })
+                .collect(),
+            validate: true,
+        }
+    }
+
+    fn index(category: &str, attr: &str) -> IndexInfo {
+        IndexInfo {
+            name: format!("{category}_{attr}"),
+            partition_schema: category.into(),
+            index_key_name: attr.into(),
+            index_key_type: "STRING".into(),
+        }
+    }
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+
+    #[test]
+    fn test_terms_split_keys_and_drop_stop_words() {
+        let t = terms("What is the doctor-appointment time?");
+        assert!(t.contains("doctor"));
+        assert!(t.contains("appointment"));
+        assert!(!t.contains("the"));
+    }
+
+    #[test]
+    fn test_trim_returns_everything_when_it_fits() {
+        let schemas = vec![schema("contacts", "People", &["email"])];
+        let indexes = vec![index("contacts", "email")];
+        let keys = vec![("contacts".to_string(), vec!["toby".to_string()])];
+        let ctx = trim_to_budget(&schemas, &indexes, &keys, "Toby's email", 10_000);
+        assert_eq!(ctx.schemas.len(), 1);
+        assert_eq!(ctx.indexes.len(), 1);
+        assert_eq!(ctx.category_keys, keys);
+    }
+
+    #[test]
+    fn test_trim_keeps_most_relevant_category() {
+        let schemas = vec![
+            schema("notes", "General-purpose catch-all", &["topic"]),
+            schema("events", "Appointments and deadlines", &["date", "title"]),
+            schema("contacts", "People", &["name", "email"]),
+        ];
+        let indexes = vec![index("notes", "topic"), index("contacts", "email")];
+        let keys = vec![
+            ("notes".to_string(), vec!["misc".to_string()]),
+            ("contacts".to_string(), vec!["toby".to_string()]),
+        ];
+
+        let ctx = trim_to_budget(&schemas, &indexes, &keys, "what is toby's email", 30);
+        assert_eq!(ctx.schemas[0].prefix, "contacts");
+        assert!(ctx.schemas.iter().all(|s| s.prefix != "events"));
+        assert!(
+            ctx.indexes
+                .iter()
+                .all(|i| ctx.schemas.iter().any(|s| s.prefix == i.partition_schema))
+        );
+    }
+
+    #[test]
+    fn test_trim_always_keeps_one_category() {
+        let schemas = vec![schema("contacts", "People", &["name", "email"])];
+        let ctx = trim_to_budget(&schemas, &[], &[], "anything", 1);
+        assert_eq!(ctx.schemas.len(), 1);
+        assert_eq!(ctx.category_keys[0].1, Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_trim_prefers_matching_keys() {
+        let schemas = vec![schema("events", "Events", &["date"])];
+        let keys = vec![(
+            "events".to_string(),
+            vec![
+                "team-standup".to_string(),
+                "quarterly-review".to_string(),
+                "doctor-appointment".to_string(),
+            ],
+        )];
+        let base = estimate_tokens(&format_schema_entry(&schemas[0], &[]));
+        let budget = base + estimate_tokens("doctor-appointment") + 1;
+
+        let ctx = trim_to_budget(&schemas, &[], &keys, "when is my doctor visit", budget);
+        assert_eq!(
+            ctx.category_keys[0].1,
+            vec!["doctor-appointment".to_string()]
+        );
+    }
+}
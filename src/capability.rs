@@ -0,0 +1,122 @@
+//! What the connected ferridyn-server supports.
+//!
+//! Older servers predate partition schemas and secondary indexes, and answer
+//! those requests with errors that say nothing about versions. The protocol
+//! has no version request, so [`probe_capabilities`] does the handshake by
+//! feature instead: at connect time it issues one read-only request per
+//! feature (`list_schemas`, `list_indexes`) and records as unsupported the
+//! ones the server rejects as unknown. Any other failure leaves the feature
+//! marked supported, so a transient error never disables it.
+//!
+//! [`MemoryBackend`](crate::backend::MemoryBackend) consults the result:
+//! listings of an unsupported feature are empty, and other operations on it
+//! fail with [`MemoryError::Unsupported`](crate::error::MemoryError::Unsupported)
+//! and an upgrade message.
+
+use ferridyn_server::FerridynClient;
+use ferridyn_server::error::ClientError;
+use serde::Serialize;
+
+/// Server error codes meaning the request itself is not understood.
+pub const UNSUPPORTED_ERROR_CODES: &[&str] = &[
+    "UnknownRequest",
+    "UnknownCommand",
+    "UnsupportedRequest",
+    "NotImplemented",
+];
+
+/// Features of the server, all supported unless a probe showed otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Capabilities {
+    pub partition_schemas: bool,
+    pub secondary_indexes: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            partition_schemas: true,
+            secondary_indexes: true,
+        }
+    }
+}
+
+impl Capabilities {
+    /// Names of the unsupported features.
+    pub fn missing(&self) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        if !self.partition_schemas {
+            missing.push("partition schemas");
+        }
+        if !self.secondary_indexes {
+            missing.push("secondary indexes");
+        }
+        missing
+    }
+}
+
+/// Whether `err` says the server does not know the request at all.
+pub fn is_unsupported(err: &ClientError) -> bool {
+    match err {
+        ClientError::Server(e) => is_unsupported_code(&e.error),
+        ClientError::Protocol(msg) => msg.contains("unknown variant"),
+        ClientError::Io(_) => false,
+    }
+}
+
+/// Whether the server error code `code` means an unknown request.
+pub fn is_unsupported_code(code: &str) -> bool {
+    UNSUPPORTED_ERROR_CODES.contains(&code)
+}
+
+/// Probe the features of the server behind `client`, against `table`.
+pub async fn probe_capabilities(client: &mut FerridynClient, table: &str) -> Capabilities {
+    let partition_schemas = match client.list_schemas(table).await {
+        Err(e) => !is_unsupported(&e),
+        Ok(_) => true,
+    };
+    let secondary_indexes = match client.list_indexes(table).await {
+        Err(e) => !is_unsupported(&e),
+        Ok(_) => true,
+    };
+    Capabilities {
+        partition_schemas,
+        secondary_indexes,
+    }
+}
+
+/// Error message for an operation needing `feature`, which the server lacks.
+pub fn upgrade_message(feature: &str) -> String {
+    format!(
+        "the connected ferridyn-server does not support {feature}; \
+         upgrade ferridyn-server to a release with {feature} and restart it"
+    )
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_unsupported() {
+        assert!(is_unsupported_code("UnknownRequest"));
+        assert!(!is_unsupported_code("TableNotFound"));
+        assert!(is_unsupported(&ClientError::Protocol(
+            "unknown variant `list_schemas`".into()
+        )));
+        assert!(!is_unsupported(&ClientError::Io(std::io::Error::other(
+            "reset"
+        ))));
+
+        let caps = Capabilities {
+            partition_schemas: false,
+            ..Default::default()
+        };
+        assert_eq!(caps.missing(), ["partition schemas"]);
+        assert!(Capabilities::default().missing().is_empty());
+    }
+}
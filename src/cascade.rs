@@ -0,0 +1,255 @@
+//! Cascading deletes: forget an item together with what refers to it.
+//!
+//! Deleting an item leaves behind the link records filed under it and the
+//! `related` and `conflicts_with` references other items hold to it.
+//! [`cascade_forget`] removes the item, its chunks, every link from or to
+//! it, and those references, so nothing points at the forgotten identity.
+//! [`cascade_forget_many`] does the same for several items of a category,
+//! scanning for references once.
+//! Secondary index entries follow the item on the server. This tree keeps no
+//! history versions, trash, or archive copies of items, so there is nothing
+//! more to remove; backup files written by `fmemory backup create` are left
+//! alone.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::backend::MemoryBackend;
+use crate::chunk::{delete_chunks, is_chunk};
+use crate::conflict::{CONFLICTS_ATTRIBUTE, item_ref};
+use crate::error::MemoryError;
+use crate::links::{LINKS_CATEGORY, normalize_item_ref};
+use crate::related::RELATED_ATTRIBUTE;
+
+/// Maximum number of items scanned per category for references.
+pub const CASCADE_SCAN_LIMIT: usize = 10_000;
+
+/// What a cascading delete removed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CascadeReport {
+    /// `category/key` of the forgotten item.
+    pub forgotten: String,
+    /// Whether the item itself existed.
+    pub found: bool,
+    /// Chunks deleted with it.
+    pub chunks: usize,
+    /// Links from or to it that were removed.
+    pub links: usize,
+    /// Items that referenced it through `related` or `conflicts_with`, and
+    /// no longer do.
+    pub references: Vec<String>,
+}
+
+/// Delete `category/key` with its chunks and links, and drop references to
+/// it from the items of `categories`. Links and references are cleaned up
+/// even if the item itself is already gone.
+pub async fn cascade_forget(
+    backend: &MemoryBackend,
+    category: &str,
+    key: &str,
+    categories: &[String],
+) -> Result<CascadeReport, MemoryError> {
+    let mut reports =
+        cascade_forget_many(backend, category, &[key.to_string()], categories).await?;
+    Ok(reports.remove(0))
+}
+
+/// [`cascade_forget`] for each of `keys` in `category`, scanning the items
+/// of `categories` once for references to any of them. Returns one report
+/// per key, in order.
+pub async fn cascade_forget_many(
+    backend: &MemoryBackend,
+    category: &str,
+    keys: &[String],
+    categories: &[String],
+) -> Result<Vec<CascadeReport>, MemoryError> {
+    let mut reports = Vec::with_capacity(keys.len());
+    for key in keys {
+        let target = normalize_item_ref(&format!("{category}/{key}"))?;
+        let mut report = CascadeReport {
+            forgotten: target.clone(),
+            ..Default::default()
+        };
+
+        if let Some(item) = backend.get_item(category, key).await? {
+            report.found = true;
+            report.chunks = delete_chunks(backend, &item).await?;
+            backend.delete_item(category, key).await?;
+        }
+
+        let links = backend.get_links(&target).await?;
+        for link in &links {
+            for record in link.to_records() {
+                let record_key = record["key"].as_str().unwrap_or_default();
+                backend.delete_item(LINKS_CATEGORY, record_key).await?;
+            }
+        }
+        report.links = links.len();
+        reports.push(report);
+    }
+
+    let targets: Vec<String> = reports.iter().map(|r| r.forgotten.clone()).collect();
+    for scanned in categories {
+        let items = backend.query(scanned, None, CASCADE_SCAN_LIMIT).await?;
+        for mut item in items.into_iter().filter(|item| !is_chunk(item)) {
+            if drop_references(&mut item, &targets).is_empty() {
+                continue;
+            }
+            // Rewrite the stored item under the update lock, so an update
+            // made since the scan is kept.
+            let Some(key) = item["key"].as_str() else {
+                continue;
+            };
+            let _held = backend.lock_updates().await;
+            let Some(mut stored) = backend.get_item(scanned, key).await? else {
+                continue;
+            };
+            let dropped = drop_references(&mut stored, &targets);
+            if dropped.is_empty() {
+                continue;
+            }
+            for index in dropped {
+                reports[index].references.extend(item_ref(&stored));
+            }
+            backend.put_item(stored).await?;
+        }
+    }
+    Ok(reports)
+}
+
+/// Remove each of `targets` from every reference list of `item`. Returns
+/// the indexes of the targets that were removed.
+fn drop_references(item: &mut Value, targets: &[String]) -> Vec<usize> {
+    let mut dropped = Vec::new();
+    for (index, target) in targets.iter().enumerate() {
+        let mut changed = false;
+        for attribute in [RELATED_ATTRIBUTE, CONFLICTS_ATTRIBUTE] {
+            changed |= remove_reference(item, attribute, target);
+        }
+        if changed {
+            dropped.push(index);
+        }
+    }
+    dropped
+}
+
+/// Remove `target` from the reference list `attribute` of `item`, dropping
+/// the attribute once it is empty. Returns whether anything was removed.
+fn remove_reference(item: &mut Value, attribute: &str, target: &str) -> bool {
+    let Some(refs) = item.get_mut(attribute).and_then(Value::as_array_mut) else {
+        return false;
+    };
+    let before = refs.len();
+    refs.retain(|r| r.as_str() != Some(target));
+    if refs.len() == before {
+        return false;
+    }
+    if refs.is_empty()
+        && let Some(obj) = item.as_object_mut()
+    {
+        obj.remove(attribute);
+    }
+    true
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_cascade_forget() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        backend
+            .put_item(json!({"category": "contacts", "key": "toby", "name": "Toby"}))
+            .await
+            .unwrap();
+        backend
+            .put_item(json!({"category": "contacts", "key": "ann", "name": "Ann", "conflicts_with": ["contacts/toby"]}))
+            .await
+            .unwrap();
+        backend
+            .put_item(json!({"category": "decisions", "key": "auth", "related": ["contacts/toby", "contacts/ann"]}))
+            .await
+            .unwrap();
+        backend
+            .link_items("decisions/auth", "contacts/toby", "decided-by")
+            .await
+            .unwrap();
+
+        let categories = vec!["contacts".to_string(), "decisions".to_string()];
+        let report = cascade_forget(&backend, "contacts", "toby", &categories)
+            .await
+            .unwrap();
+        assert!(report.found);
+        assert_eq!(report.forgotten, "contacts/toby");
+        assert_eq!(report.links, 1);
+        assert_eq!(report.references, ["contacts/ann", "decisions/auth"]);
+
+        assert!(
+            backend
+                .get_item("contacts", "toby")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            backend
+                .get_links("decisions/auth")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let ann = backend.get_item("contacts", "ann").await.unwrap().unwrap();
+        assert!(ann.get(CONFLICTS_ATTRIBUTE).is_none());
+        let auth = backend
+            .get_item("decisions", "auth")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(auth[RELATED_ATTRIBUTE], json!(["contacts/ann"]));
+
+        let again = cascade_forget(&backend, "contacts", "toby", &categories)
+            .await
+            .unwrap();
+        assert!(!again.found);
+        assert!(again.references.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cascade_forget_many() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for key in ["a", "b"] {
+            backend
+                .put_item(json!({"category": "notes", "key": key}))
+                .await
+                .unwrap();
+        }
+        backend
+            .put_item(json!({"category": "decisions", "key": "auth", "related": ["notes/a", "notes/b", "notes/c"]}))
+            .await
+            .unwrap();
+
+        let keys = vec!["a".to_string(), "b".to_string()];
+        let categories = vec!["notes".to_string(), "decisions".to_string()];
+        let reports = cascade_forget_many(&backend, "notes", &keys, &categories)
+            .await
+            .unwrap();
+        assert_eq!(reports.len(), 2);
+        assert!(reports.iter().all(|r| r.found));
+        assert_eq!(reports[1].forgotten, "notes/b");
+        assert_eq!(reports[0].references, ["decisions/auth"]);
+        assert_eq!(reports[1].references, ["decisions/auth"]);
+        let auth = backend
+            .get_item("decisions", "auth")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(auth[RELATED_ATTRIBUTE], json!(["notes/c"]));
+    }
+}
@@ -0,0 +1,136 @@
+//! Multi-turn chat with memory in the loop.
+//!
+//! Each message sent to a [`ChatSession`] is classified like `-p` input.
+//! Memories relevant to it are retrieved through [`MemoryStore::resolve_recall`]
+//! and the reply is written from them and the conversation so far
+//! ([`chat_reply`]). Nothing is stored during the chat: facts the user
+//! states are collected ([`ChatSession::facts`]) so `fmemory chat` can offer
+//! them for saving when the chat ends.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::llm::LlmClient;
+use crate::schema::{ChatTurn, NlIntent, chat_reply, classify_intent};
+use crate::store::{MemoryStore, StoreError};
+
+/// Maximum number of memories retrieved per message.
+pub const CHAT_RECALL_LIMIT: usize = 10;
+
+/// The outcome of one chat message.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatReply {
+    pub reply: String,
+    /// Memories retrieved for the message and given to the LLM.
+    pub items: Vec<Value>,
+    /// New fact the message stated, if it was classified as one.
+    pub fact: Option<String>,
+}
+
+/// A conversation over one namespace's memories.
+pub struct ChatSession {
+    store: MemoryStore,
+    llm: Arc<dyn LlmClient>,
+    history: Vec<ChatTurn>,
+    facts: Vec<String>,
+}
+
+impl ChatSession {
+    /// Start a conversation over `store`'s memories, using `llm` for
+    /// classification, retrieval, and replies.
+    pub fn new(store: MemoryStore, llm: Arc<dyn LlmClient>) -> Self {
+        Self {
+            store: store.with_llm(llm.clone()),
+            llm,
+            history: Vec::new(),
+            facts: Vec::new(),
+        }
+    }
+
+    /// Reply to `message`. A namespace without schemas has no memories to
+    /// retrieve, so the reply comes from the conversation alone.
+    pub async fn send(&mut self, message: &str) -> Result<ChatReply, StoreError> {
+        let (query, fact) = match classify_intent(self.llm.as_ref(), message).await? {
+            NlIntent::Remember { content } => (content.clone(), Some(content)),
+            NlIntent::Recall { query } => (query, None),
+        };
+        let resolved = match self.store.resolve_recall(&query, CHAT_RECALL_LIMIT).await {
+            Err(StoreError::NoSchemas) => None,
+            result => Some(result?),
+        };
+        let items = resolved
+            .as_ref()
+            .map(|r| r.items.clone())
+            .unwrap_or_default();
+        let reply = chat_reply(self.llm.as_ref(), &self.history, message, &items).await?;
+        if let Some(resolved) = &resolved {
+            self.store.log_recall(&query, resolved, Some(&reply)).await;
+        }
+
+        if let Some(ref fact) = fact
+            && !self.facts.contains(fact)
+        {
+            self.facts.push(fact.clone());
+        }
+        self.history.push(ChatTurn {
+            user: message.to_string(),
+            assistant: reply.clone(),
+        });
+        Ok(ChatReply { reply, items, fact })
+    }
+
+    /// Turns so far, oldest first.
+    pub fn history(&self) -> &[ChatTurn] {
+        &self.history
+    }
+
+    /// New facts stated so far, in order, without duplicates.
+    pub fn facts(&self) -> &[String] {
+        &self.facts
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryBackend;
+    use crate::llm::MockLlmClient;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_chat_retrieves_memories_and_collects_facts() {
+        let toby = r#"{"type":"exact","category":"contacts","key":"toby"}"#;
+        let mock = MockLlmClient::new(vec![
+            r#"{"intent":"recall","query":"where does Toby work"}"#.into(),
+            toby.into(),
+            "Toby works at Acme.".into(),
+            r#"{"intent":"remember","content":"Toby moved to Berlin"}"#.into(),
+            toby.into(),
+            "Noted, Toby is in Berlin now.".into(),
+        ]);
+        let store = MemoryStore::new(MemoryBackend::in_memory(crate::TABLE_NAME.to_string()));
+        store.init().await.unwrap();
+        store
+            .store_structured("contacts", "toby", &json!({"company": "Acme"}), None)
+            .await
+            .unwrap();
+        let mut session = ChatSession::new(store, Arc::new(mock));
+
+        let first = session.send("Where does Toby work?").await.unwrap();
+        assert_eq!(first.reply, "Toby works at Acme.");
+        assert_eq!(first.items[0]["company"], "Acme");
+        assert_eq!(first.fact, None);
+
+        let second = session.send("Toby moved to Berlin").await.unwrap();
+        assert_eq!(second.fact.as_deref(), Some("Toby moved to Berlin"));
+        assert_eq!(session.facts(), ["Toby moved to Berlin"]);
+        assert_eq!(session.history().len(), 2);
+        assert_eq!(session.history()[0].assistant, "Toby works at Acme.");
+    }
+}
@@ -0,0 +1,471 @@
+//! Automatic chunking of oversized memories.
+//!
+//! Long inputs (design docs, transcripts) are stored as a parent item plus
+//! linked chunk items keyed `{parent}#1`, `{parent}#2`, ... in the same
+//! category. The parent carries a `chunk_count` attribute; each chunk carries
+//! `chunk_of` (the parent key) and `chunk_index` (1-based). On read, chunks are
+//! either reassembled into the parent's `content` or collapsed away in favour
+//! of the parent summary.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::backend::MemoryBackend;
+use crate::error::MemoryError;
+
+/// Inputs longer than this many characters are split into chunks.
+pub const CHUNK_THRESHOLD_CHARS: usize = 4000;
+
+/// Target maximum size of a single chunk, in characters.
+pub const CHUNK_SIZE_CHARS: usize = 3000;
+
+/// Whether the input is large enough to be stored as chunks.
+pub fn needs_chunking(text: &str) -> bool {
+    text.chars().count() > CHUNK_THRESHOLD_CHARS
+}
+
+/// Build the sort key for the `index`-th chunk (1-based) of `parent_key`.
+pub fn chunk_key(parent_key: &str, index: usize) -> String {
+    format!("{parent_key}#{index}")
+}
+
+/// Whether an item is a chunk of a larger parent item.
+pub fn is_chunk(item: &Value) -> bool {
+    item.get("chunk_of").and_then(|v| v.as_str()).is_some()
+}
+
+/// Split text into chunks of at most `max_chars` characters.
+///
+/// Prefers paragraph boundaries, then line breaks, then whitespace; only
+/// splits mid-word when a single word exceeds `max_chars`.
+pub fn split_into_chunks(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = Vec::new();
+    let mut rest = text.trim();
+
+    while !rest.is_empty() {
+        if rest.chars().count() <= max_chars {
+            chunks.push(rest.to_string());
+            break;
+        }
+
+        // Byte offset of the character just past the size limit.
+        let limit = rest
+            .char_indices()
+            .nth(max_chars)
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+        let window = &rest[..limit];
+
+        let split_at = window
+            .rfind("\n\n")
+            .or_else(|| window.rfind('\n'))
+            .or_else(|| window.rfind(char::is_whitespace))
+            .filter(|&i| i > 0)
+            .unwrap_or(limit);
+
+        chunks.push(rest[..split_at].trim_end().to_string());
+        rest = rest[split_at..].trim_start();
+    }
+
+    chunks
+}
+
+/// Split `item`'s `content` into chunks when it is oversized, keeping the
+/// first on the item and setting `chunk_count`. A `chunk_count` left from an
+/// earlier version is dropped otherwise. Returns the chunks to write with
+/// [`store_chunks`] once the item is stored, empty when none are needed.
+pub fn chunk_content(item: &mut Value) -> Vec<String> {
+    let chunks = match item["content"].as_str() {
+        Some(content) if needs_chunking(content) => split_into_chunks(content, CHUNK_SIZE_CHARS),
+        _ => Vec::new(),
+    };
+    if let Some(fields) = item.as_object_mut() {
+        match chunks.first() {
+            Some(first) => {
+                fields.insert("content".into(), Value::String(first.clone()));
+                fields.insert("chunk_count".into(), Value::from(chunks.len()));
+            }
+            None => {
+                fields.remove("chunk_count");
+            }
+        }
+    }
+    chunks
+}
+
+/// Build chunk items for a parent item.
+///
+/// Each chunk inherits `created_at` and `expires_at` from the parent so that
+/// TTL filtering and pruning treat the whole document as a unit.
+pub fn build_chunk_items(parent: &Value, chunks: &[String]) -> Vec<Value> {
+    let category = parent["category"].as_str().unwrap_or_default();
+    let parent_key = parent["key"].as_str().unwrap_or_default();
+
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, text)| {
+            let mut item = serde_json::json!({
+                "category": category,
+                "key": chunk_key(parent_key, i + 1),
+                "content": text,
+                "chunk_of": parent_key,
+                "chunk_index": i + 1,
+            });
+            for attr in ["created_at", "expires_at"] {
+                if let Some(v) = parent.get(attr) {
+                    item[attr] = v.clone();
+                }
+            }
+            item
+        })
+        .collect()
+}
+
+/// Fold chunk items into their parents' `content`, removing the chunk items.
+///
+/// Chunks whose parent is not in `items` are merged into a single item keyed by
+/// the parent key. Item order otherwise follows the input.
+pub fn reassemble_chunks(items: Vec<Value>) -> Vec<Value> {
+    let mut groups: BTreeMap<(String, String), Vec<(u64, String)>> = BTreeMap::new();
+    let mut others = Vec::new();
+
+    for item in items {
+        if is_chunk(&item) {
+            let category = item["category"].as_str().unwrap_or_default().to_string();
+            let parent = item["chunk_of"].as_str().unwrap_or_default().to_string();
+            let index = item["chunk_index"].as_u64().unwrap_or(0);
+            let text = item["content"].as_str().unwrap_or_default().to_string();
+            groups
+                .entry((category, parent))
+                .or_default()
+                .push((index, text));
+        } else {
+            others.push(item);
+        }
+    }
+
+    let join = |mut parts: Vec<(u64, String)>| {
+        parts.sort_by_key(|(i, _)| *i);
+        parts
+            .into_iter()
+            .map(|(_, t)| t)
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    };
+
+    for item in &mut others {
+        let id = (
+            item["category"].as_str().unwrap_or_default().to_string(),
+            item["key"].as_str().unwrap_or_default().to_string(),
+        );
+        if let Some(parts) = groups.remove(&id) {
+            item["content"] = Value::String(join(parts));
+        }
+    }
+
+    for ((category, parent), parts) in groups {
+        others.push(serde_json::json!({
+            "category": category,
+            "key": parent,
+            "content": join(parts),
+        }));
+    }
+
+    others
+}
+
+/// Drop chunk items whose parent is already present, keeping the parent's summary.
+///
+/// Used for answer synthesis, where the full text would crowd the LLM context.
+pub fn collapse_chunks(items: Vec<Value>) -> Vec<Value> {
+    let parents: Vec<(String, String)> = items
+        .iter()
+        .filter(|i| !is_chunk(i))
+        .map(|i| {
+            (
+                i["category"].as_str().unwrap_or_default().to_string(),
+                i["key"].as_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect();
+
+    items
+        .into_iter()
+        .filter(|i| {
+            !is_chunk(i)
+                || !parents.iter().any(|(c, k)| {
+                    i["category"].as_str() == Some(c) && i["chunk_of"].as_str() == Some(k)
+                })
+        })
+        .collect()
+}
+
+/// Write chunk items for a parent that has already been stored, and delete
+/// the chunks a longer earlier version left past the new last one.
+///
+/// Called with no chunks when a parent is rewritten unchunked, so its old
+/// chunks do not linger.
+pub async fn store_chunks(
+    backend: &MemoryBackend,
+    parent: &Value,
+    chunks: &[String],
+) -> Result<(), MemoryError> {
+    for item in build_chunk_items(parent, chunks) {
+        backend.put_item(item).await?;
+    }
+    let (Some(category), Some(key)) = (parent["category"].as_str(), parent["key"].as_str()) else {
+        return Ok(());
+    };
+    // Chunks are numbered without gaps, so the leftovers end at the first
+    // index that is not a chunk of this parent.
+    for index in chunks.len() + 1.. {
+        let stale = chunk_key(key, index);
+        match backend.get_item(category, &stale).await? {
+            Some(item) if item["chunk_of"].as_str() == Some(key) => {
+                backend.delete_item(category, &stale).await?;
+            }
+            _ => break,
+        }
+    }
+    Ok(())
+}
+
+/// Fetch the chunks of a parent item (identified by its `chunk_count`), in
+/// order, by their exact keys.
+async fn fetch_chunks(backend: &MemoryBackend, parent: &Value) -> Result<Vec<Value>, MemoryError> {
+    let (Some(category), Some(key), Some(count)) = (
+        parent["category"].as_str(),
+        parent["key"].as_str(),
+        parent["chunk_count"].as_u64(),
+    ) else {
+        return Ok(Vec::new());
+    };
+
+    let mut chunks = Vec::new();
+    for index in 1..=count as usize {
+        if let Some(chunk) = backend.get_item(category, &chunk_key(key, index)).await?
+            && chunk["chunk_of"].as_str() == Some(key)
+        {
+            chunks.push(chunk);
+        }
+    }
+    Ok(chunks)
+}
+
+/// Load missing chunks for any chunked parents and reassemble their full content.
+pub async fn hydrate_chunks(
+    backend: &MemoryBackend,
+    items: Vec<Value>,
+) -> Result<Vec<Value>, MemoryError> {
+    let mut extra = Vec::new();
+    for item in items.iter().filter(|i| i.get("chunk_count").is_some()) {
+        extra.extend(fetch_chunks(backend, item).await?);
+    }
+
+    let mut all = items;
+    for chunk in extra {
+        let present = all
+            .iter()
+            .any(|i| i["category"] == chunk["category"] && i["key"] == chunk["key"]);
+        if !present {
+            all.push(chunk);
+        }
+    }
+    Ok(reassemble_chunks(all))
+}
+
+/// Delete all chunks belonging to a parent item. Returns the number deleted.
+pub async fn delete_chunks(backend: &MemoryBackend, parent: &Value) -> Result<usize, MemoryError> {
+    let category = parent["category"].as_str().unwrap_or_default();
+    let chunks = fetch_chunks(backend, parent).await?;
+    for chunk in &chunks {
+        if let Some(key) = chunk["key"].as_str() {
+            backend.delete_item(category, key).await?;
+        }
+    }
+    Ok(chunks.len())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_needs_chunking_threshold() {
+        assert!(!needs_chunking("short"));
+        assert!(needs_chunking(&"x".repeat(CHUNK_THRESHOLD_CHARS + 1)));
+    }
+
+    #[test]
+    fn test_split_prefers_paragraphs() {
+        let text = format!("{}\n\n{}", "a".repeat(8), "b".repeat(8));
+        let chunks = split_into_chunks(&text, 12);
+        assert_eq!(chunks, vec!["a".repeat(8), "b".repeat(8)]);
+    }
+
+    #[test]
+    fn test_split_respects_max_and_unicode() {
+        let text = "é".repeat(25);
+        let chunks = split_into_chunks(&text, 10);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks.iter().all(|c| c.chars().count() <= 10));
+        assert_eq!(chunks.concat(), text);
+    }
+
+    #[test]
+    fn test_chunk_content() {
+        let long = "word ".repeat(CHUNK_THRESHOLD_CHARS);
+        let mut item = json!({"category": "notes", "key": "k", "content": long});
+        let chunks = chunk_content(&mut item);
+        assert!(chunks.len() > 1);
+        assert_eq!(item["content"], chunks[0].as_str());
+        assert_eq!(item["chunk_count"], chunks.len());
+
+        let mut item =
+            json!({"category": "notes", "key": "k", "content": "short", "chunk_count": 3});
+        assert!(chunk_content(&mut item).is_empty());
+        assert!(item.get("chunk_count").is_none());
+    }
+
+    #[test]
+    fn test_build_chunk_items_inherits_timestamps() {
+        let parent = json!({
+            "category": "project",
+            "key": "design-doc",
+            "created_at": "2026-01-01T00:00:00Z",
+            "expires_at": "2027-01-01T00:00:00Z",
+        });
+        let items = build_chunk_items(&parent, &["one".into(), "two".into()]);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1]["key"], "design-doc#2");
+        assert_eq!(items[1]["chunk_of"], "design-doc");
+        assert_eq!(items[1]["chunk_index"], 2);
+        assert_eq!(items[0]["expires_at"], "2027-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn test_reassemble_orders_by_index() {
+        let items = vec![
+            json!({"category": "project", "key": "doc", "content": "summary", "chunk_count": 2}),
+            json!({"category": "project", "key": "doc#2", "content": "second", "chunk_of": "doc", "chunk_index": 2}),
+            json!({"category": "project", "key": "doc#1", "content": "first", "chunk_of": "doc", "chunk_index": 1}),
+        ];
+        let merged = reassemble_chunks(items);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0]["content"], "first\n\nsecond");
+    }
+
+    #[test]
+    fn test_reassemble_orphan_chunks() {
+        let items = vec![
+            json!({"category": "project", "key": "doc#1", "content": "first", "chunk_of": "doc", "chunk_index": 1}),
+        ];
+        let merged = reassemble_chunks(items);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0]["key"], "doc");
+    }
+
+    #[test]
+    fn test_collapse_keeps_parent_summary() {
+        let items = vec![
+            json!({"category": "project", "key": "doc", "content": "summary", "chunk_count": 1}),
+            json!({"category": "project", "key": "doc#1", "content": "full", "chunk_of": "doc", "chunk_index": 1}),
+            json!({"category": "project", "key": "other#1", "content": "orphan", "chunk_of": "other", "chunk_index": 1}),
+        ];
+        let collapsed = collapse_chunks(items);
+        assert_eq!(collapsed.len(), 2);
+        assert_eq!(collapsed[0]["content"], "summary");
+        assert_eq!(collapsed[1]["key"], "other#1");
+    }
+
+    #[tokio::test]
+    async fn test_store_hydrate_and_delete_chunks() {
+        use crate::TABLE_NAME;
+        use ferridyn_core::api::FerridynDB;
+        use ferridyn_core::types::KeyType;
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = FerridynDB::create(dir.path().join("test.db")).unwrap();
+        db.create_table(TABLE_NAME)
+            .partition_key("category", KeyType::String)
+            .sort_key("key", KeyType::String)
+            .execute()
+            .unwrap();
+        let backend = MemoryBackend::direct(db, TABLE_NAME.to_string());
+
+        let parent = json!({
+            "category": "project",
+            "key": "design-doc",
+            "content": "summary",
+            "chunk_count": 2,
+        });
+        backend.put_item(parent.clone()).await.unwrap();
+        store_chunks(&backend, &parent, &["part one".into(), "part two".into()])
+            .await
+            .unwrap();
+
+        let hydrated = hydrate_chunks(&backend, vec![parent.clone()])
+            .await
+            .unwrap();
+        assert_eq!(hydrated.len(), 1);
+        assert_eq!(hydrated[0]["content"], "part one\n\npart two");
+
+        assert_eq!(delete_chunks(&backend, &parent).await.unwrap(), 2);
+        let remaining = backend.query("project", None, 10).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_many_chunks_hydrate_in_order_and_restore_shrinks() {
+        use crate::TABLE_NAME;
+
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        let parts: Vec<String> = (1..=12).map(|i| format!("part {i}")).collect();
+        let mut parent = json!({
+            "category": "project",
+            "key": "design-doc",
+            "content": "part 1",
+            "chunk_count": parts.len(),
+        });
+        backend.put_item(parent.clone()).await.unwrap();
+        store_chunks(&backend, &parent, &parts).await.unwrap();
+        // A user item whose key merely looks like a chunk key is left alone.
+        backend
+            .put_item(json!({"category": "project", "key": "design-doc#13", "content": "x"}))
+            .await
+            .unwrap();
+
+        let hydrated = hydrate_chunks(&backend, vec![parent.clone()])
+            .await
+            .unwrap();
+        assert_eq!(hydrated[0]["content"], parts.join("\n\n"));
+
+        parent["chunk_count"] = json!(2);
+        backend.put_item(parent.clone()).await.unwrap();
+        store_chunks(&backend, &parent, &parts[..2]).await.unwrap();
+        let keys: Vec<String> = backend
+            .query("project", None, 100)
+            .await
+            .unwrap()
+            .iter()
+            .map(|i| i["key"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "design-doc",
+                "design-doc#1",
+                "design-doc#13",
+                "design-doc#2"
+            ]
+        );
+    }
+}
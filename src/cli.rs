@@ -1,21 +1,120 @@
+use std::process::ExitCode;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use serde_json::Value;
-use tokio::sync::Mutex;
+use tracing::Instrument;
 
+#[cfg(feature = "llm")]
+use ferridyn_memory::PartitionSchemaInfo;
+use ferridyn_memory::activity::activity_report;
+use ferridyn_memory::agents_md::generate_agents_md;
+use ferridyn_memory::append::append_content;
 use ferridyn_memory::backend::MemoryBackend;
-use ferridyn_memory::llm::{AnthropicClient, LlmClient};
+use ferridyn_memory::backup::{Backup, CategoryDiff, create_backup, diff_backups};
+use ferridyn_memory::cascade::cascade_forget;
+#[cfg(feature = "llm")]
+use ferridyn_memory::chat::ChatSession;
+#[cfg(feature = "llm")]
+use ferridyn_memory::chunk::{CHUNK_SIZE_CHARS, needs_chunking, split_into_chunks, store_chunks};
+use ferridyn_memory::chunk::{delete_chunks, hydrate_chunks};
+#[cfg(feature = "llm")]
+use ferridyn_memory::conflict::flag_conflicts;
+use ferridyn_memory::conflict::{conflicts, item_ref, list_conflicts};
+use ferridyn_memory::context::{DEFAULT_CONTEXT_BUDGET, build_context_pack};
+#[cfg(feature = "llm")]
+use ferridyn_memory::digest::{
+    DIGEST_CATEGORY, build_digest_item, digest_input, digest_key, gather_day, parse_digest_date,
+    prune_entries, undigested,
+};
+#[cfg(feature = "llm")]
+use ferridyn_memory::fixture::{FIXTURE_MODE_ENV, FIXTURES_ENV, FixtureLlmClient, FixtureMode};
+use ferridyn_memory::graph::{build_graph, to_dot, to_graphml};
+use ferridyn_memory::http::{
+    API_TOKEN_ENV, HttpState, api_router, generate_token, ui_router, with_token_auth,
+};
+use ferridyn_memory::import::{csv_to_docs, parse_column_map};
+#[cfg(feature = "llm")]
+use ferridyn_memory::import::{fetch_github_issues, github_issue_to_doc};
+use ferridyn_memory::infer::{infer_schema, parse_examples};
+#[cfg(feature = "llm")]
+use ferridyn_memory::ingest::{
+    fetch_url_text, read_clipboard, read_file_text, read_git_commits, split_facts,
+};
+use ferridyn_memory::issue::{ISSUES_CATEGORY, open_issues, reopen_issue, resolve_issue};
+#[cfg(feature = "llm")]
+use ferridyn_memory::keys::KeyStrategies;
+#[cfg(feature = "llm")]
+use ferridyn_memory::language::LanguageLlmClient;
+use ferridyn_memory::limits::Limits;
+use ferridyn_memory::links::DEFAULT_RELATION;
+#[cfg(feature = "llm")]
+use ferridyn_memory::llm::{LlmClient, client_from_env};
+use ferridyn_memory::locale::DateContext;
+use ferridyn_memory::logging::{self, LOG_ENV, OpLog, Outcome, new_request_id};
+use ferridyn_memory::mcp::{MemoryServer, NamespacePolicy, RateLimit, ToolProfile};
+use ferridyn_memory::migrate::{empty_table, migrate_table};
+use ferridyn_memory::mirror::{MIRROR_SOCKET_ENV, sync_mirror};
+#[cfg(feature = "llm")]
+use ferridyn_memory::notify::webhook_client;
+use ferridyn_memory::notify::{Channel, deliver_due, scan_due};
+use ferridyn_memory::output::{CliError, ErrorCode, success};
+use ferridyn_memory::page::list_keys_page;
+#[cfg(feature = "llm")]
+use ferridyn_memory::quota::Quotas;
+#[cfg(feature = "llm")]
+use ferridyn_memory::related::link_related;
+use ferridyn_memory::related::{
+    DEFAULT_RELATED_HOPS, DEFAULT_RELATED_LIMIT, RelatedBudget, backlinks, hydrate_related_within,
+};
+use ferridyn_memory::review::{mark_reviewed, resurface_candidates, stale_items};
+#[cfg(feature = "llm")]
 use ferridyn_memory::schema::{
-    NlIntent, PREDEFINED_SCHEMAS, ResolvedQuery, SchemaDefinition, SchemaManager, answer_query,
-    classify_intent, parse_to_document, parse_to_document_with_category, resolve_query,
+    FollowUp, NlIntent, classify_intent, extract_commit_memories, parse_to_document,
+    propose_schema, summarize_day, summarize_text, summarize_week,
 };
-use ferridyn_memory::ttl::{
-    INTERACTIONS_DEFAULT_TTL, SCRATCHPAD_DEFAULT_TTL, SESSIONS_DEFAULT_TTL, auto_ttl_from_date,
-    compute_expires_at, filter_expired, is_expired, parse_ttl,
+use ferridyn_memory::schema::{PREDEFINED_SCHEMAS, SchemaDefinition, SchemaManager};
+use ferridyn_memory::score::{
+    IMPORTANCE_ATTRIBUTE, MAX_IMPORTANCE, MIN_IMPORTANCE, SCORE_SCAN_LIMIT, last_active,
+    parse_importance, prune_low_score, record_access, score_distribution, sort_by_score,
 };
+use ferridyn_memory::search::{
+    NAMESPACE_LABEL, search_all_namespaces, search_items, unregister_namespace,
+};
+use ferridyn_memory::seed::{SeedProfile, seed};
+#[cfg(feature = "llm")]
+use ferridyn_memory::session::end_session;
+use ferridyn_memory::session::{
+    FACTS_ATTRIBUTE, SESSION_ACTIVE, SESSION_CATEGORY, append_to_session, start_session,
+};
+use ferridyn_memory::similar::suggest_related;
+use ferridyn_memory::size::{format_bytes, size_stats};
+use ferridyn_memory::sort::{SortField, query_sorted};
+#[cfg(feature = "llm")]
+use ferridyn_memory::store::{CollisionPolicy, put_generated_item};
+use ferridyn_memory::store::{MemoryStore, build_item, connect_backend};
+#[cfg(feature = "llm")]
+use ferridyn_memory::store::{
+    OVERVIEW_KEY, RememberOptions, StoreError, parse_memory, summarize_if_oversized,
+};
+use ferridyn_memory::tenant::TenantMap;
+#[cfg(feature = "llm")]
+use ferridyn_memory::timeout::{TimeoutLlmClient, Timeouts};
+use ferridyn_memory::tree::{key_tree, render_tree};
+#[cfg(feature = "llm")]
+use ferridyn_memory::ttl::take_ttl;
+use ferridyn_memory::ttl::{filter_expired, is_expired, parse_ttl};
+#[cfg(feature = "llm")]
+use ferridyn_memory::usage::TrackedLlmClient;
+use ferridyn_memory::usage::{USAGE_CATEGORY, UsageTracker, load_usage, summarize_usage};
+use ferridyn_memory::validation::WriteValidation;
+use ferridyn_memory::verify::{rebuild_index, verify_indexes};
+#[cfg(feature = "llm")]
+use ferridyn_memory::weekly::{gather_week, headline};
 use ferridyn_memory::{
-    PartitionSchemaInfo, ensure_memories_table_via_server, resolve_socket_path, resolve_table_name,
+    SYSTEM_CATEGORIES, base_table_name, namespace_of, resolve_table_name, validate_namespace,
+    validate_table_identifier,
 };
 
 #[derive(Parser)]
@@ -28,7 +127,8 @@ struct Cli {
     #[arg(long, global = true)]
     json: bool,
 
-    /// Natural language prompt (remember or recall via intent classification)
+    /// Natural language prompt (remember or recall via intent classification;
+    /// `ask` and `note` skip the classification)
     #[arg(short, long)]
     prompt: Option<String>,
 
@@ -36,10 +136,23 @@ struct Cli {
     #[arg(long, global = true)]
     include_expired: bool,
 
+    /// Print recalled items instead of a synthesized answer (also `FMEMORY_RAW=1`)
+    #[arg(long, global = true)]
+    raw: bool,
+
     /// Namespace for memory isolation (table prefix)
     #[arg(long, global = true)]
     namespace: Option<String>,
 
+    /// Emit structured JSON operation logs to `stderr` or a file path
+    #[arg(long, global = true, value_name = "stderr|PATH")]
+    log: Option<String>,
+
+    /// Skip client-side schema validation of writes (`FMEMORY_VALIDATE`);
+    /// the server still enforces validating schemas
+    #[arg(long, global = true)]
+    no_validate: bool,
+
     #[command(subcommand)]
     command: Option<Command>,
 }
@@ -50,8 +163,22 @@ enum Command {
     Discover {
         #[arg(long)]
         category: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: usize,
+        #[arg(
+            long,
+            help = "Maximum results (default 20, or as configured; capped by the configured max)"
+        )]
+        limit: Option<usize>,
+        #[arg(
+            long,
+            requires = "category",
+            help = "Continue a key listing after this key (the previous page's next cursor)"
+        )]
+        cursor: Option<String>,
+    },
+    /// Show a category's `#`-separated keys as a tree
+    Tree {
+        #[arg(long)]
+        category: String,
     },
     /// Retrieve memories
     Recall {
@@ -61,10 +188,78 @@ enum Command {
         key: Option<String>,
         #[arg(long, help = "Natural language query")]
         query: Option<String>,
-        #[arg(long, default_value = "20")]
-        limit: usize,
+        #[arg(
+            long,
+            help = "Maximum results (default 20, or as configured; capped by the configured max)"
+        )]
+        limit: Option<usize>,
+        #[arg(
+            long,
+            help = "Sort by decay score (importance and recency), highest first"
+        )]
+        by_score: bool,
+        #[arg(
+            long,
+            conflicts_with = "by_score",
+            value_name = "FIELD",
+            help = "Sort a category scan by created_at, key, or updated_at"
+        )]
+        sort: Option<SortField>,
+        #[arg(long, requires = "sort", help = "Sort descending")]
+        desc: bool,
+        #[arg(
+            long,
+            help = "Also return the items that results reference through `related`"
+        )]
+        related: bool,
+        #[arg(
+            long,
+            requires = "related",
+            default_value_t = DEFAULT_RELATED_HOPS,
+            help = "With --related, also follow the references of related items, up to N hops"
+        )]
+        hops: usize,
+        #[arg(
+            long,
+            requires = "related",
+            default_value_t = DEFAULT_RELATED_LIMIT,
+            help = "With --related, add at most N related items, nearest first"
+        )]
+        related_limit: usize,
+        #[arg(
+            long,
+            requires = "query",
+            help = "With --query --json, also synthesize an answer and follow-up suggestions"
+        )]
+        answer: bool,
+    },
+    /// Answer a question from memory (recall only, no intent classification)
+    Ask {
+        #[arg(
+            long,
+            help = "Maximum results (default 20, or as configured; capped by the configured max)"
+        )]
+        limit: Option<usize>,
+        /// The question (positional, collects remaining args)
+        question: Vec<String>,
+    },
+    /// Keyword search over item contents (no LLM)
+    Search {
+        #[arg(
+            long,
+            help = "Search the default namespace and every known one, labeling matches by namespace"
+        )]
+        all_namespaces: bool,
+        #[arg(
+            long,
+            help = "Maximum results per namespace (default 20, or as configured; capped by the configured max)"
+        )]
+        limit: Option<usize>,
+        /// Text to search for (positional, collects remaining args)
+        query: Vec<String>,
     },
     /// Store a memory (NL-first)
+    #[cfg(feature = "llm")]
     Remember {
         #[arg(long)]
         category: Option<String>,
@@ -72,29 +267,185 @@ enum Command {
         key: Option<String>,
         #[arg(long, help = "Time-to-live: 24h, 7d, 30d")]
         ttl: Option<String>,
+        #[arg(
+            long,
+            help = "Fetch a web page, summarize it, and store it with its URL as source"
+        )]
+        url: Option<String>,
+        #[arg(
+            long,
+            conflicts_with_all = ["url", "key", "importance", "input"],
+            help = "Remember each line of a file as a fact, or each block between --- lines"
+        )]
+        file: Option<std::path::PathBuf>,
+        #[arg(
+            long,
+            default_value = "suffix",
+            help = "When a generated key holds a different item: replace, suffix, or merge"
+        )]
+        on_collision: CollisionPolicy,
+        #[arg(
+            long,
+            value_parser = parse_importance,
+            help = "Importance from 1 to 5, overriding the LLM estimate"
+        )]
+        importance: Option<u8>,
         /// Natural language input (positional, collects remaining args)
         input: Vec<String>,
     },
+    /// Capture the system clipboard as a memory (scratchpad by default)
+    #[cfg(feature = "llm")]
+    Clip {
+        #[arg(long, default_value = "scratchpad")]
+        category: String,
+        #[arg(long)]
+        key: Option<String>,
+        #[arg(long, help = "Time-to-live: 24h, 7d, 30d")]
+        ttl: Option<String>,
+        #[arg(
+            long,
+            default_value = "suffix",
+            help = "When a generated key holds a different item: replace, suffix, or merge"
+        )]
+        on_collision: CollisionPolicy,
+    },
+    /// Store a note without intent classification (scratchpad by default)
+    #[cfg(feature = "llm")]
+    Note {
+        #[arg(long, default_value = "scratchpad")]
+        category: String,
+        #[arg(long, help = "Time-to-live: 24h, 7d, 30d")]
+        ttl: Option<String>,
+        /// The note (positional, collects remaining args)
+        text: Vec<String>,
+    },
+    /// Ingest a text, markdown, or PDF file as a memory
+    #[cfg(feature = "llm")]
+    IngestFile {
+        /// Path to the file
+        path: std::path::PathBuf,
+        #[arg(long)]
+        category: Option<String>,
+        #[arg(long)]
+        key: Option<String>,
+        #[arg(long, help = "Time-to-live: 24h, 7d, 30d")]
+        ttl: Option<String>,
+        #[arg(
+            long,
+            default_value = "suffix",
+            help = "When a generated key holds a different item: replace, suffix, or merge"
+        )]
+        on_collision: CollisionPolicy,
+    },
+    /// Extract decisions and project facts from this repo's git history
+    #[cfg(feature = "llm")]
+    IngestGit {
+        #[arg(
+            long,
+            help = "Only read commits after this revision (e.g. a tag or SHA)"
+        )]
+        since: Option<String>,
+        #[arg(long, default_value = "50")]
+        limit: usize,
+    },
+    /// Print the most relevant memories as a compact block within a token budget
+    Context {
+        #[arg(long, default_value_t = DEFAULT_CONTEXT_BUDGET, help = "Token budget of the block")]
+        budget: usize,
+        #[arg(long, help = "Prefer memories about this topic")]
+        topic: Option<String>,
+    },
+    /// Generate files from stored memories
+    Generate {
+        #[command(subcommand)]
+        target: GenerateTarget,
+    },
+    /// Export the memory graph: items and their links and related references
+    Export {
+        #[arg(long, value_enum, default_value = "dot")]
+        format: ExportFormat,
+        #[arg(long, help = "Only items of this category (default: all categories)")]
+        category: Option<String>,
+        #[arg(long, help = "Write to this file instead of stdout")]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Bulk-import records from an external source
+    Import {
+        /// File to import (format inferred from the extension)
+        path: Option<std::path::PathBuf>,
+        #[arg(long, value_enum)]
+        format: Option<ImportFormat>,
+        #[cfg(feature = "llm")]
+        #[arg(long, help = "GitHub repository (owner/name) for --format github")]
+        repo: Option<String>,
+        #[arg(long, help = "Target category for file imports")]
+        category: Option<String>,
+        #[arg(long, help = "Column-to-attribute mapping: \"Name=name,Email=email\"")]
+        map: Option<String>,
+        #[cfg(feature = "llm")]
+        #[arg(
+            long,
+            default_value = "500",
+            help = "Maximum issues for --format github"
+        )]
+        limit: usize,
+    },
     /// Remove a specific memory
     Forget {
         #[arg(long)]
         category: String,
         #[arg(long)]
         key: String,
+        #[arg(
+            long,
+            help = "Also remove its links and the related/conflicts_with references to it"
+        )]
+        cascade: bool,
     },
     /// Define a category schema with typed attributes
     Define {
-        #[arg(long)]
-        category: String,
-        #[arg(long)]
-        description: String,
+        #[arg(long, required_unless_present = "infer")]
+        category: Option<String>,
+        #[arg(long, required_unless_present = "infer")]
+        description: Option<String>,
         #[arg(
             long,
-            help = "JSON array of attributes: [{\"name\":\"...\",\"type\":\"STRING\",\"required\":true}]"
+            required_unless_present_any = ["from_examples", "infer"],
+            conflicts_with_all = ["from_examples", "infer"],
+            help = "JSON array of attributes: [{\"name\":\"...\",\"type\":\"STRING\",\"required\":true,\"aliases\":[\"...\"]}]"
+        )]
+        attributes: Option<String>,
+        #[arg(
+            long,
+            value_name = "PATH",
+            conflicts_with = "infer",
+            help = "Infer attributes and indexes from sample JSON documents (array or one per line)"
+        )]
+        from_examples: Option<std::path::PathBuf>,
+        #[arg(
+            long,
+            value_name = "DESCRIPTION",
+            help = "Have the LLM propose the schema from a description of what to track"
+        )]
+        infer: Option<String>,
+        #[arg(
+            long,
+            short = 'y',
+            requires = "infer",
+            help = "Create the proposed schema without asking"
+        )]
+        yes: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["from_examples", "infer"],
+            help = "Auto-create indexes for suggested attributes"
         )]
-        attributes: String,
-        #[arg(long, help = "Auto-create indexes for suggested attributes")]
         auto_index: bool,
+        #[arg(
+            long,
+            help = "Extra instructions for parsing memories into this category"
+        )]
+        hints: Option<String>,
     },
     /// Show schema/index info
     Schema {
@@ -106,6 +457,25 @@ enum Command {
         #[arg(long, help = "Recreate schemas even if they already exist")]
         force: bool,
     },
+    /// Copy a memory table (items, schemas, indexes) to another table name
+    MigrateTable {
+        #[arg(long, help = "Source table, e.g. memories_old")]
+        from: String,
+        #[arg(long, help = "Target table, created if missing, e.g. memories_new")]
+        to: String,
+        #[arg(
+            long,
+            help = "After a verified copy, delete the source's items, indexes, and schemas"
+        )]
+        drop_source: bool,
+    },
+    /// Load a curated set of example memories for demos and tests
+    Seed {
+        #[arg(long, value_enum, default_value = "demo")]
+        profile: SeedSet,
+        #[arg(long, help = "Overwrite existing items with the same keys")]
+        force: bool,
+    },
     /// Promote a memory: remove TTL (STM to LTM), optionally re-categorize
     Promote {
         #[arg(long, help = "Source category")]
@@ -115,30 +485,581 @@ enum Command {
         #[arg(long, help = "Target category (re-categorize during promotion)")]
         to: Option<String>,
     },
+    /// Append text to a memory's content as a timestamped entry
+    Append {
+        #[arg(long)]
+        category: String,
+        #[arg(long)]
+        key: String,
+        /// The text to append (positional, collects remaining args)
+        text: Vec<String>,
+    },
+    /// Merge attributes into a memory, leaving the others as they are
+    Update {
+        #[arg(long)]
+        category: String,
+        #[arg(long)]
+        key: String,
+        /// attribute=value pairs; values are JSON literals or text, null removes
+        #[arg(required = true)]
+        assignments: Vec<String>,
+    },
+    /// Set how important a memory is (1-5)
+    Importance {
+        #[arg(long)]
+        category: String,
+        #[arg(long)]
+        key: String,
+        /// Importance from 1 (trivia) to 5 (critical)
+        #[arg(value_parser = parse_importance)]
+        importance: u8,
+    },
+    /// List the items that reference a memory
+    Backlinks {
+        #[arg(long)]
+        category: String,
+        #[arg(long)]
+        key: String,
+    },
+    /// Suggest memories related to an item, best first
+    Related {
+        #[arg(long)]
+        category: String,
+        #[arg(long)]
+        key: String,
+        #[arg(
+            long,
+            help = "Maximum suggestions (default 10, or as configured; capped by the configured max)"
+        )]
+        limit: Option<usize>,
+    },
+    /// Link two memories, remove a link, or list an item's links
+    Link {
+        /// Source item as category/key
+        source: String,
+        /// Target item as category/key; omit to list the source's links
+        target: Option<String>,
+        #[arg(long, help = "Relation type, e.g. decided-by (default: related)")]
+        relation: Option<String>,
+        #[arg(
+            long,
+            requires = "target",
+            help = "Remove the link (all relations unless --relation)"
+        )]
+        remove: bool,
+    },
     /// Delete all expired memories
     Prune {
         #[arg(long, help = "Only prune this category")]
         category: Option<String>,
+        #[arg(
+            long,
+            value_name = "SCORE",
+            help = "Also delete short-term items whose decay score (0-1) is below SCORE"
+        )]
+        below: Option<f64>,
+    },
+    /// Copy everything the mirror is missing (FERRIDYN_MEMORY_MIRROR_SOCKET)
+    MirrorSync,
+    /// Start a work session, record facts on it, and summarize it at the end
+    Session {
+        #[command(subcommand)]
+        action: SessionAction,
+    },
+    /// Resolve, reopen, or list open issues
+    Issue {
+        #[command(subcommand)]
+        action: IssueAction,
+    },
+    /// Check secondary indexes against the items they cover
+    VerifyIndexes {
+        #[arg(long, help = "Only check indexes on this category")]
+        category: Option<String>,
+        #[arg(long, help = "Drop and recreate indexes found inconsistent")]
+        repair: bool,
+    },
+    /// Write backup archives of all items, or compare two of them
+    Backup {
+        #[command(subcommand)]
+        action: BackupAction,
+    },
+    /// Review long-term memories that have gone unread: keep, update, or forget
+    Resurface {
+        #[arg(long, help = "Only this category")]
+        category: Option<String>,
+        #[arg(long, default_value = "5", help = "Maximum items to review")]
+        limit: usize,
+        #[arg(
+            long,
+            default_value = "30d",
+            help = "Idle time before an item is first due; doubles after each keep"
+        )]
+        after: String,
+    },
+    /// Summarize a day's scratchpad, sessions, and interactions into a note
+    #[cfg(feature = "llm")]
+    Digest {
+        #[arg(
+            long,
+            default_value = "today",
+            help = "Day to digest: today, yesterday, or YYYY-MM-DD"
+        )]
+        date: String,
+        #[arg(long, help = "Delete the digested entries afterward")]
+        prune: bool,
+    },
+    /// Condense a category's items into a single {category}/_overview item
+    #[cfg(feature = "llm")]
+    Summarize {
+        #[arg(long)]
+        category: String,
+    },
+    /// Report the last 7 days of decisions, issues, and new contacts
+    #[cfg(feature = "llm")]
+    ReviewWeek,
+    /// Chat with memory in the loop; offers new facts for saving at the end
+    #[cfg(feature = "llm")]
+    Chat {
+        #[arg(long, help = "Save every new fact at the end without asking")]
+        save: bool,
+    },
+    /// List long-term memories untouched for a long time, by category
+    Stale {
+        #[arg(long, help = "Only this category")]
+        category: Option<String>,
+        #[arg(
+            long,
+            default_value = "180d",
+            help = "Minimum time since the item was stored, updated, or read"
+        )]
+        age: String,
+    },
+    /// List memories flagged as contradicting each other
+    Conflicts {
+        #[arg(long, help = "Only this category")]
+        category: Option<String>,
+    },
+    /// Show item counts and importance/decay score distributions
+    Stats {
+        #[arg(long, help = "Only this category")]
+        category: Option<String>,
+        #[arg(
+            long,
+            help = "Report serialized size per category and the largest items"
+        )]
+        size: bool,
+        #[arg(
+            long,
+            conflicts_with = "size",
+            help = "Report how often each category is read and written"
+        )]
+        activity: bool,
+    },
+    /// Summarize LLM token usage and estimated spend by feature
+    Cost {
+        #[arg(long, default_value = "30d", help = "Time window: 24h, 7d, 30d")]
+        since: String,
+    },
+    /// Watch for upcoming events and expiring items, firing webhooks
+    #[cfg(feature = "llm")]
+    Watch {
+        #[arg(long = "webhook", help = "URL to POST notifications to (repeatable)")]
+        webhooks: Vec<String>,
+        #[arg(
+            long,
+            default_value = "24h",
+            help = "Notify about events starting within this window"
+        )]
+        events_within: String,
+        #[arg(
+            long,
+            default_value = "1h",
+            help = "Notify about items expiring within this window"
+        )]
+        expiring_within: String,
+        #[arg(long, default_value = "5m", help = "Time between scans: 5m, 1h")]
+        interval: String,
+        #[arg(long, help = "Scan once and exit")]
+        once: bool,
+    },
+    /// Raise desktop notifications ahead of upcoming events
+    Remind {
+        #[arg(
+            long,
+            default_value = "30m",
+            help = "How far ahead of an event to notify"
+        )]
+        ahead: String,
+        #[arg(long, default_value = "1m", help = "Time between checks: 1m, 5m")]
+        interval: String,
+        #[arg(long, help = "Check once and exit")]
+        once: bool,
     },
-    /// Start MCP server on stdio transport
+    /// Start MCP server on stdio transport, or over HTTP with --listen
     Serve {
         #[arg(long, help = "Namespace for this server instance")]
         namespace: Option<String>,
+        #[arg(long, help = "Reject tool calls that select another namespace")]
+        lock_namespace: bool,
+        #[arg(long, help = "Give each MCP client a namespace named after it")]
+        client_namespaces: bool,
+        #[arg(
+            long,
+            value_name = "NS",
+            help = "Namespace every client may read but not write"
+        )]
+        shared_namespace: Option<String>,
+        #[arg(long, help = "Hide tools that modify memory")]
+        read_only: bool,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "full",
+            help = "Which tools to expose"
+        )]
+        tools: ServeTools,
+        #[arg(long, value_name = "N", help = "Maximum tool calls per minute")]
+        rate_limit: Option<u32>,
+        #[arg(
+            long,
+            value_name = "ADDR",
+            help = "Serve streamable HTTP at http://ADDR/mcp instead of stdio"
+        )]
+        listen: Option<std::net::SocketAddr>,
+        #[arg(
+            long,
+            value_name = "FILE",
+            requires = "listen",
+            help = "File of 'tenant token' lines: confine each bearer token to its tenant's namespace"
+        )]
+        tenants: Option<std::path::PathBuf>,
+        #[cfg(feature = "llm")]
+        #[arg(
+            long,
+            help = "Expose the LLM tools (memory_remember, memory_recall, ...) when an LLM provider is configured"
+        )]
+        nl_tools: bool,
+    },
+    /// Serve the web dashboard for browsing and editing memories
+    ServeUi {
+        #[arg(long, default_value = "127.0.0.1:7777")]
+        listen: std::net::SocketAddr,
+        #[arg(
+            long,
+            help = "Token the dashboard must present (or FMEMORY_API_TOKEN; generated if unset)"
+        )]
+        token: Option<String>,
+    },
+    /// Serve the JSON HTTP API (bearer-token auth)
+    ServeApi {
+        #[arg(long, default_value = "127.0.0.1:7778")]
+        listen: std::net::SocketAddr,
+        #[arg(
+            long,
+            help = "Bearer token clients must present (or FMEMORY_API_TOKEN)"
+        )]
+        token: Option<String>,
     },
 }
 
-// ============================================================================
-// Output Formatting
-// ============================================================================
-
-/// Format a single item for prose output.
-/// Displays key (category) header then attributes with capitalized names.
-fn format_item(item: &Value) {
-    let key = item["key"].as_str().unwrap_or("?");
-    let category = item["category"].as_str().unwrap_or("?");
-    println!("{key} ({category})");
+/// Subcommands of `fmemory session`.
+#[derive(Subcommand)]
+enum SessionAction {
+    /// Create an active sessions item
+    Start {
+        #[arg(long, help = "Session key (default session-YYYYMMDD-HHMMSS)")]
+        key: Option<String>,
+        #[arg(long, help = "Project the session works on")]
+        project: Option<String>,
+        #[arg(long, help = "Branch the session works on")]
+        branch: Option<String>,
+        /// Goal of the session (positional, collects remaining args)
+        goal: Vec<String>,
+    },
+    /// Record a notable fact on the active session
+    Append {
+        #[arg(long, help = "Session key (default: the latest active session)")]
+        key: Option<String>,
+        /// The fact (positional, collects remaining args)
+        #[arg(required = true)]
+        fact: Vec<String>,
+    },
+    /// Summarize the active session and mark it ended
+    #[cfg(feature = "llm")]
+    End {
+        #[arg(long, help = "Session key (default: the latest active session)")]
+        key: Option<String>,
+    },
+}
 
-    if let Some(obj) = item.as_object() {
+/// Subcommands of `fmemory issue`.
+#[derive(Subcommand)]
+enum IssueAction {
+    /// Mark an issue resolved
+    Resolve {
+        #[arg(long, help = "Key of the issue")]
+        key: String,
+        #[arg(long, help = "How it was fixed (stored as `fix`)")]
+        fix: Option<String>,
+    },
+    /// Mark a resolved issue open again
+    Reopen {
+        #[arg(long, help = "Key of the issue")]
+        key: String,
+    },
+    /// List unresolved issues, newest first
+    Open {
+        #[arg(long, default_value_t = 50, help = "Maximum issues listed")]
+        limit: usize,
+    },
+}
+
+/// Subcommands of `fmemory backup`.
+#[derive(Subcommand)]
+enum BackupAction {
+    /// Write every item of the table to a JSON archive
+    Create {
+        /// Archive file to write
+        path: std::path::PathBuf,
+    },
+    /// Report items added, removed, or changed from one archive to another
+    Diff {
+        /// The earlier archive
+        before: std::path::PathBuf,
+        /// The later archive
+        after: std::path::PathBuf,
+    },
+}
+
+/// Subcommands of `fmemory generate`.
+#[derive(Subcommand)]
+enum GenerateTarget {
+    /// Agent instructions (AGENTS.md, CLAUDE.md) from preferences, project
+    /// knowledge, decisions, and tools
+    AgentsMd {
+        #[arg(
+            long,
+            help = "File to create or update in place, keeping text outside the generated sections (default: print to stdout)"
+        )]
+        out: Option<std::path::PathBuf>,
+    },
+}
+
+/// Source formats accepted by `fmemory import`.
+#[derive(Clone, Copy, ValueEnum)]
+enum ImportFormat {
+    /// GitHub issues, mapped onto the `issues` category
+    #[cfg(feature = "llm")]
+    Github,
+    /// CSV with a header row
+    Csv,
+}
+
+/// Graph formats written by `fmemory export`.
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    /// Graphviz DOT
+    Dot,
+    /// GraphML, e.g. for Gephi
+    Graphml,
+}
+
+/// Example sets accepted by `fmemory seed --profile`.
+#[derive(Clone, Copy, ValueEnum)]
+enum SeedSet {
+    /// A small web team's project across every predefined category
+    Demo,
+}
+
+impl From<SeedSet> for SeedProfile {
+    fn from(set: SeedSet) -> Self {
+        match set {
+            SeedSet::Demo => SeedProfile::Demo,
+        }
+    }
+}
+
+/// Tool sets accepted by `fmemory serve --tools`.
+#[derive(Clone, Copy, ValueEnum)]
+enum ServeTools {
+    /// Every tool
+    Full,
+    /// Store, get, query, delete, and list only
+    Core,
+}
+
+impl From<ServeTools> for ToolProfile {
+    fn from(tools: ServeTools) -> Self {
+        match tools {
+            ServeTools::Full => ToolProfile::Full,
+            ServeTools::Core => ToolProfile::Core,
+        }
+    }
+}
+
+impl Command {
+    /// Servers and watchers, which run until interrupted.
+    fn is_long_running(&self) -> bool {
+        match self {
+            Command::Serve { .. }
+            | Command::ServeUi { .. }
+            | Command::ServeApi { .. }
+            | Command::Remind { .. } => true,
+            #[cfg(feature = "llm")]
+            Command::Watch { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the command scans the namespace for upkeep or reporting,
+    /// rather than using it, so its reads and writes are not counted as
+    /// category activity.
+    fn is_maintenance(&self) -> bool {
+        matches!(
+            self,
+            Command::Stats { .. }
+                | Command::Prune { .. }
+                | Command::Backup { .. }
+                | Command::Export { .. }
+                | Command::VerifyIndexes { .. }
+                | Command::MigrateTable { .. }
+                | Command::MirrorSync
+        )
+    }
+
+    /// Operation name and target used for structured operation logs.
+    fn op_log(&self, namespace: Option<&str>) -> OpLog {
+        let (operation, category, key) = match self {
+            Command::Discover { category, .. } => ("discover", category.as_deref(), None),
+            Command::Tree { category } => ("tree", Some(category.as_str()), None),
+            Command::Recall { category, key, .. } => {
+                ("recall", category.as_deref(), key.as_deref())
+            }
+            #[cfg(feature = "llm")]
+            Command::Remember { category, key, .. } => {
+                ("remember", category.as_deref(), key.as_deref())
+            }
+            Command::Ask { .. } => ("ask", None, None),
+            Command::Search { .. } => ("search", None, None),
+            #[cfg(feature = "llm")]
+            Command::Clip { category, key, .. } => {
+                ("clip", Some(category.as_str()), key.as_deref())
+            }
+            #[cfg(feature = "llm")]
+            Command::Note { category, .. } => ("note", Some(category.as_str()), None),
+            #[cfg(feature = "llm")]
+            Command::IngestFile { category, key, .. } => {
+                ("ingest_file", category.as_deref(), key.as_deref())
+            }
+            #[cfg(feature = "llm")]
+            Command::IngestGit { .. } => ("ingest_git", None, None),
+            Command::Context { .. } => ("context", None, None),
+            Command::Generate { target } => match target {
+                GenerateTarget::AgentsMd { .. } => ("generate_agents_md", None, None),
+            },
+            Command::Export { category, .. } => ("export", category.as_deref(), None),
+            Command::Import { category, .. } => ("import", category.as_deref(), None),
+            Command::Forget { category, key, .. } => {
+                ("forget", Some(category.as_str()), Some(key.as_str()))
+            }
+            Command::Define { category, .. } => ("define", category.as_deref(), None),
+            Command::Schema { category } => ("schema", category.as_deref(), None),
+            Command::Init { .. } => ("init", None, None),
+            Command::Seed { .. } => ("seed", None, None),
+            Command::MigrateTable { .. } => ("migrate_table", None, None),
+            Command::Promote { category, key, .. } => {
+                ("promote", Some(category.as_str()), Some(key.as_str()))
+            }
+            Command::Append { category, key, .. } => {
+                ("append", Some(category.as_str()), Some(key.as_str()))
+            }
+            Command::Update { category, key, .. } => {
+                ("update", Some(category.as_str()), Some(key.as_str()))
+            }
+            Command::Importance { category, key, .. } => {
+                ("importance", Some(category.as_str()), Some(key.as_str()))
+            }
+            Command::Backlinks { category, key } => {
+                ("backlinks", Some(category.as_str()), Some(key.as_str()))
+            }
+            Command::Related { category, key, .. } => {
+                ("related", Some(category.as_str()), Some(key.as_str()))
+            }
+            Command::Link { source, .. } => {
+                let (category, key) = source.split_once('/').unzip();
+                ("link", category, key)
+            }
+            Command::Prune { category, .. } => ("prune", category.as_deref(), None),
+            Command::MirrorSync => ("mirror_sync", None, None),
+            Command::Issue { action } => match action {
+                IssueAction::Resolve { key, .. } => {
+                    ("issue_resolve", Some(ISSUES_CATEGORY), Some(key.as_str()))
+                }
+                IssueAction::Reopen { key } => {
+                    ("issue_reopen", Some(ISSUES_CATEGORY), Some(key.as_str()))
+                }
+                IssueAction::Open { .. } => ("issue_open", Some(ISSUES_CATEGORY), None),
+            },
+            Command::Session { action } => match action {
+                SessionAction::Start { key, .. } => {
+                    ("session_start", Some(SESSION_CATEGORY), key.as_deref())
+                }
+                SessionAction::Append { key, .. } => {
+                    ("session_append", Some(SESSION_CATEGORY), key.as_deref())
+                }
+                #[cfg(feature = "llm")]
+                SessionAction::End { key } => {
+                    ("session_end", Some(SESSION_CATEGORY), key.as_deref())
+                }
+            },
+            Command::VerifyIndexes { category, .. } => {
+                ("verify_indexes", category.as_deref(), None)
+            }
+            Command::Backup { action } => match action {
+                BackupAction::Create { .. } => ("backup_create", None, None),
+                BackupAction::Diff { .. } => ("backup_diff", None, None),
+            },
+            Command::Resurface { category, .. } => ("resurface", category.as_deref(), None),
+            #[cfg(feature = "llm")]
+            Command::Digest { .. } => ("digest", Some(DIGEST_CATEGORY), None),
+            #[cfg(feature = "llm")]
+            Command::Summarize { category } => {
+                ("summarize", Some(category.as_str()), Some(OVERVIEW_KEY))
+            }
+            #[cfg(feature = "llm")]
+            Command::ReviewWeek => ("review_week", None, None),
+            #[cfg(feature = "llm")]
+            Command::Chat { .. } => ("chat", None, None),
+            Command::Stale { category, .. } => ("stale", category.as_deref(), None),
+            Command::Conflicts { category } => ("conflicts", category.as_deref(), None),
+            Command::Stats { category, .. } => ("stats", category.as_deref(), None),
+            Command::Cost { .. } => ("cost", Some(USAGE_CATEGORY), None),
+            #[cfg(feature = "llm")]
+            Command::Watch { .. } => ("watch", None, None),
+            Command::Remind { .. } => ("remind", Some("events"), None),
+            Command::Serve { .. } => ("serve", None, None),
+            Command::ServeUi { .. } => ("serve_ui", None, None),
+            Command::ServeApi { .. } => ("serve_api", None, None),
+        };
+        OpLog::start(operation, namespace)
+            .category(category)
+            .key(key)
+    }
+}
+
+// ============================================================================
+// Output Formatting
+// ============================================================================
+
+/// Format a single item for prose output.
+/// Displays key (category) header then attributes with capitalized names.
+fn format_item(item: &Value) {
+    let item = &DateContext::from_env().localize_item(item);
+    let key = item["key"].as_str().unwrap_or("?");
+    let category = item["category"].as_str().unwrap_or("?");
+    println!("{key} ({category})");
+
+    if let Some(obj) = item.as_object() {
         for (attr_name, attr_value) in obj {
             if attr_name == "category" || attr_name == "key" {
                 continue;
@@ -166,6 +1087,27 @@ fn format_items(items: &[Value]) {
     }
 }
 
+/// Format cross-namespace search matches under one header per namespace.
+fn print_by_namespace(items: Vec<Value>) {
+    let mut current: Option<Option<String>> = None;
+    for mut item in items {
+        let namespace = item
+            .as_object_mut()
+            .and_then(|obj| obj.remove(NAMESPACE_LABEL))
+            .and_then(|ns| ns.as_str().map(String::from));
+        if current.as_ref() != Some(&namespace) {
+            if current.is_some() {
+                println!();
+            }
+            println!("== {} ==", namespace.as_deref().unwrap_or("(default)"));
+            current = Some(namespace);
+        } else {
+            println!();
+        }
+        format_item(&item);
+    }
+}
+
 /// Capitalize the first letter of a string.
 fn capitalize_first(s: &str) -> String {
     let mut chars = s.chars();
@@ -179,33 +1121,162 @@ fn capitalize_first(s: &str) -> String {
 // Main
 // ============================================================================
 
+/// Set once a `--json` envelope has been printed, so a command that printed
+/// none still gets one.
+static JSON_PRINTED: AtomicBool = AtomicBool::new(false);
+
+/// Print the `--json` success envelope around `data`.
+fn print_json(data: &impl serde::Serialize) -> Result<(), serde_json::Error> {
+    println!("{}", serde_json::to_string_pretty(&success(data)?)?);
+    JSON_PRINTED.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
+async fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // Help and version requests, or argument errors without --json.
+        Err(e) if !e.use_stderr() || !std::env::args().any(|arg| arg == "--json") => e.exit(),
+        Err(e) => {
+            let rendered = e.render().to_string();
+            let error = CliError::usage(rendered.trim().trim_start_matches("error: "));
+            println!("{:#}", error.envelope());
+            return ExitCode::from(ErrorCode::Usage.exit_code());
+        }
+    };
+    let json = cli.json;
+    let request_id = new_request_id();
+    match logging::with_request_id(request_id.clone(), run(cli)).await {
+        Ok(()) => {
+            if json && !JSON_PRINTED.load(Ordering::Relaxed) {
+                println!(
+                    "{:#}",
+                    serde_json::json!({"ok": true, "data": null, "error": null})
+                );
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            let error = CliError::from_error(e.as_ref()).with_request_id(&request_id);
+            if json {
+                println!("{:#}", error.envelope());
+            } else {
+                eprintln!("Error: {error} (request {request_id})");
+            }
+            ExitCode::from(error.code.exit_code())
+        }
+    }
+}
 
+/// Run the parsed command. Errors are reported by `main`.
+async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
     // Resolve namespace: --namespace flag > FMEMORY_NAMESPACE env var > default.
     let namespace = cli
         .namespace
         .clone()
         .or_else(|| std::env::var("FMEMORY_NAMESPACE").ok());
+    base_table_name().map_err(CliError::usage)?;
+    if let Some(ns) = &namespace {
+        validate_namespace(ns).map_err(CliError::usage)?;
+    }
     let table_name = resolve_table_name(namespace.as_deref());
 
-    let backend = connect_backend(&table_name).await?;
+    // Structured operation logging: --log flag > FMEMORY_LOG env var > off.
+    if let Some(target) = cli.log.clone().or_else(|| std::env::var(LOG_ENV).ok()) {
+        logging::init(&target)
+            .map_err(|e| CliError::context(&format!("Failed to open log {target}"), &e))?;
+    }
+    let mut op_log = match cli.command {
+        Some(ref command) => command.op_log(namespace.as_deref()),
+        None => OpLog::start("prompt", namespace.as_deref()),
+    };
+
+    // Root span correlating backend and LLM spans for this invocation. `main`
+    // drives a single task, so the guard stays valid across awaits. Servers
+    // and watchers are skipped: each MCP tool call, HTTP request, or scan
+    // opens its own span.
+    let long_running = cli.command.as_ref().is_some_and(Command::is_long_running);
+    let _request_span = (!long_running).then(|| {
+        tracing::info_span!(
+            "cli.command",
+            operation = op_log.operation(),
+            request_id = %logging::request_id()
+        )
+        .entered()
+    });
+
+    // Comparing archives needs no server.
+    if let Some(Command::Backup {
+        action: BackupAction::Diff { before, after },
+    }) = &cli.command
+    {
+        let diff = diff_backups(&read_backup(before)?, &read_backup(after)?);
+        if cli.json {
+            print_json(&diff)?;
+        } else {
+            print_backup_diff(&diff);
+        }
+        op_log.set_outcome(Outcome::Ok);
+        return Ok(());
+    }
+
+    let mut backend = connect_backend(&table_name).await?;
+    if cli.no_validate {
+        backend = backend.with_write_validation(WriteValidation::off());
+    }
+    if cli.command.as_ref().is_some_and(Command::is_maintenance) {
+        backend = backend.with_activity_counting(false);
+    }
     let schema_manager = SchemaManager::new(backend.clone());
+    let usage = Arc::new(UsageTracker::default());
+
+    // Usage, activity, and mirrored writes are flushed even when the command
+    // fails, so an error exit keeps the LLM spend it incurred.
+    let result = run_command(
+        cli,
+        namespace,
+        backend.clone(),
+        schema_manager,
+        usage.clone(),
+        &mut op_log,
+    )
+    .await;
+    if let Err(e) = usage.persist(&backend, op_log.operation()).await {
+        tracing::warn!("failed to record LLM usage: {e}");
+    }
+    if let Err(e) = backend.flush_activity().await {
+        tracing::warn!("failed to persist category activity: {e}");
+    }
+    backend.flush_mirror().await;
+    result?;
+    op_log.set_outcome(Outcome::Ok);
+    Ok(())
+}
+
+/// Run the parsed command against `backend`; `run` flushes what it recorded.
+async fn run_command(
+    cli: Cli,
+    namespace: Option<String>,
+    backend: MemoryBackend,
+    schema_manager: SchemaManager,
+    usage: Arc<UsageTracker>,
+    op_log: &mut OpLog,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let limits = Limits::load();
 
     match cli.command {
-        Some(Command::Discover { category, limit }) => {
+        Some(Command::Discover {
+            category,
+            limit,
+            cursor,
+        }) => {
+            let limit = limits.resolve("discover", limit);
             if let Some(ref cat) = category {
-                // Show keys in category, attributes, and indexes.
-                let items = backend
-                    .query(cat, None, limit)
-                    .await
-                    .map_err(|e| e.to_string())?;
-                let items = if cli.include_expired {
-                    items
-                } else {
-                    filter_expired(items)
-                };
+                // Show a page of keys in category, attributes, and indexes.
+                let page =
+                    list_keys_page(&backend, cat, cursor.as_deref(), limit, cli.include_expired)
+                        .await?;
                 let schema = schema_manager.get_schema(cat).await.ok().flatten();
                 let indexes = schema_manager.list_indexes().await.unwrap_or_default();
                 let cat_indexes: Vec<_> = indexes
@@ -214,13 +1285,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     .collect();
 
                 if cli.json {
-                    let keys: Vec<&str> = items
-                        .iter()
-                        .filter_map(|item| item["key"].as_str())
-                        .collect();
                     let output = serde_json::json!({
                         "category": cat,
-                        "keys": keys,
+                        "keys": page.items,
+                        "next_cursor": page.next_cursor,
                         "schema": schema.as_ref().map(|s| serde_json::json!({
                             "description": s.description,
                             "attributes": s.attributes.iter().map(|a| serde_json::json!({
@@ -235,21 +1303,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             "type": idx.index_key_type,
                         })).collect::<Vec<_>>(),
                     });
-                    println!("{}", serde_json::to_string_pretty(&output)?);
+                    print_json(&output)?;
                 } else {
                     // Keys
-                    let keys: Vec<&str> = items
-                        .iter()
-                        .filter_map(|item| item["key"].as_str())
-                        .collect();
-                    if keys.is_empty() {
+                    if page.items.is_empty() {
                         eprintln!("No keys found in category '{cat}'.");
                     } else {
                         println!("Keys in {cat}:");
-                        for key in &keys {
+                        for key in &page.items {
                             println!("  - {key}");
                         }
                     }
+                    if let Some(ref next) = page.next_cursor {
+                        eprintln!("More keys: discover --category {cat} --cursor {next}");
+                    }
 
                     // Schema
                     if let Some(ref s) = schema {
@@ -294,7 +1361,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             })
                         })
                         .collect();
-                    println!("{}", serde_json::to_string_pretty(&enriched)?);
+                    print_json(&enriched)?;
                 } else if schemas.is_empty() {
                     eprintln!("No categories found.");
                 } else {
@@ -314,40 +1381,110 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
         }
+        Some(Command::Tree { category }) => {
+            let tree = key_tree(&backend, &category, cli.include_expired).await?;
+            let count: usize = tree.iter().map(|node| node.count).sum();
+
+            if cli.json {
+                let output = serde_json::json!({
+                    "category": category,
+                    "count": count,
+                    "tree": tree,
+                });
+                print_json(&output)?;
+            } else if tree.is_empty() {
+                eprintln!("No keys found in category '{category}'.");
+            } else {
+                print!("{}", render_tree(&tree));
+                eprintln!("{count} item(s) in {category}");
+            }
+        }
         Some(Command::Recall {
             category,
             key,
             query,
             limit,
+            by_score,
+            sort,
+            desc,
+            related,
+            hops,
+            related_limit,
+            answer,
         }) => {
+            let limit = limits.resolve("recall", limit);
+            let budget = RelatedBudget {
+                hops,
+                max_items: related_limit.min(limits.max_for("recall")),
+            };
             if let Some(ref cat) = category {
                 if let Some(ref k) = key {
                     // Exact item by category + key.
-                    let item = backend.get_item(cat, k).await.map_err(|e| e.to_string())?;
+                    let item = backend.get_item(cat, k).await?;
                     // Filter expired items unless --include-expired.
                     let item = item.filter(|i| cli.include_expired || !is_expired(i));
+                    if let Some(ref i) = item {
+                        record_access(&backend, std::slice::from_ref(i)).await;
+                    }
+                    let item = match item {
+                        Some(i) => hydrate_chunks(&backend, vec![i]).await?.into_iter().next(),
+                        None => None,
+                    };
                     if let Some(item) = item {
-                        if cli.json {
-                            println!("{}", serde_json::to_string_pretty(&item)?);
+                        let linked = if related {
+                            hydrate_related_within(
+                                &backend,
+                                std::slice::from_ref(&item),
+                                budget,
+                                cli.include_expired,
+                            )
+                            .await?
+                        } else {
+                            Vec::new()
+                        };
+                        if cli.json && related {
+                            print_json(&serde_json::json!({"item": item, "related": linked}))?;
+                        } else if cli.json {
+                            print_json(&item)?;
                         } else {
                             format_item(&item);
+                            if !linked.is_empty() {
+                                println!("\nRelated:");
+                                format_items(&linked);
+                            }
                         }
                     } else {
-                        eprintln!("No memory found for {cat}/{k}");
+                        op_log.set_outcome(Outcome::NotFound);
+                        return Err(
+                            CliError::not_found(format!("No memory found for {cat}/{k}")).into(),
+                        );
                     }
                 } else {
-                    // Scan category.
-                    let items = backend
-                        .query(cat, None, limit)
-                        .await
-                        .map_err(|e| e.to_string())?;
-                    let items = if cli.include_expired {
-                        items
-                    } else {
-                        filter_expired(items)
-                    };
+                    // Scan category. Ranking by score needs the whole category.
+                    let scan_limit = if by_score { SCORE_SCAN_LIMIT } else { limit };
+                    let items = query_sorted(
+                        &backend,
+                        cat,
+                        None,
+                        scan_limit,
+                        sort.unwrap_or_default(),
+                        desc,
+                        cli.include_expired,
+                    )
+                    .await?;
+                    let mut items = hydrate_chunks(&backend, items).await?;
+                    if by_score {
+                        sort_by_score(&mut items, chrono::Utc::now());
+                        items.truncate(limit);
+                    }
+                    if related {
+                        let linked =
+                            hydrate_related_within(&backend, &items, budget, cli.include_expired)
+                                .await?;
+                        items.extend(linked);
+                    }
                     if cli.json {
-                        println!("{}", serde_json::to_string_pretty(&items)?);
+                        print_json(&items)?;
                     } else if items.is_empty() {
                         eprintln!("No memories found in category '{cat}'.");
                     } else {
@@ -355,224 +1492,826 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 }
             } else if let Some(ref q) = query {
-                // NL query resolution.
-                let llm = require_llm()?;
-                let schemas = schema_manager
-                    .list_schemas()
-                    .await
-                    .map_err(|e| e.to_string())?;
-                if schemas.is_empty() {
-                    eprintln!(
-                        "No schemas defined. Use --category instead, or define schemas first."
-                    );
-                    std::process::exit(1);
-                }
-                let indexes = schema_manager.list_indexes().await.unwrap_or_default();
-
-                let category_keys = fetch_category_keys(&backend, &schemas).await;
-                let resolved = resolve_query(llm.as_ref(), &schemas, &indexes, &category_keys, q)
-                    .await
-                    .map_err(|e| format!("Query resolution failed: {e}"))?;
-
-                let (items, _) = execute_with_fallback(&backend, &resolved, limit).await?;
-                let items = if cli.include_expired {
-                    items
-                } else {
-                    filter_expired(items)
-                };
-
-                if cli.json {
-                    println!("{}", serde_json::to_string_pretty(&items)?);
-                } else if items.is_empty() {
-                    eprintln!("No memories found.");
-                } else {
-                    match answer_query(llm.as_ref(), q, &items).await {
-                        Ok(Some(answer)) => println!("{answer}"),
-                        Ok(None) => eprintln!("No relevant memories found."),
-                        Err(_) => {
-                            // LLM synthesis failed — fall back to raw items.
-                            format_items(&items);
-                        }
-                    }
+                let mut store =
+                    MemoryStore::new(backend.clone()).with_include_expired(cli.include_expired);
+                if related {
+                    store = store.with_related_budget(budget);
                 }
+                let format = RecallFormat::new(cli.json, cli.raw);
+                recall_query(store, &usage, q, limit, format, by_score, answer).await?;
+            } else {
+                return Err(CliError::usage("Either --category or --query is required.").into());
+            }
+        }
+        Some(Command::Ask { limit, question }) => {
+            let query = question.join(" ");
+            if query.trim().is_empty() {
+                return Err(CliError::usage("No question provided.").into());
+            }
+            auto_init(&backend, &schema_manager).await?;
+            let store = MemoryStore::new(backend.clone()).with_include_expired(cli.include_expired);
+            let format = RecallFormat::new(cli.json, cli.raw);
+            let limit = limits.resolve("ask", limit);
+            recall_query(store, &usage, &query, limit, format, false, false).await?;
+        }
+        Some(Command::Search {
+            all_namespaces,
+            limit,
+            query,
+        }) => {
+            let query = query.join(" ");
+            if query.trim().is_empty() {
+                return Err(CliError::usage("No search text provided.").into());
+            }
+            let limit = limits.resolve("search", limit);
+            let items = if all_namespaces {
+                search_all_namespaces(&backend, &query, limit, cli.include_expired).await
+            } else {
+                search_items(&backend, &query, limit, cli.include_expired).await
+            }
+            .map_err(|e| CliError::context("Search failed", &e))?;
+            if cli.json {
+                print_json(&items)?;
+            } else if items.is_empty() {
+                eprintln!("No memories match '{query}'.");
+            } else if all_namespaces {
+                print_by_namespace(items);
             } else {
-                eprintln!("Either --category or --query is required.");
-                std::process::exit(1);
+                format_items(&items);
             }
         }
+        #[cfg(feature = "llm")]
         Some(Command::Remember {
             category,
             key,
             ttl,
+            url,
+            file,
+            on_collision,
+            importance,
             input,
         }) => {
-            let input_text = input.join(" ");
-            if input_text.is_empty() {
+            if let Some(path) = file {
+                let text = std::fs::read_to_string(&path).map_err(|e| {
+                    CliError::context(&format!("Failed to read {}", path.display()), &e)
+                })?;
+                let facts = split_facts(&text);
+                if facts.is_empty() {
+                    eprintln!("No facts found in {}", path.display());
+                    op_log.set_outcome(Outcome::Ok);
+                    return Ok(());
+                }
+
+                auto_init(&backend, &schema_manager).await?;
+                let llm = require_llm(&usage)?;
+                let store = MemoryStore::new(backend.clone())
+                    .with_llm(llm)
+                    .with_collision_policy(on_collision);
+                let options = RememberOptions {
+                    category,
+                    ttl: ttl
+                        .as_deref()
+                        .map(parse_ttl)
+                        .transpose()
+                        .map_err(CliError::invalid_input)?,
+                    ..Default::default()
+                };
+                let results = store.remember_batch(&facts, options).await?;
+
+                let mut stored = Vec::new();
+                let mut per_category = std::collections::BTreeMap::new();
+                for (fact, result) in facts.iter().zip(results) {
+                    match result {
+                        Ok(item) => {
+                            if !cli.json {
+                                report_stored(
+                                    &item,
+                                    item["chunk_count"].as_u64().unwrap_or(0) as usize,
+                                );
+                            }
+                            let category = item["category"].as_str().unwrap_or("?");
+                            *per_category.entry(category.to_string()).or_insert(0) += 1;
+                            stored.push(item);
+                        }
+                        Err(e) => {
+                            let preview: String = fact.chars().take(60).collect();
+                            eprintln!("Failed to remember \"{preview}\": {e}");
+                        }
+                    }
+                }
+                let breakdown: Vec<String> = per_category
+                    .iter()
+                    .map(|(category, count)| format!("{category}: {count}"))
+                    .collect();
                 eprintln!(
-                    "Error: No input provided. Provide text to remember as positional arguments."
+                    "Stored {} of {} facts ({})",
+                    stored.len(),
+                    facts.len(),
+                    breakdown.join(", ")
                 );
-                std::process::exit(1);
+                if stored.len() < facts.len() {
+                    let message = format!("Stored {} of {} facts", stored.len(), facts.len());
+                    return Err(CliError::new(ErrorCode::PartialFailure, message)
+                        .with_data(serde_json::to_value(&stored)?)
+                        .into());
+                }
+                if cli.json {
+                    print_json(&stored)?;
+                }
+                op_log.set_outcome(Outcome::Ok);
+                return Ok(());
+            }
+
+            let input_text = input.join(" ");
+            if input_text.is_empty() && url.is_none() {
+                return Err(CliError::usage(
+                    "No input provided. Provide text to remember as positional arguments, \
+                     a page with --url, or a facts file with --file.",
+                )
+                .into());
             }
 
             // Auto-init: ensure predefined schemas exist on first use.
             auto_init(&backend, &schema_manager).await?;
 
-            let llm = require_llm()?;
+            let llm = require_llm(&usage)?;
 
-            let (category, final_key, final_doc) = if let Some(cat) = category {
-                // Category provided: validate it has a schema.
-                if !schema_manager.has_schema(&cat).await.unwrap_or(false) {
-                    let available: Vec<&str> = PREDEFINED_SCHEMAS.iter().map(|s| s.name).collect();
-                    return Err(format!(
-                        "Unknown category '{cat}'. Available: {}. \
-                         Use `fmemory define` to create custom categories.",
-                        available.join(", ")
-                    )
-                    .into());
-                }
-                let schema_info = schema_manager
-                    .get_schema(&cat)
+            // URL ingestion: summarize the page; any positional text is kept as a note.
+            let (parse_input, chunks) = if let Some(ref url) = url {
+                let page = fetch_url_text(url)
                     .await
-                    .map_err(|e| e.to_string())?
-                    .ok_or_else(|| format!("Schema for '{cat}' not found"))?;
-
-                let doc = parse_to_document(llm.as_ref(), &cat, &schema_info, &input_text)
+                    .map_err(|e| CliError::context(&format!("Failed to fetch {url}"), &e))?;
+                let page_text = page.full_text();
+                let summary = summarize_text(llm.as_ref(), &page_text)
                     .await
-                    .map_err(|e| format!("Document parsing failed: {e}"))?;
-                let parsed_key = doc["key"].as_str().unwrap_or("unknown").to_string();
-                let used_key = key.unwrap_or(parsed_key);
-                (cat, used_key, doc)
+                    .map_err(|e| CliError::context("Summarization failed", &e))?;
+                let parse_input = if input_text.is_empty() {
+                    summary
+                } else {
+                    format!("{summary}\n\nNote: {input_text}")
+                };
+                let chunks = if needs_chunking(&page_text) {
+                    split_into_chunks(&page_text, CHUNK_SIZE_CHARS)
+                } else {
+                    Vec::new()
+                };
+                (parse_input, chunks)
             } else {
-                // No category: let LLM pick from available schemas.
-                let schemas = schema_manager.list_schemas().await.unwrap_or_default();
-                let doc = parse_to_document_with_category(llm.as_ref(), &schemas, &input_text)
+                summarize_if_oversized(llm.as_ref(), &input_text)
                     .await
-                    .map_err(|e| format!("Document parsing failed: {e}"))?;
-                let chosen_cat = doc["category"].as_str().unwrap_or("notes").to_string();
-                let parsed_key = doc["key"].as_str().unwrap_or("unknown").to_string();
-                let used_key = key.unwrap_or(parsed_key);
-                (chosen_cat, used_key, doc)
+                    .map_err(|e| CliError::context("Summarization failed", &e))?
             };
+            let input_text = parse_input;
 
-            // Build final document with category, key, and created_at.
-            let mut final_item = serde_json::json!({
-                "category": category,
-                "key": final_key,
-            });
-            if let Some(obj) = final_doc.as_object() {
-                for (k, v) in obj {
-                    if k == "key" || k == "category" {
-                        continue;
-                    }
-                    final_item[k] = v.clone();
-                }
-            }
-            // Auto-inject created_at timestamp.
-            final_item["created_at"] = Value::String(chrono::Utc::now().to_rfc3339());
-
-            // Auto-inject expires_at based on --ttl flag or category defaults.
-            if let Some(ref ttl_str) = ttl {
-                let duration = parse_ttl(ttl_str).map_err(|e| e.to_string())?;
-                final_item["expires_at"] = Value::String(compute_expires_at(duration));
-            } else if category == "scratchpad" {
-                final_item["expires_at"] =
-                    Value::String(compute_expires_at(SCRATCHPAD_DEFAULT_TTL));
-            } else if category == "sessions" {
-                final_item["expires_at"] = Value::String(compute_expires_at(SESSIONS_DEFAULT_TTL));
-            } else if category == "interactions" {
-                final_item["expires_at"] =
-                    Value::String(compute_expires_at(INTERACTIONS_DEFAULT_TTL));
-            } else if category == "events"
-                && let Some(expires) = auto_ttl_from_date(&final_item)
-            {
-                final_item["expires_at"] = Value::String(expires);
-            }
-
-            backend
-                .put_item(final_item.clone())
-                .await
-                .map_err(|e| e.to_string())?;
+            let generated_key = key.is_none();
+            let (category, final_key, mut final_doc) = parse_memory(
+                llm.as_ref(),
+                &schema_manager.snapshot().await?,
+                &KeyStrategies::from_env(),
+                category,
+                key,
+                &input_text,
+            )
+            .await?;
+            let doc_ttl = take_ttl(&mut final_doc);
+            let mut final_item = build_item(
+                &category,
+                &final_key,
+                &final_doc,
+                ttl.as_deref()
+                    .map(parse_ttl)
+                    .transpose()
+                    .map_err(CliError::invalid_input)?
+                    .or(doc_ttl),
+            );
 
-            // Prose output: list non-null attribute names.
-            let attr_names: Vec<&str> = final_item
-                .as_object()
-                .map(|obj| {
-                    obj.iter()
-                        .filter(|(k, v)| {
-                            *k != "category"
-                                && *k != "key"
-                                && *k != "created_at"
-                                && *k != "expires_at"
-                                && !v.is_null()
-                        })
-                        .map(|(k, _)| k.as_str())
-                        .collect()
-                })
-                .unwrap_or_default();
+            if !chunks.is_empty() {
+                final_item["content"] = Value::String(input_text.clone());
+                final_item["chunk_count"] = Value::from(chunks.len());
+            }
+            if let Some(url) = url {
+                final_item["source"] = Value::String(url);
+            }
+            if let Some(importance) = importance {
+                final_item[IMPORTANCE_ATTRIBUTE] = Value::from(importance);
+            }
+            if generated_key {
+                final_item = put_generated_item(&backend, final_item, on_collision).await?;
+            } else {
+                backend.put_item(final_item.clone()).await?;
+            }
+            store_chunks(&backend, &final_item, &chunks).await?;
+            let evicted = Quotas::from_env()
+                .enforce_after_write(&backend, &final_item)
+                .await?;
+            link_related(&backend, &final_item).await?;
+            let conflicts = flag_conflicts(&backend, llm.as_ref(), &mut final_item).await;
 
-            if attr_names.is_empty() {
-                eprintln!("Stored {category}/{final_key}");
+            if cli.json {
+                print_json(&final_item)?;
             } else {
-                eprintln!("Stored {category}/{final_key} ({})", attr_names.join(", "));
+                report_stored(&final_item, chunks.len());
             }
+            report_evicted(&final_item, &evicted);
+            report_conflicts(&conflicts);
         }
-        Some(Command::Forget { category, key }) => {
-            backend
-                .delete_item(&category, &key)
-                .await
-                .map_err(|e| e.to_string())?;
-            eprintln!("Forgot: {category}/{key}");
-        }
-        Some(Command::Define {
+        #[cfg(feature = "llm")]
+        Some(Command::Clip {
             category,
-            description,
-            attributes,
-            auto_index,
+            key,
+            ttl,
+            on_collision,
         }) => {
-            let attr_defs: Vec<ferridyn_memory::schema::AttributeDef> =
-                serde_json::from_str(&attributes)
-                    .map_err(|e| format!("Invalid attributes JSON: {e}"))?;
+            let text =
+                read_clipboard().map_err(|e| CliError::context("Failed to read clipboard", &e))?;
 
-            let suggested_indexes = if auto_index {
-                attr_defs.iter().map(|a| a.name.clone()).collect()
-            } else {
-                vec![]
-            };
-
-            let definition = SchemaDefinition {
-                description,
-                attributes: attr_defs,
-                suggested_indexes,
-            };
+            auto_init(&backend, &schema_manager).await?;
+            let llm = require_llm(&usage)?;
 
-            schema_manager
-                .create_schema_with_indexes(&category, &definition, true)
+            let (parse_input, chunks) = summarize_if_oversized(llm.as_ref(), &text)
                 .await
-                .map_err(|e| e.to_string())?;
-            eprintln!("Schema defined for '{category}'");
-        }
+                .map_err(|e| CliError::context("Summarization failed", &e))?;
+            let generated_key = key.is_none();
+            let (category, final_key, mut final_doc) = parse_memory(
+                llm.as_ref(),
+                &schema_manager.snapshot().await?,
+                &KeyStrategies::from_env(),
+                Some(category),
+                key,
+                &parse_input,
+            )
+            .await?;
+            let doc_ttl = take_ttl(&mut final_doc);
+            let mut final_item = build_item(
+                &category,
+                &final_key,
+                &final_doc,
+                ttl.as_deref()
+                    .map(parse_ttl)
+                    .transpose()
+                    .map_err(CliError::invalid_input)?
+                    .or(doc_ttl),
+            );
+            if !chunks.is_empty() {
+                final_item["content"] = Value::String(parse_input);
+                final_item["chunk_count"] = Value::from(chunks.len());
+            }
+            if final_item.get("source").is_none_or(Value::is_null) {
+                final_item["source"] = Value::String("clipboard".to_string());
+            }
+            if generated_key {
+                final_item = put_generated_item(&backend, final_item, on_collision).await?;
+            } else {
+                backend.put_item(final_item.clone()).await?;
+            }
+            store_chunks(&backend, &final_item, &chunks).await?;
+            let evicted = Quotas::from_env()
+                .enforce_after_write(&backend, &final_item)
+                .await?;
+            link_related(&backend, &final_item).await?;
+            let conflicts = flag_conflicts(&backend, llm.as_ref(), &mut final_item).await;
+
+            if cli.json {
+                print_json(&final_item)?;
+            } else {
+                report_stored(&final_item, chunks.len());
+            }
+            report_evicted(&final_item, &evicted);
+            report_conflicts(&conflicts);
+        }
+        #[cfg(feature = "llm")]
+        Some(Command::Note {
+            category,
+            ttl,
+            text,
+        }) => {
+            let text = text.join(" ");
+            if text.trim().is_empty() {
+                return Err(CliError::usage("No note provided.").into());
+            }
+            auto_init(&backend, &schema_manager).await?;
+            let llm = require_llm(&usage)?;
+            let options = RememberOptions {
+                category: Some(category),
+                ttl: ttl
+                    .as_deref()
+                    .map(parse_ttl)
+                    .transpose()
+                    .map_err(CliError::invalid_input)?,
+                ..Default::default()
+            };
+            let item = MemoryStore::new(backend.clone())
+                .with_llm(llm)
+                .remember_with(options, &text)
+                .await?;
+            if cli.json {
+                print_json(&item)?;
+            } else {
+                report_stored(&item, item["chunk_count"].as_u64().unwrap_or(0) as usize);
+            }
+        }
+        #[cfg(feature = "llm")]
+        Some(Command::IngestFile {
+            path,
+            category,
+            key,
+            ttl,
+            on_collision,
+        }) => {
+            let file = read_file_text(&path).map_err(|e| {
+                CliError::context(&format!("Failed to read {}", path.display()), &e)
+            })?;
+
+            // With an explicit target, skip re-ingesting an unchanged file.
+            if let (Some(cat), Some(k)) = (&category, &key)
+                && let Some(existing) = backend.get_item(cat, k).await?
+                && existing["source_sha256"].as_str() == Some(file.sha256.as_str())
+            {
+                eprintln!("Unchanged: {cat}/{k} already matches {}", file.path);
+                op_log.set_outcome(Outcome::Ok);
+                return Ok(());
+            }
+
+            auto_init(&backend, &schema_manager).await?;
+            let llm = require_llm(&usage)?;
+
+            let (parse_input, chunks) = summarize_if_oversized(llm.as_ref(), &file.text)
+                .await
+                .map_err(|e| CliError::context("Summarization failed", &e))?;
+
+            let generated_key = key.is_none();
+            let (category, final_key, mut final_doc) = parse_memory(
+                llm.as_ref(),
+                &schema_manager.snapshot().await?,
+                &KeyStrategies::from_env(),
+                category,
+                key,
+                &parse_input,
+            )
+            .await?;
+            let doc_ttl = take_ttl(&mut final_doc);
+            let mut final_item = build_item(
+                &category,
+                &final_key,
+                &final_doc,
+                ttl.as_deref()
+                    .map(parse_ttl)
+                    .transpose()
+                    .map_err(CliError::invalid_input)?
+                    .or(doc_ttl),
+            );
+            if !chunks.is_empty() {
+                final_item["content"] = Value::String(parse_input);
+                final_item["chunk_count"] = Value::from(chunks.len());
+            }
+            final_item["source"] = Value::String(file.path.clone());
+            final_item["source_sha256"] = Value::String(file.sha256.clone());
+            if generated_key {
+                final_item = put_generated_item(&backend, final_item, on_collision).await?;
+            } else {
+                // Re-ingesting over an existing item: drop its old chunks first.
+                if let Some(existing) = backend.get_item(&category, &final_key).await? {
+                    delete_chunks(&backend, &existing).await?;
+                }
+                backend.put_item(final_item.clone()).await?;
+            }
+            store_chunks(&backend, &final_item, &chunks).await?;
+            let evicted = Quotas::from_env()
+                .enforce_after_write(&backend, &final_item)
+                .await?;
+            link_related(&backend, &final_item).await?;
+            let conflicts = flag_conflicts(&backend, llm.as_ref(), &mut final_item).await;
+
+            if cli.json {
+                print_json(&final_item)?;
+            } else {
+                report_stored(&final_item, chunks.len());
+            }
+            report_evicted(&final_item, &evicted);
+            report_conflicts(&conflicts);
+        }
+        #[cfg(feature = "llm")]
+        Some(Command::IngestGit { since, limit }) => {
+            let cwd = std::env::current_dir()?;
+            let commits = read_git_commits(&cwd, since.as_deref(), limit)
+                .map_err(|e| CliError::context("Failed to read git history", &e))?;
+            if commits.is_empty() {
+                eprintln!("No commits to ingest");
+                op_log.set_outcome(Outcome::Ok);
+                return Ok(());
+            }
+
+            auto_init(&backend, &schema_manager).await?;
+            let llm = require_llm(&usage)?;
+            let schemas: Vec<PartitionSchemaInfo> = schema_manager
+                .list_schemas()
+                .await?
+                .into_iter()
+                .filter(|s| GIT_INGEST_CATEGORIES.contains(&s.prefix.as_str()))
+                .collect();
+
+            let mut stored = Vec::new();
+            for batch in commits.chunks(GIT_INGEST_BATCH_SIZE) {
+                let docs = extract_commit_memories(llm.as_ref(), &schemas, batch)
+                    .await
+                    .map_err(|e| CliError::context("LLM extraction failed", &e))?;
+                for doc in docs {
+                    let (Some(category), Some(key), Some(sha)) = (
+                        doc["category"].as_str(),
+                        doc["key"].as_str(),
+                        doc["commit"].as_str(),
+                    ) else {
+                        continue;
+                    };
+                    let mut item = build_item(category, key, &doc, None);
+                    if let Some(obj) = item.as_object_mut() {
+                        obj.remove("commit");
+                    }
+                    item["source"] = Value::String(format!("git:{sha}"));
+                    item["commit_sha"] = Value::String(sha.to_string());
+                    backend.put_item(item.clone()).await?;
+                    stored.push(item);
+                }
+            }
+
+            if cli.json {
+                print_json(&stored)?;
+            } else {
+                for item in &stored {
+                    report_stored(item, 0);
+                }
+                eprintln!(
+                    "Ingested {} memories from {} commits",
+                    stored.len(),
+                    commits.len()
+                );
+            }
+        }
+        Some(Command::Import {
+            path,
+            format,
+            #[cfg(feature = "llm")]
+            repo,
+            category,
+            map,
+            #[cfg(feature = "llm")]
+            limit,
+        }) => {
+            let format = format.or_else(|| {
+                let ext = path.as_ref()?.extension()?.to_str()?.to_ascii_lowercase();
+                (ext == "csv").then_some(ImportFormat::Csv)
+            });
+            auto_init(&backend, &schema_manager).await?;
+
+            let docs = match format {
+                #[cfg(feature = "llm")]
+                Some(ImportFormat::Github) => {
+                    let repo = repo.ok_or_else(|| {
+                        CliError::usage("--repo owner/name is required for --format github")
+                    })?;
+                    let issues = fetch_github_issues(&repo, limit).await.map_err(|e| {
+                        CliError::context(&format!("Failed to fetch issues from {repo}"), &e)
+                    })?;
+                    issues
+                        .iter()
+                        .map(|issue| github_issue_to_doc(&repo, issue))
+                        .collect::<Vec<_>>()
+                }
+                Some(ImportFormat::Csv) => {
+                    let path =
+                        path.ok_or_else(|| CliError::usage("A CSV file path is required"))?;
+                    let category = category
+                        .ok_or_else(|| CliError::usage("--category is required for CSV import"))?;
+                    let schema = schema_manager.get_schema(&category).await?.ok_or_else(|| {
+                        format!(
+                            "Unknown category '{category}'. \
+                                 Use `fmemory define` to create custom categories."
+                        )
+                    })?;
+                    let map = map
+                        .as_deref()
+                        .map(parse_column_map)
+                        .transpose()
+                        .map_err(|e| CliError::invalid_input(e.to_string()))?;
+                    let file = std::fs::File::open(&path).map_err(|e| {
+                        CliError::context(&format!("Failed to open {}", path.display()), &e)
+                    })?;
+                    csv_to_docs(file, &schema, map.as_deref()).map_err(|e| {
+                        CliError::context(&format!("Failed to import {}", path.display()), &e)
+                    })?
+                }
+                None => return Err(CliError::usage("--format is required (github, csv)").into()),
+            };
+
+            let mut imported = Vec::with_capacity(docs.len());
+            for doc in docs {
+                let (Some(category), Some(key)) = (doc["category"].as_str(), doc["key"].as_str())
+                else {
+                    continue;
+                };
+                // Re-importing updates what the source provides and keeps
+                // the rest, such as a `fix` recorded since.
+                let mut patch = doc.as_object().cloned().unwrap_or_default();
+                patch.remove("category");
+                patch.remove("key");
+                let item = match backend.update_item(category, key, &patch).await? {
+                    Some(item) => item,
+                    None => {
+                        let item = build_item(category, key, &doc, None);
+                        backend.put_item(item.clone()).await?;
+                        item
+                    }
+                };
+                imported.push(item);
+            }
+
+            if cli.json {
+                print_json(&imported)?;
+            } else {
+                eprintln!("Imported {} items", imported.len());
+            }
+        }
+        Some(Command::Context { budget, topic }) => {
+            let categories: Vec<String> = schema_manager
+                .list_schemas()
+                .await?
+                .into_iter()
+                .map(|s| s.prefix)
+                .collect();
+            let pack = build_context_pack(&backend, &categories, topic.as_deref(), budget).await?;
+            if cli.json {
+                print_json(&pack)?;
+            } else {
+                print!("{}", pack.text);
+                eprintln!(
+                    "{} memories, ~{} tokens ({} left out)",
+                    pack.items.len(),
+                    pack.tokens,
+                    pack.omitted
+                );
+            }
+        }
+        Some(Command::Generate {
+            target: GenerateTarget::AgentsMd { out },
+        }) => {
+            let existing = match &out {
+                Some(path) => match std::fs::read_to_string(path) {
+                    Ok(text) => Some(text),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                    Err(e) => {
+                        return Err(CliError::context(
+                            &format!("Failed to read {}", path.display()),
+                            &e,
+                        )
+                        .into());
+                    }
+                },
+                None => None,
+            };
+            let document = generate_agents_md(&backend, existing.as_deref()).await?;
+            match &out {
+                Some(path) => {
+                    std::fs::write(path, &document).map_err(|e| {
+                        CliError::context(&format!("Failed to write {}", path.display()), &e)
+                    })?;
+                    if cli.json {
+                        print_json(&serde_json::json!({
+                            "path": path.display().to_string(),
+                            "updated": existing.is_some(),
+                        }))?;
+                    } else {
+                        let verb = if existing.is_some() {
+                            "Updated"
+                        } else {
+                            "Wrote"
+                        };
+                        eprintln!("{verb} {}", path.display());
+                    }
+                }
+                None if cli.json => print_json(&serde_json::json!({ "content": document }))?,
+                None => print!("{document}"),
+            }
+        }
+        Some(Command::Export {
+            format,
+            category,
+            output,
+        }) => {
+            let categories = match category {
+                Some(category) => vec![category],
+                None => schema_manager
+                    .list_schemas()
+                    .await?
+                    .into_iter()
+                    .map(|s| s.prefix)
+                    .collect(),
+            };
+            let graph = build_graph(&backend, &categories).await?;
+            let document = match format {
+                ExportFormat::Dot => to_dot(&graph),
+                ExportFormat::Graphml => to_graphml(&graph),
+            };
+            match &output {
+                Some(path) => std::fs::write(path, &document).map_err(|e| {
+                    CliError::context(&format!("Failed to write {}", path.display()), &e)
+                })?,
+                None if cli.json => {}
+                None => print!("{document}"),
+            }
+            if cli.json {
+                print_json(&graph)?;
+            } else {
+                eprintln!(
+                    "Exported {} items and {} edges",
+                    graph.nodes.len(),
+                    graph.edges.len()
+                );
+            }
+        }
+        Some(Command::Forget {
+            category,
+            key,
+            cascade: true,
+        }) => {
+            let categories = schema_manager.selected_categories(None).await?;
+            let report = cascade_forget(&backend, &category, &key, &categories).await?;
+            if cli.json {
+                print_json(&report)?;
+            } else {
+                if !report.found {
+                    eprintln!("No memory {category}/{key}; cleaning up references only");
+                }
+                for source in &report.references {
+                    eprintln!("  dropped reference from {source}");
+                }
+                eprintln!(
+                    "Forgot: {category}/{key} ({} chunk(s), {} link(s), {} reference(s))",
+                    report.chunks,
+                    report.links,
+                    report.references.len()
+                );
+            }
+        }
+        Some(Command::Forget { category, key, .. }) => {
+            // Remove linked chunks along with a chunked parent.
+            if let Some(item) = backend.get_item(&category, &key).await? {
+                delete_chunks(&backend, &item).await?;
+            }
+            backend.delete_item(&category, &key).await?;
+            if cli.json {
+                print_json(&serde_json::json!({ "forgotten": format!("{category}/{key}") }))?;
+            } else {
+                eprintln!("Forgot: {category}/{key}");
+            }
+        }
+        Some(Command::Define {
+            category,
+            description,
+            infer: Some(request),
+            yes,
+            hints,
+            ..
+        }) => {
+            let (proposed, mut definition) = propose_definition(&usage, &request).await?;
+            let category = category.unwrap_or(proposed);
+            if category.is_empty() {
+                return Err(CliError::invalid_input(
+                    "The proposal has no category name; pass --category",
+                )
+                .into());
+            }
+            if let Some(description) = description {
+                definition.description = description;
+            }
+            if hints.is_some() {
+                definition.hints = hints;
+            }
+            if !cli.json {
+                print_definition(&category, &definition);
+            }
+            let create = yes
+                || (!cli.json
+                    && matches!(
+                        prompt_line("Create this schema? [y/N] ")?.as_deref(),
+                        Some("y" | "yes")
+                    ));
+            if create {
+                schema_manager
+                    .create_schema_with_indexes(&category, &definition, true)
+                    .await?;
+            }
+            if cli.json {
+                print_json(&serde_json::json!({
+                    "category": category,
+                    "definition": definition,
+                    "created": create,
+                }))?;
+            } else if create {
+                eprintln!("Schema defined for '{category}'");
+            } else {
+                eprintln!("Schema not created.");
+            }
+        }
+        Some(Command::Define {
+            category,
+            description,
+            attributes,
+            from_examples,
+            auto_index,
+            hints,
+            ..
+        }) => {
+            let category = category.ok_or_else(|| CliError::usage("--category is required"))?;
+            let description =
+                description.ok_or_else(|| CliError::usage("--description is required"))?;
+            let inferred = match from_examples {
+                Some(path) => {
+                    let text = std::fs::read_to_string(&path).map_err(|e| {
+                        CliError::invalid_input(format!("Failed to read {}: {e}", path.display()))
+                    })?;
+                    let examples = parse_examples(&text).map_err(CliError::invalid_input)?;
+                    Some(infer_schema(&examples).map_err(CliError::invalid_input)?)
+                }
+                None => None,
+            };
+            let (attr_defs, suggested_indexes) = match &inferred {
+                Some(inferred) => (
+                    inferred.attributes.clone(),
+                    inferred.suggested_indexes.clone(),
+                ),
+                None => {
+                    let attr_defs: Vec<ferridyn_memory::schema::AttributeDef> =
+                        serde_json::from_str(attributes.as_deref().unwrap_or_default()).map_err(
+                            |e| CliError::invalid_input(format!("Invalid attributes JSON: {e}")),
+                        )?;
+                    let suggested_indexes = if auto_index {
+                        attr_defs.iter().map(|a| a.name.clone()).collect()
+                    } else {
+                        vec![]
+                    };
+                    (attr_defs, suggested_indexes)
+                }
+            };
+
+            let definition = SchemaDefinition {
+                description,
+                attributes: attr_defs,
+                suggested_indexes,
+                hints,
+            };
+
+            schema_manager
+                .create_schema_with_indexes(&category, &definition, true)
+                .await?;
+            if cli.json {
+                match &inferred {
+                    Some(inferred) => print_json(&serde_json::json!({
+                        "defined": category,
+                        "inferred": inferred,
+                    }))?,
+                    None => print_json(&serde_json::json!({ "defined": category }))?,
+                }
+            } else {
+                if let Some(inferred) = &inferred {
+                    eprintln!("Inferred from {} example(s):", inferred.examples);
+                    for attr in &inferred.attributes {
+                        let req = if attr.required { ", required" } else { "" };
+                        let indexed = if inferred.suggested_indexes.contains(&attr.name) {
+                            ", indexed"
+                        } else {
+                            ""
+                        };
+                        eprintln!("  - {} ({}{req}{indexed})", attr.name, attr.attr_type);
+                    }
+                    if !inferred.skipped.is_empty() {
+                        eprintln!(
+                            "  skipped (arrays or objects): {}",
+                            inferred.skipped.join(", ")
+                        );
+                    }
+                }
+                eprintln!("Schema defined for '{category}'");
+            }
+        }
         Some(Command::Schema { category }) => {
             if let Some(ref cat) = category {
-                let schema = schema_manager
-                    .get_schema(cat)
-                    .await
-                    .map_err(|e| e.to_string())?;
-                let indexes = schema_manager.list_indexes().await.unwrap_or_default();
-                let cat_indexes: Vec<_> = indexes
-                    .iter()
-                    .filter(|idx| idx.partition_schema == *cat)
-                    .collect();
+                let snapshot = schema_manager.snapshot().await?;
+                let cat_indexes = snapshot.indexes_of(cat);
+                let aliases = &snapshot.aliases;
+                let hints = &snapshot.hints;
 
-                match schema {
+                match snapshot.get(cat) {
                     Some(s) => {
                         if cli.json {
                             let output = serde_json::json!({
                                 "category": cat,
                                 "description": s.description,
+                                "hints": hints.get(cat),
                                 "attributes": s.attributes.iter().map(|a| serde_json::json!({
                                     "name": a.name,
                                     "type": a.attr_type,
                                     "required": a.required,
+                                    "aliases": aliases.aliases_of(cat, &a.name),
                                 })).collect::<Vec<_>>(),
                                 "indexes": cat_indexes.iter().map(|idx| serde_json::json!({
                                     "name": idx.name,
@@ -580,14 +2319,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                     "type": idx.index_key_type,
                                 })).collect::<Vec<_>>(),
                             });
-                            println!("{}", serde_json::to_string_pretty(&output)?);
+                            print_json(&output)?;
                         } else {
                             println!("Category: {cat}");
                             println!("Description: {}", s.description);
+                            if let Some(hints) = hints.get(cat) {
+                                println!("Hints: {hints}");
+                            }
                             println!("Attributes:");
                             for attr in &s.attributes {
                                 let req = if attr.required { ", required" } else { "" };
-                                println!("  - {} ({}{})", attr.name, attr.attr_type, req);
+                                let also = aliases.aliases_of(cat, &attr.name);
+                                if also.is_empty() {
+                                    println!("  - {} ({}{})", attr.name, attr.attr_type, req);
+                                } else {
+                                    println!(
+                                        "  - {} ({}{}; aliases: {})",
+                                        attr.name,
+                                        attr.attr_type,
+                                        req,
+                                        also.join(", ")
+                                    );
+                                }
                             }
                             if !cat_indexes.is_empty() {
                                 println!("Indexes:");
@@ -601,14 +2354,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         }
                     }
                     None => {
-                        eprintln!("No schema defined for category '{cat}'");
+                        return Err(CliError::not_found(format!(
+                            "No schema defined for category '{cat}'"
+                        ))
+                        .into());
                     }
                 }
             } else {
-                let schemas = schema_manager
-                    .list_schemas()
-                    .await
-                    .map_err(|e| e.to_string())?;
+                let schemas = schema_manager.list_schemas().await?;
                 let indexes = schema_manager.list_indexes().await.unwrap_or_default();
 
                 if schemas.is_empty() {
@@ -637,7 +2390,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             })
                         })
                         .collect();
-                    println!("{}", serde_json::to_string_pretty(&output)?);
+                    print_json(&output)?;
                 } else {
                     for s in &schemas {
                         let idx_count = indexes
@@ -655,11 +2408,73 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
         }
+        Some(Command::MigrateTable {
+            from,
+            to,
+            drop_source,
+        }) => {
+            for name in [&from, &to] {
+                validate_table_identifier(name).map_err(CliError::usage)?;
+            }
+            let source = connect_backend(&from).await?;
+            let target = connect_backend(&to).await?;
+            let report = migrate_table(&source, &target, |category, count| {
+                eprintln!("Copied {count} items from {category}");
+            })
+            .await?;
+            if report.items_copied == 0 && report.schemas_created == 0 {
+                eprintln!("Warning: table '{from}' is empty or does not exist");
+            }
+            if !report.verified() {
+                return Err(CliError::invalid_input(format!(
+                    "Verification failed: {} items differ in '{to}' ({}); the source was kept",
+                    report.mismatched.len(),
+                    report.mismatched.join(", ")
+                ))
+                .into());
+            }
+            let mut report = report;
+            if drop_source {
+                empty_table(&source)
+                    .await
+                    .map_err(|e| CliError::context(&format!("Failed to empty '{from}'"), &e))?;
+                if let Some(namespace) = namespace_of(&from)
+                    && let Err(e) = unregister_namespace(&source, &namespace).await
+                {
+                    eprintln!("Warning: failed to unregister namespace {namespace}: {e}");
+                }
+                report.source_emptied = true;
+            }
+            if cli.json {
+                print_json(&report)?;
+            } else {
+                eprintln!(
+                    "Migrated {} items, {} schemas, and {} indexes from '{from}' to '{to}' (verified)",
+                    report.items_copied, report.schemas_created, report.indexes_created
+                );
+                if report.source_emptied {
+                    eprintln!("Emptied '{from}'");
+                }
+            }
+        }
+        Some(Command::Seed { profile, force }) => {
+            auto_init(&backend, &schema_manager).await?;
+            let report = seed(&backend, profile.into(), force).await?;
+            if cli.json {
+                print_json(&report)?;
+            } else {
+                eprintln!(
+                    "Seeded {} memories ({} already existed; --force to overwrite)",
+                    report.written.len(),
+                    report.skipped.len()
+                );
+            }
+        }
         Some(Command::Init { force }) => {
             if force {
                 // Drop and recreate all predefined schemas.
                 for predefined in PREDEFINED_SCHEMAS {
-                    let _ = backend.drop_schema(predefined.name).await;
+                    let _ = schema_manager.drop_schema(predefined.name).await;
                     // Also drop associated indexes.
                     let indexes = schema_manager.list_indexes().await.unwrap_or_default();
                     for idx in &indexes {
@@ -669,19 +2484,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 }
             }
-            backend
-                .ensure_predefined_schemas()
-                .await
-                .map_err(|e| e.to_string())?;
+            backend.ensure_predefined_schemas().await?;
+            schema_manager.invalidate().await;
 
             if cli.json {
                 let names: Vec<&str> = PREDEFINED_SCHEMAS.iter().map(|s| s.name).collect();
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&serde_json::json!({
-                        "initialized": names,
-                    }))?
-                );
+                print_json(&serde_json::json!({
+                    "initialized": names,
+                }))?;
             } else {
                 eprintln!(
                     "Initialized {} predefined categories:",
@@ -693,424 +2503,1579 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
         Some(Command::Promote { category, key, to }) => {
-            let item = backend
-                .get_item(&category, &key)
-                .await
-                .map_err(|e| e.to_string())?;
+            let item = backend.get_item(&category, &key).await?;
             let item = match item {
                 Some(i) => i,
                 None => {
-                    eprintln!("No memory found for {category}/{key}");
-                    std::process::exit(1);
+                    op_log.set_outcome(Outcome::NotFound);
+                    return Err(CliError::not_found(format!(
+                        "No memory found for {category}/{key}"
+                    ))
+                    .into());
                 }
             };
 
             let target_category = to.as_deref().unwrap_or(&category);
 
             if target_category != category {
-                // Re-categorize: re-parse content against target schema.
-                let llm = require_llm()?;
-                auto_init(&backend, &schema_manager).await?;
-
-                let schema_info = schema_manager
-                    .get_schema(target_category)
-                    .await
-                    .map_err(|e| e.to_string())?
-                    .ok_or_else(|| format!("Schema for '{}' not found", target_category))?;
-
-                // Use item's content (or all string attributes) as input for re-parsing.
-                let input_text = item["content"]
-                    .as_str()
-                    .unwrap_or_else(|| {
-                        item.as_object()
-                            .and_then(|obj| {
-                                obj.iter()
-                                    .filter(|(k, v)| {
-                                        *k != "category"
-                                            && *k != "key"
-                                            && *k != "created_at"
-                                            && *k != "expires_at"
-                                            && v.is_string()
-                                    })
-                                    .map(|(_, v)| v.as_str().unwrap_or(""))
-                                    .next()
-                            })
-                            .unwrap_or("")
-                    })
-                    .to_string();
-
-                let doc =
-                    parse_to_document(llm.as_ref(), target_category, &schema_info, &input_text)
-                        .await
-                        .map_err(|e| format!("Document parsing failed: {e}"))?;
-                let new_key = doc["key"].as_str().unwrap_or(&key).to_string();
-
-                // Build promoted item without expires_at.
-                let mut promoted = serde_json::json!({
-                    "category": target_category,
-                    "key": new_key,
-                });
-                if let Some(obj) = doc.as_object() {
-                    for (k, v) in obj {
-                        if k == "key" || k == "category" {
-                            continue;
-                        }
-                        promoted[k] = v.clone();
-                    }
-                }
-                promoted["created_at"] = Value::String(chrono::Utc::now().to_rfc3339());
-                // Explicitly remove expires_at (promotion = LTM).
-                if let Some(obj) = promoted.as_object_mut() {
-                    obj.remove("expires_at");
-                }
-
-                backend
-                    .put_item(promoted.clone())
-                    .await
-                    .map_err(|e| e.to_string())?;
-                backend
-                    .delete_item(&category, &key)
-                    .await
-                    .map_err(|e| e.to_string())?;
-
-                if cli.json {
-                    println!(
-                        "{}",
-                        serde_json::to_string_pretty(&serde_json::json!({
-                            "promoted": true,
-                            "from": format!("{category}/{key}"),
-                            "to": format!("{target_category}/{new_key}"),
-                        }))?
-                    );
-                } else {
-                    eprintln!("Promoted {category}/{key} → {target_category}/{new_key}");
-                }
+                promote_to_category(
+                    &backend,
+                    &schema_manager,
+                    &usage,
+                    &item,
+                    target_category,
+                    cli.json,
+                )
+                .await?;
             } else {
-                // Same category: just remove expires_at (in-place promotion).
-                let mut promoted = item.clone();
-                if let Some(obj) = promoted.as_object_mut() {
-                    obj.remove("expires_at");
-                }
-                // Re-inject created_at to update timestamp.
-                promoted["created_at"] = Value::String(chrono::Utc::now().to_rfc3339());
-
-                backend
-                    .put_item(promoted)
-                    .await
-                    .map_err(|e| e.to_string())?;
+                // Same category: just remove expires_at (in-place promotion),
+                // and re-inject created_at to update timestamp.
+                let mut patch = serde_json::Map::new();
+                patch.insert("expires_at".into(), Value::Null);
+                patch.insert(
+                    "created_at".into(),
+                    Value::String(chrono::Utc::now().to_rfc3339()),
+                );
+                backend.update_item(&category, &key, &patch).await?;
 
                 if cli.json {
-                    println!(
-                        "{}",
-                        serde_json::to_string_pretty(&serde_json::json!({
-                            "promoted": true,
-                            "category": category,
-                            "key": key,
-                        }))?
-                    );
+                    print_json(&serde_json::json!({
+                        "promoted": true,
+                        "category": category,
+                        "key": key,
+                    }))?;
                 } else {
                     eprintln!("Promoted {category}/{key} (TTL removed)");
                 }
             }
         }
-        Some(Command::Prune { category }) => {
-            let categories: Vec<String> = if let Some(ref cat) = category {
-                vec![cat.clone()]
-            } else {
-                let schemas = schema_manager.list_schemas().await.unwrap_or_default();
-                schemas.iter().map(|s| s.prefix.clone()).collect()
-            };
-
-            let mut total_pruned = 0usize;
-            for cat in &categories {
-                let items = backend
-                    .query(cat, None, 1000)
-                    .await
-                    .map_err(|e| e.to_string())?;
-                for item in &items {
-                    if is_expired(item)
-                        && let Some(key) = item["key"].as_str()
-                    {
-                        backend
-                            .delete_item(cat, key)
-                            .await
-                            .map_err(|e| e.to_string())?;
-                        total_pruned += 1;
-                    }
-                }
+        Some(Command::Append {
+            category,
+            key,
+            text,
+        }) => {
+            let text = text.join(" ");
+            if text.trim().is_empty() {
+                return Err(CliError::usage("Text to append is required.").into());
             }
+            let item = append_content(&backend, &category, &key, &text, chrono::Utc::now())
+                .await?
+                .ok_or_else(|| {
+                    CliError::not_found(format!("No memory found for {category}/{key}"))
+                })?;
 
             if cli.json {
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&serde_json::json!({
-                        "pruned": total_pruned,
-                    }))?
-                );
-            } else if total_pruned == 0 {
-                eprintln!("No expired memories found.");
+                print_json(&item)?;
             } else {
-                eprintln!("Pruned {total_pruned} expired memories.");
+                let chars = item["content"].as_str().map_or(0, |c| c.chars().count());
+                eprintln!("Appended to {category}/{key} ({chars} characters)");
             }
         }
-        Some(Command::Serve {
-            namespace: serve_ns,
+        Some(Command::Update {
+            category,
+            key,
+            assignments,
         }) => {
-            // Use serve-specific namespace, falling back to global namespace.
-            let ns = serve_ns.or(namespace);
-            ferridyn_memory::mcp::run_mcp_server(backend, ns).await?;
+            let mut patch = serde_json::Map::new();
+            for assignment in &assignments {
+                let (attr, value) = parse_assignment(assignment).map_err(CliError::usage)?;
+                patch.insert(attr, value);
+            }
+            let item = backend
+                .update_item(&category, &key, &patch)
+                .await
+                .map_err(|e| CliError::context("Update failed", &e))?
+                .ok_or_else(|| {
+                    CliError::not_found(format!("No memory found for {category}/{key}"))
+                })?;
+
+            if cli.json {
+                print_json(&item)?;
+            } else {
+                eprintln!("Updated {} attribute(s) of {category}/{key}", patch.len());
+            }
         }
-        None => {
-            let input = match cli.prompt {
-                Some(ref p) => p.clone(),
-                None => {
-                    Cli::parse_from(["fmemory", "--help"]);
-                    return Ok(());
+        Some(Command::Importance {
+            category,
+            key,
+            importance,
+        }) => {
+            let mut patch = serde_json::Map::new();
+            patch.insert(IMPORTANCE_ATTRIBUTE.into(), Value::from(importance));
+            let item = backend
+                .update_item(&category, &key, &patch)
+                .await?
+                .ok_or_else(|| {
+                    CliError::not_found(format!("No memory found for {category}/{key}"))
+                })?;
+
+            if cli.json {
+                print_json(&item)?;
+            } else {
+                eprintln!("Set importance of {category}/{key} to {importance}");
+            }
+        }
+        Some(Command::Backlinks { category, key }) => {
+            let categories = schema_manager.selected_categories(None).await?;
+            let target = format!("{category}/{key}");
+            let found = backlinks(&backend, &target, &categories, cli.include_expired).await?;
+
+            if cli.json {
+                print_json(&found)?;
+            } else if found.is_empty() {
+                eprintln!("Nothing references {target}");
+            } else {
+                for backlink in &found {
+                    println!("  {}  ({})", backlink.source, backlink.via);
                 }
+                eprintln!("{} reference(s) to {target}", found.len());
+            }
+        }
+        Some(Command::Related {
+            category,
+            key,
+            limit,
+        }) => {
+            let categories = schema_manager.selected_categories(None).await?;
+            let target = format!("{category}/{key}");
+            let limit = limits.resolve("related", limit);
+            let suggestions =
+                suggest_related(&backend, &target, &categories, limit, cli.include_expired).await?;
+
+            if cli.json {
+                print_json(&suggestions)?;
+            } else if suggestions.is_empty() {
+                eprintln!("Nothing related to {target}");
+            } else {
+                for suggestion in &suggestions {
+                    println!(
+                        "  {:>4.1}  {}  ({})",
+                        suggestion.score,
+                        suggestion.reference,
+                        suggestion.reasons.join(", ")
+                    );
+                }
+            }
+        }
+        Some(Command::Link {
+            source,
+            target,
+            relation,
+            remove,
+        }) => match target {
+            Some(target) if remove => {
+                let removed = backend
+                    .unlink_items(&source, &target, relation.as_deref())
+                    .await?;
+                if cli.json {
+                    print_json(&serde_json::json!({ "removed": removed }))?;
+                } else if removed == 0 {
+                    eprintln!("No link from {source} to {target}");
+                } else {
+                    eprintln!("Removed {removed} link(s) from {source} to {target}");
+                }
+            }
+            Some(target) => {
+                let link = backend
+                    .link_items(
+                        &source,
+                        &target,
+                        relation.as_deref().unwrap_or(DEFAULT_RELATION),
+                    )
+                    .await?;
+                if cli.json {
+                    print_json(&link)?;
+                } else {
+                    eprintln!(
+                        "Linked {} -[{}]-> {}",
+                        link.source, link.relation, link.target
+                    );
+                }
+            }
+            None => {
+                let links = backend.get_links(&source).await?;
+                if cli.json {
+                    print_json(&links)?;
+                } else if links.is_empty() {
+                    eprintln!("No links for {source}");
+                } else {
+                    for link in &links {
+                        println!("  {} -[{}]-> {}", link.source, link.relation, link.target);
+                    }
+                }
+            }
+        },
+        Some(Command::Prune { category, below }) => {
+            let categories = match category {
+                Some(ref cat) => vec![cat.clone()],
+                None => backend.list_categories().await?,
             };
 
-            let llm = require_llm().map_err(|e| {
-                format!(
-                    "{e}\n\n-p/--prompt requires ANTHROPIC_API_KEY. \
-                     Use explicit subcommands (discover, recall, remember, ...) \
-                     for API-key-free operation."
-                )
+            let total_pruned = backend.prune_expired(&categories).await?;
+            let low_score = match below {
+                Some(threshold) => {
+                    let scored: Vec<String> = categories
+                        .iter()
+                        .filter(|c| !SYSTEM_CATEGORIES.contains(&c.as_str()))
+                        .cloned()
+                        .collect();
+                    prune_low_score(&backend, &scored, threshold, chrono::Utc::now()).await?
+                }
+                None => Vec::new(),
+            };
+
+            if cli.json {
+                let mut output = serde_json::json!({ "pruned": total_pruned });
+                if below.is_some() {
+                    output["low_score"] = low_score
+                        .iter()
+                        .map(|(category, key)| format!("{category}/{key}"))
+                        .collect();
+                }
+                print_json(&output)?;
+            } else {
+                if total_pruned == 0 {
+                    eprintln!("No expired memories found.");
+                } else {
+                    eprintln!("Pruned {total_pruned} expired memories.");
+                }
+                if below.is_some() {
+                    eprintln!(
+                        "Pruned {} low-scoring short-term memories.",
+                        low_score.len()
+                    );
+                    for (category, key) in &low_score {
+                        eprintln!("  - {category}/{key}");
+                    }
+                }
+            }
+        }
+        Some(Command::MirrorSync) => {
+            let mirror = backend.mirror().ok_or_else(|| {
+                CliError::usage(format!(
+                    "No mirror configured. Set {MIRROR_SOCKET_ENV} to the mirror's socket."
+                ))
             })?;
+            // Let queued copies land first so they are not counted as missing.
+            backend.flush_mirror().await;
+            let report = sync_mirror(&backend, &mirror)
+                .await
+                .map_err(|e| CliError::context("Mirror sync failed", &e))?;
+            if cli.json {
+                print_json(&report)?;
+            } else {
+                eprintln!(
+                    "Mirror synced: {} schema(s) and {} index(es) created, {} item(s) copied, {} deleted.",
+                    report.schemas_created,
+                    report.indexes_created,
+                    report.items_copied,
+                    report.items_deleted
+                );
+            }
+        }
+        Some(Command::Session { action }) => {
+            let item = match action {
+                SessionAction::Start {
+                    key,
+                    project,
+                    branch,
+                    goal,
+                } => {
+                    let goal = goal.join(" ");
+                    let details = serde_json::json!({
+                        "project": project,
+                        "branch": branch,
+                        "goal": (!goal.is_empty()).then_some(goal),
+                    });
+                    start_session(&backend, key.as_deref(), &details).await?
+                }
+                SessionAction::Append { key, fact } => {
+                    append_to_session(&backend, key.as_deref(), &fact.join(" ")).await?
+                }
+                #[cfg(feature = "llm")]
+                SessionAction::End { key } => {
+                    let llm = require_llm(&usage)?;
+                    end_session(&backend, llm.as_ref(), key.as_deref())
+                        .await
+                        .map_err(|e| CliError::context("Ending the session failed", &e))?
+                }
+            };
+            let key = item["key"].as_str().unwrap_or_default();
+            if cli.json {
+                print_json(&item)?;
+            } else if item["status"] == SESSION_ACTIVE {
+                let facts = item[FACTS_ATTRIBUTE].as_array().map_or(0, Vec::len);
+                eprintln!("Session {SESSION_CATEGORY}/{key} is active ({facts} fact(s))");
+            } else {
+                if let Some(summary) = item["content"].as_str() {
+                    println!("{summary}");
+                }
+                eprintln!("Ended session {SESSION_CATEGORY}/{key}");
+            }
+        }
+        Some(Command::Issue { action }) => match action {
+            IssueAction::Resolve { key, fix } => {
+                let item = resolve_issue(&backend, &key, fix.as_deref())
+                    .await?
+                    .ok_or_else(|| {
+                        CliError::not_found(format!("No memory found for {ISSUES_CATEGORY}/{key}"))
+                    })?;
+                if cli.json {
+                    print_json(&item)?;
+                } else {
+                    eprintln!("Resolved {ISSUES_CATEGORY}/{key}");
+                }
+            }
+            IssueAction::Reopen { key } => {
+                let item = reopen_issue(&backend, &key).await?.ok_or_else(|| {
+                    CliError::not_found(format!("No memory found for {ISSUES_CATEGORY}/{key}"))
+                })?;
+                if cli.json {
+                    print_json(&item)?;
+                } else {
+                    eprintln!("Reopened {ISSUES_CATEGORY}/{key}");
+                }
+            }
+            IssueAction::Open { limit } => {
+                let issues = open_issues(&backend, limit).await?;
+                if cli.json {
+                    print_json(&issues)?;
+                } else if issues.is_empty() {
+                    eprintln!("No open issues.");
+                } else {
+                    for issue in &issues {
+                        let key = issue["key"].as_str().unwrap_or("?");
+                        match issue["symptom"].as_str().or(issue["content"].as_str()) {
+                            Some(symptom) => println!("{key}: {symptom}"),
+                            None => println!("{key}"),
+                        }
+                    }
+                }
+            }
+        },
+        Some(Command::VerifyIndexes { category, repair }) => {
+            let mut reports = verify_indexes(&backend, category.as_deref()).await?;
+            if repair {
+                for report in reports.iter_mut().filter(|r| !r.is_consistent()) {
+                    let index = backend.describe_index(&report.index).await?;
+                    rebuild_index(&backend, &index).await.map_err(|e| {
+                        CliError::context(&format!("Rebuilding {} failed", report.index), &e)
+                    })?;
+                    report.rebuilt = true;
+                }
+            }
+
+            if cli.json {
+                print_json(&reports)?;
+            } else if reports.is_empty() {
+                eprintln!("No indexes to check.");
+            } else {
+                for report in &reports {
+                    let status = if report.is_consistent() {
+                        "ok".to_string()
+                    } else {
+                        format!(
+                            "{} missing, {} orphaned{}",
+                            report.missing.len(),
+                            report.orphaned.len(),
+                            if report.rebuilt { ", rebuilt" } else { "" }
+                        )
+                    };
+                    println!("{} ({} items): {status}", report.index, report.checked);
+                    for key in &report.missing {
+                        println!("  missing  {}/{key}", report.category);
+                    }
+                    for key in &report.orphaned {
+                        println!("  orphaned {}/{key}", report.category);
+                    }
+                }
+                let broken = reports.iter().filter(|r| !r.is_consistent()).count();
+                if broken > 0 && !repair {
+                    eprintln!(
+                        "{broken} inconsistent index(es). Run with --repair to rebuild them."
+                    );
+                }
+            }
+        }
+        Some(Command::Backup { action }) => match action {
+            BackupAction::Create { path } => {
+                let backup = create_backup(&backend).await?;
+                let items: usize = backup.items.values().map(Vec::len).sum();
+                let file = std::fs::File::create(&path).map_err(|e| {
+                    CliError::context(&format!("Failed to create {}", path.display()), &e)
+                })?;
+                serde_json::to_writer_pretty(std::io::BufWriter::new(file), &backup)?;
+                if cli.json {
+                    print_json(&serde_json::json!({
+                        "path": path,
+                        "categories": backup.items.len(),
+                        "items": items,
+                    }))?;
+                } else {
+                    eprintln!(
+                        "Backed up {items} item(s) in {} categories to {}",
+                        backup.items.len(),
+                        path.display()
+                    );
+                }
+            }
+            BackupAction::Diff { .. } => unreachable!("handled before connecting"),
+        },
+        Some(Command::Resurface {
+            category,
+            limit,
+            after,
+        }) => {
+            let base = parse_ttl(&after).map_err(CliError::invalid_input)?;
+            let categories = schema_manager
+                .selected_categories(category.as_deref())
+                .await?;
+            let mut items = Vec::new();
+            for cat in &categories {
+                items.extend(backend.query(cat, None, SCORE_SCAN_LIMIT).await?);
+            }
+            let candidates = resurface_candidates(items, chrono::Utc::now(), base, limit);
+
+            if cli.json {
+                print_json(&candidates)?;
+            } else if candidates.is_empty() {
+                eprintln!("Nothing due for review.");
+            } else {
+                review_items(&backend, &candidates).await?;
+            }
+        }
+        #[cfg(feature = "llm")]
+        Some(Command::Digest { date, prune }) => {
+            let date = parse_digest_date(&date, DateContext::from_env().today())
+                .map_err(CliError::invalid_input)?;
+            let earlier = match backend.get_item(DIGEST_CATEGORY, &digest_key(date)).await? {
+                Some(item) => hydrate_chunks(&backend, vec![item]).await?.pop(),
+                None => None,
+            };
+            let entries = undigested(gather_day(&backend, date).await?, earlier.as_ref());
+            if entries.is_empty() {
+                eprintln!("Nothing to digest for {}", date.format("%Y-%m-%d"));
+                op_log.set_outcome(Outcome::Ok);
+                return Ok(());
+            }
 
-            // Auto-init predefined schemas.
             auto_init(&backend, &schema_manager).await?;
+            let llm = require_llm(&usage)?;
+            let summary = summarize_day(
+                llm.as_ref(),
+                date,
+                earlier.as_ref().and_then(|d| d["content"].as_str()),
+                &digest_input(&entries),
+            )
+            .await
+            .map_err(|e| CliError::context("Digest failed", &e))?;
+            let digest = build_digest_item(date, &summary, &entries, earlier.as_ref());
+            backend.put_item(digest.clone()).await?;
+
+            let pruned = if prune {
+                prune_entries(&backend, &entries).await?
+            } else {
+                Vec::new()
+            };
 
-            // Classify intent: remember or recall.
-            let intent = classify_intent(llm.as_ref(), &input)
+            if cli.json {
+                let output = serde_json::json!({
+                    "digest": digest,
+                    "entries": entries.len(),
+                    "pruned": pruned
+                        .iter()
+                        .map(|(cat, key)| format!("{cat}/{key}"))
+                        .collect::<Vec<_>>(),
+                });
+                print_json(&output)?;
+            } else {
+                println!("{summary}");
+                eprintln!(
+                    "Stored digest of {} entries as {DIGEST_CATEGORY}/{}",
+                    entries.len(),
+                    digest["key"].as_str().unwrap_or_default()
+                );
+                if !pruned.is_empty() {
+                    eprintln!("Pruned {} digested entries", pruned.len());
+                }
+            }
+        }
+        #[cfg(feature = "llm")]
+        Some(Command::Summarize { category }) => {
+            let llm = require_llm(&usage)?;
+            let store = MemoryStore::new(backend.clone())
+                .with_llm(llm)
+                .with_include_expired(cli.include_expired);
+            let Some(overview) = store
+                .summarize(&category)
                 .await
-                .map_err(|e| format!("Intent classification failed: {e}"))?;
+                .map_err(|e| CliError::context("Summarize failed", &e))?
+            else {
+                eprintln!("No items in '{category}' to summarize");
+                op_log.set_outcome(Outcome::Ok);
+                return Ok(());
+            };
 
-            match intent {
-                NlIntent::Remember { content } => {
-                    // Let LLM pick category from available schemas.
-                    let schemas = schema_manager.list_schemas().await.unwrap_or_default();
-                    let doc = parse_to_document_with_category(llm.as_ref(), &schemas, &content)
-                        .await
-                        .map_err(|e| format!("Document parsing failed: {e}"))?;
-                    let category = doc["category"].as_str().unwrap_or("notes").to_string();
-                    let final_key = doc["key"].as_str().unwrap_or("unknown").to_string();
+            if cli.json {
+                print_json(&overview)?;
+            } else {
+                println!("{}", overview["content"].as_str().unwrap_or_default());
+                eprintln!(
+                    "Stored {category}/{OVERVIEW_KEY} from {} items",
+                    overview["item_count"]
+                );
+            }
+        }
+        #[cfg(feature = "llm")]
+        Some(Command::Chat { save }) => {
+            let llm = require_llm(&usage)?;
+            auto_init(&backend, &schema_manager).await?;
+            let store = MemoryStore::new(backend.clone()).with_include_expired(cli.include_expired);
+            let mut session = ChatSession::new(store.clone(), llm.clone());
 
-                    // Build final document with created_at.
-                    let mut final_item = serde_json::json!({
-                        "category": category,
-                        "key": final_key,
-                    });
-                    if let Some(obj) = doc.as_object() {
-                        for (k, v) in obj {
-                            if k == "key" || k == "category" {
-                                continue;
-                            }
-                            final_item[k] = v.clone();
+            eprintln!("Chatting with memory. /quit or Ctrl-D to finish.");
+            while let Some(line) = prompt_line("> ")? {
+                match line.as_str() {
+                    "" => continue,
+                    "/quit" | "/exit" => break,
+                    _ => {}
+                }
+                match session.send(&line).await {
+                    Ok(reply) if cli.json => {
+                        print_json(&reply)?;
+                    }
+                    Ok(reply) => {
+                        println!("{}", reply.reply);
+                        let refs: Vec<String> = reply.items.iter().filter_map(item_ref).collect();
+                        if !refs.is_empty() {
+                            eprintln!("  (recalled {})", refs.join(", "));
                         }
                     }
-                    final_item["created_at"] = Value::String(chrono::Utc::now().to_rfc3339());
-
-                    // Auto-inject expires_at for categories with default TTLs.
-                    if category == "scratchpad" {
-                        final_item["expires_at"] =
-                            Value::String(compute_expires_at(SCRATCHPAD_DEFAULT_TTL));
-                    } else if category == "sessions" {
-                        final_item["expires_at"] =
-                            Value::String(compute_expires_at(SESSIONS_DEFAULT_TTL));
-                    } else if category == "interactions" {
-                        final_item["expires_at"] =
-                            Value::String(compute_expires_at(INTERACTIONS_DEFAULT_TTL));
-                    } else if category == "events"
-                        && let Some(expires) = auto_ttl_from_date(&final_item)
-                    {
-                        final_item["expires_at"] = Value::String(expires);
+                    Err(e) => eprintln!("Error: {e}"),
+                }
+            }
+
+            let facts = session.facts();
+            if !facts.is_empty() {
+                eprintln!("\n{} new fact(s) from this chat:", facts.len());
+            }
+            let store = store.with_llm(llm);
+            for fact in facts {
+                let keep = save
+                    || matches!(
+                        prompt_line(&format!("Save \"{fact}\"? [y/N] "))?.as_deref(),
+                        Some("y" | "yes")
+                    );
+                if !keep {
+                    continue;
+                }
+                match store.remember(fact).await {
+                    Ok(item) => {
+                        report_stored(&item, item["chunk_count"].as_u64().unwrap_or(0) as usize)
                     }
+                    Err(e) => eprintln!("Error saving \"{fact}\": {e}"),
+                }
+            }
+        }
+        #[cfg(feature = "llm")]
+        Some(Command::ReviewWeek) => {
+            let mut report = gather_week(&backend, chrono::Utc::now(), cli.include_expired).await?;
+            if report.is_empty() {
+                if cli.json {
+                    print_json(&report)?;
+                } else {
+                    eprintln!("Nothing recorded in the last 7 days.");
+                }
+                op_log.set_outcome(Outcome::Ok);
+                return Ok(());
+            }
 
-                    backend
-                        .put_item(final_item.clone())
-                        .await
-                        .map_err(|e| e.to_string())?;
+            let llm = require_llm(&usage)?;
+            let summary = summarize_week(llm.as_ref(), &serde_json::to_value(&report)?)
+                .await
+                .map_err(|e| CliError::context("Weekly review failed", &e))?;
+            report.summary = Some(summary);
 
-                    // Output.
-                    if cli.json {
-                        println!("{}", serde_json::to_string_pretty(&final_item)?);
-                    } else {
-                        let attr_names: Vec<&str> = final_item
-                            .as_object()
-                            .map(|obj| {
-                                obj.iter()
-                                    .filter(|(k, v)| {
-                                        *k != "category"
-                                            && *k != "key"
-                                            && *k != "created_at"
-                                            && *k != "expires_at"
-                                            && !v.is_null()
-                                    })
-                                    .map(|(k, _)| k.as_str())
-                                    .collect()
-                            })
-                            .unwrap_or_default();
+            if cli.json {
+                print_json(&report)?;
+            } else {
+                println!(
+                    "Week ending {}\n",
+                    DateContext::from_env().today().format("%Y-%m-%d (%A)")
+                );
+                println!("{}", report.summary.as_deref().unwrap_or_default());
+                for (title, items) in report.sections() {
+                    if items.is_empty() {
+                        continue;
+                    }
+                    println!("\n{title} ({})", items.len());
+                    for item in items {
+                        println!(
+                            "  - {}/{}: {}",
+                            item["category"].as_str().unwrap_or("?"),
+                            item["key"].as_str().unwrap_or("?"),
+                            headline(item)
+                        );
+                    }
+                }
+            }
+        }
+        Some(Command::Stale { category, age }) => {
+            let age = parse_ttl(&age).map_err(CliError::invalid_input)?;
+            let categories = schema_manager
+                .selected_categories(category.as_deref())
+                .await?;
+            let now = chrono::Utc::now();
+            let mut report = std::collections::BTreeMap::new();
+            for cat in categories {
+                let items = backend.query(&cat, None, SCORE_SCAN_LIMIT).await?;
+                let stale = stale_items(items, now, age);
+                if !stale.is_empty() {
+                    report.insert(cat, stale);
+                }
+            }
 
-                        if attr_names.is_empty() {
-                            eprintln!("Stored {category}/{final_key}");
-                        } else {
-                            eprintln!("Stored {category}/{final_key} ({})", attr_names.join(", "));
-                        }
+            if cli.json {
+                print_json(&report)?;
+            } else if report.is_empty() {
+                eprintln!("No stale memories.");
+            } else {
+                for (cat, items) in &report {
+                    println!("{cat} ({})", items.len());
+                    for item in items {
+                        let days = last_active(item).map_or(0, |t| (now - t).num_days());
+                        println!(
+                            "  {:<32} last touched {days} days ago",
+                            item["key"].as_str().unwrap_or("?")
+                        );
                     }
                 }
-                NlIntent::Recall { query } => {
-                    // --- Recall flow (existing NL query resolution) ---
-                    let schemas = schema_manager
-                        .list_schemas()
-                        .await
-                        .map_err(|e| e.to_string())?;
-                    if schemas.is_empty() {
-                        eprintln!("No schemas defined yet. Run `fmemory init` first.");
-                        std::process::exit(1);
+                eprintln!(
+                    "Re-validate with `fmemory remember --category CAT --key KEY ...` or remove with `fmemory forget`."
+                );
+            }
+        }
+        Some(Command::Conflicts { category }) => {
+            let categories = schema_manager
+                .selected_categories(category.as_deref())
+                .await?;
+            let flagged = list_conflicts(&backend, &categories).await?;
+            let flagged = if cli.include_expired {
+                flagged
+            } else {
+                filter_expired(flagged)
+            };
+
+            if cli.json {
+                print_json(&flagged)?;
+            } else if flagged.is_empty() {
+                eprintln!("No conflicts recorded.");
+            } else {
+                // Each conflict is recorded on both sides; print every pair once.
+                let mut pairs = std::collections::BTreeSet::new();
+                for item in &flagged {
+                    let own = item_ref(item).unwrap_or_default();
+                    for other in conflicts(item) {
+                        pairs.insert(if own <= other {
+                            (own.clone(), other)
+                        } else {
+                            (other, own.clone())
+                        });
                     }
-                    let indexes = schema_manager.list_indexes().await.unwrap_or_default();
+                }
+                for (a, b) in &pairs {
+                    println!("  {a}  <->  {b}");
+                }
+                eprintln!("{} conflicting pair(s)", pairs.len());
+            }
+        }
+        Some(Command::Stats {
+            category,
+            activity: true,
+            ..
+        }) => {
+            let categories = schema_manager
+                .selected_categories(category.as_deref())
+                .await?;
+            // Report what this process counted, too.
+            backend
+                .flush_activity()
+                .await
+                .map_err(|e| CliError::context("Failed to persist category activity", &e))?;
+            let mut report = activity_report(&backend, &categories).await?;
+            if category.is_some() {
+                report.retain(|activity| Some(&activity.category) == category.as_ref());
+            }
 
-                    let category_keys = fetch_category_keys(&backend, &schemas).await;
-                    let resolved =
-                        resolve_query(llm.as_ref(), &schemas, &indexes, &category_keys, &query)
-                            .await
-                            .map_err(|e| format!("Query resolution failed: {e}"))?;
+            if cli.json {
+                print_json(&report)?;
+            } else if report.is_empty() {
+                eprintln!("No categories found.");
+            } else {
+                println!(
+                    "  {:<16} {:>8} {:>8}  {:<20} {:<20}",
+                    "category", "reads", "writes", "last read", "last write"
+                );
+                let date = |at: &Option<String>| {
+                    at.as_deref()
+                        .map(|at| at.get(..19).unwrap_or(at).replace('T', " "))
+                        .unwrap_or_else(|| "never".to_string())
+                };
+                for activity in &report {
+                    println!(
+                        "  {:<16} {:>8} {:>8}  {:<20} {:<20}",
+                        activity.category,
+                        activity.reads,
+                        activity.writes,
+                        date(&activity.last_read_at),
+                        date(&activity.last_write_at)
+                    );
+                }
+                if let Some(since) = report.iter().filter_map(|a| a.since.as_deref()).min() {
+                    eprintln!("Counted since {}", since.get(..10).unwrap_or(since));
+                }
+            }
+        }
+        Some(Command::Stats {
+            category,
+            size: true,
+            ..
+        }) => {
+            // Expired items take space until pruned, so they are always counted.
+            let categories = match category {
+                Some(ref cat) => vec![cat.clone()],
+                None => backend.list_categories().await?,
+            };
+            let mut sizes = Vec::with_capacity(categories.len());
+            for cat in categories {
+                let items = backend.query(&cat, None, SCORE_SCAN_LIMIT).await?;
+                if !items.is_empty() || category.is_some() {
+                    sizes.push((cat, size_stats(&items)));
+                }
+            }
+            sizes.sort_by(|(a, x), (b, y)| y.bytes.cmp(&x.bytes).then_with(|| a.cmp(b)));
 
-                    let (items, _) = execute_with_fallback(&backend, &resolved, 20).await?;
-                    let items = if cli.include_expired {
-                        items
-                    } else {
-                        filter_expired(items)
-                    };
+            if cli.json {
+                let output: Vec<Value> = sizes
+                    .iter()
+                    .map(|(cat, stats)| {
+                        let mut entry = serde_json::to_value(stats).unwrap_or_default();
+                        entry["category"] = Value::String(cat.clone());
+                        entry
+                    })
+                    .collect();
+                print_json(&output)?;
+            } else if sizes.is_empty() {
+                eprintln!("No categories found.");
+            } else {
+                println!(
+                    "  {:<16} {:>6} {:>10}  largest",
+                    "category", "items", "size"
+                );
+                for (cat, stats) in &sizes {
+                    let largest = stats
+                        .largest
+                        .iter()
+                        .map(|item| format!("{} ({})", item.key, format_bytes(item.bytes)))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    println!(
+                        "  {:<16} {:>6} {:>10}  {largest}",
+                        cat,
+                        stats.items,
+                        format_bytes(stats.bytes)
+                    );
+                }
+                let total: usize = sizes.iter().map(|(_, stats)| stats.bytes).sum();
+                eprintln!("Total: {}", format_bytes(total));
+            }
+        }
+        Some(Command::Stats { category, .. }) => {
+            let categories = schema_manager
+                .selected_categories(category.as_deref())
+                .await?;
+            let now = chrono::Utc::now();
+            let mut stats = Vec::with_capacity(categories.len());
+            for cat in categories {
+                let items = backend.query(&cat, None, SCORE_SCAN_LIMIT).await?;
+                let items = if cli.include_expired {
+                    items
+                } else {
+                    filter_expired(items)
+                };
+                stats.push((cat, score_distribution(&items, now)));
+            }
 
-                    if cli.json {
-                        println!("{}", serde_json::to_string_pretty(&items)?);
-                    } else if items.is_empty() {
-                        eprintln!("No memories found.");
+            if cli.json {
+                let output: Vec<Value> = stats
+                    .iter()
+                    .map(|(cat, dist)| {
+                        let mut entry = serde_json::to_value(dist).unwrap_or_default();
+                        entry["category"] = Value::String(cat.clone());
+                        entry
+                    })
+                    .collect();
+                print_json(&output)?;
+            } else if stats.is_empty() {
+                eprintln!("No categories found.");
+            } else {
+                println!(
+                    "  {:<16} {:>6} {:>16} {:>16} {:>6}",
+                    "category", "items", "importance 1-5", "score 0-1", "mean"
+                );
+                for (cat, dist) in &stats {
+                    let importance = join_counts(
+                        (MIN_IMPORTANCE..=MAX_IMPORTANCE)
+                            .map(|level| dist.by_importance.get(&level).copied().unwrap_or(0)),
+                    );
+                    println!(
+                        "  {:<16} {:>6} {:>16} {:>16} {:>6.2}",
+                        cat,
+                        dist.items,
+                        importance,
+                        join_counts(dist.by_score),
+                        dist.mean_score
+                    );
+                }
+            }
+        }
+        Some(Command::Cost { since }) => {
+            let window = parse_ttl(&since).map_err(CliError::invalid_input)?;
+            let items = load_usage(&backend, Some(window)).await?;
+            let summary = summarize_usage(&items);
+            let total_cost: f64 = summary.values().map(|u| u.cost_usd).sum();
+            let unpriced_calls: u64 = summary.values().map(|u| u.unpriced_calls).sum();
+
+            if cli.json {
+                let features: serde_json::Map<String, Value> = summary
+                    .iter()
+                    .map(|(feature, u)| (feature.clone(), serde_json::to_value(u).unwrap()))
+                    .collect();
+                print_json(&serde_json::json!({
+                    "since": since,
+                    "features": features,
+                    "total_cost_usd": total_cost,
+                    "unpriced_calls": unpriced_calls,
+                }))?;
+            } else if summary.is_empty() {
+                eprintln!("No LLM usage recorded in the last {since}.");
+            } else {
+                println!("LLM usage (last {since}):");
+                println!(
+                    "  {:<10} {:>6} {:>12} {:>12} {:>10}",
+                    "feature", "calls", "input", "output", "cost"
+                );
+                for (feature, u) in &summary {
+                    let cost = if u.unpriced_calls == u.usage.calls {
+                        "-".to_string()
                     } else {
-                        match answer_query(llm.as_ref(), &query, &items).await {
-                            Ok(Some(answer)) => println!("{answer}"),
-                            Ok(None) => eprintln!("No relevant memories found."),
-                            Err(_) => {
-                                // LLM synthesis failed — fall back to raw items.
-                                format_items(&items);
-                            }
-                        }
-                    }
+                        format!("${:.4}", u.cost_usd)
+                    };
+                    println!(
+                        "  {:<10} {:>6} {:>12} {:>12} {:>10}",
+                        feature, u.usage.calls, u.usage.input_tokens, u.usage.output_tokens, cost
+                    );
+                }
+                println!("  Total estimated cost: ${total_cost:.4}");
+                if unpriced_calls > 0 {
+                    println!(
+                        "  {unpriced_calls} call(s) used a model without a known price and are \
+                         not in the cost."
+                    );
                 }
             }
         }
+        #[cfg(feature = "llm")]
+        Some(Command::Watch {
+            webhooks,
+            events_within,
+            expiring_within,
+            interval,
+            once,
+        }) => {
+            if webhooks.is_empty() {
+                return Err(CliError::usage(
+                    "No notification targets: pass at least one --webhook URL",
+                )
+                .into());
+            }
+            let client = webhook_client()?;
+            let channels: Vec<Channel> = webhooks
+                .into_iter()
+                .map(|url| Channel::Webhook {
+                    client: client.clone(),
+                    url,
+                })
+                .collect();
+            run_notifier(
+                &backend,
+                &channels,
+                Some(parse_ttl(&events_within).map_err(CliError::invalid_input)?),
+                Some(parse_ttl(&expiring_within).map_err(CliError::invalid_input)?),
+                parse_ttl(&interval)
+                    .map_err(CliError::invalid_input)?
+                    .to_std()?,
+                once,
+            )
+            .await?;
+        }
+        Some(Command::Remind {
+            ahead,
+            interval,
+            once,
+        }) => {
+            run_notifier(
+                &backend,
+                &[Channel::Desktop],
+                Some(parse_ttl(&ahead).map_err(CliError::invalid_input)?),
+                None,
+                parse_ttl(&interval)
+                    .map_err(CliError::invalid_input)?
+                    .to_std()?,
+                once,
+            )
+            .await?;
+        }
+        Some(Command::Serve {
+            namespace: serve_ns,
+            lock_namespace,
+            client_namespaces,
+            shared_namespace,
+            read_only,
+            tools,
+            rate_limit,
+            listen,
+            tenants,
+            #[cfg(feature = "llm")]
+            nl_tools,
+        }) => {
+            // Use serve-specific namespace, falling back to global namespace.
+            for ns in [&serve_ns, &shared_namespace].into_iter().flatten() {
+                validate_namespace(ns).map_err(CliError::usage)?;
+            }
+            let ns = serve_ns.or(namespace);
+            let policy = if lock_namespace {
+                NamespacePolicy::Locked
+            } else {
+                NamespacePolicy::Open
+            };
+            let mut builder = MemoryServer::builder(backend.clone())
+                .namespace(ns)
+                .namespace_policy(policy)
+                .client_namespaces(client_namespaces)
+                .shared_namespace(shared_namespace)
+                .tool_profile(tools.into())
+                .read_only(read_only)
+                .usage(usage.clone());
+            if let Some(max_calls) = rate_limit {
+                builder = builder.rate_limit(RateLimit {
+                    max_calls,
+                    per: std::time::Duration::from_secs(60),
+                });
+            }
+            // Without an LLM the structured tools still work; the natural
+            // language ones answer with a capability-missing error.
+            #[cfg(feature = "llm")]
+            if nl_tools {
+                match require_llm(&usage) {
+                    Ok(llm) => builder = builder.llm(llm),
+                    Err(e) => eprintln!("Warning: {e} Natural language tools are disabled."),
+                }
+            }
+            if let Some(path) = &tenants {
+                builder = builder.tenants(TenantMap::load(path).map_err(CliError::usage)?);
+            }
+            match listen {
+                Some(addr) => {
+                    eprintln!("Serving MCP at http://{addr}/mcp");
+                    ferridyn_memory::mcp::run_mcp_http_server(builder.build(), addr).await?;
+                }
+                None => ferridyn_memory::mcp::run_mcp_server(builder.build()).await?,
+            }
+        }
+        Some(Command::ServeUi { listen, token }) => {
+            let token = token
+                .or_else(|| std::env::var(API_TOKEN_ENV).ok())
+                .filter(|t| !t.is_empty())
+                .unwrap_or_else(generate_token);
+            auto_init(&backend, &schema_manager).await?;
+            let router = ui_router(HttpState::new(backend.clone(), namespace.clone()), &token);
+            eprintln!("Serving dashboard at http://{listen}/#token={token}");
+            ferridyn_memory::http::serve(router, listen)
+                .await
+                .map_err(|e| CliError::context(&format!("Failed to serve on {listen}"), &e))?;
+        }
+        Some(Command::ServeApi { listen, token }) => {
+            let token = token
+                .or_else(|| std::env::var(API_TOKEN_ENV).ok())
+                .filter(|t| !t.is_empty())
+                .ok_or_else(|| {
+                    CliError::usage(
+                        "serve-api requires a token: pass --token or set FMEMORY_API_TOKEN",
+                    )
+                })?;
+            auto_init(&backend, &schema_manager).await?;
+            let router = with_token_auth(
+                api_router(HttpState::new(backend.clone(), namespace.clone())),
+                &token,
+            );
+            eprintln!("Serving API at http://{listen}/api");
+            ferridyn_memory::http::serve(router, listen)
+                .await
+                .map_err(|e| CliError::context(&format!("Failed to serve on {listen}"), &e))?;
+        }
+        None => {
+            let input = match cli.prompt {
+                Some(ref p) => p.clone(),
+                None => {
+                    op_log.set_outcome(Outcome::Ok);
+                    Cli::parse_from(["fmemory", "--help"]);
+                    return Ok(());
+                }
+            };
+
+            run_prompt(
+                &backend,
+                &schema_manager,
+                &usage,
+                &input,
+                RecallFormat::new(cli.json, cli.raw),
+                cli.include_expired,
+            )
+            .await?;
+        }
     }
 
     Ok(())
 }
 
 // ============================================================================
-// Resolved Query Execution
+// Natural Language Flows
 // ============================================================================
 
-/// Execute a resolved query against the backend.
-async fn execute_resolved_query(
-    backend: &MemoryBackend,
-    resolved: &ResolvedQuery,
+/// Message for natural language features in builds without the `llm` feature.
+#[cfg(not(feature = "llm"))]
+const LLM_UNAVAILABLE: &str =
+    "This fmemory build has no natural language support (built without the `llm` feature).";
+
+/// Environment variable making natural language recall print raw items by
+/// default, like `--raw`.
+const RAW_ENV: &str = "FMEMORY_RAW";
+
+/// How natural language recall prints its results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RecallFormat {
+    /// A synthesized answer, falling back to the items if synthesis fails.
+    Answer,
+    /// The recalled items, formatted, without an answer synthesis call.
+    Raw,
+    /// The recalled items as JSON.
+    Json,
+}
+
+impl RecallFormat {
+    /// The format for `--json` and `--raw`; `FMEMORY_RAW=1` (or `true`)
+    /// stands in for `--raw`.
+    fn new(json: bool, raw: bool) -> Self {
+        let raw_env = std::env::var(RAW_ENV)
+            .is_ok_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"));
+        if json {
+            Self::Json
+        } else if raw || raw_env {
+            Self::Raw
+        } else {
+            Self::Answer
+        }
+    }
+}
+
+/// Resolve a natural language `--query` and print the items (`--json`,
+/// `--raw`) or a synthesized answer.
+#[cfg(feature = "llm")]
+async fn recall_query(
+    store: MemoryStore,
+    usage: &Arc<UsageTracker>,
+    query: &str,
     limit: usize,
-) -> Result<Vec<Value>, Box<dyn std::error::Error>> {
-    match resolved {
-        ResolvedQuery::IndexLookup {
-            index_name,
-            key_value,
-            ..
-        } => {
-            let items = backend
-                .query_index(index_name, Value::String(key_value.clone()), Some(limit))
-                .await
-                .map_err(|e| e.to_string())?;
-            Ok(items)
+    format: RecallFormat,
+    by_score: bool,
+    with_answer: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let llm = require_llm(usage)?;
+    print_recall(store, llm, query, limit, format, by_score, with_answer).await
+}
+
+#[cfg(not(feature = "llm"))]
+async fn recall_query(
+    _store: MemoryStore,
+    _usage: &Arc<UsageTracker>,
+    _query: &str,
+    _limit: usize,
+    _format: RecallFormat,
+    _by_score: bool,
+    _with_answer: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Err(CliError::new(ErrorCode::Llm, LLM_UNAVAILABLE).into())
+}
+
+/// Shared recall output for `recall --query` and prompt mode. `store`
+/// carries the read options (expired items, related items). JSON output
+/// includes a synthesized answer only `with_answer`.
+#[cfg(feature = "llm")]
+async fn print_recall(
+    store: MemoryStore,
+    llm: Arc<dyn LlmClient>,
+    query: &str,
+    limit: usize,
+    format: RecallFormat,
+    by_score: bool,
+    with_answer: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let store = store.with_llm(llm);
+    let mut resolved = match store.resolve_recall(query, limit).await {
+        Err(StoreError::NoSchemas) => {
+            return Err(
+                "No schemas defined. Use --category instead, or define schemas first.".into(),
+            );
         }
-        ResolvedQuery::PartitionScan {
-            category,
-            key_prefix,
-        } => {
-            let items = backend
-                .query(category, key_prefix.as_deref(), limit)
+        result => result.map_err(|e| CliError::context("Query resolution failed", &e))?,
+    };
+    if by_score {
+        sort_by_score(&mut resolved.items, chrono::Utc::now());
+    }
+    let items = &resolved.items;
+
+    let mut answer = None;
+    if format != RecallFormat::Json && resolved.fallback {
+        eprintln!("No direct match; showing what a broader search found.");
+    }
+    if format == RecallFormat::Json {
+        let mut output = serde_json::json!({"fallback_used": resolved.fallback, "items": items});
+        if with_answer {
+            let synthesized = store
+                .answer(query, items)
                 .await
-                .map_err(|e| e.to_string())?;
-            Ok(items)
+                .map_err(|e| CliError::context("Answer synthesis failed", &e))?;
+            let (text, follow_up) = synthesized
+                .map(|a| (Some(a.text), a.follow_up))
+                .unwrap_or_default();
+            output["answer"] = serde_json::json!(text);
+            output["follow_up"] = serde_json::json!(follow_up);
+            answer = text;
         }
-        ResolvedQuery::ExactLookup { category, key } => {
-            let item = backend
-                .get_item(category, key)
-                .await
-                .map_err(|e| e.to_string())?;
-            Ok(item.into_iter().collect())
+        print_json(&output)?;
+    } else if items.is_empty() {
+        eprintln!("No memories found.");
+    } else if format == RecallFormat::Raw {
+        format_items(items);
+    } else {
+        match store.answer(query, items).await {
+            Ok(Some(synthesized)) => {
+                println!("{}", synthesized.text);
+                print_follow_up(&synthesized.follow_up);
+                answer = Some(synthesized.text);
+            }
+            Ok(None) => eprintln!("No relevant memories found."),
+            Err(_) => {
+                // LLM synthesis failed — fall back to raw items.
+                format_items(items);
+            }
         }
     }
+    store.log_recall(query, &resolved, answer.as_deref()).await;
+    Ok(())
 }
 
-/// Execute a resolved query with broadening fallback.
-///
-/// If the initial query returns no results, falls back to scanning the entire
-/// category. Returns `(items, is_fallback)`.
-async fn execute_with_fallback(
-    backend: &MemoryBackend,
-    resolved: &ResolvedQuery,
-    limit: usize,
-) -> Result<(Vec<Value>, bool), Box<dyn std::error::Error>> {
-    let items = execute_resolved_query(backend, resolved, limit).await?;
-    if !items.is_empty() {
-        return Ok((items, false));
+/// Print the follow-up suggestions of a partial answer as commands to run.
+#[cfg(feature = "llm")]
+fn print_follow_up(follow_up: &FollowUp) {
+    if follow_up.is_empty() {
+        return;
+    }
+    eprintln!("\nTo find out more:");
+    for query in &follow_up.queries {
+        eprintln!("  fmemory recall --query {query:?}");
+    }
+    for category in &follow_up.categories {
+        eprintln!("  fmemory recall --category {category}");
     }
+}
 
-    // Already a full category scan — no broader fallback possible.
-    if matches!(
-        resolved,
-        ResolvedQuery::PartitionScan {
-            key_prefix: None,
-            ..
+/// Move `item` into `target_category`, re-parsing its content against the
+/// target schema. The promoted item has no TTL.
+#[cfg(feature = "llm")]
+async fn promote_to_category(
+    backend: &MemoryBackend,
+    schema_manager: &SchemaManager,
+    usage: &Arc<UsageTracker>,
+    item: &Value,
+    target_category: &str,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let category = item["category"].as_str().unwrap_or_default();
+    let key = item["key"].as_str().unwrap_or_default();
+
+    // Re-categorize: re-parse content against target schema.
+    let llm = require_llm(usage)?;
+    auto_init(backend, schema_manager).await?;
+
+    let snapshot = schema_manager.snapshot().await?;
+    let schema_info = snapshot.get(target_category).ok_or_else(|| {
+        CliError::invalid_input(format!("Schema for '{}' not found", target_category))
+    })?;
+
+    // Use item's content (or all string attributes) as input for re-parsing.
+    let input_text = item["content"]
+        .as_str()
+        .unwrap_or_else(|| {
+            item.as_object()
+                .and_then(|obj| {
+                    obj.iter()
+                        .filter(|(k, v)| {
+                            *k != "category"
+                                && *k != "key"
+                                && *k != "created_at"
+                                && *k != "expires_at"
+                                && v.is_string()
+                        })
+                        .map(|(_, v)| v.as_str().unwrap_or(""))
+                        .next()
+                })
+                .unwrap_or("")
+        })
+        .to_string();
+
+    let mut doc = parse_to_document(
+        llm.as_ref(),
+        target_category,
+        schema_info,
+        &snapshot.aliases,
+        snapshot.hints_of(target_category),
+        &input_text,
+    )
+    .await
+    .map_err(|e| CliError::context("Document parsing failed", &e))?;
+    take_ttl(&mut doc);
+    let new_key = doc["key"].as_str().unwrap_or(key).to_string();
+
+    // Build promoted item without expires_at.
+    let mut promoted = serde_json::json!({
+        "category": target_category,
+        "key": new_key,
+    });
+    if let Some(obj) = doc.as_object() {
+        for (k, v) in obj {
+            if k == "key" || k == "category" {
+                continue;
+            }
+            promoted[k] = v.clone();
         }
-    ) {
-        return Ok((items, false));
+    }
+    promoted["created_at"] = Value::String(chrono::Utc::now().to_rfc3339());
+    // Explicitly remove expires_at (promotion = LTM).
+    if let Some(obj) = promoted.as_object_mut() {
+        obj.remove("expires_at");
     }
 
-    let category = resolved_category(resolved);
-    let fallback_items = backend
-        .query(category, None, limit)
-        .await
-        .map_err(|e| e.to_string())?;
-    let has_results = !fallback_items.is_empty();
-    Ok((fallback_items, has_results))
+    backend.put_item(promoted.clone()).await?;
+    backend.delete_item(category, key).await?;
+
+    if json {
+        print_json(&serde_json::json!({
+            "promoted": true,
+            "from": format!("{category}/{key}"),
+            "to": format!("{target_category}/{new_key}"),
+        }))?;
+    } else {
+        eprintln!("Promoted {category}/{key} → {target_category}/{new_key}");
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "llm"))]
+async fn promote_to_category(
+    _backend: &MemoryBackend,
+    _schema_manager: &SchemaManager,
+    _usage: &Arc<UsageTracker>,
+    _item: &Value,
+    _target_category: &str,
+    _json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Err(CliError::new(ErrorCode::Llm, LLM_UNAVAILABLE).into())
 }
 
-/// Extract the category from any resolved query variant.
-fn resolved_category(resolved: &ResolvedQuery) -> &str {
-    match resolved {
-        ResolvedQuery::IndexLookup { category, .. }
-        | ResolvedQuery::PartitionScan { category, .. }
-        | ResolvedQuery::ExactLookup { category, .. } => category,
+/// Handle `-p/--prompt`: classify the input, then remember or recall it.
+#[cfg(feature = "llm")]
+async fn run_prompt(
+    backend: &MemoryBackend,
+    schema_manager: &SchemaManager,
+    usage: &Arc<UsageTracker>,
+    input: &str,
+    format: RecallFormat,
+    include_expired: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let llm = require_llm(usage).map_err(|e| {
+        CliError::new(
+            e.code,
+            format!(
+                "{e}\n\n-p/--prompt needs an LLM provider (FMEMORY_LLM_PROVIDER and its \
+                 API key). Use explicit subcommands (discover, recall, remember, ...) \
+                 to work without one."
+            ),
+        )
+    })?;
+
+    // Auto-init predefined schemas.
+    auto_init(backend, schema_manager).await?;
+
+    // Classify intent: remember or recall.
+    let intent = classify_intent(llm.as_ref(), input)
+        .await
+        .map_err(|e| CliError::context("Intent classification failed", &e))?;
+
+    match intent {
+        NlIntent::Remember { content } => {
+            // Let LLM pick category from available schemas.
+            let item = MemoryStore::new(backend.clone())
+                .with_llm(llm)
+                .remember(&content)
+                .await?;
+            if format == RecallFormat::Json {
+                print_json(&item)?;
+            } else {
+                report_stored(&item, item["chunk_count"].as_u64().unwrap_or(0) as usize);
+            }
+        }
+        NlIntent::Recall { query } => {
+            let store = MemoryStore::new(backend.clone()).with_include_expired(include_expired);
+            print_recall(store, llm, &query, 20, format, false, false).await?;
+        }
     }
+    Ok(())
+}
+
+#[cfg(not(feature = "llm"))]
+async fn run_prompt(
+    _backend: &MemoryBackend,
+    _schema_manager: &SchemaManager,
+    _usage: &Arc<UsageTracker>,
+    _input: &str,
+    _format: RecallFormat,
+    _include_expired: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Err(CliError::new(ErrorCode::Llm, LLM_UNAVAILABLE).into())
 }
 
 // ============================================================================
 // Helpers
 // ============================================================================
 
-/// Fetch a sample of sort keys for each category (for query resolution context).
-async fn fetch_category_keys(
+#[cfg(feature = "llm")]
+/// Categories `ingest-git` may store extracted memories in.
+const GIT_INGEST_CATEGORIES: &[&str] = &["project", "decisions"];
+
+#[cfg(feature = "llm")]
+/// Commits sent to the LLM per extraction call.
+const GIT_INGEST_BATCH_SIZE: usize = 20;
+
+#[cfg(feature = "llm")]
+/// Print the "Stored ..." status line listing non-null attribute names.
+fn report_stored(item: &Value, chunk_count: usize) {
+    let category = item["category"].as_str().unwrap_or("?");
+    let key = item["key"].as_str().unwrap_or("?");
+    let attr_names: Vec<&str> = item
+        .as_object()
+        .map(|obj| {
+            obj.iter()
+                .filter(|(k, v)| {
+                    !matches!(
+                        k.as_str(),
+                        "category" | "key" | "created_at" | "expires_at" | "chunk_count"
+                    ) && !v.is_null()
+                })
+                .map(|(k, _)| k.as_str())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if attr_names.is_empty() {
+        eprintln!("Stored {category}/{key}");
+    } else {
+        eprintln!("Stored {category}/{key} ({})", attr_names.join(", "));
+    }
+    if chunk_count > 0 {
+        eprintln!("  Full text stored in {chunk_count} chunks");
+    }
+}
+
+#[cfg(feature = "llm")]
+/// Print which items were evicted to keep the stored item's category within
+/// its quota.
+fn report_evicted(item: &Value, evicted: &[String]) {
+    if evicted.is_empty() {
+        return;
+    }
+    let category = item["category"].as_str().unwrap_or("?");
+    eprintln!(
+        "  Evicted {} item(s) from {category} to stay within its quota: {}",
+        evicted.len(),
+        evicted.join(", ")
+    );
+}
+
+/// Tell the user which stored items a new memory contradicts.
+#[cfg(feature = "llm")]
+fn report_conflicts(conflicts: &[String]) {
+    if conflicts.is_empty() {
+        return;
+    }
+    eprintln!(
+        "  Conflicts with: {} (see `fmemory conflicts`)",
+        conflicts.join(", ")
+    );
+}
+
+/// Walk the user through `items` on stdin: keep, update, forget, skip, or quit.
+async fn review_items(
     backend: &MemoryBackend,
-    schemas: &[PartitionSchemaInfo],
-) -> Vec<(String, Vec<String>)> {
-    let mut result = Vec::new();
-    for schema in schemas {
-        let keys = backend
-            .list_sort_key_prefixes(&schema.prefix, 20)
-            .await
-            .unwrap_or_default()
+    items: &[Value],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let total = items.len();
+    for (i, item) in items.iter().enumerate() {
+        let category = item["category"].as_str().unwrap_or_default();
+        let key = item["key"].as_str().unwrap_or_default();
+        let item = hydrate_chunks(backend, vec![item.clone()])
+            .await?
             .into_iter()
-            .filter_map(|v| v.as_str().map(|s| s.to_string()))
-            .collect();
-        result.push((schema.prefix.clone(), keys));
+            .next()
+            .unwrap_or_else(|| item.clone());
+        println!();
+        println!("[{}/{total}]", i + 1);
+        format_item(&item);
+        loop {
+            let Some(answer) = prompt_line("[k]eep, [u]pdate, [f]orget, [s]kip, [q]uit? ")? else {
+                return Ok(());
+            };
+            match answer.as_str() {
+                "k" | "keep" => {
+                    mark_reviewed(backend, category, key).await?;
+                    eprintln!("Kept {category}/{key}");
+                }
+                "u" | "update" => {
+                    let mut patch = serde_json::Map::new();
+                    eprintln!("Enter attribute=value lines; an empty line finishes.");
+                    while let Some(line) = prompt_line("  ")? {
+                        if line.is_empty() {
+                            break;
+                        }
+                        match parse_assignment(&line) {
+                            Ok((attr, value)) => {
+                                patch.insert(attr, value);
+                            }
+                            Err(e) => eprintln!("  {e}"),
+                        }
+                    }
+                    backend
+                        .update_item(category, key, &patch)
+                        .await?
+                        .ok_or_else(|| format!("{category}/{key} no longer exists"))?;
+                    mark_reviewed(backend, category, key).await?;
+                    eprintln!("Updated {category}/{key}");
+                }
+                "f" | "forget" => {
+                    delete_chunks(backend, &item).await?;
+                    backend.delete_item(category, key).await?;
+                    eprintln!("Forgot {category}/{key}");
+                }
+                "s" | "skip" => {}
+                "q" | "quit" => return Ok(()),
+                _ => continue,
+            }
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Ask the LLM to design a schema for `request`, for `define --infer`.
+#[cfg(feature = "llm")]
+async fn propose_definition(
+    usage: &Arc<UsageTracker>,
+    request: &str,
+) -> Result<(String, SchemaDefinition), Box<dyn std::error::Error>> {
+    let llm = require_llm(usage)?;
+    Ok(propose_schema(llm.as_ref(), request)
+        .await
+        .map_err(|e| CliError::context("Schema proposal failed", &e))?)
+}
+
+#[cfg(not(feature = "llm"))]
+async fn propose_definition(
+    _usage: &Arc<UsageTracker>,
+    _request: &str,
+) -> Result<(String, SchemaDefinition), Box<dyn std::error::Error>> {
+    Err(CliError::new(ErrorCode::Llm, LLM_UNAVAILABLE).into())
+}
+
+/// Show a schema definition before it is created, in the layout of
+/// `fmemory schema --category`.
+fn print_definition(category: &str, definition: &SchemaDefinition) {
+    println!("Category: {category}");
+    println!("Description: {}", definition.description);
+    if let Some(hints) = &definition.hints {
+        println!("Hints: {hints}");
+    }
+    println!("Attributes:");
+    for attr in &definition.attributes {
+        let req = if attr.required { ", required" } else { "" };
+        println!("  - {} ({}{})", attr.name, attr.attr_type, req);
     }
-    result
+    if !definition.suggested_indexes.is_empty() {
+        println!("Indexes:");
+        for name in &definition.suggested_indexes {
+            println!("  - {category}_{name} ({name})");
+        }
+    }
+}
+
+/// Print `prompt` to stderr and read one trimmed line from stdin. `None` at
+/// end of input.
+fn prompt_line(prompt: &str) -> std::io::Result<Option<String>> {
+    use std::io::Write;
+    eprint!("{prompt}");
+    std::io::stderr().flush()?;
+    let mut line = String::new();
+    if std::io::stdin().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+/// Parse an `attribute=value` assignment. JSON literals keep their type;
+/// anything else is text.
+fn parse_assignment(assignment: &str) -> Result<(String, Value), String> {
+    let Some((attr, value)) = assignment.split_once('=') else {
+        return Err(format!("Expected attribute=value, got '{assignment}'."));
+    };
+    let attr = attr.trim();
+    if attr.is_empty() {
+        return Err(format!("Missing attribute name in '{assignment}'."));
+    }
+    if matches!(attr, "category" | "key") {
+        return Err(format!("{attr} cannot be changed."));
+    }
+    let value = value.trim();
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    Ok((attr.to_string(), value))
+}
+
+/// Render counts as `1/0/4`, as in `fmemory stats`.
+fn join_counts(counts: impl IntoIterator<Item = usize>) -> String {
+    counts
+        .into_iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join("/")
 }
 
 /// Ensure predefined schemas exist. Called transparently on first use.
@@ -1122,10 +4087,8 @@ async fn auto_init(
 ) -> Result<(), Box<dyn std::error::Error>> {
     let schemas = schema_manager.list_schemas().await.unwrap_or_default();
     if schemas.is_empty() {
-        backend
-            .ensure_predefined_schemas()
-            .await
-            .map_err(|e| e.to_string())?;
+        backend.ensure_predefined_schemas().await?;
+        schema_manager.invalidate().await;
         eprintln!(
             "Initialized {} predefined categories.",
             PREDEFINED_SCHEMAS.len()
@@ -1134,36 +4097,114 @@ async fn auto_init(
     Ok(())
 }
 
+#[cfg(feature = "llm")]
 /// Create an LLM client from environment, or error if not available.
-fn require_llm() -> Result<Arc<dyn LlmClient>, String> {
-    let client = AnthropicClient::from_env()
-        .map_err(|e| format!("{e}. Set ANTHROPIC_API_KEY for natural language queries."))?;
-    Ok(Arc::new(client))
+///
+/// Calls are tallied into `usage` so token spend can be persisted per command.
+///
+/// The provider is chosen by `FMEMORY_LLM_PROVIDER` (see [`client_from_env`]).
+/// With `FMEMORY_LLM_FIXTURES` set, completions are recorded to or (by
+/// default) replayed from that directory; replay needs no API key. With
+/// `FMEMORY_LANGUAGE` set, prompts are adapted to that language.
+fn require_llm(usage: &Arc<UsageTracker>) -> Result<Arc<dyn LlmClient>, CliError> {
+    let fixtures = std::env::var(FIXTURES_ENV).ok();
+    let mode = match std::env::var(FIXTURE_MODE_ENV) {
+        Ok(mode) => mode.parse().map_err(CliError::invalid_input)?,
+        Err(_) => FixtureMode::Replay,
+    };
+    let client: Arc<dyn LlmClient> = match fixtures {
+        Some(dir) if mode == FixtureMode::Replay => Arc::new(FixtureLlmClient::replay(dir)),
+        fixtures => {
+            let client = client_from_env().map_err(|e| {
+                CliError::new(
+                    ErrorCode::Llm,
+                    format!(
+                        "{e}. Set ANTHROPIC_API_KEY, FMEMORY_LLM_PROVIDER=openai and \
+                         OPENAI_API_KEY, or FMEMORY_LLM_PROVIDER=ollama for a local model, \
+                         for natural language queries."
+                    ),
+                )
+            })?;
+            match fixtures {
+                Some(dir) => Arc::new(FixtureLlmClient::record(client, dir)),
+                None => client,
+            }
+        }
+    };
+    let client = TimeoutLlmClient::wrap(LanguageLlmClient::from_env(client), &Timeouts::from_env());
+    Ok(Arc::new(TrackedLlmClient::new(client, usage.clone())))
 }
 
-/// Connect to the ferridyn-server socket. Errors if the server is not available.
-async fn connect_backend(table_name: &str) -> Result<MemoryBackend, Box<dyn std::error::Error>> {
-    let socket_path = resolve_socket_path();
+/// Read a backup archive written by `fmemory backup create`.
+fn read_backup(path: &std::path::Path) -> Result<Backup, CliError> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| CliError::context(&format!("Failed to open {}", path.display()), &e))?;
+    serde_json::from_reader(std::io::BufReader::new(file)).map_err(|e| {
+        CliError::invalid_input(format!("{} is not a backup archive: {e}", path.display()))
+    })
+}
 
-    if !socket_path.exists() {
-        return Err(format!(
-            "ferridyn-server socket not found at {}. Start the server with: ferridyn-server",
-            socket_path.display()
-        )
-        .into());
+fn print_backup_diff(diff: &[CategoryDiff]) {
+    if diff.is_empty() {
+        eprintln!("No differences.");
+        return;
     }
+    for category in diff {
+        println!(
+            "{}: +{} -{} ~{}",
+            category.category,
+            category.added.len(),
+            category.removed.len(),
+            category.changed.len()
+        );
+        for key in &category.added {
+            println!("  + {key}");
+        }
+        for key in &category.removed {
+            println!("  - {key}");
+        }
+        for item in &category.changed {
+            println!("  ~ {} ({})", item.key, item.attributes.join(", "));
+        }
+    }
+}
 
-    let mut client = ferridyn_server::FerridynClient::connect(&socket_path)
-        .await
-        .map_err(|e| {
-            format!(
-                "Failed to connect to ferridyn-server at {}: {e}",
-                socket_path.display()
-            )
-        })?;
-    ensure_memories_table_via_server(&mut client, table_name).await?;
-    Ok(MemoryBackend::server(
-        Arc::new(Mutex::new(client)),
-        table_name.to_string(),
-    ))
+/// Scan for due items and deliver them on `channels` every `interval` until
+/// interrupted (or once, with `once`). Scan errors end a single run but are
+/// only reported while looping.
+async fn run_notifier(
+    backend: &MemoryBackend,
+    channels: &[Channel],
+    events_within: Option<chrono::Duration>,
+    expiring_within: Option<chrono::Duration>,
+    interval: std::time::Duration,
+    once: bool,
+) -> Result<(), String> {
+    loop {
+        let scan = async {
+            let due = scan_due(backend, events_within, expiring_within, chrono::Utc::now()).await?;
+            deliver_due(backend, &due, channels).await
+        }
+        .instrument(tracing::info_span!("notify.scan", request_id = %new_request_id()));
+
+        match scan.await {
+            Ok(report) => {
+                for failure in &report.failures {
+                    eprintln!("Delivery failed ({failure}); will retry");
+                }
+                if report.delivered > 0 {
+                    eprintln!("Delivered {} notification(s)", report.delivered);
+                }
+            }
+            Err(e) if once => return Err(e.to_string()),
+            Err(e) => eprintln!("Scan failed: {e}"),
+        }
+        if once {
+            return Ok(());
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
 }
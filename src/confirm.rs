@@ -0,0 +1,166 @@
+//! User confirmation of destructive MCP tool calls.
+//!
+//! Deleting by key prefix, pruning, and forcing `memory_init` act on many
+//! items at once on the word of an agent. When the MCP client supports
+//! elicitation, [`confirm`] shows the user a summary of what the call is
+//! about to do and asks them to approve it first; declining, cancelling, or
+//! not answering within [`CONFIRM_TIMEOUT`] stops the call. Clients without
+//! elicitation are not asked, and the call proceeds as before.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use rmcp::model::{CreateElicitationRequestParam, ElicitationAction, ElicitationSchema};
+use rmcp::service::{Peer, RoleServer};
+
+use crate::backend::MemoryBackend;
+use crate::chunk::is_chunk;
+use crate::error::MemoryError;
+use crate::ttl::is_expired;
+
+/// Field of the elicited form the user ticks to approve.
+pub const CONFIRM_FIELD: &str = "confirm";
+
+/// How long the user has to answer before the call is abandoned.
+pub const CONFIRM_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Maximum number of item references listed in a summary.
+pub const SUMMARY_REF_LIMIT: usize = 10;
+
+/// Maximum number of items scanned per category when counting expired ones.
+const EXPIRED_SCAN_LIMIT: usize = 10_000;
+
+/// What came of asking the user to confirm a call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Confirmation {
+    /// The client cannot ask the user; the call proceeds unconfirmed.
+    Unavailable,
+    /// The user approved the call.
+    Confirmed,
+    /// The user did not approve the call, for the given reason.
+    Refused(&'static str),
+}
+
+impl Confirmation {
+    /// Whether the call may go ahead.
+    pub fn proceeds(&self) -> bool {
+        !matches!(self, Confirmation::Refused(_))
+    }
+}
+
+/// Ask the user behind `peer` to approve `summary`, if the client supports
+/// elicitation.
+pub async fn confirm(peer: Option<&Peer<RoleServer>>, summary: &str) -> Confirmation {
+    let Some(peer) = peer.filter(|peer| peer.supports_elicitation()) else {
+        return Confirmation::Unavailable;
+    };
+    let Ok(schema) = ElicitationSchema::builder()
+        .required_bool_with(CONFIRM_FIELD, |field| {
+            field
+                .title("Confirm")
+                .description("Tick to go ahead; this cannot be undone")
+        })
+        .build()
+    else {
+        return Confirmation::Refused("invalid confirmation form");
+    };
+    let request = CreateElicitationRequestParam {
+        message: summary.to_string(),
+        requested_schema: schema,
+    };
+    match peer
+        .create_elicitation_with_timeout(request, Some(CONFIRM_TIMEOUT))
+        .await
+    {
+        Ok(result) => match result.action {
+            ElicitationAction::Accept
+                if result
+                    .content
+                    .as_ref()
+                    .and_then(|content| content[CONFIRM_FIELD].as_bool())
+                    == Some(true) =>
+            {
+                Confirmation::Confirmed
+            }
+            ElicitationAction::Accept => Confirmation::Refused("not confirmed"),
+            ElicitationAction::Decline => Confirmation::Refused("declined"),
+            ElicitationAction::Cancel => Confirmation::Refused("cancelled"),
+        },
+        Err(e) => {
+            tracing::warn!(error = %e, "confirmation request failed");
+            Confirmation::Refused("no answer")
+        }
+    }
+}
+
+/// `refs` as a readable list, the first [`SUMMARY_REF_LIMIT`] named and the
+/// rest counted: `notes/a, notes/b, and 3 more`.
+pub fn describe_refs(refs: &[String]) -> String {
+    let shown = &refs[..refs.len().min(SUMMARY_REF_LIMIT)];
+    let mut text = shown.join(", ");
+    if refs.len() > shown.len() {
+        text.push_str(&format!(", and {} more", refs.len() - shown.len()));
+    }
+    text
+}
+
+/// Number of expired items in each of `categories` that has any, as
+/// `prune_expired` would delete them.
+pub async fn expired_counts(
+    backend: &MemoryBackend,
+    categories: &[String],
+) -> Result<BTreeMap<String, usize>, MemoryError> {
+    let mut counts = BTreeMap::new();
+    for category in categories {
+        let expired = backend
+            .query(category, None, EXPIRED_SCAN_LIMIT)
+            .await?
+            .iter()
+            .filter(|item| !is_chunk(item) && is_expired(item))
+            .count();
+        if expired > 0 {
+            counts.insert(category.clone(), expired);
+        }
+    }
+    Ok(counts)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    #[test]
+    fn test_describe_refs() {
+        let refs: Vec<String> = (1..=12).map(|i| format!("notes/n{i}")).collect();
+        assert_eq!(describe_refs(&refs[..2]), "notes/n1, notes/n2");
+        assert!(describe_refs(&refs).ends_with("notes/n10, and 2 more"));
+    }
+
+    #[tokio::test]
+    async fn test_expired_counts_and_unavailable_confirmation() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for (key, expires_at) in [
+            ("old", "2020-01-01T00:00:00+00:00"),
+            ("older", "2019-01-01T00:00:00+00:00"),
+            ("fresh", "2999-01-01T00:00:00+00:00"),
+        ] {
+            backend
+                .put_item(json!({"category": "scratchpad", "key": key, "expires_at": expires_at}))
+                .await
+                .unwrap();
+        }
+        let categories = ["scratchpad".to_string(), "notes".to_string()];
+        let counts = expired_counts(&backend, &categories).await.unwrap();
+        assert_eq!(counts, BTreeMap::from([("scratchpad".to_string(), 2)]));
+
+        assert_eq!(confirm(None, "Prune?").await, Confirmation::Unavailable);
+        assert!(Confirmation::Unavailable.proceeds());
+        assert!(!Confirmation::Refused("declined").proceeds());
+    }
+}
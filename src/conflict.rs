@@ -0,0 +1,292 @@
+//! Contradictions between memories.
+//!
+//! When a parsed memory is stored, the newest items of its category are sent
+//! to the LLM with it ([`flag_conflicts`]). Items it contradicts ("prefers
+//! tabs" vs a stored "prefers spaces") are linked both ways through
+//! [`CONFLICTS_ATTRIBUTE`], a list of `category/key` references, and
+//! `fmemory conflicts` lists them until one side is re-stored or forgotten:
+//! only links recorded on both sides count.
+
+use std::collections::HashSet;
+
+use serde_json::Value;
+#[cfg(feature = "llm")]
+use tracing::warn;
+
+use crate::backend::MemoryBackend;
+use crate::chunk::is_chunk;
+use crate::error::MemoryError;
+#[cfg(feature = "llm")]
+use crate::llm::LlmClient;
+#[cfg(feature = "llm")]
+use crate::schema::detect_conflicts;
+use crate::ttl::is_expired;
+
+/// Attribute listing the `category/key` of each item an item contradicts.
+pub const CONFLICTS_ATTRIBUTE: &str = "conflicts_with";
+
+/// Maximum number of existing items checked against a new one.
+pub const CONFLICT_CANDIDATE_LIMIT: usize = 30;
+
+/// Maximum number of items read per category when looking for conflicts.
+pub const CONFLICT_SCAN_LIMIT: usize = 10_000;
+
+/// `category/key` of an item.
+pub fn item_ref(item: &Value) -> Option<String> {
+    Some(format!(
+        "{}/{}",
+        item["category"].as_str()?,
+        item["key"].as_str()?
+    ))
+}
+
+/// References in an item's [`CONFLICTS_ATTRIBUTE`].
+pub fn conflicts(item: &Value) -> Vec<String> {
+    item[CONFLICTS_ATTRIBUTE]
+        .as_array()
+        .map(|refs| {
+            refs.iter()
+                .filter_map(|r| r.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Add `reference` to an item's conflicts. Returns `false` if already there.
+fn add_conflict(item: &mut Value, reference: &str) -> bool {
+    let mut refs = conflicts(item);
+    if refs.iter().any(|r| r == reference) {
+        return false;
+    }
+    refs.push(reference.to_string());
+    item[CONFLICTS_ATTRIBUTE] = Value::from(refs);
+    true
+}
+
+/// Live items from `item`'s category it could contradict: the newest
+/// [`CONFLICT_CANDIDATE_LIMIT`], excluding the item itself and chunks.
+pub async fn conflict_candidates(
+    backend: &MemoryBackend,
+    item: &Value,
+) -> Result<Vec<Value>, MemoryError> {
+    let (Some(category), Some(key)) = (item["category"].as_str(), item["key"].as_str()) else {
+        return Ok(Vec::new());
+    };
+    let mut candidates: Vec<Value> = backend
+        .query(category, None, CONFLICT_SCAN_LIMIT)
+        .await?
+        .into_iter()
+        .filter(|c| !is_chunk(c) && !is_expired(c) && c["key"].as_str() != Some(key))
+        .collect();
+    candidates.sort_by(|a, b| {
+        let created_at = |item: &Value| item["created_at"].as_str().unwrap_or_default().to_string();
+        created_at(b).cmp(&created_at(a))
+    });
+    candidates.truncate(CONFLICT_CANDIDATE_LIMIT);
+    Ok(candidates)
+}
+
+/// Link `item` and each of `keys` (in the same category) as conflicting,
+/// writing both sides. Keys that no longer exist are skipped.
+///
+/// Returns the references added to `item`.
+pub async fn record_conflicts(
+    backend: &MemoryBackend,
+    item: &mut Value,
+    keys: &[String],
+) -> Result<Vec<String>, MemoryError> {
+    let (Some(category), Some(own_ref)) = (item["category"].as_str(), item_ref(item)) else {
+        return Ok(Vec::new());
+    };
+    let category = category.to_string();
+    let mut added = Vec::new();
+    for key in keys {
+        let Some(mut other) = backend.get_item(&category, key).await? else {
+            continue;
+        };
+        if add_conflict(&mut other, &own_ref) {
+            let mut patch = serde_json::Map::new();
+            patch.insert(
+                CONFLICTS_ATTRIBUTE.into(),
+                other[CONFLICTS_ATTRIBUTE].clone(),
+            );
+            backend.update_item(&category, key, &patch).await?;
+        }
+        let other_ref = format!("{category}/{key}");
+        if add_conflict(item, &other_ref) {
+            added.push(other_ref);
+        }
+    }
+    if !added.is_empty() {
+        backend.put_item(item.clone()).await?;
+    }
+    Ok(added)
+}
+
+/// Ask the LLM whether a just-stored `item` contradicts existing items in its
+/// category, and link any it does. Failures are logged rather than failing
+/// the store.
+///
+/// Returns the references added to `item`.
+#[cfg(feature = "llm")]
+pub async fn flag_conflicts(
+    backend: &MemoryBackend,
+    llm: &dyn LlmClient,
+    item: &mut Value,
+) -> Vec<String> {
+    let result = async {
+        let candidates = conflict_candidates(backend, item).await?;
+        let keys = detect_conflicts(llm, item, &candidates)
+            .await
+            .map_err(|e| MemoryError::Internal(e.to_string()))?;
+        record_conflicts(backend, item, &keys).await
+    };
+    match result.await {
+        Ok(added) => added,
+        Err(e) => {
+            warn!(
+                "Conflict check failed for {}: {e}",
+                item_ref(item).unwrap_or_default()
+            );
+            Vec::new()
+        }
+    }
+}
+
+/// Items in `categories` with at least one live conflict.
+///
+/// A link only counts while both sides record it, so references to items
+/// that were forgotten, or re-stored without the link, are dropped from the
+/// returned items' [`CONFLICTS_ATTRIBUTE`].
+pub async fn list_conflicts(
+    backend: &MemoryBackend,
+    categories: &[String],
+) -> Result<Vec<Value>, MemoryError> {
+    let mut flagged = Vec::new();
+    for category in categories {
+        let items: Vec<Value> = backend
+            .query(category, None, CONFLICT_SCAN_LIMIT)
+            .await?
+            .into_iter()
+            .filter(|item| !is_chunk(item) && !conflicts(item).is_empty())
+            .collect();
+        let links: HashSet<(String, String)> = items
+            .iter()
+            .filter_map(|item| Some((item_ref(item)?, conflicts(item))))
+            .flat_map(|(own, refs)| refs.into_iter().map(move |r| (own.clone(), r)))
+            .collect();
+        for mut item in items {
+            let own = item_ref(&item).unwrap_or_default();
+            let live: Vec<String> = conflicts(&item)
+                .into_iter()
+                .filter(|other| links.contains(&(other.clone(), own.clone())))
+                .collect();
+            if !live.is_empty() {
+                item[CONFLICTS_ATTRIBUTE] = Value::from(live);
+                flagged.push(item);
+            }
+        }
+    }
+    Ok(flagged)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    async fn backend_with(items: Vec<Value>) -> MemoryBackend {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for item in items {
+            backend.put_item(item).await.unwrap();
+        }
+        backend
+    }
+
+    #[tokio::test]
+    async fn test_conflict_candidates_newest_first() {
+        let backend = backend_with(vec![
+            json!({"category": "preferences", "key": "old", "created_at": "2026-01-01T00:00:00Z"}),
+            json!({"category": "preferences", "key": "new", "created_at": "2026-02-01T00:00:00Z"}),
+            json!({"category": "preferences", "key": "gone", "expires_at": "2020-01-01T00:00:00Z"}),
+            json!({"category": "preferences", "key": "new#1", "chunk_of": "new"}),
+            json!({"category": "preferences", "key": "self"}),
+        ])
+        .await;
+        let item = json!({"category": "preferences", "key": "self"});
+        let candidates = conflict_candidates(&backend, &item).await.unwrap();
+        let keys: Vec<&str> = candidates
+            .iter()
+            .filter_map(|i| i["key"].as_str())
+            .collect();
+        assert_eq!(keys, vec!["new", "old"]);
+    }
+
+    #[tokio::test]
+    async fn test_record_and_list_conflicts() {
+        let backend = backend_with(vec![
+            json!({"category": "preferences", "key": "spaces", "preference": "prefers spaces"}),
+        ])
+        .await;
+        let mut item =
+            json!({"category": "preferences", "key": "tabs", "preference": "prefers tabs"});
+        backend.put_item(item.clone()).await.unwrap();
+
+        let keys = vec!["spaces".to_string(), "missing".to_string()];
+        let added = record_conflicts(&backend, &mut item, &keys).await.unwrap();
+        assert_eq!(added, vec!["preferences/spaces"]);
+        // Recording again is a no-op.
+        assert!(
+            record_conflicts(&backend, &mut item, &keys)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let spaces = backend
+            .get_item("preferences", "spaces")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(conflicts(&spaces), vec!["preferences/tabs"]);
+
+        let flagged = list_conflicts(&backend, &["preferences".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(flagged.len(), 2);
+
+        // Once one side is forgotten, the other's link is stale.
+        backend.delete_item("preferences", "tabs").await.unwrap();
+        let flagged = list_conflicts(&backend, &["preferences".to_string()])
+            .await
+            .unwrap();
+        assert!(flagged.is_empty());
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_flag_conflicts_survives_llm_errors() {
+        use crate::llm::MockLlmClient;
+
+        let backend = backend_with(vec![json!({"category": "preferences", "key": "spaces"})]).await;
+        let mut item = json!({"category": "preferences", "key": "tabs"});
+        backend.put_item(item.clone()).await.unwrap();
+
+        let garbled = MockLlmClient::new(vec!["not json".into()]);
+        assert!(
+            flag_conflicts(&backend, &garbled, &mut item)
+                .await
+                .is_empty()
+        );
+
+        let mock = MockLlmClient::new(vec![r#"["spaces"]"#.into()]);
+        let added = flag_conflicts(&backend, &mock, &mut item).await;
+        assert_eq!(added, vec!["preferences/spaces"]);
+        assert_eq!(item[CONFLICTS_ATTRIBUTE], json!(["preferences/spaces"]));
+    }
+}
@@ -0,0 +1,214 @@
+//! Context packs: the memories worth pasting into a tool that is not
+//! MCP-aware, as one compact block within a token budget.
+//!
+//! [`build_context_pack`] puts every live `preferences` item first, best
+//! [`decay_score`] first, and includes them even past the budget. The items
+//! of the other categories follow, ranked by keyword overlap with the topic,
+//! if one is given (items sharing no keyword with it are left out), and then
+//! by decay score, until the next one no longer fits. Each item is one line,
+//! `- category/key: attribute: value; ...`, without the attributes fmemory
+//! manages itself. Token counts use [`estimate_tokens`].
+
+use std::cmp::Ordering;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::backend::MemoryBackend;
+use crate::budget::{estimate_tokens, overlap, terms};
+use crate::chunk::is_chunk;
+use crate::error::MemoryError;
+use crate::infer::MANAGED_ATTRIBUTES;
+use crate::interaction::INTERACTIONS_CATEGORY;
+use crate::score::{IMPORTANCE_ATTRIBUTE, LAST_ACCESSED_ATTRIBUTE, decay_score};
+use crate::ttl::is_expired;
+
+/// Default token budget of `fmemory context`.
+pub const DEFAULT_CONTEXT_BUDGET: usize = 2000;
+
+/// Category whose items are always included.
+pub const PREFERENCES_CATEGORY: &str = "preferences";
+
+/// Maximum number of items read per category.
+pub const CONTEXT_SCAN_LIMIT: usize = 10_000;
+
+/// Heading of every context block.
+const CONTEXT_HEADING: &str = "# Memory context";
+
+/// Attributes left out of context lines besides [`MANAGED_ATTRIBUTES`].
+const OMITTED_ATTRIBUTES: &[&str] = &[IMPORTANCE_ATTRIBUTE, LAST_ACCESSED_ATTRIBUTE];
+
+/// A context block and what went into it.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ContextPack {
+    /// The block to paste.
+    pub text: String,
+    /// Estimated tokens of `text`.
+    pub tokens: usize,
+    /// `category/key` of each included item, in block order.
+    pub items: Vec<String>,
+    /// Candidates left out for lack of budget.
+    pub omitted: usize,
+}
+
+/// The context block for `categories` and optional `topic`, within `budget`
+/// estimated tokens apart from preferences.
+pub async fn build_context_pack(
+    backend: &MemoryBackend,
+    categories: &[String],
+    topic: Option<&str>,
+    budget: usize,
+) -> Result<ContextPack, MemoryError> {
+    let now = chrono::Utc::now();
+    let topic_terms = topic.map(terms).unwrap_or_default();
+    let mut preferences = Vec::new();
+    let mut ranked = Vec::new();
+    for category in categories {
+        if category == INTERACTIONS_CATEGORY {
+            continue;
+        }
+        let items = backend.query(category, None, CONTEXT_SCAN_LIMIT).await?;
+        for item in items
+            .into_iter()
+            .filter(|item| !is_chunk(item) && !is_expired(item))
+        {
+            let line = context_line(&item);
+            let score = decay_score(&item, now);
+            if category == PREFERENCES_CATEGORY {
+                preferences.push((0, score, line));
+                continue;
+            }
+            let relevance = overlap(&topic_terms, &line.1);
+            if topic_terms.is_empty() || relevance > 0 {
+                ranked.push((relevance, score, line));
+            }
+        }
+    }
+    preferences.sort_by(by_rank);
+    ranked.sort_by(by_rank);
+
+    let mut pack = ContextPack {
+        text: format!("{CONTEXT_HEADING}\n"),
+        ..Default::default()
+    };
+    for (_, _, line) in preferences {
+        pack.add(line);
+    }
+    let mut ranked = ranked.into_iter();
+    for (_, _, line) in ranked.by_ref() {
+        if pack.tokens + estimate_tokens(&line.1) + 1 > budget {
+            pack.omitted = 1;
+            break;
+        }
+        pack.add(line);
+    }
+    pack.omitted += ranked.count();
+    Ok(pack)
+}
+
+/// A candidate line: topic relevance, decay score, and the line.
+type Candidate = (usize, f64, (String, String));
+
+/// Most relevant first, then highest scoring.
+fn by_rank(a: &Candidate, b: &Candidate) -> Ordering {
+    b.0.cmp(&a.0).then(b.1.total_cmp(&a.1))
+}
+
+impl ContextPack {
+    fn add(&mut self, line: (String, String)) {
+        let (item_ref, text) = line;
+        self.text.push_str(&text);
+        self.text.push('\n');
+        self.tokens = estimate_tokens(&self.text);
+        self.items.push(item_ref);
+    }
+}
+
+/// `category/key` of `item` and its line in a context block.
+fn context_line(item: &Value) -> (String, String) {
+    let item_ref = format!(
+        "{}/{}",
+        item["category"].as_str().unwrap_or("?"),
+        item["key"].as_str().unwrap_or("?")
+    );
+    let line = format!("- {item_ref}: {}", item_summary(item));
+    (item_ref, line.trim_end().to_string())
+}
+
+/// The attributes of `item` that describe it, as `attribute: value; ...`.
+pub(crate) fn item_summary(item: &Value) -> String {
+    item.as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(name, value)| {
+            !value.is_null()
+                && !name.starts_with('_')
+                && !MANAGED_ATTRIBUTES.contains(&name.as_str())
+                && !OMITTED_ATTRIBUTES.contains(&name.as_str())
+        })
+        .map(|(name, value)| match value {
+            Value::String(s) => format!("{name}: {s}"),
+            other => format!("{name}: {other}"),
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_build_context_pack() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for doc in [
+            json!({"category": "preferences", "key": "editor", "value": "neovim", "importance": 2}),
+            json!({"category": "decisions", "key": "auth", "decision": "JWT for auth", "importance": 5}),
+            json!({"category": "decisions", "key": "db", "decision": "Postgres", "importance": 4}),
+            json!({"category": "notes", "key": "lunch", "content": "Tacos on Friday", "importance": 1}),
+            json!({"category": "interactions", "key": "q1", "query": "auth"}),
+        ] {
+            backend.put_item(doc).await.unwrap();
+        }
+        let categories: Vec<String> = ["preferences", "decisions", "notes", "interactions"]
+            .map(String::from)
+            .to_vec();
+
+        let pack = build_context_pack(&backend, &categories, None, 2000)
+            .await
+            .unwrap();
+        assert_eq!(
+            pack.items,
+            [
+                "preferences/editor",
+                "decisions/auth",
+                "decisions/db",
+                "notes/lunch"
+            ]
+        );
+        assert!(pack.text.starts_with(CONTEXT_HEADING));
+        assert!(
+            pack.text
+                .contains("- decisions/auth: decision: JWT for auth\n")
+        );
+        assert!(!pack.text.contains("importance"));
+        assert_eq!(pack.omitted, 0);
+
+        let topical = build_context_pack(&backend, &categories, Some("auth tokens"), 2000)
+            .await
+            .unwrap();
+        assert_eq!(topical.items, ["preferences/editor", "decisions/auth"]);
+
+        let tight = build_context_pack(&backend, &categories, None, 1)
+            .await
+            .unwrap();
+        assert_eq!(tight.items, ["preferences/editor"]);
+        assert_eq!(tight.omitted, 3);
+    }
+}
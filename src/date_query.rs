@@ -0,0 +1,309 @@
+//! Deterministic resolution of date-bearing calendar queries.
+//!
+//! "What's on my calendar tomorrow" names a category (`events`) and a date,
+//! which is all the `events_date` index needs; asking the LLM to resolve it
+//! costs a round trip and, too often, a scan of every event. [`pre_resolve`]
+//! recognizes such queries without the LLM: a calendar word ("calendar",
+//! "meeting", "appointment", ...) together with a date phrase ("today",
+//! "tomorrow", "day after tomorrow", "on Friday", "next Monday", "this week",
+//! "next weekend", "in 3 days", or an ISO date). One day becomes an index
+//! lookup, several an [`IndexRange`](ResolvedQuery::IndexRange) looked up a
+//! day at a time. Anything else is left to the LLM resolver.
+//!
+//! Weekdays and weeks follow the [`DateContext`]: its timezone decides what
+//! today is, its week start where "this week" begins.
+
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+use crate::IndexInfo;
+use crate::locale::DateContext;
+use crate::schema::ResolvedQuery;
+
+/// Category holding dated calendar entries.
+pub const EVENTS_CATEGORY: &str = "events";
+
+/// Attribute of [`EVENTS_CATEGORY`] holding the `YYYY-MM-DD` date.
+pub const EVENTS_DATE_ATTRIBUTE: &str = "date";
+
+/// Longest span, in days, resolved to an index range.
+pub const MAX_RANGE_DAYS: u64 = 31;
+
+/// Words marking a query as being about the calendar.
+const CALENDAR_WORDS: &[&str] = &[
+    "calendar",
+    "schedule",
+    "scheduled",
+    "agenda",
+    "event",
+    "events",
+    "meeting",
+    "meetings",
+    "appointment",
+    "appointments",
+    "happening",
+    "planned",
+    "booked",
+];
+
+/// An inclusive span of days.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateSpan {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl DateSpan {
+    fn day(date: NaiveDate) -> Self {
+        Self {
+            from: date,
+            to: date,
+        }
+    }
+
+    /// Every day of the span, in order.
+    pub fn days(&self) -> impl Iterator<Item = NaiveDate> {
+        self.from.iter_days().take_while(|day| *day <= self.to)
+    }
+}
+
+/// Resolve `query` to a lookup in the events date index, if it is a
+/// calendar query with a recognizable date and the index exists.
+pub fn pre_resolve(
+    query: &str,
+    indexes: &[IndexInfo],
+    context: &DateContext,
+    today: NaiveDate,
+) -> Option<ResolvedQuery> {
+    let index = indexes.iter().find(|i| {
+        i.partition_schema == EVENTS_CATEGORY && i.index_key_name == EVENTS_DATE_ATTRIBUTE
+    })?;
+    let words = words(query);
+    if !words.iter().any(|w| CALENDAR_WORDS.contains(&w.as_str())) && !mentions_whats_on(&words) {
+        return None;
+    }
+    let span = date_span(&words, context.week_start(), today)?;
+    let days = span.to.signed_duration_since(span.from).num_days();
+    if !(0..MAX_RANGE_DAYS as i64).contains(&days) {
+        return None;
+    }
+    let (category, index_name) = (EVENTS_CATEGORY.to_string(), index.name.clone());
+    Some(if span.from == span.to {
+        ResolvedQuery::IndexLookup {
+            category,
+            index_name,
+            key_value: format_date(span.from),
+        }
+    } else {
+        ResolvedQuery::IndexRange {
+            category,
+            index_name,
+            from: format_date(span.from),
+            to: format_date(span.to),
+        }
+    })
+}
+
+/// The dates `query` refers to, relative to `today`.
+pub fn date_span(words: &[String], week_start: Weekday, today: NaiveDate) -> Option<DateSpan> {
+    let has = |phrase: &[&str]| words.windows(phrase.len()).any(|w| w == phrase);
+    if has(&["day", "after", "tomorrow"]) {
+        return today.checked_add_days(Days::new(2)).map(DateSpan::day);
+    }
+    if has(&["day", "before", "yesterday"]) {
+        return today.checked_sub_days(Days::new(2)).map(DateSpan::day);
+    }
+    for word in words {
+        if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+            return Some(DateSpan::day(date));
+        }
+    }
+    for (i, word) in words.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|p| words[p].as_str());
+        match word.as_str() {
+            "today" | "tonight" => return Some(DateSpan::day(today)),
+            "tomorrow" => return today.checked_add_days(Days::new(1)).map(DateSpan::day),
+            "yesterday" => return today.checked_sub_days(Days::new(1)).map(DateSpan::day),
+            "week" | "weekend" => {
+                let offset = match previous {
+                    Some("this") => 0,
+                    Some("next") => 1,
+                    Some("last") => -1,
+                    _ => continue,
+                };
+                let start = week_of(today, week_start, offset)?;
+                return if word == "week" {
+                    Some(DateSpan {
+                        from: start,
+                        to: start.checked_add_days(Days::new(6))?,
+                    })
+                } else {
+                    let saturday = next_weekday(start, Weekday::Sat);
+                    Some(DateSpan {
+                        from: saturday,
+                        to: saturday.checked_add_days(Days::new(1))?,
+                    })
+                };
+            }
+            "days" if previous.is_some() && i >= 2 && words[i - 2] == "in" => {
+                let n: u64 = previous?.parse().ok()?;
+                return today.checked_add_days(Days::new(n)).map(DateSpan::day);
+            }
+            _ => {}
+        }
+        if let Some(day) = weekday(word) {
+            let date = match previous {
+                Some("next") => next_weekday(week_of(today, week_start, 1)?, day),
+                Some("last") => next_weekday(week_of(today, week_start, -1)?, day),
+                _ => next_weekday(today, day),
+            };
+            return Some(DateSpan::day(date));
+        }
+    }
+    None
+}
+
+/// Lowercased words of `query`, split on anything but letters, digits,
+/// `-`, and `'`.
+fn words(query: &str) -> Vec<String> {
+    query
+        .to_lowercase()
+        .split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '\'' || c == '’'))
+        .filter(|w| !w.is_empty())
+        .map(|w| w.trim_matches('-').replace('’', "'"))
+        .collect()
+}
+
+/// Whether the words contain "what's on" (or "whats on").
+fn mentions_whats_on(words: &[String]) -> bool {
+    words
+        .windows(2)
+        .any(|w| matches!(w[0].as_str(), "what's" | "whats") && w[1] == "on")
+}
+
+fn weekday(word: &str) -> Option<Weekday> {
+    let day = match word {
+        "monday" => Weekday::Mon,
+        "tuesday" => Weekday::Tue,
+        "wednesday" => Weekday::Wed,
+        "thursday" => Weekday::Thu,
+        "friday" => Weekday::Fri,
+        "saturday" => Weekday::Sat,
+        "sunday" => Weekday::Sun,
+        _ => return None,
+    };
+    Some(day)
+}
+
+/// The first `day` on or after `from`.
+fn next_weekday(from: NaiveDate, day: Weekday) -> NaiveDate {
+    let ahead = (7 + day.num_days_from_monday() - from.weekday().num_days_from_monday()) % 7;
+    from + Days::new(ahead.into())
+}
+
+/// First day of the week `offset` weeks from the one holding `today`.
+fn week_of(today: NaiveDate, week_start: Weekday, offset: i64) -> Option<NaiveDate> {
+    let into_week =
+        (7 + today.weekday().num_days_from_monday() - week_start.num_days_from_monday()) % 7;
+    let start = today.checked_sub_days(Days::new(into_week.into()))?;
+    if offset >= 0 {
+        start.checked_add_days(Days::new(7 * offset as u64))
+    } else {
+        start.checked_sub_days(Days::new(7 * offset.unsigned_abs()))
+    }
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events_index() -> IndexInfo {
+        IndexInfo {
+            name: "events_date".into(),
+            partition_schema: EVENTS_CATEGORY.into(),
+            index_key_name: EVENTS_DATE_ATTRIBUTE.into(),
+            index_key_type: "STRING".into(),
+        }
+    }
+
+    fn resolve(query: &str) -> Option<ResolvedQuery> {
+        // A Wednesday.
+        let today = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+        let context = DateContext::default().with_week_start(Weekday::Mon);
+        pre_resolve(query, &[events_index()], &context, today)
+    }
+
+    fn lookup(query: &str) -> Option<String> {
+        match resolve(query)? {
+            ResolvedQuery::IndexLookup { key_value, .. } => Some(key_value),
+            other => panic!("expected a lookup for {query:?}, got {other:?}"),
+        }
+    }
+
+    fn range(query: &str) -> Option<(String, String)> {
+        match resolve(query)? {
+            ResolvedQuery::IndexRange { from, to, .. } => Some((from, to)),
+            other => panic!("expected a range for {query:?}, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_pre_resolve_calendar_dates() {
+        assert_eq!(
+            lookup("What's on my calendar tomorrow?").as_deref(),
+            Some("2026-03-05")
+        );
+        assert_eq!(lookup("what’s on today").as_deref(), Some("2026-03-04"));
+        assert_eq!(
+            lookup("meetings the day after tomorrow").as_deref(),
+            Some("2026-03-06")
+        );
+        assert_eq!(
+            lookup("any meetings on Friday").as_deref(),
+            Some("2026-03-06")
+        );
+        assert_eq!(
+            lookup("appointments on Wednesday").as_deref(),
+            Some("2026-03-04")
+        );
+        assert_eq!(lookup("events next Monday").as_deref(), Some("2026-03-09"));
+        assert_eq!(lookup("events in 10 days").as_deref(), Some("2026-03-14"));
+        assert_eq!(
+            lookup("events on 2026-04-01").as_deref(),
+            Some("2026-04-01")
+        );
+        assert_eq!(
+            range("what's scheduled this week"),
+            Some(("2026-03-02".into(), "2026-03-08".into()))
+        );
+        assert_eq!(
+            range("my agenda next weekend"),
+            Some(("2026-03-14".into(), "2026-03-15".into()))
+        );
+    }
+
+    #[test]
+    fn test_pre_resolve_leaves_other_queries_to_the_llm() {
+        assert!(resolve("Toby's email").is_none());
+        assert!(resolve("what did we decide yesterday").is_none());
+        assert!(resolve("upcoming meetings").is_none());
+        let today = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+        assert!(pre_resolve("meetings tomorrow", &[], &DateContext::default(), today).is_none());
+    }
+
+    #[test]
+    fn test_week_follows_week_start() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+        let words = words("events this week");
+        let span = date_span(&words, Weekday::Sun, today).unwrap();
+        assert_eq!(span.from, NaiveDate::from_ymd_opt(2026, 3, 1).unwrap());
+        assert_eq!(span.days().count(), 7);
+    }
+}
@@ -0,0 +1,292 @@
+//! Daily digests of short-term memories.
+//!
+//! `fmemory digest` gathers a day's [`DIGEST_CATEGORIES`] entries, has the LLM
+//! condense them, and stores the summary as a long-term `notes` item keyed by
+//! [`digest_key`]. The raw entries can then be pruned: the digest keeps what
+//! mattered after their TTLs would have dropped it anyway. Digesting a day
+//! again merges the entries created since into its existing digest
+//! ([`undigested`]), so a second `digest --prune` loses nothing.
+
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde_json::Value;
+
+use crate::backend::MemoryBackend;
+use crate::chunk::{delete_chunks, is_chunk};
+use crate::error::MemoryError;
+use crate::store::build_item;
+
+/// Categories whose entries are digested.
+pub const DIGEST_CATEGORIES: &[&str] = &["scratchpad", "sessions", "interactions"];
+
+/// Category digests are stored in.
+pub const DIGEST_CATEGORY: &str = "notes";
+
+/// `source` of stored digests.
+pub const DIGEST_SOURCE: &str = "digest";
+
+/// Maximum number of items read per category when gathering a day.
+pub const DIGEST_SCAN_LIMIT: usize = 10_000;
+
+/// Attributes left out of the text sent to the LLM.
+const BOOKKEEPING_ATTRIBUTES: &[&str] = &[
+    "category",
+    "key",
+    "created_at",
+    "updated_at",
+    "expires_at",
+    "last_accessed",
+    "importance",
+    "review_count",
+    "chunk_count",
+];
+
+/// Parse `today`, `yesterday`, or `YYYY-MM-DD` relative to `today`.
+pub fn parse_digest_date(s: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    match s.trim() {
+        "today" => Ok(today),
+        "yesterday" => Ok(today - Duration::days(1)),
+        other => NaiveDate::parse_from_str(other, "%Y-%m-%d").map_err(|_| {
+            format!("Invalid date '{other}' (expected today, yesterday, or YYYY-MM-DD)")
+        }),
+    }
+}
+
+/// Key of the digest for `date`, e.g. `digest-2026-03-14`.
+pub fn digest_key(date: NaiveDate) -> String {
+    format!("digest-{}", date.format("%Y-%m-%d"))
+}
+
+/// Attribute of a digest holding the `created_at` of the newest entry it
+/// covers.
+pub const DIGESTED_THROUGH_ATTRIBUTE: &str = "digested_through";
+
+/// Entries not covered yet by `earlier`, the digest already stored for their
+/// day: those created after its [`DIGESTED_THROUGH_ATTRIBUTE`]. All entries
+/// without an earlier digest.
+pub fn undigested(entries: Vec<Value>, earlier: Option<&Value>) -> Vec<Value> {
+    let Some(through) = earlier.and_then(|d| d[DIGESTED_THROUGH_ATTRIBUTE].as_str()) else {
+        return entries;
+    };
+    entries
+        .into_iter()
+        .filter(|item| item["created_at"].as_str().is_some_and(|c| c > through))
+        .collect()
+}
+
+/// Local calendar date an item was created on.
+fn created_on(item: &Value) -> Option<NaiveDate> {
+    let created_at = DateTime::parse_from_rfc3339(item["created_at"].as_str()?).ok()?;
+    Some(created_at.with_timezone(&Local).date_naive())
+}
+
+/// Entries from [`DIGEST_CATEGORIES`] created on `date` (local time), oldest
+/// first. Expired entries are included; chunks are not.
+pub async fn gather_day(
+    backend: &MemoryBackend,
+    date: NaiveDate,
+) -> Result<Vec<Value>, MemoryError> {
+    let mut entries = Vec::new();
+    for category in DIGEST_CATEGORIES {
+        let items = backend.query(category, None, DIGEST_SCAN_LIMIT).await?;
+        entries.extend(
+            items
+                .into_iter()
+                .filter(|item| !is_chunk(item) && created_on(item) == Some(date)),
+        );
+    }
+    entries.sort_by(|a, b| {
+        let created_at = |item: &Value| item["created_at"].as_str().unwrap_or_default().to_string();
+        created_at(a).cmp(&created_at(b))
+    });
+    Ok(entries)
+}
+
+/// Render entries as one line each for the LLM:
+/// `HH:MM [category/key] {attributes}`.
+pub fn digest_input(entries: &[Value]) -> String {
+    entries
+        .iter()
+        .map(|item| {
+            let time = DateTime::parse_from_rfc3339(item["created_at"].as_str().unwrap_or(""))
+                .map(|t| t.with_timezone(&Local).format("%H:%M").to_string())
+                .unwrap_or_else(|_| "--:--".to_string());
+            let mut attrs = item.clone();
+            if let Some(obj) = attrs.as_object_mut() {
+                obj.retain(|k, v| !BOOKKEEPING_ATTRIBUTES.contains(&k.as_str()) && !v.is_null());
+            }
+            format!(
+                "{time} [{}/{}] {attrs}",
+                item["category"].as_str().unwrap_or("?"),
+                item["key"].as_str().unwrap_or("?"),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The `notes` item holding the digest for `date`, replacing `earlier` when
+/// `summary` merges it: entry counts add up and its `created_at` is kept.
+pub fn build_digest_item(
+    date: NaiveDate,
+    summary: &str,
+    entries: &[Value],
+    earlier: Option<&Value>,
+) -> Value {
+    let earlier_count = earlier.and_then(|d| d["entry_count"].as_u64()).unwrap_or(0);
+    let through = entries
+        .iter()
+        .filter_map(|item| item["created_at"].as_str())
+        .chain(earlier.and_then(|d| d[DIGESTED_THROUGH_ATTRIBUTE].as_str()))
+        .max();
+    let doc = serde_json::json!({
+        "topic": format!("daily digest {}", date.format("%Y-%m-%d")),
+        "content": summary,
+        "source": DIGEST_SOURCE,
+        "digest_date": date.format("%Y-%m-%d").to_string(),
+        "entry_count": earlier_count + entries.len() as u64,
+        DIGESTED_THROUGH_ATTRIBUTE: through,
+    });
+    let mut item = build_item(DIGEST_CATEGORY, &digest_key(date), &doc, None);
+    if let Some(created_at) = earlier.and_then(|d| d.get("created_at")) {
+        item["created_at"] = created_at.clone();
+    }
+    item
+}
+
+/// Delete digested entries and their chunks. Returns the `(category, key)`
+/// of each deleted entry.
+pub async fn prune_entries(
+    backend: &MemoryBackend,
+    entries: &[Value],
+) -> Result<Vec<(String, String)>, MemoryError> {
+    let mut pruned = Vec::new();
+    for item in entries {
+        let (Some(category), Some(key)) = (item["category"].as_str(), item["key"].as_str()) else {
+            continue;
+        };
+        delete_chunks(backend, item).await?;
+        backend.delete_item(category, key).await?;
+        pruned.push((category.to_string(), key.to_string()));
+    }
+    Ok(pruned)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use chrono::Utc;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_digest_date() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 14).unwrap();
+        assert_eq!(parse_digest_date("today", today).unwrap(), today);
+        assert_eq!(
+            parse_digest_date("yesterday", today).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 3, 13).unwrap()
+        );
+        assert_eq!(
+            parse_digest_date("2026-01-02", today).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 1, 2).unwrap()
+        );
+        assert!(parse_digest_date("last week", today).is_err());
+        assert_eq!(digest_key(today), "digest-2026-03-14");
+    }
+
+    #[test]
+    fn test_digest_input_and_item() {
+        let entries = vec![json!({
+            "category": "scratchpad",
+            "key": "idea",
+            "content": "try a bloom filter",
+            "created_at": Utc::now().to_rfc3339(),
+            "expires_at": Utc::now().to_rfc3339(),
+            "topic": null,
+        })];
+        let input = digest_input(&entries);
+        assert!(input.contains("[scratchpad/idea] {\"content\":\"try a bloom filter\"}"));
+
+        let date = NaiveDate::from_ymd_opt(2026, 3, 14).unwrap();
+        let item = build_digest_item(date, "Explored bloom filters.", &entries, None);
+        assert_eq!(item["category"], "notes");
+        assert_eq!(item["key"], "digest-2026-03-14");
+        assert_eq!(item["content"], "Explored bloom filters.");
+        assert_eq!(item["entry_count"], 1);
+        assert_eq!(item[DIGESTED_THROUGH_ATTRIBUTE], entries[0]["created_at"]);
+        assert!(item.get("expires_at").is_none());
+    }
+
+    #[test]
+    fn test_redigest_merges_new_entries() {
+        let entry = |key: &str, created_at: &str| json!({"category": "scratchpad", "key": key, "created_at": created_at});
+        let date = NaiveDate::from_ymd_opt(2026, 3, 14).unwrap();
+        let first = vec![entry("a", "2026-03-14T09:00:00+00:00")];
+        let mut earlier = build_digest_item(date, "- a", &first, None);
+        earlier["created_at"] = json!("2026-03-14T12:00:00+00:00");
+
+        let day = vec![
+            entry("a", "2026-03-14T09:00:00+00:00"),
+            entry("b", "2026-03-14T15:00:00+00:00"),
+        ];
+        assert_eq!(undigested(day.clone(), None).len(), 2);
+        let new = undigested(day, Some(&earlier));
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[0]["key"], "b");
+
+        let merged = build_digest_item(date, "- a\n- b", &new, Some(&earlier));
+        assert_eq!(merged["entry_count"], 2);
+        assert_eq!(
+            merged[DIGESTED_THROUGH_ATTRIBUTE],
+            "2026-03-14T15:00:00+00:00"
+        );
+        assert_eq!(merged["created_at"], "2026-03-14T12:00:00+00:00");
+    }
+
+    #[tokio::test]
+    async fn test_gather_and_prune_day() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        let now = Utc::now();
+        for (category, key, created_at, chunk_count) in [
+            ("scratchpad", "today-note", now, Some(1)),
+            ("interactions", "standup", now, None),
+            ("scratchpad", "old-note", now - Duration::days(3), None),
+            ("notes", "unrelated", now, None),
+        ] {
+            backend
+                .put_item(json!({
+                    "category": category,
+                    "key": key,
+                    "created_at": created_at.to_rfc3339(),
+                    "chunk_count": chunk_count,
+                }))
+                .await
+                .unwrap();
+        }
+        backend
+            .put_item(json!({
+                "category": "scratchpad",
+                "key": "today-note#1",
+                "chunk_of": "today-note",
+                "created_at": now.to_rfc3339(),
+            }))
+            .await
+            .unwrap();
+
+        let today = now.with_timezone(&Local).date_naive();
+        let entries = gather_day(&backend, today).await.unwrap();
+        let mut keys: Vec<&str> = entries.iter().filter_map(|i| i["key"].as_str()).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["standup", "today-note"]);
+
+        let pruned = prune_entries(&backend, &entries).await.unwrap();
+        assert_eq!(pruned.len(), 2);
+        let left = backend.query("scratchpad", None, 10).await.unwrap();
+        let keys: Vec<&str> = left.iter().filter_map(|i| i["key"].as_str()).collect();
+        assert_eq!(keys, vec!["old-note"]);
+    }
+}
@@ -17,6 +17,10 @@ pub enum MemoryError {
     InvalidParams(String),
     /// Internal error during operation.
     Internal(String),
+    /// An operation ran past its time limit (see [`crate::timeout`]).
+    Timeout(String),
+    /// The connected server lacks a feature (see [`crate::capability`]).
+    Unsupported(String),
 }
 
 impl fmt::Display for MemoryError {
@@ -28,6 +32,8 @@ impl fmt::Display for MemoryError {
             Self::Index(msg) => write!(f, "Index error: {msg}"),
             Self::InvalidParams(msg) => write!(f, "Invalid parameters: {msg}"),
             Self::Internal(msg) => write!(f, "Internal error: {msg}"),
+            Self::Timeout(msg) => write!(f, "Timed out: {msg}"),
+            Self::Unsupported(msg) => write!(f, "Not supported by ferridyn-server: {msg}"),
         }
     }
 }
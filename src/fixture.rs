@@ -0,0 +1,281 @@
+//! Record/replay of LLM completions for deterministic integration tests.
+//!
+//! [`FixtureLlmClient`] wraps an [`LlmClient`]. In record mode every completion
+//! is forwarded to the inner client and written to a JSON fixture file named
+//! after a hash of the prompt; in replay mode completions are read back from
+//! those files and no request is made, so end-to-end flows run in CI without
+//! an API key.
+//!
+//! Fixtures are keyed on the prompt with the `Today's date:` line and RFC 3339
+//! timestamps masked, so recordings stay valid on later days and for items
+//! stored at a different time.
+//!
+//! The CLI enables this through [`FIXTURES_ENV`] and [`FIXTURE_MODE_ENV`].
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::ingest::sha256_hex;
+use crate::llm::{Completion, LlmClient, LlmError, LlmFeature, Usage};
+
+/// Environment variable naming the fixture directory.
+pub const FIXTURES_ENV: &str = "FMEMORY_LLM_FIXTURES";
+
+/// Environment variable selecting `record` or `replay` (default `replay`).
+pub const FIXTURE_MODE_ENV: &str = "FMEMORY_LLM_FIXTURE_MODE";
+
+/// Whether fixtures are written or read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureMode {
+    /// Forward to the inner client and save each response.
+    Record,
+    /// Serve responses from saved fixtures only.
+    Replay,
+}
+
+impl std::str::FromStr for FixtureMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "record" => Ok(FixtureMode::Record),
+            "replay" => Ok(FixtureMode::Replay),
+            other => Err(format!(
+                "Unknown fixture mode '{other}' (expected record or replay)"
+            )),
+        }
+    }
+}
+
+/// A recorded request/response pair, stored as one JSON file.
+#[derive(Debug, Serialize, Deserialize)]
+struct Fixture {
+    feature: String,
+    system: String,
+    user: String,
+    text: String,
+    usage: Usage,
+}
+
+/// LLM client that records completions to, or replays them from, a directory.
+pub struct FixtureLlmClient {
+    inner: Option<Arc<dyn LlmClient>>,
+    dir: PathBuf,
+}
+
+impl FixtureLlmClient {
+    /// Forward completions to `inner`, saving each under `dir`.
+    pub fn record(inner: Arc<dyn LlmClient>, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            inner: Some(inner),
+            dir: dir.into(),
+        }
+    }
+
+    /// Serve completions from fixtures under `dir`; unrecorded prompts fail.
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            inner: None,
+            dir: dir.into(),
+        }
+    }
+
+    pub fn mode(&self) -> FixtureMode {
+        if self.inner.is_some() {
+            FixtureMode::Record
+        } else {
+            FixtureMode::Replay
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Fixture path for a prompt. Prompts equal up to dates share a fixture.
+    fn fixture_path(&self, system: &str, user: &str) -> PathBuf {
+        let key = format!("{}\0{}", normalize_prompt(system), normalize_prompt(user));
+        let digest = sha256_hex(key.as_bytes());
+        self.dir.join(format!("{}.json", &digest[..16]))
+    }
+}
+
+/// Drop the `Today's date:` line and mask RFC 3339 timestamps.
+fn normalize_prompt(prompt: &str) -> String {
+    let without_date: Vec<&str> = prompt
+        .lines()
+        .filter(|line| !line.starts_with("Today's date:"))
+        .collect();
+    mask_timestamps(&without_date.join("\n"))
+}
+
+/// Replace each `YYYY-MM-DDThh:mm:ss...` run with `<timestamp>`.
+fn mask_timestamps(text: &str) -> String {
+    const PATTERN: &[u8] = b"dddd-dd-ddT";
+    let bytes = text.as_bytes();
+    let mut out = String::with_capacity(text.len());
+    let mut start = 0;
+    let mut i = 0;
+    while i + PATTERN.len() <= bytes.len() {
+        let is_timestamp = PATTERN.iter().zip(&bytes[i..]).all(|(p, b)| match p {
+            b'd' => b.is_ascii_digit(),
+            p => p == b,
+        });
+        if !is_timestamp {
+            i += 1;
+            continue;
+        }
+        let end = bytes[i..]
+            .iter()
+            .position(|b| !(b.is_ascii_digit() || b":.+-TZ".contains(b)))
+            .map_or(bytes.len(), |n| i + n);
+        out.push_str(&text[start..i]);
+        out.push_str("<timestamp>");
+        start = end;
+        i = end;
+    }
+    out.push_str(&text[start..]);
+    out
+}
+
+#[async_trait]
+impl LlmClient for FixtureLlmClient {
+    async fn complete(&self, system: &str, user: &str) -> Result<Completion, LlmError> {
+        self.complete_for(LlmFeature::Other, system, user).await
+    }
+
+    async fn complete_for(
+        &self,
+        feature: LlmFeature,
+        system: &str,
+        user: &str,
+    ) -> Result<Completion, LlmError> {
+        let path = self.fixture_path(system, user);
+        let Some(inner) = &self.inner else {
+            let raw = tokio::fs::read_to_string(&path).await.map_err(|e| {
+                LlmError::Fixture(format!(
+                    "no recorded {} completion at {}: {e}",
+                    feature.as_str(),
+                    path.display()
+                ))
+            })?;
+            let fixture: Fixture = serde_json::from_str(&raw)
+                .map_err(|e| LlmError::Fixture(format!("{}: {e}", path.display())))?;
+            return Ok(Completion {
+                text: fixture.text,
+                usage: fixture.usage,
+            });
+        };
+
+        let completion = inner.complete_for(feature, system, user).await?;
+        let fixture = Fixture {
+            feature: feature.as_str().to_string(),
+            system: system.to_string(),
+            user: user.to_string(),
+            text: completion.text.clone(),
+            usage: completion.usage,
+        };
+        let json = serde_json::to_string_pretty(&fixture).unwrap();
+        let write = async {
+            tokio::fs::create_dir_all(&self.dir).await?;
+            tokio::fs::write(&path, json).await
+        };
+        write
+            .await
+            .map_err(|e| LlmError::Fixture(format!("{}: {e}", path.display())))?;
+        Ok(completion)
+    }
+
+    fn model(&self) -> Option<&str> {
+        self.inner.as_ref()?.model()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::MockLlmClient;
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let mock = Arc::new(MockLlmClient::new(vec!["one".into(), "two".into()]));
+        let recorder = FixtureLlmClient::record(mock, dir.path());
+        assert_eq!(recorder.complete("sys", "a").await.unwrap().text, "one");
+        assert_eq!(
+            recorder
+                .complete_for(LlmFeature::Parse, "sys", "b")
+                .await
+                .unwrap()
+                .text,
+            "two"
+        );
+
+        let replayer = FixtureLlmClient::replay(dir.path());
+        assert_eq!(replayer.mode(), FixtureMode::Replay);
+        // Order does not matter: fixtures are looked up by prompt.
+        assert_eq!(replayer.complete("sys", "b").await.unwrap().text, "two");
+        assert_eq!(replayer.complete("sys", "a").await.unwrap().text, "one");
+        assert!(matches!(
+            replayer.complete("sys", "c").await,
+            Err(LlmError::Fixture(_))
+        ));
+    }
+
+    #[test]
+    fn test_normalize_prompt_masks_dates() {
+        let a = "Today's date: 2026-01-01 (Thursday)\nItems: {\"created_at\":\"2026-01-01T10:00:00.123+00:00\"}";
+        let b =
+            "Today's date: 2026-03-05 (Thursday)\nItems: {\"created_at\":\"2026-03-05T08:30:00Z\"}";
+        assert_eq!(normalize_prompt(a), normalize_prompt(b));
+        assert_eq!(
+            normalize_prompt(b),
+            "Items: {\"created_at\":\"<timestamp>\"}"
+        );
+        assert_ne!(normalize_prompt("key: a"), normalize_prompt("key: b"));
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_remember_and_recall_replay_without_api() {
+        use crate::TABLE_NAME;
+        use crate::backend::MemoryBackend;
+        use crate::store::MemoryStore;
+
+        let dir = tempfile::tempdir().unwrap();
+        let remember = serde_json::json!({
+            "category": "notes", "key": "staging", "content": "staging is at staging.example.com"
+        });
+        let resolve = serde_json::json!({"type": "exact", "category": "notes", "key": "staging"});
+        let mock = Arc::new(MockLlmClient::new(vec![
+            remember.to_string(),
+            resolve.to_string(),
+            "Staging is at staging.example.com.".into(),
+        ]));
+
+        let run = |llm: Arc<dyn LlmClient>| async move {
+            let store =
+                MemoryStore::new(MemoryBackend::in_memory(TABLE_NAME.to_string())).with_llm(llm);
+            store.init().await.unwrap();
+            let item = store
+                .remember("remember that staging is at staging.example.com")
+                .await
+                .unwrap();
+            let recall = store.recall("where is staging?").await.unwrap();
+            (item["key"].clone(), recall.items.len(), recall.answer)
+        };
+
+        let recorded = run(Arc::new(FixtureLlmClient::record(mock, dir.path()))).await;
+        let replayed = run(Arc::new(FixtureLlmClient::replay(dir.path()))).await;
+        assert_eq!(recorded, replayed);
+        assert_eq!(replayed.0, "staging");
+        assert_eq!(replayed.1, 1);
+    }
+}
@@ -0,0 +1,222 @@
+//! The memory graph: items as nodes, links and `related` references as
+//! edges, exported for Graphviz (DOT) or Gephi (GraphML).
+//!
+//! [`build_graph`] reads every live item of the given categories and the
+//! edges between them: one per `related` entry, with relation `related`, and
+//! one per explicit link in [`LINKS_CATEGORY`], with the link's relation.
+//! Edges to items outside the graph (forgotten, expired, or in another
+//! category) are left out. Nodes are colored by category from a fixed
+//! palette, in category order.
+
+use std::collections::{BTreeSet, HashSet};
+
+use serde::Serialize;
+
+use crate::backend::MemoryBackend;
+use crate::chunk::is_chunk;
+use crate::conflict::item_ref;
+use crate::error::MemoryError;
+use crate::links::{LINKS_CATEGORY, LINKS_SCAN_LIMIT, Link};
+use crate::related::{RELATED_ATTRIBUTE, related};
+use crate::ttl::is_expired;
+
+/// Maximum number of items read per category.
+pub const GRAPH_SCAN_LIMIT: usize = 10_000;
+
+/// Node fill colors, assigned to categories in turn.
+pub const CATEGORY_COLORS: &[&str] = &[
+    "#8dd3c7", "#ffffb3", "#bebada", "#fb8072", "#80b1d3", "#fdb462", "#b3de69", "#fccde5",
+    "#d9d9d9", "#bc80bd",
+];
+
+/// An item in the graph.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphNode {
+    /// `category/key` of the item.
+    pub id: String,
+    pub category: String,
+    pub key: String,
+    pub color: String,
+}
+
+/// A directed edge between two items.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    pub relation: String,
+}
+
+/// Items and the edges between them.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// The graph of the live items of `categories`.
+pub async fn build_graph(
+    backend: &MemoryBackend,
+    categories: &[String],
+) -> Result<Graph, MemoryError> {
+    let mut graph = Graph::default();
+    let mut edges = BTreeSet::new();
+    for (i, category) in categories.iter().enumerate() {
+        let color = CATEGORY_COLORS[i % CATEGORY_COLORS.len()];
+        let items = backend.query(category, None, GRAPH_SCAN_LIMIT).await?;
+        for item in items
+            .iter()
+            .filter(|item| !is_chunk(item) && !is_expired(item))
+        {
+            let Some(id) = item_ref(item) else {
+                continue;
+            };
+            for target in related(item) {
+                edges.insert(GraphEdge {
+                    source: id.clone(),
+                    target,
+                    relation: RELATED_ATTRIBUTE.to_string(),
+                });
+            }
+            graph.nodes.push(GraphNode {
+                category: category.clone(),
+                key: item["key"].as_str().unwrap_or_default().to_string(),
+                color: color.to_string(),
+                id,
+            });
+        }
+    }
+
+    let links = backend
+        .query(LINKS_CATEGORY, None, LINKS_SCAN_LIMIT)
+        .await?;
+    edges.extend(
+        links
+            .iter()
+            .filter_map(Link::from_record)
+            .map(|link| GraphEdge {
+                source: link.source,
+                target: link.target,
+                relation: link.relation,
+            }),
+    );
+
+    let ids: HashSet<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+    graph.edges = edges
+        .into_iter()
+        .filter(|e| ids.contains(e.source.as_str()) && ids.contains(e.target.as_str()))
+        .collect();
+    Ok(graph)
+}
+
+/// `graph` as a Graphviz DOT digraph.
+pub fn to_dot(graph: &Graph) -> String {
+    let mut out = String::from("digraph memory {\n  node [shape=box, style=filled];\n");
+    for node in &graph.nodes {
+        out.push_str(&format!(
+            "  {} [fillcolor={}, tooltip={}];\n",
+            dot_quote(&node.id),
+            dot_quote(&node.color),
+            dot_quote(&node.category),
+        ));
+    }
+    for edge in &graph.edges {
+        out.push_str(&format!(
+            "  {} -> {} [label={}];\n",
+            dot_quote(&edge.source),
+            dot_quote(&edge.target),
+            dot_quote(&edge.relation),
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// `graph` as a GraphML document, with `category`, `color`, and `relation`
+/// data keys.
+pub fn to_graphml(graph: &Graph) -> String {
+    let mut out = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        "  <key id=\"category\" for=\"node\" attr.name=\"category\" attr.type=\"string\"/>\n",
+        "  <key id=\"color\" for=\"node\" attr.name=\"color\" attr.type=\"string\"/>\n",
+        "  <key id=\"relation\" for=\"edge\" attr.name=\"relation\" attr.type=\"string\"/>\n",
+        "  <graph id=\"memory\" edgedefault=\"directed\">\n",
+    ));
+    for node in &graph.nodes {
+        out.push_str(&format!(
+            "    <node id=\"{}\"><data key=\"category\">{}</data><data key=\"color\">{}</data></node>\n",
+            xml_escape(&node.id),
+            xml_escape(&node.category),
+            xml_escape(&node.color),
+        ));
+    }
+    for edge in &graph.edges {
+        out.push_str(&format!(
+            "    <edge source=\"{}\" target=\"{}\"><data key=\"relation\">{}</data></edge>\n",
+            xml_escape(&edge.source),
+            xml_escape(&edge.target),
+            xml_escape(&edge.relation),
+        ));
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+fn dot_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_build_and_export_graph() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        backend
+            .put_item(json!({"category": "contacts", "key": "toby", "name": "Toby"}))
+            .await
+            .unwrap();
+        backend
+            .put_item(json!({"category": "decisions", "key": "auth", "related": ["contacts/toby", "contacts/gone"]}))
+            .await
+            .unwrap();
+        backend
+            .link_items("decisions/auth", "contacts/toby", "decided-by")
+            .await
+            .unwrap();
+
+        let categories = vec!["contacts".to_string(), "decisions".to_string()];
+        let graph = build_graph(&backend, &categories).await.unwrap();
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.nodes[0].color, CATEGORY_COLORS[0]);
+        assert_eq!(graph.nodes[1].color, CATEGORY_COLORS[1]);
+        let relations: Vec<&str> = graph.edges.iter().map(|e| e.relation.as_str()).collect();
+        assert_eq!(relations, ["decided-by", "related"]);
+
+        let dot = to_dot(&graph);
+        assert!(dot.starts_with("digraph memory {"));
+        assert!(dot.contains("\"decisions/auth\" -> \"contacts/toby\" [label=\"decided-by\"];"));
+        assert!(!dot.contains("contacts/gone"));
+
+        let graphml = to_graphml(&graph);
+        assert_eq!(graphml.matches("<node ").count(), 2);
+        assert_eq!(graphml.matches("<edge ").count(), 2);
+        assert_eq!(xml_escape("a<b & \"c\""), "a&lt;b &amp; &quot;c&quot;");
+        assert_eq!(dot_quote("say \"hi\""), "\"say \\\"hi\\\"\"");
+    }
+}
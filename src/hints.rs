@@ -0,0 +1,81 @@
+//! Per-category parsing hints.
+//!
+//! A schema can carry free-text hints ("key should be the person's first
+//! name") that are added to the parsing prompts for its category, so
+//! extraction can be tuned without code changes. Like attribute aliases,
+//! hints do not fit in a FerridynDB partition schema: each category's hints
+//! are one record in the reserved [`HINTS_CATEGORY`] partition, written when
+//! the schema is created.
+
+use std::collections::BTreeMap;
+
+use crate::backend::MemoryBackend;
+use crate::error::MemoryError;
+
+/// Partition holding one hints record per category.
+pub const HINTS_CATEGORY: &str = "_parse_hints";
+
+/// Maximum number of hints records read.
+pub const HINTS_SCAN_LIMIT: usize = 10_000;
+
+/// Store the parsing hints of `category`, replacing any previous ones. Empty
+/// or missing hints remove them.
+pub async fn save_hints(
+    backend: &MemoryBackend,
+    category: &str,
+    hints: Option<&str>,
+) -> Result<(), MemoryError> {
+    match hints.map(str::trim).filter(|h| !h.is_empty()) {
+        Some(hints) => {
+            backend
+                .put_system_item(serde_json::json!({
+                    "category": HINTS_CATEGORY,
+                    "key": category,
+                    "hints": hints,
+                }))
+                .await
+        }
+        None => backend.delete_item(HINTS_CATEGORY, category).await,
+    }
+}
+
+/// Parsing hints of every category that has them.
+pub async fn load_hints(backend: &MemoryBackend) -> Result<BTreeMap<String, String>, MemoryError> {
+    let records = backend
+        .query(HINTS_CATEGORY, None, HINTS_SCAN_LIMIT)
+        .await?;
+    Ok(records
+        .into_iter()
+        .filter_map(|record| {
+            Some((
+                record["key"].as_str()?.to_string(),
+                record["hints"].as_str()?.to_string(),
+            ))
+        })
+        .collect())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+
+    #[tokio::test]
+    async fn test_save_and_load_hints() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        save_hints(&backend, "contacts", Some(" key is the first name "))
+            .await
+            .unwrap();
+        save_hints(&backend, "notes", Some("   ")).await.unwrap();
+        let hints = load_hints(&backend).await.unwrap();
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints["contacts"], "key is the first name");
+
+        save_hints(&backend, "contacts", None).await.unwrap();
+        assert!(load_hints(&backend).await.unwrap().is_empty());
+    }
+}
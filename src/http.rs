@@ -0,0 +1,737 @@
+//! HTTP interface: a JSON API over [`MemoryBackend`], served with token auth
+//! by `fmemory serve-api` and alongside the embedded web dashboard by
+//! `fmemory serve-ui`.
+//!
+//! The dashboard is a single static page (`assets/ui/`) compiled into the
+//! binary; it talks to the `/api` routes defined here, which require the
+//! same bearer token as `serve-api`. The page itself is public and picks the
+//! token up from the `#token=` fragment of the URL `serve-ui` prints.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::backend::MemoryBackend;
+use crate::chunk::{chunk_content, collapse_chunks, delete_chunks, hydrate_chunks, store_chunks};
+use crate::error::MemoryError;
+use crate::limits::Limits;
+use crate::logging::{
+    OpLog, Outcome, current_request_id, new_request_id, request_id, with_request_id,
+};
+use crate::quota::Quotas;
+use crate::schema::{SchemaDefinition, SchemaManager};
+use crate::search::search_items;
+use crate::sort::UPDATED_AT_ATTRIBUTE;
+use crate::ttl::{filter_expired, is_expired};
+
+/// Environment variable holding the bearer token required by `serve-api`.
+pub const API_TOKEN_ENV: &str = "FMEMORY_API_TOKEN";
+
+/// Response header carrying the request ID (see [`crate::logging`]).
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Maximum number of categories returned by the category listing.
+const MAX_CATEGORIES: usize = 1000;
+
+const INDEX_HTML: &str = include_str!("../assets/ui/index.html");
+const APP_JS: &str = include_str!("../assets/ui/app.js");
+const STYLE_CSS: &str = include_str!("../assets/ui/style.css");
+
+/// Shared state for HTTP handlers.
+#[derive(Clone)]
+pub struct HttpState {
+    backend: MemoryBackend,
+    namespace: Option<String>,
+    quotas: Quotas,
+    limits: Limits,
+}
+
+impl HttpState {
+    pub fn new(backend: MemoryBackend, namespace: Option<String>) -> Self {
+        Self {
+            backend,
+            namespace,
+            quotas: Quotas::from_env(),
+            limits: Limits::load(),
+        }
+    }
+
+    /// Replace the per-category item caps enforced after writes.
+    pub fn with_quotas(mut self, quotas: Quotas) -> Self {
+        self.quotas = quotas;
+        self
+    }
+
+    /// Replace the default and maximum result counts of list and search.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    fn op_log(&self, operation: &'static str) -> OpLog {
+        OpLog::start(operation, self.namespace.as_deref())
+    }
+}
+
+/// Error response rendered as `{"error": "..."}`.
+#[derive(Debug)]
+pub struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn not_found(what: impl Into<String>) -> Self {
+        Self(StatusCode::NOT_FOUND, what.into())
+    }
+
+    fn bad_request(msg: impl Into<String>) -> Self {
+        Self(StatusCode::BAD_REQUEST, msg.into())
+    }
+}
+
+impl From<MemoryError> for ApiError {
+    fn from(e: MemoryError) -> Self {
+        let status = match e {
+            MemoryError::InvalidParams(_) | MemoryError::Schema(_) => StatusCode::BAD_REQUEST,
+            MemoryError::ServerUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            MemoryError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            MemoryError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self(status, e.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut body = serde_json::json!({ "error": self.1 });
+        if let Some(request_id) = current_request_id() {
+            body["request_id"] = Value::String(request_id);
+        }
+        (self.0, Json(body)).into_response()
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+// ============================================================================
+// Routers
+// ============================================================================
+
+/// JSON API routes, mounted under `/api`.
+pub fn api_router(state: HttpState) -> Router {
+    Router::new()
+        .route("/api/categories", get(list_categories))
+        .route("/api/categories/{category}/items", get(list_items))
+        .route("/api/search", get(search))
+        .route(
+            "/api/items/{category}/{key}",
+            get(get_item).put(put_item).delete(delete_item),
+        )
+        .route("/api/schemas", get(list_schemas))
+        .route("/api/schemas/{category}", get(get_schema).put(put_schema))
+        .route("/api/prune", post(prune))
+        .with_state(state)
+        .layer(middleware::from_fn(tag_request))
+}
+
+/// Run each request under a fresh request ID, returned in `x-request-id`.
+async fn tag_request(request: Request, next: Next) -> Response {
+    let request_id = new_request_id();
+    let mut response = with_request_id(request_id.clone(), next.run(request)).await;
+    if let Ok(value) = header::HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Require `Authorization: Bearer <token>` on every route of `router`.
+pub fn with_token_auth(router: Router, token: &str) -> Router {
+    let token: Arc<str> = Arc::from(token);
+    router.layer(middleware::from_fn_with_state(token, require_token))
+}
+
+async fn require_token(State(token): State<Arc<str>>, request: Request, next: Next) -> Response {
+    let presented = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    match presented {
+        Some(presented) if tokens_match(presented, &token) => next.run(request).await,
+        _ => ApiError(
+            StatusCode::UNAUTHORIZED,
+            "missing or invalid bearer token".into(),
+        )
+        .into_response(),
+    }
+}
+
+/// A random token for a dashboard started without one.
+pub fn generate_token() -> String {
+    format!("{:020x}", ulid::Ulid::new().random())
+}
+
+/// Compare tokens without short-circuiting on the first differing byte.
+pub(crate) fn tokens_match(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+/// The embedded dashboard plus the API routes, which require
+/// `Authorization: Bearer <token>` as with [`with_token_auth`].
+pub fn ui_router(state: HttpState, token: &str) -> Router {
+    Router::new()
+        .route(
+            "/",
+            get(|| async { asset("text/html; charset=utf-8", INDEX_HTML) }),
+        )
+        .route(
+            "/app.js",
+            get(|| async { asset("text/javascript; charset=utf-8", APP_JS) }),
+        )
+        .route(
+            "/style.css",
+            get(|| async { asset("text/css; charset=utf-8", STYLE_CSS) }),
+        )
+        .merge(with_token_auth(api_router(state), token))
+}
+
+/// Serve `router` on `addr` until the process is interrupted.
+pub async fn serve(router: Router, addr: SocketAddr) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+}
+
+fn asset(content_type: &'static str, body: &'static str) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, content_type)], body)
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ListParams {
+    pub prefix: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    pub q: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PruneRequest {
+    pub category: Option<String>,
+}
+
+/// List categories: every partition holding data, plus every defined schema.
+#[tracing::instrument(
+    name = "http.request",
+    skip_all,
+    fields(route = "list_categories", request_id = %request_id()),
+)]
+async fn list_categories(State(state): State<HttpState>) -> ApiResult<Json<Value>> {
+    let mut op = state.op_log("ui_list_categories");
+
+    // Schema listing is unavailable on some backends; fall back to partitions.
+    let schemas = state.backend.list_schemas().await.unwrap_or_default();
+    let mut categories: Vec<Value> = schemas
+        .iter()
+        .map(|s| {
+            serde_json::json!({
+                "name": s.prefix,
+                "description": s.description,
+                "attributes": s.attributes.iter().map(|a| serde_json::json!({
+                    "name": a.name,
+                    "type": a.attr_type,
+                    "required": a.required,
+                })).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    for partition in state.backend.list_partition_keys(MAX_CATEGORIES).await? {
+        if let Some(name) = partition.as_str()
+            && !categories.iter().any(|c| c["name"] == name)
+        {
+            categories.push(serde_json::json!({ "name": name }));
+        }
+    }
+    categories.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+
+    op.set_outcome(Outcome::Ok);
+    Ok(Json(Value::Array(categories)))
+}
+
+/// List live items in a category, chunks collapsed into their parents.
+#[tracing::instrument(
+    name = "http.request",
+    skip_all,
+    fields(route = "list_items", request_id = %request_id()),
+)]
+async fn list_items(
+    State(state): State<HttpState>,
+    Path(category): Path<String>,
+    Query(params): Query<ListParams>,
+) -> ApiResult<Json<Vec<Value>>> {
+    let mut op = state.op_log("ui_list_items").category(Some(&category));
+    let limit = state.limits.resolve("ui_list_items", params.limit);
+    let items = state
+        .backend
+        .query(&category, params.prefix.as_deref(), limit)
+        .await?;
+    op.set_outcome(Outcome::Ok);
+    Ok(Json(collapse_chunks(filter_expired(items))))
+}
+
+/// Case-insensitive substring search over every category's items.
+#[tracing::instrument(
+    name = "http.request",
+    skip_all,
+    fields(route = "search", request_id = %request_id()),
+)]
+async fn search(
+    State(state): State<HttpState>,
+    Query(params): Query<SearchParams>,
+) -> ApiResult<Json<Vec<Value>>> {
+    let mut op = state.op_log("ui_search");
+    let needle = params.q.trim().to_lowercase();
+    if needle.is_empty() {
+        return Err(ApiError::bad_request("q must not be empty"));
+    }
+    let limit = state.limits.resolve("ui_search", params.limit);
+
+    let matches = search_items(&state.backend, &needle, limit, false).await?;
+
+    op.set_outcome(Outcome::Ok);
+    Ok(Json(matches))
+}
+
+/// Fetch one item with its full chunked content.
+#[tracing::instrument(
+    name = "http.request",
+    skip_all,
+    fields(route = "get_item", request_id = %request_id()),
+)]
+async fn get_item(
+    State(state): State<HttpState>,
+    Path((category, key)): Path<(String, String)>,
+) -> ApiResult<Json<Value>> {
+    let mut op = state
+        .op_log("ui_get")
+        .category(Some(&category))
+        .key(Some(&key));
+    let Some(item) = state
+        .backend
+        .get_item(&category, &key)
+        .await?
+        .filter(|i| !is_expired(i))
+    else {
+        op.set_outcome(Outcome::NotFound);
+        return Err(ApiError::not_found(format!("{category}/{key} not found")));
+    };
+    let item = hydrate_chunks(&state.backend, vec![item])
+        .await?
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    op.set_outcome(Outcome::Ok);
+    Ok(Json(item))
+}
+
+/// Create or replace an item. The body is a JSON object of attributes;
+/// `category` and `key` come from the path, and an existing `created_at` is kept.
+/// Oversized `content` is chunked again, replacing the item's old chunks, so
+/// an edited copy of a hydrated item keeps its new text.
+#[tracing::instrument(
+    name = "http.request",
+    skip_all,
+    fields(route = "put_item", request_id = %request_id()),
+)]
+async fn put_item(
+    State(state): State<HttpState>,
+    Path((category, key)): Path<(String, String)>,
+    Json(body): Json<Value>,
+) -> ApiResult<Json<Value>> {
+    let mut op = state
+        .op_log("ui_put")
+        .category(Some(&category))
+        .key(Some(&key));
+    let Value::Object(mut doc) = body else {
+        return Err(ApiError::bad_request("body must be a JSON object"));
+    };
+
+    let existing = state.backend.get_item(&category, &key).await?;
+    let now = Value::String(chrono::Utc::now().to_rfc3339());
+    let created_at = existing
+        .as_ref()
+        .and_then(|e| e.get("created_at").cloned())
+        .unwrap_or_else(|| now.clone());
+    doc.insert("category".into(), Value::String(category));
+    doc.insert("key".into(), Value::String(key));
+    doc.entry("created_at").or_insert(created_at);
+    doc.insert(UPDATED_AT_ATTRIBUTE.into(), now);
+
+    let mut item = Value::Object(doc);
+    let chunks = chunk_content(&mut item);
+    state.backend.put_item(item.clone()).await?;
+    store_chunks(&state.backend, &item, &chunks).await?;
+    state
+        .quotas
+        .enforce_after_write(&state.backend, &item)
+        .await?;
+    if !chunks.is_empty() {
+        // Return the full content, as a read will.
+        item["content"] = Value::String(chunks.join("\n\n"));
+    }
+    op.set_outcome(Outcome::Ok);
+    Ok(Json(item))
+}
+
+/// Delete an item and any chunks linked to it.
+#[tracing::instrument(
+    name = "http.request",
+    skip_all,
+    fields(route = "delete_item", request_id = %request_id()),
+)]
+async fn delete_item(
+    State(state): State<HttpState>,
+    Path((category, key)): Path<(String, String)>,
+) -> ApiResult<StatusCode> {
+    let mut op = state
+        .op_log("ui_delete")
+        .category(Some(&category))
+        .key(Some(&key));
+    let Some(item) = state.backend.get_item(&category, &key).await? else {
+        op.set_outcome(Outcome::NotFound);
+        return Err(ApiError::not_found(format!("{category}/{key} not found")));
+    };
+    delete_chunks(&state.backend, &item).await?;
+    state.backend.delete_item(&category, &key).await?;
+    op.set_outcome(Outcome::Ok);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List all category schemas.
+#[tracing::instrument(
+    name = "http.request",
+    skip_all,
+    fields(route = "list_schemas", request_id = %request_id()),
+)]
+async fn list_schemas(State(state): State<HttpState>) -> ApiResult<Json<Value>> {
+    let mut op = state.op_log("api_list_schemas");
+    let schemas = state.backend.list_schemas().await?;
+    op.set_outcome(Outcome::Ok);
+    Ok(Json(serde_json::to_value(schemas).unwrap_or_default()))
+}
+
+/// Fetch one category's schema.
+#[tracing::instrument(
+    name = "http.request",
+    skip_all,
+    fields(route = "get_schema", request_id = %request_id()),
+)]
+async fn get_schema(
+    State(state): State<HttpState>,
+    Path(category): Path<String>,
+) -> ApiResult<Json<Value>> {
+    let mut op = state.op_log("api_get_schema").category(Some(&category));
+    let sm = SchemaManager::new(state.backend.clone());
+    let Some(schema) = sm.get_schema(&category).await? else {
+        op.set_outcome(Outcome::NotFound);
+        return Err(ApiError::not_found(format!("no schema for '{category}'")));
+    };
+    op.set_outcome(Outcome::Ok);
+    Ok(Json(serde_json::to_value(schema).unwrap_or_default()))
+}
+
+/// Define a category schema (validated writes), creating its suggested indexes.
+#[tracing::instrument(
+    name = "http.request",
+    skip_all,
+    fields(route = "put_schema", request_id = %request_id()),
+)]
+async fn put_schema(
+    State(state): State<HttpState>,
+    Path(category): Path<String>,
+    Json(definition): Json<SchemaDefinition>,
+) -> ApiResult<StatusCode> {
+    let mut op = state.op_log("api_define").category(Some(&category));
+    SchemaManager::new(state.backend.clone())
+        .create_schema_with_indexes(&category, &definition, true)
+        .await?;
+    op.set_outcome(Outcome::Ok);
+    Ok(StatusCode::CREATED)
+}
+
+/// Delete expired items in one category, or in every schema-backed category
+/// (plus system partitions) when none is given.
+#[tracing::instrument(
+    name = "http.request",
+    skip_all,
+    fields(route = "prune", request_id = %request_id()),
+)]
+async fn prune(
+    State(state): State<HttpState>,
+    body: Option<Json<PruneRequest>>,
+) -> ApiResult<Json<Value>> {
+    let Json(request) = body.unwrap_or_default();
+    let mut op = state
+        .op_log("api_prune")
+        .category(request.category.as_deref());
+
+    let categories = match request.category {
+        Some(category) => vec![category],
+        None => state.backend.list_categories().await?,
+    };
+    let pruned = state.backend.prune_expired(&categories).await?;
+    op.set_outcome(Outcome::Ok);
+    Ok(Json(serde_json::json!({ "pruned": pruned })))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use ferridyn_core::api::FerridynDB;
+    use ferridyn_core::types::KeyType;
+
+    fn test_state() -> (HttpState, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = FerridynDB::create(dir.path().join("test.db")).unwrap();
+        db.create_table(TABLE_NAME)
+            .partition_key("category", KeyType::String)
+            .sort_key("key", KeyType::String)
+            .execute()
+            .unwrap();
+        let backend = MemoryBackend::direct(db, TABLE_NAME.to_string());
+        (HttpState::new(backend, None), dir)
+    }
+
+    fn item_path(category: &str, key: &str) -> Path<(String, String)> {
+        Path((category.to_string(), key.to_string()))
+    }
+
+    #[tokio::test]
+    async fn test_put_get_delete_roundtrip() {
+        let (state, _dir) = test_state();
+
+        let Json(stored) = put_item(
+            State(state.clone()),
+            item_path("contacts", "toby"),
+            Json(serde_json::json!({"email": "toby@example.com", "key": "ignored"})),
+        )
+        .await
+        .unwrap();
+        assert_eq!(stored["key"], "toby");
+        assert!(stored.get("created_at").is_some());
+
+        let Json(item) = get_item(State(state.clone()), item_path("contacts", "toby"))
+            .await
+            .unwrap();
+        assert_eq!(item["email"], "toby@example.com");
+
+        let status = delete_item(State(state.clone()), item_path("contacts", "toby"))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let err = get_item(State(state), item_path("contacts", "toby"))
+            .await
+            .unwrap_err();
+        assert_eq!(err.0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_put_rechunks_edited_content() {
+        use crate::chunk::CHUNK_THRESHOLD_CHARS;
+
+        let (state, _dir) = test_state();
+        let original = "old ".repeat(CHUNK_THRESHOLD_CHARS);
+        let Json(stored) = put_item(
+            State(state.clone()),
+            item_path("notes", "long"),
+            Json(serde_json::json!({ "content": original })),
+        )
+        .await
+        .unwrap();
+        let Json(mut hydrated) = get_item(State(state.clone()), item_path("notes", "long"))
+            .await
+            .unwrap();
+        assert!(hydrated["chunk_count"].as_u64().unwrap() > 1);
+        assert_eq!(stored["content"], hydrated["content"]);
+
+        // The dashboard sends the hydrated item back with its edits.
+        let edited = "new ".repeat(CHUNK_THRESHOLD_CHARS / 8);
+        hydrated["content"] = Value::String(edited.trim().to_string());
+        let Json(stored) = put_item(
+            State(state.clone()),
+            item_path("notes", "long"),
+            Json(hydrated),
+        )
+        .await
+        .unwrap();
+        assert!(stored.get("chunk_count").is_none());
+        let Json(item) = get_item(State(state.clone()), item_path("notes", "long"))
+            .await
+            .unwrap();
+        assert_eq!(item["content"], edited.trim());
+        assert!(item.get("chunk_count").is_none());
+        let keys = state.backend.query("notes", None, 100).await.unwrap();
+        assert_eq!(keys.len(), 1, "old chunks are deleted");
+    }
+
+    #[tokio::test]
+    async fn test_put_rejects_non_object_body() {
+        let (state, _dir) = test_state();
+        let err = put_item(
+            State(state),
+            item_path("notes", "x"),
+            Json(serde_json::json!("text")),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_categories_and_search() {
+        let (state, _dir) = test_state();
+        for (category, key, text) in [
+            ("contacts", "toby", "Toby works on Rust"),
+            ("notes", "lunch", "Pizza on Friday"),
+        ] {
+            state
+                .backend
+                .put_item(serde_json::json!({"category": category, "key": key, "content": text}))
+                .await
+                .unwrap();
+        }
+
+        let Json(categories) = list_categories(State(state.clone())).await.unwrap();
+        let names: Vec<&str> = categories
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|c| c["name"].as_str())
+            .collect();
+        assert_eq!(names, vec!["contacts", "notes"]);
+
+        let Json(results) = search(
+            State(state),
+            Query(SearchParams {
+                q: "rust".into(),
+                limit: None,
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["key"], "toby");
+    }
+
+    #[tokio::test]
+    async fn test_prune_category() {
+        let (state, _dir) = test_state();
+        state
+            .backend
+            .put_item(serde_json::json!({
+                "category": "scratchpad",
+                "key": "old",
+                "expires_at": "2000-01-01T00:00:00Z",
+            }))
+            .await
+            .unwrap();
+
+        let Json(result) = prune(
+            State(state),
+            Some(Json(PruneRequest {
+                category: Some("scratchpad".into()),
+            })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result["pruned"], 1);
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secret", "secreT"));
+        assert!(!tokens_match("secret", "secret2"));
+    }
+
+    #[tokio::test]
+    async fn test_token_auth() {
+        use axum::body::Body;
+        use tower::ServiceExt;
+
+        let (state, _dir) = test_state();
+        let app = with_token_auth(api_router(state), "s3cret");
+        let request = |auth: Option<&str>| {
+            let mut builder = Request::builder().uri("/api/items/notes/missing");
+            if let Some(auth) = auth {
+                builder = builder.header(header::AUTHORIZATION, auth);
+            }
+            builder.body(Body::empty()).unwrap()
+        };
+
+        let status = |auth| {
+            let app = app.clone();
+            async move { app.oneshot(request(auth)).await.unwrap().status() }
+        };
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("Bearer wrong")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("Bearer s3cret")).await, StatusCode::NOT_FOUND);
+
+        // The dashboard's API needs the token too; the page itself does not.
+        let (state, _dir) = test_state();
+        let ui = ui_router(state, "s3cret");
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let page = ui.clone().oneshot(get("/")).await.unwrap();
+        assert_eq!(page.status(), StatusCode::OK);
+        let api = ui.oneshot(get("/api/items/notes/missing")).await.unwrap();
+        assert_eq!(api.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_errors_carry_request_id() {
+        use axum::body::{Body, to_bytes};
+        use tower::ServiceExt;
+
+        let (state, _dir) = test_state();
+        let request = Request::builder()
+            .uri("/api/items/notes/missing")
+            .body(Body::empty())
+            .unwrap();
+        let response = api_router(state).oneshot(request).await.unwrap();
+        let header = response.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["request_id"], header.as_str());
+    }
+}
@@ -0,0 +1,519 @@
+//! Bulk import of structured data from external sources.
+//!
+//! Importers map source records directly onto category attributes without
+//! LLM involvement. Keys are derived deterministically (issue numbers, slugs
+//! of row values), so re-importing updates existing items instead of
+//! duplicating them. Records carry only the attributes the source provides,
+//! and a re-import leaves the others as they are.
+
+use std::collections::HashMap;
+use std::io::Read;
+#[cfg(feature = "llm")]
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::PartitionSchemaInfo;
+use crate::keys::slugify;
+
+/// GitHub REST API base URL.
+#[cfg(feature = "llm")]
+const GITHUB_API: &str = "https://api.github.com";
+
+/// Environment variable holding an optional GitHub token (raises rate limits,
+/// grants access to private repositories).
+pub const GITHUB_TOKEN_ENV: &str = "GITHUB_TOKEN";
+
+/// Issues requested per page (GitHub maximum).
+#[cfg(feature = "llm")]
+const GITHUB_PAGE_SIZE: usize = 100;
+
+/// Timeout for each GitHub API request.
+#[cfg(feature = "llm")]
+const GITHUB_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Maximum characters of an issue body kept in `content`.
+pub const ISSUE_BODY_MAX_CHARS: usize = 2000;
+
+/// Errors that can occur during import.
+#[derive(Debug, Error)]
+pub enum ImportError {
+    /// HTTP or network error occurred.
+    #[error("HTTP error: {0}")]
+    Http(String),
+
+    /// The response could not be parsed.
+    #[error("Parse error: {0}")]
+    Parse(String),
+
+    /// Invalid import arguments.
+    #[error("{0}")]
+    Invalid(String),
+}
+
+// ============================================================================
+// GitHub Issues
+// ============================================================================
+
+/// A GitHub issue as returned by the REST API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GithubIssue {
+    pub number: u64,
+    pub title: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    /// `open` or `closed`.
+    pub state: String,
+    #[serde(default)]
+    pub labels: Vec<GithubLabel>,
+    pub html_url: String,
+    /// Present when the "issue" is a pull request.
+    #[serde(default)]
+    pub pull_request: Option<Value>,
+}
+
+/// A GitHub issue label.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GithubLabel {
+    pub name: String,
+}
+
+/// Split `owner/name` into its parts.
+pub fn parse_repo(repo: &str) -> Result<(&str, &str), ImportError> {
+    match repo.split_once('/') {
+        Some((owner, name)) if !owner.is_empty() && !name.is_empty() && !name.contains('/') => {
+            Ok((owner, name))
+        }
+        _ => Err(ImportError::Invalid(format!(
+            "Invalid repository '{repo}', expected owner/name"
+        ))),
+    }
+}
+
+/// Fetch up to `limit` issues (open and closed, pull requests excluded) from
+/// `owner/name`, most recently created first.
+///
+/// Uses `GITHUB_TOKEN` for authentication when set.
+#[cfg(feature = "llm")]
+pub async fn fetch_github_issues(
+    repo: &str,
+    limit: usize,
+) -> Result<Vec<GithubIssue>, ImportError> {
+    let (owner, name) = parse_repo(repo)?;
+    let client = reqwest::Client::builder()
+        .timeout(GITHUB_TIMEOUT)
+        .user_agent(concat!("fmemory/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| ImportError::Http(e.to_string()))?;
+    let token = std::env::var(GITHUB_TOKEN_ENV)
+        .ok()
+        .filter(|t| !t.is_empty());
+
+    let mut issues = Vec::new();
+    let mut page = 1;
+    while issues.len() < limit {
+        let mut request = client
+            .get(format!("{GITHUB_API}/repos/{owner}/{name}/issues"))
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .query(&[
+                ("state", "all".to_string()),
+                ("per_page", GITHUB_PAGE_SIZE.to_string()),
+                ("page", page.to_string()),
+            ]);
+        if let Some(token) = &token {
+            request = request.bearer_auth(token);
+        }
+
+        let batch: Vec<GithubIssue> = request
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ImportError::Http(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| ImportError::Parse(e.to_string()))?;
+
+        let last_page = batch.len() < GITHUB_PAGE_SIZE;
+        issues.extend(batch.into_iter().filter(|i| i.pull_request.is_none()));
+        if last_page {
+            break;
+        }
+        page += 1;
+    }
+
+    issues.truncate(limit);
+    Ok(issues)
+}
+
+/// Stable item key for an issue: `gh-<owner>-<name>-<number>`, lowercased.
+pub fn github_issue_key(repo: &str, number: u64) -> String {
+    format!("gh-{}-{number}", slugify(repo))
+}
+
+/// Map an issue onto `issues` category attributes.
+///
+/// The title becomes the `symptom`, labels the `area`, and a closed state sets
+/// `resolved`. The body is kept (truncated) in `content`. `fix` is never set,
+/// since GitHub does not record one, so one added later survives
+/// re-imports; `area` and `content` are left out when the issue has none.
+pub fn github_issue_to_doc(repo: &str, issue: &GithubIssue) -> Value {
+    let area = (!issue.labels.is_empty()).then(|| {
+        issue
+            .labels
+            .iter()
+            .map(|l| l.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+    let content = issue
+        .body
+        .as_deref()
+        .map(str::trim)
+        .filter(|b| !b.is_empty())
+        .map(|b| truncate_chars(b, ISSUE_BODY_MAX_CHARS));
+
+    let mut doc = serde_json::json!({
+        "category": "issues",
+        "key": github_issue_key(repo, issue.number),
+        "symptom": issue.title,
+        "resolved": issue.state == "closed",
+        "source": issue.html_url,
+        "issue_number": issue.number,
+    });
+    if let Some(area) = area {
+        doc["area"] = Value::String(area);
+    }
+    if let Some(content) = content {
+        doc["content"] = Value::String(content);
+    }
+    doc
+}
+
+// ============================================================================
+// CSV
+// ============================================================================
+
+/// Parse a `--map` spec (`"Column=attribute,Other Column=other"`) into
+/// `(column, attribute)` pairs.
+pub fn parse_column_map(spec: &str) -> Result<Vec<(String, String)>, ImportError> {
+    spec.split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((column, attr)) if !column.trim().is_empty() && !attr.trim().is_empty() => {
+                Ok((column.trim().to_string(), attr.trim().to_string()))
+            }
+            _ => Err(ImportError::Invalid(format!(
+                "Invalid mapping '{pair}', expected Column=attribute"
+            ))),
+        })
+        .collect()
+}
+
+/// Convert CSV rows into documents for `schema`'s category.
+///
+/// `map` selects columns and names the attribute each fills; without it,
+/// columns whose normalized header matches an attribute name are used. A
+/// column mapped to `key` supplies the item key; otherwise the key is a slug
+/// of the first mapped value in the row. Duplicate keys get `-2`, `-3`, ...
+/// suffixes. Cells are converted to the attribute's declared type, and rows
+/// missing a required attribute are rejected.
+pub fn csv_to_docs<R: Read>(
+    reader: R,
+    schema: &PartitionSchemaInfo,
+    map: Option<&[(String, String)]>,
+) -> Result<Vec<Value>, ImportError> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(reader);
+    let headers = rdr
+        .headers()
+        .map_err(|e| ImportError::Parse(e.to_string()))?
+        .clone();
+
+    let columns = resolve_columns(&headers, schema, map)?;
+    let attr_type = |attr: &str| {
+        schema
+            .attributes
+            .iter()
+            .find(|a| a.name == attr)
+            .map_or("STRING", |a| a.attr_type.as_str())
+    };
+
+    let mut docs = Vec::new();
+    let mut seen_keys: HashMap<String, usize> = HashMap::new();
+    for record in rdr.records() {
+        let record = record.map_err(|e| ImportError::Parse(e.to_string()))?;
+        let line = record.position().map_or(0, |p| p.line());
+
+        let mut doc = serde_json::json!({ "category": schema.prefix });
+        let mut key_source = None;
+        for (idx, attr) in &columns {
+            let cell = record.get(*idx).unwrap_or_default();
+            if cell.is_empty() {
+                continue;
+            }
+            if attr == "key" {
+                key_source = Some(cell.to_string());
+                continue;
+            }
+            key_source.get_or_insert_with(|| cell.to_string());
+            doc[attr] = convert_cell(cell, attr_type(attr)).ok_or_else(|| {
+                ImportError::Parse(format!(
+                    "line {line}, column '{}': expected {}, got '{cell}'",
+                    &headers[*idx],
+                    attr_type(attr)
+                ))
+            })?;
+        }
+
+        if let Some(missing) = schema
+            .attributes
+            .iter()
+            .find(|a| a.required && doc.get(&a.name).is_none())
+        {
+            return Err(ImportError::Parse(format!(
+                "line {line}: missing required attribute '{}'",
+                missing.name
+            )));
+        }
+
+        let base = key_source.map(|k| slugify(&k)).unwrap_or_default();
+        if base.is_empty() {
+            return Err(ImportError::Parse(format!(
+                "line {line}: no value to derive a key from"
+            )));
+        }
+        let count = seen_keys.entry(base.clone()).or_insert(0);
+        *count += 1;
+        doc["key"] = Value::String(match *count {
+            1 => base,
+            n => format!("{base}-{n}"),
+        });
+        docs.push(doc);
+    }
+    Ok(docs)
+}
+
+/// Resolve `(column index, attribute)` pairs from the header row.
+fn resolve_columns(
+    headers: &csv::StringRecord,
+    schema: &PartitionSchemaInfo,
+    map: Option<&[(String, String)]>,
+) -> Result<Vec<(usize, String)>, ImportError> {
+    let is_attr = |name: &str| schema.attributes.iter().any(|a| a.name == name);
+
+    let columns: Vec<(usize, String)> = match map {
+        Some(map) => map
+            .iter()
+            .map(|(column, attr)| {
+                let idx = headers.iter().position(|h| h == column).ok_or_else(|| {
+                    ImportError::Invalid(format!("Column '{column}' not found in CSV header"))
+                })?;
+                if attr != "key" && schema.validate && !is_attr(attr) {
+                    return Err(ImportError::Invalid(format!(
+                        "Attribute '{attr}' is not defined for category '{}'",
+                        schema.prefix
+                    )));
+                }
+                Ok((idx, attr.clone()))
+            })
+            .collect::<Result<_, _>>()?,
+        None => headers
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, header)| {
+                let name = header.trim().to_lowercase().replace([' ', '-'], "_");
+                (name == "key" || is_attr(&name)).then_some((idx, name))
+            })
+            .collect(),
+    };
+
+    if let Some((_, attr)) = columns
+        .iter()
+        .find(|(_, a)| a == "created_at" || a == "category")
+    {
+        return Err(ImportError::Invalid(format!(
+            "Attribute '{attr}' cannot be imported"
+        )));
+    }
+    if columns.is_empty() {
+        return Err(ImportError::Invalid(format!(
+            "No CSV columns match attributes of '{}'; use --map Column=attribute",
+            schema.prefix
+        )));
+    }
+    Ok(columns)
+}
+
+/// Convert a cell to JSON according to a schema attribute type.
+fn convert_cell(cell: &str, attr_type: &str) -> Option<Value> {
+    match attr_type {
+        "NUMBER" => cell
+            .parse::<i64>()
+            .map(Value::from)
+            .ok()
+            .or_else(|| cell.parse::<f64>().ok().map(Value::from)),
+        "BOOLEAN" => match cell.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" => Some(Value::Bool(true)),
+            "false" | "no" | "n" | "0" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        _ => Some(Value::String(cell.to_string())),
+    }
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((idx, _)) => format!("{}…", &text[..idx]),
+        None => text.to_string(),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(json: Value) -> GithubIssue {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_parse_repo() {
+        assert_eq!(parse_repo("acme/widgets").unwrap(), ("acme", "widgets"));
+        assert!(parse_repo("widgets").is_err());
+        assert!(parse_repo("acme/").is_err());
+        assert!(parse_repo("a/b/c").is_err());
+    }
+
+    #[test]
+    fn test_github_issue_key_is_stable() {
+        assert_eq!(github_issue_key("Acme/my.repo", 42), "gh-acme-my-repo-42");
+    }
+
+    #[test]
+    fn test_github_issue_to_doc() {
+        let closed = issue(serde_json::json!({
+            "number": 7,
+            "title": "Crash on empty config",
+            "body": "  Steps to reproduce...  ",
+            "state": "closed",
+            "labels": [{"name": "config"}, {"name": "bug"}],
+            "html_url": "https://github.com/acme/widgets/issues/7",
+        }));
+        let doc = github_issue_to_doc("acme/widgets", &closed);
+        assert_eq!(doc["category"], "issues");
+        assert_eq!(doc["key"], "gh-acme-widgets-7");
+        assert_eq!(doc["area"], "config, bug");
+        assert_eq!(doc["symptom"], "Crash on empty config");
+        assert_eq!(doc["resolved"], true);
+        assert_eq!(doc["content"], "Steps to reproduce...");
+        assert_eq!(doc["source"], "https://github.com/acme/widgets/issues/7");
+
+        let open = issue(serde_json::json!({
+            "number": 8,
+            "title": "Slow startup",
+            "body": null,
+            "state": "open",
+            "html_url": "https://github.com/acme/widgets/issues/8",
+        }));
+        let doc = github_issue_to_doc("acme/widgets", &open);
+        assert_eq!(doc["resolved"], false);
+        for absent in ["area", "content", "fix"] {
+            assert!(doc.get(absent).is_none(), "{absent} should be left out");
+        }
+    }
+
+    #[test]
+    fn test_truncate_chars() {
+        assert_eq!(truncate_chars("héllo", 10), "héllo");
+        assert_eq!(truncate_chars("héllo", 2), "hé…");
+    }
+
+    fn contacts_schema() -> PartitionSchemaInfo {
+        use crate::AttributeInfo;
+        let attr = |name: &str, attr_type: &str, required: bool| AttributeInfo {
+            name: name.into(),
+            attr_type: attr_type.into(),
+            required,
+        };
+        PartitionSchemaInfo {
+            prefix: "contacts".into(),
+            description: "People".into(),
+            attributes: vec![
+                attr("name", "STRING", true),
+                attr("email", "STRING", false),
+                attr("age", "NUMBER", false),
+                attr("active", "BOOLEAN", false),
+            ],
+            validate: true,
+        }
+    }
+
+    #[test]
+    fn test_parse_column_map() {
+        let map = parse_column_map("Name=name, E-mail = email").unwrap();
+        assert_eq!(
+            map,
+            vec![
+                ("Name".to_string(), "name".to_string()),
+                ("E-mail".to_string(), "email".to_string())
+            ]
+        );
+        assert!(parse_column_map("Name").is_err());
+    }
+
+    #[test]
+    fn test_csv_to_docs_with_map() {
+        let csv = "Name,Email,Age,Active,Notes\n\
+                   Toby Smith,toby@example.com,42,yes,ignored\n\
+                   Toby Smith,toby2@example.com,,no,\n";
+        let map = parse_column_map("Name=name,Email=email,Age=age,Active=active").unwrap();
+        let docs = csv_to_docs(csv.as_bytes(), &contacts_schema(), Some(&map)).unwrap();
+
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0]["category"], "contacts");
+        assert_eq!(docs[0]["key"], "toby-smith");
+        assert_eq!(docs[0]["age"], 42);
+        assert_eq!(docs[0]["active"], true);
+        assert!(docs[0].get("Notes").is_none());
+        assert_eq!(docs[1]["key"], "toby-smith-2");
+        assert!(docs[1].get("age").is_none());
+    }
+
+    #[test]
+    fn test_csv_to_docs_matches_headers_without_map() {
+        let csv = "Key,NAME,email\nalice,Alice,alice@example.com\n";
+        let docs = csv_to_docs(csv.as_bytes(), &contacts_schema(), None).unwrap();
+        assert_eq!(docs[0]["key"], "alice");
+        assert_eq!(docs[0]["name"], "Alice");
+        assert_eq!(docs[0]["email"], "alice@example.com");
+    }
+
+    #[test]
+    fn test_csv_to_docs_validation_errors() {
+        let schema = contacts_schema();
+
+        let unknown = parse_column_map("Name=nickname").unwrap();
+        assert!(csv_to_docs("Name\nx\n".as_bytes(), &schema, Some(&unknown)).is_err());
+
+        let bad_number = parse_column_map("Name=name,Age=age").unwrap();
+        let err =
+            csv_to_docs("Name,Age\nx,old\n".as_bytes(), &schema, Some(&bad_number)).unwrap_err();
+        assert!(err.to_string().contains("expected NUMBER"));
+
+        let missing_required = parse_column_map("Email=email").unwrap();
+        let err = csv_to_docs(
+            "Email\na@example.com\n".as_bytes(),
+            &schema,
+            Some(&missing_required),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("required attribute 'name'"));
+    }
+}
@@ -0,0 +1,291 @@
+//! In-process, `HashMap`-backed database for [`MemoryBackend::in_memory`].
+//!
+//! Mirrors the subset of ferridyn-server behavior the memory layer relies on —
+//! items keyed by `(category, key)`, partition schemas with optional
+//! validation, and secondary indexes — so crates embedding this library can
+//! test against a real [`MemoryBackend`] without a running server. Nothing is
+//! persisted.
+//!
+//! [`MemoryBackend`]: crate::backend::MemoryBackend
+//! [`MemoryBackend::in_memory`]: crate::backend::MemoryBackend::in_memory
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Mutex;
+
+use ferridyn_server::client::{AttributeDefInput, AttributeInfo, IndexInfo, PartitionSchemaInfo};
+use serde_json::Value;
+
+use crate::error::MemoryError;
+use crate::validation::check_attributes;
+
+/// Contents of a single table.
+#[derive(Default)]
+struct Table {
+    /// Items by partition key, then sort key.
+    items: BTreeMap<String, BTreeMap<String, Value>>,
+    schemas: BTreeMap<String, PartitionSchemaInfo>,
+    indexes: BTreeMap<String, IndexInfo>,
+}
+
+/// Shared in-memory store of tables. Tables are created on first use.
+#[derive(Default)]
+pub(crate) struct InMemoryDb {
+    tables: Mutex<HashMap<String, Table>>,
+}
+
+impl InMemoryDb {
+    fn with_table<T>(&self, table: &str, f: impl FnOnce(&mut Table) -> T) -> T {
+        let mut tables = self.tables.lock().unwrap();
+        f(tables.entry(table.to_string()).or_default())
+    }
+
+    pub(crate) fn put_item(&self, table: &str, doc: Value) -> Result<(), MemoryError> {
+        let (Some(category), Some(key)) = (doc["category"].as_str(), doc["key"].as_str()) else {
+            return Err(MemoryError::InvalidParams(
+                "item must have string 'category' and 'key' attributes".into(),
+            ));
+        };
+        let (category, key) = (category.to_string(), key.to_string());
+        self.with_table(table, |t| {
+            if let Some(schema) = t.schemas.get(&category)
+                && schema.validate
+            {
+                check_attributes(schema, &doc)?;
+            }
+            t.items.entry(category).or_default().insert(key, doc);
+            Ok(())
+        })
+    }
+
+    pub(crate) fn get_item(&self, table: &str, category: &str, key: &str) -> Option<Value> {
+        self.with_table(table, |t| {
+            t.items
+                .get(category)
+                .and_then(|partition| partition.get(key))
+                .cloned()
+        })
+    }
+
+    pub(crate) fn query(
+        &self,
+        table: &str,
+        category: &str,
+        prefix: Option<&str>,
+        limit: usize,
+        descending: bool,
+    ) -> Vec<Value> {
+        self.with_table(table, |t| {
+            let Some(partition) = t.items.get(category) else {
+                return Vec::new();
+            };
+            let matching = partition
+                .iter()
+                .filter(|(key, _)| prefix.is_none_or(|p| key.starts_with(p)));
+            let ordered: Box<dyn Iterator<Item = _>> = if descending {
+                Box::new(matching.rev())
+            } else {
+                Box::new(matching)
+            };
+            ordered.take(limit).map(|(_, item)| item.clone()).collect()
+        })
+    }
+
+    pub(crate) fn query_after(
+        &self,
+        table: &str,
+        category: &str,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Vec<Value> {
+        use std::ops::Bound::{Excluded, Unbounded};
+        self.with_table(table, |t| {
+            let Some(partition) = t.items.get(category) else {
+                return Vec::new();
+            };
+            let start = after.map_or(Unbounded, |after| Excluded(after.to_string()));
+            partition
+                .range((start, Unbounded))
+                .take(limit)
+                .map(|(_, item)| item.clone())
+                .collect()
+        })
+    }
+
+    pub(crate) fn delete_item(&self, table: &str, category: &str, key: &str) {
+        self.with_table(table, |t| {
+            if let Some(partition) = t.items.get_mut(category) {
+                partition.remove(key);
+                if partition.is_empty() {
+                    t.items.remove(category);
+                }
+            }
+        })
+    }
+
+    pub(crate) fn list_partition_keys(&self, table: &str, limit: usize) -> Vec<Value> {
+        self.with_table(table, |t| {
+            t.items
+                .keys()
+                .take(limit)
+                .map(|k| Value::String(k.clone()))
+                .collect()
+        })
+    }
+
+    /// Distinct sort key prefixes (the part before the first `#`).
+    pub(crate) fn list_sort_key_prefixes(
+        &self,
+        table: &str,
+        category: &str,
+        limit: usize,
+    ) -> Vec<Value> {
+        self.with_table(table, |t| {
+            let Some(partition) = t.items.get(category) else {
+                return Vec::new();
+            };
+            partition
+                .keys()
+                .map(|k| k.split('#').next().unwrap_or(k))
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .take(limit)
+                .map(|p| Value::String(p.to_string()))
+                .collect()
+        })
+    }
+
+    pub(crate) fn create_schema(
+        &self,
+        table: &str,
+        prefix: &str,
+        description: Option<&str>,
+        attrs: &[AttributeDefInput],
+        validate: bool,
+    ) -> Result<(), MemoryError> {
+        self.with_table(table, |t| {
+            if t.schemas.contains_key(prefix) {
+                return Err(MemoryError::Schema(format!(
+                    "schema '{prefix}' already exists"
+                )));
+            }
+            let schema = PartitionSchemaInfo {
+                prefix: prefix.to_string(),
+                description: description.unwrap_or_default().to_string(),
+                attributes: attrs
+                    .iter()
+                    .map(|a| AttributeInfo {
+                        name: a.name.clone(),
+                        attr_type: a.attr_type.clone(),
+                        required: a.required,
+                    })
+                    .collect(),
+                validate,
+            };
+            t.schemas.insert(prefix.to_string(), schema);
+            Ok(())
+        })
+    }
+
+    pub(crate) fn describe_schema(
+        &self,
+        table: &str,
+        prefix: &str,
+    ) -> Result<PartitionSchemaInfo, MemoryError> {
+        self.with_table(table, |t| {
+            t.schemas
+                .get(prefix)
+                .cloned()
+                .ok_or_else(|| MemoryError::Schema(format!("schema '{prefix}' not found")))
+        })
+    }
+
+    pub(crate) fn list_schemas(&self, table: &str) -> Vec<PartitionSchemaInfo> {
+        self.with_table(table, |t| t.schemas.values().cloned().collect())
+    }
+
+    /// Drop a schema and the indexes defined on it.
+    pub(crate) fn drop_schema(&self, table: &str, prefix: &str) -> Result<(), MemoryError> {
+        self.with_table(table, |t| {
+            t.schemas
+                .remove(prefix)
+                .ok_or_else(|| MemoryError::Schema(format!("schema '{prefix}' not found")))?;
+            t.indexes.retain(|_, idx| idx.partition_schema != prefix);
+            Ok(())
+        })
+    }
+
+    pub(crate) fn create_index(
+        &self,
+        table: &str,
+        name: &str,
+        partition_schema: &str,
+        key_name: &str,
+        key_type: &str,
+    ) -> Result<(), MemoryError> {
+        self.with_table(table, |t| {
+            if !t.schemas.contains_key(partition_schema) {
+                return Err(MemoryError::Index(format!(
+                    "schema '{partition_schema}' not found"
+                )));
+            }
+            if t.indexes.contains_key(name) {
+                return Err(MemoryError::Index(format!("index '{name}' already exists")));
+            }
+            let index = IndexInfo {
+                name: name.to_string(),
+                partition_schema: partition_schema.to_string(),
+                index_key_name: key_name.to_string(),
+                index_key_type: key_type.to_string(),
+            };
+            t.indexes.insert(name.to_string(), index);
+            Ok(())
+        })
+    }
+
+    pub(crate) fn list_indexes(&self, table: &str) -> Vec<IndexInfo> {
+        self.with_table(table, |t| t.indexes.values().cloned().collect())
+    }
+
+    pub(crate) fn describe_index(&self, table: &str, name: &str) -> Result<IndexInfo, MemoryError> {
+        self.with_table(table, |t| {
+            t.indexes
+                .get(name)
+                .cloned()
+                .ok_or_else(|| MemoryError::Index(format!("index '{name}' not found")))
+        })
+    }
+
+    pub(crate) fn drop_index(&self, table: &str, name: &str) -> Result<(), MemoryError> {
+        self.with_table(table, |t| {
+            t.indexes
+                .remove(name)
+                .map(|_| ())
+                .ok_or_else(|| MemoryError::Index(format!("index '{name}' not found")))
+        })
+    }
+
+    /// Items in the index's partition whose indexed attribute equals `key_value`.
+    pub(crate) fn query_index(
+        &self,
+        table: &str,
+        index_name: &str,
+        key_value: &Value,
+        limit: Option<usize>,
+    ) -> Result<Vec<Value>, MemoryError> {
+        self.with_table(table, |t| {
+            let index = t
+                .indexes
+                .get(index_name)
+                .ok_or_else(|| MemoryError::Index(format!("index '{index_name}' not found")))?;
+            let Some(partition) = t.items.get(&index.partition_schema) else {
+                return Ok(Vec::new());
+            };
+            Ok(partition
+                .values()
+                .filter(|item| &item[&index.index_key_name] == key_value)
+                .take(limit.unwrap_or(usize::MAX))
+                .cloned()
+                .collect())
+        })
+    }
+}
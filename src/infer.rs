@@ -0,0 +1,164 @@
+//! Schema inference from example documents, without the LLM.
+//!
+//! [`infer_schema`] derives one attribute per top-level field of a set of
+//! sample JSON objects. A field's type is `NUMBER` or `BOOLEAN` when every
+//! non-null value has that JSON type and `STRING` otherwise; it is required
+//! when every example has a non-null value for it. Fields holding arrays or
+//! objects cannot be typed and are skipped, as are the attributes fmemory
+//! manages itself (`key`, `created_at`, ...).
+//!
+//! Indexes are suggested from value cardinality: a `STRING` or `NUMBER`
+//! attribute whose values repeat, with more than one distinct value but no
+//! more than half as many as examples carrying it, is one items get looked
+//! up by (`role`, `status`) rather than an identifier or free text.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::schema::AttributeDef;
+
+/// Fields never turned into attributes: set by fmemory on every item.
+pub const MANAGED_ATTRIBUTES: &[&str] =
+    &["key", "category", "created_at", "updated_at", "expires_at"];
+
+/// A schema derived from examples.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InferredSchema {
+    /// Number of examples read.
+    pub examples: usize,
+    pub attributes: Vec<AttributeDef>,
+    /// Attributes to index, by name.
+    pub suggested_indexes: Vec<String>,
+    /// Fields left out because they hold arrays or objects.
+    pub skipped: Vec<String>,
+}
+
+/// What one field looked like across the examples.
+#[derive(Default)]
+struct FieldStats {
+    present: usize,
+    types: HashSet<&'static str>,
+    structured: bool,
+    values: HashSet<String>,
+}
+
+/// Infer a schema from `examples`, which must all be JSON objects.
+pub fn infer_schema(examples: &[Value]) -> Result<InferredSchema, String> {
+    if examples.is_empty() {
+        return Err("No examples to infer a schema from".into());
+    }
+    let mut fields: BTreeMap<&str, FieldStats> = BTreeMap::new();
+    for (i, example) in examples.iter().enumerate() {
+        let object = example
+            .as_object()
+            .ok_or_else(|| format!("Example {} is not a JSON object", i + 1))?;
+        for (name, value) in object {
+            if MANAGED_ATTRIBUTES.contains(&name.as_str()) || value.is_null() {
+                continue;
+            }
+            let stats = fields.entry(name).or_default();
+            stats.present += 1;
+            match value {
+                Value::Array(_) | Value::Object(_) => stats.structured = true,
+                Value::Number(_) => {
+                    stats.types.insert("NUMBER");
+                }
+                Value::Bool(_) => {
+                    stats.types.insert("BOOLEAN");
+                }
+                _ => {
+                    stats.types.insert("STRING");
+                }
+            }
+            stats.values.insert(value.to_string());
+        }
+    }
+
+    let mut schema = InferredSchema {
+        examples: examples.len(),
+        ..Default::default()
+    };
+    for (name, stats) in fields {
+        if stats.structured {
+            schema.skipped.push(name.to_string());
+            continue;
+        }
+        let attr_type = match stats.types.iter().collect::<Vec<_>>()[..] {
+            [only] => *only,
+            _ => "STRING",
+        };
+        if attr_type != "BOOLEAN"
+            && stats.values.len() > 1
+            && stats.values.len() * 2 <= stats.present
+        {
+            schema.suggested_indexes.push(name.to_string());
+        }
+        schema.attributes.push(AttributeDef {
+            name: name.to_string(),
+            attr_type: attr_type.to_string(),
+            required: stats.present == examples.len(),
+            aliases: Vec::new(),
+        });
+    }
+    if schema.attributes.is_empty() {
+        return Err("Examples have no attributes that can be typed".into());
+    }
+    Ok(schema)
+}
+
+/// Parse an examples file: a JSON array of objects, or one object per line.
+pub fn parse_examples(text: &str) -> Result<Vec<Value>, String> {
+    let text = text.trim();
+    if text.starts_with('[') {
+        return serde_json::from_str(text).map_err(|e| format!("Invalid examples JSON: {e}"));
+    }
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| format!("Line {}: invalid JSON: {e}", i + 1))
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_schema_from_examples() {
+        let examples = parse_examples(
+            r#"{"name": "Toby", "role": "cto", "age": 41, "remote": true, "tags": ["a"], "key": "toby"}
+{"name": "Ann", "role": "cto", "age": 35, "remote": false}
+{"name": "Raj", "role": "designer", "age": "n/a", "email": "raj@x.dev"}
+{"name": "Mia", "role": "designer", "remote": null}"#,
+        )
+        .unwrap();
+        let schema = infer_schema(&examples).unwrap();
+        assert_eq!(schema.examples, 4);
+
+        let attr = |name: &str| schema.attributes.iter().find(|a| a.name == name).unwrap();
+        assert_eq!(attr("name").attr_type, "STRING");
+        assert!(attr("name").required);
+        assert_eq!(attr("age").attr_type, "STRING");
+        assert!(!attr("age").required);
+        assert_eq!(attr("remote").attr_type, "BOOLEAN");
+        assert!(!attr("email").required);
+        assert!(schema.attributes.iter().all(|a| a.name != "key"));
+        assert_eq!(schema.skipped, ["tags"]);
+        assert_eq!(schema.suggested_indexes, ["role"]);
+
+        assert!(infer_schema(&[]).is_err());
+        assert!(infer_schema(&[serde_json::json!([1])]).is_err());
+        assert_eq!(
+            parse_examples(r#"[{"n": 1}, {"n": 2.5}]"#).unwrap().len(),
+            2
+        );
+    }
+}
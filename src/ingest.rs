@@ -0,0 +1,710 @@
+//! Ingestion of external content (web pages, documents, clipboard, git history)
+//! into memory.
+//!
+//! Fetched pages, local files, clipboard contents, and commit logs are reduced
+//! to readable plain text here; summarization, parsing, and chunking are
+//! handled by the normal `remember` pipeline.
+
+use std::io::Read;
+use std::path::Path;
+#[cfg(feature = "llm")]
+use std::time::Duration;
+
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Maximum response body accepted when fetching a URL.
+pub const MAX_FETCH_BYTES: usize = 5 * 1024 * 1024;
+
+/// Maximum file size accepted by [`read_file_text`].
+pub const MAX_FILE_BYTES: usize = 20 * 1024 * 1024;
+
+/// Timeout for fetching a URL.
+#[cfg(feature = "llm")]
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Errors that can occur while ingesting external content.
+#[derive(Debug, Error)]
+pub enum IngestError {
+    /// HTTP or network error occurred.
+    #[error("HTTP error: {0}")]
+    Http(String),
+
+    /// The content type cannot be converted to text.
+    #[error("Unsupported content type: {0}")]
+    Unsupported(String),
+
+    /// The content exceeds the size limit.
+    #[error("Content too large (at least {0} bytes, limit {1})")]
+    TooLarge(usize, usize),
+
+    /// Reading a local file failed.
+    #[error("I/O error: {0}")]
+    Io(String),
+
+    /// Text extraction from a PDF failed.
+    #[error("PDF extraction failed: {0}")]
+    Pdf(String),
+
+    /// The system clipboard could not be read.
+    #[error("Clipboard error: {0}")]
+    Clipboard(String),
+
+    /// Running `git` failed.
+    #[error("git error: {0}")]
+    Git(String),
+
+    /// No readable text could be extracted.
+    #[error("No readable text found")]
+    Empty,
+}
+
+/// Readable text extracted from a fetched page.
+#[derive(Debug, Clone)]
+pub struct PageText {
+    /// The page `<title>`, if any.
+    pub title: Option<String>,
+    /// Readable body text with paragraphs separated by blank lines.
+    pub text: String,
+}
+
+impl PageText {
+    /// Title and body combined, for summarization.
+    pub fn full_text(&self) -> String {
+        match &self.title {
+            Some(title) => format!("{title}\n\n{}", self.text),
+            None => self.text.clone(),
+        }
+    }
+}
+
+// ============================================================================
+// URL Fetching
+// ============================================================================
+
+/// Fetch a URL and extract its readable text.
+///
+/// HTML is reduced with [`html_to_text`]; `text/plain` and `text/markdown`
+/// bodies are used as-is. Other content types are rejected.
+#[cfg(feature = "llm")]
+pub async fn fetch_url_text(url: &str) -> Result<PageText, IngestError> {
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("fmemory/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| IngestError::Http(e.to_string()))?;
+
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| IngestError::Http(e.to_string()))?;
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("text/html")
+        .to_ascii_lowercase();
+
+    let body = read_body(response, MAX_FETCH_BYTES).await?;
+    let body = String::from_utf8_lossy(&body);
+
+    let page = if content_type.contains("html") {
+        PageText {
+            title: extract_title(&body),
+            text: html_to_text(&body),
+        }
+    } else if content_type.starts_with("text/") {
+        PageText {
+            title: None,
+            text: body.trim().to_string(),
+        }
+    } else {
+        return Err(IngestError::Unsupported(content_type));
+    };
+
+    if page.text.is_empty() {
+        return Err(IngestError::Empty);
+    }
+    Ok(page)
+}
+
+/// Read `response`'s body, rejecting it as soon as it is known to exceed
+/// `limit` bytes: up front from `Content-Length`, otherwise while streaming.
+#[cfg(feature = "llm")]
+async fn read_body(mut response: reqwest::Response, limit: usize) -> Result<Vec<u8>, IngestError> {
+    if let Some(length) = response.content_length()
+        && length > limit as u64
+    {
+        return Err(IngestError::TooLarge(length as usize, limit));
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| IngestError::Http(e.to_string()))?
+    {
+        if body.len() + chunk.len() > limit {
+            return Err(IngestError::TooLarge(body.len() + chunk.len(), limit));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+// ============================================================================
+// File Extraction
+// ============================================================================
+
+/// Document formats supported by [`read_file_text`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Text,
+    Markdown,
+    Pdf,
+}
+
+impl FileKind {
+    /// Detect the format from the file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "txt" | "text" | "log" | "rst" => Some(FileKind::Text),
+            "md" | "markdown" => Some(FileKind::Markdown),
+            "pdf" => Some(FileKind::Pdf),
+            _ => None,
+        }
+    }
+}
+
+/// Text extracted from a local file, with provenance.
+#[derive(Debug, Clone)]
+pub struct FileText {
+    /// Absolute path of the file.
+    pub path: String,
+    /// Hex-encoded SHA-256 of the file contents.
+    pub sha256: String,
+    pub kind: FileKind,
+    pub text: String,
+}
+
+/// Read the file at `path`, rejecting it without loading it when it exceeds
+/// `limit` bytes: up front from its size, otherwise (if it grew since) once
+/// `limit` bytes have been read.
+fn read_file_bytes(path: &Path, limit: usize) -> Result<Vec<u8>, IngestError> {
+    let file = std::fs::File::open(path).map_err(|e| IngestError::Io(e.to_string()))?;
+    let length = file
+        .metadata()
+        .map_err(|e| IngestError::Io(e.to_string()))?
+        .len();
+    if length > limit as u64 {
+        return Err(IngestError::TooLarge(length as usize, limit));
+    }
+    let mut bytes = Vec::with_capacity(length as usize);
+    file.take(limit as u64 + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| IngestError::Io(e.to_string()))?;
+    if bytes.len() > limit {
+        return Err(IngestError::TooLarge(bytes.len(), limit));
+    }
+    Ok(bytes)
+}
+
+/// Read a plain text, markdown, or PDF file and extract its text.
+pub fn read_file_text(path: &Path) -> Result<FileText, IngestError> {
+    let kind = FileKind::from_path(path).ok_or_else(|| {
+        IngestError::Unsupported(format!("{} (supported: .txt, .md, .pdf)", path.display()))
+    })?;
+
+    let bytes = read_file_bytes(path, MAX_FILE_BYTES)?;
+    let sha256 = sha256_hex(&bytes);
+
+    let text = match kind {
+        FileKind::Text | FileKind::Markdown => String::from_utf8_lossy(&bytes).trim().to_string(),
+        FileKind::Pdf => pdf_extract::extract_text_from_mem(&bytes)
+            .map_err(|e| IngestError::Pdf(e.to_string()))?
+            .trim()
+            .to_string(),
+    };
+    if text.is_empty() {
+        return Err(IngestError::Empty);
+    }
+
+    let path = std::fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string();
+    Ok(FileText {
+        path,
+        sha256,
+        kind,
+        text,
+    })
+}
+
+/// Hex-encoded SHA-256 digest.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Line separating multi-line facts in a facts file.
+pub const FACT_SEPARATOR: &str = "---";
+
+/// Split the contents of a facts file into facts.
+///
+/// If any line is exactly [`FACT_SEPARATOR`], each block between separators
+/// is one fact; otherwise each line is. Facts are trimmed and empty ones
+/// dropped.
+pub fn split_facts(text: &str) -> Vec<String> {
+    let facts: Vec<String> = if text.lines().any(|l| l.trim() == FACT_SEPARATOR) {
+        text.lines()
+            .collect::<Vec<_>>()
+            .split(|l| l.trim() == FACT_SEPARATOR)
+            .map(|block| block.join("\n"))
+            .collect()
+    } else {
+        text.lines().map(String::from).collect()
+    };
+    facts
+        .into_iter()
+        .map(|fact| fact.trim().to_string())
+        .filter(|fact| !fact.is_empty())
+        .collect()
+}
+
+// ============================================================================
+// Clipboard
+// ============================================================================
+
+/// Read text from the system clipboard.
+pub fn read_clipboard() -> Result<String, IngestError> {
+    let text = arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|e| IngestError::Clipboard(e.to_string()))?;
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(IngestError::Empty);
+    }
+    Ok(text.to_string())
+}
+
+// ============================================================================
+// Git History
+// ============================================================================
+
+/// Field and record separators for `git log` output (ASCII US / RS).
+const GIT_FIELD_SEP: char = '\x1f';
+const GIT_RECORD_SEP: char = '\x1e';
+
+/// A commit read from `git log`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitInfo {
+    pub sha: String,
+    pub author: String,
+    /// Author date, ISO 8601.
+    pub date: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Read commits from the repository in `repo_dir`, newest first.
+///
+/// With `since`, only commits in `since..HEAD` are returned. Merge commits
+/// are skipped. At most `limit` commits are read.
+pub fn read_git_commits(
+    repo_dir: &Path,
+    since: Option<&str>,
+    limit: usize,
+) -> Result<Vec<CommitInfo>, IngestError> {
+    let format = format!(
+        "--format=%H{GIT_FIELD_SEP}%an{GIT_FIELD_SEP}%aI{GIT_FIELD_SEP}%s{GIT_FIELD_SEP}%b{GIT_RECORD_SEP}"
+    );
+    let mut cmd = std::process::Command::new("git");
+    cmd.current_dir(repo_dir)
+        .args(["log", "--no-merges", &format])
+        .arg(format!("--max-count={limit}"));
+    if let Some(rev) = since {
+        cmd.arg(format!("{rev}..HEAD"));
+    }
+
+    let output = cmd.output().map_err(|e| IngestError::Git(e.to_string()))?;
+    if !output.status.success() {
+        return Err(IngestError::Git(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(parse_git_log(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `git log` output produced with the separators used by [`read_git_commits`].
+pub fn parse_git_log(output: &str) -> Vec<CommitInfo> {
+    output
+        .split(GIT_RECORD_SEP)
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(5, GIT_FIELD_SEP);
+            let sha = fields.next()?.trim();
+            if sha.is_empty() {
+                return None;
+            }
+            Some(CommitInfo {
+                sha: sha.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+                body: fields.next().unwrap_or_default().trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+// ============================================================================
+// HTML Extraction
+// ============================================================================
+
+/// Elements whose content is never readable text.
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "svg", "head", "nav", "footer", "form", "iframe",
+];
+
+/// Elements that start a new line of text.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "br",
+    "li",
+    "tr",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "section",
+    "article",
+    "blockquote",
+    "pre",
+    "header",
+    "table",
+    "ul",
+    "ol",
+    "dd",
+    "dt",
+    "hr",
+];
+
+/// Extract the contents of the `<title>` element.
+pub fn extract_title(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let start = lower.find("<title")?;
+    let open_end = lower[start..].find('>')? + start + 1;
+    let close = lower[open_end..].find("</title")? + open_end;
+    let title = collapse_whitespace(&decode_entities(&html[open_end..close]));
+    (!title.is_empty()).then_some(title)
+}
+
+/// Reduce an HTML document to readable plain text.
+///
+/// Prefers the `<article>` or `<main>` element when present, drops scripts,
+/// styles, and navigation chrome, and separates blocks with blank lines.
+pub fn html_to_text(html: &str) -> String {
+    let body = main_content(html);
+
+    let mut out = String::new();
+    let mut skipping: Option<String> = None;
+    let mut rest = body;
+
+    while let Some(lt) = rest.find('<') {
+        if skipping.is_none() {
+            out.push_str(&decode_entities(&rest[..lt]));
+        }
+        rest = &rest[lt..];
+
+        // Comments.
+        if rest.starts_with("<!--") {
+            rest = rest.find("-->").map_or("", |end| &rest[end + 3..]);
+            continue;
+        }
+
+        let Some(gt) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[1..gt];
+        rest = &rest[gt + 1..];
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        if let Some(ref skipped) = skipping {
+            if closing && &name == skipped {
+                skipping = None;
+            }
+            continue;
+        }
+        if !closing && !tag.ends_with('/') && SKIPPED_ELEMENTS.contains(&name.as_str()) {
+            skipping = Some(name);
+            continue;
+        }
+        if BLOCK_ELEMENTS.contains(&name.as_str()) {
+            out.push_str("\n\n");
+        }
+    }
+    if skipping.is_none() {
+        out.push_str(&decode_entities(rest));
+    }
+
+    // Collapse whitespace within lines and runs of blank lines.
+    let mut paragraphs: Vec<String> = Vec::new();
+    for block in out.split("\n\n") {
+        let line = collapse_whitespace(block);
+        if !line.is_empty() {
+            paragraphs.push(line);
+        }
+    }
+    paragraphs.join("\n\n")
+}
+
+/// Slice out the `<article>` or `<main>` element, or `<body>`, if present.
+fn main_content(html: &str) -> &str {
+    let lower = html.to_ascii_lowercase();
+    for tag in ["article", "main", "body"] {
+        let open = format!("<{tag}");
+        let close = format!("</{tag}>");
+        if let Some(start) = lower.find(&open)
+            && let Some(end) = lower.rfind(&close)
+            && end > start
+        {
+            return &html[start..end];
+        }
+    }
+    html
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Decode the common named entities and numeric character references.
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let decoded = rest.find(';').filter(|&semi| semi <= 10).and_then(|semi| {
+            let entity = &rest[1..semi];
+            let ch = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" | "#39" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|d| d.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            ch.map(|c| (c, semi))
+        });
+
+        match decoded {
+            Some((c, semi)) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_read_body_stops_at_limit() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for head in [
+                "Content-Length: 64",
+                "Connection: close",
+                "Connection: close",
+            ] {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = [0u8; 1024];
+                let _ = socket.read(&mut request).await;
+                let body = if head.starts_with("Content") { 64 } else { 48 };
+                let response = format!("HTTP/1.1 200 OK\r\n{head}\r\n\r\n{}", "x".repeat(body));
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        let get = || reqwest::get(format!("http://{addr}/"));
+
+        // Declared too large: rejected before reading.
+        let declared = read_body(get().await.unwrap(), 32).await;
+        assert!(matches!(declared, Err(IngestError::TooLarge(64, 32))));
+        // Undeclared: rejected once the stream passes the limit.
+        let streamed = read_body(get().await.unwrap(), 32).await;
+        assert!(matches!(streamed, Err(IngestError::TooLarge(n, 32)) if n > 32));
+        assert_eq!(read_body(get().await.unwrap(), 64).await.unwrap().len(), 48);
+    }
+
+    #[test]
+    fn test_split_facts() {
+        assert_eq!(
+            split_facts("Toby is on infra\n\n  We deploy on Fridays  \n"),
+            vec!["Toby is on infra", "We deploy on Fridays"]
+        );
+        assert_eq!(
+            split_facts("Auth uses JWT.\nTokens last 1h.\n---\n\n---\nToby owns billing\n"),
+            vec!["Auth uses JWT.\nTokens last 1h.", "Toby owns billing"]
+        );
+        assert!(split_facts("\n \n").is_empty());
+    }
+
+    #[test]
+    fn test_extract_title() {
+        let html = "<html><head><title> Rust &amp; Memory </title></head></html>";
+        assert_eq!(extract_title(html).as_deref(), Some("Rust & Memory"));
+        assert_eq!(extract_title("<p>no title</p>"), None);
+    }
+
+    #[test]
+    fn test_html_to_text_drops_scripts_and_chrome() {
+        let html = r#"<html><head><style>p{}</style></head><body>
+            <nav><a href="/">Home</a></nav>
+            <h1>Release notes</h1>
+            <script>var x = "<p>hidden</p>";</script>
+            <p>Version <b>2.0</b> adds   chunking.</p>
+            <!-- comment -->
+            <footer>Copyright</footer>
+        </body></html>"#;
+        let text = html_to_text(html);
+        assert_eq!(text, "Release notes\n\nVersion 2.0 adds chunking.");
+    }
+
+    #[test]
+    fn test_html_to_text_prefers_article() {
+        let html = "<body><div>sidebar</div><article><p>Main story</p></article></body>";
+        assert_eq!(html_to_text(html), "Main story");
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("a &lt;b&gt; &#65;&#x42; &unknown; &"),
+            "a <b> AB &unknown; &"
+        );
+    }
+
+    #[test]
+    fn test_page_full_text_includes_title() {
+        let page = PageText {
+            title: Some("Title".into()),
+            text: "Body".into(),
+        };
+        assert_eq!(page.full_text(), "Title\n\nBody");
+    }
+
+    #[test]
+    fn test_file_kind_from_path() {
+        assert_eq!(
+            FileKind::from_path(Path::new("a/README.md")),
+            Some(FileKind::Markdown)
+        );
+        assert_eq!(
+            FileKind::from_path(Path::new("spec.PDF")),
+            Some(FileKind::Pdf)
+        );
+        assert_eq!(
+            FileKind::from_path(Path::new("notes.txt")),
+            Some(FileKind::Text)
+        );
+        assert_eq!(FileKind::from_path(Path::new("image.png")), None);
+        assert_eq!(FileKind::from_path(Path::new("Makefile")), None);
+    }
+
+    #[test]
+    fn test_read_file_text_with_provenance() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("design.md");
+        std::fs::write(&path, "# Design\n\nUse chunking.\n").unwrap();
+
+        let file = read_file_text(&path).unwrap();
+        assert_eq!(file.kind, FileKind::Markdown);
+        assert_eq!(file.text, "# Design\n\nUse chunking.");
+        assert_eq!(file.sha256, sha256_hex(b"# Design\n\nUse chunking.\n"));
+        assert!(file.path.ends_with("design.md"));
+    }
+
+    #[test]
+    fn test_read_file_bytes_rejects_oversized_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, [b'a'; 64]).unwrap();
+        assert!(matches!(
+            read_file_bytes(&path, 32),
+            Err(IngestError::TooLarge(64, 32))
+        ));
+        assert_eq!(read_file_bytes(&path, 64).unwrap().len(), 64);
+    }
+
+    #[test]
+    fn test_read_file_text_rejects_unsupported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.png");
+        std::fs::write(&path, [0u8; 4]).unwrap();
+        assert!(matches!(
+            read_file_text(&path),
+            Err(IngestError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_parse_git_log() {
+        let output = "abc123\x1fAda\x1f2026-01-02T03:04:05+00:00\x1fUse WAL mode\x1fFaster writes.\n\nCloses #4\n\x1e\n\
+                      def456\x1fBob\x1f2026-01-01T00:00:00+00:00\x1fFix typo\x1f\x1e\n";
+        let commits = parse_git_log(output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].sha, "abc123");
+        assert_eq!(commits[0].subject, "Use WAL mode");
+        assert_eq!(commits[0].body, "Faster writes.\n\nCloses #4");
+        assert_eq!(commits[1].author, "Bob");
+        assert_eq!(commits[1].body, "");
+    }
+}
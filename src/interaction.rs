@@ -0,0 +1,157 @@
+//! Opt-in history of natural language recalls.
+//!
+//! With `FMEMORY_LOG_RECALL=1`, every recall run through a
+//! [`MemoryStore`](crate::store::MemoryStore) is recorded as an
+//! `interactions` item with `source: "recall"`: the question, the plan it
+//! was resolved to, the answer when one was synthesized, the addresses
+//! of the items it was answered from, and the request ID of the recall. The items expire with the category's
+//! 90-day TTL and can be searched like any other memory, e.g. to find
+//! questions that came back empty.
+
+use serde_json::{Value, json};
+
+use crate::backend::MemoryBackend;
+use crate::error::MemoryError;
+use crate::keys::new_ulid_key;
+use crate::logging::current_request_id;
+use crate::schema::ResolvedQuery;
+use crate::store::build_item;
+
+/// Environment variable enabling recall logging (`1`, `true`, or `yes`).
+pub const RECALL_LOG_ENV: &str = "FMEMORY_LOG_RECALL";
+
+/// Category recall logs are stored in.
+pub const INTERACTIONS_CATEGORY: &str = "interactions";
+
+/// `source` of a recall log item.
+pub const RECALL_SOURCE: &str = "recall";
+
+/// Whether `FMEMORY_LOG_RECALL` enables recall logging.
+pub fn recall_log_from_env() -> bool {
+    std::env::var(RECALL_LOG_ENV)
+        .is_ok_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+}
+
+/// `plan` in the shape the LLM resolves queries to, plus whether the
+/// results came from the category-wide `fallback` scan.
+pub fn plan_json(plan: &ResolvedQuery, fallback: bool) -> Value {
+    let mut value = match plan {
+        ResolvedQuery::IndexLookup {
+            category,
+            index_name,
+            key_value,
+        } => json!({
+            "type": "index",
+            "category": category,
+            "index_name": index_name,
+            "key_value": key_value,
+        }),
+        ResolvedQuery::PartitionScan {
+            category,
+            key_prefix,
+        } => json!({"type": "scan", "category": category, "key_prefix": key_prefix}),
+        ResolvedQuery::ExactLookup { category, key } => {
+            json!({"type": "exact", "category": category, "key": key})
+        }
+        ResolvedQuery::IndexRange {
+            category,
+            index_name,
+            from,
+            to,
+        } => json!({
+            "type": "range",
+            "category": category,
+            "index_name": index_name,
+            "from": from,
+            "to": to,
+        }),
+    };
+    value["fallback"] = json!(fallback);
+    value
+}
+
+/// The `interactions` item recording one recall.
+pub fn recall_log_item(
+    query: &str,
+    plan: &ResolvedQuery,
+    fallback: bool,
+    answer: Option<&str>,
+    items: &[Value],
+) -> Value {
+    let used: Vec<String> = items
+        .iter()
+        .filter_map(|item| {
+            Some(format!(
+                "{}/{}",
+                item["category"].as_str()?,
+                item["key"].as_str()?
+            ))
+        })
+        .collect();
+    let mut doc = json!({
+        "source": RECALL_SOURCE,
+        "date": chrono::Local::now().format("%Y-%m-%d").to_string(),
+        "summary": format!("Recall: {query}"),
+        "query": query,
+        "plan": plan_json(plan, fallback),
+        "items": used,
+    });
+    if let Some(answer) = answer {
+        doc["answer"] = json!(answer);
+    }
+    if let Some(request_id) = current_request_id() {
+        doc["request_id"] = json!(request_id);
+    }
+    build_item(INTERACTIONS_CATEGORY, &new_ulid_key(), &doc, None)
+}
+
+/// Store the log item of one recall.
+pub async fn log_recall(
+    backend: &MemoryBackend,
+    query: &str,
+    plan: &ResolvedQuery,
+    fallback: bool,
+    answer: Option<&str>,
+    items: &[Value],
+) -> Result<Value, MemoryError> {
+    let item = recall_log_item(query, plan, fallback, answer, items);
+    backend.put_bookkeeping_item(item.clone()).await?;
+    Ok(item)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recall_log_item() {
+        let plan = ResolvedQuery::IndexLookup {
+            category: "contacts".into(),
+            index_name: "contacts_role".into(),
+            key_value: "cto".into(),
+        };
+        let items = [json!({"category": "contacts", "key": "toby", "role": "cto"})];
+        let item = recall_log_item("who is the CTO?", &plan, false, Some("Toby."), &items);
+        assert_eq!(item["category"], INTERACTIONS_CATEGORY);
+        assert_eq!(item["source"], RECALL_SOURCE);
+        assert_eq!(item["plan"]["type"], "index");
+        assert_eq!(item["plan"]["key_value"], "cto");
+        assert_eq!(item["plan"]["fallback"], false);
+        assert_eq!(item["items"], json!(["contacts/toby"]));
+        assert_eq!(item["answer"], "Toby.");
+        assert!(item["expires_at"].is_string());
+
+        let scan = ResolvedQuery::PartitionScan {
+            category: "notes".into(),
+            key_prefix: None,
+        };
+        let empty = recall_log_item("anything on Rust?", &scan, false, None, &[]);
+        assert!(empty.get("answer").is_none());
+        assert_eq!(empty["items"], json!([]));
+        assert_eq!(empty["plan"]["key_prefix"], Value::Null);
+    }
+}
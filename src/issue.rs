@@ -0,0 +1,144 @@
+//! Issue lifecycle: resolving and reopening `issues` items.
+//!
+//! An issue is open until its `resolved` attribute is `true`.
+//! [`resolve_issue`] sets it, stamps `resolved_at`, and records the `fix`
+//! when one is given; [`reopen_issue`] clears `resolved` and `resolved_at`
+//! and keeps the fix as a record of what was tried. Both go through
+//! [`MemoryBackend::update_item`], so the rest of the item is untouched.
+//! [`open_issues`] lists the live issues not yet resolved.
+
+use serde_json::{Map, Value};
+
+use crate::backend::MemoryBackend;
+use crate::chunk::is_chunk;
+use crate::error::MemoryError;
+use crate::ttl::is_expired;
+
+/// Category issue items are stored in.
+pub const ISSUES_CATEGORY: &str = "issues";
+
+/// Boolean attribute marking an issue resolved.
+pub const RESOLVED_ATTRIBUTE: &str = "resolved";
+
+/// RFC 3339 timestamp of the latest resolution.
+pub const RESOLVED_AT_ATTRIBUTE: &str = "resolved_at";
+
+/// Attribute describing how an issue was fixed.
+pub const FIX_ATTRIBUTE: &str = "fix";
+
+/// Maximum number of issues scanned by [`open_issues`].
+const ISSUES_SCAN_LIMIT: usize = 10_000;
+
+/// Mark `issues/key` resolved, recording `fix` if given. Returns the updated
+/// item, or `None` if there is no such issue.
+pub async fn resolve_issue(
+    backend: &MemoryBackend,
+    key: &str,
+    fix: Option<&str>,
+) -> Result<Option<Value>, MemoryError> {
+    let mut changes = Map::new();
+    changes.insert(RESOLVED_ATTRIBUTE.into(), Value::Bool(true));
+    changes.insert(
+        RESOLVED_AT_ATTRIBUTE.into(),
+        Value::String(chrono::Utc::now().to_rfc3339()),
+    );
+    if let Some(fix) = fix {
+        changes.insert(FIX_ATTRIBUTE.into(), Value::String(fix.to_string()));
+    }
+    backend.update_item(ISSUES_CATEGORY, key, &changes).await
+}
+
+/// Mark `issues/key` open again. Returns the updated item, or `None` if
+/// there is no such issue.
+pub async fn reopen_issue(
+    backend: &MemoryBackend,
+    key: &str,
+) -> Result<Option<Value>, MemoryError> {
+    let mut changes = Map::new();
+    changes.insert(RESOLVED_ATTRIBUTE.into(), Value::Bool(false));
+    changes.insert(RESOLVED_AT_ATTRIBUTE.into(), Value::Null);
+    backend.update_item(ISSUES_CATEGORY, key, &changes).await
+}
+
+/// Whether `item` is an issue not marked resolved.
+pub fn is_open(item: &Value) -> bool {
+    item[RESOLVED_ATTRIBUTE].as_bool() != Some(true)
+}
+
+/// Unexpired open issues, newest first, at most `limit`.
+pub async fn open_issues(backend: &MemoryBackend, limit: usize) -> Result<Vec<Value>, MemoryError> {
+    let mut issues: Vec<Value> = backend
+        .query(ISSUES_CATEGORY, None, ISSUES_SCAN_LIMIT)
+        .await?
+        .into_iter()
+        .filter(|item| !is_chunk(item) && !is_expired(item) && is_open(item))
+        .collect();
+    issues.sort_by(|a, b| {
+        let created = |item: &Value| item["created_at"].as_str().unwrap_or_default().to_string();
+        created(b).cmp(&created(a))
+    });
+    issues.truncate(limit);
+    Ok(issues)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_resolve_and_reopen_issue() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for (key, created, resolved) in [
+            ("login-loop", "2026-03-01T00:00:00+00:00", false),
+            ("slow-build", "2026-03-02T00:00:00+00:00", false),
+            ("old-crash", "2026-02-01T00:00:00+00:00", true),
+        ] {
+            backend
+                .put_item(json!({"category": ISSUES_CATEGORY, "key": key,
+                    "symptom": key, "created_at": created, "resolved": resolved}))
+                .await
+                .unwrap();
+        }
+        let keys = |items: Vec<Value>| -> Vec<String> {
+            items
+                .iter()
+                .filter_map(|i| i["key"].as_str().map(String::from))
+                .collect()
+        };
+        assert_eq!(
+            keys(open_issues(&backend, 10).await.unwrap()),
+            ["slow-build", "login-loop"]
+        );
+
+        let resolved = resolve_issue(&backend, "login-loop", Some("Cleared stale cookies"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(resolved[RESOLVED_ATTRIBUTE], true);
+        assert_eq!(resolved["symptom"], "login-loop");
+        assert!(resolved[RESOLVED_AT_ATTRIBUTE].is_string());
+        assert_eq!(
+            keys(open_issues(&backend, 10).await.unwrap()),
+            ["slow-build"]
+        );
+
+        let reopened = reopen_issue(&backend, "login-loop").await.unwrap().unwrap();
+        assert_eq!(reopened[RESOLVED_ATTRIBUTE], false);
+        assert!(reopened.get(RESOLVED_AT_ATTRIBUTE).is_none());
+        assert_eq!(reopened[FIX_ATTRIBUTE], "Cleared stale cookies");
+        assert_eq!(open_issues(&backend, 1).await.unwrap().len(), 1);
+
+        assert!(
+            resolve_issue(&backend, "missing", None)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
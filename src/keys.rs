@@ -0,0 +1,345 @@
+//! Item key generation.
+//!
+//! [`slugify`] normalizes free text into the lowercase, hyphenated form the
+//! parse prompts ask the LLM for, so the same entity gets the same key however
+//! the model (or a user) spells it. Append-heavy categories can opt into
+//! time-sortable ULID keys instead; [`KeyStrategies`] picks between the two
+//! per category.
+//!
+//! [`normalize_key`] and [`normalize_category`] are the validation every
+//! stored item passes through: NFC normalization, an allowed character set,
+//! and a maximum length. Categories starting with `_` are kept for the
+//! internal partitions in [`SYSTEM_CATEGORIES`](crate::SYSTEM_CATEGORIES),
+//! and [`validate_schema_category`] additionally keeps `_` out of categories that
+//! carry schemas, since their index names are `{category}_{attr}`.
+
+use std::collections::HashMap;
+
+use deunicode::deunicode;
+use tracing::warn;
+use unicode_normalization::UnicodeNormalization;
+
+/// Maximum length of a generated slug, in characters.
+pub const MAX_SLUG_CHARS: usize = 64;
+
+/// Maximum length of an item key, in characters.
+pub const MAX_KEY_CHARS: usize = 512;
+
+/// Maximum length of a category name, in characters.
+pub const MAX_CATEGORY_CHARS: usize = 64;
+
+/// Punctuation allowed in keys besides letters and digits. `#` separates
+/// chunk and hierarchy segments.
+const KEY_PUNCTUATION: &[char] = &['-', '_', '.', '#', ':', '/', '@', '+', '=', '~'];
+
+/// Environment variable opting categories into other key strategies, as
+/// comma-separated `category=slug|ulid` pairs.
+pub const KEY_STRATEGY_ENV: &str = "FMEMORY_KEY_STRATEGY";
+
+/// How keys are chosen for items stored without an explicit key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStrategy {
+    /// Slug of the LLM-proposed key (see [`slugify`]).
+    Slug,
+    /// A new lowercase ULID, so keys sort chronologically.
+    Ulid,
+}
+
+impl std::str::FromStr for KeyStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "slug" => Ok(KeyStrategy::Slug),
+            "ulid" => Ok(KeyStrategy::Ulid),
+            other => Err(format!(
+                "Unknown key strategy '{other}' (expected slug or ulid)"
+            )),
+        }
+    }
+}
+
+/// Per-category key strategies: slugs, except where overridden.
+#[derive(Debug, Clone, Default)]
+pub struct KeyStrategies {
+    overrides: HashMap<String, KeyStrategy>,
+}
+
+impl KeyStrategies {
+    /// Defaults plus overrides from `FMEMORY_KEY_STRATEGY`. Malformed entries
+    /// are skipped with a warning.
+    pub fn from_env() -> Self {
+        let mut strategies = Self::default();
+        let Ok(spec) = std::env::var(KEY_STRATEGY_ENV) else {
+            return strategies;
+        };
+        for entry in spec.split(',').filter(|e| !e.trim().is_empty()) {
+            let parsed = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected category=strategy, got '{entry}'"))
+                .and_then(|(category, strategy)| Ok((category.trim(), strategy.parse()?)));
+            match parsed {
+                Ok((category, strategy)) => strategies.set(category, strategy),
+                Err(e) => warn!("Ignoring {KEY_STRATEGY_ENV} entry: {e}"),
+            }
+        }
+        strategies
+    }
+
+    /// Override the strategy for `category`.
+    pub fn set(&mut self, category: &str, strategy: KeyStrategy) {
+        self.overrides.insert(category.to_string(), strategy);
+    }
+
+    pub fn for_category(&self, category: &str) -> KeyStrategy {
+        self.overrides
+            .get(category)
+            .copied()
+            .unwrap_or(KeyStrategy::Slug)
+    }
+
+    /// Key for a new item in `category`: `proposed` for slug categories, a
+    /// fresh ULID otherwise.
+    pub fn key_for(&self, category: &str, proposed: String) -> String {
+        match self.for_category(category) {
+            KeyStrategy::Slug => proposed,
+            KeyStrategy::Ulid => new_ulid_key(),
+        }
+    }
+}
+
+/// A new ULID in lowercase: 26 characters, ordered by creation time.
+pub fn new_ulid_key() -> String {
+    ulid::Ulid::new().to_string().to_ascii_lowercase()
+}
+
+/// Turn text into a key: transliterate to ASCII, lowercase, and join the
+/// alphanumeric runs with `-`.
+///
+/// `"Café Résumé!"` becomes `"cafe-resume"`. Slugs longer than
+/// [`MAX_SLUG_CHARS`] are cut at the last word boundary that fits. Returns an
+/// empty string if `text` has no alphanumeric characters.
+pub fn slugify(text: &str) -> String {
+    let folded = deunicode(text);
+    let mut slug = String::new();
+    for word in folded
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        let sep = usize::from(!slug.is_empty());
+        if slug.len() + sep + word.len() > MAX_SLUG_CHARS {
+            if slug.is_empty() {
+                slug = word[..MAX_SLUG_CHARS].to_ascii_lowercase();
+            }
+            break;
+        }
+        if sep == 1 {
+            slug.push('-');
+        }
+        slug.push_str(&word.to_ascii_lowercase());
+    }
+    slug
+}
+
+/// Key for a parsed document: the LLM-proposed key slugified, or a slug of the
+/// source text when the proposal is missing or has no usable characters.
+pub fn key_or_fallback(proposed: Option<&str>, text: &str) -> String {
+    let key = proposed.map(slugify).unwrap_or_default();
+    if !key.is_empty() {
+        return key;
+    }
+    match slugify(text) {
+        fallback if fallback.is_empty() => "unknown".to_string(),
+        fallback => fallback,
+    }
+}
+
+/// Unicode NFC form of `text`, so composed and decomposed spellings of the
+/// same characters compare (and prefix-match) equal.
+pub fn nfc(text: &str) -> String {
+    text.nfc().collect()
+}
+
+/// Normalize and validate an item key.
+///
+/// Keys are NFC-normalized and may contain letters and digits in any script
+/// plus `- _ . # : / @ + = ~`. Whitespace, emoji, and control characters are
+/// rejected, as are empty keys and keys over [`MAX_KEY_CHARS`].
+pub fn normalize_key(key: &str) -> Result<String, String> {
+    let key = nfc(key);
+    if key.is_empty() {
+        return Err("key must not be empty".to_string());
+    }
+    if key.chars().count() > MAX_KEY_CHARS {
+        return Err(format!("key is longer than {MAX_KEY_CHARS} characters"));
+    }
+    if let Some(bad) = key
+        .chars()
+        .find(|c| !c.is_alphanumeric() && !KEY_PUNCTUATION.contains(c))
+    {
+        return Err(format!(
+            "key '{key}' contains '{}'; use letters, digits, and - _ . # : / @ + = ~",
+            bad.escape_default()
+        ));
+    }
+    Ok(key)
+}
+
+/// Normalize and validate a category name.
+///
+/// Categories become partition keys and index-name prefixes, so they are
+/// limited to lowercase ASCII letters, digits, `_`, and `-`, must start with a
+/// letter, and may be at most [`MAX_CATEGORY_CHARS`] long. Names starting with
+/// `_` are reserved for [`SYSTEM_CATEGORIES`](crate::SYSTEM_CATEGORIES).
+pub fn normalize_category(category: &str) -> Result<String, String> {
+    let category = nfc(category);
+    if category.is_empty() {
+        return Err("category must not be empty".to_string());
+    }
+    if category.starts_with('_') {
+        return Err(format!(
+            "category '{category}' is reserved for internal use"
+        ));
+    }
+    if category.chars().count() > MAX_CATEGORY_CHARS {
+        return Err(format!(
+            "category is longer than {MAX_CATEGORY_CHARS} characters"
+        ));
+    }
+    if !category.starts_with(|c: char| c.is_ascii_lowercase()) {
+        return Err(format!(
+            "category '{category}' must start with a lowercase letter"
+        ));
+    }
+    if let Some(bad) = category
+        .chars()
+        .find(|c| !(c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '_' || *c == '-'))
+    {
+        return Err(format!(
+            "category '{category}' contains '{}'; use lowercase a-z, 0-9, _ and -",
+            bad.escape_default()
+        ));
+    }
+    Ok(category)
+}
+
+/// Validate a category that is getting a partition schema.
+///
+/// On top of [`normalize_category`], `_` is rejected: index names are
+/// `{category}_{attr}`, so `a_b` + `c` and `a` + `b_c` would both be `a_b_c`.
+pub fn validate_schema_category(category: &str) -> Result<String, String> {
+    let category = normalize_category(category)?;
+    if category.contains('_') {
+        return Err(format!(
+            "schema category '{category}' must not contain '_' (index names are category_attribute); use '-' instead"
+        ));
+    }
+    Ok(category)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify_normalizes() {
+        assert_eq!(slugify("Auth Method"), "auth-method");
+        assert_eq!(slugify("  auth_method!! "), "auth-method");
+        assert_eq!(
+            slugify("AetherXHub/ferridyn-memory"),
+            "aetherxhub-ferridyn-memory"
+        );
+        assert_eq!(slugify("Café Résumé"), "cafe-resume");
+        assert_eq!(slugify("Straße"), "strasse");
+        assert_eq!(slugify("!!!"), "");
+    }
+
+    #[test]
+    fn test_slugify_is_idempotent() {
+        for text in ["Toby's Birthday", "ferridyndb", "Q3 2026 — planning"] {
+            let once = slugify(text);
+            assert_eq!(slugify(&once), once);
+        }
+    }
+
+    #[test]
+    fn test_slugify_truncates_at_word_boundary() {
+        let slug = slugify(&"word ".repeat(30));
+        assert!(slug.len() <= MAX_SLUG_CHARS);
+        assert!(slug.ends_with("word"));
+        assert_eq!(slugify(&"x".repeat(100)).len(), MAX_SLUG_CHARS);
+    }
+
+    #[test]
+    fn test_key_strategies() {
+        let mut strategies = KeyStrategies::default();
+        assert_eq!(strategies.for_category("scratchpad"), KeyStrategy::Slug);
+        assert_eq!(strategies.for_category("contacts"), KeyStrategy::Slug);
+        assert_eq!(strategies.key_for("contacts", "toby".into()), "toby");
+
+        strategies.set("notes", KeyStrategy::Ulid);
+        assert_eq!(strategies.for_category("notes"), KeyStrategy::Ulid);
+        let key = strategies.key_for("notes", "ignored".into());
+        assert_eq!(key.len(), 26);
+        assert_eq!(key, key.to_ascii_lowercase());
+    }
+
+    #[test]
+    fn test_ulid_keys_sort_chronologically() {
+        let first = new_ulid_key();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = new_ulid_key();
+        assert!(first < second);
+    }
+
+    #[test]
+    fn test_normalize_key() {
+        // Decomposed "e" + combining acute becomes the composed form.
+        assert_eq!(normalize_key("cafe\u{301}").unwrap(), "caf\u{e9}");
+        assert_eq!(normalize_key("Toby").unwrap(), "Toby");
+        assert_eq!(normalize_key("doc#1").unwrap(), "doc#1");
+        assert_eq!(
+            normalize_key("email:reminder:events/standup:2026-03-10T09:00:00+00:00").unwrap(),
+            "email:reminder:events/standup:2026-03-10T09:00:00+00:00"
+        );
+        assert!(normalize_key("").is_err());
+        assert!(normalize_key("two words").is_err());
+        assert!(normalize_key("party-\u{1f389}").is_err());
+        assert!(normalize_key("line\nbreak").is_err());
+        assert!(normalize_key(&"k".repeat(MAX_KEY_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_normalize_category() {
+        assert_eq!(normalize_category("contacts").unwrap(), "contacts");
+        assert_eq!(normalize_category("llm_usage").unwrap(), "llm_usage");
+        assert!(normalize_category("_llm_usage").is_err());
+        assert!(normalize_category("Contacts").is_err());
+        assert!(normalize_category("1st").is_err());
+        assert!(normalize_category("caf\u{e9}").is_err());
+        assert!(normalize_category("a b").is_err());
+        assert!(normalize_category(&"c".repeat(MAX_CATEGORY_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_reserved_categories() {
+        for reserved in crate::SYSTEM_CATEGORIES.iter().chain(&["_internal"]) {
+            let err = normalize_category(reserved).unwrap_err();
+            assert!(err.contains("reserved"), "{err}");
+        }
+        assert!(validate_schema_category("meeting-notes").is_ok());
+        assert!(validate_schema_category("meeting_notes").is_err());
+        assert!(validate_schema_category("_schema").is_err());
+    }
+
+    #[test]
+    fn test_key_or_fallback() {
+        assert_eq!(key_or_fallback(Some("Toby Smith"), "ignored"), "toby-smith");
+        assert_eq!(key_or_fallback(None, "Staging URL"), "staging-url");
+        assert_eq!(key_or_fallback(Some("??"), "!!"), "unknown");
+    }
+}
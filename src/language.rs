@@ -0,0 +1,195 @@
+//! Prompts for memories written in a language other than English.
+//!
+//! The system prompts are written in English and their rules quote English
+//! phrases ("remember", "what", "tomorrow"). With `FMEMORY_LANGUAGE` set,
+//! [`LanguageLlmClient`] appends an instruction to every system prompt that
+//! applies those rules to the configured language and keeps extracted
+//! values, answers, and summaries in the language of the source text. JSON
+//! field names, keys, categories, and other fixed values stay as the prompts
+//! specify.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::llm::{Completion, LlmClient, LlmError, LlmFeature};
+
+/// Environment variable naming the language memories are written in.
+pub const LANGUAGE_ENV: &str = "FMEMORY_LANGUAGE";
+
+/// ISO 639-1 codes accepted in place of a language name.
+const LANGUAGE_CODES: &[(&str, &str)] = &[
+    ("da", "Danish"),
+    ("de", "German"),
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fi", "Finnish"),
+    ("fr", "French"),
+    ("it", "Italian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("nl", "Dutch"),
+    ("no", "Norwegian"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("ru", "Russian"),
+    ("sv", "Swedish"),
+    ("tr", "Turkish"),
+    ("uk", "Ukrainian"),
+    ("zh", "Chinese"),
+];
+
+/// The language named by `value`, as a language name: codes such as `de` are
+/// expanded, names are kept as given. English and empty values need no
+/// adjustment and give `None`.
+pub fn parse_language(value: &str) -> Option<String> {
+    let value = value.trim();
+    let name = LANGUAGE_CODES
+        .iter()
+        .find(|(code, _)| code.eq_ignore_ascii_case(value))
+        .map_or(value, |(_, name)| name);
+    (!name.is_empty() && !name.eq_ignore_ascii_case("english")).then(|| name.to_string())
+}
+
+/// Read the language from `FMEMORY_LANGUAGE`; `None` when unset or English.
+pub fn language_from_env() -> Option<String> {
+    std::env::var(LANGUAGE_ENV)
+        .ok()
+        .and_then(|v| parse_language(&v))
+}
+
+/// The instruction appended to system prompts for `language`.
+pub fn language_instruction(language: &str) -> String {
+    format!(
+        "Language: the user writes in {language}. Inputs, stored memories, and queries may be in \
+         {language}; read them as such, and apply rules that quote English words or phrases \
+         (such as \"remember\", \"what\", or \"tomorrow\") to their {language} equivalents. Keep \
+         attribute values, answers, summaries, and other free text in the language of the \
+         source text; never translate them into English. JSON field names, keys, category \
+         names, and fixed values such as \"remember\"/\"recall\" stay exactly as specified above."
+    )
+}
+
+/// LLM client wrapper that adapts every system prompt to a language.
+pub struct LanguageLlmClient {
+    inner: Arc<dyn LlmClient>,
+    instruction: String,
+}
+
+impl LanguageLlmClient {
+    pub fn new(inner: Arc<dyn LlmClient>, language: &str) -> Self {
+        Self {
+            inner,
+            instruction: language_instruction(language),
+        }
+    }
+
+    /// Wrap `inner` for the language in `FMEMORY_LANGUAGE`, or return it
+    /// unchanged when none is set.
+    pub fn from_env(inner: Arc<dyn LlmClient>) -> Arc<dyn LlmClient> {
+        match language_from_env() {
+            Some(language) => Arc::new(Self::new(inner, &language)),
+            None => inner,
+        }
+    }
+
+    fn system_prompt(&self, system: &str) -> String {
+        format!("{system}\n\n{}", self.instruction)
+    }
+}
+
+#[async_trait]
+impl LlmClient for LanguageLlmClient {
+    async fn complete(&self, system: &str, user: &str) -> Result<Completion, LlmError> {
+        self.inner.complete(&self.system_prompt(system), user).await
+    }
+
+    async fn complete_for(
+        &self,
+        feature: LlmFeature,
+        system: &str,
+        user: &str,
+    ) -> Result<Completion, LlmError> {
+        self.inner
+            .complete_for(feature, &self.system_prompt(system), user)
+            .await
+    }
+
+    fn model(&self) -> Option<&str> {
+        self.inner.model()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alias::AttributeAliases;
+    use crate::llm::MockLlmClient;
+    use crate::schema::{
+        AttributeInfo, NlIntent, PartitionSchemaInfo, classify_intent, parse_to_document,
+    };
+
+    #[test]
+    fn test_parse_language() {
+        assert_eq!(parse_language("de").as_deref(), Some("German"));
+        assert_eq!(parse_language(" DE ").as_deref(), Some("German"));
+        assert_eq!(parse_language("Deutsch").as_deref(), Some("Deutsch"));
+        assert_eq!(parse_language("en"), None);
+        assert_eq!(parse_language("English"), None);
+        assert_eq!(parse_language(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_german_input_keeps_source_language() {
+        let mock = Arc::new(MockLlmClient::new(vec![
+            r#"{"intent":"remember","content":"Toby arbeitet im Infrastruktur-Team"}"#.into(),
+            r#"{"key":"toby","name":"Toby","team":"Infrastruktur","notes":"Bevorzugt Rückrufe am Vormittag"}"#.into(),
+        ]));
+        let llm = LanguageLlmClient::new(mock.clone(), "German");
+
+        let intent = classify_intent(&llm, "Merk dir: Toby arbeitet im Infrastruktur-Team")
+            .await
+            .unwrap();
+        let NlIntent::Remember { content } = intent else {
+            panic!("expected remember, got {intent:?}");
+        };
+        assert_eq!(content, "Toby arbeitet im Infrastruktur-Team");
+
+        let schema = PartitionSchemaInfo {
+            prefix: "contacts".into(),
+            description: "People".into(),
+            attributes: ["name", "team", "notes"]
+                .into_iter()
+                .map(|name| AttributeInfo {
+                    name: name.into(),
+                    attr_type: "STRING".into(),
+                    required: false,
+                })
+                .collect(),
+            validate: false,
+        };
+        let doc = parse_to_document(
+            &llm,
+            "contacts",
+            &schema,
+            &AttributeAliases::default(),
+            None,
+            "Toby aus dem Infrastruktur-Team bevorzugt Rückrufe am Vormittag",
+        )
+        .await
+        .unwrap();
+        assert_eq!(doc["team"], "Infrastruktur");
+        assert_eq!(doc["notes"], "Bevorzugt Rückrufe am Vormittag");
+
+        let requests = mock.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        for (system, _) in requests.iter() {
+            assert!(system.ends_with(&language_instruction("German")));
+        }
+        assert!(requests[1].1.contains("bevorzugt Rückrufe"));
+    }
+}
@@ -1,17 +1,91 @@
 //! FerridynDB Memory — shared library for MCP server and CLI.
 
+pub mod activity;
+pub mod agents_md;
+pub mod alias;
+pub mod append;
 pub mod backend;
+pub mod backup;
+pub mod budget;
+pub mod capability;
+pub mod cascade;
+#[cfg(feature = "llm")]
+pub mod chat;
+pub mod chunk;
+pub mod confirm;
+pub mod conflict;
+pub mod context;
+pub mod date_query;
+pub mod digest;
 pub mod error;
+pub mod fixture;
+pub mod graph;
+pub mod hints;
+pub mod http;
+pub mod import;
+mod in_memory;
+pub mod infer;
+pub mod ingest;
+pub mod interaction;
+pub mod issue;
+pub mod keys;
+#[cfg(feature = "llm")]
+pub mod language;
+pub mod limits;
+pub mod links;
 pub mod llm;
+pub mod locale;
+pub mod logging;
 pub mod mcp;
+pub mod migrate;
+pub mod mirror;
+pub mod notify;
+pub mod output;
+pub mod page;
+pub mod preferences;
+pub mod promote;
+pub mod quota;
+#[cfg(feature = "llm")]
+pub mod recall_cache;
+pub mod related;
+pub mod review;
 pub mod schema;
+pub mod score;
+pub mod search;
+pub mod seed;
+pub mod session;
+pub mod similar;
+pub mod size;
+pub mod sort;
+pub mod store;
+pub mod tenant;
+pub mod timeout;
+pub mod tree;
 pub mod ttl;
+pub mod usage;
+pub mod validation;
+pub mod verify;
+pub mod version;
+pub mod weekly;
 
 use std::path::PathBuf;
 
 /// Default table name used for all memories (no namespace).
 pub const TABLE_NAME: &str = "memories";
 
+/// Internal partitions, without schemas, that `prune` still sweeps for expired
+/// items. Their names start with `_`, which no user category may, so they
+/// never collide with stored memories; this is the one list of them.
+pub const SYSTEM_CATEGORIES: &[&str] = &[
+    usage::USAGE_CATEGORY,
+    notify::NOTIFICATIONS_CATEGORY,
+    links::LINKS_CATEGORY,
+    alias::ALIASES_CATEGORY,
+    hints::HINTS_CATEGORY,
+    search::NAMESPACES_CATEGORY,
+    activity::ACTIVITY_CATEGORY,
+];
+
 // Re-export server types for schema and index operations.
 pub use ferridyn_server::client::{
     AttributeDefInput, AttributeInfo, IndexInfo, PartitionSchemaInfo, QueryResult,
@@ -26,17 +100,114 @@ pub use ttl::{
     compute_expires_at, filter_expired, is_expired, parse_ttl,
 };
 
-/// Resolve the table name from an optional namespace.
+/// Environment variable replacing [`TABLE_NAME`] as the base table name, so
+/// independent deployments can share one ferridyn-server.
+pub const TABLE_ENV: &str = "FMEMORY_TABLE";
+
+/// Longest accepted base table name.
+pub const MAX_TABLE_NAME_LEN: usize = 64;
+
+/// Check that `name` can serve as a base table name: a valid table
+/// identifier ([`validate_table_identifier`]) not starting with `memories_`,
+/// since those are the namespace tables of the default deployment.
+pub fn validate_table_name(name: &str) -> Result<(), String> {
+    validate_table_identifier(name)?;
+    if name.starts_with(&format!("{TABLE_NAME}_")) {
+        return Err(format!(
+            "table name '{name}' would collide with a namespace of the default '{TABLE_NAME}' table"
+        ));
+    }
+    Ok(())
+}
+
+/// Check that `name` is a table name ferridyn-server accepts: an ASCII
+/// letter followed by ASCII letters, digits, `_` or `-`, at most
+/// [`MAX_TABLE_NAME_LEN`] long.
+pub fn validate_table_identifier(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > MAX_TABLE_NAME_LEN {
+        return Err(format!(
+            "table name must be 1 to {MAX_TABLE_NAME_LEN} characters"
+        ));
+    }
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic())
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!(
+            "table name '{name}' must start with a letter and contain only letters, digits, '_' and '-'"
+        ));
+    }
+    Ok(())
+}
+
+/// Check that `namespace` can name a namespace table: letters, digits, `_`
+/// and `-`, short enough that its table ([`resolve_table_name`]) stays within
+/// [`MAX_TABLE_NAME_LEN`]. Namespaces reach table names from `--namespace`,
+/// `FMEMORY_NAMESPACE`, and MCP tool calls, so each of those checks it.
+pub fn validate_namespace(namespace: &str) -> Result<(), String> {
+    if namespace.is_empty()
+        || !namespace
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!(
+            "namespace '{namespace}' must be non-empty and contain only letters, digits, '_' and '-'"
+        ));
+    }
+    let table = resolve_table_name(Some(namespace));
+    if table.len() > MAX_TABLE_NAME_LEN {
+        return Err(format!(
+            "namespace '{namespace}' is too long: its table '{table}' exceeds {MAX_TABLE_NAME_LEN} characters"
+        ));
+    }
+    Ok(())
+}
+
+/// The base table name: `FMEMORY_TABLE` if set, else [`TABLE_NAME`].
+pub fn base_table_name() -> Result<String, String> {
+    match std::env::var(TABLE_ENV) {
+        Ok(name) => {
+            let name = name.trim().to_string();
+            validate_table_name(&name).map_err(|e| format!("Invalid {TABLE_ENV}: {e}"))?;
+            Ok(name)
+        }
+        Err(_) => Ok(TABLE_NAME.to_string()),
+    }
+}
+
+/// Resolve the table name from an optional namespace, under the base table
+/// name. An invalid `FMEMORY_TABLE` is ignored with a warning here; `fmemory`
+/// refuses to start with one.
 ///
 /// - `None` → `"memories"` (backward compatible default)
 /// - `Some("myproject")` → `"memories_myproject"`
 pub fn resolve_table_name(namespace: Option<&str>) -> String {
+    let base = base_table_name().unwrap_or_else(|e| {
+        tracing::warn!("{e}; using '{TABLE_NAME}'");
+        TABLE_NAME.to_string()
+    });
+    table_name_in(&base, namespace)
+}
+
+/// The table of `namespace` under the base table name `base`.
+pub fn table_name_in(base: &str, namespace: Option<&str>) -> String {
     match namespace {
-        Some(ns) => format!("memories_{ns}"),
-        None => TABLE_NAME.to_string(),
+        Some(ns) => format!("{base}_{ns}"),
+        None => base.to_string(),
     }
 }
 
+/// The namespace whose table is `table_name`, if it is a namespace table of
+/// the current base.
+pub fn namespace_of(table_name: &str) -> Option<String> {
+    let base = resolve_table_name(None);
+    table_name
+        .strip_prefix(&base)?
+        .strip_prefix('_')
+        .map(String::from)
+}
+
 /// Resolve the socket path from env var or default location.
 pub fn resolve_socket_path() -> PathBuf {
     if let Ok(path) = std::env::var("FERRIDYN_MEMORY_SOCKET") {
@@ -0,0 +1,218 @@
+//! Result count limits: how many items a call returns by default and at most.
+//!
+//! Every listing, query and recall resolves its result count through
+//! [`Limits::resolve`]: the caller's `limit`, or the default when it passes
+//! none, clamped to the maximum so no request turns into an unbounded scan.
+//! Limits apply to every operation unless overridden for one, by the name it
+//! has in the operation log (`memory_query`, `recall`, `ui_search`, ...).
+//!
+//! Configuration is read from a JSON file, `FMEMORY_LIMITS_FILE` or
+//! `fmemory/limits.json` under the user config directory:
+//!
+//! ```json
+//! {"default": 20, "max": 100, "tools": {"memory_query": {"max": 50}}}
+//! ```
+//!
+//! and then from `FMEMORY_LIMITS`, e.g.
+//! `default=10,max=200,memory_query.max=50`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tracing::warn;
+
+/// Environment variable overriding limits, as comma-separated
+/// `[operation.]default|max=N` entries.
+pub const LIMITS_ENV: &str = "FMEMORY_LIMITS";
+
+/// Environment variable naming the limits config file.
+pub const LIMITS_FILE_ENV: &str = "FMEMORY_LIMITS_FILE";
+
+/// Result count when the caller passes no `limit`.
+pub const DEFAULT_LIMIT: usize = 20;
+
+/// Upper bound on the result count of any call.
+pub const MAX_LIMIT: usize = 100;
+
+/// Per-operation defaults applied unless overridden.
+pub const DEFAULT_OVERRIDES: &[(&str, usize)] = &[
+    ("related", 10),
+    ("memory_list", MAX_LIMIT),
+    ("ui_list_items", 100),
+    ("ui_search", 100),
+];
+
+/// Bounds for one operation; unset fields fall back to the global ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LimitOverride {
+    pub default: Option<usize>,
+    pub max: Option<usize>,
+}
+
+/// Default and maximum result counts, globally and per operation.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    pub default: usize,
+    pub max: usize,
+    pub tools: BTreeMap<String, LimitOverride>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        let tools = DEFAULT_OVERRIDES
+            .iter()
+            .map(|(tool, default)| {
+                let limit = LimitOverride {
+                    default: Some(*default),
+                    max: None,
+                };
+                (tool.to_string(), limit)
+            })
+            .collect();
+        Self {
+            default: DEFAULT_LIMIT,
+            max: MAX_LIMIT,
+            tools,
+        }
+    }
+}
+
+impl Limits {
+    /// Defaults, then the config file, then `FMEMORY_LIMITS`. An unreadable
+    /// config file and malformed entries are skipped with a warning.
+    pub fn load() -> Self {
+        let mut limits = Self::default();
+        if let Some(path) = config_path() {
+            match Self::from_file(&path) {
+                Ok(Some(loaded)) => limits = loaded,
+                Ok(None) => {}
+                Err(e) => warn!("Ignoring limits config {}: {e}", path.display()),
+            }
+        }
+        if let Ok(spec) = std::env::var(LIMITS_ENV) {
+            limits.apply_spec(&spec);
+        }
+        limits
+    }
+
+    /// Limits from the JSON file at `path`, on top of the defaults, or
+    /// `None` if there is no such file.
+    pub fn from_file(path: &Path) -> Result<Option<Self>, String> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.to_string()),
+        };
+        let mut limits = Self::default();
+        let loaded: Self = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+        limits.default = loaded.default;
+        limits.max = loaded.max;
+        limits.tools.extend(loaded.tools);
+        Ok(Some(limits))
+    }
+
+    /// Apply comma-separated `[operation.]default|max=N` entries, skipping
+    /// malformed ones with a warning.
+    pub fn apply_spec(&mut self, spec: &str) {
+        for entry in spec.split(',').filter(|e| !e.trim().is_empty()) {
+            if let Err(e) = self.apply_entry(entry) {
+                warn!("Ignoring {LIMITS_ENV} entry '{entry}': {e}");
+            }
+        }
+    }
+
+    fn apply_entry(&mut self, entry: &str) -> Result<(), String> {
+        let (name, value) = entry
+            .split_once('=')
+            .ok_or("expected [operation.]default|max=N")?;
+        let value: usize = value.trim().parse().map_err(|e| format!("{e}"))?;
+        let (tool, field) = match name.trim().rsplit_once('.') {
+            Some((tool, field)) => (Some(tool), field),
+            None => (None, name.trim()),
+        };
+        match (tool, field) {
+            (None, "default") => self.default = value,
+            (None, "max") => self.max = value,
+            (Some(tool), "default") => self.tool_mut(tool).default = Some(value),
+            (Some(tool), "max") => self.tool_mut(tool).max = Some(value),
+            _ => return Err(format!("unknown limit '{field}', expected default or max")),
+        }
+        Ok(())
+    }
+
+    fn tool_mut(&mut self, tool: &str) -> &mut LimitOverride {
+        self.tools.entry(tool.to_string()).or_default()
+    }
+
+    /// Upper bound on the result count of `tool`.
+    pub fn max_for(&self, tool: &str) -> usize {
+        self.tools.get(tool).and_then(|o| o.max).unwrap_or(self.max)
+    }
+
+    /// Result count of `tool` when the caller passes none, within its max.
+    pub fn default_for(&self, tool: &str) -> usize {
+        self.tools
+            .get(tool)
+            .and_then(|o| o.default)
+            .unwrap_or(self.default)
+            .min(self.max_for(tool))
+    }
+
+    /// The result count for a call of `tool` that asked for `requested`.
+    pub fn resolve(&self, tool: &str, requested: Option<usize>) -> usize {
+        requested
+            .unwrap_or_else(|| self.default_for(tool))
+            .min(self.max_for(tool))
+    }
+}
+
+/// `FMEMORY_LIMITS_FILE`, or `fmemory/limits.json` in the config directory.
+pub fn config_path() -> Option<PathBuf> {
+    match std::env::var(LIMITS_FILE_ENV) {
+        Ok(path) => Some(PathBuf::from(path)),
+        Err(_) => dirs::config_dir().map(|dir| dir.join("fmemory").join("limits.json")),
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_resolve_and_overrides() {
+        let mut limits = Limits::default();
+        assert_eq!(limits.resolve("memory_query", None), DEFAULT_LIMIT);
+        assert_eq!(limits.resolve("memory_query", Some(5000)), MAX_LIMIT);
+        assert_eq!(limits.resolve("related", None), 10);
+
+        limits.apply_spec("default=30, memory_query.max=50,memory_list.default=500,bogus,x.min=1");
+        assert_eq!(limits.resolve("memory_recall", None), 30);
+        assert_eq!(limits.resolve("memory_query", Some(80)), 50);
+        assert_eq!(limits.resolve("memory_query", Some(8)), 8);
+        assert_eq!(limits.default_for("memory_list"), MAX_LIMIT);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("limits.json");
+        assert_eq!(Limits::from_file(&path), Ok(None));
+        std::fs::write(
+            &path,
+            r#"{"max": 40, "tools": {"memory_recall": {"default": 5}}}"#,
+        )
+        .unwrap();
+        let loaded = Limits::from_file(&path).unwrap().unwrap();
+        assert_eq!(loaded.resolve("memory_query", None), DEFAULT_LIMIT);
+        assert_eq!(loaded.resolve("memory_query", Some(60)), 40);
+        assert_eq!(loaded.resolve("memory_recall", None), 5);
+        assert_eq!(loaded.resolve("related", None), 10);
+
+        std::fs::write(&path, r#"{"maximum": 40}"#).unwrap();
+        assert!(Limits::from_file(&path).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! Explicit links between memories.
+//!
+//! A [`Link`] connects a source item to a target item (each a `category/key`
+//! reference) with a relation type such as `decided-by` or `part-of`. Links
+//! live in the reserved [`LINKS_CATEGORY`] partition rather than on the items
+//! themselves, so linking never rewrites a memory; the links recorded for a
+//! memory's `related` references ([`crate::related::link_related`]) are
+//! links like any other. Each link is written twice,
+//! once under its source and once under its target, so both directions are a
+//! single prefix query: see [`MemoryBackend::link_items`],
+//! [`MemoryBackend::get_links`] and [`MemoryBackend::unlink_items`].
+//!
+//! [`MemoryBackend::link_items`]: crate::backend::MemoryBackend::link_items
+//! [`MemoryBackend::get_links`]: crate::backend::MemoryBackend::get_links
+//! [`MemoryBackend::unlink_items`]: crate::backend::MemoryBackend::unlink_items
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::MemoryError;
+use crate::ingest::sha256_hex;
+use crate::keys::{normalize_category, normalize_key, slugify};
+
+/// Partition holding link records.
+pub const LINKS_CATEGORY: &str = "_links";
+
+/// Relation used when none is given.
+pub const DEFAULT_RELATION: &str = "related";
+
+/// Maximum number of link records read per item and direction.
+pub const LINKS_SCAN_LIMIT: usize = 10_000;
+
+/// Hex digits of a reference's hash used in link record keys.
+const REF_HASH_CHARS: usize = 16;
+
+/// A typed link from one memory to another.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Link {
+    /// `category/key` of the item the link starts from.
+    pub source: String,
+    /// `category/key` of the item the link points to.
+    pub target: String,
+    /// Relation type, slugified (e.g. `decided-by`).
+    pub relation: String,
+    /// RFC 3339 time the link was recorded.
+    pub created_at: String,
+}
+
+/// Which end of a link a record is filed under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LinkDirection {
+    /// Filed under the source: the link goes out of it.
+    Out,
+    /// Filed under the target: the link comes in to it.
+    In,
+}
+
+impl LinkDirection {
+    fn as_str(self) -> &'static str {
+        match self {
+            LinkDirection::Out => "out",
+            LinkDirection::In => "in",
+        }
+    }
+}
+
+/// Split and validate a `category/key` reference.
+pub fn parse_item_ref(reference: &str) -> Result<(String, String), MemoryError> {
+    let (category, key) = reference.trim().split_once('/').ok_or_else(|| {
+        MemoryError::InvalidParams(format!("'{reference}' is not a category/key reference"))
+    })?;
+    let category = normalize_category(category).map_err(MemoryError::InvalidParams)?;
+    let key = normalize_key(key).map_err(MemoryError::InvalidParams)?;
+    Ok((category, key))
+}
+
+/// `reference` in normalized `category/key` form.
+pub fn normalize_item_ref(reference: &str) -> Result<String, MemoryError> {
+    let (category, key) = parse_item_ref(reference)?;
+    Ok(format!("{category}/{key}"))
+}
+
+fn short_hash(text: &str) -> String {
+    sha256_hex(text.as_bytes())[..REF_HASH_CHARS].to_string()
+}
+
+/// Key prefix of the records filed under `item_ref` in `direction`.
+pub(crate) fn link_key_prefix(direction: LinkDirection, item_ref: &str) -> String {
+    format!("{}-{}-", direction.as_str(), short_hash(item_ref))
+}
+
+impl Link {
+    /// A link from `source` to `target`, stamped now. References are
+    /// normalized; an empty `relation` becomes [`DEFAULT_RELATION`].
+    pub fn new(source: &str, target: &str, relation: &str) -> Result<Self, MemoryError> {
+        let (source, target) = (normalize_item_ref(source)?, normalize_item_ref(target)?);
+        if source == target {
+            return Err(MemoryError::InvalidParams(format!(
+                "cannot link {source} to itself"
+            )));
+        }
+        let relation = match slugify(relation) {
+            relation if relation.is_empty() => DEFAULT_RELATION.to_string(),
+            relation => relation,
+        };
+        Ok(Link {
+            source,
+            target,
+            relation,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+
+    /// The reference at the other end from `item_ref`.
+    pub fn other_end(&self, item_ref: &str) -> &str {
+        if self.source == item_ref {
+            &self.target
+        } else {
+            &self.source
+        }
+    }
+
+    fn record_key(&self, direction: LinkDirection) -> String {
+        let own = match direction {
+            LinkDirection::Out => &self.source,
+            LinkDirection::In => &self.target,
+        };
+        let id = short_hash(&format!(
+            "{}\n{}\n{}",
+            self.source, self.target, self.relation
+        ));
+        format!("{}{id}", link_key_prefix(direction, own))
+    }
+
+    /// The two records stored for this link: filed under the source, then
+    /// under the target.
+    pub(crate) fn to_records(&self) -> [Value; 2] {
+        [LinkDirection::Out, LinkDirection::In].map(|direction| {
+            serde_json::json!({
+                "category": LINKS_CATEGORY,
+                "key": self.record_key(direction),
+                "source": self.source,
+                "target": self.target,
+                "relation": self.relation,
+                "created_at": self.created_at,
+            })
+        })
+    }
+
+    /// Read a link back from one of its records.
+    pub(crate) fn from_record(record: &Value) -> Option<Self> {
+        let attr = |name: &str| record[name].as_str().map(String::from);
+        Some(Link {
+            source: attr("source")?,
+            target: attr("target")?,
+            relation: attr("relation")?,
+            created_at: attr("created_at").unwrap_or_default(),
+        })
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_link_normalizes() {
+        let link = Link::new(" decisions/auth-method", "contacts/toby", "Decided By").unwrap();
+        assert_eq!(link.source, "decisions/auth-method");
+        assert_eq!(link.relation, "decided-by");
+        assert_eq!(link.other_end("contacts/toby"), "decisions/auth-method");
+        assert_eq!(
+            Link::new("a/b", "c/d", "  ").unwrap().relation,
+            DEFAULT_RELATION
+        );
+
+        assert!(Link::new("toby", "contacts/toby", "x").is_err());
+        assert!(Link::new("contacts/toby", "contacts/toby", "x").is_err());
+        assert!(Link::new("Contacts/toby", "c/d", "x").is_err());
+    }
+
+    #[test]
+    fn test_link_records_round_trip() {
+        let link = Link::new("decisions/auth-method", "contacts/toby", "decided-by").unwrap();
+        let [out, inbound] = link.to_records();
+        assert!(out["key"].as_str().unwrap().starts_with(&link_key_prefix(
+            LinkDirection::Out,
+            "decisions/auth-method"
+        )));
+        assert!(
+            inbound["key"]
+                .as_str()
+                .unwrap()
+                .starts_with(&link_key_prefix(LinkDirection::In, "contacts/toby"))
+        );
+        assert_eq!(Link::from_record(&out).unwrap(), link);
+        assert_eq!(Link::from_record(&inbound).unwrap(), link);
+    }
+}
@@ -1,15 +1,26 @@
-//! LLM client abstraction and Anthropic API implementation.
+//! LLM client abstraction and API implementations.
 //!
 //! This module provides a generic [`LlmClient`] trait for interacting with
 //! language models, along with concrete implementations:
 //!
 //! - [`AnthropicClient`]: production client for Anthropic's Claude API
+//!   (requires the `llm` feature)
+//! - [`OpenAiClient`]: client for OpenAI, Azure OpenAI, and servers
+//!   implementing the OpenAI chat completions API (requires the `llm` feature)
+//! - [`OllamaClient`]: client for a local Ollama server, for offline use
+//!   (requires the `llm` feature)
+//! - [`FixtureLlmClient`](crate::fixture::FixtureLlmClient): records or
+//!   replays completions for integration tests
 //! - [`MockLlmClient`]: test double for unit tests
 //!
 //! Used by the schema system for inference and natural language recall resolution.
+//! [`client_from_env`] picks the production client named by
+//! `FMEMORY_LLM_PROVIDER`.
 
 use async_trait::async_trait;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
+#[cfg(feature = "llm")]
+use serde::Serialize;
 use thiserror::Error;
 
 // ============================================================================
@@ -19,9 +30,13 @@ use thiserror::Error;
 /// Errors that can occur during LLM operations.
 #[derive(Debug, Error)]
 pub enum LlmError {
-    /// The ANTHROPIC_API_KEY environment variable is not set.
-    #[error("ANTHROPIC_API_KEY environment variable not set")]
-    MissingApiKey,
+    /// The environment variable holding the provider's API key is not set.
+    #[error("{0} environment variable not set")]
+    MissingApiKey(&'static str),
+
+    /// The LLM provider is misconfigured.
+    #[error("LLM configuration error: {0}")]
+    Config(String),
 
     /// HTTP or network error occurred.
     #[error("HTTP error: {0}")]
@@ -34,6 +49,14 @@ pub enum LlmError {
     /// Model returned no text content.
     #[error("Model returned empty response")]
     EmptyResponse,
+
+    /// A recorded completion could not be read or written.
+    #[error("Fixture error: {0}")]
+    Fixture(String),
+
+    /// The completion ran past its time limit.
+    #[error("LLM call timed out after {0:?}")]
+    Timeout(std::time::Duration),
 }
 
 // ============================================================================
@@ -45,6 +68,65 @@ pub enum LlmError {
 pub struct Completion {
     /// The generated text from the model.
     pub text: String,
+    /// Token usage reported by the API (zero when unavailable).
+    pub usage: Usage,
+}
+
+/// Token counts for a single completion.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, serde::Serialize)]
+pub struct Usage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+}
+
+/// The feature an LLM call serves, used to attribute token spend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LlmFeature {
+    /// Parsing natural language into a structured document.
+    Parse,
+    /// Resolving a natural language query to a lookup strategy.
+    Resolve,
+    /// Synthesizing an answer from retrieved items.
+    Answer,
+    /// Classifying prompt intent (remember vs recall).
+    Classify,
+    /// Summarizing oversized input.
+    Summarize,
+    /// Extracting memories from bulk sources (e.g. commit history).
+    Extract,
+    /// Periodic summaries: daily digests and weekly reviews.
+    Digest,
+    /// Checking a new memory for contradictions with stored ones.
+    Conflict,
+    /// Replying in `fmemory chat`.
+    Chat,
+    /// Judging which expiring short-term items are worth promoting.
+    Promote,
+    /// Proposing a category schema for `define --infer`.
+    Schema,
+    /// Calls made without a feature tag.
+    Other,
+}
+
+impl LlmFeature {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LlmFeature::Parse => "parse",
+            LlmFeature::Resolve => "resolve",
+            LlmFeature::Answer => "answer",
+            LlmFeature::Classify => "classify",
+            LlmFeature::Summarize => "summarize",
+            LlmFeature::Extract => "extract",
+            LlmFeature::Digest => "digest",
+            LlmFeature::Conflict => "conflict",
+            LlmFeature::Chat => "chat",
+            LlmFeature::Promote => "promote",
+            LlmFeature::Schema => "schema",
+            LlmFeature::Other => "other",
+        }
+    }
 }
 
 // ============================================================================
@@ -67,12 +149,120 @@ pub trait LlmClient: Send + Sync {
     ///
     /// A [`Completion`] containing the model's response text.
     async fn complete(&self, system: &str, user: &str) -> Result<Completion, LlmError>;
+
+    /// Generate a completion on behalf of a specific [`LlmFeature`].
+    ///
+    /// Defaults to [`complete`](LlmClient::complete); wrappers that track
+    /// usage override this to attribute tokens to the feature.
+    async fn complete_for(
+        &self,
+        _feature: LlmFeature,
+        system: &str,
+        user: &str,
+    ) -> Result<Completion, LlmError> {
+        self.complete(system, user).await
+    }
+
+    /// The model completions come from, when known; usage reports price
+    /// tokens by it.
+    fn model(&self) -> Option<&str> {
+        None
+    }
+}
+
+// ============================================================================
+// Provider Selection
+// ============================================================================
+
+/// Environment variable naming the LLM provider: `anthropic` (default),
+/// `openai`, `azure`, or `ollama`.
+pub const LLM_PROVIDER_ENV: &str = "FMEMORY_LLM_PROVIDER";
+
+/// Environment variable overriding the model of the selected provider.
+pub const LLM_MODEL_ENV: &str = "FMEMORY_LLM_MODEL";
+
+/// Environment variable with the base URL of an OpenAI-compatible API or an
+/// Ollama server, or the deployment URL for Azure OpenAI.
+pub const LLM_BASE_URL_ENV: &str = "FMEMORY_LLM_BASE_URL";
+
+/// Environment variable with the Azure OpenAI API version.
+pub const LLM_API_VERSION_ENV: &str = "FMEMORY_LLM_API_VERSION";
+
+/// Which API production completions are sent to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LlmProvider {
+    /// Anthropic's Messages API.
+    #[default]
+    Anthropic,
+    /// OpenAI's chat completions API, or a server compatible with it.
+    OpenAi,
+    /// An Azure OpenAI deployment.
+    Azure,
+    /// A local Ollama server's chat API.
+    Ollama,
+}
+
+impl std::str::FromStr for LlmProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "anthropic" | "claude" => Ok(LlmProvider::Anthropic),
+            "openai" => Ok(LlmProvider::OpenAi),
+            "azure" | "azure-openai" => Ok(LlmProvider::Azure),
+            "ollama" => Ok(LlmProvider::Ollama),
+            other => Err(format!(
+                "unknown LLM provider '{other}': expected anthropic, openai, azure, or ollama"
+            )),
+        }
+    }
+}
+
+impl LlmProvider {
+    /// The provider named by `FMEMORY_LLM_PROVIDER`, or Anthropic when unset.
+    pub fn from_env() -> Result<Self, LlmError> {
+        match std::env::var(LLM_PROVIDER_ENV) {
+            Ok(value) if !value.trim().is_empty() => value.parse().map_err(LlmError::Config),
+            _ => Ok(LlmProvider::Anthropic),
+        }
+    }
+
+    /// Environment variable holding the provider's API key, if it takes one.
+    pub fn api_key_env(self) -> Option<&'static str> {
+        match self {
+            LlmProvider::Anthropic => Some("ANTHROPIC_API_KEY"),
+            LlmProvider::OpenAi | LlmProvider::Azure => Some("OPENAI_API_KEY"),
+            LlmProvider::Ollama => None,
+        }
+    }
+}
+
+/// The production client for the provider selected by `FMEMORY_LLM_PROVIDER`,
+/// configured from the environment.
+#[cfg(feature = "llm")]
+pub fn client_from_env() -> Result<std::sync::Arc<dyn LlmClient>, LlmError> {
+    Ok(match LlmProvider::from_env()? {
+        LlmProvider::Anthropic => std::sync::Arc::new(AnthropicClient::from_env()?),
+        LlmProvider::OpenAi => std::sync::Arc::new(OpenAiClient::from_env()?),
+        LlmProvider::Azure => std::sync::Arc::new(OpenAiClient::azure_from_env()?),
+        LlmProvider::Ollama => std::sync::Arc::new(OllamaClient::from_env()),
+    })
+}
+
+/// `name`'s value, if set and not blank.
+#[cfg(feature = "llm")]
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
 }
 
 // ============================================================================
 // Anthropic API Implementation
 // ============================================================================
 
+#[cfg(feature = "llm")]
 /// Client for the Anthropic Claude API.
 ///
 /// Makes HTTP requests to the Anthropic Messages API endpoint to generate
@@ -84,6 +274,7 @@ pub struct AnthropicClient {
     client: reqwest::Client,
 }
 
+#[cfg(feature = "llm")]
 /// Request body for the Anthropic Messages API.
 #[derive(Debug, Serialize)]
 struct AnthropicRequest {
@@ -93,6 +284,7 @@ struct AnthropicRequest {
     messages: Vec<Message>,
 }
 
+#[cfg(feature = "llm")]
 /// A message in the conversation.
 #[derive(Debug, Serialize)]
 struct Message {
@@ -100,30 +292,41 @@ struct Message {
     content: String,
 }
 
+#[cfg(feature = "llm")]
 /// Response from the Anthropic Messages API.
 #[derive(Debug, Deserialize)]
 struct AnthropicResponse {
     content: Vec<ContentBlock>,
+    #[serde(default)]
+    usage: Usage,
 }
 
+#[cfg(feature = "llm")]
 /// A content block in the API response.
 #[derive(Debug, Deserialize)]
 struct ContentBlock {
     text: String,
 }
 
+#[cfg(feature = "llm")]
 impl AnthropicClient {
     /// Create a new client by reading the API key from the environment.
     ///
-    /// Reads the `ANTHROPIC_API_KEY` environment variable. Uses default
-    /// model `claude-haiku-4-5` and max tokens `2048`.
+    /// Reads the `ANTHROPIC_API_KEY` environment variable, and the model from
+    /// `FMEMORY_LLM_MODEL` if set. Uses default model `claude-haiku-4-5` and
+    /// max tokens `2048`.
     ///
     /// # Errors
     ///
     /// Returns [`LlmError::MissingApiKey`] if the environment variable is not set.
     pub fn from_env() -> Result<Self, LlmError> {
-        let api_key = std::env::var("ANTHROPIC_API_KEY").map_err(|_| LlmError::MissingApiKey)?;
-        Ok(Self::new(api_key))
+        let api_key = std::env::var("ANTHROPIC_API_KEY")
+            .map_err(|_| LlmError::MissingApiKey("ANTHROPIC_API_KEY"))?;
+        let client = Self::new(api_key);
+        Ok(match env_value(LLM_MODEL_ENV) {
+            Some(model) => client.with_model(model),
+            None => client,
+        })
     }
 
     /// Create a new client with an explicit API key.
@@ -137,10 +340,27 @@ impl AnthropicClient {
             client: reqwest::Client::new(),
         }
     }
+
+    /// Use `model` instead of `claude-haiku-4-5`.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
 }
 
+#[cfg(feature = "llm")]
 #[async_trait]
 impl LlmClient for AnthropicClient {
+    fn model(&self) -> Option<&str> {
+        Some(&self.model)
+    }
+
+    #[tracing::instrument(
+        name = "llm.complete",
+        level = "debug",
+        skip_all,
+        fields(model = %self.model),
+    )]
     async fn complete(&self, system: &str, user: &str) -> Result<Completion, LlmError> {
         let request_body = AnthropicRequest {
             model: self.model.clone(),
@@ -175,7 +395,450 @@ impl LlmClient for AnthropicClient {
             .ok_or(LlmError::EmptyResponse)?
             .text;
 
-        Ok(Completion { text })
+        Ok(Completion {
+            text,
+            usage: api_response.usage,
+        })
+    }
+}
+
+// ============================================================================
+// OpenAI-Compatible API Implementation
+// ============================================================================
+
+#[cfg(feature = "llm")]
+/// Default base URL of the OpenAI API.
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+#[cfg(feature = "llm")]
+/// Default OpenAI model.
+pub const OPENAI_DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+#[cfg(feature = "llm")]
+/// Default Azure OpenAI API version.
+pub const AZURE_DEFAULT_API_VERSION: &str = "2024-10-21";
+
+#[cfg(feature = "llm")]
+/// Client for the OpenAI chat completions API.
+///
+/// Also talks to Azure OpenAI deployments and to other servers implementing
+/// the same API (vLLM, Ollama, LiteLLM, ...) through a different base URL.
+pub struct OpenAiClient {
+    /// Sent as a bearer token, or as `api-key` for Azure; `None` sends no
+    /// credentials.
+    api_key: Option<String>,
+    base_url: String,
+    model: String,
+    max_tokens: u32,
+    /// Azure API version, sent as the `api-version` query parameter; `None`
+    /// for the OpenAI API.
+    azure_api_version: Option<String>,
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "llm")]
+/// Request body for the chat completions API.
+#[derive(Debug, Serialize)]
+struct ChatRequest {
+    model: String,
+    max_tokens: u32,
+    messages: Vec<Message>,
+}
+
+#[cfg(feature = "llm")]
+/// Response from the chat completions API.
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    #[serde(default)]
+    choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+#[cfg(feature = "llm")]
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[cfg(feature = "llm")]
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[cfg(feature = "llm")]
+/// Token counts as the chat completions API reports them.
+#[derive(Debug, Deserialize)]
+struct ChatUsage {
+    #[serde(default)]
+    prompt_tokens: u64,
+    #[serde(default)]
+    completion_tokens: u64,
+}
+
+#[cfg(feature = "llm")]
+impl ChatResponse {
+    fn into_completion(self) -> Result<Completion, LlmError> {
+        let text = self
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .filter(|text| !text.is_empty())
+            .ok_or(LlmError::EmptyResponse)?;
+        let usage = self.usage.map_or_else(Usage::default, |usage| Usage {
+            input_tokens: usage.prompt_tokens,
+            output_tokens: usage.completion_tokens,
+        });
+        Ok(Completion { text, usage })
+    }
+}
+
+#[cfg(feature = "llm")]
+impl OpenAiClient {
+    /// Create a client for the OpenAI API, or the compatible server at
+    /// `FMEMORY_LLM_BASE_URL`, from the environment.
+    ///
+    /// Reads the key from `OPENAI_API_KEY` and the model from
+    /// `FMEMORY_LLM_MODEL` (default `gpt-4o-mini`). Servers at a custom base
+    /// URL may not need a key, so it is only required for the OpenAI API.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LlmError::MissingApiKey`] if no key is set for the OpenAI API.
+    pub fn from_env() -> Result<Self, LlmError> {
+        let api_key = env_value("OPENAI_API_KEY");
+        let base_url = env_value(LLM_BASE_URL_ENV);
+        if api_key.is_none() && base_url.is_none() {
+            return Err(LlmError::MissingApiKey("OPENAI_API_KEY"));
+        }
+        let mut client = Self::new(api_key);
+        if let Some(base_url) = base_url {
+            client = client.with_base_url(base_url);
+        }
+        if let Some(model) = env_value(LLM_MODEL_ENV) {
+            client = client.with_model(model);
+        }
+        Ok(client)
+    }
+
+    /// Create a client for the Azure OpenAI deployment at
+    /// `FMEMORY_LLM_BASE_URL` (such as
+    /// `https://NAME.openai.azure.com/openai/deployments/DEPLOYMENT`) from
+    /// the environment, with the key from `OPENAI_API_KEY` and the API
+    /// version from `FMEMORY_LLM_API_VERSION` (default `2024-10-21`).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LlmError::MissingApiKey`] without a key and
+    /// [`LlmError::Config`] without a deployment URL.
+    pub fn azure_from_env() -> Result<Self, LlmError> {
+        let api_key =
+            env_value("OPENAI_API_KEY").ok_or(LlmError::MissingApiKey("OPENAI_API_KEY"))?;
+        let deployment_url = env_value(LLM_BASE_URL_ENV).ok_or_else(|| {
+            LlmError::Config(format!(
+                "{LLM_BASE_URL_ENV} must be set to the Azure OpenAI deployment URL"
+            ))
+        })?;
+        let api_version =
+            env_value(LLM_API_VERSION_ENV).unwrap_or_else(|| AZURE_DEFAULT_API_VERSION.into());
+        Ok(Self::azure(api_key, deployment_url, api_version))
+    }
+
+    /// Create a client for the OpenAI API with an explicit key, or none.
+    ///
+    /// Uses default model `gpt-4o-mini` and max tokens `2048`.
+    pub fn new(api_key: Option<String>) -> Self {
+        Self {
+            api_key,
+            base_url: OPENAI_BASE_URL.to_string(),
+            model: OPENAI_DEFAULT_MODEL.to_string(),
+            max_tokens: 2048,
+            azure_api_version: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Create a client for an Azure OpenAI deployment. The deployment picks
+    /// the model.
+    pub fn azure(
+        api_key: String,
+        deployment_url: impl Into<String>,
+        api_version: impl Into<String>,
+    ) -> Self {
+        Self {
+            azure_api_version: Some(api_version.into()),
+            ..Self::new(Some(api_key)).with_base_url(deployment_url)
+        }
+    }
+
+    /// Send requests to the server at `base_url` (up to, not including,
+    /// `/chat/completions`).
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Use `model` instead of `gpt-4o-mini`.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// URL completions are requested from.
+    fn endpoint(&self) -> String {
+        match &self.azure_api_version {
+            Some(version) => format!("{}/chat/completions?api-version={version}", self.base_url),
+            None => format!("{}/chat/completions", self.base_url),
+        }
+    }
+
+    fn request_body(&self, system: &str, user: &str) -> ChatRequest {
+        ChatRequest {
+            model: self.model.clone(),
+            max_tokens: self.max_tokens,
+            messages: vec![
+                Message {
+                    role: "system".to_string(),
+                    content: system.to_string(),
+                },
+                Message {
+                    role: "user".to_string(),
+                    content: user.to_string(),
+                },
+            ],
+        }
+    }
+}
+
+#[cfg(feature = "llm")]
+#[async_trait]
+impl LlmClient for OpenAiClient {
+    fn model(&self) -> Option<&str> {
+        Some(&self.model)
+    }
+
+    #[tracing::instrument(
+        name = "llm.complete",
+        level = "debug",
+        skip_all,
+        fields(model = %self.model),
+    )]
+    async fn complete(&self, system: &str, user: &str) -> Result<Completion, LlmError> {
+        let mut request = self
+            .client
+            .post(self.endpoint())
+            .header("content-type", "application/json")
+            .json(&self.request_body(system, user));
+        request = match (&self.api_key, &self.azure_api_version) {
+            (Some(key), Some(_)) => request.header("api-key", key),
+            (Some(key), None) => request.bearer_auth(key),
+            (None, _) => request,
+        };
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| LlmError::Http(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(LlmError::Http(format!("{status}: {body}")));
+        }
+
+        let api_response: ChatResponse = response
+            .json()
+            .await
+            .map_err(|e| LlmError::Parse(e.to_string()))?;
+        api_response.into_completion()
+    }
+}
+
+// ============================================================================
+// Ollama Implementation
+// ============================================================================
+
+#[cfg(feature = "llm")]
+/// Default address of a local Ollama server.
+pub const OLLAMA_BASE_URL: &str = "http://localhost:11434";
+
+#[cfg(feature = "llm")]
+/// Default Ollama model.
+pub const OLLAMA_DEFAULT_MODEL: &str = "llama3.2";
+
+#[cfg(feature = "llm")]
+/// Client for the chat API of an Ollama server.
+///
+/// Needs no API key or network access beyond the server, so natural language
+/// features keep working offline with a locally pulled model.
+pub struct OllamaClient {
+    base_url: String,
+    model: String,
+    max_tokens: u32,
+    client: reqwest::Client,
+}
+
+#[cfg(feature = "llm")]
+/// Request body for Ollama's `/api/chat`.
+#[derive(Debug, Serialize)]
+struct OllamaRequest {
+    model: String,
+    messages: Vec<Message>,
+    stream: bool,
+    options: OllamaOptions,
+}
+
+#[cfg(feature = "llm")]
+#[derive(Debug, Serialize)]
+struct OllamaOptions {
+    num_predict: u32,
+}
+
+#[cfg(feature = "llm")]
+/// Response from Ollama's `/api/chat` when not streaming.
+#[derive(Debug, Deserialize)]
+struct OllamaResponse {
+    message: Option<OllamaMessage>,
+    #[serde(default)]
+    prompt_eval_count: u64,
+    #[serde(default)]
+    eval_count: u64,
+}
+
+#[cfg(feature = "llm")]
+#[derive(Debug, Deserialize)]
+struct OllamaMessage {
+    #[serde(default)]
+    content: String,
+}
+
+#[cfg(feature = "llm")]
+impl OllamaResponse {
+    fn into_completion(self) -> Result<Completion, LlmError> {
+        let text = self
+            .message
+            .map(|message| message.content)
+            .filter(|text| !text.is_empty())
+            .ok_or(LlmError::EmptyResponse)?;
+        Ok(Completion {
+            text,
+            usage: Usage {
+                input_tokens: self.prompt_eval_count,
+                output_tokens: self.eval_count,
+            },
+        })
+    }
+}
+
+#[cfg(feature = "llm")]
+impl OllamaClient {
+    /// Create a client for the Ollama server at `FMEMORY_LLM_BASE_URL`
+    /// (default `http://localhost:11434`), with the model from
+    /// `FMEMORY_LLM_MODEL` (default `llama3.2`).
+    pub fn from_env() -> Self {
+        let mut client = Self::new();
+        if let Some(base_url) = env_value(LLM_BASE_URL_ENV) {
+            client = client.with_base_url(base_url);
+        }
+        if let Some(model) = env_value(LLM_MODEL_ENV) {
+            client = client.with_model(model);
+        }
+        client
+    }
+
+    /// Create a client for a local server on the default port.
+    ///
+    /// Uses default model `llama3.2` and max tokens `2048`.
+    pub fn new() -> Self {
+        Self {
+            base_url: OLLAMA_BASE_URL.to_string(),
+            model: OLLAMA_DEFAULT_MODEL.to_string(),
+            max_tokens: 2048,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Send requests to the server at `base_url` (up to, not including,
+    /// `/api/chat`).
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Use `model` instead of `llama3.2`. The server must have pulled it.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// URL completions are requested from.
+    fn endpoint(&self) -> String {
+        format!("{}/api/chat", self.base_url)
+    }
+
+    fn request_body(&self, system: &str, user: &str) -> OllamaRequest {
+        OllamaRequest {
+            model: self.model.clone(),
+            messages: vec![
+                Message {
+                    role: "system".to_string(),
+                    content: system.to_string(),
+                },
+                Message {
+                    role: "user".to_string(),
+                    content: user.to_string(),
+                },
+            ],
+            stream: false,
+            options: OllamaOptions {
+                num_predict: self.max_tokens,
+            },
+        }
+    }
+}
+
+#[cfg(feature = "llm")]
+impl Default for OllamaClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "llm")]
+#[async_trait]
+impl LlmClient for OllamaClient {
+    fn model(&self) -> Option<&str> {
+        Some(&self.model)
+    }
+
+    #[tracing::instrument(
+        name = "llm.complete",
+        level = "debug",
+        skip_all,
+        fields(model = %self.model),
+    )]
+    async fn complete(&self, system: &str, user: &str) -> Result<Completion, LlmError> {
+        let response = self
+            .client
+            .post(self.endpoint())
+            .header("content-type", "application/json")
+            .json(&self.request_body(system, user))
+            .send()
+            .await
+            .map_err(|e| LlmError::Http(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(LlmError::Http(format!("{status}: {body}")));
+        }
+
+        let api_response: OllamaResponse = response
+            .json()
+            .await
+            .map_err(|e| LlmError::Parse(e.to_string()))?;
+        api_response.into_completion()
     }
 }
 
@@ -188,6 +851,8 @@ impl LlmClient for AnthropicClient {
 pub struct MockLlmClient {
     /// Pre-programmed responses to return in FIFO order.
     pub responses: std::sync::Mutex<std::collections::VecDeque<String>>,
+    /// `(system, user)` prompts received, in order.
+    pub requests: std::sync::Mutex<Vec<(String, String)>>,
 }
 
 #[cfg(test)]
@@ -204,6 +869,7 @@ impl MockLlmClient {
     pub fn new(responses: Vec<String>) -> Self {
         Self {
             responses: std::sync::Mutex::new(responses.into()),
+            requests: std::sync::Mutex::new(Vec::new()),
         }
     }
 }
@@ -211,7 +877,11 @@ impl MockLlmClient {
 #[cfg(test)]
 #[async_trait]
 impl LlmClient for MockLlmClient {
-    async fn complete(&self, _system: &str, _user: &str) -> Result<Completion, LlmError> {
+    async fn complete(&self, system: &str, user: &str) -> Result<Completion, LlmError> {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((system.to_string(), user.to_string()));
         let text = self
             .responses
             .lock()
@@ -219,7 +889,10 @@ impl LlmClient for MockLlmClient {
             .pop_front()
             .expect("MockLlmClient: no more responses available");
 
-        Ok(Completion { text })
+        Ok(Completion {
+            text,
+            usage: Usage::default(),
+        })
     }
 }
 
@@ -231,6 +904,7 @@ impl LlmClient for MockLlmClient {
 mod tests {
     use super::*;
 
+    #[cfg(feature = "llm")]
     #[test]
     fn test_from_env_missing_key() {
         // Ensure the environment variable is not set.
@@ -238,7 +912,109 @@ mod tests {
         unsafe { std::env::remove_var("ANTHROPIC_API_KEY") };
 
         let result = AnthropicClient::from_env();
-        assert!(matches!(result, Err(LlmError::MissingApiKey)));
+        assert!(matches!(
+            result,
+            Err(LlmError::MissingApiKey("ANTHROPIC_API_KEY"))
+        ));
+    }
+
+    #[test]
+    fn test_provider_parsing() {
+        assert_eq!("OpenAI".parse(), Ok(LlmProvider::OpenAi));
+        assert_eq!("azure".parse(), Ok(LlmProvider::Azure));
+        assert_eq!("anthropic".parse(), Ok(LlmProvider::Anthropic));
+        assert_eq!(" Ollama ".parse(), Ok(LlmProvider::Ollama));
+        assert!("gemini".parse::<LlmProvider>().is_err());
+        assert_eq!(LlmProvider::Azure.api_key_env(), Some("OPENAI_API_KEY"));
+        assert_eq!(LlmProvider::Ollama.api_key_env(), None);
+    }
+
+    #[cfg(feature = "llm")]
+    #[test]
+    fn test_openai_request_and_response() {
+        let client = OpenAiClient::new(Some("sk-test".into()))
+            .with_base_url("http://localhost:8000/v1/")
+            .with_model("llama3");
+        assert_eq!(
+            client.endpoint(),
+            "http://localhost:8000/v1/chat/completions"
+        );
+        let body = serde_json::to_value(client.request_body("sys", "hi")).unwrap();
+        assert_eq!(body["model"], "llama3");
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][1]["content"], "hi");
+
+        let azure = OpenAiClient::azure(
+            "key".into(),
+            "https://acme.openai.azure.com/openai/deployments/mini",
+            "2024-10-21",
+        );
+        assert_eq!(
+            azure.endpoint(),
+            "https://acme.openai.azure.com/openai/deployments/mini/chat/completions?api-version=2024-10-21"
+        );
+
+        let response: ChatResponse = serde_json::from_value(serde_json::json!({
+            "choices": [{"message": {"role": "assistant", "content": "{\"ok\": true}"}}],
+            "usage": {"prompt_tokens": 12, "completion_tokens": 5, "total_tokens": 17}
+        }))
+        .unwrap();
+        let completion = response.into_completion().unwrap();
+        assert_eq!(completion.text, "{\"ok\": true}");
+        assert_eq!(
+            completion.usage,
+            Usage {
+                input_tokens: 12,
+                output_tokens: 5
+            }
+        );
+
+        let empty: ChatResponse =
+            serde_json::from_value(serde_json::json!({"choices": []})).unwrap();
+        assert!(matches!(
+            empty.into_completion(),
+            Err(LlmError::EmptyResponse)
+        ));
+    }
+
+    #[cfg(feature = "llm")]
+    #[test]
+    fn test_ollama_request_and_response() {
+        let client = OllamaClient::new()
+            .with_base_url("http://gpu-box:11434/")
+            .with_model("qwen2.5");
+        assert_eq!(client.endpoint(), "http://gpu-box:11434/api/chat");
+        let body = serde_json::to_value(client.request_body("sys", "hi")).unwrap();
+        assert_eq!(body["model"], "qwen2.5");
+        assert_eq!(body["stream"], false);
+        assert_eq!(body["options"]["num_predict"], 2048);
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][1]["content"], "hi");
+
+        let response: OllamaResponse = serde_json::from_value(serde_json::json!({
+            "model": "qwen2.5",
+            "message": {"role": "assistant", "content": "done"},
+            "done": true,
+            "prompt_eval_count": 30,
+            "eval_count": 4
+        }))
+        .unwrap();
+        let completion = response.into_completion().unwrap();
+        assert_eq!(completion.text, "done");
+        assert_eq!(
+            completion.usage,
+            Usage {
+                input_tokens: 30,
+                output_tokens: 4
+            }
+        );
+
+        let empty: OllamaResponse =
+            serde_json::from_value(serde_json::json!({"done": true})).unwrap();
+        assert!(matches!(
+            empty.into_completion(),
+            Err(LlmError::EmptyResponse)
+        ));
     }
 
     #[tokio::test]
@@ -0,0 +1,374 @@
+//! Locale conventions for reading and showing dates.
+//!
+//! Whether `03/04` is 4 March or 3 April, and which Monday "next Monday" is,
+//! depend on where the user is. A [`DateContext`] holds those conventions,
+//! from `FMEMORY_LOCALE` (a tag such as `de-DE` or `en_GB`) and
+//! `FMEMORY_WEEK_START`: [`DateContext::prompt_context`] spells them out
+//! next to today's date in the LLM prompts, and [`DateContext::parse_date`]
+//! reads numeric dates in the same order for `auto_ttl_from_date`. With
+//! neither set, prompts carry only today's date, as before, and numeric
+//! dates are read month first.
+//!
+//! Timestamps are stored in UTC. `FMEMORY_TIMEZONE` (an IANA name such as
+//! `Europe/Berlin`) sets the zone "today" is taken in, the zone an event's
+//! day ends in, and the zone timestamps are shown in. Without it, today is
+//! the system's local date, events end at midnight UTC, and timestamps are
+//! shown as stored.
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde_json::Value;
+
+/// Environment variable holding the locale tag, e.g. `de-DE`.
+pub const LOCALE_ENV: &str = "FMEMORY_LOCALE";
+
+/// Environment variable overriding the locale's first day of the week.
+pub const WEEK_START_ENV: &str = "FMEMORY_WEEK_START";
+
+/// Environment variable holding the display timezone, e.g. `Europe/Berlin`.
+pub const TIMEZONE_ENV: &str = "FMEMORY_TIMEZONE";
+
+/// Suffix of attributes holding RFC 3339 timestamps (`created_at`,
+/// `expires_at`, ...).
+const TIMESTAMP_SUFFIX: &str = "_at";
+
+/// Order of the day, month, and year in a numeric date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOrder {
+    /// `03/04/2026` is 4 March (United States and a few others).
+    MonthDayYear,
+    /// `03/04/2026` is 3 April (most of the world).
+    DayMonthYear,
+    /// `2026/03/04` is 4 March (China, Japan, Korea, Hungary, ...).
+    YearMonthDay,
+}
+
+/// Regions writing the month first.
+const MONTH_FIRST_REGIONS: &[&str] = &["US", "PH", "FM", "MH", "PW", "BZ"];
+
+/// Languages writing the year first.
+const YEAR_FIRST_LANGUAGES: &[&str] = &["zh", "ja", "ko", "hu", "lt", "mn"];
+
+/// Regions whose week starts on Sunday.
+const SUNDAY_REGIONS: &[&str] = &[
+    "US", "CA", "MX", "BR", "JP", "KR", "TW", "HK", "IL", "PH", "IN", "ZA",
+];
+
+/// Regions whose week starts on Saturday.
+const SATURDAY_REGIONS: &[&str] = &[
+    "AE", "AF", "BH", "DZ", "EG", "IQ", "IR", "JO", "KW", "LY", "OM", "QA", "SA", "SD", "SY",
+];
+
+/// Date conventions for prompts and date parsing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DateContext {
+    /// Locale tag as configured, e.g. `de-DE`.
+    pub locale: Option<String>,
+    /// First day of the week, overriding the locale's.
+    pub week_start: Option<Weekday>,
+    /// Timezone for today's date, event expiry, and displayed timestamps.
+    pub timezone: Option<Tz>,
+}
+
+/// Parse an IANA timezone name (`Europe/Berlin`, `UTC`).
+pub fn parse_timezone(value: &str) -> Result<Tz, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Unknown timezone '{}'", value.trim()))
+}
+
+impl DateContext {
+    /// Conventions for the locale tag `locale` (`de-DE`, `en_GB.UTF-8`, `ja`).
+    pub fn for_locale(locale: &str) -> Self {
+        let locale = locale.trim();
+        Self {
+            locale: (!locale.is_empty()).then(|| locale.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Conventions from `FMEMORY_LOCALE`, `FMEMORY_WEEK_START`, and
+    /// `FMEMORY_TIMEZONE`. An unrecognized week start or timezone is ignored.
+    pub fn from_env() -> Self {
+        let mut context = std::env::var(LOCALE_ENV)
+            .map(|locale| Self::for_locale(&locale))
+            .unwrap_or_default();
+        context.week_start = std::env::var(WEEK_START_ENV)
+            .ok()
+            .and_then(|day| day.trim().parse().ok());
+        context.timezone = std::env::var(TIMEZONE_ENV)
+            .ok()
+            .and_then(|tz| parse_timezone(&tz).ok());
+        context
+    }
+
+    /// Use `day` as the first day of the week.
+    pub fn with_week_start(mut self, day: Weekday) -> Self {
+        self.week_start = Some(day);
+        self
+    }
+
+    /// Use `timezone` for today's date, event expiry, and display.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
+
+    /// Today's date in the configured timezone, or the system's local date.
+    pub fn today(&self) -> NaiveDate {
+        match self.timezone {
+            Some(tz) => Utc::now().with_timezone(&tz).date_naive(),
+            None => chrono::Local::now().date_naive(),
+        }
+    }
+
+    /// The last second of `date` in the configured timezone, or in UTC.
+    pub fn end_of_day(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+        let end = date.and_time(NaiveTime::from_hms_opt(23, 59, 59)?);
+        match self.timezone {
+            Some(tz) => Some(tz.from_local_datetime(&end).latest()?.with_timezone(&Utc)),
+            None => Some(end.and_utc()),
+        }
+    }
+
+    /// An RFC 3339 timestamp in the configured timezone, e.g.
+    /// `2026-03-04 15:30:00 CET`. `None` without a timezone or when `text`
+    /// is not a timestamp.
+    pub fn display_timestamp(&self, text: &str) -> Option<String> {
+        let tz = self.timezone?;
+        let timestamp = DateTime::parse_from_rfc3339(text).ok()?;
+        Some(
+            timestamp
+                .with_timezone(&tz)
+                .format("%Y-%m-%d %H:%M:%S %Z")
+                .to_string(),
+        )
+    }
+
+    /// `item` with its timestamp attributes (`created_at`, `expires_at`,
+    /// ...) shown in the configured timezone. Unchanged without one.
+    pub fn localize_item(&self, item: &Value) -> Value {
+        let mut item = item.clone();
+        if let Some(obj) = item.as_object_mut() {
+            for (name, value) in obj.iter_mut() {
+                if name.ends_with(TIMESTAMP_SUFFIX)
+                    && let Some(shown) = value.as_str().and_then(|t| self.display_timestamp(t))
+                {
+                    *value = Value::String(shown);
+                }
+            }
+        }
+        item
+    }
+
+    /// Lowercase language and uppercase region of the locale tag.
+    fn language_region(&self) -> (String, Option<String>) {
+        let Some(ref locale) = self.locale else {
+            return ("en".to_string(), Some("US".to_string()));
+        };
+        let tag = locale.split(['.', '@']).next().unwrap_or_default();
+        let mut parts = tag.split(['-', '_']);
+        let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let region = parts
+            .find(|part| part.len() == 2 && part.chars().all(|c| c.is_ascii_alphabetic()))
+            .map(|part| part.to_ascii_uppercase());
+        (language, region)
+    }
+
+    /// Order of numeric dates. A bare `en` is taken as United States usage.
+    pub fn date_order(&self) -> DateOrder {
+        let (language, region) = self.language_region();
+        match region.as_deref() {
+            Some(region) if MONTH_FIRST_REGIONS.contains(&region) => DateOrder::MonthDayYear,
+            None if language == "en" => DateOrder::MonthDayYear,
+            _ if YEAR_FIRST_LANGUAGES.contains(&language.as_str()) => DateOrder::YearMonthDay,
+            _ => DateOrder::DayMonthYear,
+        }
+    }
+
+    /// First day of the week: the override, else the locale's (Monday unless
+    /// the region starts weeks on Sunday or Saturday).
+    pub fn week_start(&self) -> Weekday {
+        if let Some(day) = self.week_start {
+            return day;
+        }
+        let (language, region) = self.language_region();
+        match region.as_deref() {
+            Some(region) if SUNDAY_REGIONS.contains(&region) => Weekday::Sun,
+            Some(region) if SATURDAY_REGIONS.contains(&region) => Weekday::Sat,
+            None if language == "en" => Weekday::Sun,
+            _ => Weekday::Mon,
+        }
+    }
+
+    /// The date lines for an LLM prompt: `Today's date: ...`, then the
+    /// timezone when one is configured and, when a locale or week start is,
+    /// the conventions for reading numeric and relative dates.
+    pub fn prompt_context(&self, today: NaiveDate) -> String {
+        let mut context = format!("Today's date: {}", today.format("%Y-%m-%d (%A)"));
+        if let Some(tz) = self.timezone {
+            context.push_str(&format!(
+                "\nTimezone: {tz}; dates and times mentioned by the user are in this timezone."
+            ));
+        }
+        if self.locale.is_none() && self.week_start.is_none() {
+            return context;
+        }
+        let example = NaiveDate::from_ymd_opt(today.year(), 3, 4).unwrap_or(today);
+        let (order, written) = match self.date_order() {
+            DateOrder::MonthDayYear => ("month/day/year", example.format("%m/%d")),
+            DateOrder::DayMonthYear => ("day/month/year", example.format("%d/%m")),
+            DateOrder::YearMonthDay => ("year/month/day", example.format("%Y/%m/%d")),
+        };
+        let week_start = weekday_name(self.week_start());
+        context.push_str(&format!(
+            "\nDate conventions{}: numeric dates are written {order} (\"{written}\" is {}); \
+             weeks start on {week_start}, so \"this week\" and \"next week\" begin on a \
+             {week_start}, and \"next <weekday>\" is that weekday in the week after the current one.",
+            self.locale
+                .as_deref()
+                .map(|locale| format!(" ({locale})"))
+                .unwrap_or_default(),
+            example.format("%-d %B"),
+        ));
+        context
+    }
+
+    /// Read a date: ISO 8601 (`2026-03-04`) always, otherwise a numeric date
+    /// in this locale's order with `/`, `.`, or `-` separators. A missing
+    /// year is `year`'s; a two-digit one is in the 2000s.
+    pub fn parse_date(&self, text: &str, year: i32) -> Option<NaiveDate> {
+        let text = text.trim();
+        if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+            return Some(date);
+        }
+        let parts: Vec<u32> = text
+            .trim_end_matches('.')
+            .split(['/', '.', '-'])
+            .map(|part| part.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        let full_year = |y: u32| if y < 100 { 2000 + y as i32 } else { y as i32 };
+        let (y, m, d) = match (self.date_order(), parts.as_slice()) {
+            (DateOrder::MonthDayYear, [m, d]) => (year, *m, *d),
+            (DateOrder::MonthDayYear, [m, d, y]) => (full_year(*y), *m, *d),
+            (DateOrder::DayMonthYear, [d, m]) => (year, *m, *d),
+            (DateOrder::DayMonthYear, [d, m, y]) => (full_year(*y), *m, *d),
+            (DateOrder::YearMonthDay, [m, d]) => (year, *m, *d),
+            (DateOrder::YearMonthDay, [y, m, d]) => (full_year(*y), *m, *d),
+            _ => return None,
+        };
+        NaiveDate::from_ymd_opt(y, m, d)
+    }
+}
+
+/// English name of `day`, as used in prompts.
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_locale_conventions() {
+        let us = DateContext::default();
+        assert_eq!(us.date_order(), DateOrder::MonthDayYear);
+        assert_eq!(us.week_start(), Weekday::Sun);
+
+        let de = DateContext::for_locale("de_DE.UTF-8");
+        assert_eq!(de.date_order(), DateOrder::DayMonthYear);
+        assert_eq!(de.week_start(), Weekday::Mon);
+
+        let gb = DateContext::for_locale("en-GB");
+        assert_eq!(gb.date_order(), DateOrder::DayMonthYear);
+        assert_eq!(gb.week_start(), Weekday::Mon);
+
+        assert_eq!(
+            DateContext::for_locale("ja-JP").date_order(),
+            DateOrder::YearMonthDay
+        );
+        assert_eq!(DateContext::for_locale("ar-SA").week_start(), Weekday::Sat);
+        assert_eq!(gb.with_week_start(Weekday::Sun).week_start(), Weekday::Sun);
+    }
+
+    #[test]
+    fn test_parse_date_follows_locale() {
+        let us = DateContext::default();
+        let de = DateContext::for_locale("de-DE");
+        assert_eq!(us.parse_date("03/04", 2026), Some(date(2026, 3, 4)));
+        assert_eq!(de.parse_date("03/04", 2026), Some(date(2026, 4, 3)));
+        assert_eq!(de.parse_date("03.04.27", 2026), Some(date(2027, 4, 3)));
+        assert_eq!(de.parse_date("2026-03-04", 2026), Some(date(2026, 3, 4)));
+        assert_eq!(
+            DateContext::for_locale("ja").parse_date("2026/03/04", 2026),
+            Some(date(2026, 3, 4))
+        );
+        assert_eq!(us.parse_date("13/04", 2026), None);
+        assert_eq!(us.parse_date("tomorrow", 2026), None);
+    }
+
+    #[test]
+    fn test_prompt_context() {
+        let today = date(2026, 3, 5);
+        assert_eq!(
+            DateContext::default().prompt_context(today),
+            "Today's date: 2026-03-05 (Thursday)"
+        );
+        let de = DateContext::for_locale("de-DE").prompt_context(today);
+        assert!(de.starts_with("Today's date: 2026-03-05 (Thursday)\nDate conventions (de-DE)"));
+        assert!(de.contains("day/month/year (\"04/03\" is 4 March)"));
+        assert!(de.contains("weeks start on Monday"));
+
+        let berlin = DateContext::default()
+            .with_timezone(chrono_tz::Europe::Berlin)
+            .prompt_context(today);
+        assert!(berlin.ends_with(
+            "\nTimezone: Europe/Berlin; dates and times mentioned by the user are in this timezone."
+        ));
+    }
+
+    #[test]
+    fn test_timezone() {
+        assert_eq!(
+            parse_timezone(" Europe/Berlin ").unwrap(),
+            chrono_tz::Europe::Berlin
+        );
+        assert!(parse_timezone("Mars/Olympus").is_err());
+
+        let utc = DateContext::default();
+        let berlin = DateContext::default().with_timezone(chrono_tz::Europe::Berlin);
+        assert_eq!(
+            utc.end_of_day(date(2026, 3, 4)).unwrap().to_rfc3339(),
+            "2026-03-04T23:59:59+00:00"
+        );
+        assert_eq!(
+            berlin.end_of_day(date(2026, 3, 4)).unwrap().to_rfc3339(),
+            "2026-03-04T22:59:59+00:00"
+        );
+
+        let item = json!({"key": "k", "created_at": "2026-07-01T12:00:00+00:00", "note": "2026-07-01T12:00:00+00:00"});
+        assert_eq!(utc.localize_item(&item), item);
+        let shown = berlin.localize_item(&item);
+        assert_eq!(shown["created_at"], "2026-07-01 14:00:00 CEST");
+        assert_eq!(shown["note"], item["note"]);
+    }
+}
@@ -0,0 +1,296 @@
+//! Structured JSON logging of memory operations.
+//!
+//! When enabled (`--log <stderr|PATH>` or `FMEMORY_LOG`), every CLI command and
+//! MCP tool call emits one JSON event with the operation, category, key,
+//! namespace, latency, and outcome, so agent memory activity can be analyzed
+//! downstream. Logging is off by default to keep stderr free for status output.
+//!
+//! MCP tool calls, [`MemoryBackend`](crate::backend::MemoryBackend) operations,
+//! and LLM completions run inside `tracing` spans tagged with a request ID; span
+//! close events carry busy/idle timings, so a slow recall can be attributed to
+//! server round-trips or LLM latency (`RUST_LOG=debug` for backend/LLM spans).
+//!
+//! The request ID is generated once per CLI invocation, MCP tool call, or HTTP
+//! request and held for its duration by [`with_request_id`]. Besides the spans,
+//! it goes into operation events, recall interaction logs, usage records, and
+//! error output (the `--json` envelope, MCP error data, HTTP error bodies and
+//! the `x-request-id` header), so one failure can be traced across them.
+//! ferridyn-server requests carry no metadata; backend spans are children of
+//! the request span instead.
+
+use std::fs::OpenOptions;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+/// Environment variable selecting the log destination (`stderr` or a file path).
+pub const LOG_ENV: &str = "FMEMORY_LOG";
+
+/// Tracing target used for per-operation events.
+pub const OP_TARGET: &str = "fmemory::op";
+
+/// Install a JSON subscriber writing to `target` (`"stderr"` or a file path).
+///
+/// The file is created if missing and appended to otherwise. The level filter
+/// honours `RUST_LOG`, defaulting to `info`.
+pub fn init(target: &str) -> std::io::Result<()> {
+    if target == "stderr" {
+        install(std::io::stderr);
+    } else {
+        let path = Path::new(target);
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        install(Mutex::new(file));
+    }
+    Ok(())
+}
+
+fn install<W>(writer: W)
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let subscriber = json_subscriber(writer);
+    // A subscriber may already be installed (e.g. in tests); keep the first.
+    let _ = tracing::subscriber::set_global_default(subscriber);
+}
+
+fn json_subscriber<W>(writer: W) -> impl tracing::Subscriber + Send + Sync
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt()
+        .json()
+        .flatten_event(true)
+        .with_current_span(false)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .with_writer(writer)
+        .finish()
+}
+
+static REQUEST_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Generate an ID for correlating one request's spans and events.
+///
+/// Combines the process ID with a per-process counter, so IDs are unique
+/// across concurrently running CLI and MCP server processes.
+pub fn new_request_id() -> String {
+    let n = REQUEST_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{:x}-{n:x}", std::process::id())
+}
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Run `f` as the request identified by `id`.
+pub async fn with_request_id<F: Future>(id: String, f: F) -> F::Output {
+    REQUEST_ID.scope(id, f).await
+}
+
+/// ID of the request being run (see [`with_request_id`]), if any.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// ID of the request being run, or a fresh one outside any.
+pub fn request_id() -> String {
+    current_request_id().unwrap_or_else(new_request_id)
+}
+
+/// Final outcome of a logged operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Ok,
+    NotFound,
+    /// The user declined to confirm the operation.
+    Cancelled,
+    Error,
+}
+
+impl Outcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Outcome::Ok => "ok",
+            Outcome::NotFound => "not_found",
+            Outcome::Cancelled => "cancelled",
+            Outcome::Error => "error",
+        }
+    }
+}
+
+/// Records one operation and emits its event when dropped.
+///
+/// The outcome defaults to [`Outcome::Error`], so early returns via `?` are
+/// logged as failures; call [`OpLog::set_outcome`] on the success path.
+pub struct OpLog {
+    operation: &'static str,
+    request_id: Option<String>,
+    namespace: Option<String>,
+    category: Option<String>,
+    key: Option<String>,
+    outcome: Outcome,
+    started: Instant,
+}
+
+impl OpLog {
+    /// Start timing an operation of the current request.
+    pub fn start(operation: &'static str, namespace: Option<&str>) -> Self {
+        Self {
+            operation,
+            request_id: current_request_id(),
+            namespace: namespace.map(str::to_string),
+            category: None,
+            key: None,
+            outcome: Outcome::Error,
+            started: Instant::now(),
+        }
+    }
+
+    /// Attach the category the operation targets.
+    pub fn category(mut self, category: Option<&str>) -> Self {
+        self.category = category.map(str::to_string);
+        self
+    }
+
+    /// Attach the item key the operation targets.
+    pub fn key(mut self, key: Option<&str>) -> Self {
+        self.key = key.map(str::to_string);
+        self
+    }
+
+    /// Name of the operation being logged.
+    pub fn operation(&self) -> &'static str {
+        self.operation
+    }
+
+    /// Set the outcome reported when the operation finishes.
+    pub fn set_outcome(&mut self, outcome: Outcome) {
+        self.outcome = outcome;
+    }
+}
+
+impl Drop for OpLog {
+    fn drop(&mut self) {
+        let latency_ms = self.started.elapsed().as_secs_f64() * 1000.0;
+        tracing::info!(
+            target: OP_TARGET,
+            operation = self.operation,
+            request_id = self.request_id.as_deref(),
+            namespace = self.namespace.as_deref(),
+            category = self.category.as_deref(),
+            key = self.key.as_deref(),
+            latency_ms,
+            outcome = self.outcome.as_str(),
+            "memory operation"
+        );
+    }
+}
+
+/// Shared in-memory writer for capturing log output in tests.
+#[cfg(test)]
+#[derive(Clone, Default)]
+struct Capture(std::sync::Arc<Mutex<Vec<u8>>>);
+
+#[cfg(test)]
+impl std::io::Write for Capture {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl<'a> MakeWriter<'a> for Capture {
+    type Writer = Capture;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture_events(f: impl FnOnce()) -> Vec<serde_json::Value> {
+        let capture = Capture::default();
+        let subscriber = json_subscriber(capture.clone());
+        tracing::subscriber::with_default(subscriber, f);
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        output
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_op_log_emits_json_event() {
+        let events = capture_events(|| {
+            let mut op = OpLog::start("recall", Some("proj"))
+                .category(Some("contacts"))
+                .key(Some("toby"));
+            op.set_outcome(Outcome::Ok);
+        });
+
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event["target"], OP_TARGET);
+        assert_eq!(event["operation"], "recall");
+        assert_eq!(event["namespace"], "proj");
+        assert_eq!(event["category"], "contacts");
+        assert_eq!(event["key"], "toby");
+        assert_eq!(event["outcome"], "ok");
+        assert!(event["latency_ms"].as_f64().unwrap() >= 0.0);
+    }
+
+    #[test]
+    fn test_op_log_defaults_to_error() {
+        let events = capture_events(|| {
+            let _op = OpLog::start("forget", None);
+        });
+
+        assert_eq!(events[0]["outcome"], "error");
+        assert!(events[0].get("category").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_request_id_scope() {
+        assert_eq!(current_request_id(), None);
+        let events = with_request_id("abc-1".into(), async {
+            assert_eq!(request_id(), "abc-1");
+            capture_events(|| {
+                let _op = OpLog::start("recall", None);
+            })
+        })
+        .await;
+        assert_eq!(events[0]["request_id"], "abc-1");
+        assert_ne!(request_id(), request_id());
+    }
+
+    #[test]
+    fn test_request_ids_are_unique() {
+        let a = new_request_id();
+        let b = new_request_id();
+        assert_ne!(a, b);
+        assert!(a.starts_with(&format!("{:x}-", std::process::id())));
+    }
+}
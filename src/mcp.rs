@@ -1,31 +1,96 @@
 //! MCP (Model Context Protocol) server interface for memory operations.
 //!
-//! Exposes memory operations as MCP tools for AI agents via stdio transport.
-//! No LLM calls — agents provide structured data directly.
+//! Exposes memory operations as MCP tools for AI agents via stdio transport,
+//! or over rmcp's streamable HTTP transport ([`run_mcp_http_server`]).
+//! Agents provide structured data directly; the natural language tools
+//! (`memory_remember`, `memory_recall`, `memory_summarize`) are only registered when the server is
+//! built with an LLM client; without one, calling them fails with a
+//! capability-missing error instead of "tool not found". [`MemoryServerBuilder`] configures namespace
+//! policy, exposed tools, rate limiting, and query limits.
+//!
+//! With a [`TenantMap`] ([`MemoryServerBuilder::tenants`]), a server shared
+//! over HTTP runs each tool call in the namespace of the tenant owning the
+//! caller's bearer token and rejects calls without one; see [`crate::tenant`].
+//!
+//! With [`MemoryServerBuilder::client_namespaces`], each MCP client gets
+//! its own namespace, named after the client (`claude-code`, `cursor`), so
+//! assistants sharing a server keep separate memory. A shared namespace
+//! ([`MemoryServerBuilder::shared_namespace`]) is readable by every client
+//! but writable by none; `memory_get` and `memory_query` fall back to it for
+//! what the caller's own namespace lacks.
+//!
+//! [`run_mcp_server`] and [`run_mcp_http_server`] stop on SIGINT or SIGTERM:
+//! they reject new tool calls, wait for the ones in flight to finish, and
+//! only then release the backend, so a signal never interrupts a write
+//! halfway.
 
+use std::collections::HashSet;
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use rmcp::{
-    ErrorData as McpError, ServerHandler, ServiceExt,
+    ErrorData as McpError, RoleServer, ServerHandler, ServiceExt,
+    handler::server::tool::ToolCallContext,
     handler::server::{router::tool::ToolRouter, wrapper::Parameters},
     model::{
-        CallToolResult, Content, Implementation, ProtocolVersion, ServerCapabilities, ServerInfo,
+        CallToolRequestParam, CallToolResult, Content, Implementation, ListToolsResult,
+        PaginatedRequestParam, ProtocolVersion, ServerCapabilities, ServerInfo,
     },
-    tool, tool_handler, tool_router,
+    service::{Peer, RequestContext},
+    tool, tool_router,
     transport::stdio,
+    transport::streamable_http_server::{
+        StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
+    },
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 
+use crate::append::append_content;
 use crate::backend::MemoryBackend;
-use crate::resolve_table_name;
+use crate::cascade::{cascade_forget, cascade_forget_many};
+use crate::chunk::{
+    CHUNK_SIZE_CHARS, delete_chunks, hydrate_chunks, is_chunk, needs_chunking, split_into_chunks,
+    store_chunks,
+};
+use crate::confirm::{Confirmation, describe_refs, expired_counts};
+use crate::conflict::item_ref;
+use crate::context::PREFERENCES_CATEGORY;
+use crate::error::MemoryError;
+use crate::keys::slugify;
+use crate::limits::Limits;
+#[cfg(feature = "llm")]
+use crate::llm::LlmClient;
+use crate::logging::{OpLog, Outcome, new_request_id, request_id, with_request_id};
+use crate::page::list_keys_page;
+use crate::preferences::preference_digest;
+#[cfg(feature = "llm")]
+use crate::promote::{DEFAULT_PROMOTION_WINDOW, suggest_promotions};
+use crate::quota::Quotas;
+#[cfg(feature = "llm")]
+use crate::recall_cache::RecallCache;
+#[cfg(feature = "llm")]
+use crate::related::RelatedBudget;
+use crate::related::backlinks;
 use crate::schema::{PREDEFINED_SCHEMAS, SchemaManager};
+use crate::search::register_namespace;
+#[cfg(feature = "llm")]
+use crate::session::end_session;
+use crate::session::{SESSION_CATEGORY, append_to_session, start_session};
+use crate::similar::suggest_related;
+use crate::sort::{SortField, UPDATED_AT_ATTRIBUTE, query_sorted};
+#[cfg(feature = "llm")]
+use crate::store::{MemoryStore, OVERVIEW_KEY, RememberOptions};
+use crate::tenant::TenantMap;
 use crate::ttl::{
     INTERACTIONS_DEFAULT_TTL, SCRATCHPAD_DEFAULT_TTL, SESSIONS_DEFAULT_TTL, compute_expires_at,
-    filter_expired, is_expired, parse_ttl,
+    is_expired, parse_ttl,
 };
+use crate::usage::{USAGE_FLUSH_INTERVAL, UsageTracker};
+use crate::{namespace_of, resolve_table_name, validate_namespace};
 
 // ============================================================================
 // Tool Input Schemas
@@ -58,6 +123,66 @@ pub struct GetParams {
     pub namespace: Option<String>,
 }
 
+/// Parameters for appending to a memory's content.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct AppendParams {
+    /// Category of the memory.
+    pub category: String,
+    /// Key of the memory.
+    pub key: String,
+    /// Text to add as a new timestamped entry at the end of `content`.
+    pub text: String,
+    /// Optional namespace override.
+    pub namespace: Option<String>,
+}
+
+/// Parameters for merging attributes into a memory.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct UpdateParams {
+    /// Category of the memory.
+    pub category: String,
+    /// Key of the memory.
+    pub key: String,
+    /// Attributes to merge in: a `null` value removes the attribute, an
+    /// object is merged into an object attribute, anything else replaces it.
+    pub attributes: serde_json::Map<String, Value>,
+    /// Optional namespace override.
+    pub namespace: Option<String>,
+}
+
+/// Parameters for loading preferences as directives.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct PreferencesParams {
+    /// Only preferences with this `scope` (e.g. "code", "git").
+    pub scope: Option<String>,
+    /// Optional namespace override.
+    pub namespace: Option<String>,
+}
+
+/// Parameters for listing the items that reference a memory.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct BacklinksParams {
+    /// Category of the referenced item.
+    pub category: String,
+    /// Key of the referenced item.
+    pub key: String,
+    /// Optional namespace override.
+    pub namespace: Option<String>,
+}
+
+/// Parameters for suggesting memories related to one item.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct RelatedParams {
+    /// Category of the item to find neighbours for.
+    pub category: String,
+    /// Key of the item to find neighbours for.
+    pub key: String,
+    /// Maximum number of suggestions (default: 20).
+    pub limit: Option<usize>,
+    /// Optional namespace override.
+    pub namespace: Option<String>,
+}
+
 /// Parameters for querying memories in a category.
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
 pub struct QueryParams {
@@ -67,6 +192,11 @@ pub struct QueryParams {
     pub prefix: Option<String>,
     /// Maximum number of results (default: 20).
     pub limit: Option<usize>,
+    /// Sort field: "key" (default), "created_at", or "updated_at".
+    #[schemars(description = "Sort by key (default), created_at, or updated_at")]
+    pub sort: Option<String>,
+    /// Sort descending instead of ascending.
+    pub desc: Option<bool>,
     /// Optional namespace override.
     pub namespace: Option<String>,
 }
@@ -78,6 +208,12 @@ pub struct DeleteParams {
     pub category: String,
     /// Item key.
     pub key: String,
+    /// Also remove links from and to the item, and the `related` and
+    /// `conflicts_with` references other items hold to it.
+    pub cascade: Option<bool>,
+    /// Treat `key` as a key prefix and delete every memory in the category
+    /// whose key starts with it.
+    pub prefix: Option<bool>,
     /// Optional namespace override.
     pub namespace: Option<String>,
 }
@@ -87,6 +223,10 @@ pub struct DeleteParams {
 pub struct ListParams {
     /// If provided, list keys within this category. Otherwise list all categories.
     pub category: Option<String>,
+    /// Maximum number of categories or keys per page (default: 100).
+    pub limit: Option<usize>,
+    /// `next_cursor` from the previous page, to continue the listing.
+    pub cursor: Option<String>,
     /// Optional namespace override.
     pub namespace: Option<String>,
 }
@@ -131,6 +271,382 @@ pub struct InitParams {
     pub force: Option<bool>,
 }
 
+/// Parameters for storing a memory from natural language.
+#[cfg(feature = "llm")]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct RememberParams {
+    /// Natural language text to remember.
+    pub text: String,
+    /// Optional target category (inferred when omitted).
+    pub category: Option<String>,
+    /// Optional key (derived from the text when omitted).
+    pub key: Option<String>,
+    /// Optional TTL (e.g. "24h", "7d", "2w").
+    #[schemars(description = "Time-to-live: 24h, 7d, 30d, etc.")]
+    pub ttl: Option<String>,
+    /// Optional namespace override.
+    pub namespace: Option<String>,
+}
+
+/// Parameters for a natural language recall.
+#[cfg(feature = "llm")]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct RecallParams {
+    /// Natural language question.
+    pub query: String,
+    /// Maximum number of results (default: 20).
+    pub limit: Option<usize>,
+    /// Also return the items that matches reference through `related`.
+    pub related: Option<bool>,
+    /// With `related`, also follow the references of related items, up to
+    /// this many hops (default: 1).
+    pub hops: Option<usize>,
+    /// With `related`, add at most this many related items (default: 20).
+    pub related_limit: Option<usize>,
+    /// Also synthesize an answer from the items. A partial answer comes
+    /// with `follow_up` queries and categories to check next.
+    pub answer: Option<bool>,
+    /// Optional namespace override.
+    pub namespace: Option<String>,
+}
+
+/// Parameters for starting a working session.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct SessionStartParams {
+    /// Session key (default: `session-YYYYMMDD-HHMMSS`).
+    pub key: Option<String>,
+    /// Project the session works on.
+    pub project: Option<String>,
+    /// Git branch the session works on.
+    pub branch: Option<String>,
+    /// What the session sets out to do.
+    pub goal: Option<String>,
+    /// Optional namespace override.
+    pub namespace: Option<String>,
+}
+
+/// Parameters for recording a fact on a session.
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct SessionAppendParams {
+    /// Notable fact, decision, or finding.
+    pub fact: String,
+    /// Session key (default: the most recently started active session).
+    pub key: Option<String>,
+    /// Optional namespace override.
+    pub namespace: Option<String>,
+}
+
+/// Parameters for ending a session.
+#[cfg(feature = "llm")]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct SessionEndParams {
+    /// Session key (default: the most recently started active session).
+    pub key: Option<String>,
+    /// Optional namespace override.
+    pub namespace: Option<String>,
+}
+
+/// Parameters for suggesting short-term items worth promoting.
+#[cfg(feature = "llm")]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct PromoteSuggestParams {
+    /// Consider items expiring within this duration, e.g. "24h" or "3d"
+    /// (default: 48h).
+    pub within: Option<String>,
+    /// Maximum number of suggestions.
+    pub limit: Option<usize>,
+    /// Optional namespace override.
+    pub namespace: Option<String>,
+}
+
+/// Parameters for condensing a category into an overview item.
+#[cfg(feature = "llm")]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct SummarizeParams {
+    /// Category to summarize.
+    pub category: String,
+    /// Optional namespace override.
+    pub namespace: Option<String>,
+}
+
+// ============================================================================
+// Server Configuration
+// ============================================================================
+
+/// Maximum number of items a `memory_delete` with `prefix` removes.
+const PREFIX_DELETE_LIMIT: usize = 1_000;
+
+/// Tools exposed by [`ToolProfile::Core`].
+const CORE_TOOLS: &[&str] = &[
+    "memory_store",
+    "memory_get",
+    "memory_query",
+    "memory_delete",
+    "memory_list",
+    "memory_remember",
+    "memory_recall",
+];
+
+/// Tools that call the LLM, registered only when the server has a client.
+pub const NL_TOOLS: &[&str] = &[
+    "memory_remember",
+    "memory_recall",
+    "memory_summarize",
+    "memory_session_end",
+    "memory_promote_suggest",
+];
+
+/// Tools that modify memory, hidden on read-only servers.
+const WRITE_TOOLS: &[&str] = &[
+    "memory_store",
+    "memory_append",
+    "memory_update",
+    "memory_delete",
+    "memory_promote",
+    "memory_prune",
+    "memory_init",
+    "memory_remember",
+    "memory_summarize",
+    "memory_session_start",
+    "memory_session_append",
+    "memory_session_end",
+];
+
+tokio::task_local! {
+    /// Caller of the tool call being handled, set by `call_tool`.
+    static CALLER: Caller;
+}
+
+/// Who is making a tool call, as far as namespace selection is concerned.
+#[derive(Debug, Clone, Default)]
+struct Caller {
+    /// Tenant owning the bearer token, on servers with a [`TenantMap`].
+    tenant: Option<String>,
+    /// Namespace named after the client, on servers with
+    /// [`MemoryServerBuilder::client_namespaces`].
+    client_namespace: Option<String>,
+    /// Whether the tool modifies memory.
+    writes: bool,
+    /// Connection to the client, for asking the user to confirm destructive
+    /// calls.
+    peer: Option<Peer<RoleServer>>,
+}
+
+/// Which namespaces a tool call may select with its `namespace` parameter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum NamespacePolicy {
+    /// Any namespace.
+    #[default]
+    Open,
+    /// Only the server's own namespace; overrides are rejected.
+    Locked,
+    /// The server's namespace plus the listed ones.
+    Allowed(Vec<String>),
+}
+
+/// Which tools the server exposes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ToolProfile {
+    /// Every tool.
+    #[default]
+    Full,
+    /// Item storage and retrieval only: no schema, promote, prune, or init tools.
+    Core,
+}
+
+/// Maximum number of tool calls per time window, shared by all clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub max_calls: u32,
+    pub per: Duration,
+}
+
+/// Fixed-window counter enforcing a [`RateLimit`].
+struct RateLimiter {
+    limit: RateLimit,
+    window: std::sync::Mutex<(Instant, u32)>,
+}
+
+impl RateLimiter {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            window: std::sync::Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    /// Count a call. Returns `false` if the current window is used up.
+    fn try_acquire(&self) -> bool {
+        let mut window = self.window.lock().unwrap();
+        let now = Instant::now();
+        if now.duration_since(window.0) >= self.limit.per {
+            *window = (now, 0);
+        }
+        if window.1 >= self.limit.max_calls {
+            return false;
+        }
+        window.1 += 1;
+        true
+    }
+}
+
+/// Builder for [`MemoryServer`].
+pub struct MemoryServerBuilder {
+    backend: MemoryBackend,
+    namespace: Option<String>,
+    namespace_policy: NamespacePolicy,
+    tool_profile: ToolProfile,
+    read_only: bool,
+    #[cfg(feature = "llm")]
+    llm: Option<Arc<dyn LlmClient>>,
+    rate_limit: Option<RateLimit>,
+    limits: Limits,
+    quotas: Quotas,
+    tenants: Option<TenantMap>,
+    client_namespaces: bool,
+    shared_namespace: Option<String>,
+    usage: Option<Arc<UsageTracker>>,
+}
+
+impl MemoryServerBuilder {
+    /// Namespace used when a tool call passes none.
+    pub fn namespace(mut self, namespace: Option<String>) -> Self {
+        self.namespace = namespace;
+        self
+    }
+
+    /// Restrict which namespaces tool calls may select.
+    pub fn namespace_policy(mut self, policy: NamespacePolicy) -> Self {
+        self.namespace_policy = policy;
+        self
+    }
+
+    /// Choose which tools are exposed.
+    pub fn tool_profile(mut self, profile: ToolProfile) -> Self {
+        self.tool_profile = profile;
+        self
+    }
+
+    /// Hide every tool that modifies memory.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Enable the natural language tools, backed by `llm`.
+    #[cfg(feature = "llm")]
+    pub fn llm(mut self, llm: Arc<dyn LlmClient>) -> Self {
+        self.llm = Some(llm);
+        self
+    }
+
+    /// Persist the LLM token usage tallied in `usage`, such as by the
+    /// client given to [`llm`](Self::llm), every [`USAGE_FLUSH_INTERVAL`]
+    /// while serving and on drain.
+    pub fn usage(mut self, usage: Arc<UsageTracker>) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    /// Reject tool calls beyond `limit`.
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Default and maximum result counts, globally and per tool. Defaults
+    /// to [`Limits::load`].
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Result count used when a tool call passes no `limit`.
+    pub fn default_limit(mut self, limit: usize) -> Self {
+        self.limits.default = limit;
+        self
+    }
+
+    /// Upper bound on the result count of any tool call.
+    pub fn max_limit(mut self, limit: usize) -> Self {
+        self.limits.max = limit;
+        self
+    }
+
+    /// Per-category item caps enforced after writes. Defaults to
+    /// [`Quotas::from_env`].
+    pub fn quotas(mut self, quotas: Quotas) -> Self {
+        self.quotas = quotas;
+        self
+    }
+
+    /// Serve several tenants: each tool call must present a token of
+    /// `tenants` and is confined to its tenant's namespace, overriding the
+    /// namespace policy. Requires an HTTP transport, which carries the
+    /// `Authorization` header; over stdio every call is rejected.
+    pub fn tenants(mut self, tenants: TenantMap) -> Self {
+        self.tenants = Some(tenants);
+        self
+    }
+
+    /// Default each tool call to a namespace named after the connecting
+    /// client (its `clientInfo.name`, slugified), in place of
+    /// [`namespace`](Self::namespace). The namespace policy still governs
+    /// overrides.
+    pub fn client_namespaces(mut self, enabled: bool) -> Self {
+        self.client_namespaces = enabled;
+        self
+    }
+
+    /// A namespace every caller may read, whatever the namespace policy,
+    /// but not write. Lookups in `memory_get` and `memory_query` fall back
+    /// to it.
+    pub fn shared_namespace(mut self, namespace: Option<String>) -> Self {
+        self.shared_namespace = namespace;
+        self
+    }
+
+    pub fn build(self) -> MemoryServer {
+        let mut tool_router = MemoryServer::tool_router();
+        #[cfg(feature = "llm")]
+        if self.llm.is_some() {
+            tool_router.merge(MemoryServer::nl_tool_router());
+        }
+        for tool in tool_router.list_all() {
+            let name = tool.name.as_ref();
+            let in_profile = match self.tool_profile {
+                ToolProfile::Full => true,
+                ToolProfile::Core => CORE_TOOLS.contains(&name),
+            };
+            if !in_profile || (self.read_only && WRITE_TOOLS.contains(&name)) {
+                tool_router.remove_route(name);
+            }
+        }
+
+        MemoryServer {
+            backend: Arc::new(Mutex::new(self.backend)),
+            default_namespace: self.namespace,
+            namespace_policy: self.namespace_policy,
+            #[cfg(feature = "llm")]
+            llm: self.llm,
+            #[cfg(feature = "llm")]
+            recall_cache: RecallCache::from_env(),
+            rate_limiter: self
+                .rate_limit
+                .map(|limit| Arc::new(RateLimiter::new(limit))),
+            limits: self.limits,
+            quotas: self.quotas,
+            tenants: self.tenants.map(Arc::new),
+            client_namespaces: self.client_namespaces,
+            shared_namespace: self.shared_namespace,
+            usage: self.usage,
+            registered_namespaces: Arc::default(),
+            draining: Arc::new(RwLock::new(false)),
+            tool_router,
+        }
+    }
+}
+
 // ============================================================================
 // MCP Server
 // ============================================================================
@@ -140,35 +656,445 @@ pub struct InitParams {
 pub struct MemoryServer {
     backend: Arc<Mutex<MemoryBackend>>,
     default_namespace: Option<String>,
+    namespace_policy: NamespacePolicy,
+    #[cfg(feature = "llm")]
+    llm: Option<Arc<dyn LlmClient>>,
+    /// Recalls shared by the tool calls, answered again only after a write.
+    #[cfg(feature = "llm")]
+    recall_cache: RecallCache,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    limits: Limits,
+    quotas: Quotas,
+    tenants: Option<Arc<TenantMap>>,
+    client_namespaces: bool,
+    shared_namespace: Option<String>,
+    usage: Option<Arc<UsageTracker>>,
+    /// Namespaces tool calls have used and that are in the registry
+    /// [`search_all_namespaces`](crate::search::search_all_namespaces)
+    /// reads.
+    registered_namespaces: Arc<std::sync::Mutex<HashSet<String>>>,
+    /// Set once shutdown starts. Tool calls hold a read guard while they
+    /// run, so taking the write guard waits for the calls in flight.
+    draining: Arc<RwLock<bool>>,
     tool_router: ToolRouter<Self>,
 }
 
 impl MemoryServer {
-    /// Create a new MCP memory server.
+    /// Create a new MCP memory server with default settings.
     pub fn new(backend: MemoryBackend, default_namespace: Option<String>) -> Self {
-        Self {
-            backend: Arc::new(Mutex::new(backend)),
-            default_namespace,
-            tool_router: Self::tool_router(),
+        Self::builder(backend).namespace(default_namespace).build()
+    }
+
+    /// Start configuring a server over `backend`.
+    pub fn builder(backend: MemoryBackend) -> MemoryServerBuilder {
+        MemoryServerBuilder {
+            backend,
+            namespace: None,
+            namespace_policy: NamespacePolicy::default(),
+            tool_profile: ToolProfile::default(),
+            read_only: false,
+            #[cfg(feature = "llm")]
+            llm: None,
+            rate_limit: None,
+            limits: Limits::load(),
+            quotas: Quotas::from_env(),
+            tenants: None,
+            client_namespaces: false,
+            shared_namespace: None,
+            usage: None,
         }
     }
 
+    /// Names of the tools this server exposes.
+    pub fn tool_names(&self) -> Vec<String> {
+        self.tool_router
+            .list_all()
+            .into_iter()
+            .map(|tool| tool.name.to_string())
+            .collect()
+    }
+
+    /// Persist the LLM token usage tallied since the last flush.
+    pub async fn flush_usage(&self) {
+        let Some(usage) = &self.usage else {
+            return;
+        };
+        let backend = self.backend.lock().await.clone();
+        if let Err(e) = usage.persist(&backend, "serve").await {
+            tracing::warn!("failed to record LLM usage: {e}");
+        }
+    }
+
+    /// Stop accepting tool calls, wait for the ones in flight to finish, and
+    /// flush LLM usage, category activity, and writes queued for the mirror.
+    /// Calls arriving afterwards are rejected.
+    pub async fn drain(&self) {
+        *self.draining.write().await = true;
+        self.flush_usage().await;
+        // Wait out any backend operation still holding the lock.
+        let backend = self.backend.lock().await;
+        if let Err(e) = backend.flush_activity().await {
+            tracing::warn!("failed to persist category activity: {e}");
+        }
+        backend.flush_mirror().await;
+    }
+
     /// Resolve a backend for the given namespace override, or use the default.
-    async fn resolve_backend(&self, namespace: &Option<String>) -> MemoryBackend {
+    ///
+    /// Every tool call starts here, so this also enforces the namespace
+    /// policy, tenant isolation, and the rate limit, and registers the
+    /// namespace the first time it is used.
+    async fn resolve_backend(&self, namespace: &Option<String>) -> Result<MemoryBackend, McpError> {
+        let backend = self.select_backend(namespace).await?;
+        if let Some(ns) = namespace_of(&backend.table_name)
+            && !self.registered_namespaces.lock().unwrap().contains(&ns)
+        {
+            match register_namespace(&backend, &ns).await {
+                Ok(()) => {
+                    self.registered_namespaces.lock().unwrap().insert(ns);
+                }
+                Err(e) => tracing::warn!("Failed to register namespace {ns}: {e}"),
+            }
+        }
+        Ok(backend)
+    }
+
+    /// The backend a tool call passing `namespace` works on.
+    async fn select_backend(&self, namespace: &Option<String>) -> Result<MemoryBackend, McpError> {
+        if let Some(limiter) = &self.rate_limiter
+            && !limiter.try_acquire()
+        {
+            return Err(McpError::invalid_request(
+                "Rate limit exceeded; retry later",
+                None,
+            ));
+        }
         let mut backend = self.backend.lock().await.clone();
-        if let Some(ns) = namespace.as_ref().or(self.default_namespace.as_ref()) {
-            backend.table_name = resolve_table_name(Some(ns));
+        let caller = CALLER.try_with(Clone::clone).unwrap_or_default();
+        if self.tenants.is_some() {
+            let tenant = caller
+                .tenant
+                .ok_or_else(|| McpError::invalid_request("Tool call has no tenant", None))?;
+            if let Some(ns) = namespace.as_deref()
+                && ns != tenant
+            {
+                return Err(McpError::invalid_params(
+                    format!("Namespace '{ns}' is not available to this tenant"),
+                    None,
+                ));
+            }
+            backend.table_name = namespace_table(&tenant)?;
+            return Ok(backend);
         }
-        backend
+        if let Some(shared) = self.shared_namespace.as_deref()
+            && namespace.as_deref() == Some(shared)
+        {
+            if caller.writes {
+                return Err(McpError::invalid_params(
+                    format!("Namespace '{shared}' is shared read-only"),
+                    None,
+                ));
+            }
+            backend.table_name = namespace_table(shared)?;
+            return Ok(backend);
+        }
+        let default = caller
+            .client_namespace
+            .as_deref()
+            .or(self.default_namespace.as_deref());
+        if let Some(ns) = self.effective_namespace(namespace, default)? {
+            backend.table_name = namespace_table(ns)?;
+        }
+        Ok(backend)
+    }
+
+    /// Apply the namespace policy to a per-call override of the caller's
+    /// `default` namespace.
+    fn effective_namespace<'a>(
+        &'a self,
+        namespace: &'a Option<String>,
+        default: Option<&'a str>,
+    ) -> Result<Option<&'a str>, McpError> {
+        let Some(ns) = namespace.as_deref() else {
+            return Ok(default);
+        };
+        if default == Some(ns) {
+            return Ok(Some(ns));
+        }
+        match &self.namespace_policy {
+            NamespacePolicy::Open => Ok(Some(ns)),
+            NamespacePolicy::Allowed(allowed) if allowed.iter().any(|a| a == ns) => Ok(Some(ns)),
+            NamespacePolicy::Locked | NamespacePolicy::Allowed(_) => Err(McpError::invalid_params(
+                format!("Namespace '{ns}' is not available on this server"),
+                None,
+            )),
+        }
+    }
+
+    /// Clamp a requested result count to the bounds configured for `tool`.
+    fn limit(&self, tool: &str, requested: Option<usize>) -> usize {
+        self.limits.resolve(tool, requested)
+    }
+
+    /// The shared namespace to fall back to when a lookup in `own`, made
+    /// without a namespace override, finds nothing. Tenants have none.
+    fn shared_overlay(
+        &self,
+        own: &MemoryBackend,
+        namespace: &Option<String>,
+    ) -> Option<MemoryBackend> {
+        let shared = self.shared_namespace.as_deref()?;
+        if namespace.is_some() || self.tenants.is_some() {
+            return None;
+        }
+        let table_name = namespace_table(shared).ok()?;
+        (own.table_name != table_name).then(|| {
+            let mut backend = own.clone();
+            backend.table_name = table_name;
+            backend
+        })
+    }
+
+    /// Start an operation log for a tool call, tagged with the effective namespace.
+    fn op_log(&self, operation: &'static str, namespace: &Option<String>) -> OpLog {
+        let caller = CALLER.try_with(Clone::clone).unwrap_or_default();
+        OpLog::start(
+            operation,
+            caller
+                .tenant
+                .as_deref()
+                .or(namespace.as_deref())
+                .or(caller.client_namespace.as_deref())
+                .or(self.default_namespace.as_deref()),
+        )
+    }
+
+    /// Ask the user making the current tool call to approve `summary`, if
+    /// their client supports elicitation (see [`crate::confirm`]).
+    async fn confirm(&self, summary: &str) -> Confirmation {
+        let peer = CALLER.try_with(|caller| caller.peer.clone()).ok().flatten();
+        crate::confirm::confirm(peer.as_ref(), summary).await
+    }
+
+    /// `memory_delete` with `prefix`: every item of the category whose key
+    /// starts with `params.key`, once the user approves the list.
+    async fn delete_prefix(
+        &self,
+        backend: &MemoryBackend,
+        params: &DeleteParams,
+        op: &mut OpLog,
+    ) -> Result<CallToolResult, McpError> {
+        if params.key.is_empty() {
+            return Err(McpError::invalid_params(
+                "A prefix delete needs a non-empty key prefix",
+                None,
+            ));
+        }
+        let items: Vec<Value> = backend
+            .query(&params.category, Some(&params.key), PREFIX_DELETE_LIMIT)
+            .await
+            .map_err(|e| err(e.to_string()))?
+            .into_iter()
+            .filter(|item| !is_chunk(item))
+            .collect();
+        let refs: Vec<String> = items.iter().filter_map(item_ref).collect();
+        if refs.is_empty() {
+            op.set_outcome(Outcome::Ok);
+            return Ok(CallToolResult::success(vec![Content::text(
+                serde_json::json!({ "deleted": refs }).to_string(),
+            )]));
+        }
+
+        let cascade = params.cascade.unwrap_or(false);
+        let summary = format!(
+            "Delete {} memories from {} whose keys start with \"{}\"{}: {}?",
+            refs.len(),
+            params.category,
+            params.key,
+            if cascade {
+                ", with their links and the references to them"
+            } else {
+                ""
+            },
+            describe_refs(&refs)
+        );
+        if let Confirmation::Refused(reason) = self.confirm(&summary).await {
+            return Ok(cancelled(op, reason, &summary));
+        }
+
+        if cascade {
+            let categories = schema_categories(backend)
+                .await
+                .map_err(|e| err(e.to_string()))?;
+            let keys: Vec<String> = items
+                .iter()
+                .filter_map(|item| item["key"].as_str().map(String::from))
+                .collect();
+            cascade_forget_many(backend, &params.category, &keys, &categories)
+                .await
+                .map_err(|e| err(e.to_string()))?;
+        } else {
+            for item in &items {
+                let key = item["key"].as_str().unwrap_or_default();
+                delete_chunks(backend, item)
+                    .await
+                    .map_err(|e| err(e.to_string()))?;
+                backend
+                    .delete_item(&params.category, key)
+                    .await
+                    .map_err(|e| err(e.to_string()))?;
+            }
+        }
+        op.set_outcome(Outcome::Ok);
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::json!({ "deleted": refs }).to_string(),
+        )]))
+    }
+
+    /// Identify the caller of `request`.
+    fn caller(
+        &self,
+        request: &CallToolRequestParam,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<Caller, McpError> {
+        let tenant = match &self.tenants {
+            Some(tenants) => Some(Self::caller_tenant(tenants, context)?),
+            None => None,
+        };
+        let client_namespace = if self.client_namespaces {
+            context
+                .peer
+                .peer_info()
+                .map(|info| slugify(&info.client_info.name))
+                .filter(|ns| !ns.is_empty())
+        } else {
+            None
+        };
+        Ok(Caller {
+            tenant,
+            client_namespace,
+            writes: WRITE_TOOLS.contains(&request.name.as_ref()),
+            peer: Some(context.peer.clone()),
+        })
+    }
+
+    /// Tenant owning the bearer token of the HTTP request behind `context`.
+    fn caller_tenant(
+        tenants: &TenantMap,
+        context: &RequestContext<RoleServer>,
+    ) -> Result<String, McpError> {
+        context
+            .extensions
+            .get::<axum::http::request::Parts>()
+            .and_then(|parts| parts.headers.get(axum::http::header::AUTHORIZATION))
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| tenants.resolve_bearer(value))
+            .map(String::from)
+            .ok_or_else(|| McpError::invalid_request("Missing or unknown tenant token", None))
     }
 }
 
+/// The table of `namespace`, rejecting names that are not valid namespaces.
+fn namespace_table(namespace: &str) -> Result<String, McpError> {
+    validate_namespace(namespace).map_err(|e| McpError::invalid_params(e, None))?;
+    Ok(resolve_table_name(Some(namespace)))
+}
+
+/// Categories with a schema.
+async fn schema_categories(backend: &MemoryBackend) -> Result<Vec<String>, MemoryError> {
+    SchemaManager::new(backend.clone())
+        .selected_categories(None)
+        .await
+}
+
+/// Result of a call the user did not approve: nothing was changed.
+fn cancelled(op: &mut OpLog, reason: &str, summary: &str) -> CallToolResult {
+    op.set_outcome(Outcome::Cancelled);
+    let result = serde_json::json!({
+        "cancelled": true,
+        "reason": reason,
+        "summary": summary,
+    });
+    CallToolResult::success(vec![Content::text(result.to_string())])
+}
+
 fn err(msg: impl Into<String>) -> McpError {
     McpError::internal_error(msg.into(), None)
 }
 
-#[tool_handler(router = self.tool_router)]
+/// `error` with the ID of the failed tool call in its data.
+fn with_request_id_data(mut error: McpError, request_id: &str) -> McpError {
+    match &mut error.data {
+        Some(Value::Object(data)) => {
+            data.insert("request_id".into(), request_id.into());
+        }
+        Some(_) => {}
+        None => error.data = Some(serde_json::json!({ "request_id": request_id })),
+    }
+    error
+}
+
+impl MemoryServer {
+    /// Whether the server was built with an LLM client.
+    pub fn has_llm(&self) -> bool {
+        #[cfg(feature = "llm")]
+        return self.llm.is_some();
+        #[cfg(not(feature = "llm"))]
+        false
+    }
+
+    /// The error for a call to a natural language tool on a server without
+    /// an LLM client, or `None` when `tool` is not one of those.
+    fn missing_capability(&self, tool: &str) -> Option<McpError> {
+        if !NL_TOOLS.contains(&tool) || self.has_llm() {
+            return None;
+        }
+        Some(McpError::invalid_request(
+            format!(
+                "{tool} needs an LLM client, and this server was started without one \
+                 (configure an LLM provider and pass --nl-tools); use the structured tools instead"
+            ),
+            Some(serde_json::json!({ "missing_capability": "llm" })),
+        ))
+    }
+}
+
 impl ServerHandler for MemoryServer {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let draining = self.draining.read().await;
+        if *draining {
+            return Err(McpError::invalid_request("Server is shutting down", None));
+        }
+        if !self.tool_router.has_route(&request.name)
+            && let Some(error) = self.missing_capability(&request.name)
+        {
+            return Err(error);
+        }
+        let caller = self.caller(&request, &context)?;
+        let tcc = ToolCallContext::new(self, request, context);
+        let request_id = new_request_id();
+        let call = CALLER.scope(caller, self.tool_router.call(tcc));
+        with_request_id(request_id.clone(), call)
+            .await
+            .map_err(|e| with_request_id_data(e, &request_id))
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        Ok(ListToolsResult {
+            tools: self.tool_router.list_all(),
+            meta: None,
+            next_cursor: None,
+        })
+    }
+
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::V_2024_11_05,
@@ -196,11 +1122,20 @@ impl MemoryServer {
         name = "memory_store",
         description = "Store a structured memory item with category, key, and typed attributes"
     )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_store", request_id = %request_id()),
+    )]
     async fn memory_store(
         &self,
         Parameters(params): Parameters<StoreParams>,
     ) -> Result<CallToolResult, McpError> {
-        let backend = self.resolve_backend(&params.namespace).await;
+        let mut op = self
+            .op_log("memory_store", &params.namespace)
+            .category(Some(&params.category))
+            .key(Some(&params.key));
+        let backend = self.resolve_backend(&params.namespace).await?;
 
         let mut doc = serde_json::json!({
             "category": params.category,
@@ -212,8 +1147,10 @@ impl MemoryServer {
             doc[k] = v.clone();
         }
 
-        // Auto-inject created_at.
-        doc["created_at"] = Value::String(chrono::Utc::now().to_rfc3339());
+        // Auto-inject created_at and updated_at.
+        let now = Value::String(chrono::Utc::now().to_rfc3339());
+        doc["created_at"] = now.clone();
+        doc[UPDATED_AT_ATTRIBUTE] = now;
 
         // Handle TTL: explicit > category default.
         if let Some(ref ttl_str) = params.ttl {
@@ -227,64 +1164,346 @@ impl MemoryServer {
             doc["expires_at"] = Value::String(compute_expires_at(INTERACTIONS_DEFAULT_TTL));
         }
 
+        // Oversized content: keep a preview on the parent, store the full text as chunks.
+        let chunks = match doc["content"].as_str() {
+            Some(content) if needs_chunking(content) => {
+                split_into_chunks(content, CHUNK_SIZE_CHARS)
+            }
+            _ => Vec::new(),
+        };
+        if !chunks.is_empty() {
+            doc["content"] = Value::String(chunks[0].clone());
+            doc["chunk_count"] = Value::from(chunks.len());
+        }
+
         backend
             .put_item(doc.clone())
             .await
             .map_err(|e| err(e.to_string()))?;
+        store_chunks(&backend, &doc, &chunks)
+            .await
+            .map_err(|e| err(e.to_string()))?;
+        let evicted = self
+            .quotas
+            .enforce_after_write(&backend, &doc)
+            .await
+            .map_err(|e| err(e.to_string()))?;
 
-        let result = serde_json::json!({
+        let mut result = serde_json::json!({
             "stored": format!("{}/{}", params.category, params.key),
         });
+        if !chunks.is_empty() {
+            result["chunks"] = Value::from(chunks.len());
+        }
+        if !evicted.is_empty() {
+            result["evicted"] = Value::from(evicted);
+        }
+        op.set_outcome(Outcome::Ok);
         Ok(CallToolResult::success(vec![Content::text(
             serde_json::to_string(&result).unwrap(),
         )]))
     }
 
+    /// Append text to a memory's content.
+    #[tool(
+        name = "memory_append",
+        description = "Append text to the content of an existing memory as a new entry behind a timestamped separator, without reading and rewriting the item; the rest of the item is left as it is"
+    )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_append", request_id = %request_id()),
+    )]
+    async fn memory_append(
+        &self,
+        Parameters(params): Parameters<AppendParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut op = self
+            .op_log("memory_append", &params.namespace)
+            .category(Some(&params.category))
+            .key(Some(&params.key));
+        let backend = self.resolve_backend(&params.namespace).await?;
+
+        let appended = append_content(
+            &backend,
+            &params.category,
+            &params.key,
+            &params.text,
+            chrono::Utc::now(),
+        )
+        .await
+        .map_err(|e| err(e.to_string()))?;
+        match appended {
+            Some(item) => {
+                let result = serde_json::json!({
+                    "appended": format!("{}/{}", params.category, params.key),
+                    "content_chars": item["content"].as_str().map_or(0, |c| c.chars().count()),
+                });
+                op.set_outcome(Outcome::Ok);
+                Ok(CallToolResult::success(vec![Content::text(
+                    serde_json::to_string(&result).unwrap(),
+                )]))
+            }
+            None => {
+                op.set_outcome(Outcome::NotFound);
+                Ok(CallToolResult::success(vec![Content::text(
+                    serde_json::to_string(&serde_json::json!({"error": "not_found"})).unwrap(),
+                )]))
+            }
+        }
+    }
+
+    /// Merge attributes into a memory.
+    #[tool(
+        name = "memory_update",
+        description = "Merge attributes into an existing memory without replacing it: attributes not given are kept, null removes one, and an object is merged into an object attribute. Returns the updated item. Use instead of memory_get followed by memory_store"
+    )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_update", request_id = %request_id()),
+    )]
+    async fn memory_update(
+        &self,
+        Parameters(params): Parameters<UpdateParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut op = self
+            .op_log("memory_update", &params.namespace)
+            .category(Some(&params.category))
+            .key(Some(&params.key));
+        let backend = self.resolve_backend(&params.namespace).await?;
+
+        let updated = backend
+            .update_item(&params.category, &params.key, &params.attributes)
+            .await
+            .map_err(|e| err(e.to_string()))?;
+        match updated {
+            Some(item) => {
+                op.set_outcome(Outcome::Ok);
+                Ok(CallToolResult::success(vec![Content::text(
+                    serde_json::to_string(&item).unwrap(),
+                )]))
+            }
+            None => {
+                op.set_outcome(Outcome::NotFound);
+                Ok(CallToolResult::success(vec![Content::text(
+                    serde_json::to_string(&serde_json::json!({"error": "not_found"})).unwrap(),
+                )]))
+            }
+        }
+    }
+
     /// Retrieve a specific memory by category and key.
     #[tool(
         name = "memory_get",
         description = "Retrieve a specific memory by category and key"
     )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_get", request_id = %request_id()),
+    )]
     async fn memory_get(
         &self,
         Parameters(params): Parameters<GetParams>,
     ) -> Result<CallToolResult, McpError> {
-        let backend = self.resolve_backend(&params.namespace).await;
+        let mut op = self
+            .op_log("memory_get", &params.namespace)
+            .category(Some(&params.category))
+            .key(Some(&params.key));
+        let backend = self.resolve_backend(&params.namespace).await?;
+        let shared = self.shared_overlay(&backend, &params.namespace);
 
-        let item = backend
+        let mut source = &backend;
+        let mut item = backend
             .get_item(&params.category, &params.key)
             .await
-            .map_err(|e| err(e.to_string()))?;
+            .map_err(|e| err(e.to_string()))?
+            .filter(|i| !is_expired(i));
+        if item.is_none()
+            && let Some(shared) = &shared
+        {
+            item = shared
+                .get_item(&params.category, &params.key)
+                .await
+                .map_err(|e| err(e.to_string()))?
+                .filter(|i| !is_expired(i));
+            source = shared;
+        }
+
+        let item = match item {
+            Some(i) => hydrate_chunks(source, vec![i])
+                .await
+                .map_err(|e| err(e.to_string()))?
+                .into_iter()
+                .next(),
+            None => None,
+        };
 
         match item {
-            Some(item) if !is_expired(&item) => Ok(CallToolResult::success(vec![Content::text(
-                serde_json::to_string_pretty(&item).unwrap(),
-            )])),
-            _ => Ok(CallToolResult::success(vec![Content::text(
-                serde_json::to_string(&serde_json::json!({"error": "not_found"})).unwrap(),
-            )])),
+            Some(item) => {
+                op.set_outcome(Outcome::Ok);
+                Ok(CallToolResult::success(vec![Content::text(
+                    serde_json::to_string_pretty(&item).unwrap(),
+                )]))
+            }
+            _ => {
+                op.set_outcome(Outcome::NotFound);
+                Ok(CallToolResult::success(vec![Content::text(
+                    serde_json::to_string(&serde_json::json!({"error": "not_found"})).unwrap(),
+                )]))
+            }
         }
     }
 
+    /// List the items referencing a memory.
+    #[tool(
+        name = "memory_backlinks",
+        description = "List every item that references a memory, through its `related` list or a link, e.g. before deleting or updating it"
+    )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_backlinks", request_id = %request_id()),
+    )]
+    async fn memory_backlinks(
+        &self,
+        Parameters(params): Parameters<BacklinksParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut op = self
+            .op_log("memory_backlinks", &params.namespace)
+            .category(Some(&params.category))
+            .key(Some(&params.key));
+        let backend = self.resolve_backend(&params.namespace).await?;
+        let categories = schema_categories(&backend)
+            .await
+            .map_err(|e| err(e.to_string()))?;
+
+        let target = format!("{}/{}", params.category, params.key);
+        let found = backlinks(&backend, &target, &categories, false)
+            .await
+            .map_err(|e| err(e.to_string()))?;
+
+        op.set_outcome(Outcome::Ok);
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&found).unwrap(),
+        )]))
+    }
+
+    /// Suggest memories related to an item, best first.
+    #[tool(
+        name = "memory_related",
+        description = "Suggest memories related to an item, ranked by references, links, shared tags, and shared attribute values, with the reasons for each"
+    )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_related", request_id = %request_id()),
+    )]
+    async fn memory_related(
+        &self,
+        Parameters(params): Parameters<RelatedParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut op = self
+            .op_log("memory_related", &params.namespace)
+            .category(Some(&params.category))
+            .key(Some(&params.key));
+        let backend = self.resolve_backend(&params.namespace).await?;
+        let categories = schema_categories(&backend)
+            .await
+            .map_err(|e| err(e.to_string()))?;
+
+        let target = format!("{}/{}", params.category, params.key);
+        let suggestions = suggest_related(
+            &backend,
+            &target,
+            &categories,
+            self.limit("memory_related", params.limit),
+            false,
+        )
+        .await
+        .map_err(|e| err(e.to_string()))?;
+
+        op.set_outcome(Outcome::Ok);
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&suggestions).unwrap(),
+        )]))
+    }
+
     /// Query memories in a category with optional prefix filtering.
     #[tool(
         name = "memory_query",
         description = "Query memories in a category, optionally filtering by key prefix"
     )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_query", request_id = %request_id()),
+    )]
     async fn memory_query(
         &self,
         Parameters(params): Parameters<QueryParams>,
     ) -> Result<CallToolResult, McpError> {
-        let backend = self.resolve_backend(&params.namespace).await;
-        let limit = params.limit.unwrap_or(20);
+        let mut op = self
+            .op_log("memory_query", &params.namespace)
+            .category(Some(&params.category))
+            .key(params.prefix.as_deref());
+        let backend = self.resolve_backend(&params.namespace).await?;
+        let limit = self.limit("memory_query", params.limit);
+        let sort = params
+            .sort
+            .as_deref()
+            .map(str::parse::<SortField>)
+            .transpose()
+            .map_err(err)?
+            .unwrap_or_default();
 
-        let items = backend
-            .query(&params.category, params.prefix.as_deref(), limit)
+        let items = query_sorted(
+            &backend,
+            &params.category,
+            params.prefix.as_deref(),
+            limit,
+            sort,
+            params.desc.unwrap_or(false),
+            false,
+        )
+        .await
+        .map_err(|e| err(e.to_string()))?;
+
+        let mut items = hydrate_chunks(&backend, items)
             .await
             .map_err(|e| err(e.to_string()))?;
 
-        let items = filter_expired(items);
+        // Fill the rest of the page from the shared namespace; own items
+        // shadow shared ones with the same key.
+        if items.len() < limit
+            && let Some(shared) = self.shared_overlay(&backend, &params.namespace)
+        {
+            let shared_items = query_sorted(
+                &shared,
+                &params.category,
+                params.prefix.as_deref(),
+                limit,
+                sort,
+                params.desc.unwrap_or(false),
+                false,
+            )
+            .await
+            .map_err(|e| err(e.to_string()))?;
+            let own: Vec<Value> = items.iter().map(|i| i["key"].clone()).collect();
+            let shared_items: Vec<Value> = shared_items
+                .into_iter()
+                .filter(|i| !own.contains(&i["key"]))
+                .take(limit - items.len())
+                .collect();
+            items.extend(
+                hydrate_chunks(&shared, shared_items)
+                    .await
+                    .map_err(|e| err(e.to_string()))?,
+            );
+        }
 
+        op.set_outcome(Outcome::Ok);
         Ok(CallToolResult::success(vec![Content::text(
             serde_json::to_string_pretty(&items).unwrap(),
         )]))
@@ -293,14 +1512,49 @@ impl MemoryServer {
     /// Delete a specific memory.
     #[tool(
         name = "memory_delete",
-        description = "Delete a specific memory by category and key"
+        description = "Delete a specific memory by category and key, or with prefix every memory in the category whose key starts with key; with cascade, also their links and the references other memories hold to them. Prefix deletes ask the user to confirm when the client supports elicitation"
+    )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_delete", request_id = %request_id()),
     )]
     async fn memory_delete(
         &self,
         Parameters(params): Parameters<DeleteParams>,
     ) -> Result<CallToolResult, McpError> {
-        let backend = self.resolve_backend(&params.namespace).await;
+        let mut op = self
+            .op_log("memory_delete", &params.namespace)
+            .category(Some(&params.category))
+            .key(Some(&params.key));
+        let backend = self.resolve_backend(&params.namespace).await?;
+
+        if params.prefix.unwrap_or(false) {
+            return self.delete_prefix(&backend, &params, &mut op).await;
+        }
+
+        if params.cascade.unwrap_or(false) {
+            let categories = schema_categories(&backend)
+                .await
+                .map_err(|e| err(e.to_string()))?;
+            let report = cascade_forget(&backend, &params.category, &params.key, &categories)
+                .await
+                .map_err(|e| err(e.to_string()))?;
+            op.set_outcome(Outcome::Ok);
+            return Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string(&report).unwrap(),
+            )]));
+        }
 
+        if let Some(item) = backend
+            .get_item(&params.category, &params.key)
+            .await
+            .map_err(|e| err(e.to_string()))?
+        {
+            delete_chunks(&backend, &item)
+                .await
+                .map_err(|e| err(e.to_string()))?;
+        }
         backend
             .delete_item(&params.category, &params.key)
             .await
@@ -309,6 +1563,7 @@ impl MemoryServer {
         let result = serde_json::json!({
             "deleted": format!("{}/{}", params.category, params.key),
         });
+        op.set_outcome(Outcome::Ok);
         Ok(CallToolResult::success(vec![Content::text(
             serde_json::to_string(&result).unwrap(),
         )]))
@@ -317,54 +1572,99 @@ impl MemoryServer {
     /// List categories or keys within a category.
     #[tool(
         name = "memory_list",
-        description = "List all categories, or list keys within a specific category"
+        description = "List all categories, or list keys within a specific category, a page at a time; pass next_cursor back as cursor to continue"
+    )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_list", request_id = %request_id()),
     )]
     async fn memory_list(
         &self,
         Parameters(params): Parameters<ListParams>,
     ) -> Result<CallToolResult, McpError> {
-        let backend = self.resolve_backend(&params.namespace).await;
+        let mut op = self
+            .op_log("memory_list", &params.namespace)
+            .category(params.category.as_deref());
+        let backend = self.resolve_backend(&params.namespace).await?;
 
+        let limit = self.limit("memory_list", params.limit);
+        let cursor = params.cursor.as_deref();
         if let Some(ref cat) = params.category {
-            let items = backend
-                .query(cat, None, 100)
+            let page = list_keys_page(&backend, cat, cursor, limit, false)
                 .await
                 .map_err(|e| err(e.to_string()))?;
-            let items = filter_expired(items);
-            let keys: Vec<&str> = items
-                .iter()
-                .filter_map(|item| item["key"].as_str())
-                .collect();
             let result = serde_json::json!({
                 "category": cat,
-                "keys": keys,
+                "keys": page.items,
+                "next_cursor": page.next_cursor,
             });
+            op.set_outcome(Outcome::Ok);
             Ok(CallToolResult::success(vec![Content::text(
                 serde_json::to_string_pretty(&result).unwrap(),
             )]))
         } else {
-            let keys = backend
-                .list_partition_keys(100)
+            let page = backend
+                .list_partition_keys_page(cursor, limit)
                 .await
                 .map_err(|e| err(e.to_string()))?;
-            let categories: Vec<&str> = keys.iter().filter_map(|v| v.as_str()).collect();
-            let result = serde_json::json!({ "categories": categories });
+            let result = serde_json::json!({
+                "categories": page.items,
+                "next_cursor": page.next_cursor,
+            });
+            op.set_outcome(Outcome::Ok);
             Ok(CallToolResult::success(vec![Content::text(
                 serde_json::to_string_pretty(&result).unwrap(),
             )]))
         }
     }
 
+    /// Return the preferences as directives, with a hash to detect changes.
+    #[tool(
+        name = "memory_preferences",
+        description = "Load the user's preferences as imperative directives to follow, optionally only one scope, in a stable order with a content hash; compare the hash with the one from the last load to tell whether preferences changed"
+    )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_preferences", request_id = %request_id()),
+    )]
+    async fn memory_preferences(
+        &self,
+        Parameters(params): Parameters<PreferencesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut op = self
+            .op_log("memory_preferences", &params.namespace)
+            .category(Some(PREFERENCES_CATEGORY));
+        let backend = self.resolve_backend(&params.namespace).await?;
+        let digest = preference_digest(&backend, params.scope.as_deref())
+            .await
+            .map_err(|e| err(e.to_string()))?;
+
+        op.set_outcome(Outcome::Ok);
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&digest).unwrap(),
+        )]))
+    }
+
     /// Show schema definitions for categories.
     #[tool(
         name = "memory_schema",
         description = "Show schema definitions for a category or list all schemas"
     )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_schema", request_id = %request_id()),
+    )]
     async fn memory_schema(
         &self,
         Parameters(params): Parameters<SchemaParams>,
     ) -> Result<CallToolResult, McpError> {
-        let backend = self.resolve_backend(&params.namespace).await;
+        let mut op = self
+            .op_log("memory_schema", &params.namespace)
+            .category(params.category.as_deref());
+        let backend = self.resolve_backend(&params.namespace).await?;
         let sm = SchemaManager::new(backend);
 
         if let Some(ref cat) = params.category {
@@ -380,14 +1680,18 @@ impl MemoryServer {
                             "required": a.required,
                         })).collect::<Vec<_>>(),
                     });
+                    op.set_outcome(Outcome::Ok);
                     Ok(CallToolResult::success(vec![Content::text(
                         serde_json::to_string_pretty(&result).unwrap(),
                     )]))
                 }
-                None => Ok(CallToolResult::success(vec![Content::text(
-                    serde_json::to_string(&serde_json::json!({"error": "schema_not_found"}))
-                        .unwrap(),
-                )])),
+                None => {
+                    op.set_outcome(Outcome::NotFound);
+                    Ok(CallToolResult::success(vec![Content::text(
+                        serde_json::to_string(&serde_json::json!({"error": "schema_not_found"}))
+                            .unwrap(),
+                    )]))
+                }
             }
         } else {
             let schemas = sm.list_schemas().await.map_err(|e| err(e.to_string()))?;
@@ -401,6 +1705,7 @@ impl MemoryServer {
                     })
                 })
                 .collect();
+            op.set_outcome(Outcome::Ok);
             Ok(CallToolResult::success(vec![Content::text(
                 serde_json::to_string_pretty(&result).unwrap(),
             )]))
@@ -412,11 +1717,20 @@ impl MemoryServer {
         name = "memory_promote",
         description = "Promote a memory to long-term (remove TTL), optionally move to a new category"
     )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_promote", request_id = %request_id()),
+    )]
     async fn memory_promote(
         &self,
         Parameters(params): Parameters<PromoteParams>,
     ) -> Result<CallToolResult, McpError> {
-        let backend = self.resolve_backend(&params.namespace).await;
+        let mut op = self
+            .op_log("memory_promote", &params.namespace)
+            .category(Some(&params.category))
+            .key(Some(&params.key));
+        let backend = self.resolve_backend(&params.namespace).await?;
 
         let item = backend
             .get_item(&params.category, &params.key)
@@ -426,6 +1740,7 @@ impl MemoryServer {
         let item = match item {
             Some(i) => i,
             None => {
+                op.set_outcome(Outcome::NotFound);
                 return Ok(CallToolResult::success(vec![Content::text(
                     serde_json::to_string(&serde_json::json!({"error": "not_found"})).unwrap(),
                 )]));
@@ -464,19 +1779,20 @@ impl MemoryServer {
                 "from": format!("{}/{}", params.category, params.key),
                 "to": format!("{}/{}", target_category, params.key),
             });
+            op.set_outcome(Outcome::Ok);
             Ok(CallToolResult::success(vec![Content::text(
                 serde_json::to_string(&result).unwrap(),
             )]))
         } else {
             // Same category: just remove expires_at.
-            let mut promoted = item.clone();
-            if let Some(obj) = promoted.as_object_mut() {
-                obj.remove("expires_at");
-            }
-            promoted["created_at"] = Value::String(chrono::Utc::now().to_rfc3339());
-
+            let mut patch = serde_json::Map::new();
+            patch.insert("expires_at".into(), Value::Null);
+            patch.insert(
+                "created_at".into(),
+                Value::String(chrono::Utc::now().to_rfc3339()),
+            );
             backend
-                .put_item(promoted)
+                .update_item(&params.category, &params.key, &patch)
                 .await
                 .map_err(|e| err(e.to_string()))?;
 
@@ -485,6 +1801,7 @@ impl MemoryServer {
                 "category": params.category,
                 "key": params.key,
             });
+            op.set_outcome(Outcome::Ok);
             Ok(CallToolResult::success(vec![Content::text(
                 serde_json::to_string(&result).unwrap(),
             )]))
@@ -496,40 +1813,53 @@ impl MemoryServer {
         name = "memory_prune",
         description = "Delete all expired memories, optionally within a specific category"
     )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_prune", request_id = %request_id()),
+    )]
     async fn memory_prune(
         &self,
         Parameters(params): Parameters<PruneParams>,
     ) -> Result<CallToolResult, McpError> {
-        let backend = self.resolve_backend(&params.namespace).await;
-        let sm = SchemaManager::new(backend.clone());
+        let mut op = self
+            .op_log("memory_prune", &params.namespace)
+            .category(params.category.as_deref());
+        let backend = self.resolve_backend(&params.namespace).await?;
 
-        let categories: Vec<String> = if let Some(ref cat) = params.category {
-            vec![cat.clone()]
-        } else {
-            let schemas = sm.list_schemas().await.map_err(|e| err(e.to_string()))?;
-            schemas.iter().map(|s| s.prefix.clone()).collect()
+        let categories = match params.category {
+            Some(ref cat) => vec![cat.clone()],
+            None => backend
+                .list_categories()
+                .await
+                .map_err(|e| err(e.to_string()))?,
         };
 
-        let mut total_pruned = 0usize;
-        for cat in &categories {
-            let items = backend
-                .query(cat, None, 1000)
-                .await
-                .map_err(|e| err(e.to_string()))?;
-            for item in &items {
-                if is_expired(item)
-                    && let Some(key) = item["key"].as_str()
-                {
-                    backend
-                        .delete_item(cat, key)
-                        .await
-                        .map_err(|e| err(e.to_string()))?;
-                    total_pruned += 1;
-                }
+        let expired = expired_counts(&backend, &categories)
+            .await
+            .map_err(|e| err(e.to_string()))?;
+        if !expired.is_empty() {
+            let breakdown: Vec<String> = expired
+                .iter()
+                .map(|(category, count)| format!("{category}: {count}"))
+                .collect();
+            let summary = format!(
+                "Permanently delete {} expired memories ({})?",
+                expired.values().sum::<usize>(),
+                breakdown.join(", ")
+            );
+            if let Confirmation::Refused(reason) = self.confirm(&summary).await {
+                return Ok(cancelled(&mut op, reason, &summary));
             }
         }
 
+        let total_pruned = backend
+            .prune_expired(&categories)
+            .await
+            .map_err(|e| err(e.to_string()))?;
+
         let result = serde_json::json!({ "pruned": total_pruned });
+        op.set_outcome(Outcome::Ok);
         Ok(CallToolResult::success(vec![Content::text(
             serde_json::to_string(&result).unwrap(),
         )]))
@@ -540,16 +1870,40 @@ impl MemoryServer {
         name = "memory_init",
         description = "Initialize predefined category schemas and indexes"
     )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_init", request_id = %request_id()),
+    )]
     async fn memory_init(
         &self,
         Parameters(params): Parameters<InitParams>,
     ) -> Result<CallToolResult, McpError> {
-        let backend = self.resolve_backend(&params.namespace).await;
+        let mut op = self.op_log("memory_init", &params.namespace);
+        let backend = self.resolve_backend(&params.namespace).await?;
 
         if params.force.unwrap_or(false) {
             let sm = SchemaManager::new(backend.clone());
+            let existing = schema_categories(&backend)
+                .await
+                .map_err(|e| err(e.to_string()))?;
+            let replaced: Vec<&str> = PREDEFINED_SCHEMAS
+                .iter()
+                .map(|s| s.name)
+                .filter(|name| existing.iter().any(|c| c == name))
+                .collect();
+            if !replaced.is_empty() {
+                let summary = format!(
+                    "Drop and recreate the schemas and indexes of {}? Items are kept, but \
+                     changes made to these schemas are lost.",
+                    replaced.join(", ")
+                );
+                if let Confirmation::Refused(reason) = self.confirm(&summary).await {
+                    return Ok(cancelled(&mut op, reason, &summary));
+                }
+            }
             for predefined in PREDEFINED_SCHEMAS {
-                let _ = backend.drop_schema(predefined.name).await;
+                let _ = sm.drop_schema(predefined.name).await;
                 let indexes = sm.list_indexes().await.unwrap_or_default();
                 for idx in &indexes {
                     if idx.partition_schema == predefined.name {
@@ -566,23 +1920,775 @@ impl MemoryServer {
 
         let names: Vec<&str> = PREDEFINED_SCHEMAS.iter().map(|s| s.name).collect();
         let result = serde_json::json!({ "initialized": names });
+        op.set_outcome(Outcome::Ok);
         Ok(CallToolResult::success(vec![Content::text(
             serde_json::to_string(&result).unwrap(),
         )]))
     }
+
+    /// Start a working session.
+    #[tool(
+        name = "memory_session_start",
+        description = "Start a working session: creates an active sessions item to record notable facts on until memory_session_end"
+    )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_session_start", request_id = %request_id()),
+    )]
+    async fn memory_session_start(
+        &self,
+        Parameters(params): Parameters<SessionStartParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut op = self
+            .op_log("memory_session_start", &params.namespace)
+            .category(Some(SESSION_CATEGORY))
+            .key(params.key.as_deref());
+        let backend = self.resolve_backend(&params.namespace).await?;
+
+        let details = serde_json::json!({
+            "project": params.project,
+            "branch": params.branch,
+            "goal": params.goal,
+        });
+        let item = start_session(&backend, params.key.as_deref(), &details)
+            .await
+            .map_err(|e| err(e.to_string()))?;
+
+        op.set_outcome(Outcome::Ok);
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&item).unwrap(),
+        )]))
+    }
+
+    /// Record a notable fact on an active session.
+    #[tool(
+        name = "memory_session_append",
+        description = "Record a notable fact, decision, or finding on an active session (default: the latest one)"
+    )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_session_append", request_id = %request_id()),
+    )]
+    async fn memory_session_append(
+        &self,
+        Parameters(params): Parameters<SessionAppendParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut op = self
+            .op_log("memory_session_append", &params.namespace)
+            .category(Some(SESSION_CATEGORY))
+            .key(params.key.as_deref());
+        let backend = self.resolve_backend(&params.namespace).await?;
+
+        let item = append_to_session(&backend, params.key.as_deref(), &params.fact)
+            .await
+            .map_err(|e| err(e.to_string()))?;
+
+        op.set_outcome(Outcome::Ok);
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&item).unwrap(),
+        )]))
+    }
+}
+
+#[cfg(feature = "llm")]
+#[tool_router(router = nl_tool_router)]
+impl MemoryServer {
+    /// Parse natural language into a structured memory and store it.
+    #[tool(
+        name = "memory_remember",
+        description = "Parse natural language into a structured memory and store it; category and key are inferred unless given"
+    )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_remember", request_id = %request_id()),
+    )]
+    async fn memory_remember(
+        &self,
+        Parameters(params): Parameters<RememberParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut op = self
+            .op_log("memory_remember", &params.namespace)
+            .category(params.category.as_deref())
+            .key(params.key.as_deref());
+        let store = self.store(&params.namespace).await?;
+        let ttl = params
+            .ttl
+            .as_deref()
+            .map(parse_ttl)
+            .transpose()
+            .map_err(err)?;
+
+        let options = RememberOptions {
+            category: params.category,
+            key: params.key,
+            ttl,
+        };
+        let item = store
+            .remember_with(options, &params.text)
+            .await
+            .map_err(|e| err(e.to_string()))?;
+
+        let result = serde_json::json!({
+            "stored": format!(
+                "{}/{}",
+                item["category"].as_str().unwrap_or_default(),
+                item["key"].as_str().unwrap_or_default()
+            ),
+            "item": item,
+        });
+        op.set_outcome(Outcome::Ok);
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&result).unwrap(),
+        )]))
+    }
+
+    /// Resolve a natural language question to matching memories.
+    #[tool(
+        name = "memory_recall",
+        description = "Find memories matching a natural language question. Returns {fallback_used, items}, plus {answer, follow_up} with answer: true, where follow_up lists queries and categories to check when the answer is partial; each item's _strategy names how it was found (index, scan, exact, fallback when nothing matched and the whole category was returned, broadened when another category was searched, or related)"
+    )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_recall", request_id = %request_id()),
+    )]
+    async fn memory_recall(
+        &self,
+        Parameters(params): Parameters<RecallParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut op = self.op_log("memory_recall", &params.namespace);
+        let mut store = self.store(&params.namespace).await?;
+        if params.related.unwrap_or(false) {
+            let default = RelatedBudget::default();
+            store = store.with_related_budget(RelatedBudget {
+                hops: params.hops.unwrap_or(default.hops),
+                max_items: params
+                    .related_limit
+                    .unwrap_or(default.max_items)
+                    .min(self.limits.max_for("memory_recall")),
+            });
+        }
+
+        let resolved = store
+            .resolve_recall(&params.query, self.limit("memory_recall", params.limit))
+            .await
+            .map_err(|e| err(e.to_string()))?;
+        let mut result = serde_json::json!({
+            "fallback_used": resolved.fallback,
+            "items": resolved.items,
+        });
+        let mut answer = None;
+        if params.answer.unwrap_or(false) {
+            let synthesized = store
+                .answer(&params.query, &resolved.items)
+                .await
+                .map_err(|e| err(e.to_string()))?;
+            let (text, follow_up) = synthesized
+                .map(|a| (Some(a.text), a.follow_up))
+                .unwrap_or_default();
+            result["answer"] = serde_json::json!(text);
+            result["follow_up"] = serde_json::json!(follow_up);
+            answer = text;
+        }
+        store
+            .log_recall(&params.query, &resolved, answer.as_deref())
+            .await;
+
+        op.set_outcome(Outcome::Ok);
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&result).unwrap(),
+        )]))
+    }
+
+    /// Condense a category into a single overview item.
+    #[tool(
+        name = "memory_summarize",
+        description = "Condense all items in a category into a single {category}/_overview item, replacing the previous overview"
+    )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_summarize", request_id = %request_id()),
+    )]
+    async fn memory_summarize(
+        &self,
+        Parameters(params): Parameters<SummarizeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut op = self
+            .op_log("memory_summarize", &params.namespace)
+            .category(Some(&params.category))
+            .key(Some(OVERVIEW_KEY));
+        let store = self.store(&params.namespace).await?;
+
+        let item = store
+            .summarize(&params.category)
+            .await
+            .map_err(|e| err(e.to_string()))?
+            .ok_or_else(|| err(format!("No items in '{}' to summarize", params.category)))?;
+
+        let result = serde_json::json!({
+            "stored": format!("{}/{OVERVIEW_KEY}", params.category),
+            "item": item,
+        });
+        op.set_outcome(Outcome::Ok);
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&result).unwrap(),
+        )]))
+    }
+
+    /// Suggest expiring short-term items worth keeping.
+    #[tool(
+        name = "memory_promote_suggest",
+        description = "Suggest scratchpad and sessions items nearing expiry that look durable, ranked by confidence, with a target category for each; promote them with memory_promote"
+    )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_promote_suggest", request_id = %request_id()),
+    )]
+    async fn memory_promote_suggest(
+        &self,
+        Parameters(params): Parameters<PromoteSuggestParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut op = self.op_log("memory_promote_suggest", &params.namespace);
+        let within = params
+            .within
+            .as_deref()
+            .map(parse_ttl)
+            .transpose()
+            .map_err(err)?
+            .unwrap_or(DEFAULT_PROMOTION_WINDOW);
+        let llm = self
+            .llm
+            .clone()
+            .ok_or_else(|| err("No LLM client configured"))?;
+        let backend = self.resolve_backend(&params.namespace).await?;
+
+        let suggestions = suggest_promotions(
+            &backend,
+            llm.as_ref(),
+            within,
+            self.limit("memory_promote_suggest", params.limit),
+        )
+        .await
+        .map_err(|e| err(e.to_string()))?;
+
+        op.set_outcome(Outcome::Ok);
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&suggestions).unwrap(),
+        )]))
+    }
+
+    /// End a session with an LLM-written summary.
+    #[tool(
+        name = "memory_session_end",
+        description = "End an active session (default: the latest one): summarizes its facts into the item and keeps it for 7 days"
+    )]
+    #[tracing::instrument(
+        name = "mcp.tool",
+        skip_all,
+        fields(tool = "memory_session_end", request_id = %request_id()),
+    )]
+    async fn memory_session_end(
+        &self,
+        Parameters(params): Parameters<SessionEndParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut op = self
+            .op_log("memory_session_end", &params.namespace)
+            .category(Some(SESSION_CATEGORY))
+            .key(params.key.as_deref());
+        let llm = self
+            .llm
+            .clone()
+            .ok_or_else(|| err("No LLM client configured"))?;
+        let backend = self.resolve_backend(&params.namespace).await?;
+
+        let item = end_session(&backend, llm.as_ref(), params.key.as_deref())
+            .await
+            .map_err(|e| err(e.to_string()))?;
+
+        op.set_outcome(Outcome::Ok);
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string_pretty(&item).unwrap(),
+        )]))
+    }
+}
+
+#[cfg(feature = "llm")]
+impl MemoryServer {
+    /// A [`MemoryStore`] over the resolved namespace, using the server's LLM.
+    async fn store(&self, namespace: &Option<String>) -> Result<MemoryStore, McpError> {
+        let llm = self
+            .llm
+            .clone()
+            .ok_or_else(|| err("No LLM client configured"))?;
+        let backend = self.resolve_backend(namespace).await?;
+        Ok(MemoryStore::new(backend)
+            .with_llm(llm)
+            .with_quotas(self.quotas.clone())
+            .with_recall_cache(self.recall_cache.clone()))
+    }
 }
 
 // ============================================================================
 // Entry Point
 // ============================================================================
 
-/// Run the MCP server on stdio transport.
-pub async fn run_mcp_server(
-    backend: MemoryBackend,
-    namespace: Option<String>,
+/// Run the MCP server on stdio transport until the client disconnects or
+/// the process receives SIGINT or SIGTERM.
+///
+/// On a signal the server drains (see [`MemoryServer::drain`]) before the
+/// transport is closed and the backend connection dropped; when the client
+/// disconnects, LLM usage is flushed. Usage is also flushed every
+/// [`USAGE_FLUSH_INTERVAL`] meanwhile.
+pub async fn run_mcp_server(server: MemoryServer) -> Result<(), Box<dyn std::error::Error>> {
+    let service = server
+        .clone()
+        .serve(stdio())
+        .await
+        .map_err(|e| e.to_string())?;
+    let cancel = service.cancellation_token();
+    let stopped = service.waiting();
+    tokio::pin!(stopped);
+    let flusher = spawn_usage_flusher(&server);
+
+    tokio::select! {
+        result = &mut stopped => {
+            flusher.abort();
+            server.flush_usage().await;
+            result.map_err(|e| e.to_string())?;
+            return Ok(());
+        }
+        signal = shutdown_signal() => {
+            signal?;
+            tracing::info!("shutdown signal received; draining tool calls");
+        }
+    }
+    flusher.abort();
+    server.drain().await;
+    cancel.cancel();
+    stopped.await.map_err(|e| e.to_string())?;
+    // The service has dropped its handle; this releases the backend.
+    drop(server);
+    Ok(())
+}
+
+/// Run the MCP server on rmcp's streamable HTTP transport at
+/// `http://ADDR/mcp` until the process receives SIGINT or SIGTERM.
+///
+/// Every client session is served by a handle on the same `server`, and tool
+/// calls see the headers of their HTTP request, which is how a server built
+/// with [`MemoryServerBuilder::tenants`] tells its callers apart. On a
+/// signal the server drains (see [`MemoryServer::drain`]) before the
+/// sessions are closed. Usage is flushed every [`USAGE_FLUSH_INTERVAL`].
+pub async fn run_mcp_http_server(
+    server: MemoryServer,
+    addr: SocketAddr,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let server = MemoryServer::new(backend, namespace);
-    let service = server.serve(stdio()).await.map_err(|e| e.to_string())?;
-    service.waiting().await.map_err(|e| e.to_string())?;
+    let config = StreamableHttpServerConfig::default();
+    let cancel = config.cancellation_token.clone();
+    let router = http_router(&server, config);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let flusher = spawn_usage_flusher(&server);
+
+    let result = axum::serve(listener, router)
+        .with_graceful_shutdown({
+            let server = server.clone();
+            async move {
+                if let Err(e) = shutdown_signal().await {
+                    tracing::warn!("failed to wait for a shutdown signal: {e}");
+                }
+                tracing::info!("shutdown signal received; draining tool calls");
+                server.drain().await;
+                cancel.cancel();
+            }
+        })
+        .await;
+    flusher.abort();
+    result?;
     Ok(())
 }
+
+/// `server` on the streamable HTTP transport, routed at `/mcp`.
+fn http_router(server: &MemoryServer, config: StreamableHttpServerConfig) -> axum::Router {
+    let server = server.clone();
+    let service = StreamableHttpService::new(
+        move || Ok(server.clone()),
+        LocalSessionManager::default().into(),
+        config,
+    );
+    axum::Router::new().nest_service("/mcp", service)
+}
+
+/// Flush the LLM usage of `server` every [`USAGE_FLUSH_INTERVAL`] until the
+/// returned task is aborted.
+fn spawn_usage_flusher(server: &MemoryServer) -> tokio::task::JoinHandle<()> {
+    let server = server.clone();
+    tokio::spawn(async move {
+        let mut ticks = tokio::time::interval(USAGE_FLUSH_INTERVAL);
+        ticks.tick().await;
+        loop {
+            ticks.tick().await;
+            server.flush_usage().await;
+        }
+    })
+}
+
+/// Resolve on the first SIGINT (Ctrl-C) or, on Unix, SIGTERM.
+async fn shutdown_signal() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        let mut term = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = term.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_backend() -> (tempfile::TempDir, MemoryBackend) {
+        use crate::TABLE_NAME;
+        use ferridyn_core::api::FerridynDB;
+        use ferridyn_core::types::KeyType;
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = FerridynDB::create(dir.path().join("test.db")).unwrap();
+        db.create_table(TABLE_NAME)
+            .partition_key("category", KeyType::String)
+            .sort_key("key", KeyType::String)
+            .execute()
+            .unwrap();
+        (dir, MemoryBackend::direct(db, TABLE_NAME.to_string()))
+    }
+
+    #[test]
+    fn test_default_server_exposes_structured_tools() {
+        let (_dir, backend) = test_backend();
+        let names = MemoryServer::new(backend, None).tool_names();
+        for tool in [
+            "memory_store",
+            "memory_schema",
+            "memory_prune",
+            "memory_init",
+            "memory_backlinks",
+            "memory_related",
+        ] {
+            assert!(names.iter().any(|n| n == tool), "missing {tool}");
+        }
+        assert!(!names.iter().any(|n| n == "memory_recall"));
+    }
+
+    #[tokio::test]
+    async fn test_drain_waits_for_calls_in_flight() {
+        let (_dir, backend) = test_backend();
+        let server = MemoryServer::new(backend, None);
+        let call = server.draining.clone().read_owned().await;
+
+        let drain = tokio::spawn({
+            let server = server.clone();
+            async move { server.drain().await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!drain.is_finished());
+
+        drop(call);
+        drain.await.unwrap();
+        assert!(*server.draining.read().await);
+    }
+
+    #[test]
+    fn test_nl_tools_without_llm_report_missing_capability() {
+        let (_dir, backend) = test_backend();
+        let server = MemoryServer::new(backend, None);
+        assert!(!server.has_llm());
+        let error = server.missing_capability("memory_recall").unwrap();
+        assert!(error.message.contains("needs an LLM client"));
+        assert_eq!(
+            error.data,
+            Some(serde_json::json!({ "missing_capability": "llm" }))
+        );
+        assert!(server.missing_capability("memory_store").is_none());
+        assert!(server.missing_capability("memory_bogus").is_none());
+    }
+
+    #[test]
+    fn test_core_read_only_profile_filters_tools() {
+        let (_dir, backend) = test_backend();
+        let server = MemoryServer::builder(backend)
+            .tool_profile(ToolProfile::Core)
+            .read_only(true)
+            .build();
+        let mut names = server.tool_names();
+        names.sort();
+        assert_eq!(names, ["memory_get", "memory_list", "memory_query"]);
+    }
+
+    #[tokio::test]
+    async fn test_namespace_policy() {
+        let (_dir, backend) = test_backend();
+        let server = MemoryServer::builder(backend.clone())
+            .namespace(Some("team".into()))
+            .namespace_policy(NamespacePolicy::Allowed(vec!["shared".into()]))
+            .build();
+
+        let default = server.resolve_backend(&None).await.unwrap();
+        assert_eq!(default.table_name, resolve_table_name(Some("team")));
+        let shared = server
+            .resolve_backend(&Some("shared".into()))
+            .await
+            .unwrap();
+        assert_eq!(shared.table_name, resolve_table_name(Some("shared")));
+        assert!(server.resolve_backend(&Some("other".into())).await.is_err());
+        // Namespaces used are registered for search across namespaces.
+        assert_eq!(
+            crate::search::known_namespaces(&backend).await.unwrap(),
+            ["shared", "team"]
+        );
+
+        let locked = MemoryServer::builder(backend.clone())
+            .namespace(Some("team".into()))
+            .namespace_policy(NamespacePolicy::Locked)
+            .build();
+        assert!(locked.resolve_backend(&Some("team".into())).await.is_ok());
+        assert!(
+            locked
+                .resolve_backend(&Some("shared".into()))
+                .await
+                .is_err()
+        );
+
+        // Names that cannot form a table are refused, even on an open server.
+        let open = MemoryServer::builder(backend).build();
+        for bad in ["a b", "../x", "x;drop"] {
+            let Err(err) = open.resolve_backend(&Some(bad.into())).await else {
+                panic!("namespace '{bad}' was accepted");
+            };
+            assert!(err.message.contains("letters, digits"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tenants_are_confined_to_their_namespace() {
+        let (_dir, backend) = test_backend();
+        let server = MemoryServer::builder(backend)
+            .tenants(TenantMap::parse("acme tok-a\nglobex tok-g").unwrap())
+            .build();
+
+        // Outside a tenant-scoped call, nothing is reachable.
+        assert!(server.resolve_backend(&None).await.is_err());
+
+        let acme = Caller {
+            tenant: Some("acme".into()),
+            ..Default::default()
+        };
+        CALLER
+            .scope(acme, async {
+                let own = server.resolve_backend(&None).await.unwrap();
+                assert_eq!(own.table_name, resolve_table_name(Some("acme")));
+                assert!(server.resolve_backend(&Some("acme".into())).await.is_ok());
+                assert!(
+                    server
+                        .resolve_backend(&Some("globex".into()))
+                        .await
+                        .is_err()
+                );
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_http_transport_resolves_tenant_from_bearer_token() {
+        use tower::ServiceExt as _;
+
+        let (_dir, backend) = test_backend();
+        let server = MemoryServer::builder(backend)
+            .tenants(TenantMap::parse("acme tok-a\nglobex tok-g").unwrap())
+            .build();
+        let router = http_router(
+            &server,
+            StreamableHttpServerConfig {
+                sse_keep_alive: None,
+                stateful_mode: false,
+                ..Default::default()
+            },
+        );
+        let call = |authorization: Option<&str>| {
+            let mut request = axum::http::Request::post("/mcp")
+                .header("content-type", "application/json")
+                .header("accept", "application/json, text/event-stream");
+            if let Some(value) = authorization {
+                request = request.header("authorization", value);
+            }
+            let body = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "tools/call",
+                "params": {
+                    "name": "memory_get",
+                    "arguments": {"category": "notes", "key": "a", "namespace": "globex"},
+                },
+            });
+            let request = request.body(axum::body::Body::from(body.to_string()));
+            let router = router.clone();
+            async move {
+                let response = router.oneshot(request.unwrap()).await.unwrap();
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                String::from_utf8(bytes.to_vec()).unwrap()
+            }
+        };
+
+        assert!(call(None).await.contains("Missing or unknown tenant token"));
+        assert!(
+            call(Some("Bearer tok-x"))
+                .await
+                .contains("Missing or unknown tenant token")
+        );
+        // The token places the call in acme's namespace, so globex is refused.
+        assert!(
+            call(Some("Bearer tok-a"))
+                .await
+                .contains("Namespace 'globex' is not available to this tenant")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_prefix_without_elicitation() {
+        let backend = MemoryBackend::in_memory(crate::TABLE_NAME.to_string());
+        let server = MemoryServer::new(backend.clone(), None);
+        for key in ["draft-a", "draft-b", "final"] {
+            backend
+                .put_item(serde_json::json!({"category": "notes", "key": key}))
+                .await
+                .unwrap();
+        }
+
+        // Outside a client connection there is no one to ask; the call proceeds.
+        let result = server
+            .memory_delete(Parameters(DeleteParams {
+                category: "notes".into(),
+                key: "draft-".into(),
+                cascade: None,
+                prefix: Some(true),
+                namespace: None,
+            }))
+            .await
+            .unwrap();
+        let text = serde_json::to_string(&result.content).unwrap();
+        assert!(text.contains("notes/draft-a") && text.contains("notes/draft-b"));
+        let left = backend.query("notes", None, 10).await.unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0]["key"], "final");
+
+        assert!(
+            server
+                .memory_delete(Parameters(DeleteParams {
+                    category: "notes".into(),
+                    key: String::new(),
+                    cascade: None,
+                    prefix: Some(true),
+                    namespace: None,
+                }))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_namespaces_with_shared_overlay() {
+        let backend = MemoryBackend::in_memory(crate::TABLE_NAME.to_string());
+        let server = MemoryServer::builder(backend.clone())
+            .client_namespaces(true)
+            .shared_namespace(Some("team".into()))
+            .namespace_policy(NamespacePolicy::Locked)
+            .build();
+        let mut shared = backend.clone();
+        shared.table_name = resolve_table_name(Some("team"));
+        shared
+            .put_item(serde_json::json!({"category": "notes", "key": "style", "content": "tabs"}))
+            .await
+            .unwrap();
+
+        let cursor = Caller {
+            client_namespace: Some("cursor".into()),
+            ..Default::default()
+        };
+        CALLER
+            .scope(cursor.clone(), async {
+                let own = server.resolve_backend(&None).await.unwrap();
+                assert_eq!(own.table_name, resolve_table_name(Some("cursor")));
+                assert!(server.resolve_backend(&Some("other".into())).await.is_err());
+                assert!(server.resolve_backend(&Some("team".into())).await.is_ok());
+
+                let result = server
+                    .memory_get(Parameters(GetParams {
+                        category: "notes".into(),
+                        key: "style".into(),
+                        namespace: None,
+                    }))
+                    .await
+                    .unwrap();
+                let text = serde_json::to_string(&result.content).unwrap();
+                assert!(text.contains("tabs"), "{text}");
+            })
+            .await;
+
+        let writer = Caller {
+            writes: true,
+            ..cursor
+        };
+        CALLER
+            .scope(writer, async {
+                assert!(server.resolve_backend(&Some("team".into())).await.is_err());
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_rejects_excess_calls() {
+        let (_dir, backend) = test_backend();
+        let server = MemoryServer::builder(backend)
+            .rate_limit(RateLimit {
+                max_calls: 2,
+                per: Duration::from_secs(3600),
+            })
+            .build();
+        assert!(server.resolve_backend(&None).await.is_ok());
+        assert!(server.resolve_backend(&None).await.is_ok());
+        assert!(server.resolve_backend(&None).await.is_err());
+    }
+
+    #[test]
+    fn test_limit_defaults_and_clamps() {
+        let (_dir, backend) = test_backend();
+        let server = MemoryServer::builder(backend)
+            .default_limit(5)
+            .max_limit(50)
+            .build();
+        assert_eq!(server.limit("memory_query", None), 5);
+        assert_eq!(server.limit("memory_query", Some(10)), 10);
+        assert_eq!(server.limit("memory_query", Some(500)), 50);
+
+        let mut limits = Limits::default();
+        limits.apply_spec("memory_list.max=10");
+        let (_dir, backend) = test_backend();
+        let server = MemoryServer::builder(backend).limits(limits).build();
+        assert_eq!(server.limit("memory_list", Some(50)), 10);
+        assert_eq!(server.limit("memory_query", Some(50)), 50);
+    }
+}
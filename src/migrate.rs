@@ -0,0 +1,201 @@
+//! Copying a memory table to another table name.
+//!
+//! Renaming a project changes its namespace, and with it the table its
+//! memories live in, which leaves them behind. [`migrate_table`] copies
+//! schemas (validation setting included), indexes, and every item (expired
+//! items, chunks, and internal partitions too) to the target table, then
+//! verifies that each item arrived unchanged. Items are read a page at a
+//! time with no cap; a table with too many categories to list fails
+//! instead (see [`MIRROR_SCAN_LIMIT`](crate::mirror::MIRROR_SCAN_LIMIT)).
+//! [`empty_table`] clears the
+//! source afterwards: ferridyn-server has no operation to drop a table, so
+//! it deletes the items, indexes, and schemas and leaves an empty table
+//! behind.
+
+use serde::Serialize;
+
+use crate::backend::MemoryBackend;
+use crate::error::MemoryError;
+use crate::mirror::{category_names, create_schema_like, items_by_key};
+
+/// What [`migrate_table`] copied.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MigrationReport {
+    pub from: String,
+    pub to: String,
+    pub schemas_created: usize,
+    pub indexes_created: usize,
+    pub items_copied: usize,
+    /// `category/key` of items missing from the target or different there
+    /// after copying. Empty when verification passed.
+    pub mismatched: Vec<String>,
+    /// Whether the source was emptied afterwards.
+    pub source_emptied: bool,
+}
+
+impl MigrationReport {
+    /// Whether every item arrived unchanged.
+    pub fn verified(&self) -> bool {
+        self.mismatched.is_empty()
+    }
+}
+
+/// Copy schemas, indexes, and items of `source` to `target`, calling
+/// `progress` with each category and its item count once it is copied.
+/// Schemas and indexes the target already has are kept as they are.
+pub async fn migrate_table(
+    source: &MemoryBackend,
+    target: &MemoryBackend,
+    mut progress: impl FnMut(&str, usize),
+) -> Result<MigrationReport, MemoryError> {
+    if source.table_name == target.table_name {
+        return Err(MemoryError::InvalidParams(format!(
+            "source and target are both '{}'",
+            source.table_name
+        )));
+    }
+    let mut report = MigrationReport {
+        from: source.table_name.clone(),
+        to: target.table_name.clone(),
+        ..Default::default()
+    };
+
+    let existing: Vec<String> = target
+        .list_schemas()
+        .await?
+        .into_iter()
+        .map(|s| s.prefix)
+        .collect();
+    for schema in source.list_schemas().await? {
+        if !existing.contains(&schema.prefix) {
+            create_schema_like(target, &schema, schema.validate).await?;
+            report.schemas_created += 1;
+        }
+    }
+    let existing: Vec<String> = target
+        .list_indexes()
+        .await?
+        .into_iter()
+        .map(|i| i.name)
+        .collect();
+    for index in source.list_indexes().await? {
+        if !existing.contains(&index.name) {
+            target
+                .create_index(
+                    &index.name,
+                    &index.partition_schema,
+                    &index.index_key_name,
+                    &index.index_key_type,
+                )
+                .await?;
+            report.indexes_created += 1;
+        }
+    }
+
+    let categories = category_names(source).await?;
+    for category in &categories {
+        let items = items_by_key(source, category).await?;
+        for item in items.values() {
+            target.put_system_item(item.clone()).await?;
+        }
+        report.items_copied += items.len();
+        progress(category, items.len());
+    }
+
+    for category in &categories {
+        let copied = items_by_key(target, category).await?;
+        let mut mismatched: Vec<String> = items_by_key(source, category)
+            .await?
+            .into_iter()
+            .filter(|(key, item)| copied.get(key) != Some(item))
+            .map(|(key, _)| format!("{category}/{key}"))
+            .collect();
+        mismatched.sort();
+        report.mismatched.extend(mismatched);
+    }
+    Ok(report)
+}
+
+/// Delete every item, index, and schema of `backend`'s table.
+pub async fn empty_table(backend: &MemoryBackend) -> Result<(), MemoryError> {
+    for category in category_names(backend).await? {
+        for key in items_by_key(backend, &category).await?.keys() {
+            backend.delete_item(&category, key).await?;
+        }
+    }
+    for index in backend.list_indexes().await? {
+        backend.drop_index(&index.name).await?;
+    }
+    for schema in backend.list_schemas().await? {
+        backend.drop_schema(&schema.prefix).await?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::links::LINKS_CATEGORY;
+    use crate::{AttributeDefInput, resolve_table_name};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_migrate_table_and_empty_source() {
+        let source = MemoryBackend::in_memory(resolve_table_name(Some("old")));
+        let attrs = [AttributeDefInput {
+            name: "decision".into(),
+            attr_type: "STRING".into(),
+            required: false,
+        }];
+        source
+            .create_schema("decisions", Some("Decisions"), &attrs, true)
+            .await
+            .unwrap();
+        source
+            .create_index("decisions_decision", "decisions", "decision", "STRING")
+            .await
+            .unwrap();
+        source
+            .put_item(json!({"category": "decisions", "key": "auth", "decision": "JWT"}))
+            .await
+            .unwrap();
+        source
+            .put_item(json!({"category": "contacts", "key": "toby", "name": "Toby"}))
+            .await
+            .unwrap();
+        source
+            .link_items("decisions/auth", "contacts/toby", "decided-by")
+            .await
+            .unwrap();
+        let mut target = source.clone();
+        target.table_name = resolve_table_name(Some("new"));
+
+        let mut seen = Vec::new();
+        let report = migrate_table(&source, &target, |category, count| {
+            seen.push((category.to_string(), count))
+        })
+        .await
+        .unwrap();
+        assert!(report.verified());
+        assert_eq!(report.items_copied, 4);
+        assert!(seen.contains(&(LINKS_CATEGORY.to_string(), 2)));
+        assert_eq!((report.schemas_created, report.indexes_created), (1, 1));
+        assert!(target.describe_schema("decisions").await.unwrap().validate);
+        assert_eq!(
+            target.get_item("decisions", "auth").await.unwrap(),
+            source.get_item("decisions", "auth").await.unwrap()
+        );
+        assert_eq!(target.get_links("contacts/toby").await.unwrap().len(), 1);
+
+        empty_table(&source).await.unwrap();
+        assert!(source.list_partition_keys(10).await.unwrap().is_empty());
+        assert!(source.list_schemas().await.unwrap().is_empty());
+        assert!(target.get_item("contacts", "toby").await.unwrap().is_some());
+
+        assert!(migrate_table(&target, &target, |_, _| {}).await.is_err());
+    }
+}
@@ -0,0 +1,251 @@
+//! Mirror backend: a second ferridyn-server holding a copy of every write.
+//!
+//! With `FERRIDYN_MEMORY_MIRROR_SOCKET` set, [`connect_backend`] attaches a
+//! mirror to the backend ([`MemoryBackend::with_mirror`]), and each item
+//! write or delete that succeeds on the primary is replayed on the mirror in
+//! the background. Writes made while the mirror was down are lost to it;
+//! [`sync_mirror`] (`fmemory mirror-sync`) reconciles the two afterwards by
+//! copying schemas, indexes, and items the mirror lacks or holds stale and
+//! deleting items the primary no longer has.
+//!
+//! [`connect_backend`]: crate::store::connect_backend
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::backend::MemoryBackend;
+use crate::error::MemoryError;
+use crate::{AttributeDefInput, PartitionSchemaInfo};
+
+/// Environment variable naming the socket of the mirror server.
+pub const MIRROR_SOCKET_ENV: &str = "FERRIDYN_MEMORY_MIRROR_SOCKET";
+
+/// Maximum number of categories compared by [`sync_mirror`] and copied by
+/// [`migrate_table`](crate::migrate::migrate_table). ferridyn-server cannot
+/// list categories a page at a time, so a table with more fails instead of
+/// being copied in part.
+pub const MIRROR_SCAN_LIMIT: usize = 100_000;
+
+/// Items read per request while paging through a category.
+pub const MIRROR_PAGE_SIZE: usize = 1_000;
+
+/// What [`sync_mirror`] changed on the mirror.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SyncReport {
+    pub schemas_created: usize,
+    pub indexes_created: usize,
+    pub items_copied: usize,
+    pub items_deleted: usize,
+}
+
+/// Bring `mirror` in line with `primary`: create missing schemas (without
+/// validation) and indexes, copy items that are missing or differ, and
+/// delete items absent from the primary. Schemas and indexes the primary no
+/// longer has are left in place.
+pub async fn sync_mirror(
+    primary: &MemoryBackend,
+    mirror: &MemoryBackend,
+) -> Result<SyncReport, MemoryError> {
+    let mut report = SyncReport::default();
+
+    let mirrored: Vec<String> = mirror
+        .list_schemas()
+        .await?
+        .into_iter()
+        .map(|s| s.prefix)
+        .collect();
+    for schema in primary.list_schemas().await? {
+        if !mirrored.contains(&schema.prefix) {
+            create_schema_like(mirror, &schema, false).await?;
+            report.schemas_created += 1;
+        }
+    }
+    let mirrored: Vec<String> = mirror
+        .list_indexes()
+        .await?
+        .into_iter()
+        .map(|i| i.name)
+        .collect();
+    for index in primary.list_indexes().await? {
+        if !mirrored.contains(&index.name) {
+            mirror
+                .create_index(
+                    &index.name,
+                    &index.partition_schema,
+                    &index.index_key_name,
+                    &index.index_key_type,
+                )
+                .await?;
+            report.indexes_created += 1;
+        }
+    }
+
+    let mut categories = category_names(primary).await?;
+    for category in category_names(mirror).await? {
+        if !categories.contains(&category) {
+            categories.push(category);
+        }
+    }
+    for category in &categories {
+        let source = items_by_key(primary, category).await?;
+        let mut target = items_by_key(mirror, category).await?;
+        for (key, item) in source {
+            if target.remove(&key).as_ref() != Some(&item) {
+                mirror.put_system_item(item).await?;
+                report.items_copied += 1;
+            }
+        }
+        for key in target.keys() {
+            mirror.delete_item(category, key).await?;
+            report.items_deleted += 1;
+        }
+    }
+    Ok(report)
+}
+
+/// Create a schema on `backend` with the attributes and description of
+/// `schema`.
+pub(crate) async fn create_schema_like(
+    backend: &MemoryBackend,
+    schema: &PartitionSchemaInfo,
+    validate: bool,
+) -> Result<(), MemoryError> {
+    let attrs: Vec<AttributeDefInput> = schema
+        .attributes
+        .iter()
+        .map(|a| AttributeDefInput {
+            name: a.name.clone(),
+            attr_type: a.attr_type.clone(),
+            required: a.required,
+        })
+        .collect();
+    backend
+        .create_schema(&schema.prefix, Some(&schema.description), &attrs, validate)
+        .await
+}
+
+/// Every category of `backend`, or an error if there are
+/// [`MIRROR_SCAN_LIMIT`] or more.
+pub(crate) async fn category_names(backend: &MemoryBackend) -> Result<Vec<String>, MemoryError> {
+    let names = backend.list_partition_keys(MIRROR_SCAN_LIMIT).await?;
+    if names.len() >= MIRROR_SCAN_LIMIT {
+        return Err(MemoryError::InvalidParams(format!(
+            "table '{}' has {MIRROR_SCAN_LIMIT} or more categories, more than can be listed",
+            backend.table_name
+        )));
+    }
+    Ok(names
+        .into_iter()
+        .filter_map(|v| v.as_str().map(String::from))
+        .collect())
+}
+
+/// Every item of `category`, by key, read [`MIRROR_PAGE_SIZE`] at a time.
+pub(crate) async fn items_by_key(
+    backend: &MemoryBackend,
+    category: &str,
+) -> Result<HashMap<String, Value>, MemoryError> {
+    let mut items = HashMap::new();
+    let mut after: Option<String> = None;
+    loop {
+        let page = backend
+            .query_after(category, after.as_deref(), MIRROR_PAGE_SIZE)
+            .await?;
+        let full = page.len() == MIRROR_PAGE_SIZE;
+        after = page
+            .last()
+            .and_then(|item| item["key"].as_str())
+            .map(String::from);
+        items.extend(
+            page.into_iter()
+                .filter_map(|item| Some((item["key"].as_str()?.to_string(), item))),
+        );
+        if !full || after.is_none() {
+            return Ok(items);
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_writes_are_mirrored() {
+        let mirror = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        let primary = MemoryBackend::in_memory(TABLE_NAME.to_string()).with_mirror(mirror.clone());
+
+        primary
+            .put_item(json!({"category": "notes", "key": "a", "content": "one"}))
+            .await
+            .unwrap();
+        primary
+            .put_item(json!({"category": "notes", "key": "b", "content": "two"}))
+            .await
+            .unwrap();
+        primary.delete_item("notes", "a").await.unwrap();
+        primary.flush_mirror().await;
+
+        let items = mirror.query("notes", None, 10).await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["content"], "two");
+    }
+
+    #[tokio::test]
+    async fn test_items_by_key_reads_past_one_page() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for n in 0..MIRROR_PAGE_SIZE * 2 + 1 {
+            backend
+                .put_item(json!({"category": "notes", "key": format!("{n:05}")}))
+                .await
+                .unwrap();
+        }
+        let items = items_by_key(&backend, "notes").await.unwrap();
+        assert_eq!(items.len(), MIRROR_PAGE_SIZE * 2 + 1);
+    }
+
+    #[tokio::test]
+    async fn test_sync_mirror_reconciles() {
+        let primary = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        let mirror = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        primary.ensure_predefined_schemas().await.unwrap();
+        primary
+            .put_item(json!({"category": "notes", "key": "a", "content": "new"}))
+            .await
+            .unwrap();
+        mirror
+            .put_item(json!({"category": "notes", "key": "a", "content": "old"}))
+            .await
+            .unwrap();
+        mirror
+            .put_item(json!({"category": "notes", "key": "gone", "content": "x"}))
+            .await
+            .unwrap();
+
+        let report = sync_mirror(&primary, &mirror).await.unwrap();
+        assert!(report.schemas_created > 0);
+        assert_eq!(
+            report.indexes_created,
+            primary.list_indexes().await.unwrap().len()
+        );
+        assert_eq!(report.items_deleted, 1);
+        assert!(report.items_copied >= 1);
+        let item = mirror.get_item("notes", "a").await.unwrap().unwrap();
+        assert_eq!(item["content"], "new");
+        assert!(mirror.get_item("notes", "gone").await.unwrap().is_none());
+
+        // A second pass has nothing left to do.
+        assert_eq!(
+            sync_mirror(&primary, &mirror).await.unwrap(),
+            SyncReport::default()
+        );
+    }
+}
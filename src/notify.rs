@@ -0,0 +1,502 @@
+//! Notifications for upcoming events and expiring items.
+//!
+//! `fmemory watch` periodically scans memory for `events` items starting soon
+//! and items whose `expires_at` is approaching, and delivers each one to the
+//! configured channels (webhooks); `fmemory remind` does the same for events
+//! with native desktop notifications. Deliveries are recorded in the
+//! [`NOTIFICATIONS_CATEGORY`] partition so a restarted watcher does not
+//! notify twice; the records expire a day after the item fell due.
+
+#[cfg(feature = "llm")]
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::SYSTEM_CATEGORIES;
+use crate::backend::MemoryBackend;
+use crate::chunk::is_chunk;
+use crate::error::MemoryError;
+#[cfg(feature = "llm")]
+use crate::ingest::sha256_hex;
+use crate::ttl::is_expired;
+
+/// Partition recording delivered notifications.
+pub const NOTIFICATIONS_CATEGORY: &str = "_notifications";
+
+/// Category scanned for upcoming events.
+pub const EVENTS_CATEGORY: &str = "events";
+
+/// How long delivery records outlive the moment the item fell due.
+const DELIVERY_RECORD_RETENTION: Duration = Duration::days(1);
+
+/// Maximum number of items scanned per category.
+const SCAN_LIMIT: usize = 10_000;
+
+/// Maximum number of categories scanned for expiring items.
+const MAX_CATEGORIES: usize = 1000;
+
+/// Timeout for webhook deliveries.
+#[cfg(feature = "llm")]
+const WEBHOOK_TIMEOUT: StdDuration = StdDuration::from_secs(10);
+
+/// Errors that can occur while delivering a notification.
+#[derive(Debug, Error)]
+pub enum NotifyError {
+    /// HTTP or network error occurred.
+    #[error("HTTP error: {0}")]
+    Http(String),
+
+    /// The desktop notification could not be shown.
+    #[error("Desktop notification error: {0}")]
+    Desktop(String),
+}
+
+/// Why an item is being notified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DueKind {
+    /// An `events` item starts soon.
+    EventSoon,
+    /// An item's `expires_at` is approaching.
+    Expiring,
+}
+
+impl DueKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DueKind::EventSoon => "event_due",
+            DueKind::Expiring => "expiring",
+        }
+    }
+}
+
+/// An item that has fallen within a notification window.
+#[derive(Debug, Clone)]
+pub struct DueItem {
+    pub kind: DueKind,
+    /// Event start or expiry time.
+    pub due_at: DateTime<Utc>,
+    pub item: Value,
+}
+
+impl DueItem {
+    /// Key of the delivery record for this item on `channel`.
+    ///
+    /// Includes the due time, so a rescheduled event notifies again.
+    pub fn delivery_key(&self, channel: &str) -> String {
+        format!(
+            "{channel}:{}:{}/{}:{}",
+            self.kind.as_str(),
+            self.item["category"].as_str().unwrap_or_default(),
+            self.item["key"].as_str().unwrap_or_default(),
+            self.due_at.to_rfc3339(),
+        )
+    }
+
+    /// JSON body posted to webhooks.
+    pub fn payload(&self) -> Value {
+        serde_json::json!({
+            "kind": self.kind.as_str(),
+            "due_at": self.due_at.to_rfc3339(),
+            "item": self.item,
+        })
+    }
+}
+
+// ============================================================================
+// Detection
+// ============================================================================
+
+/// Start time of an `events` item from its `date` (`YYYY-MM-DD`) and optional
+/// `time` (`15:00`, `3:30 PM`, `3pm`), interpreted in local time. Events
+/// without a recognizable time start at midnight.
+pub fn event_start(item: &Value) -> Option<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(item["date"].as_str()?.trim(), "%Y-%m-%d").ok()?;
+    let time = item["time"]
+        .as_str()
+        .and_then(parse_time)
+        .unwrap_or(NaiveTime::MIN);
+    Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    let mut text = text.trim().to_uppercase().replace(' ', "");
+    // chrono needs minutes: "3PM" -> "3:00PM".
+    if (text.ends_with("AM") || text.ends_with("PM")) && !text.contains(':') {
+        text.insert_str(text.len() - 2, ":00");
+    }
+    ["%H:%M", "%H:%M:%S", "%I:%M%p"]
+        .iter()
+        .find_map(|fmt| NaiveTime::parse_from_str(&text, fmt).ok())
+}
+
+/// Events starting within `(now, now + within]`.
+pub fn due_events(items: &[Value], now: DateTime<Utc>, within: Duration) -> Vec<DueItem> {
+    items
+        .iter()
+        .filter_map(|item| {
+            let start = event_start(item)?;
+            (start > now && start <= now + within).then(|| DueItem {
+                kind: DueKind::EventSoon,
+                due_at: start,
+                item: item.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Items whose `expires_at` falls within `(now, now + within]`. Chunks share
+/// their parent's expiry and are left out, so a document notifies once.
+pub fn expiring_items(items: &[Value], now: DateTime<Utc>, within: Duration) -> Vec<DueItem> {
+    items
+        .iter()
+        .filter(|item| !is_chunk(item))
+        .filter_map(|item| {
+            let expires = DateTime::parse_from_rfc3339(item["expires_at"].as_str()?)
+                .ok()?
+                .with_timezone(&Utc);
+            (expires > now && expires <= now + within).then(|| DueItem {
+                kind: DueKind::Expiring,
+                due_at: expires,
+                item: item.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Scan memory for due events and expiring items. A `None` window disables
+/// that kind of notification.
+pub async fn scan_due(
+    backend: &MemoryBackend,
+    events_within: Option<Duration>,
+    expiring_within: Option<Duration>,
+    now: DateTime<Utc>,
+) -> Result<Vec<DueItem>, MemoryError> {
+    let mut due = Vec::new();
+
+    if let Some(within) = events_within {
+        let events = backend.query(EVENTS_CATEGORY, None, SCAN_LIMIT).await?;
+        let live: Vec<Value> = events.into_iter().filter(|i| !is_expired(i)).collect();
+        due.extend(due_events(&live, now, within));
+    }
+
+    if let Some(within) = expiring_within {
+        for partition in backend.list_partition_keys(MAX_CATEGORIES).await? {
+            let Some(category) = partition.as_str() else {
+                continue;
+            };
+            if SYSTEM_CATEGORIES.contains(&category) {
+                continue;
+            }
+            let items = backend.query(category, None, SCAN_LIMIT).await?;
+            due.extend(expiring_items(&items, now, within));
+        }
+    }
+
+    due.sort_by_key(|d| d.due_at);
+    Ok(due)
+}
+
+// ============================================================================
+// Delivery
+// ============================================================================
+
+/// Whether `due` was already delivered on `channel`.
+pub async fn already_delivered(
+    backend: &MemoryBackend,
+    due: &DueItem,
+    channel: &str,
+) -> Result<bool, MemoryError> {
+    Ok(backend
+        .get_item(NOTIFICATIONS_CATEGORY, &due.delivery_key(channel))
+        .await?
+        .is_some())
+}
+
+/// Record that `due` was delivered on `channel`.
+pub async fn record_delivery(
+    backend: &MemoryBackend,
+    due: &DueItem,
+    channel: &str,
+) -> Result<(), MemoryError> {
+    let now = Utc::now();
+    backend
+        .put_system_item(serde_json::json!({
+            "category": NOTIFICATIONS_CATEGORY,
+            "key": due.delivery_key(channel),
+            "channel": channel,
+            "kind": due.kind.as_str(),
+            "target": format!(
+                "{}/{}",
+                due.item["category"].as_str().unwrap_or_default(),
+                due.item["key"].as_str().unwrap_or_default()
+            ),
+            "created_at": now.to_rfc3339(),
+            "expires_at": (due.due_at.max(now) + DELIVERY_RECORD_RETENTION).to_rfc3339(),
+        }))
+        .await
+}
+
+/// Where a notification is delivered.
+#[derive(Clone)]
+pub enum Channel {
+    /// POST the [`DueItem::payload`] as JSON to a URL.
+    #[cfg(feature = "llm")]
+    Webhook {
+        client: reqwest::Client,
+        url: String,
+    },
+    /// Show a native desktop notification.
+    Desktop,
+}
+
+impl Channel {
+    /// Stable channel name used in delivery records. A webhook is named by
+    /// the SHA-256 of its URL: URLs often carry a secret token, and their
+    /// `?`, `&`, and `%` are not allowed in keys.
+    pub fn name(&self) -> String {
+        match self {
+            #[cfg(feature = "llm")]
+            Channel::Webhook { url, .. } => format!("webhook:{}", sha256_hex(url.as_bytes())),
+            Channel::Desktop => "desktop".to_string(),
+        }
+    }
+
+    /// Deliver one notification.
+    pub async fn deliver(&self, due: &DueItem) -> Result<(), NotifyError> {
+        match self {
+            #[cfg(feature = "llm")]
+            Channel::Webhook { client, url } => post_webhook(client, url, due).await,
+            Channel::Desktop => {
+                let (summary, body) = desktop_message(due);
+                tokio::task::spawn_blocking(move || show_desktop_notification(&summary, &body))
+                    .await
+                    .map_err(|e| NotifyError::Desktop(e.to_string()))?
+            }
+        }
+    }
+}
+
+/// Outcome of delivering a batch of due items.
+#[derive(Debug, Default)]
+pub struct DeliveryReport {
+    /// Notifications delivered and recorded.
+    pub delivered: usize,
+    /// Failed deliveries, as `"<channel>: <error>"`. These are not recorded,
+    /// so the next scan retries them.
+    pub failures: Vec<String>,
+}
+
+/// Deliver each due item on each channel, skipping ones already delivered.
+pub async fn deliver_due(
+    backend: &MemoryBackend,
+    due: &[DueItem],
+    channels: &[Channel],
+) -> Result<DeliveryReport, MemoryError> {
+    let mut report = DeliveryReport::default();
+    for item in due {
+        for channel in channels {
+            let name = channel.name();
+            if already_delivered(backend, item, &name).await? {
+                continue;
+            }
+            match channel.deliver(item).await {
+                Ok(()) => {
+                    record_delivery(backend, item, &name).await?;
+                    report.delivered += 1;
+                }
+                Err(e) => report.failures.push(format!("{name}: {e}")),
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// HTTP client for webhook deliveries.
+#[cfg(feature = "llm")]
+pub fn webhook_client() -> Result<reqwest::Client, NotifyError> {
+    reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .user_agent(concat!("fmemory/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| NotifyError::Http(e.to_string()))
+}
+
+/// POST `due`'s payload as JSON to `url`.
+#[cfg(feature = "llm")]
+pub async fn post_webhook(
+    client: &reqwest::Client,
+    url: &str,
+    due: &DueItem,
+) -> Result<(), NotifyError> {
+    client
+        .post(url)
+        .json(&due.payload())
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| NotifyError::Http(e.to_string()))?;
+    Ok(())
+}
+
+/// Summary and body of a desktop notification: the event title (or key),
+/// then its local start time and location.
+pub fn desktop_message(due: &DueItem) -> (String, String) {
+    let item = &due.item;
+    let summary = item["title"]
+        .as_str()
+        .or_else(|| item["key"].as_str())
+        .unwrap_or("Reminder")
+        .to_string();
+
+    let when = due.due_at.with_timezone(&Local);
+    let mut body = match due.kind {
+        DueKind::EventSoon => format!("Starts {}", when.format("%a %b %-d, %H:%M")),
+        DueKind::Expiring => format!("Expires {}", when.format("%a %b %-d, %H:%M")),
+    };
+    if let Some(location) = item["location"].as_str().filter(|l| !l.is_empty()) {
+        body.push_str(&format!(" at {location}"));
+    }
+    (summary, body)
+}
+
+fn show_desktop_notification(summary: &str, body: &str) -> Result<(), NotifyError> {
+    notify_rust::Notification::new()
+        .appname("fmemory")
+        .summary(summary)
+        .body(body)
+        .show()
+        .map(|_| ())
+        .map_err(|e| NotifyError::Desktop(e.to_string()))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn local(date: &str, time: &str) -> DateTime<Utc> {
+        let naive = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap()
+            .and_time(NaiveTime::parse_from_str(time, "%H:%M").unwrap());
+        Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_event_start_time_formats() {
+        let expected = local("2026-03-10", "15:30");
+        for time in ["15:30", "3:30 PM", "3:30pm"] {
+            let item = json!({"date": "2026-03-10", "time": time});
+            assert_eq!(event_start(&item), Some(expected), "time {time}");
+        }
+        let item = json!({"date": "2026-03-10", "time": "3pm"});
+        assert_eq!(event_start(&item), Some(local("2026-03-10", "15:00")));
+
+        let all_day = json!({"date": "2026-03-10"});
+        assert_eq!(event_start(&all_day), Some(local("2026-03-10", "00:00")));
+        assert_eq!(event_start(&json!({"date": "next tuesday"})), None);
+    }
+
+    #[test]
+    fn test_due_events_window() {
+        let now = local("2026-03-10", "12:00");
+        let items = vec![
+            json!({"category": "events", "key": "past", "date": "2026-03-10", "time": "09:00"}),
+            json!({"category": "events", "key": "soon", "date": "2026-03-10", "time": "15:00"}),
+            json!({"category": "events", "key": "later", "date": "2026-03-12", "time": "15:00"}),
+        ];
+        let due = due_events(&items, now, Duration::hours(24));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].item["key"], "soon");
+        assert_eq!(due[0].kind, DueKind::EventSoon);
+    }
+
+    #[test]
+    fn test_expiring_items_window() {
+        let now = Utc::now();
+        let items = vec![
+            json!({"key": "soon", "expires_at": (now + Duration::minutes(30)).to_rfc3339()}),
+            json!({"key": "expired", "expires_at": (now - Duration::minutes(1)).to_rfc3339()}),
+            json!({"key": "ltm"}),
+            json!({"key": "soon#2", "chunk_of": "soon",
+                "expires_at": (now + Duration::minutes(30)).to_rfc3339()}),
+        ];
+        let due = expiring_items(&items, now, Duration::hours(1));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].item["key"], "soon");
+    }
+
+    #[tokio::test]
+    async fn test_delivery_records_dedupe() {
+        use crate::TABLE_NAME;
+        use ferridyn_core::api::FerridynDB;
+        use ferridyn_core::types::KeyType;
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = FerridynDB::create(dir.path().join("test.db")).unwrap();
+        db.create_table(TABLE_NAME)
+            .partition_key("category", KeyType::String)
+            .sort_key("key", KeyType::String)
+            .execute()
+            .unwrap();
+        let backend = MemoryBackend::direct(db, TABLE_NAME.to_string());
+
+        let due = DueItem {
+            kind: DueKind::Expiring,
+            due_at: Utc::now() + Duration::minutes(5),
+            item: json!({"category": "scratchpad", "key": "todo"}),
+        };
+        assert!(!already_delivered(&backend, &due, "webhook").await.unwrap());
+        record_delivery(&backend, &due, "webhook").await.unwrap();
+        assert!(already_delivered(&backend, &due, "webhook").await.unwrap());
+        assert!(!already_delivered(&backend, &due, "desktop").await.unwrap());
+
+        #[cfg(feature = "llm")]
+        {
+            let webhook = Channel::Webhook {
+                client: reqwest::Client::new(),
+                url: "https://hooks.example.com/in?token=s3cr%2Ft&x=1".into(),
+            };
+            let name = webhook.name();
+            assert!(!name.contains("s3cr"));
+            record_delivery(&backend, &due, &name).await.unwrap();
+            assert!(already_delivered(&backend, &due, &name).await.unwrap());
+        }
+
+        // Delivery records are not themselves reported as expiring.
+        let found = scan_due(&backend, None, Some(Duration::days(2)), Utc::now())
+            .await
+            .unwrap();
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn test_desktop_message() {
+        let due = DueItem {
+            kind: DueKind::EventSoon,
+            due_at: local("2026-03-10", "15:00"),
+            item: json!({"key": "dentist", "title": "Dentist", "location": "Main St"}),
+        };
+        let (summary, body) = desktop_message(&due);
+        assert_eq!(summary, "Dentist");
+        assert_eq!(body, "Starts Tue Mar 10, 15:00 at Main St");
+
+        let untitled = DueItem {
+            item: json!({"key": "standup"}),
+            ..due
+        };
+        assert_eq!(desktop_message(&untitled).0, "standup");
+    }
+}
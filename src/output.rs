@@ -0,0 +1,264 @@
+//! JSON envelope and exit codes of the `fmemory` CLI.
+//!
+//! With `--json`, a command prints one JSON document to stdout, the same shape
+//! for every command: `{"ok": true, "data": ..., "error": null}` on success
+//! and `{"ok": false, "data": null, "error": {"code": ..., "message": ...}}` on
+//! failure. `data` holds what the command produced; a failure that still
+//! produced something (some facts of a batch stored) carries it too.
+//!
+//! The exit code follows the class of the error ([`ErrorCode`]), with or
+//! without `--json`, so scripts can branch on it without parsing output. A
+//! failure also names the request ID of the invocation (`error.request_id`),
+//! matching the `request_id` of its log events.
+
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::error::MemoryError;
+use crate::llm::LlmError;
+use crate::store::StoreError;
+
+/// Class of a CLI failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// Any failure not covered below.
+    General,
+    /// Invalid command-line usage: a missing or conflicting argument.
+    Usage,
+    /// The requested item or schema does not exist.
+    NotFound,
+    /// Input that was read but is invalid: a bad TTL, attributes JSON, or an
+    /// unknown category.
+    InvalidInput,
+    /// The LLM is not configured or a call to it failed.
+    Llm,
+    /// The database could not be reached or an operation on it failed.
+    Backend,
+    /// Part of a batch failed; the rest succeeded.
+    PartialFailure,
+}
+
+impl ErrorCode {
+    /// The `error.code` string of the JSON envelope.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::General => "error",
+            Self::Usage => "usage",
+            Self::NotFound => "not_found",
+            Self::InvalidInput => "invalid_input",
+            Self::Llm => "llm",
+            Self::Backend => "backend",
+            Self::PartialFailure => "partial_failure",
+        }
+    }
+
+    /// The process exit code. `2` matches the exit code of argument errors
+    /// reported by the parser.
+    pub fn exit_code(self) -> u8 {
+        match self {
+            Self::General => 1,
+            Self::Usage => 2,
+            Self::NotFound => 3,
+            Self::InvalidInput => 4,
+            Self::Llm => 5,
+            Self::Backend => 6,
+            Self::PartialFailure => 7,
+        }
+    }
+
+    /// Class of `err`, looking through the error types of this crate.
+    /// Anything else (plain messages included) is [`ErrorCode::General`].
+    pub fn of(err: &(dyn std::error::Error + 'static)) -> Self {
+        if let Some(err) = err.downcast_ref::<CliError>() {
+            return err.code;
+        }
+        if let Some(err) = err.downcast_ref::<StoreError>() {
+            return match err {
+                StoreError::Connect(_) => Self::Backend,
+                StoreError::Memory(err) => Self::of(err),
+                StoreError::Llm(_) | StoreError::NoLlm => Self::Llm,
+                StoreError::UnknownCategory { .. } => Self::InvalidInput,
+                StoreError::NoSchemas => Self::NotFound,
+            };
+        }
+        if let Some(err) = err.downcast_ref::<MemoryError>() {
+            return match err {
+                MemoryError::InvalidParams(_) => Self::InvalidInput,
+                MemoryError::Internal(_) => Self::General,
+                MemoryError::Server(_)
+                | MemoryError::ServerUnavailable(_)
+                | MemoryError::Schema(_)
+                | MemoryError::Index(_)
+                | MemoryError::Timeout(_)
+                | MemoryError::Unsupported(_) => Self::Backend,
+            };
+        }
+        if err.is::<LlmError>() {
+            return Self::Llm;
+        }
+        if let Some(err) = err.downcast_ref::<std::io::Error>()
+            && err.kind() == std::io::ErrorKind::NotFound
+        {
+            return Self::NotFound;
+        }
+        Self::General
+    }
+}
+
+/// A CLI failure: its class, the message shown to the user, any data
+/// produced before it, and the ID of the request that failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub data: Option<Value>,
+    pub request_id: Option<String>,
+}
+
+impl CliError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+            request_id: None,
+        }
+    }
+
+    pub fn usage(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Usage, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidInput, message)
+    }
+
+    /// `err` prefixed with what was being done ("Summarization failed:
+    /// ..."), keeping its class.
+    pub fn context(what: &str, err: &(dyn std::error::Error + 'static)) -> Self {
+        Self::new(ErrorCode::of(err), format!("{what}: {err}"))
+    }
+
+    /// Attach what the command produced before failing.
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Attach the ID of the request that failed.
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    /// `err` as a `CliError`, classified by [`ErrorCode::of`].
+    pub fn from_error(err: &(dyn std::error::Error + 'static)) -> Self {
+        match err.downcast_ref::<CliError>() {
+            Some(err) => err.clone(),
+            None => Self::new(ErrorCode::of(err), err.to_string()),
+        }
+    }
+
+    /// The failure envelope.
+    pub fn envelope(&self) -> Value {
+        let mut envelope = json!({
+            "ok": false,
+            "data": self.data,
+            "error": {
+                "code": self.code.as_str(),
+                "message": self.message,
+            },
+        });
+        if let Some(request_id) = &self.request_id {
+            envelope["error"]["request_id"] = json!(request_id);
+        }
+        envelope
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// The success envelope around `data`.
+pub fn success(data: &impl Serialize) -> Result<Value, serde_json::Error> {
+    Ok(json!({
+        "ok": true,
+        "data": serde_json::to_value(data)?,
+        "error": null,
+    }))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelopes() {
+        assert_eq!(
+            success(&json!({"key": "toby"})).unwrap(),
+            json!({"ok": true, "data": {"key": "toby"}, "error": null})
+        );
+        let err = CliError::not_found("No memory found for contacts/toby");
+        assert_eq!(
+            err.envelope(),
+            json!({
+                "ok": false,
+                "data": null,
+                "error": {"code": "not_found", "message": "No memory found for contacts/toby"},
+            })
+        );
+        let partial = CliError::new(ErrorCode::PartialFailure, "Stored 1 of 2 facts")
+            .with_data(json!([{"key": "a"}]));
+        assert_eq!(partial.envelope()["data"], json!([{"key": "a"}]));
+        assert_eq!(partial.code.exit_code(), 7);
+        let traced = err.with_request_id("1f-0");
+        assert_eq!(traced.envelope()["error"]["request_id"], "1f-0");
+    }
+
+    #[test]
+    fn test_error_classes() {
+        let boxed: Box<dyn std::error::Error> = Box::new(StoreError::Memory(
+            MemoryError::ServerUnavailable("gone".into()),
+        ));
+        assert_eq!(
+            CliError::from_error(boxed.as_ref()).code,
+            ErrorCode::Backend
+        );
+
+        let boxed: Box<dyn std::error::Error> = Box::new(StoreError::UnknownCategory {
+            category: "pets".into(),
+            available: "contacts".into(),
+        });
+        assert_eq!(ErrorCode::of(boxed.as_ref()), ErrorCode::InvalidInput);
+
+        let boxed: Box<dyn std::error::Error> = "something broke".into();
+        let err = CliError::from_error(boxed.as_ref());
+        assert_eq!(
+            (err.code, err.message.as_str()),
+            (ErrorCode::General, "something broke")
+        );
+
+        let err = CliError::context("Summarization failed", &LlmError::EmptyResponse);
+        assert_eq!(err.code, ErrorCode::Llm);
+        assert_eq!(
+            err.message,
+            "Summarization failed: Model returned empty response"
+        );
+        assert_eq!(CliError::usage("x").code.exit_code(), 2);
+    }
+}
@@ -0,0 +1,144 @@
+//! Cursor-based pagination for category and key listings.
+//!
+//! A cursor is the last name of the previous page, and the next page holds
+//! the names after it. A listing is complete when [`Page::next_cursor`] is
+//! `None`. Key pages are read from the server starting after the cursor
+//! ([`MemoryBackend::query_after`]). FerridynDB cannot start a listing of
+//! partition keys after a given one, so category pages are cut client-side
+//! from a sorted scan of at most [`PAGE_SCAN_LIMIT`] categories.
+
+use serde::Serialize;
+
+use crate::backend::MemoryBackend;
+use crate::error::MemoryError;
+use crate::ttl::is_expired;
+
+/// Maximum number of categories read to cut one page.
+pub const PAGE_SCAN_LIMIT: usize = 100_000;
+
+/// One page of a listing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Page {
+    pub items: Vec<String>,
+    /// Cursor for the following page, or `None` on the last one.
+    pub next_cursor: Option<String>,
+}
+
+/// The page of up to `limit` names following `cursor` (from the start
+/// without one). `names` must be sorted ascending.
+pub fn paginate(
+    names: impl IntoIterator<Item = String>,
+    cursor: Option<&str>,
+    limit: usize,
+) -> Page {
+    let limit = limit.max(1);
+    let mut items: Vec<String> = names
+        .into_iter()
+        .filter(|name| cursor.is_none_or(|cursor| name.as_str() > cursor))
+        .take(limit + 1)
+        .collect();
+    let next_cursor = if items.len() > limit {
+        items.truncate(limit);
+        items.last().cloned()
+    } else {
+        None
+    };
+    Page { items, next_cursor }
+}
+
+/// A page of the keys in `category`, expired items left out unless
+/// `include_expired`.
+///
+/// Items are read `limit + 1` at a time from the cursor on, and again past
+/// the last one read while expired items leave the page short.
+pub async fn list_keys_page(
+    backend: &MemoryBackend,
+    category: &str,
+    cursor: Option<&str>,
+    limit: usize,
+    include_expired: bool,
+) -> Result<Page, MemoryError> {
+    let limit = limit.max(1);
+    let mut keys = Vec::new();
+    let mut after = cursor.map(String::from);
+    while keys.len() <= limit {
+        let batch = backend
+            .query_after(category, after.as_deref(), limit + 1)
+            .await?;
+        let exhausted = batch.len() <= limit;
+        after = batch
+            .last()
+            .and_then(|item| item["key"].as_str())
+            .map(String::from);
+        keys.extend(
+            batch
+                .iter()
+                .filter(|item| include_expired || !is_expired(item))
+                .filter_map(|item| item["key"].as_str().map(String::from)),
+        );
+        if exhausted || after.is_none() {
+            break;
+        }
+    }
+    Ok(paginate(keys, None, limit))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_paginate() {
+        let all = names(&["a", "b", "c", "d", "e"]);
+        let first = paginate(all.clone(), None, 2);
+        assert_eq!(first.items, names(&["a", "b"]));
+        assert_eq!(first.next_cursor.as_deref(), Some("b"));
+
+        let second = paginate(all.clone(), first.next_cursor.as_deref(), 2);
+        assert_eq!(second.items, names(&["c", "d"]));
+        let last = paginate(all.clone(), second.next_cursor.as_deref(), 2);
+        assert_eq!(last.items, names(&["e"]));
+        assert_eq!(last.next_cursor, None);
+
+        // A page that ends exactly at the end has no cursor.
+        assert_eq!(paginate(all.clone(), Some("c"), 2).next_cursor, None);
+        assert!(paginate(all, Some("z"), 2).items.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_keys_page() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for item in [
+            json!({"category": "notes", "key": "a"}),
+            json!({"category": "notes", "key": "b", "expires_at": "2020-01-01T00:00:00Z"}),
+            json!({"category": "notes", "key": "c"}),
+            json!({"category": "notes", "key": "d"}),
+        ] {
+            backend.put_item(item).await.unwrap();
+        }
+        let page = list_keys_page(&backend, "notes", None, 2, false)
+            .await
+            .unwrap();
+        assert_eq!(page.items, names(&["a", "c"]));
+        let page = list_keys_page(&backend, "notes", page.next_cursor.as_deref(), 2, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            page,
+            Page {
+                items: names(&["d"]),
+                next_cursor: None
+            }
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Preferences as a digest of directives for agents.
+//!
+//! [`preference_digest`] turns the live `preferences` items, optionally of
+//! one `scope`, into imperative directives ("Prefer tabs.", "Never
+//! force-push to main.") in a stable order: by scope, unscoped last, then by key.
+//! The digest carries a SHA-256 `hash` of its text, so an agent that kept the
+//! hash from its last load can tell whether anything changed without
+//! comparing directives.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::backend::MemoryBackend;
+use crate::chunk::is_chunk;
+use crate::context::PREFERENCES_CATEGORY;
+use crate::error::MemoryError;
+use crate::ingest::sha256_hex;
+use crate::ttl::is_expired;
+
+/// Attribute naming the area a preference applies to.
+pub const SCOPE_ATTRIBUTE: &str = "scope";
+
+/// Maximum number of preferences scanned by [`preference_digest`].
+const PREFERENCES_SCAN_LIMIT: usize = 10_000;
+
+/// Leading words of a preference mapped to the imperative they become.
+const IMPERATIVES: &[(&str, &str)] = &[
+    ("prefers", "Prefer"),
+    ("prefer", "Prefer"),
+    ("likes", "Prefer"),
+    ("like", "Prefer"),
+    ("wants", "Use"),
+    ("uses", "Use"),
+    ("use", "Use"),
+    ("always", "Always"),
+    ("never", "Never"),
+    ("avoids", "Avoid"),
+    ("avoid", "Avoid"),
+    ("dislikes", "Avoid"),
+    ("don't", "Don't"),
+    ("do", "Do"),
+];
+
+/// One preference as a directive.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Directive {
+    pub key: String,
+    pub scope: Option<String>,
+    pub directive: String,
+}
+
+/// Every matching preference as directives, with a hash of the result.
+#[derive(Debug, Clone, Serialize)]
+pub struct PreferenceDigest {
+    pub scope: Option<String>,
+    pub directives: Vec<Directive>,
+    /// One directive per line, as `- [scope] directive`.
+    pub text: String,
+    /// Hex SHA-256 of `text`; unchanged as long as the directives are.
+    pub hash: String,
+}
+
+/// Digest of the unexpired preferences, of `scope` if given.
+pub async fn preference_digest(
+    backend: &MemoryBackend,
+    scope: Option<&str>,
+) -> Result<PreferenceDigest, MemoryError> {
+    let items = backend
+        .query(PREFERENCES_CATEGORY, None, PREFERENCES_SCAN_LIMIT)
+        .await?;
+    let mut directives: Vec<Directive> = items
+        .iter()
+        .filter(|item| !is_chunk(item) && !is_expired(item))
+        .filter(|item| scope.is_none() || item[SCOPE_ATTRIBUTE].as_str() == scope)
+        .filter_map(directive)
+        .collect();
+    directives.sort_by(|a, b| {
+        (a.scope.is_none(), &a.scope, &a.key).cmp(&(b.scope.is_none(), &b.scope, &b.key))
+    });
+    let text: String = directives
+        .iter()
+        .map(|d| match &d.scope {
+            Some(scope) => format!("- [{scope}] {}\n", d.directive),
+            None => format!("- {}\n", d.directive),
+        })
+        .collect();
+    Ok(PreferenceDigest {
+        scope: scope.map(String::from),
+        hash: sha256_hex(text.as_bytes()),
+        directives,
+        text,
+    })
+}
+
+/// The directive for a preference item, from its `preference` or `content`.
+fn directive(item: &Value) -> Option<Directive> {
+    let text = ["preference", "content"]
+        .iter()
+        .filter_map(|name| item[*name].as_str())
+        .map(str::trim)
+        .find(|text| !text.is_empty())?;
+    let scope = item[SCOPE_ATTRIBUTE]
+        .as_str()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from);
+    Some(Directive {
+        key: item["key"].as_str().unwrap_or_default().to_string(),
+        scope,
+        directive: imperative(text),
+    })
+}
+
+/// Phrase `text` as an imperative sentence: "prefers tabs" becomes "Prefer
+/// tabs", and a statement without a known leading verb becomes "Prefer: ...".
+pub fn imperative(text: &str) -> String {
+    let text = text.trim().trim_end_matches('.');
+    let text = ["the user ", "user ", "i "]
+        .iter()
+        .find_map(|subject| {
+            text.get(..subject.len())
+                .filter(|head| head.eq_ignore_ascii_case(subject))
+                .map(|_| &text[subject.len()..])
+        })
+        .unwrap_or(text);
+    let (first, rest) = text.split_once(' ').unwrap_or((text, ""));
+    let sentence = match IMPERATIVES
+        .iter()
+        .find(|(word, _)| first.eq_ignore_ascii_case(word))
+    {
+        Some((_, verb)) if rest.is_empty() => verb.to_string(),
+        Some((_, verb)) => format!("{verb} {rest}"),
+        None => format!("Prefer: {text}"),
+    };
+    format!("{sentence}.")
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_preference_digest() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for item in [
+            json!({"category": "preferences", "key": "indent", "scope": "code",
+                "preference": "prefers tabs"}),
+            json!({"category": "preferences", "key": "editor", "scope": "tooling",
+                "preference": "Neovim with rust-analyzer"}),
+            json!({"category": "preferences", "key": "commits", "scope": "code",
+                "preference": "Never force-push to main."}),
+            json!({"category": "preferences", "key": "tone", "content": "The user likes short answers"}),
+            json!({"category": "preferences", "key": "old", "preference": "uses vim",
+                "expires_at": "2020-01-01T00:00:00+00:00"}),
+        ] {
+            backend.put_item(item).await.unwrap();
+        }
+
+        let digest = preference_digest(&backend, None).await.unwrap();
+        assert_eq!(
+            digest.text,
+            "- [code] Never force-push to main.\n\
+             - [code] Prefer tabs.\n\
+             - [tooling] Prefer: Neovim with rust-analyzer.\n\
+             - Prefer short answers.\n"
+        );
+        assert_eq!(digest.hash.len(), 64);
+        assert_eq!(
+            preference_digest(&backend, None).await.unwrap().hash,
+            digest.hash
+        );
+
+        let code = preference_digest(&backend, Some("code")).await.unwrap();
+        assert_eq!(code.directives.len(), 2);
+        assert_ne!(code.hash, digest.hash);
+
+        backend
+            .put_item(
+                json!({"category": "preferences", "key": "indent", "scope": "code",
+                "preference": "prefers spaces"}),
+            )
+            .await
+            .unwrap();
+        assert_ne!(
+            preference_digest(&backend, None).await.unwrap().hash,
+            digest.hash
+        );
+    }
+
+    #[test]
+    fn test_imperative() {
+        assert_eq!(imperative("uses helix"), "Use helix.");
+        assert_eq!(imperative("Always run tests"), "Always run tests.");
+        assert_eq!(
+            imperative("Imperative commit subjects"),
+            "Prefer: Imperative commit subjects."
+        );
+    }
+}
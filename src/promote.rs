@@ -0,0 +1,203 @@
+//! Suggestions for short-term memories worth keeping.
+//!
+//! `scratchpad` and `sessions` items expire after 24 hours and 7 days.
+//! [`promotion_candidates`] collects the ones expiring within a window, and
+//! [`suggest_promotions`] asks the LLM which of them hold durable knowledge
+//! (decisions, contacts, preferences, ...) and which long-term category each
+//! belongs in, ranked by confidence. Nothing is moved: the caller promotes
+//! the suggestions it agrees with (`memory_promote`, `fmemory promote`).
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::backend::MemoryBackend;
+use crate::chunk::is_chunk;
+use crate::error::MemoryError;
+#[cfg(feature = "llm")]
+use crate::llm::LlmClient;
+use crate::notify::expiring_items;
+#[cfg(feature = "llm")]
+use crate::schema::{SchemaManager, judge_promotions};
+#[cfg(feature = "llm")]
+use crate::store::StoreError;
+
+/// Categories whose items are considered for promotion.
+pub const PROMOTION_SOURCE_CATEGORIES: &[&str] = &["scratchpad", "sessions"];
+
+/// Categories with a default TTL, never suggested as targets.
+pub const SHORT_TERM_CATEGORIES: &[&str] = &["scratchpad", "sessions", "interactions"];
+
+/// Default window: items expiring within it are candidates.
+pub const DEFAULT_PROMOTION_WINDOW: Duration = Duration::hours(48);
+
+/// Maximum number of items sent to the LLM, soonest to expire first.
+pub const PROMOTION_CANDIDATE_LIMIT: usize = 50;
+
+/// Maximum number of items read per source category.
+pub const PROMOTION_SCAN_LIMIT: usize = 10_000;
+
+/// A short-term item the LLM judged worth keeping.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromotionSuggestion {
+    /// Category of the item, `scratchpad` or `sessions`.
+    pub category: String,
+    pub key: String,
+    /// Long-term category the item belongs in.
+    pub target_category: String,
+    /// How sure the LLM is that the item is durable, from 0 to 1.
+    #[serde(default)]
+    pub confidence: f64,
+    #[serde(default)]
+    pub reason: String,
+    /// When the item expires unless promoted.
+    #[serde(default)]
+    pub expires_at: Option<String>,
+}
+
+/// Live `scratchpad` and `sessions` items expiring within `(now, now +
+/// within]`, soonest first, at most [`PROMOTION_CANDIDATE_LIMIT`].
+pub async fn promotion_candidates(
+    backend: &MemoryBackend,
+    now: DateTime<Utc>,
+    within: Duration,
+) -> Result<Vec<Value>, MemoryError> {
+    let mut candidates = Vec::new();
+    for category in PROMOTION_SOURCE_CATEGORIES {
+        let items: Vec<Value> = backend
+            .query(category, None, PROMOTION_SCAN_LIMIT)
+            .await?
+            .into_iter()
+            .filter(|item| !is_chunk(item))
+            .collect();
+        candidates.extend(
+            expiring_items(&items, now, within)
+                .into_iter()
+                .map(|due| due.item),
+        );
+    }
+    candidates.sort_by(|a, b| a["expires_at"].as_str().cmp(&b["expires_at"].as_str()));
+    candidates.truncate(PROMOTION_CANDIDATE_LIMIT);
+    Ok(candidates)
+}
+
+/// Keep the suggestions naming a candidate and an allowed target, fill in
+/// their expiry, and rank them by confidence, highest first.
+pub fn rank_suggestions(
+    suggestions: Vec<PromotionSuggestion>,
+    candidates: &[Value],
+    targets: &[String],
+    limit: usize,
+) -> Vec<PromotionSuggestion> {
+    let mut ranked: Vec<PromotionSuggestion> = suggestions
+        .into_iter()
+        .filter(|s| targets.contains(&s.target_category))
+        .filter_map(|mut s| {
+            let item = candidates.iter().find(|c| {
+                c["category"].as_str() == Some(&s.category) && c["key"].as_str() == Some(&s.key)
+            })?;
+            s.expires_at = item["expires_at"].as_str().map(String::from);
+            s.confidence = s.confidence.clamp(0.0, 1.0);
+            Some(s)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    let mut seen = HashSet::new();
+    ranked.retain(|s| seen.insert((s.category.clone(), s.key.clone())));
+    ranked.truncate(limit);
+    ranked
+}
+
+/// Up to `limit` promotion suggestions for items expiring within `within`.
+#[cfg(feature = "llm")]
+pub async fn suggest_promotions(
+    backend: &MemoryBackend,
+    llm: &dyn LlmClient,
+    within: Duration,
+    limit: usize,
+) -> Result<Vec<PromotionSuggestion>, StoreError> {
+    let candidates = promotion_candidates(backend, Utc::now(), within).await?;
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+    let schemas: Vec<_> = SchemaManager::new(backend.clone())
+        .list_schemas()
+        .await?
+        .into_iter()
+        .filter(|s| !SHORT_TERM_CATEGORIES.contains(&s.prefix.as_str()))
+        .collect();
+    if schemas.is_empty() {
+        return Err(StoreError::NoSchemas);
+    }
+    let suggestions = judge_promotions(llm, &candidates, &schemas).await?;
+    let targets: Vec<String> = schemas.into_iter().map(|s| s.prefix).collect();
+    Ok(rank_suggestions(suggestions, &candidates, &targets, limit))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use crate::ttl::compute_expires_at;
+    use serde_json::json;
+
+    fn suggestion(category: &str, key: &str, target: &str, confidence: f64) -> PromotionSuggestion {
+        PromotionSuggestion {
+            category: category.into(),
+            key: key.into(),
+            target_category: target.into(),
+            confidence,
+            reason: String::new(),
+            expires_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_promotion_candidates_and_ranking() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        let soon = compute_expires_at(Duration::hours(3));
+        let later = compute_expires_at(Duration::days(6));
+        for (category, key, expires_at) in [
+            ("scratchpad", "db-choice", &soon),
+            ("scratchpad", "lunch", &soon),
+            ("sessions", "session-1", &later),
+        ] {
+            backend
+                .put_item(json!({"category": category, "key": key, "expires_at": expires_at}))
+                .await
+                .unwrap();
+        }
+
+        let candidates = promotion_candidates(&backend, Utc::now(), DEFAULT_PROMOTION_WINDOW)
+            .await
+            .unwrap();
+        let keys: Vec<&str> = candidates
+            .iter()
+            .filter_map(|c| c["key"].as_str())
+            .collect();
+        assert_eq!(keys, ["db-choice", "lunch"]);
+
+        let ranked = rank_suggestions(
+            vec![
+                suggestion("scratchpad", "lunch", "preferences", 0.3),
+                suggestion("scratchpad", "db-choice", "decisions", 1.4),
+                suggestion("scratchpad", "gone", "decisions", 0.9),
+                suggestion("scratchpad", "lunch", "scratchpad", 0.8),
+            ],
+            &candidates,
+            &["decisions".to_string(), "preferences".to_string()],
+            10,
+        );
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].key, "db-choice");
+        assert_eq!(ranked[0].confidence, 1.0);
+        assert_eq!(ranked[0].expires_at.as_deref(), Some(soon.as_str()));
+        assert_eq!(ranked[1].target_category, "preferences");
+    }
+}
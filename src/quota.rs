@@ -0,0 +1,350 @@
+//! Per-category item caps.
+//!
+//! Append-heavy categories such as `scratchpad` and `interactions` otherwise
+//! grow without bound between prunes. A [`Quota`], configured through
+//! [`QUOTAS_ENV`], caps a category's item count; no category is capped by
+//! default. After a write that takes the category over its cap,
+//! [`enforce_quota`] deletes items (with their chunks) according to the
+//! quota's [`EvictionPolicy`] and returns the keys it evicted so callers can
+//! report them. Expired items are always evicted first.
+//!
+//! ferridyn-server cannot count a partition, so each write first reads at
+//! most one item more than the cap; only a category that fills that read is
+//! scanned in full.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::backend::MemoryBackend;
+use crate::chunk::{delete_chunks, is_chunk};
+use crate::error::MemoryError;
+use crate::score::importance;
+use crate::ttl::is_expired;
+
+/// Environment variable setting quotas, as comma-separated
+/// `category=max[:policy]` entries. `category=off` removes a cap.
+pub const QUOTAS_ENV: &str = "FMEMORY_QUOTAS";
+
+/// Maximum number of items read when checking a quota.
+pub const QUOTA_SCAN_LIMIT: usize = 10_000;
+
+/// Which items go once a category is over its cap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Expired items, then the oldest by `created_at`.
+    #[default]
+    Oldest,
+    /// Expired items, then the lowest [`importance`], oldest first among
+    /// equals.
+    LowestImportance,
+    /// Expired items only; the cap is exceeded rather than evicting live items.
+    ExpiredOnly,
+}
+
+impl std::str::FromStr for EvictionPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "oldest" => Ok(EvictionPolicy::Oldest),
+            "importance" => Ok(EvictionPolicy::LowestImportance),
+            "expired" => Ok(EvictionPolicy::ExpiredOnly),
+            other => Err(format!(
+                "Unknown eviction policy '{other}' (expected oldest, importance or expired)"
+            )),
+        }
+    }
+}
+
+/// Item cap for one category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    /// Maximum number of items, not counting chunks.
+    pub max_items: usize,
+    pub policy: EvictionPolicy,
+}
+
+impl Quota {
+    pub fn new(max_items: usize) -> Self {
+        Self {
+            max_items,
+            policy: EvictionPolicy::default(),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: EvictionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+impl std::str::FromStr for Quota {
+    type Err = String;
+
+    /// Parse `max` or `max:policy`, e.g. `200:importance`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (max, policy) = match s.split_once(':') {
+            Some((max, policy)) => (max, policy.parse()?),
+            None => (s, EvictionPolicy::default()),
+        };
+        let max_items = max
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|&n| n > 0)
+            .ok_or_else(|| format!("invalid item cap '{}'", max.trim()))?;
+        Ok(Quota::new(max_items).with_policy(policy))
+    }
+}
+
+/// Quotas per category. Categories without one are not capped.
+#[derive(Debug, Clone, Default)]
+pub struct Quotas {
+    quotas: HashMap<String, Quota>,
+}
+
+impl Quotas {
+    /// No caps at all.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Caps from `FMEMORY_QUOTAS`, none if unset. Malformed entries are
+    /// skipped with a warning.
+    pub fn from_env() -> Self {
+        let mut quotas = Self::default();
+        let Ok(spec) = std::env::var(QUOTAS_ENV) else {
+            return quotas;
+        };
+        for entry in spec.split(',').filter(|e| !e.trim().is_empty()) {
+            let Some((category, quota)) = entry.split_once('=') else {
+                warn!("Ignoring {QUOTAS_ENV} entry: expected category=max, got '{entry}'");
+                continue;
+            };
+            let category = category.trim();
+            if quota.trim() == "off" {
+                quotas.remove(category);
+                continue;
+            }
+            match quota.parse() {
+                Ok(quota) => quotas.set(category, quota),
+                Err(e) => warn!("Ignoring {QUOTAS_ENV} entry '{entry}': {e}"),
+            }
+        }
+        quotas
+    }
+
+    /// Cap `category` at `quota`.
+    pub fn set(&mut self, category: &str, quota: Quota) {
+        self.quotas.insert(category.to_string(), quota);
+    }
+
+    /// Remove the cap on `category`.
+    pub fn remove(&mut self, category: &str) {
+        self.quotas.remove(category);
+    }
+
+    pub fn get(&self, category: &str) -> Option<Quota> {
+        self.quotas.get(category).copied()
+    }
+
+    /// Enforce the quota of the category `item` was just written to. The item
+    /// itself is never evicted. Returns the evicted keys.
+    pub async fn enforce_after_write(
+        &self,
+        backend: &MemoryBackend,
+        item: &Value,
+    ) -> Result<Vec<String>, MemoryError> {
+        let (Some(category), Some(key)) = (item["category"].as_str(), item["key"].as_str()) else {
+            return Ok(Vec::new());
+        };
+        match self.get(category) {
+            Some(quota) => enforce_quota(backend, category, &quota, Some(key)).await,
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Evict items from `category` until it is within `quota`, never touching
+/// `keep`. Chunks of evicted items are deleted too. Returns the evicted keys
+/// in eviction order.
+pub async fn enforce_quota(
+    backend: &MemoryBackend,
+    category: &str,
+    quota: &Quota,
+    keep: Option<&str>,
+) -> Result<Vec<String>, MemoryError> {
+    // Reading one item past the cap, chunks included, settles most writes:
+    // a category with no more items than that is within its quota.
+    let probe = backend.query(category, None, quota.max_items + 1).await?;
+    if probe.len() <= quota.max_items {
+        return Ok(Vec::new());
+    }
+    let items: Vec<Value> = backend
+        .query(category, None, QUOTA_SCAN_LIMIT)
+        .await?
+        .into_iter()
+        .filter(|item| !is_chunk(item))
+        .collect();
+    if items.len() <= quota.max_items {
+        return Ok(Vec::new());
+    }
+    let excess = items.len() - quota.max_items;
+
+    let mut candidates: Vec<&Value> = items
+        .iter()
+        .filter(|item| keep.is_none_or(|k| item["key"].as_str() != Some(k)))
+        .filter(|item| quota.policy != EvictionPolicy::ExpiredOnly || is_expired(item))
+        .collect();
+    candidates.sort_by(|a, b| eviction_order(quota.policy, a, b));
+
+    let mut evicted = Vec::new();
+    for item in candidates.into_iter().take(excess) {
+        let Some(key) = item["key"].as_str() else {
+            continue;
+        };
+        delete_chunks(backend, item).await?;
+        backend.delete_item(category, key).await?;
+        evicted.push(key.to_string());
+    }
+    if !evicted.is_empty() {
+        info!(
+            category,
+            max_items = quota.max_items,
+            evicted = ?evicted,
+            "Evicted items over quota"
+        );
+    }
+    Ok(evicted)
+}
+
+/// Expired items first, then by policy, then oldest first.
+fn eviction_order(policy: EvictionPolicy, a: &Value, b: &Value) -> Ordering {
+    let created_at = |item: &Value| item["created_at"].as_str().unwrap_or_default().to_string();
+    is_expired(b)
+        .cmp(&is_expired(a))
+        .then_with(|| match policy {
+            EvictionPolicy::LowestImportance => importance(a).cmp(&importance(b)),
+            EvictionPolicy::Oldest | EvictionPolicy::ExpiredOnly => Ordering::Equal,
+        })
+        .then_with(|| created_at(a).cmp(&created_at(b)))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    fn item(key: &str, created_at: &str, extra: Value) -> Value {
+        let mut item = json!({"category": "scratchpad", "key": key, "created_at": created_at});
+        if let Value::Object(extra) = extra {
+            item.as_object_mut().unwrap().extend(extra);
+        }
+        item
+    }
+
+    async fn backend_with(items: Vec<Value>) -> MemoryBackend {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for item in items {
+            backend.put_item(item).await.unwrap();
+        }
+        backend
+    }
+
+    #[test]
+    fn test_parse_quota() {
+        assert_eq!("200".parse::<Quota>().unwrap(), Quota::new(200));
+        assert_eq!(
+            "10:importance".parse::<Quota>().unwrap(),
+            Quota::new(10).with_policy(EvictionPolicy::LowestImportance)
+        );
+        assert!("0".parse::<Quota>().is_err());
+        assert!("ten".parse::<Quota>().is_err());
+        assert!("10:newest".parse::<Quota>().is_err());
+        assert!(Quotas::default().get("scratchpad").is_none());
+        let mut quotas = Quotas::none();
+        quotas.set("scratchpad", Quota::new(200));
+        assert_eq!(quotas.get("scratchpad"), Some(Quota::new(200)));
+    }
+
+    #[tokio::test]
+    async fn test_evicts_expired_then_oldest() {
+        let backend = backend_with(vec![
+            item("a", "2026-01-01T00:00:00Z", json!({})),
+            item("b", "2026-01-02T00:00:00Z", json!({})),
+            item(
+                "c",
+                "2026-01-03T00:00:00Z",
+                json!({"expires_at": "2020-01-01T00:00:00Z"}),
+            ),
+            item("d", "2026-01-04T00:00:00Z", json!({})),
+        ])
+        .await;
+        let evicted = enforce_quota(&backend, "scratchpad", &Quota::new(2), Some("d"))
+            .await
+            .unwrap();
+        assert_eq!(evicted, vec!["c", "a"]);
+        let left = backend.query("scratchpad", None, 10).await.unwrap();
+        let keys: Vec<&str> = left.iter().filter_map(|i| i["key"].as_str()).collect();
+        assert_eq!(keys, vec!["b", "d"]);
+    }
+
+    #[tokio::test]
+    async fn test_lowest_importance_and_kept_item() {
+        let backend = backend_with(vec![
+            item(
+                "old-important",
+                "2026-01-01T00:00:00Z",
+                json!({"importance": 5}),
+            ),
+            item("minor", "2026-01-02T00:00:00Z", json!({"importance": 1})),
+            item("new", "2026-01-03T00:00:00Z", json!({})),
+        ])
+        .await;
+        let quota = Quota::new(1).with_policy(EvictionPolicy::LowestImportance);
+        let evicted = enforce_quota(&backend, "scratchpad", &quota, Some("new"))
+            .await
+            .unwrap();
+        assert_eq!(evicted, vec!["minor", "old-important"]);
+        assert!(
+            backend
+                .get_item("scratchpad", "new")
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expired_only_and_chunks() {
+        let backend = backend_with(vec![
+            item(
+                "doc",
+                "2026-01-01T00:00:00Z",
+                json!({"chunk_count": 2, "expires_at": "2020-01-01T00:00:00Z"}),
+            ),
+            item("doc#1", "2026-01-01T00:00:00Z", json!({"chunk_of": "doc"})),
+            item("doc#2", "2026-01-01T00:00:00Z", json!({"chunk_of": "doc"})),
+            item("live-1", "2026-01-02T00:00:00Z", json!({})),
+            item("live-2", "2026-01-03T00:00:00Z", json!({})),
+        ])
+        .await;
+        let quota = Quota::new(1).with_policy(EvictionPolicy::ExpiredOnly);
+        let evicted = enforce_quota(&backend, "scratchpad", &quota, None)
+            .await
+            .unwrap();
+        assert_eq!(evicted, vec!["doc"]);
+        assert_eq!(
+            backend.query("scratchpad", None, 10).await.unwrap().len(),
+            2
+        );
+    }
+}
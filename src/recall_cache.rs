@@ -0,0 +1,203 @@
+//! Caching of recall results.
+//!
+//! An NL recall costs an LLM round trip to resolve the query, the lookups it
+//! resolves to, and another round trip to answer from the items. Asked the
+//! same question again, with nothing written in between, it would return the
+//! same. [`RecallCache`] keeps each [`ResolvedRecall`], and the answer
+//! synthesized from it, keyed by the query and the read options, together
+//! with the [data versions](crate::version) of the namespace when it was
+//! computed. An entry is only served while those versions are unchanged:
+//! queries are resolved against every category's keys, so any write to the
+//! namespace through this process invalidates it. Writes made by other
+//! processes are not seen, so entries also expire after a TTL
+//! (`FMEMORY_RECALL_CACHE`, default 5 minutes, `off` to disable).
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use tracing::warn;
+
+use crate::related::RelatedBudget;
+use crate::schema::Answer;
+use crate::store::ResolvedRecall;
+use crate::ttl::parse_ttl;
+
+/// Environment variable setting how long cached recalls are served: a TTL
+/// such as `5m` or `1h`, or `off`.
+pub const RECALL_CACHE_ENV: &str = "FMEMORY_RECALL_CACHE";
+
+/// How long cached recalls are served by default.
+pub const DEFAULT_RECALL_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Maximum number of cached recalls; the oldest is evicted first.
+pub const RECALL_CACHE_CAPACITY: usize = 256;
+
+/// What a recall's result depends on, besides the data.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RecallKey {
+    table: String,
+    /// The query, lowercased with whitespace collapsed.
+    query: String,
+    limit: usize,
+    include_expired: bool,
+    related: Option<(usize, usize)>,
+}
+
+impl RecallKey {
+    pub fn new(
+        table: &str,
+        query: &str,
+        limit: usize,
+        include_expired: bool,
+        related: Option<RelatedBudget>,
+    ) -> Self {
+        Self {
+            table: table.to_string(),
+            query: normalize_query(query),
+            limit,
+            include_expired,
+            related: related.map(|budget| (budget.hops, budget.max_items)),
+        }
+    }
+}
+
+struct Entry {
+    versions: BTreeMap<String, u64>,
+    stored_at: Instant,
+    resolved: ResolvedRecall,
+    /// The answer synthesized from `resolved`, once there is one.
+    answer: Option<Option<Answer>>,
+}
+
+/// Recall results, shared between clones.
+#[derive(Clone)]
+pub struct RecallCache {
+    /// How long entries are served; `None` disables the cache.
+    ttl: Option<Duration>,
+    entries: Arc<Mutex<HashMap<RecallKey, Entry>>>,
+}
+
+impl Default for RecallCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_RECALL_CACHE_TTL)
+    }
+}
+
+impl RecallCache {
+    /// A cache serving entries for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl: Some(ttl),
+            entries: Arc::default(),
+        }
+    }
+
+    /// A cache that keeps nothing.
+    pub fn off() -> Self {
+        Self {
+            ttl: None,
+            entries: Arc::default(),
+        }
+    }
+
+    /// The default cache, or as configured by `FMEMORY_RECALL_CACHE`.
+    /// Unrecognized values are ignored with a warning.
+    pub fn from_env() -> Self {
+        let Ok(value) = std::env::var(RECALL_CACHE_ENV) else {
+            return Self::default();
+        };
+        match value.trim() {
+            "off" | "0" => Self::off(),
+            ttl => match parse_ttl(ttl).ok().and_then(|ttl| ttl.to_std().ok()) {
+                Some(ttl) => Self::new(ttl),
+                None => {
+                    warn!("Ignoring {RECALL_CACHE_ENV}={ttl}: expected a TTL such as 5m, or off");
+                    Self::default()
+                }
+            },
+        }
+    }
+
+    /// The recall cached for `key`, if computed at `versions` and still fresh.
+    pub fn get(&self, key: &RecallKey, versions: &BTreeMap<String, u64>) -> Option<ResolvedRecall> {
+        let ttl = self.ttl?;
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.get(key)?;
+        if entry.versions != *versions || entry.stored_at.elapsed() >= ttl {
+            entries.remove(key);
+            return None;
+        }
+        Some(entry.resolved.clone())
+    }
+
+    /// Cache `resolved` as the recall for `key`, computed at `versions`.
+    pub fn insert(
+        &self,
+        key: RecallKey,
+        versions: BTreeMap<String, u64>,
+        resolved: ResolvedRecall,
+    ) {
+        if self.ttl.is_none() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= RECALL_CACHE_CAPACITY && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            Entry {
+                versions,
+                stored_at: Instant::now(),
+                resolved,
+                answer: None,
+            },
+        );
+    }
+
+    /// The answer cached for `query` on `table`, if synthesized from
+    /// exactly `items`. `Some(None)` means the items were found not to
+    /// answer it.
+    pub fn answer(&self, table: &str, query: &str, items: &[Value]) -> Option<Option<Answer>> {
+        let ttl = self.ttl?;
+        let query = normalize_query(query);
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .filter(|(key, entry)| {
+                key.table == table
+                    && key.query == query
+                    && entry.stored_at.elapsed() < ttl
+                    && entry.resolved.items == items
+            })
+            .find_map(|(_, entry)| entry.answer.clone())
+    }
+
+    /// Record `answer` as synthesized from `items` for `query` on `table`,
+    /// in the entries holding those items.
+    pub fn insert_answer(&self, table: &str, query: &str, items: &[Value], answer: Option<Answer>) {
+        let query = normalize_query(query);
+        let mut entries = self.entries.lock().unwrap();
+        for (_, entry) in entries.iter_mut().filter(|(key, entry)| {
+            key.table == table && key.query == query && entry.resolved.items == items
+        }) {
+            entry.answer = Some(answer.clone());
+        }
+    }
+}
+
+fn normalize_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
@@ -0,0 +1,476 @@
+//! Links between memories in different categories.
+//!
+//! When a memory is parsed, the LLM lists the other memories it mentions
+//! (a decision naming contact "toby", a contact's project) as `category/key`
+//! references in [`RELATED_ATTRIBUTE`]. Storing the item records a link to
+//! each referenced item that exists ([`link_related`]), in [`crate::links`]
+//! like any other, so the reference can be followed from either side without
+//! rewriting the referenced item. `recall --related` then appends the referenced
+//! items, along with items connected through explicit [`crate::links`], to
+//! the results ([`hydrate_related`]), which lets "what did Toby decide about
+//! auth" find both the contact and the decision. A [`RelatedBudget`] lets
+//! recall follow references of references too, up to a number of hops and
+//! items ([`hydrate_related_within`]). [`backlinks`] answers the reverse
+//! question: which items depend on this one.
+
+use std::collections::HashSet;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::backend::MemoryBackend;
+use crate::chunk::{hydrate_chunks, is_chunk};
+use crate::conflict::item_ref;
+use crate::error::MemoryError;
+use crate::keys::{normalize_category, slugify};
+use crate::links::{DEFAULT_RELATION, normalize_item_ref, parse_item_ref};
+use crate::ttl::is_expired;
+
+/// Attribute listing the `category/key` of each item an item mentions.
+pub const RELATED_ATTRIBUTE: &str = "related";
+
+/// Maximum number of items read per category when looking for backlinks.
+pub const BACKLINK_SCAN_LIMIT: usize = 10_000;
+
+/// Hops recall follows by default: direct references only.
+pub const DEFAULT_RELATED_HOPS: usize = 1;
+
+/// Related items recall adds at most by default.
+pub const DEFAULT_RELATED_LIMIT: usize = 20;
+
+/// How far recall follows references from its results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelatedBudget {
+    /// Steps away from the results to follow; `1` adds only the items the
+    /// results reference directly.
+    pub hops: usize,
+    /// Maximum number of items added, nearest first.
+    pub max_items: usize,
+}
+
+impl Default for RelatedBudget {
+    fn default() -> Self {
+        RelatedBudget {
+            hops: DEFAULT_RELATED_HOPS,
+            max_items: DEFAULT_RELATED_LIMIT,
+        }
+    }
+}
+
+/// An item that references another.
+#[derive(Debug, Clone, Serialize)]
+pub struct Backlink {
+    /// `category/key` of the referencing item.
+    pub source: String,
+    /// How it references the target: [`RELATED_ATTRIBUTE`], or the relation
+    /// of a link.
+    pub via: String,
+    pub item: Value,
+}
+
+/// References in an item's [`RELATED_ATTRIBUTE`].
+pub fn related(item: &Value) -> Vec<String> {
+    item[RELATED_ATTRIBUTE]
+        .as_array()
+        .map(|refs| {
+            refs.iter()
+                .filter_map(|r| r.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Clean up the [`RELATED_ATTRIBUTE`] of a parsed document.
+///
+/// Accepts a list of references or a single one. Each must be
+/// `category/key`; the key is slugified the way parsed keys are, and
+/// malformed or duplicate references are dropped. The attribute is removed
+/// when nothing is left.
+pub fn normalize_related(doc: &mut Value) {
+    let Some(obj) = doc.as_object_mut() else {
+        return;
+    };
+    let raw = match obj.remove(RELATED_ATTRIBUTE) {
+        Some(Value::Array(refs)) => refs,
+        Some(single @ Value::String(_)) => vec![single],
+        _ => return,
+    };
+    let mut refs: Vec<String> = Vec::new();
+    for reference in raw.iter().filter_map(Value::as_str) {
+        let Some((category, key)) = reference.trim().split_once('/') else {
+            continue;
+        };
+        let Ok(category) = normalize_category(&category.trim().to_lowercase()) else {
+            continue;
+        };
+        let key = slugify(key);
+        let reference = format!("{category}/{key}");
+        if !key.is_empty() && !refs.contains(&reference) {
+            refs.push(reference);
+        }
+    }
+    if !refs.is_empty() {
+        obj.insert(RELATED_ATTRIBUTE.to_string(), Value::from(refs));
+    }
+}
+
+/// Link a just-stored `item` to each item it references, with the
+/// [`DEFAULT_RELATION`]. Links are found from both ends, so the referenced
+/// item leads back to `item` without being rewritten. References to items
+/// that do not exist (yet) are left one-sided.
+///
+/// Returns the references that resolved to a stored item.
+pub async fn link_related(
+    backend: &MemoryBackend,
+    item: &Value,
+) -> Result<Vec<String>, MemoryError> {
+    let Some(own_ref) = item_ref(item) else {
+        return Ok(Vec::new());
+    };
+    let mut linked = Vec::new();
+    for reference in related(item) {
+        let Some((category, key)) = reference.split_once('/') else {
+            continue;
+        };
+        if reference == own_ref {
+            continue;
+        }
+        match backend.get_item(category, key).await? {
+            Some(other) if !is_chunk(&other) => {}
+            _ => continue,
+        }
+        backend
+            .link_items(&own_ref, &reference, DEFAULT_RELATION)
+            .await?;
+        linked.push(reference);
+    }
+    Ok(linked)
+}
+
+/// References of `item`: its [`RELATED_ATTRIBUTE`], then the other end of
+/// each of its links.
+async fn references(backend: &MemoryBackend, item: &Value) -> Result<Vec<String>, MemoryError> {
+    let mut references = related(item);
+    if let Some(own) = item_ref(item) {
+        let links = backend.get_links(&own).await?;
+        references.extend(links.iter().map(|link| link.other_end(&own).to_string()));
+    }
+    Ok(references)
+}
+
+/// Items referenced by `items` (through [`RELATED_ATTRIBUTE`] or a link in
+/// either direction) that are not already among them, in reference order,
+/// with chunked content reassembled. Missing items are skipped, as are
+/// expired ones unless `include_expired`. Only direct references are
+/// followed.
+pub async fn hydrate_related(
+    backend: &MemoryBackend,
+    items: &[Value],
+    include_expired: bool,
+) -> Result<Vec<Value>, MemoryError> {
+    let budget = RelatedBudget {
+        hops: 1,
+        max_items: usize::MAX,
+    };
+    hydrate_related_within(backend, items, budget, include_expired).await
+}
+
+/// Like [`hydrate_related`], but also follows the references of the items
+/// found, breadth first, for up to `budget.hops` hops, and stops once
+/// `budget.max_items` items have been added. Each item is added once, at
+/// the hop it is first reached.
+pub async fn hydrate_related_within(
+    backend: &MemoryBackend,
+    items: &[Value],
+    budget: RelatedBudget,
+    include_expired: bool,
+) -> Result<Vec<Value>, MemoryError> {
+    let mut seen: HashSet<String> = items.iter().filter_map(item_ref).collect();
+    let mut extra: Vec<Value> = Vec::new();
+    let mut frontier = items.to_vec();
+    'hops: for _ in 0..budget.hops {
+        let start = extra.len();
+        for item in &frontier {
+            for reference in references(backend, item).await? {
+                if extra.len() >= budget.max_items {
+                    break 'hops;
+                }
+                if !seen.insert(reference.clone()) {
+                    continue;
+                }
+                let Some((category, key)) = reference.split_once('/') else {
+                    continue;
+                };
+                if let Some(item) = backend.get_item(category, key).await?
+                    && !is_chunk(&item)
+                    && (include_expired || !is_expired(&item))
+                {
+                    extra.push(item);
+                }
+            }
+        }
+        if extra.len() == start {
+            break;
+        }
+        frontier = extra[start..].to_vec();
+    }
+    hydrate_chunks(backend, extra).await
+}
+
+/// Every item referencing `target` (`category/key`): items in `categories`
+/// listing it in [`RELATED_ATTRIBUTE`], and the sources of links pointing to
+/// it. An item referencing the target both ways appears once per way.
+/// Expired items are skipped unless `include_expired`.
+pub async fn backlinks(
+    backend: &MemoryBackend,
+    target: &str,
+    categories: &[String],
+    include_expired: bool,
+) -> Result<Vec<Backlink>, MemoryError> {
+    let target = normalize_item_ref(target)?;
+    let live = |item: &Value| !is_chunk(item) && (include_expired || !is_expired(item));
+    let mut found = Vec::new();
+    for category in categories {
+        let items = backend.query(category, None, BACKLINK_SCAN_LIMIT).await?;
+        for item in items {
+            if live(&item)
+                && related(&item).contains(&target)
+                && let Some(source) = item_ref(&item)
+            {
+                found.push(Backlink {
+                    source,
+                    via: RELATED_ATTRIBUTE.to_string(),
+                    item,
+                });
+            }
+        }
+    }
+    for link in backend.get_links(&target).await? {
+        // The link [`link_related`] records for a `related` reference
+        // duplicates the reference itself.
+        if link.target != target
+            || found
+                .iter()
+                .any(|b| b.source == link.source && b.via == link.relation)
+        {
+            continue;
+        }
+        let (category, key) = parse_item_ref(&link.source)?;
+        if let Some(item) = backend.get_item(&category, &key).await?
+            && live(&item)
+        {
+            found.push(Backlink {
+                source: link.source,
+                via: link.relation,
+                item,
+            });
+        }
+    }
+    Ok(found)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    #[test]
+    fn test_normalize_related() {
+        let mut doc = json!({
+            "related": ["contacts/Toby Smith", "contacts/toby-smith", "toby", "Bad Cat/x", 7],
+        });
+        normalize_related(&mut doc);
+        assert_eq!(doc["related"], json!(["contacts/toby-smith"]));
+
+        let mut doc = json!({"related": "project/auth-service"});
+        normalize_related(&mut doc);
+        assert_eq!(doc["related"], json!(["project/auth-service"]));
+
+        let mut doc = json!({"related": []});
+        normalize_related(&mut doc);
+        assert!(doc.get("related").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_link_and_hydrate_related() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        backend
+            .put_item(json!({"category": "contacts", "key": "toby", "name": "Toby"}))
+            .await
+            .unwrap();
+        backend
+            .put_item(json!({
+                "category": "project",
+                "key": "legacy",
+                "expires_at": "2020-01-01T00:00:00Z",
+            }))
+            .await
+            .unwrap();
+        let decision = json!({
+            "category": "decisions",
+            "key": "auth-method",
+            "related": ["contacts/toby", "project/legacy", "project/missing"],
+        });
+        backend.put_item(decision.clone()).await.unwrap();
+
+        let linked = link_related(&backend, &decision).await.unwrap();
+        assert_eq!(linked, vec!["contacts/toby", "project/legacy"]);
+        // The contact is linked to, not rewritten.
+        let toby = backend.get_item("contacts", "toby").await.unwrap().unwrap();
+        assert!(related(&toby).is_empty());
+        let links = backend.get_links("contacts/toby").await.unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].source, "decisions/auth-method");
+        // Linking again does not duplicate the link.
+        link_related(&backend, &decision).await.unwrap();
+        assert_eq!(backend.get_links("contacts/toby").await.unwrap().len(), 1);
+
+        // Following the back-link from the contact finds the decision.
+        let found = hydrate_related(&backend, std::slice::from_ref(&toby), false)
+            .await
+            .unwrap();
+        assert_eq!(found[0]["key"], "auth-method");
+
+        let found = hydrate_related(&backend, std::slice::from_ref(&decision), false)
+            .await
+            .unwrap();
+        let keys: Vec<&str> = found.iter().filter_map(|i| i["key"].as_str()).collect();
+        assert_eq!(keys, vec!["toby"]);
+        let found = hydrate_related(&backend, &[decision, toby], true)
+            .await
+            .unwrap();
+        let keys: Vec<&str> = found.iter().filter_map(|i| i["key"].as_str()).collect();
+        assert_eq!(keys, vec!["legacy"]);
+    }
+
+    #[tokio::test]
+    async fn test_hydrate_follows_links() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for (category, key) in [("project", "auth-service"), ("decisions", "auth-method")] {
+            backend
+                .put_item(json!({"category": category, "key": key}))
+                .await
+                .unwrap();
+        }
+        backend
+            .link_items(
+                "decisions/auth-method",
+                "project/auth-service",
+                "applies-to",
+            )
+            .await
+            .unwrap();
+
+        let service = backend
+            .get_item("project", "auth-service")
+            .await
+            .unwrap()
+            .unwrap();
+        let found = hydrate_related(&backend, &[service], false).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0]["key"], "auth-method");
+    }
+
+    #[tokio::test]
+    async fn test_hydrate_related_within_budget() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        // toby -> auth-method -> auth-service -> payments, plus toby -> alice.
+        for item in [
+            json!({"category": "contacts", "key": "toby", "related": ["decisions/auth-method", "contacts/alice"]}),
+            json!({"category": "contacts", "key": "alice"}),
+            json!({"category": "decisions", "key": "auth-method", "related": ["project/auth-service"]}),
+            json!({"category": "project", "key": "auth-service"}),
+            json!({"category": "project", "key": "payments"}),
+        ] {
+            backend.put_item(item).await.unwrap();
+        }
+        backend
+            .link_items("project/auth-service", "project/payments", "depends-on")
+            .await
+            .unwrap();
+        let toby = backend.get_item("contacts", "toby").await.unwrap().unwrap();
+        let keys_within = |hops, max_items| {
+            let (backend, toby) = (backend.clone(), toby.clone());
+            async move {
+                let budget = RelatedBudget { hops, max_items };
+                let found = hydrate_related_within(&backend, &[toby], budget, false)
+                    .await
+                    .unwrap();
+                found
+                    .iter()
+                    .filter_map(|i| i["key"].as_str().map(String::from))
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(keys_within(1, 20).await, vec!["auth-method", "alice"]);
+        assert_eq!(
+            keys_within(2, 20).await,
+            vec!["auth-method", "alice", "auth-service"]
+        );
+        assert_eq!(
+            keys_within(3, 20).await,
+            vec!["auth-method", "alice", "auth-service", "payments"]
+        );
+        // Nearest items fill the budget first.
+        assert_eq!(
+            keys_within(3, 3).await,
+            vec!["auth-method", "alice", "auth-service"]
+        );
+        assert!(keys_within(0, 20).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_backlinks() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for item in [
+            json!({"category": "contacts", "key": "toby"}),
+            json!({"category": "decisions", "key": "auth-method", "related": ["contacts/toby"]}),
+            json!({"category": "decisions", "key": "db", "related": ["contacts/alice"]}),
+            json!({
+                "category": "project",
+                "key": "old",
+                "related": ["contacts/toby"],
+                "expires_at": "2020-01-01T00:00:00Z",
+            }),
+            json!({"category": "project", "key": "auth-service"}),
+        ] {
+            backend.put_item(item).await.unwrap();
+        }
+        backend
+            .link_items("project/auth-service", "contacts/toby", "owned-by")
+            .await
+            .unwrap();
+        // Outgoing links from the target are not backlinks.
+        backend
+            .link_items("contacts/toby", "decisions/db", "")
+            .await
+            .unwrap();
+
+        let categories = vec!["decisions".to_string(), "project".to_string()];
+        let found = backlinks(&backend, "contacts/toby", &categories, false)
+            .await
+            .unwrap();
+        let refs: Vec<(&str, &str)> = found
+            .iter()
+            .map(|b| (b.source.as_str(), b.via.as_str()))
+            .collect();
+        assert_eq!(
+            refs,
+            vec![
+                ("decisions/auth-method", "related"),
+                ("project/auth-service", "owned-by"),
+            ]
+        );
+
+        let found = backlinks(&backend, "contacts/toby", &categories, true)
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 3);
+    }
+}
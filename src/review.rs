@@ -0,0 +1,189 @@
+//! Spaced-repetition review of long-term memories.
+//!
+//! A long-term item (one without `expires_at`) comes due for review once it
+//! has gone unread for its [`review_interval`]: [`BASE_REVIEW_INTERVAL`] at
+//! first, doubling each time it is kept. [`resurface_candidates`] picks due
+//! items, most important and most overdue first, and [`mark_reviewed`] resets
+//! the clock on the ones the user keeps. [`stale_items`] is the blunter
+//! report: everything long-term left untouched past a fixed age.
+
+use chrono::{DateTime, Duration, Utc};
+use serde_json::{Map, Value};
+
+use crate::backend::MemoryBackend;
+use crate::chunk::is_chunk;
+use crate::error::MemoryError;
+use crate::score::{LAST_ACCESSED_ATTRIBUTE, MAX_IMPORTANCE, importance, last_active};
+
+/// Attribute counting how many times an item was kept during review.
+pub const REVIEW_COUNT_ATTRIBUTE: &str = "review_count";
+
+/// Idle time before a long-term item is first due for review.
+pub const BASE_REVIEW_INTERVAL: Duration = Duration::days(30);
+
+/// Reviews after which the interval stops doubling (64 × the base).
+const MAX_INTERVAL_DOUBLINGS: u64 = 6;
+
+/// How long `item` may go unread before it is due: `base`, doubled for every
+/// previous review.
+pub fn review_interval(item: &Value, base: Duration) -> Duration {
+    let reviews = item[REVIEW_COUNT_ATTRIBUTE]
+        .as_u64()
+        .unwrap_or(0)
+        .min(MAX_INTERVAL_DOUBLINGS) as u32;
+    base * (1i32 << reviews)
+}
+
+/// Review priority of `item`, or `None` if it is not due.
+///
+/// Importance (scaled to `0..=1`) times the number of review intervals the
+/// item has been idle.
+pub fn review_priority(item: &Value, now: DateTime<Utc>, base: Duration) -> Option<f64> {
+    let idle = now - last_active(item)?;
+    let interval = review_interval(item, base);
+    if idle < interval {
+        return None;
+    }
+    let weight = f64::from(importance(item)) / f64::from(MAX_IMPORTANCE);
+    Some(weight * idle.num_seconds() as f64 / interval.num_seconds() as f64)
+}
+
+/// Up to `limit` long-term items due for review, highest priority first.
+/// Chunks and items with `expires_at` are skipped.
+pub fn resurface_candidates(
+    items: Vec<Value>,
+    now: DateTime<Utc>,
+    base: Duration,
+    limit: usize,
+) -> Vec<Value> {
+    let mut due: Vec<(f64, Value)> = items
+        .into_iter()
+        .filter(|item| !is_chunk(item) && item.get("expires_at").is_none_or(Value::is_null))
+        .filter_map(|item| Some((review_priority(&item, now, base)?, item)))
+        .collect();
+    due.sort_by(|a, b| b.0.total_cmp(&a.0));
+    due.into_iter().take(limit).map(|(_, item)| item).collect()
+}
+
+/// Long-term items not stored, updated, or read within `age` of `now`, oldest
+/// first. Chunks and items with `expires_at` are skipped.
+pub fn stale_items(items: Vec<Value>, now: DateTime<Utc>, age: Duration) -> Vec<Value> {
+    let mut stale: Vec<(DateTime<Utc>, Value)> = items
+        .into_iter()
+        .filter(|item| !is_chunk(item) && item.get("expires_at").is_none_or(Value::is_null))
+        .filter_map(|item| Some((last_active(&item)?, item)))
+        .filter(|(active, _)| now - *active >= age)
+        .collect();
+    stale.sort_by_key(|(active, _)| *active);
+    stale.into_iter().map(|(_, item)| item).collect()
+}
+
+/// Record that the user kept `category/key`: stamp `last_accessed` and bump
+/// the review count, pushing the next review further out.
+///
+/// Returns the updated item, or `None` if it no longer exists.
+pub async fn mark_reviewed(
+    backend: &MemoryBackend,
+    category: &str,
+    key: &str,
+) -> Result<Option<Value>, MemoryError> {
+    let Some(item) = backend.get_item(category, key).await? else {
+        return Ok(None);
+    };
+    let reviews = item[REVIEW_COUNT_ATTRIBUTE].as_u64().unwrap_or(0);
+    let mut patch = Map::new();
+    patch.insert(REVIEW_COUNT_ATTRIBUTE.into(), Value::from(reviews + 1));
+    patch.insert(
+        LAST_ACCESSED_ATTRIBUTE.into(),
+        Value::String(Utc::now().to_rfc3339()),
+    );
+    backend.update_item(category, key, &patch).await
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    fn idle(key: &str, days: i64, importance: u8) -> Value {
+        json!({
+            "category": "notes",
+            "key": key,
+            "importance": importance,
+            "created_at": (Utc::now() - Duration::days(days)).to_rfc3339(),
+        })
+    }
+
+    #[test]
+    fn test_review_interval_doubles() {
+        let base = BASE_REVIEW_INTERVAL;
+        assert_eq!(review_interval(&json!({}), base), base);
+        assert_eq!(review_interval(&json!({"review_count": 2}), base), base * 4);
+        assert_eq!(
+            review_interval(&json!({"review_count": 50}), base),
+            base * 64
+        );
+    }
+
+    #[test]
+    fn test_resurface_candidates() {
+        let mut kept = idle("kept-once", 45, 5);
+        kept["review_count"] = json!(1);
+        let mut short_term = idle("scratch", 90, 5);
+        short_term["expires_at"] = json!((Utc::now() + Duration::days(1)).to_rfc3339());
+        let items = vec![
+            idle("fresh", 5, 5),
+            idle("minor", 90, 1),
+            idle("major", 40, 5),
+            kept,
+            short_term,
+            json!({"category": "notes", "key": "major#1", "chunk_of": "major"}),
+        ];
+        let picked = resurface_candidates(items, Utc::now(), BASE_REVIEW_INTERVAL, 10);
+        let keys: Vec<&str> = picked.iter().filter_map(|i| i["key"].as_str()).collect();
+        // 5/5 × 40/30 outranks 1/5 × 90/30; "kept-once" is not due until day 60.
+        assert_eq!(keys, vec!["major", "minor"]);
+    }
+
+    #[test]
+    fn test_stale_items() {
+        let mut read_recently = idle("read", 400, 3);
+        read_recently["last_accessed"] = json!(Utc::now().to_rfc3339());
+        let mut short_term = idle("scratch", 400, 3);
+        short_term["expires_at"] = json!(Utc::now().to_rfc3339());
+        let items = vec![
+            idle("old-endpoint", 200, 3),
+            idle("ancient", 500, 5),
+            idle("recent", 10, 3),
+            read_recently,
+            short_term,
+            json!({"category": "notes", "key": "undated"}),
+        ];
+        let stale = stale_items(items, Utc::now(), Duration::days(180));
+        let keys: Vec<&str> = stale.iter().filter_map(|i| i["key"].as_str()).collect();
+        assert_eq!(keys, vec!["ancient", "old-endpoint"]);
+    }
+
+    #[tokio::test]
+    async fn test_mark_reviewed_defers_next_review() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        backend.put_item(idle("major", 40, 5)).await.unwrap();
+        let item = mark_reviewed(&backend, "notes", "major")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(item["review_count"], 1);
+        assert!(review_priority(&item, Utc::now(), BASE_REVIEW_INTERVAL).is_none());
+        assert!(
+            mark_reviewed(&backend, "notes", "missing")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
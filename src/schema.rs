@@ -5,15 +5,44 @@
 //! - [`PREDEFINED_SCHEMAS`] — 15 built-in category definitions with typed attributes and indexes
 //! - [`SchemaDefinition`] for explicit schema creation (via `define` or predefined init)
 //! - [`ResolvedQuery`] for routing natural language queries to the most efficient query strategy
-//! - LLM-powered functions for document parsing and query resolution
+//! - LLM-powered functions for document parsing and query resolution (`llm` feature)
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "llm")]
 use serde_json::Value;
+use tokio::sync::Mutex;
 use tracing::warn;
 
+use crate::alias::{AttributeAliases, alias_map, load_aliases, save_aliases};
 use crate::backend::MemoryBackend;
+#[cfg(feature = "llm")]
+use crate::budget::{DEFAULT_PROMPT_TOKEN_BUDGET, trim_to_budget};
+#[cfg(feature = "llm")]
+use crate::chunk::split_into_chunks;
 use crate::error::MemoryError;
-use crate::llm::{LlmClient, LlmError};
+use crate::hints::{load_hints, save_hints};
+#[cfg(feature = "llm")]
+use crate::infer::MANAGED_ATTRIBUTES;
+#[cfg(feature = "llm")]
+use crate::ingest::CommitInfo;
+#[cfg(feature = "llm")]
+use crate::keys::{key_or_fallback, slugify};
+#[cfg(feature = "llm")]
+use crate::llm::{LlmClient, LlmError, LlmFeature};
+#[cfg(feature = "llm")]
+use crate::locale::DateContext;
+#[cfg(feature = "llm")]
+use crate::promote::PromotionSuggestion;
+#[cfg(feature = "llm")]
+use crate::related::normalize_related;
+#[cfg(feature = "llm")]
+use crate::score::clamp_importance;
+#[cfg(feature = "llm")]
+use crate::ttl::normalize_ttl;
 
 // Re-export server types used in public API.
 pub use ferridyn_server::client::{
@@ -33,6 +62,9 @@ pub struct SchemaDefinition {
     pub attributes: Vec<AttributeDef>,
     /// Attribute names that should be indexed for fast lookups.
     pub suggested_indexes: Vec<String>,
+    /// Extra instructions for parsing memories into this category.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hints: Option<String>,
 }
 
 /// Attribute definition for a schema.
@@ -43,6 +75,9 @@ pub struct AttributeDef {
     #[serde(rename = "type")]
     pub attr_type: String,
     pub required: bool,
+    /// Other names for the attribute, normalized to `name` on write.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
 }
 
 // ============================================================================
@@ -55,6 +90,8 @@ pub struct PredefinedCategory {
     pub description: &'static str,
     pub attributes: &'static [StaticAttributeDef],
     pub indexed_attributes: &'static [&'static str],
+    /// `(alias, attribute)` pairs.
+    pub aliases: &'static [(&'static str, &'static str)],
 }
 
 /// Compile-time attribute definition for predefined schemas.
@@ -76,6 +113,12 @@ impl PredefinedCategory {
                     name: a.name.to_string(),
                     attr_type: a.attr_type.to_string(),
                     required: a.required,
+                    aliases: self
+                        .aliases
+                        .iter()
+                        .filter(|(_, attribute)| *attribute == a.name)
+                        .map(|(alias, _)| alias.to_string())
+                        .collect(),
                 })
                 .collect(),
             suggested_indexes: self
@@ -83,6 +126,7 @@ impl PredefinedCategory {
                 .iter()
                 .map(|s| s.to_string())
                 .collect(),
+            hints: None,
         }
     }
 }
@@ -127,6 +171,7 @@ pub static PREDEFINED_SCHEMAS: &[PredefinedCategory] = &[
             },
         ],
         indexed_attributes: &["area", "topic"],
+        aliases: &[],
     },
     PredefinedCategory {
         name: "decisions",
@@ -169,6 +214,7 @@ pub static PREDEFINED_SCHEMAS: &[PredefinedCategory] = &[
             },
         ],
         indexed_attributes: &["domain"],
+        aliases: &[],
     },
     PredefinedCategory {
         name: "contacts",
@@ -216,6 +262,12 @@ pub static PREDEFINED_SCHEMAS: &[PredefinedCategory] = &[
             },
         ],
         indexed_attributes: &["name", "email", "role", "team"],
+        aliases: &[
+            ("group", "team"),
+            ("org", "team"),
+            ("position", "role"),
+            ("title", "role"),
+        ],
     },
     PredefinedCategory {
         name: "preferences",
@@ -248,6 +300,7 @@ pub static PREDEFINED_SCHEMAS: &[PredefinedCategory] = &[
             },
         ],
         indexed_attributes: &["scope"],
+        aliases: &[],
     },
     PredefinedCategory {
         name: "issues",
@@ -300,6 +353,7 @@ pub static PREDEFINED_SCHEMAS: &[PredefinedCategory] = &[
             },
         ],
         indexed_attributes: &["area"],
+        aliases: &[("root_cause", "cause"), ("solution", "fix")],
     },
     PredefinedCategory {
         name: "tools",
@@ -342,6 +396,7 @@ pub static PREDEFINED_SCHEMAS: &[PredefinedCategory] = &[
             },
         ],
         indexed_attributes: &["kind", "name"],
+        aliases: &[],
     },
     PredefinedCategory {
         name: "events",
@@ -389,6 +444,7 @@ pub static PREDEFINED_SCHEMAS: &[PredefinedCategory] = &[
             },
         ],
         indexed_attributes: &["date", "title"],
+        aliases: &[("place", "location"), ("venue", "location")],
     },
     PredefinedCategory {
         name: "notes",
@@ -416,6 +472,7 @@ pub static PREDEFINED_SCHEMAS: &[PredefinedCategory] = &[
             },
         ],
         indexed_attributes: &["topic"],
+        aliases: &[],
     },
     PredefinedCategory {
         name: "scratchpad",
@@ -448,6 +505,7 @@ pub static PREDEFINED_SCHEMAS: &[PredefinedCategory] = &[
             },
         ],
         indexed_attributes: &["topic"],
+        aliases: &[],
     },
     // -- Coding Agent Categories --
     PredefinedCategory {
@@ -506,6 +564,7 @@ pub static PREDEFINED_SCHEMAS: &[PredefinedCategory] = &[
             },
         ],
         indexed_attributes: &["project", "status"],
+        aliases: &[],
     },
     PredefinedCategory {
         name: "errors",
@@ -563,6 +622,7 @@ pub static PREDEFINED_SCHEMAS: &[PredefinedCategory] = &[
             },
         ],
         indexed_attributes: &["signature", "language"],
+        aliases: &[("root_cause", "cause"), ("solution", "fix")],
     },
     PredefinedCategory {
         name: "architecture",
@@ -615,6 +675,7 @@ pub static PREDEFINED_SCHEMAS: &[PredefinedCategory] = &[
             },
         ],
         indexed_attributes: &["component", "pattern"],
+        aliases: &[],
     },
     PredefinedCategory {
         name: "snippets",
@@ -662,6 +723,7 @@ pub static PREDEFINED_SCHEMAS: &[PredefinedCategory] = &[
             },
         ],
         indexed_attributes: &["language", "purpose"],
+        aliases: &[],
     },
     // -- Personal Assistant Agent Categories --
     PredefinedCategory {
@@ -720,6 +782,12 @@ pub static PREDEFINED_SCHEMAS: &[PredefinedCategory] = &[
             },
         ],
         indexed_attributes: &["status", "due_date", "assigned_to", "priority"],
+        aliases: &[
+            ("assignee", "assigned_to"),
+            ("deadline", "due_date"),
+            ("due", "due_date"),
+            ("owner", "assigned_to"),
+        ],
     },
     PredefinedCategory {
         name: "interactions",
@@ -772,6 +840,7 @@ pub static PREDEFINED_SCHEMAS: &[PredefinedCategory] = &[
             },
         ],
         indexed_attributes: &["date", "source"],
+        aliases: &[],
     },
 ];
 
@@ -791,6 +860,26 @@ pub enum ResolvedQuery {
     },
     /// Exact item by category + key.
     ExactLookup { category: String, key: String },
+    /// Look up each day from `from` to `to` (inclusive, `YYYY-MM-DD`) in a
+    /// date index. Produced by [`crate::date_query`], never by the LLM.
+    IndexRange {
+        category: String,
+        index_name: String,
+        from: String,
+        to: String,
+    },
+}
+
+impl ResolvedQuery {
+    /// Short name of the lookup: `index`, `scan`, `exact`, or `range`.
+    pub fn strategy(&self) -> &'static str {
+        match self {
+            ResolvedQuery::IndexLookup { .. } => "index",
+            ResolvedQuery::PartitionScan { .. } => "scan",
+            ResolvedQuery::ExactLookup { .. } => "exact",
+            ResolvedQuery::IndexRange { .. } => "range",
+        }
+    }
 }
 
 /// Result of classifying a natural language input's intent.
@@ -802,37 +891,54 @@ pub enum NlIntent {
     Recall { query: String },
 }
 
+/// One exchange in a chat: the user's message and the reply to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatTurn {
+    pub user: String,
+    pub assistant: String,
+}
+
 // ============================================================================
 // SchemaManager
 // ============================================================================
 
+/// How long a listing of the partition schemas is reused before the server
+/// is asked again.
+pub const SCHEMA_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// A schema listing and when it was fetched.
+type CachedSchemas = Option<(Instant, Vec<PartitionSchemaInfo>)>;
+
 /// Manages partition schemas and secondary indexes via the memory backend.
 ///
 /// Delegates to native FerridynDB partition schema and index operations.
+/// Schema lookups are served from one listing, cached for
+/// [`SCHEMA_CACHE_TTL`] and shared between clones, so the existence checks
+/// made on nearly every operation do not each cost a round trip. Defining or
+/// dropping a schema through the manager clears the cache; after changing
+/// schemas on the backend directly, call [`SchemaManager::invalidate`].
 #[derive(Clone)]
 pub struct SchemaManager {
     backend: MemoryBackend,
+    cache: Arc<Mutex<CachedSchemas>>,
 }
 
 impl SchemaManager {
     pub fn new(backend: MemoryBackend) -> Self {
-        Self { backend }
+        Self {
+            backend,
+            cache: Arc::default(),
+        }
+    }
+
+    /// Forget the cached schema listing.
+    pub async fn invalidate(&self) {
+        *self.cache.lock().await = None;
     }
 
     /// Check if a partition schema exists for a category.
     pub async fn has_schema(&self, category: &str) -> Result<bool, MemoryError> {
-        match self.backend.describe_schema(category).await {
-            Ok(_) => Ok(true),
-            Err(MemoryError::Schema(ref msg))
-                if msg.contains("not found")
-                    || msg.contains("NotFound")
-                    || msg.contains("does not exist")
-                    || msg.contains("SchemaNotFound") =>
-            {
-                Ok(false)
-            }
-            Err(e) => Err(e),
-        }
+        Ok(self.get_schema(category).await?.is_some())
     }
 
     /// Get the partition schema for a category, if one exists.
@@ -840,35 +946,88 @@ impl SchemaManager {
         &self,
         category: &str,
     ) -> Result<Option<PartitionSchemaInfo>, MemoryError> {
-        match self.backend.describe_schema(category).await {
-            Ok(info) => Ok(Some(info)),
-            Err(MemoryError::Schema(ref msg))
-                if msg.contains("not found")
-                    || msg.contains("NotFound")
-                    || msg.contains("does not exist")
-                    || msg.contains("SchemaNotFound") =>
-            {
-                Ok(None)
-            }
-            Err(e) => Err(e),
-        }
+        let schemas = self.list_schemas().await?;
+        Ok(schemas.into_iter().find(|s| s.prefix == category))
     }
 
     /// List all partition schemas.
     pub async fn list_schemas(&self) -> Result<Vec<PartitionSchemaInfo>, MemoryError> {
-        self.backend.list_schemas().await
+        let mut cache = self.cache.lock().await;
+        if let Some((fetched_at, ref schemas)) = *cache
+            && fetched_at.elapsed() < SCHEMA_CACHE_TTL
+        {
+            return Ok(schemas.clone());
+        }
+        let schemas = self.backend.list_schemas().await?;
+        *cache = Some((Instant::now(), schemas.clone()));
+        Ok(schemas)
+    }
+
+    /// `category` alone when given, otherwise every category with a schema:
+    /// the categories a command scans when not told which one.
+    pub async fn selected_categories(
+        &self,
+        category: Option<&str>,
+    ) -> Result<Vec<String>, MemoryError> {
+        match category {
+            Some(category) => Ok(vec![category.to_string()]),
+            None => Ok(self
+                .list_schemas()
+                .await?
+                .into_iter()
+                .map(|s| s.prefix)
+                .collect()),
+        }
+    }
+
+    /// Schemas, indexes, attribute aliases, and parsing hints, fetched once
+    /// so one command can consult them without further round trips.
+    ///
+    /// The listings are requested one after another; a backend that cannot
+    /// list indexes yields an empty index list rather than failing the command.
+    pub async fn snapshot(&self) -> Result<SchemaSnapshot, MemoryError> {
+        let schemas = self.list_schemas().await?;
+        let indexes = self.list_indexes().await.unwrap_or_default();
+        let aliases = self.list_aliases().await?;
+        let hints = self.list_hints().await?;
+        Ok(SchemaSnapshot {
+            schemas,
+            indexes,
+            aliases,
+            hints,
+        })
+    }
+
+    /// Drop the partition schema of `category`.
+    pub async fn drop_schema(&self, category: &str) -> Result<(), MemoryError> {
+        let result = self.backend.drop_schema(category).await;
+        self.invalidate().await;
+        result
+    }
+
+    /// Attribute aliases of every category.
+    pub async fn list_aliases(&self) -> Result<AttributeAliases, MemoryError> {
+        load_aliases(&self.backend).await
+    }
+
+    /// Parsing hints of every category that has them.
+    pub async fn list_hints(&self) -> Result<BTreeMap<String, String>, MemoryError> {
+        load_hints(&self.backend).await
     }
 
     /// Create a partition schema and secondary indexes from a schema definition.
     ///
     /// When `validate` is true, the server will reject writes that don't conform
-    /// to the schema. Use false for predefined schemas (lenient).
+    /// to the schema. Use false for predefined schemas (lenient). Attribute
+    /// aliases and parsing hints are stored alongside, replacing any the
+    /// category had.
     pub async fn create_schema_with_indexes(
         &self,
         category: &str,
         definition: &SchemaDefinition,
         validate: bool,
     ) -> Result<(), MemoryError> {
+        alias_map(&definition.attributes)?;
         let attrs: Vec<AttributeDefInput> = definition
             .attributes
             .iter()
@@ -879,9 +1038,14 @@ impl SchemaManager {
             })
             .collect();
 
-        self.backend
+        let created = self
+            .backend
             .create_schema(category, Some(&definition.description), &attrs, validate)
-            .await?;
+            .await;
+        self.invalidate().await;
+        created?;
+        save_aliases(&self.backend, category, &definition.attributes).await?;
+        save_hints(&self.backend, category, definition.hints.as_deref()).await?;
 
         // Create indexes for suggested attributes.
         for attr_name in &definition.suggested_indexes {
@@ -917,15 +1081,60 @@ impl SchemaManager {
     }
 }
 
+/// Schema metadata of a namespace at one point in time, from
+/// [`SchemaManager::snapshot`].
+#[derive(Debug, Clone, Default)]
+pub struct SchemaSnapshot {
+    pub schemas: Vec<PartitionSchemaInfo>,
+    pub indexes: Vec<IndexInfo>,
+    pub aliases: AttributeAliases,
+    pub hints: BTreeMap<String, String>,
+}
+
+impl SchemaSnapshot {
+    /// The partition schema of `category`, if one exists.
+    pub fn get(&self, category: &str) -> Option<&PartitionSchemaInfo> {
+        self.schemas.iter().find(|s| s.prefix == category)
+    }
+
+    /// Whether `category` has a partition schema.
+    pub fn has(&self, category: &str) -> bool {
+        self.get(category).is_some()
+    }
+
+    /// Secondary indexes of `category`.
+    pub fn indexes_of(&self, category: &str) -> Vec<&IndexInfo> {
+        self.indexes
+            .iter()
+            .filter(|idx| idx.partition_schema == category)
+            .collect()
+    }
+
+    /// Parsing hints of `category`.
+    pub fn hints_of(&self, category: &str) -> Option<&str> {
+        self.hints.get(category).map(String::as_str)
+    }
+
+    /// Names of all categories, comma-separated.
+    pub fn category_names(&self) -> String {
+        let names: Vec<&str> = self.schemas.iter().map(|s| s.prefix.as_str()).collect();
+        names.join(", ")
+    }
+}
+
 // ============================================================================
 // LLM-Powered Document Parsing
 // ============================================================================
 
+#[cfg(feature = "llm")]
 const PARSE_DOCUMENT_PROMPT: &str = r#"You are a document parser for a structured memory system. Given a category schema and natural language input, extract a structured JSON document.
 
 Respond with ONLY a JSON object (no markdown, no explanation):
 {
   "key": "short-identifier-for-this-item",
+  "importance": 3,
+  "related": ["contacts/toby"],
+  "ttl": null,
   "attribute1": "value1",
   "attribute2": "value2",
   ...
@@ -933,21 +1142,29 @@ Respond with ONLY a JSON object (no markdown, no explanation):
 
 Rules:
 - "key" must be a short, lowercase, hyphenated identifier (e.g. "toby", "auth-method", "ferridyndb")
+- "importance" is an integer from 1 (trivia, fine to forget) to 5 (critical to remember long-term) estimating how much this memory matters
+- "related" lists other memories the input mentions (people, projects, decisions) as "category/key", using the key each would be stored under (e.g. "contacts/toby" for a person named Toby); use [] if none
 - Extract values for each schema attribute from the input text
+- Some attributes list other names they are "also called"; always use the attribute's own name as the JSON field, never an alias
 - Use null for attributes not mentioned in the input
 - For STRING attributes: use plain text values
 - For NUMBER attributes: use numeric values
 - For BOOLEAN attributes: use true/false
 - Keep values concise but complete
+- "ttl" is how long to keep the memory, set ONLY when the input explicitly says so ("for a week" → "1w", "for the next 3 days" → "3d", "for an hour" → "1h"): a whole number followed by m, h, d, or w; use null otherwise
 - Do NOT include "created_at" or "expires_at" — those are handled automatically
 - IMPORTANT: Resolve all relative dates and times to absolute values using the provided current date. "tomorrow" → actual date, "next week" → actual date, "in 3 days" → actual date. Use ISO 8601 format (YYYY-MM-DD) for dates and 24h format (HH:MM) for times."#;
 
+#[cfg(feature = "llm")]
 const PARSE_WITH_CATEGORY_PROMPT: &str = r#"You are a document parser for a structured memory system. Given a set of available categories and natural language input, pick the best category and extract a structured JSON document.
 
 Respond with ONLY a JSON object (no markdown, no explanation):
 {
   "category": "chosen-category-name",
   "key": "short-identifier-for-this-item",
+  "importance": 3,
+  "related": ["contacts/toby"],
+  "ttl": null,
   "attribute1": "value1",
   "attribute2": "value2",
   ...
@@ -956,77 +1173,231 @@ Respond with ONLY a JSON object (no markdown, no explanation):
 Rules:
 - "category" MUST be one of the available categories listed below — never invent a new one
 - "key" must be a short, lowercase, hyphenated identifier (e.g. "toby", "auth-method", "ferridyndb")
+- "importance" is an integer from 1 (trivia, fine to forget) to 5 (critical to remember long-term) estimating how much this memory matters
+- "related" lists other memories the input mentions (people, projects, decisions) as "category/key" with a category from the list below, using the key each would be stored under (e.g. "contacts/toby" for a person named Toby); use [] if none
 - Extract values for the CHOSEN category's schema attributes from the input text
+- Follow the "Hints" of the chosen category, if it has any
+- Some attributes list other names they are "also called"; always use the attribute's own name as the JSON field, never an alias
 - Use null for attributes not mentioned in the input
 - For STRING attributes: use plain text values
 - For NUMBER attributes: use numeric values
 - For BOOLEAN attributes: use true/false
 - Keep values concise but complete
+- "ttl" is how long to keep the memory, set ONLY when the input explicitly says so ("for a week" → "1w", "for the next 3 days" → "3d", "for an hour" → "1h"): a whole number followed by m, h, d, or w; use null otherwise
 - Do NOT include "created_at" or "expires_at" — those are handled automatically
 - If the input doesn't fit any category well, use "notes" as the fallback
 - IMPORTANT: Resolve all relative dates and times to absolute values using the provided current date. "tomorrow" → actual date, "next week" → actual date, "in 3 days" → actual date. Use ISO 8601 format (YYYY-MM-DD) for dates and 24h format (HH:MM) for times."#;
 
 /// Parse natural language input into a structured document using the schema.
+///
+/// The category's parsing `hints`, if any, are appended to the prompt.
+#[cfg(feature = "llm")]
 pub async fn parse_to_document(
     llm: &dyn LlmClient,
     category: &str,
     schema: &PartitionSchemaInfo,
+    aliases: &AttributeAliases,
+    hints: Option<&str>,
     input: &str,
 ) -> Result<Value, LlmError> {
     let attrs_desc: Vec<String> = schema
         .attributes
         .iter()
         .filter(|a| a.name != "created_at" && a.name != "expires_at")
-        .map(|a| {
-            format!(
-                "  - {} ({}{})",
-                a.name,
-                a.attr_type,
-                if a.required { ", required" } else { "" }
-            )
-        })
+        .map(|a| format!("  - {}", describe_attribute(category, a, aliases)))
         .collect();
 
-    let today = chrono::Local::now().format("%Y-%m-%d (%A)");
+    let today = today_context();
     let user_msg = format!(
-        "Today's date: {today}\nCategory: {category}\nSchema description: {}\nAttributes:\n{}\n\nInput: {input}",
+        "{today}\nCategory: {category}\nSchema description: {}\nAttributes:\n{}\n\nInput: {input}",
         schema.description,
         attrs_desc.join("\n")
     );
 
-    let completion = llm.complete(PARSE_DOCUMENT_PROMPT, &user_msg).await?;
+    let system = match hints {
+        Some(hints) => {
+            format!("{PARSE_DOCUMENT_PROMPT}\n\nRules for the {category} category:\n{hints}")
+        }
+        None => PARSE_DOCUMENT_PROMPT.to_string(),
+    };
+    let completion = llm
+        .complete_for(LlmFeature::Parse, &system, &user_msg)
+        .await?;
     let cleaned = strip_markdown_fences(completion.text.trim());
 
-    serde_json::from_str(&cleaned).map_err(|e| {
+    let doc = serde_json::from_str(&cleaned).map_err(|e| {
         LlmError::Parse(format!(
             "Failed to parse document: {e}\nResponse: {}",
             completion.text
         ))
-    })
+    })?;
+    let mut doc = with_slug_key(doc, input);
+    aliases
+        .canonicalize(category, &mut doc)
+        .map_err(|e| LlmError::Parse(e.to_string()))?;
+    clamp_importance(&mut doc);
+    normalize_related(&mut doc);
+    normalize_ttl(&mut doc);
+    Ok(doc)
 }
 
 /// Parse natural language input, letting the LLM pick the best category from available schemas.
 ///
 /// Returns a JSON document that includes a `"category"` field chosen by the LLM.
+#[cfg(feature = "llm")]
 pub async fn parse_to_document_with_category(
     llm: &dyn LlmClient,
     schemas: &[PartitionSchemaInfo],
+    aliases: &AttributeAliases,
+    hints: &BTreeMap<String, String>,
     input: &str,
 ) -> Result<Value, LlmError> {
+    let categories_desc = describe_categories(schemas, aliases, hints);
+    let today = today_context();
+    let user_msg = format!("{today}\n\nAvailable categories:{categories_desc}\n\nInput: {input}");
+
+    let completion = llm
+        .complete_for(LlmFeature::Parse, PARSE_WITH_CATEGORY_PROMPT, &user_msg)
+        .await?;
+    let cleaned = strip_markdown_fences(completion.text.trim());
+
+    let doc = serde_json::from_str(&cleaned).map_err(|e| {
+        LlmError::Parse(format!(
+            "Failed to parse document: {e}\nResponse: {}",
+            completion.text
+        ))
+    })?;
+    let mut doc = with_slug_key(doc, input);
+    if let Some(category) = doc["category"].as_str().map(String::from) {
+        aliases
+            .canonicalize(&category, &mut doc)
+            .map_err(|e| LlmError::Parse(e.to_string()))?;
+    }
+    clamp_importance(&mut doc);
+    normalize_related(&mut doc);
+    normalize_ttl(&mut doc);
+    Ok(doc)
+}
+
+#[cfg(feature = "llm")]
+const PARSE_BATCH_PROMPT: &str = r#"You are a document parser for a structured memory system. Given a set of available categories and a numbered list of facts, pick the best category for EACH fact and extract a structured JSON document from it.
+
+Respond with ONLY a JSON array (no markdown, no explanation), one element per fact:
+[
+  {
+    "fact": 1,
+    "category": "chosen-category-name",
+    "key": "short-identifier-for-this-item",
+    "importance": 3,
+    "related": ["contacts/toby"],
+    "ttl": null,
+    "attribute1": "value1",
+    ...
+  }
+]
+
+Rules:
+- "fact" is the number of the fact the document is parsed from; return exactly one document per fact
+- Parse each fact on its own: never merge facts or copy attributes from one fact to another
+- "category" MUST be one of the available categories listed below — never invent a new one
+- "key" must be a short, lowercase, hyphenated identifier (e.g. "toby", "auth-method", "ferridyndb")
+- "importance" is an integer from 1 (trivia, fine to forget) to 5 (critical to remember long-term) estimating how much this memory matters
+- "related" lists other memories the fact mentions (people, projects, decisions) as "category/key" with a category from the list below, using the key each would be stored under (e.g. "contacts/toby" for a person named Toby); use [] if none
+- "ttl" is how long to keep the memory, set ONLY when the fact explicitly says so ("for a week" → "1w", "for the next 3 days" → "3d", "for an hour" → "1h"): a whole number followed by m, h, d, or w; use null otherwise
+- Extract values for the CHOSEN category's schema attributes from the fact
+- Follow the "Hints" of the chosen category, if it has any
+- Some attributes list other names they are "also called"; always use the attribute's own name as the JSON field, never an alias
+- Use null for attributes not mentioned in the fact
+- Keep values concise but complete, with the same types as the schema (STRING, NUMBER, BOOLEAN)
+- Do NOT include "created_at" or "expires_at" — those are handled automatically
+- If a fact doesn't fit any category well, use "notes" as the fallback
+- IMPORTANT: Resolve all relative dates and times to absolute values using the provided current date. Use ISO 8601 format (YYYY-MM-DD) for dates and 24h format (HH:MM) for times."#;
+
+/// Parse several facts in one LLM call, each into a document with a
+/// `category` picked from `schemas`, as [`parse_to_document_with_category`]
+/// does for one.
+///
+/// Returns one entry per fact, in order. A fact the response leaves out, or
+/// whose document names a category outside `schemas`, gets `None`.
+#[cfg(feature = "llm")]
+pub async fn parse_facts_batch(
+    llm: &dyn LlmClient,
+    schemas: &[PartitionSchemaInfo],
+    aliases: &AttributeAliases,
+    hints: &BTreeMap<String, String>,
+    facts: &[String],
+) -> Result<Vec<Option<Value>>, LlmError> {
+    if facts.is_empty() {
+        return Ok(Vec::new());
+    }
+    let categories_desc = describe_categories(schemas, aliases, hints);
+    let facts_desc: String = facts
+        .iter()
+        .enumerate()
+        .map(|(i, fact)| format!("\n{}. {}", i + 1, fact.trim()))
+        .collect();
+    let today = today_context();
+    let user_msg =
+        format!("{today}\n\nAvailable categories:{categories_desc}\n\nFacts:{facts_desc}");
+
+    let completion = llm
+        .complete_for(LlmFeature::Parse, PARSE_BATCH_PROMPT, &user_msg)
+        .await?;
+    let cleaned = strip_markdown_fences(completion.text.trim());
+    let parsed: Vec<Value> = serde_json::from_str(&cleaned).map_err(|e| {
+        LlmError::Parse(format!(
+            "Failed to parse batch documents: {e}\nResponse: {}",
+            completion.text
+        ))
+    })?;
+
+    let mut docs: Vec<Option<Value>> = vec![None; facts.len()];
+    for mut doc in parsed {
+        let Some(index) = doc["fact"]
+            .as_u64()
+            .and_then(|n| (n as usize).checked_sub(1))
+            .filter(|&i| i < facts.len() && docs[i].is_none())
+        else {
+            continue;
+        };
+        let Some(category) = doc["category"]
+            .as_str()
+            .filter(|c| schemas.iter().any(|s| s.prefix == *c))
+            .map(String::from)
+        else {
+            continue;
+        };
+        if let Some(obj) = doc.as_object_mut() {
+            obj.remove("fact");
+        }
+        let mut doc = with_slug_key(doc, &facts[index]);
+        // Left unparsed, like a fact the response skipped.
+        if aliases.canonicalize(&category, &mut doc).is_err() {
+            continue;
+        }
+        clamp_importance(&mut doc);
+        normalize_related(&mut doc);
+        normalize_ttl(&mut doc);
+        docs[index] = Some(doc);
+    }
+    Ok(docs)
+}
+
+/// Describe each category, its attributes, and its hints for the parsing
+/// prompts.
+#[cfg(feature = "llm")]
+fn describe_categories(
+    schemas: &[PartitionSchemaInfo],
+    aliases: &AttributeAliases,
+    hints: &BTreeMap<String, String>,
+) -> String {
     let mut categories_desc = String::new();
     for schema in schemas {
         let attrs: Vec<String> = schema
             .attributes
             .iter()
             .filter(|a| a.name != "created_at" && a.name != "expires_at")
-            .map(|a| {
-                format!(
-                    "    - {} ({}{})",
-                    a.name,
-                    a.attr_type,
-                    if a.required { ", required" } else { "" }
-                )
-            })
+            .map(|a| format!("    - {}", describe_attribute(&schema.prefix, a, aliases)))
             .collect();
         categories_desc.push_str(&format!(
             "\nCategory: {}\n  Description: {}\n  Attributes:\n{}\n",
@@ -1034,28 +1405,47 @@ pub async fn parse_to_document_with_category(
             schema.description,
             attrs.join("\n")
         ));
+        if let Some(hints) = hints.get(&schema.prefix) {
+            categories_desc.push_str(&format!("  Hints: {hints}\n"));
+        }
     }
+    categories_desc
+}
 
-    let today = chrono::Local::now().format("%Y-%m-%d (%A)");
-    let user_msg = format!(
-        "Today's date: {today}\n\nAvailable categories:{categories_desc}\n\nInput: {input}"
-    );
-
-    let completion = llm.complete(PARSE_WITH_CATEGORY_PROMPT, &user_msg).await?;
-    let cleaned = strip_markdown_fences(completion.text.trim());
+/// One attribute for the parsing prompts: name, type, whether it is
+/// required, and the other names it is also called.
+#[cfg(feature = "llm")]
+fn describe_attribute(category: &str, attr: &AttributeInfo, aliases: &AttributeAliases) -> String {
+    let required = if attr.required { ", required" } else { "" };
+    let also = aliases.aliases_of(category, &attr.name);
+    if also.is_empty() {
+        format!("{} ({}{required})", attr.name, attr.attr_type)
+    } else {
+        format!(
+            "{} ({}{required}; also called {})",
+            attr.name,
+            attr.attr_type,
+            also.join(", ")
+        )
+    }
+}
 
-    serde_json::from_str(&cleaned).map_err(|e| {
-        LlmError::Parse(format!(
-            "Failed to parse document: {e}\nResponse: {}",
-            completion.text
-        ))
-    })
+/// Replace the LLM-proposed `key` with its slug, deriving one from `input`
+/// if the proposal is missing or unusable.
+#[cfg(feature = "llm")]
+fn with_slug_key(mut doc: Value, input: &str) -> Value {
+    if let Some(obj) = doc.as_object_mut() {
+        let key = key_or_fallback(obj.get("key").and_then(Value::as_str), input);
+        obj.insert("key".to_string(), Value::String(key));
+    }
+    doc
 }
 
 // ============================================================================
 // LLM-Powered Query Resolution
 // ============================================================================
 
+#[cfg(feature = "llm")]
 const RESOLVE_QUERY_PROMPT: &str = r#"You are a query resolver for a structured memory system. Given the available schemas, indexes, existing keys, and a natural language query, determine how to find the data.
 
 Respond with ONLY a JSON object (no markdown, no explanation). Use one of these forms:
@@ -1079,12 +1469,17 @@ Rules:
 - key_prefix does a begins_with match on sort keys — "doctor" matches "doctor-appointment", "doctor-checkup", etc.
 - Use null key_prefix only when you need ALL items in a category
 - Only use index lookup for specific attribute VALUE queries (e.g. "who has email toby@example.com")
-- Choose the category that best matches what the user is asking about"#;
+- Choose the category that best matches what the user is asking about
+- A query may name an attribute by one of its aliases (listed under "Attribute aliases"); treat it as that attribute"#;
 
 /// Resolve a natural language query to a [`ResolvedQuery`].
 ///
 /// `category_keys` maps each category name to its existing sort keys (up to a sample limit).
 /// This helps the LLM match queries to concrete keys and prefixes.
+///
+/// The prompt context is trimmed to [`DEFAULT_PROMPT_TOKEN_BUDGET`]; use
+/// [`resolve_query_with_budget`] to choose a different budget.
+#[cfg(feature = "llm")]
 pub async fn resolve_query(
     llm: &dyn LlmClient,
     schemas: &[PartitionSchemaInfo],
@@ -1092,52 +1487,78 @@ pub async fn resolve_query(
     category_keys: &[(String, Vec<String>)],
     query: &str,
 ) -> Result<ResolvedQuery, LlmError> {
+    resolve_query_with_budget(
+        llm,
+        schemas,
+        &AttributeAliases::predefined(),
+        indexes,
+        category_keys,
+        query,
+        DEFAULT_PROMPT_TOKEN_BUDGET,
+    )
+    .await
+}
+
+/// Resolve a natural language query, keeping the schema/index/key context
+/// under `token_budget` estimated tokens.
+///
+/// Categories and keys are ranked by keyword overlap with the query (see
+/// [`trim_to_budget`]); the least relevant are dropped first.
+#[cfg(feature = "llm")]
+pub async fn resolve_query_with_budget(
+    llm: &dyn LlmClient,
+    schemas: &[PartitionSchemaInfo],
+    aliases: &AttributeAliases,
+    indexes: &[IndexInfo],
+    category_keys: &[(String, Vec<String>)],
+    query: &str,
+    token_budget: usize,
+) -> Result<ResolvedQuery, LlmError> {
+    let context = trim_to_budget(schemas, indexes, category_keys, query, token_budget);
+
     let mut schema_desc = String::new();
-    for schema in schemas {
-        let keys_for_cat: Vec<&str> = category_keys
+    let mut alias_desc = String::new();
+    for schema in &context.schemas {
+        let keys_for_cat: Vec<&str> = context
+            .category_keys
             .iter()
             .find(|(cat, _)| cat == &schema.prefix)
             .map(|(_, keys)| keys.iter().map(|s| s.as_str()).collect())
             .unwrap_or_default();
-
-        let keys_str = if keys_for_cat.is_empty() {
-            "(empty)".to_string()
-        } else {
-            keys_for_cat.join(", ")
-        };
-
-        schema_desc.push_str(&format!(
-            "\nCategory: {}\n  Description: {}\n  Attributes: {}\n  Keys: {}\n",
-            schema.prefix,
-            schema.description,
-            schema
-                .attributes
-                .iter()
-                .map(|a| format!("{}({})", a.name, a.attr_type))
-                .collect::<Vec<_>>()
-                .join(", "),
-            keys_str,
-        ));
+        schema_desc.push_str(&format_schema_entry(schema, &keys_for_cat));
+        for attr in &schema.attributes {
+            let also = aliases.aliases_of(&schema.prefix, &attr.name);
+            if !also.is_empty() {
+                alias_desc.push_str(&format!(
+                    "\n{}.{}: {}",
+                    schema.prefix,
+                    attr.name,
+                    also.join(", ")
+                ));
+            }
+        }
+    }
+    if !alias_desc.is_empty() {
+        schema_desc.push_str(&format!("\nAttribute aliases:{alias_desc}\n"));
     }
 
     let mut index_desc = String::new();
-    if indexes.is_empty() {
+    if context.indexes.is_empty() {
         index_desc.push_str("\n(none)");
     } else {
-        for idx in indexes {
-            index_desc.push_str(&format!(
-                "\nIndex: {} (category={}, attribute={}, type={})",
-                idx.name, idx.partition_schema, idx.index_key_name, idx.index_key_type
-            ));
+        for idx in &context.indexes {
+            index_desc.push_str(&format_index_entry(idx));
         }
     }
 
-    let today = chrono::Local::now().format("%Y-%m-%d (%A)");
+    let today = today_context();
     let user_msg = format!(
-        "Today's date: {today}\n\nAvailable schemas:{schema_desc}\nAvailable indexes:{index_desc}\n\nQuery: {query}"
+        "{today}\n\nAvailable schemas:{schema_desc}\nAvailable indexes:{index_desc}\n\nQuery: {query}"
     );
 
-    let completion = llm.complete(RESOLVE_QUERY_PROMPT, &user_msg).await?;
+    let completion = llm
+        .complete_for(LlmFeature::Resolve, RESOLVE_QUERY_PROMPT, &user_msg)
+        .await?;
     let cleaned = strip_markdown_fences(completion.text.trim());
 
     let parsed: Value = serde_json::from_str(&cleaned).map_err(|e| {
@@ -1203,6 +1624,7 @@ pub async fn resolve_query(
 // LLM-Powered Intent Classification
 // ============================================================================
 
+#[cfg(feature = "llm")]
 const CLASSIFY_INTENT_PROMPT: &str = r#"You are an intent classifier for a memory system. Given natural language input, determine if the user wants to STORE a new memory or RECALL an existing one.
 
 Respond with ONLY a JSON object (no markdown, no explanation):
@@ -1221,8 +1643,11 @@ Rules:
 - Default to STORE if ambiguous — it's safer to store than to lose information"#;
 
 /// Classify a natural language input as either a remember (store) or recall (retrieve) intent.
+#[cfg(feature = "llm")]
 pub async fn classify_intent(llm: &dyn LlmClient, input: &str) -> Result<NlIntent, LlmError> {
-    let completion = llm.complete(CLASSIFY_INTENT_PROMPT, input).await?;
+    let completion = llm
+        .complete_for(LlmFeature::Classify, CLASSIFY_INTENT_PROMPT, input)
+        .await?;
     let cleaned = strip_markdown_fences(completion.text.trim());
 
     let parsed: Value = serde_json::from_str(&cleaned).map_err(|e| {
@@ -1261,6 +1686,7 @@ pub async fn classify_intent(llm: &dyn LlmClient, input: &str) -> Result<NlInten
 // LLM-Powered Answer Synthesis
 // ============================================================================
 
+#[cfg(feature = "llm")]
 const ANSWER_QUERY_PROMPT: &str = r#"You are answering a question using data from a personal memory system. Given the user's question and retrieved memory items, provide a concise, direct answer.
 
 Rules:
@@ -1270,109 +1696,908 @@ Rules:
 - If no items are relevant at all, respond with exactly: NO_RELEVANT_DATA
 - Do NOT add speculation, caveats, or information not present in the data
 - Do NOT mention "the data shows" or "according to the records" — just answer naturally
-- For dates and times, state them clearly (e.g. "Your doctor's appointment is on 2026-02-03 at 12:00")"#;
+- For dates and times, state them clearly (e.g. "Your doctor's appointment is on 2026-02-03 at 12:00")
+- If the data answers the question only in part, end with a line containing exactly FOLLOW_UP: and then at most 3 lines, each either "query: <a question that would find what is missing>" or "category: <a category of the items worth checking>"
+- Omit FOLLOW_UP entirely when the answer is complete
+
+Example of a partial answer:
+Toby is the CTO. His email is not recorded.
+FOLLOW_UP:
+query: what is Toby's email address
+category: contacts"#;
+
+/// Marker line after which a partial answer lists follow-up suggestions.
+#[cfg(feature = "llm")]
+const FOLLOW_UP_MARKER: &str = "FOLLOW_UP:";
+
+/// Where to look next when an answer is partial.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FollowUp {
+    /// Queries that may find the missing information.
+    pub queries: Vec<String>,
+    /// Categories worth checking directly.
+    pub categories: Vec<String>,
+}
+
+impl FollowUp {
+    pub fn is_empty(&self) -> bool {
+        self.queries.is_empty() && self.categories.is_empty()
+    }
+}
+
+/// An answer synthesized by [`answer_query_with_follow_up`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answer {
+    pub text: String,
+    /// Suggestions for drilling down, empty when the answer is complete.
+    pub follow_up: FollowUp,
+}
 
 /// Synthesize a natural language answer from retrieved items and the original query.
 ///
 /// Returns `None` if the LLM determines no items are relevant.
+#[cfg(feature = "llm")]
 pub async fn answer_query(
     llm: &dyn LlmClient,
     query: &str,
     items: &[Value],
 ) -> Result<Option<String>, LlmError> {
-    let items_json = serde_json::to_string_pretty(items).unwrap_or_default();
-    let today = chrono::Local::now().format("%Y-%m-%d (%A)");
+    Ok(answer_query_with_follow_up(llm, query, items)
+        .await?
+        .map(|answer| answer.text))
+}
 
-    let user_msg =
-        format!("Today's date: {today}\n\nQuestion: {query}\n\nRetrieved items:\n{items_json}");
+/// Like [`answer_query`], keeping the follow-up queries and categories the
+/// LLM suggests when the items answer the question only in part.
+#[cfg(feature = "llm")]
+pub async fn answer_query_with_follow_up(
+    llm: &dyn LlmClient,
+    query: &str,
+    items: &[Value],
+) -> Result<Option<Answer>, LlmError> {
+    let dates = DateContext::from_env();
+    let items: Vec<Value> = items.iter().map(|item| dates.localize_item(item)).collect();
+    let items_json = serde_json::to_string_pretty(&items).unwrap_or_default();
+    let today = today_context();
+
+    let user_msg = format!("{today}\n\nQuestion: {query}\n\nRetrieved items:\n{items_json}");
 
-    let completion = llm.complete(ANSWER_QUERY_PROMPT, &user_msg).await?;
-    let text = completion.text.trim().to_string();
+    let completion = llm
+        .complete_for(LlmFeature::Answer, ANSWER_QUERY_PROMPT, &user_msg)
+        .await?;
+    let text = completion.text.trim();
 
     if text == "NO_RELEVANT_DATA" {
         Ok(None)
     } else {
-        Ok(Some(text))
+        Ok(Some(parse_answer(text)))
+    }
+}
+
+/// Split an answer from the follow-up lines after [`FOLLOW_UP_MARKER`].
+/// Lines that are neither `query:` nor `category:` are dropped.
+#[cfg(feature = "llm")]
+fn parse_answer(text: &str) -> Answer {
+    let mut lines = text.lines();
+    let answer: Vec<&str> = lines
+        .by_ref()
+        .take_while(|line| line.trim() != FOLLOW_UP_MARKER)
+        .collect();
+    let mut follow_up = FollowUp::default();
+    for line in lines {
+        let line = line.trim().trim_start_matches(['-', '*']).trim_start();
+        let Some((kind, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        match kind.trim().to_lowercase().as_str() {
+            "query" => follow_up.queries.push(value.to_string()),
+            "category" => follow_up.categories.push(value.to_lowercase()),
+            _ => {}
+        }
+    }
+    Answer {
+        text: answer.join("\n").trim().to_string(),
+        follow_up,
     }
 }
 
 // ============================================================================
-// Helpers
+// LLM-Powered Chat
 // ============================================================================
 
-/// Strip markdown code fences from LLM output.
-pub fn strip_markdown_fences(text: &str) -> String {
-    let trimmed = text.trim();
-    if trimmed.starts_with("```") {
-        let after_first_fence = trimmed
-            .find('\n')
-            .map(|i| &trimmed[i + 1..])
-            .unwrap_or(trimmed);
-        if let Some(end) = after_first_fence.rfind("```") {
-            return after_first_fence[..end].trim().to_string();
-        }
+#[cfg(feature = "llm")]
+const CHAT_PROMPT: &str = r#"You are chatting with the user of a personal memory system. Each message comes with the conversation so far and the memory items retrieved for it.
+
+Rules:
+- Reply naturally and concisely, in 1-4 sentences unless the user asks for more
+- Use the memory items when they are relevant; they are what you know about the user
+- Do NOT invent facts about the user that are not in the memory items or the conversation
+- When the user states something new, acknowledge it briefly; it is offered for saving when the chat ends
+- If a memory item contradicts what the user says now, point it out
+- Do NOT mention "memory items" or "records" — just talk"#;
+
+/// Previous turns sent with each chat message; older ones are dropped.
+#[cfg(feature = "llm")]
+const MAX_CHAT_HISTORY_TURNS: usize = 10;
+
+/// Reply to `message` in a chat, given the turns so far and the memory
+/// items retrieved for it.
+///
+/// Only the last [`MAX_CHAT_HISTORY_TURNS`] turns are sent, and the items
+/// are truncated to [`MAX_SUMMARY_INPUT_CHARS`] characters of JSON.
+#[cfg(feature = "llm")]
+pub async fn chat_reply(
+    llm: &dyn LlmClient,
+    history: &[ChatTurn],
+    message: &str,
+    items: &[Value],
+) -> Result<String, LlmError> {
+    let today = today_context();
+    let recent = &history[history.len().saturating_sub(MAX_CHAT_HISTORY_TURNS)..];
+    let transcript: String = recent
+        .iter()
+        .map(|turn| format!("User: {}\nAssistant: {}\n", turn.user, turn.assistant))
+        .collect();
+    let items_json = serde_json::to_string_pretty(items).unwrap_or_default();
+    let items_json: String = items_json.chars().take(MAX_SUMMARY_INPUT_CHARS).collect();
+
+    let user_msg = format!(
+        "{today}\n\nConversation so far:\n{transcript}\n\
+         Memory items:\n{items_json}\n\nUser: {message}"
+    );
+    let completion = llm
+        .complete_for(LlmFeature::Chat, CHAT_PROMPT, &user_msg)
+        .await?;
+    let reply = completion.text.trim().to_string();
+    if reply.is_empty() {
+        return Err(LlmError::EmptyResponse);
     }
-    trimmed.to_string()
+    Ok(reply)
 }
 
 // ============================================================================
-// Tests
+// LLM-Powered Summarization
 // ============================================================================
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::llm::MockLlmClient;
+#[cfg(feature = "llm")]
+const SUMMARIZE_PROMPT: &str = r#"You are summarizing a long document for a structured memory system. The full text is stored separately; your summary is what gets parsed into structured attributes and shown when answering questions.
 
-    // --- strip_markdown_fences ---
+Rules:
+- Write a dense, factual summary of at most 10 sentences
+- Preserve names, dates, decisions, identifiers, and numbers exactly
+- Lead with what the document is (e.g. "Design doc for the auth service: ...")
+- Do NOT add information that is not in the text
+- Respond with ONLY the summary text (no markdown headings, no preamble)"#;
 
-    #[test]
-    fn test_strip_no_fences() {
-        assert_eq!(strip_markdown_fences("hello"), "hello");
+#[cfg(feature = "llm")]
+const MERGE_SUMMARY_PROMPT: &str = r#"You are merging summaries of consecutive parts of one long document, in order, into a single summary for a structured memory system.
+
+Rules:
+- Write a dense, factual summary of at most 10 sentences
+- Keep every distinct fact; merge repeats across the part summaries
+- Preserve names, dates, decisions, identifiers, and numbers exactly
+- Lead with what the document is (e.g. "Design doc for the auth service: ...")
+- Do NOT add information that is not in the part summaries
+- Respond with ONLY the summary text (no markdown headings, no preamble)"#;
+
+/// Maximum number of input characters sent to the LLM for summarization.
+#[cfg(feature = "llm")]
+const MAX_SUMMARY_INPUT_CHARS: usize = 24_000;
+
+/// Separator between partial summaries sent to be merged.
+#[cfg(feature = "llm")]
+const PARTIAL_SEPARATOR: &str = "\n\n---\n\n";
+
+/// Summarize a long text into a short paragraph.
+///
+/// Text longer than [`MAX_SUMMARY_INPUT_CHARS`] is summarized in parts, and
+/// the part summaries are merged by [`merge_partials`].
+#[cfg(feature = "llm")]
+pub async fn summarize_text(llm: &dyn LlmClient, text: &str) -> Result<String, LlmError> {
+    let mut partials = Vec::new();
+    for part in split_into_chunks(text, MAX_SUMMARY_INPUT_CHARS) {
+        let completion = llm
+            .complete_for(LlmFeature::Summarize, SUMMARIZE_PROMPT, &part)
+            .await?;
+        let partial = completion.text.trim().to_string();
+        if !partial.is_empty() {
+            partials.push(partial);
+        }
     }
+    merge_partials(llm, MERGE_SUMMARY_PROMPT, "Part summaries", partials).await
+}
 
-    #[test]
-    fn test_strip_json_fences() {
-        assert_eq!(strip_markdown_fences("```json\n{}\n```"), "{}");
+/// Merge partial summaries into one, a level at a time: each call merges
+/// as many as fit in [`MAX_SUMMARY_INPUT_CHARS`], and the results are merged
+/// again until one is left, so no partial is cut off. The `heading` labels
+/// them in the request. Fails with [`LlmError::EmptyResponse`] when nothing
+/// is left.
+#[cfg(feature = "llm")]
+async fn merge_partials(
+    llm: &dyn LlmClient,
+    prompt: &str,
+    heading: &str,
+    mut partials: Vec<String>,
+) -> Result<String, LlmError> {
+    while partials.len() > 1 {
+        let mut merged = Vec::new();
+        for mut group in group_partials(partials, MAX_SUMMARY_INPUT_CHARS) {
+            if group.len() == 1 {
+                merged.append(&mut group);
+                continue;
+            }
+            let user_msg = format!("{heading}:\n\n{}", group.join(PARTIAL_SEPARATOR));
+            let completion = llm
+                .complete_for(LlmFeature::Summarize, prompt, &user_msg)
+                .await?;
+            let text = completion.text.trim().to_string();
+            if !text.is_empty() {
+                merged.push(text);
+            }
+        }
+        partials = merged;
+    }
+    match partials.pop() {
+        Some(summary) if !summary.is_empty() => Ok(summary),
+        _ => Err(LlmError::EmptyResponse),
     }
+}
 
-    #[test]
-    fn test_strip_bare_fences() {
-        assert_eq!(strip_markdown_fences("```\nfoo\n```"), "foo");
+/// Group partial summaries to be merged, each group at most `max_chars`
+/// long once joined by [`PARTIAL_SEPARATOR`]. Every group but the last holds
+/// at least two, so each level of [`merge_partials`] shrinks; a partial
+/// longer than half of `max_chars` is truncated to that.
+#[cfg(feature = "llm")]
+fn group_partials(partials: Vec<String>, max_chars: usize) -> Vec<Vec<String>> {
+    let half = max_chars.saturating_sub(PARTIAL_SEPARATOR.len()) / 2;
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    let mut len = 0;
+    for partial in partials {
+        let partial: String = partial.chars().take(half).collect();
+        match groups.last_mut() {
+            Some(group)
+                if group.len() < 2
+                    || len + PARTIAL_SEPARATOR.len() + partial.len() <= max_chars =>
+            {
+                len += PARTIAL_SEPARATOR.len() + partial.len();
+                group.push(partial);
+            }
+            _ => {
+                len = partial.len();
+                groups.push(vec![partial]);
+            }
+        }
     }
+    groups
+}
 
-    // --- predefined schemas ---
+#[cfg(feature = "llm")]
+const OVERVIEW_PROMPT: &str = r#"You are condensing the stored items of one memory category into an overview. The overview replaces the individual items when memory is injected into a prompt, so it must stand on its own.
 
-    #[test]
-    fn test_predefined_schemas_count() {
-        assert_eq!(PREDEFINED_SCHEMAS.len(), 15);
-    }
+Rules:
+- Write a dense, factual overview of at most 12 sentences
+- Cover every distinct topic; merge duplicates and note where items contradict each other
+- Preserve names, dates, decisions, identifiers, and numbers exactly
+- Do NOT add information that is not in the items
+- Respond with ONLY the overview text (no markdown headings, no preamble)"#;
 
-    #[test]
-    fn test_predefined_schemas_have_created_at() {
-        for schema in PREDEFINED_SCHEMAS {
-            assert!(
-                schema
-                    .attributes
-                    .iter()
-                    .any(|a| a.name == "created_at" && a.attr_type == "STRING" && !a.required),
-                "Category '{}' missing created_at attribute",
-                schema.name
-            );
+#[cfg(feature = "llm")]
+const MERGE_OVERVIEW_PROMPT: &str = r#"You are merging partial overviews of one memory category, each written from a different batch of its items, into a single overview.
+
+Rules:
+- Write a dense, factual overview of at most 12 sentences
+- Keep every distinct fact; merge repeats across the partial overviews
+- Preserve names, dates, decisions, identifiers, and numbers exactly
+- Do NOT add information that is not in the partial overviews
+- Respond with ONLY the overview text (no markdown headings, no preamble)"#;
+
+/// Split items into batches of compact JSON lines, each at most `max_chars`
+/// long. An item longer than `max_chars` on its own is truncated.
+#[cfg(feature = "llm")]
+fn batch_items(items: &[Value], max_chars: usize) -> Vec<String> {
+    let mut batches = Vec::new();
+    let mut batch = String::new();
+    for item in items {
+        let line: String = item.to_string().chars().take(max_chars).collect();
+        if !batch.is_empty() && batch.len() + line.len() + 1 > max_chars {
+            batches.push(std::mem::take(&mut batch));
         }
+        if !batch.is_empty() {
+            batch.push('\n');
+        }
+        batch.push_str(&line);
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
     }
+    batches
+}
 
-    #[test]
-    fn test_predefined_schemas_have_content() {
-        for schema in PREDEFINED_SCHEMAS {
-            assert!(
-                schema
-                    .attributes
-                    .iter()
-                    .any(|a| a.name == "content" && a.attr_type == "STRING"),
-                "Category '{}' missing content attribute",
-                schema.name
-            );
+/// Condense a category's items into a single overview.
+///
+/// Items are sent in batches of at most [`MAX_SUMMARY_INPUT_CHARS`]; when
+/// there is more than one batch, the partial overviews are merged by
+/// [`merge_partials`].
+#[cfg(feature = "llm")]
+pub async fn summarize_category(
+    llm: &dyn LlmClient,
+    category: &str,
+    items: &[Value],
+) -> Result<String, LlmError> {
+    let mut partials = Vec::new();
+    for batch in batch_items(items, MAX_SUMMARY_INPUT_CHARS) {
+        let user_msg =
+            format!("Category: {category}\n\nItems (one JSON object per line):\n{batch}");
+        let completion = llm
+            .complete_for(LlmFeature::Summarize, OVERVIEW_PROMPT, &user_msg)
+            .await?;
+        let partial = completion.text.trim().to_string();
+        if !partial.is_empty() {
+            partials.push(partial);
+        }
+    }
+    let heading = format!("Category: {category}\n\nPartial overviews");
+    merge_partials(llm, MERGE_OVERVIEW_PROMPT, &heading, partials).await
+}
+
+#[cfg(feature = "llm")]
+const DIGEST_PROMPT: &str = r#"You are writing a daily digest for a structured memory system. You receive one day's short-term entries (scratchpad notes, work sessions, interactions), one per line as "HH:MM [category/key] {attributes}", sometimes preceded by an earlier digest of the same day covering entries that came before them. The digest is kept long-term after the raw entries expire.
+
+Rules:
+- Write a concise digest of at most 8 short bullet points, each starting with "- "
+- Group related entries; lead with decisions, outcomes, and open action items
+- When an earlier digest is given, merge it with the new entries into one digest, keeping what still matters from it
+- Preserve names, dates, identifiers, and numbers exactly
+- Skip trivia that will not matter next week
+- Do NOT add information that is not in the entries
+- Respond with ONLY the bullet points (no heading, no preamble)"#;
+
+/// Summarize a day's entries, rendered one per line, into a short digest,
+/// merged with the `earlier` digest of the same day when there is one.
+///
+/// Input beyond [`MAX_SUMMARY_INPUT_CHARS`] is truncated before the LLM call.
+#[cfg(feature = "llm")]
+pub async fn summarize_day(
+    llm: &dyn LlmClient,
+    date: chrono::NaiveDate,
+    earlier: Option<&str>,
+    entries: &str,
+) -> Result<String, LlmError> {
+    let truncated: String = entries.chars().take(MAX_SUMMARY_INPUT_CHARS).collect();
+    let earlier = earlier
+        .map(|digest| format!("Earlier digest:\n{digest}\n\n"))
+        .unwrap_or_default();
+    let user_msg = format!(
+        "Date: {}\n\n{earlier}Entries:\n{truncated}",
+        date.format("%Y-%m-%d (%A)")
+    );
+    let completion = llm
+        .complete_for(LlmFeature::Digest, DIGEST_PROMPT, &user_msg)
+        .await?;
+    let digest = completion.text.trim().to_string();
+    if digest.is_empty() {
+        return Err(LlmError::EmptyResponse);
+    }
+    Ok(digest)
+}
+
+#[cfg(feature = "llm")]
+const SESSION_PROMPT: &str = r#"You are closing a work session for a structured memory system. You receive the session's goal and details, then the notable facts recorded during it, one per line as "- HH:MM fact". The summary is what a later session will read to pick up where this one left off.
+
+Rules:
+- Write at most 6 sentences
+- Say what was accomplished, what was decided, and what is still open or blocked
+- Preserve names, file paths, identifiers, and numbers exactly
+- Do NOT add information that is not in the session
+- Respond with ONLY the summary text (no heading, no preamble)"#;
+
+/// Summarize a session, rendered by [`crate::session::session_input`], for
+/// whoever resumes the work.
+///
+/// Input beyond [`MAX_SUMMARY_INPUT_CHARS`] is truncated before the LLM call.
+#[cfg(feature = "llm")]
+pub async fn summarize_session(llm: &dyn LlmClient, session: &str) -> Result<String, LlmError> {
+    let truncated: String = session.chars().take(MAX_SUMMARY_INPUT_CHARS).collect();
+    let completion = llm
+        .complete_for(LlmFeature::Summarize, SESSION_PROMPT, &truncated)
+        .await?;
+    let summary = completion.text.trim().to_string();
+    if summary.is_empty() {
+        return Err(LlmError::EmptyResponse);
+    }
+    Ok(summary)
+}
+
+#[cfg(feature = "llm")]
+const WEEKLY_REVIEW_PROMPT: &str = r#"You are writing a weekly review for a structured memory system. You receive a JSON report of the past week: decisions made, issues opened, issues resolved, and new contacts, each a list of stored items.
+
+Rules:
+- Write 1-3 short paragraphs of plain prose, most significant items first
+- Say what was decided and why, what broke and what got fixed, and who was met
+- Mention anything that looks unfinished (open issues, decisions without rationale)
+- Preserve names, dates, identifiers, and numbers exactly
+- Do NOT add information that is not in the report
+- Respond with ONLY the review text (no markdown headings, no preamble)"#;
+
+/// Write a prose summary of a weekly report, given as JSON.
+///
+/// Input beyond [`MAX_SUMMARY_INPUT_CHARS`] is truncated before the LLM call.
+#[cfg(feature = "llm")]
+pub async fn summarize_week(llm: &dyn LlmClient, report: &Value) -> Result<String, LlmError> {
+    let report_json = serde_json::to_string_pretty(report).unwrap_or_default();
+    let truncated: String = report_json.chars().take(MAX_SUMMARY_INPUT_CHARS).collect();
+    let today = today_context();
+    let user_msg = format!("{today}\n\nWeekly report:\n{truncated}");
+    let completion = llm
+        .complete_for(LlmFeature::Digest, WEEKLY_REVIEW_PROMPT, &user_msg)
+        .await?;
+    let review = completion.text.trim().to_string();
+    if review.is_empty() {
+        return Err(LlmError::EmptyResponse);
+    }
+    Ok(review)
+}
+
+// ============================================================================
+// LLM-Powered Contradiction Detection
+// ============================================================================
+
+#[cfg(feature = "llm")]
+const DETECT_CONFLICTS_PROMPT: &str = r#"You are checking a structured memory system for contradictions. Given a NEW memory item and a list of EXISTING items from the same category, decide which existing items the new one contradicts.
+
+Two items contradict when they cannot both be true at the same time about the same subject, e.g. "prefers tabs" vs "prefers spaces" for the same scope, or two different emails for the same person.
+
+Rules:
+- Return a JSON array of the "key" values of contradicted EXISTING items, e.g. ["indent-style"]
+- Return [] when nothing is contradicted
+- Items about different subjects, scopes, or time periods do NOT contradict
+- Additional detail, refinement, or an unrelated fact is NOT a contradiction
+- Respond with ONLY the JSON array, no markdown fences, no explanation"#;
+
+/// Keys of the `existing` items that `item` contradicts, as judged by the LLM.
+///
+/// Keys the LLM returns that are not among `existing` are dropped.
+#[cfg(feature = "llm")]
+pub async fn detect_conflicts(
+    llm: &dyn LlmClient,
+    item: &Value,
+    existing: &[Value],
+) -> Result<Vec<String>, LlmError> {
+    if existing.is_empty() {
+        return Ok(Vec::new());
+    }
+    let existing_json: Vec<String> = existing.iter().map(Value::to_string).collect();
+    let user_msg = format!(
+        "NEW item:\n{item}\n\nEXISTING items (one JSON object per line):\n{}",
+        existing_json.join("\n")
+    );
+    let completion = llm
+        .complete_for(LlmFeature::Conflict, DETECT_CONFLICTS_PROMPT, &user_msg)
+        .await?;
+    let cleaned = strip_markdown_fences(completion.text.trim());
+    let keys: Vec<String> = serde_json::from_str(&cleaned).map_err(|e| {
+        LlmError::Parse(format!(
+            "Failed to parse conflicts: {e}. Response: {}",
+            completion.text
+        ))
+    })?;
+    Ok(keys
+        .into_iter()
+        .filter(|k| existing.iter().any(|e| e["key"].as_str() == Some(k)))
+        .collect())
+}
+
+// ============================================================================
+// LLM-Powered Promotion Suggestions
+// ============================================================================
+
+#[cfg(feature = "llm")]
+const JUDGE_PROMOTIONS_PROMPT: &str = r#"You are reviewing short-term memories (scratchpad notes and work sessions) that are about to expire, to find the ones holding durable knowledge worth keeping long-term.
+
+Durable: decisions and their rationale, facts about people, preferences, project facts, recurring problems and their fixes. Not durable: transient status, finished to-dos, small talk, anything that only mattered that day.
+
+Rules:
+- Return a JSON array with one object per ITEM worth keeping: {"category": "...", "key": "...", "target_category": "...", "confidence": 0.0-1.0, "reason": "one short sentence"}
+- "category" and "key" identify the ITEM exactly as given
+- "target_category" must be one of the available categories
+- Leave out items that are not durable; return [] when none are
+- Respond with ONLY the JSON array, no markdown fences, no explanation"#;
+
+/// Maximum characters of a single item shown to the LLM when judging
+/// promotions.
+#[cfg(feature = "llm")]
+const MAX_PROMOTION_ITEM_CHARS: usize = 2_000;
+
+/// Which of the short-term `items` the LLM considers durable, and where each
+/// belongs among `schemas`. Suggestions are returned as given; see
+/// [`crate::promote::rank_suggestions`] for validation.
+#[cfg(feature = "llm")]
+pub async fn judge_promotions(
+    llm: &dyn LlmClient,
+    items: &[Value],
+    schemas: &[PartitionSchemaInfo],
+) -> Result<Vec<PromotionSuggestion>, LlmError> {
+    if items.is_empty() {
+        return Ok(Vec::new());
+    }
+    let categories: Vec<String> = schemas
+        .iter()
+        .map(|s| format!("- {}: {}", s.prefix, s.description))
+        .collect();
+    let items_json: Vec<String> = items
+        .iter()
+        .map(|item| {
+            item.to_string()
+                .chars()
+                .take(MAX_PROMOTION_ITEM_CHARS)
+                .collect()
+        })
+        .collect();
+    let user_msg = format!(
+        "Available categories:\n{}\n\nITEMS (one JSON object per line):\n{}",
+        categories.join("\n"),
+        items_json.join("\n")
+    );
+    let completion = llm
+        .complete_for(LlmFeature::Promote, JUDGE_PROMOTIONS_PROMPT, &user_msg)
+        .await?;
+    let cleaned = strip_markdown_fences(completion.text.trim());
+    serde_json::from_str(&cleaned).map_err(|e| {
+        LlmError::Parse(format!(
+            "Failed to parse promotion suggestions: {e}. Response: {}",
+            completion.text
+        ))
+    })
+}
+
+// ============================================================================
+// LLM-Powered Schema Proposals
+// ============================================================================
+
+#[cfg(feature = "llm")]
+const PROPOSE_SCHEMA_PROMPT: &str = r#"You are designing a category schema for a structured memory system from the user's description of what they want to track.
+
+Respond with ONLY a JSON object (no markdown, no explanation):
+{
+  "category": "short lowercase plural noun, words joined by hyphens",
+  "description": "one sentence describing what the category holds",
+  "attributes": [{"name": "snake_case_name", "type": "STRING", "required": true}],
+  "suggested_indexes": ["attribute names items will be looked up by"],
+  "hints": "one sentence on how to choose item keys, or null"
+}
+
+Rules:
+- "type" is one of "STRING", "NUMBER", "BOOLEAN"
+- Include every attribute the description mentions, plus at most a few it clearly implies
+- Dates and times are STRING in ISO 8601 (YYYY-MM-DD); ratings, counts, and amounts are NUMBER
+- Mark required only what every item will have
+- Do not include key, category, created_at, updated_at, or expires_at; they are managed automatically
+- Suggest indexes for attributes with repeating values people filter by (author, status, genre), not for free text or unique values"#;
+
+/// Have the LLM design a schema for what `request` describes.
+///
+/// Returns the proposed category name, slugified, and definition. Managed
+/// attributes are dropped, types are checked, and suggested indexes are
+/// limited to proposed attributes.
+#[cfg(feature = "llm")]
+pub async fn propose_schema(
+    llm: &dyn LlmClient,
+    request: &str,
+) -> Result<(String, SchemaDefinition), LlmError> {
+    let completion = llm
+        .complete_for(LlmFeature::Schema, PROPOSE_SCHEMA_PROMPT, request)
+        .await?;
+    let cleaned = strip_markdown_fences(completion.text.trim());
+    let parse_error = |e: serde_json::Error| {
+        LlmError::Parse(format!(
+            "Failed to parse schema proposal: {e}. Response: {}",
+            completion.text
+        ))
+    };
+    let proposal: Value = serde_json::from_str(&cleaned).map_err(parse_error)?;
+    let category = slugify(proposal["category"].as_str().unwrap_or_default());
+    let mut definition: SchemaDefinition = serde_json::from_value(proposal).map_err(parse_error)?;
+
+    let mut seen = Vec::new();
+    definition.attributes.retain(|a| {
+        let keep = !MANAGED_ATTRIBUTES.contains(&a.name.as_str()) && !seen.contains(&a.name);
+        seen.push(a.name.clone());
+        keep
+    });
+    for attr in &mut definition.attributes {
+        attr.attr_type = attr.attr_type.to_ascii_uppercase();
+        if !matches!(attr.attr_type.as_str(), "STRING" | "NUMBER" | "BOOLEAN") {
+            return Err(LlmError::Parse(format!(
+                "Proposed attribute '{}' has unsupported type '{}'",
+                attr.name, attr.attr_type
+            )));
+        }
+    }
+    let names: Vec<String> = definition
+        .attributes
+        .iter()
+        .map(|a| a.name.clone())
+        .collect();
+    definition.suggested_indexes.retain(|i| names.contains(i));
+    definition.suggested_indexes.dedup();
+    Ok((category, definition))
+}
+
+// ============================================================================
+// LLM-Powered Commit Extraction
+// ============================================================================
+
+#[cfg(feature = "llm")]
+const EXTRACT_COMMITS_PROMPT: &str = r#"You are extracting durable project knowledge from git commit messages for a structured memory system. Given category schemas and a batch of commits, return the decisions and project facts worth remembering.
+
+Respond with ONLY a JSON array (no markdown, no explanation). Each element:
+{
+  "commit": "full commit sha this memory comes from",
+  "category": "one of the available categories",
+  "key": "short-identifier-for-this-item",
+  "importance": 3,
+  "attribute1": "value1",
+  ...
+}
+
+Rules:
+- Only use the available categories listed below
+- Use "decisions" for choices with a rationale (what was chosen and why); use "project" for facts about structure, patterns, or components
+- Skip trivial commits (typos, formatting, version bumps, merges) — most commits produce nothing
+- "key" must be a short, lowercase, hyphenated identifier describing the memory, not the commit
+- "importance" is an integer from 1 (trivia, fine to forget) to 5 (critical to remember long-term) estimating how much this memory matters
+- Extract values for the chosen category's schema attributes; use null for attributes not mentioned
+- Do NOT include "created_at" or "expires_at"
+- Return [] if nothing is worth remembering"#;
+
+/// Extract decision/project memories from a batch of commits.
+///
+/// Returned documents carry `category`, `key`, `commit`, and schema
+/// attributes. Documents naming a category outside `schemas` or a commit not
+/// in the batch are dropped.
+#[cfg(feature = "llm")]
+pub async fn extract_commit_memories(
+    llm: &dyn LlmClient,
+    schemas: &[PartitionSchemaInfo],
+    commits: &[CommitInfo],
+) -> Result<Vec<Value>, LlmError> {
+    if commits.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut categories_desc = String::new();
+    for schema in schemas {
+        let attrs: Vec<String> = schema
+            .attributes
+            .iter()
+            .filter(|a| a.name != "created_at" && a.name != "expires_at")
+            .map(|a| format!("    - {} ({})", a.name, a.attr_type))
+            .collect();
+        categories_desc.push_str(&format!(
+            "\nCategory: {}\n  Description: {}\n  Attributes:\n{}\n",
+            schema.prefix,
+            schema.description,
+            attrs.join("\n")
+        ));
+    }
+
+    let mut commits_desc = String::new();
+    for commit in commits {
+        commits_desc.push_str(&format!(
+            "\n---\ncommit {}\nDate: {}\n\n{}\n{}\n",
+            commit.sha, commit.date, commit.subject, commit.body
+        ));
+    }
+
+    let user_msg = format!("Available categories:{categories_desc}\nCommits:{commits_desc}");
+    let completion = llm
+        .complete_for(LlmFeature::Extract, EXTRACT_COMMITS_PROMPT, &user_msg)
+        .await?;
+    let cleaned = strip_markdown_fences(completion.text.trim());
+
+    let parsed: Vec<Value> = serde_json::from_str(&cleaned).map_err(|e| {
+        LlmError::Parse(format!(
+            "Failed to parse commit extraction: {e}\nResponse: {}",
+            completion.text
+        ))
+    })?;
+
+    Ok(parsed
+        .into_iter()
+        .filter_map(|mut doc| {
+            let category_ok = doc["category"]
+                .as_str()
+                .is_some_and(|c| schemas.iter().any(|s| s.prefix == c));
+            let commit_ok = doc["commit"]
+                .as_str()
+                .is_some_and(|sha| commits.iter().any(|c| c.sha == sha));
+            let key = doc["key"].as_str().map(slugify).unwrap_or_default();
+            if !category_ok || !commit_ok || key.is_empty() {
+                return None;
+            }
+            doc["key"] = Value::String(key);
+            clamp_importance(&mut doc);
+            Some(doc)
+        })
+        .collect())
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Render one category for the query-resolution prompt.
+pub(crate) fn format_schema_entry(schema: &PartitionSchemaInfo, keys: &[&str]) -> String {
+    let keys_str = if keys.is_empty() {
+        "(empty)".to_string()
+    } else {
+        keys.join(", ")
+    };
+
+    format!(
+        "\nCategory: {}\n  Description: {}\n  Attributes: {}\n  Keys: {}\n",
+        schema.prefix,
+        schema.description,
+        schema
+            .attributes
+            .iter()
+            .map(|a| format!("{}({})", a.name, a.attr_type))
+            .collect::<Vec<_>>()
+            .join(", "),
+        keys_str,
+    )
+}
+
+/// Render one secondary index for the query-resolution prompt.
+pub(crate) fn format_index_entry(idx: &IndexInfo) -> String {
+    format!(
+        "\nIndex: {} (category={}, attribute={}, type={})",
+        idx.name, idx.partition_schema, idx.index_key_name, idx.index_key_type
+    )
+}
+
+/// Today's date and, when configured, the locale's date conventions, for
+/// the first lines of a prompt.
+#[cfg(feature = "llm")]
+fn today_context() -> String {
+    let dates = DateContext::from_env();
+    dates.prompt_context(dates.today())
+}
+
+/// Strip markdown code fences from LLM output.
+pub fn strip_markdown_fences(text: &str) -> String {
+    let trimmed = text.trim();
+    if trimmed.starts_with("```") {
+        let after_first_fence = trimmed
+            .find('\n')
+            .map(|i| &trimmed[i + 1..])
+            .unwrap_or(trimmed);
+        if let Some(end) = after_first_fence.rfind("```") {
+            return after_first_fence[..end].trim().to_string();
+        }
+    }
+    trimmed.to_string()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "llm")]
+    use crate::llm::MockLlmClient;
+
+    // --- strip_markdown_fences ---
+
+    #[test]
+    fn test_strip_no_fences() {
+        assert_eq!(strip_markdown_fences("hello"), "hello");
+    }
+
+    #[test]
+    fn test_strip_json_fences() {
+        assert_eq!(strip_markdown_fences("```json\n{}\n```"), "{}");
+    }
+
+    #[test]
+    fn test_strip_bare_fences() {
+        assert_eq!(strip_markdown_fences("```\nfoo\n```"), "foo");
+    }
+
+    // --- SchemaManager ---
+
+    #[tokio::test]
+    async fn test_schema_cache_invalidation() {
+        let backend = MemoryBackend::in_memory(crate::TABLE_NAME.to_string());
+        let sm = SchemaManager::new(backend.clone());
+        assert!(!sm.has_schema("pets").await.unwrap());
+
+        // Created behind the manager's back: the cached listing still wins.
+        backend
+            .create_schema("pets", Some("Pets"), &[], false)
+            .await
+            .unwrap();
+        assert!(!sm.clone().has_schema("pets").await.unwrap());
+        sm.invalidate().await;
+        assert!(sm.has_schema("pets").await.unwrap());
+
+        // Defining and dropping through the manager clear it.
+        let definition = SchemaDefinition {
+            description: "Plants".into(),
+            attributes: vec![],
+            suggested_indexes: vec![],
+            hints: None,
+        };
+        sm.create_schema_with_indexes("plants", &definition, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            sm.get_schema("plants").await.unwrap().unwrap().description,
+            "Plants"
+        );
+        sm.drop_schema("plants").await.unwrap();
+        assert!(!sm.has_schema("plants").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot() {
+        let backend = MemoryBackend::in_memory(crate::TABLE_NAME.to_string());
+        backend.ensure_predefined_schemas().await.unwrap();
+        let snapshot = SchemaManager::new(backend).snapshot().await.unwrap();
+        assert!(snapshot.has("contacts"));
+        assert!(!snapshot.has("pets"));
+        assert_eq!(snapshot.schemas.len(), PREDEFINED_SCHEMAS.len());
+        assert!(
+            snapshot
+                .indexes_of("contacts")
+                .iter()
+                .all(|idx| idx.partition_schema == "contacts")
+        );
+        assert_eq!(
+            snapshot.aliases.aliases_of("contacts", "team"),
+            ["group", "org"]
+        );
+        assert!(snapshot.category_names().contains("contacts"));
+    }
+
+    // --- predefined schemas ---
+
+    #[test]
+    fn test_predefined_schemas_count() {
+        assert_eq!(PREDEFINED_SCHEMAS.len(), 15);
+    }
+
+    #[test]
+    fn test_predefined_schemas_have_created_at() {
+        for schema in PREDEFINED_SCHEMAS {
+            assert!(
+                schema
+                    .attributes
+                    .iter()
+                    .any(|a| a.name == "created_at" && a.attr_type == "STRING" && !a.required),
+                "Category '{}' missing created_at attribute",
+                schema.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_predefined_schemas_have_content() {
+        for schema in PREDEFINED_SCHEMAS {
+            assert!(
+                schema
+                    .attributes
+                    .iter()
+                    .any(|a| a.name == "content" && a.attr_type == "STRING"),
+                "Category '{}' missing content attribute",
+                schema.name
+            );
         }
     }
 
@@ -1550,8 +2775,35 @@ mod tests {
         );
     }
 
+    // --- propose_schema ---
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_propose_schema() {
+        let mock = MockLlmClient::new(vec![
+            r#"{"category":"Books Read","description":"Books I have finished","attributes":[{"name":"title","type":"STRING","required":true},{"name":"rating","type":"number","required":false},{"name":"created_at","type":"STRING","required":false}],"suggested_indexes":["author","rating"],"hints":null}"#.into(),
+            r#"{"category":"x","description":"x","attributes":[{"name":"a","type":"DATE","required":true}],"suggested_indexes":[]}"#.into(),
+        ]);
+        let (category, definition) = propose_schema(&mock, "track books I've read with rating")
+            .await
+            .unwrap();
+        assert_eq!(category, "books-read");
+        let names: Vec<&str> = definition
+            .attributes
+            .iter()
+            .map(|a| a.name.as_str())
+            .collect();
+        assert_eq!(names, ["title", "rating"]);
+        assert_eq!(definition.attributes[1].attr_type, "NUMBER");
+        assert_eq!(definition.suggested_indexes, ["rating"]);
+        assert!(definition.hints.is_none());
+
+        assert!(propose_schema(&mock, "anything").await.is_err());
+    }
+
     // --- parse_to_document ---
 
+    #[cfg(feature = "llm")]
     #[tokio::test]
     async fn test_parse_to_document_success() {
         let mock = MockLlmClient::new(vec![
@@ -1586,6 +2838,8 @@ mod tests {
             &mock,
             "contacts",
             &schema,
+            &AttributeAliases::default(),
+            None,
             "Toby is a backend engineer, email toby@example.com",
         )
         .await
@@ -1595,6 +2849,7 @@ mod tests {
         assert_eq!(doc["email"], "toby@example.com");
     }
 
+    #[cfg(feature = "llm")]
     #[tokio::test]
     async fn test_parse_to_document_with_fences() {
         let mock = MockLlmClient::new(vec![
@@ -1612,14 +2867,220 @@ mod tests {
             validate: true,
         };
 
-        let doc = parse_to_document(&mock, "contacts", &schema, "Toby")
-            .await
-            .unwrap();
+        let doc = parse_to_document(
+            &mock,
+            "contacts",
+            &schema,
+            &AttributeAliases::default(),
+            None,
+            "Toby",
+        )
+        .await
+        .unwrap();
         assert_eq!(doc["key"], "toby");
     }
 
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_parse_to_document_slugifies_key() {
+        let mock = MockLlmClient::new(vec![
+            r#"{"key":"Zoë Smith","name":"Zoë"}"#.into(),
+            r#"{"name":"Staging"}"#.into(),
+        ]);
+        let schema = PartitionSchemaInfo {
+            prefix: "contacts".into(),
+            description: "People".into(),
+            attributes: vec![],
+            validate: false,
+        };
+
+        let doc = parse_to_document(
+            &mock,
+            "contacts",
+            &schema,
+            &AttributeAliases::default(),
+            None,
+            "Zoë",
+        )
+        .await
+        .unwrap();
+        assert_eq!(doc["key"], "zoe-smith");
+
+        // No proposed key: derived from the input.
+        let doc = parse_to_document(
+            &mock,
+            "contacts",
+            &schema,
+            &AttributeAliases::default(),
+            None,
+            "Staging server URL",
+        )
+        .await
+        .unwrap();
+        assert_eq!(doc["key"], "staging-server-url");
+    }
+
+    #[cfg(feature = "llm")]
+    #[test]
+    fn test_describe_categories_includes_aliases_and_hints() {
+        let schemas = vec![PartitionSchemaInfo {
+            prefix: "contacts".into(),
+            description: "People".into(),
+            attributes: vec![AttributeInfo {
+                name: "team".into(),
+                attr_type: "STRING".into(),
+                required: false,
+            }],
+            validate: false,
+        }];
+        let hints = BTreeMap::from([("contacts".to_string(), "key is the first name".to_string())]);
+        let desc = describe_categories(&schemas, &AttributeAliases::predefined(), &hints);
+        assert!(desc.contains("- team (STRING; also called group, org)"));
+        assert!(desc.contains("Hints: key is the first name"));
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_parse_to_document_canonicalizes_aliases() {
+        let mock = MockLlmClient::new(vec![
+            r#"{"key":"toby","name":"Toby","org":"infra","title":"SRE"}"#.into(),
+        ]);
+        let schema = PartitionSchemaInfo {
+            prefix: "contacts".into(),
+            description: "People".into(),
+            attributes: vec![],
+            validate: false,
+        };
+
+        let doc = parse_to_document(
+            &mock,
+            "contacts",
+            &schema,
+            &AttributeAliases::predefined(),
+            None,
+            "Toby is an SRE in the infra org",
+        )
+        .await
+        .unwrap();
+        assert_eq!(doc["team"], "infra");
+        assert_eq!(doc["role"], "SRE");
+        assert!(doc.get("org").is_none());
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_parse_to_document_normalizes_related() {
+        let mock = MockLlmClient::new(vec![
+            r#"{"key":"auth-method","decision":"JWT","related":["contacts/Toby","nonsense"]}"#
+                .into(),
+        ]);
+        let schema = PartitionSchemaInfo {
+            prefix: "decisions".into(),
+            description: "Decisions".into(),
+            attributes: vec![],
+            validate: false,
+        };
+
+        let doc = parse_to_document(
+            &mock,
+            "decisions",
+            &schema,
+            &AttributeAliases::default(),
+            None,
+            "Toby picked JWT for auth",
+        )
+        .await
+        .unwrap();
+        assert_eq!(doc["related"], serde_json::json!(["contacts/toby"]));
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_parse_facts_batch() {
+        let mock = MockLlmClient::new(vec![
+            r#"[
+                {"fact": 2, "category": "contacts", "key": "Toby Smith", "name": "Toby"},
+                {"fact": 1, "category": "notes", "key": "parking", "ttl": "1w"},
+                {"fact": 3, "category": "invented", "key": "x"},
+                {"fact": 9, "category": "notes", "key": "y"}
+            ]"#
+            .into(),
+        ]);
+        let schemas: Vec<PartitionSchemaInfo> = ["notes", "contacts"]
+            .into_iter()
+            .map(|prefix| PartitionSchemaInfo {
+                prefix: prefix.into(),
+                description: prefix.into(),
+                attributes: vec![],
+                validate: false,
+            })
+            .collect();
+        let facts: Vec<String> = [
+            "parked on level 3 for a week",
+            "Toby is on the infra team",
+            "?",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        let docs = parse_facts_batch(
+            &mock,
+            &schemas,
+            &AttributeAliases::default(),
+            &BTreeMap::new(),
+            &facts,
+        )
+        .await
+        .unwrap();
+        assert_eq!(docs.len(), 3);
+        let parking = docs[0].as_ref().unwrap();
+        assert_eq!(parking["key"], "parking");
+        assert_eq!(parking["ttl"], "1w");
+        assert!(parking.get("fact").is_none());
+        assert_eq!(docs[1].as_ref().unwrap()["key"], "toby-smith");
+        assert!(docs[2].is_none());
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_parse_extracts_ttl() {
+        let mock = MockLlmClient::new(vec![
+            r#"{"category":"notes","key":"parking","content":"Level 3","ttl":"1 w"}"#.into(),
+            r#"{"category":"notes","key":"wifi","content":"hunter2","ttl":"a while"}"#.into(),
+        ]);
+        let schemas = vec![PartitionSchemaInfo {
+            prefix: "notes".into(),
+            description: "Notes".into(),
+            attributes: vec![],
+            validate: false,
+        }];
+
+        let doc = parse_to_document_with_category(
+            &mock,
+            &schemas,
+            &AttributeAliases::default(),
+            &BTreeMap::new(),
+            "parked on level 3, for a week",
+        )
+        .await
+        .unwrap();
+        assert_eq!(doc["ttl"], "1w");
+        let doc = parse_to_document_with_category(
+            &mock,
+            &schemas,
+            &AttributeAliases::default(),
+            &BTreeMap::new(),
+            "wifi password is hunter2",
+        )
+        .await
+        .unwrap();
+        assert!(doc.get("ttl").is_none());
+    }
+
     // --- resolve_query ---
 
+    #[cfg(feature = "llm")]
     #[tokio::test]
     async fn test_resolve_query_index_lookup() {
         let mock = MockLlmClient::new(vec![
@@ -1660,6 +3121,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "llm")]
     #[tokio::test]
     async fn test_resolve_query_partition_scan() {
         let mock = MockLlmClient::new(vec![
@@ -1688,6 +3150,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "llm")]
     #[tokio::test]
     async fn test_resolve_query_exact_lookup() {
         let mock = MockLlmClient::new(vec![
@@ -1713,6 +3176,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "llm")]
     #[tokio::test]
     async fn test_resolve_query_with_markdown_fences() {
         let mock = MockLlmClient::new(vec![
@@ -1744,6 +3208,7 @@ mod tests {
 
     // --- classify_intent ---
 
+    #[cfg(feature = "llm")]
     #[tokio::test]
     async fn test_classify_intent_remember() {
         let mock = MockLlmClient::new(vec![
@@ -1761,6 +3226,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "llm")]
     #[tokio::test]
     async fn test_classify_intent_recall() {
         let mock = MockLlmClient::new(vec![
@@ -1778,6 +3244,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "llm")]
     #[tokio::test]
     async fn test_classify_intent_with_fences() {
         let mock = MockLlmClient::new(vec![
@@ -1798,6 +3265,7 @@ mod tests {
 
     // --- answer_query ---
 
+    #[cfg(feature = "llm")]
     #[tokio::test]
     async fn test_answer_query_returns_answer() {
         let mock = MockLlmClient::new(vec![
@@ -1819,6 +3287,7 @@ mod tests {
         assert!(result.unwrap().contains("12:00"));
     }
 
+    #[cfg(feature = "llm")]
     #[tokio::test]
     async fn test_answer_query_no_relevant_data() {
         let mock = MockLlmClient::new(vec!["NO_RELEVANT_DATA".into()]);
@@ -1834,4 +3303,179 @@ mod tests {
             .unwrap();
         assert!(result.is_none());
     }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_answer_query_with_follow_up() {
+        let mock = MockLlmClient::new(vec![
+            "Toby is the CTO.\nHis email is not recorded.\nFOLLOW_UP:\n- query: what is Toby's email\ncategory: Contacts\nnote: ignored\n".into(),
+            "Toby is the CTO.".into(),
+        ]);
+        let items = vec![serde_json::json!({"category": "contacts", "key": "toby", "role": "cto"})];
+
+        let answer = answer_query_with_follow_up(&mock, "who is toby and his email", &items)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(answer.text, "Toby is the CTO.\nHis email is not recorded.");
+        assert_eq!(answer.follow_up.queries, ["what is Toby's email"]);
+        assert_eq!(answer.follow_up.categories, ["contacts"]);
+
+        let complete = answer_query_with_follow_up(&mock, "who is toby", &items)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(complete.follow_up.is_empty());
+    }
+
+    // --- summarize_text ---
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_summarize_text_trims_response() {
+        let mock = MockLlmClient::new(vec!["  Design doc for the auth service.  \n".into()]);
+        let summary = summarize_text(&mock, &"word ".repeat(2000)).await.unwrap();
+        assert_eq!(summary, "Design doc for the auth service.");
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_summarize_text_empty_response() {
+        let mock = MockLlmClient::new(vec!["   ".into()]);
+        let result = summarize_text(&mock, "some text").await;
+        assert!(matches!(result, Err(LlmError::EmptyResponse)));
+    }
+
+    // --- summarize_category ---
+
+    #[cfg(feature = "llm")]
+    #[test]
+    fn test_batch_items_respects_limit() {
+        let items: Vec<Value> = (0..3)
+            .map(|i| serde_json::json!({"key": format!("k{i}"), "details": "x".repeat(40)}))
+            .collect();
+        let batches = batch_items(&items, 150);
+        assert_eq!(batches.len(), 2);
+        assert!(batches.iter().all(|b| b.len() <= 150));
+        assert_eq!(batches[0].lines().count(), 2);
+
+        let huge = vec![serde_json::json!({"details": "y".repeat(500)})];
+        assert_eq!(batch_items(&huge, 100)[0].chars().count(), 100);
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_summarize_category_merges_batches() {
+        let items: Vec<Value> = (0..2)
+            .map(|i| serde_json::json!({"key": format!("k{i}"), "details": "z".repeat(MAX_SUMMARY_INPUT_CHARS - 100)}))
+            .collect();
+        let mock = MockLlmClient::new(vec![
+            "First half.".into(),
+            "Second half.".into(),
+            " Both halves. ".into(),
+        ]);
+        let overview = summarize_category(&mock, "project", &items).await.unwrap();
+        assert_eq!(overview, "Both halves.");
+        assert!(mock.responses.lock().unwrap().is_empty());
+    }
+
+    #[cfg(feature = "llm")]
+    #[test]
+    fn test_group_partials_shrinks_every_level() {
+        let partials: Vec<String> = (0..5).map(|i| format!("{i}").repeat(40)).collect();
+        let groups = group_partials(partials, 100);
+        let sizes: Vec<usize> = groups.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![2, 2, 1]);
+        assert!(
+            groups
+                .iter()
+                .all(|g| g.join(PARTIAL_SEPARATOR).len() <= 100)
+        );
+
+        // Oversized partials are cut to half the limit and still paired.
+        let huge = vec!["a".repeat(500), "b".repeat(500)];
+        let groups = group_partials(huge, 100);
+        assert_eq!(groups.len(), 1);
+        assert!(groups[0].join(PARTIAL_SEPARATOR).len() <= 100);
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_summarize_text_merges_parts_in_levels() {
+        let part = "word ".repeat(MAX_SUMMARY_INPUT_CHARS / 5 - 10);
+        let text = [part.as_str(); 3].join("\n\n");
+        let long = "s".repeat(MAX_SUMMARY_INPUT_CHARS / 2);
+        let mock = MockLlmClient::new(vec![
+            long.clone(),
+            long.clone(),
+            long,
+            "First two.".into(),
+            "Whole document.".into(),
+        ]);
+        let summary = summarize_text(&mock, &text).await.unwrap();
+        assert_eq!(summary, "Whole document.");
+        // Three part summaries, too long to merge in one call: the first two
+        // are merged, then the result with the third.
+        let requests = mock.requests.lock().unwrap();
+        assert_eq!(requests.len(), 5);
+        assert!(requests[4].1.starts_with("Part summaries:\n\nFirst two."));
+    }
+
+    // --- detect_conflicts ---
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_detect_conflicts_keeps_known_keys() {
+        let mock = MockLlmClient::new(vec![r#"["indent-style", "made-up"]"#.into()]);
+        let item = serde_json::json!({"key": "tabs", "preference": "prefers tabs"});
+        let existing = vec![
+            serde_json::json!({"key": "indent-style", "preference": "prefers spaces"}),
+            serde_json::json!({"key": "editor", "preference": "uses helix"}),
+        ];
+        let keys = detect_conflicts(&mock, &item, &existing).await.unwrap();
+        assert_eq!(keys, vec!["indent-style"]);
+
+        // No candidates: no LLM call.
+        let idle = MockLlmClient::new(vec![]);
+        assert!(
+            detect_conflicts(&idle, &item, &[])
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    // --- extract_commit_memories ---
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_extract_commit_memories_filters_invalid() {
+        let mock = MockLlmClient::new(vec![
+            r#"[
+                {"commit": "abc", "category": "decisions", "key": "use-wal", "domain": "storage"},
+                {"commit": "zzz", "category": "decisions", "key": "unknown-commit"},
+                {"commit": "abc", "category": "contacts", "key": "wrong-category"}
+            ]"#
+            .into(),
+        ]);
+        let schemas = vec![PartitionSchemaInfo {
+            prefix: "decisions".into(),
+            description: "Decisions".into(),
+            attributes: vec![],
+            validate: true,
+        }];
+        let commits = vec![CommitInfo {
+            sha: "abc".into(),
+            author: "Ada".into(),
+            date: "2026-01-01T00:00:00+00:00".into(),
+            subject: "Switch to WAL mode".into(),
+            body: "Concurrent readers no longer block the writer.".into(),
+        }];
+
+        let docs = extract_commit_memories(&mock, &schemas, &commits)
+            .await
+            .unwrap();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0]["key"], "use-wal");
+    }
 }
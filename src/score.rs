@@ -0,0 +1,325 @@
+//! Importance and decay scoring.
+//!
+//! Every item carries an [`IMPORTANCE_ATTRIBUTE`] from 1 (trivia) to 5
+//! (critical), estimated by the LLM when a memory is parsed and adjustable
+//! afterwards. Reads stamp [`LAST_ACCESSED_ATTRIBUTE`]. [`decay_score`]
+//! combines the two: importance scaled to `0..=1`, halved for every
+//! [`DECAY_HALF_LIFE`] since the item was last accessed (or created). Recall
+//! can sort by the score, prune can drop low-scoring short-term items, and
+//! [`score_distribution`] summarizes a category for `fmemory stats`.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use tracing::warn;
+
+use crate::SYSTEM_CATEGORIES;
+use crate::backend::MemoryBackend;
+use crate::chunk::{delete_chunks, is_chunk};
+use crate::error::MemoryError;
+use crate::sort::UPDATED_AT_ATTRIBUTE;
+
+/// Attribute holding an item's importance, 1 to 5.
+pub const IMPORTANCE_ATTRIBUTE: &str = "importance";
+
+/// Attribute stamped with the RFC 3339 time of the item's last read.
+pub const LAST_ACCESSED_ATTRIBUTE: &str = "last_accessed";
+
+pub const MIN_IMPORTANCE: u8 = 1;
+pub const MAX_IMPORTANCE: u8 = 5;
+
+/// Importance assumed for items without one.
+pub const DEFAULT_IMPORTANCE: u8 = 3;
+
+/// Time for an untouched item's score to halve.
+pub const DECAY_HALF_LIFE: Duration = Duration::days(30);
+
+/// Number of equal-width buckets [`score_distribution`] splits `0..=1` into.
+pub const SCORE_BUCKETS: usize = 5;
+
+/// Maximum number of items read per category when ranking or pruning by score.
+pub const SCORE_SCAN_LIMIT: usize = 10_000;
+
+/// Parse an importance given on the command line or by a tool call.
+pub fn parse_importance(s: &str) -> Result<u8, String> {
+    s.trim()
+        .parse::<u8>()
+        .ok()
+        .filter(|n| (MIN_IMPORTANCE..=MAX_IMPORTANCE).contains(n))
+        .ok_or_else(|| {
+            format!(
+                "importance must be an integer from {MIN_IMPORTANCE} to {MAX_IMPORTANCE}, got '{s}'"
+            )
+        })
+}
+
+/// An item's importance, clamped to the valid range; [`DEFAULT_IMPORTANCE`]
+/// when missing or not a number.
+pub fn importance(item: &Value) -> u8 {
+    item[IMPORTANCE_ATTRIBUTE]
+        .as_f64()
+        .map_or(DEFAULT_IMPORTANCE, |n| {
+            n.round()
+                .clamp(f64::from(MIN_IMPORTANCE), f64::from(MAX_IMPORTANCE)) as u8
+        })
+}
+
+/// Normalize an LLM-estimated importance in place: numbers are rounded and
+/// clamped, anything else is dropped.
+pub fn clamp_importance(doc: &mut Value) {
+    let Some(obj) = doc.as_object_mut() else {
+        return;
+    };
+    match obj.get(IMPORTANCE_ATTRIBUTE) {
+        Some(Value::Number(_)) => {
+            let value = importance(&Value::Object(obj.clone()));
+            obj.insert(IMPORTANCE_ATTRIBUTE.to_string(), Value::from(value));
+        }
+        Some(_) => {
+            obj.remove(IMPORTANCE_ATTRIBUTE);
+        }
+        None => {}
+    }
+}
+
+/// When the item was last read or written: the latest of its
+/// `last_accessed`, `updated_at` and `created_at`.
+pub fn last_active(item: &Value) -> Option<DateTime<Utc>> {
+    [LAST_ACCESSED_ATTRIBUTE, UPDATED_AT_ATTRIBUTE, "created_at"]
+        .iter()
+        .filter_map(|attr| item[*attr].as_str())
+        .filter_map(|ts| DateTime::parse_from_rfc3339(ts).ok())
+        .map(|ts| ts.with_timezone(&Utc))
+        .max()
+}
+
+/// Importance scaled to `0..=1`, decayed by time since last activity.
+///
+/// Items without timestamps do not decay.
+pub fn decay_score(item: &Value, now: DateTime<Utc>) -> f64 {
+    let weight = f64::from(importance(item)) / f64::from(MAX_IMPORTANCE);
+    let Some(active) = last_active(item) else {
+        return weight;
+    };
+    let age = (now - active).num_seconds().max(0) as f64;
+    let half_life = DECAY_HALF_LIFE.num_seconds() as f64;
+    weight * 0.5f64.powf(age / half_life)
+}
+
+/// Sort items by [`decay_score`], highest first.
+pub fn sort_by_score(items: &mut [Value], now: DateTime<Utc>) {
+    items.sort_by(|a, b| decay_score(b, now).total_cmp(&decay_score(a, now)));
+}
+
+/// Stamp [`LAST_ACCESSED_ATTRIBUTE`] on items that were just read.
+///
+/// Each item is re-read and written back under
+/// [`MemoryBackend::update_item`]'s lock, so hydrated chunk content is not
+/// persisted and concurrent updates are not lost. Chunks and
+/// [`SYSTEM_CATEGORIES`] are skipped; failures are logged rather than failing
+/// the read.
+pub async fn record_access(backend: &MemoryBackend, items: &[Value]) {
+    let now = Utc::now().to_rfc3339();
+    for item in items.iter().filter(|i| !is_chunk(i)) {
+        let (Some(category), Some(key)) = (item["category"].as_str(), item["key"].as_str()) else {
+            continue;
+        };
+        if SYSTEM_CATEGORIES.contains(&category) {
+            continue;
+        }
+        let result = async {
+            let _held = backend.lock_updates().await;
+            if let Some(mut stored) = backend.get_item(category, key).await? {
+                stored[LAST_ACCESSED_ATTRIBUTE] = Value::String(now.clone());
+                backend.put_bookkeeping_item(stored).await?;
+            }
+            Ok::<_, MemoryError>(())
+        };
+        if let Err(e) = result.await {
+            warn!("Failed to record access to {category}/{key}: {e}");
+        }
+    }
+}
+
+/// Delete short-term items (those with `expires_at`) scoring below
+/// `threshold`, with their chunks. Long-term items are never touched.
+///
+/// Returns the `(category, key)` of each deleted item.
+pub async fn prune_low_score(
+    backend: &MemoryBackend,
+    categories: &[String],
+    threshold: f64,
+    now: DateTime<Utc>,
+) -> Result<Vec<(String, String)>, MemoryError> {
+    let mut pruned = Vec::new();
+    for category in categories {
+        let items = backend.query(category, None, SCORE_SCAN_LIMIT).await?;
+        for item in items.iter().filter(|i| !is_chunk(i)) {
+            let short_term = item["expires_at"].is_string();
+            if short_term
+                && decay_score(item, now) < threshold
+                && let Some(key) = item["key"].as_str()
+            {
+                delete_chunks(backend, item).await?;
+                backend.delete_item(category, key).await?;
+                pruned.push((category.clone(), key.to_string()));
+            }
+        }
+    }
+    Ok(pruned)
+}
+
+/// Importance and score spread of a set of items.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ScoreDistribution {
+    pub items: usize,
+    /// Item count per importance level; items without one count as
+    /// [`DEFAULT_IMPORTANCE`].
+    pub by_importance: BTreeMap<u8, usize>,
+    /// Item count per score bucket, lowest first: bucket `i` holds scores in
+    /// `[i / SCORE_BUCKETS, (i + 1) / SCORE_BUCKETS)`, the last one including 1.
+    pub by_score: [usize; SCORE_BUCKETS],
+    pub mean_score: f64,
+}
+
+/// Summarize importance and decay scores of `items`, ignoring chunks.
+pub fn score_distribution(items: &[Value], now: DateTime<Utc>) -> ScoreDistribution {
+    let mut dist = ScoreDistribution::default();
+    let mut total = 0.0;
+    for item in items.iter().filter(|i| !is_chunk(i)) {
+        let score = decay_score(item, now);
+        dist.items += 1;
+        *dist.by_importance.entry(importance(item)).or_default() += 1;
+        let bucket = ((score * SCORE_BUCKETS as f64) as usize).min(SCORE_BUCKETS - 1);
+        dist.by_score[bucket] += 1;
+        total += score;
+    }
+    if dist.items > 0 {
+        dist.mean_score = total / dist.items as f64;
+    }
+    dist
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    fn at(days_ago: i64) -> String {
+        (Utc::now() - Duration::days(days_ago)).to_rfc3339()
+    }
+
+    #[test]
+    fn test_importance_parsing_and_clamping() {
+        assert_eq!(parse_importance("4").unwrap(), 4);
+        assert!(parse_importance("0").is_err());
+        assert!(parse_importance("6").is_err());
+        assert!(parse_importance("high").is_err());
+
+        assert_eq!(importance(&json!({})), DEFAULT_IMPORTANCE);
+        assert_eq!(importance(&json!({"importance": 9})), MAX_IMPORTANCE);
+        assert_eq!(importance(&json!({"importance": 1.6})), 2);
+
+        let mut doc = json!({"importance": 7.2});
+        clamp_importance(&mut doc);
+        assert_eq!(doc["importance"], 5);
+        let mut doc = json!({"importance": "very"});
+        clamp_importance(&mut doc);
+        assert!(doc.get("importance").is_none());
+    }
+
+    #[test]
+    fn test_decay_score() {
+        let now = Utc::now();
+        let fresh = json!({"importance": 5, "created_at": now.to_rfc3339()});
+        assert!((decay_score(&fresh, now) - 1.0).abs() < 1e-9);
+
+        let month_old = json!({"importance": 5, "created_at": at(30)});
+        assert!((decay_score(&month_old, now) - 0.5).abs() < 1e-3);
+
+        // A recent read or write resets the decay.
+        let accessed = json!({"importance": 5, "created_at": at(90), "last_accessed": at(0)});
+        assert!(decay_score(&accessed, now) > 0.99);
+        let updated = json!({"importance": 5, "created_at": at(90), "last_accessed": at(60), "updated_at": at(0)});
+        assert!(decay_score(&updated, now) > 0.99);
+
+        let mut items = vec![
+            json!({"key": "old", "importance": 5, "created_at": at(20)}),
+            json!({"key": "new-minor", "importance": 1, "created_at": at(0)}),
+            json!({"key": "new-major", "importance": 4, "created_at": at(1)}),
+        ];
+        sort_by_score(&mut items, now);
+        let keys: Vec<&str> = items.iter().filter_map(|i| i["key"].as_str()).collect();
+        assert_eq!(keys, vec!["new-major", "old", "new-minor"]);
+    }
+
+    #[test]
+    fn test_score_distribution() {
+        let now = Utc::now();
+        let items = vec![
+            json!({"importance": 5, "created_at": now.to_rfc3339()}),
+            json!({"importance": 1, "created_at": now.to_rfc3339()}),
+            json!({"created_at": now.to_rfc3339()}),
+            json!({"chunk_of": "x"}),
+        ];
+        let dist = score_distribution(&items, now);
+        assert_eq!(dist.items, 3);
+        assert_eq!(dist.by_importance, BTreeMap::from([(1, 1), (3, 1), (5, 1)]));
+        assert_eq!(dist.by_score, [0, 1, 0, 1, 1]);
+        assert!((dist.mean_score - 0.6).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_record_access_and_prune_low_score() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        let expires = (Utc::now() + Duration::days(1)).to_rfc3339();
+        for (key, importance, expires_at) in [
+            ("stale-minor", 1, Some(&expires)),
+            ("stale-major", 5, Some(&expires)),
+            ("stale-ltm", 1, None),
+        ] {
+            backend
+                .put_item(json!({
+                    "category": "scratchpad",
+                    "key": key,
+                    "importance": importance,
+                    "created_at": at(60),
+                    "expires_at": expires_at,
+                }))
+                .await
+                .unwrap();
+        }
+
+        let major = backend
+            .get_item("scratchpad", "stale-major")
+            .await
+            .unwrap()
+            .unwrap();
+        record_access(&backend, &[major]).await;
+        let major = backend
+            .get_item("scratchpad", "stale-major")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(major[LAST_ACCESSED_ATTRIBUTE].is_string());
+
+        let pruned = prune_low_score(&backend, &["scratchpad".to_string()], 0.2, Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(
+            pruned,
+            vec![("scratchpad".to_string(), "stale-minor".to_string())]
+        );
+        assert_eq!(
+            backend.query("scratchpad", None, 10).await.unwrap().len(),
+            2
+        );
+    }
+}
@@ -0,0 +1,281 @@
+//! Keyword search over items, in one namespace or across all of them.
+//!
+//! [`search_items`] is a case-insensitive substring match over every
+//! category of a namespace, without the LLM. [`search_all_namespaces`] runs
+//! it on the default namespace and every known one, up to
+//! [`MAX_CONCURRENT_CATEGORY_OPS`] namespaces in flight at once, and labels
+//! each match with its namespace in [`NAMESPACE_LABEL`].
+//!
+//! ferridyn-server cannot list tables, so namespaces are known from a
+//! registry: a [`NAMESPACES_CATEGORY`] partition of the default table with
+//! one item per namespace, recorded by [`register_namespace`] whenever a
+//! backend is connected to one and whenever an MCP tool call first selects
+//! one. Unlike listing the `memories_*` tables, the registry misses tables
+//! never reached that way: those last used before the registry existed,
+//! written by other clients of the server, or only switched to with
+//! [`MemoryStore::with_namespace`](crate::store::MemoryStore::with_namespace).
+//! Such a namespace is searched once it is used through one of the paths
+//! above.
+
+use futures::stream::{self, StreamExt};
+use serde_json::{Value, json};
+
+use crate::backend::{MAX_CONCURRENT_CATEGORY_OPS, MemoryBackend};
+use crate::chunk::is_chunk;
+use crate::error::MemoryError;
+use crate::ttl::is_expired;
+use crate::{SYSTEM_CATEGORIES, resolve_table_name};
+
+/// Partition of the default table listing known namespaces.
+pub const NAMESPACES_CATEGORY: &str = "_namespaces";
+
+/// Attribute added to cross-namespace matches, naming the namespace they
+/// were found in (`null` for the default one). Never stored.
+pub const NAMESPACE_LABEL: &str = "_namespace";
+
+/// Maximum number of categories searched per namespace.
+pub const SEARCH_MAX_CATEGORIES: usize = 1000;
+
+/// Maximum number of items scanned per category.
+pub const SEARCH_SCAN_LIMIT: usize = 10_000;
+
+/// Maximum number of namespaces read from the registry.
+const NAMESPACE_SCAN_LIMIT: usize = 10_000;
+
+/// Record `namespace` in the registry of the default table behind
+/// `backend`, unless it is already there.
+pub async fn register_namespace(
+    backend: &MemoryBackend,
+    namespace: &str,
+) -> Result<(), MemoryError> {
+    let registry = default_table(backend);
+    if registry
+        .get_item(NAMESPACES_CATEGORY, namespace)
+        .await?
+        .is_some()
+    {
+        return Ok(());
+    }
+    registry
+        .put_system_item(json!({
+            "category": NAMESPACES_CATEGORY,
+            "key": namespace,
+            "table": resolve_table_name(Some(namespace)),
+            "registered_at": chrono::Utc::now().to_rfc3339(),
+        }))
+        .await
+}
+
+/// Remove `namespace` from the registry, e.g. once its table is emptied.
+pub async fn unregister_namespace(
+    backend: &MemoryBackend,
+    namespace: &str,
+) -> Result<(), MemoryError> {
+    default_table(backend)
+        .delete_item(NAMESPACES_CATEGORY, namespace)
+        .await
+}
+
+/// Registered namespaces, in key order.
+pub async fn known_namespaces(backend: &MemoryBackend) -> Result<Vec<String>, MemoryError> {
+    Ok(default_table(backend)
+        .query(NAMESPACES_CATEGORY, None, NAMESPACE_SCAN_LIMIT)
+        .await?
+        .iter()
+        .filter_map(|item| item["key"].as_str().map(String::from))
+        .collect())
+}
+
+/// Up to `limit` items of `backend` containing `query`, case-insensitively,
+/// in the string value of any attribute but `category`, including strings
+/// nested in arrays and objects. Attribute names never match. Chunks, the
+/// [`SYSTEM_CATEGORIES`], and expired items (unless `include_expired`) are
+/// skipped.
+pub async fn search_items(
+    backend: &MemoryBackend,
+    query: &str,
+    limit: usize,
+    include_expired: bool,
+) -> Result<Vec<Value>, MemoryError> {
+    let needle = query.trim().to_lowercase();
+    let mut matches = Vec::new();
+    if needle.is_empty() {
+        return Ok(matches);
+    }
+    for partition in backend.list_partition_keys(SEARCH_MAX_CATEGORIES).await? {
+        let Some(category) = partition.as_str() else {
+            continue;
+        };
+        if SYSTEM_CATEGORIES.contains(&category) {
+            continue;
+        }
+        let items = backend.query(category, None, SEARCH_SCAN_LIMIT).await?;
+        matches.extend(
+            items
+                .into_iter()
+                .filter(|item| !is_chunk(item) && (include_expired || !is_expired(item)))
+                .filter(|item| item_contains(item, &needle)),
+        );
+        if matches.len() >= limit {
+            break;
+        }
+    }
+    matches.truncate(limit);
+    Ok(matches)
+}
+
+/// Whether a string value of `item`, other than its category, contains the
+/// lowercase `needle`.
+fn item_contains(item: &Value, needle: &str) -> bool {
+    match item.as_object() {
+        Some(attributes) => attributes
+            .iter()
+            .any(|(name, value)| name != "category" && value_contains(value, needle)),
+        None => value_contains(item, needle),
+    }
+}
+
+/// Whether `value`, or a string nested anywhere in it, contains `needle`.
+fn value_contains(value: &Value, needle: &str) -> bool {
+    match value {
+        Value::String(text) => text.to_lowercase().contains(needle),
+        Value::Array(values) => values.iter().any(|v| value_contains(v, needle)),
+        Value::Object(map) => map.values().any(|v| value_contains(v, needle)),
+        _ => false,
+    }
+}
+
+/// [`search_items`] in the default namespace and every registered one, up to
+/// [`MAX_CONCURRENT_CATEGORY_OPS`] namespaces in flight at once and `limit`
+/// matches per namespace. Matches are grouped by
+/// namespace, default first, and labeled with [`NAMESPACE_LABEL`]. A
+/// namespace that fails to search is skipped with a warning.
+pub async fn search_all_namespaces(
+    backend: &MemoryBackend,
+    query: &str,
+    limit: usize,
+    include_expired: bool,
+) -> Result<Vec<Value>, MemoryError> {
+    let mut namespaces = vec![None];
+    namespaces.extend(known_namespaces(backend).await?.into_iter().map(Some));
+    let results: Vec<_> = stream::iter(namespaces)
+        .map(|namespace| async move {
+            let mut target = backend.clone();
+            target.table_name = resolve_table_name(namespace.as_deref());
+            let found = search_items(&target, query, limit, include_expired).await;
+            (namespace, found)
+        })
+        .buffered(MAX_CONCURRENT_CATEGORY_OPS)
+        .collect()
+        .await;
+
+    let mut merged = Vec::new();
+    for (namespace, found) in results {
+        match found {
+            Ok(items) => merged.extend(items.into_iter().map(|mut item| {
+                item[NAMESPACE_LABEL] = json!(namespace);
+                item
+            })),
+            Err(e) => tracing::warn!(
+                "Skipping namespace {}: {e}",
+                namespace.as_deref().unwrap_or("(default)")
+            ),
+        }
+    }
+    Ok(merged)
+}
+
+/// `backend` retargeted at the default table, where the registry lives.
+fn default_table(backend: &MemoryBackend) -> MemoryBackend {
+    let mut registry = backend.clone();
+    registry.table_name = resolve_table_name(None);
+    registry
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+
+    #[tokio::test]
+    async fn test_search_items_matches_values_not_attribute_names() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        backend
+            .put_item(json!({
+                "category": "notes",
+                "key": "quote",
+                "content": "She said \"ship it\"",
+                "pinned": true,
+                "tags": ["Release", {"owner": "dana"}],
+            }))
+            .await
+            .unwrap();
+        backend
+            .put_system_item(
+                json!({"category": crate::links::LINKS_CATEGORY, "key": "x", "note": "ship"}),
+            )
+            .await
+            .unwrap();
+
+        let found = |query: &'static str| {
+            let backend = backend.clone();
+            async move { search_items(&backend, query, 10, false).await.unwrap() }
+        };
+        for name in ["content", "category", "key", "pinned", "true", "notes"] {
+            assert!(found(name).await.is_empty(), "{name} matched");
+        }
+        assert_eq!(found("\"ship it\"").await.len(), 1);
+        assert_eq!(found("release").await.len(), 1);
+        assert_eq!(found("DANA").await.len(), 1);
+        assert_eq!(found("quote").await.len(), 1);
+        assert_eq!(found("ship").await[0]["key"], "quote");
+        assert_eq!(found("ship").await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_all_namespaces() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        backend
+            .put_item(json!({"category": "notes", "key": "standup", "content": "Standup at 10"}))
+            .await
+            .unwrap();
+        let mut work = backend.clone();
+        work.table_name = resolve_table_name(Some("work"));
+        work.put_item(json!({"category": "decisions", "key": "db", "content": "Postgres for the STANDUP bot"}))
+            .await
+            .unwrap();
+        work.put_item(json!({"category": "notes", "key": "lunch", "content": "Tacos"}))
+            .await
+            .unwrap();
+
+        register_namespace(&work, "work").await.unwrap();
+        register_namespace(&backend, "work").await.unwrap();
+        assert_eq!(known_namespaces(&work).await.unwrap(), ["work"]);
+        register_namespace(&backend, "old").await.unwrap();
+        unregister_namespace(&backend, "old").await.unwrap();
+        assert_eq!(known_namespaces(&backend).await.unwrap(), ["work"]);
+
+        let own = search_items(&backend, "standup", 10, false).await.unwrap();
+        assert_eq!(own.len(), 1);
+
+        let all = search_all_namespaces(&backend, "standup", 10, false)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0]["key"], "standup");
+        assert_eq!(all[0][NAMESPACE_LABEL], Value::Null);
+        assert_eq!(all[1]["key"], "db");
+        assert_eq!(all[1][NAMESPACE_LABEL], "work");
+
+        assert!(
+            search_items(&backend, "  ", 10, false)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
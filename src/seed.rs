@@ -0,0 +1,163 @@
+//! Curated example memories for demos and reproducible test setups.
+//!
+//! [`seed_items`] returns a fixed set of items for a [`SeedProfile`], with
+//! deterministic keys and `created_at`/`updated_at` stamps, covering every
+//! predefined category; [`seed`] writes them. Seeded items carry no
+//! `expires_at`, so short-term ones (`scratchpad`, `sessions`,
+//! `interactions`) stay put until forgotten. `related` references between
+//! them make `recall --related` and `export` show something.
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::backend::MemoryBackend;
+use crate::error::MemoryError;
+
+/// Timestamp stamped on every seeded item.
+pub const SEED_TIMESTAMP: &str = "2025-01-06T09:00:00+00:00";
+
+/// A set of example memories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedProfile {
+    /// A small web team's project: people, decisions, tools, and a bug.
+    Demo,
+}
+
+/// What [`seed`] wrote.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SeedReport {
+    /// `category/key` of each item written.
+    pub written: Vec<String>,
+    /// `category/key` of each item left alone because it already existed.
+    pub skipped: Vec<String>,
+}
+
+/// Write the items of `profile`. Existing items with the same key are left
+/// alone unless `overwrite`.
+pub async fn seed(
+    backend: &MemoryBackend,
+    profile: SeedProfile,
+    overwrite: bool,
+) -> Result<SeedReport, MemoryError> {
+    let mut report = SeedReport::default();
+    for item in seed_items(profile) {
+        let category = item["category"].as_str().unwrap_or_default();
+        let key = item["key"].as_str().unwrap_or_default();
+        let item_ref = format!("{category}/{key}");
+        if !overwrite && backend.get_item(category, key).await?.is_some() {
+            report.skipped.push(item_ref);
+            continue;
+        }
+        backend.put_item(item).await?;
+        report.written.push(item_ref);
+    }
+    Ok(report)
+}
+
+/// The items of `profile`, stamped with [`SEED_TIMESTAMP`].
+pub fn seed_items(profile: SeedProfile) -> Vec<Value> {
+    let mut items = match profile {
+        SeedProfile::Demo => demo_items(),
+    };
+    for item in &mut items {
+        item["created_at"] = json!(SEED_TIMESTAMP);
+        item["updated_at"] = json!(SEED_TIMESTAMP);
+    }
+    items
+}
+
+fn demo_items() -> Vec<Value> {
+    vec![
+        json!({"category": "project", "key": "overview", "topic": "overview", "area": "product",
+            "details": "Acme Shop: a Rust storefront API with a React admin", "importance": 5}),
+        json!({"category": "project", "key": "deploy-flow", "topic": "deployment", "area": "ops",
+            "details": "main deploys to staging on merge; tagged releases go to production"}),
+        json!({"category": "decisions", "key": "auth-method", "title": "Auth method", "domain": "backend",
+            "decision": "JWT access tokens with 15 minute expiry", "rationale": "Stateless API servers behind the load balancer",
+            "importance": 5, "related": ["contacts/toby"]}),
+        json!({"category": "decisions", "key": "database", "title": "Primary database", "domain": "backend",
+            "decision": "PostgreSQL 16", "rationale": "Team experience and JSONB for product attributes",
+            "importance": 4, "related": ["contacts/ann"]}),
+        json!({"category": "contacts", "key": "toby", "name": "Toby", "email": "toby@example.com",
+            "role": "CTO", "team": "platform", "notes": "Owns auth and infrastructure decisions"}),
+        json!({"category": "contacts", "key": "ann", "name": "Ann", "email": "ann@example.com",
+            "role": "Backend engineer", "team": "platform"}),
+        json!({"category": "contacts", "key": "raj", "name": "Raj", "email": "raj@example.com",
+            "role": "Designer", "team": "product"}),
+        json!({"category": "preferences", "key": "editor", "scope": "tooling", "preference": "Neovim with rust-analyzer"}),
+        json!({"category": "preferences", "key": "commit-style", "scope": "git",
+            "preference": "Imperative commit subjects under 72 characters", "importance": 4}),
+        json!({"category": "issues", "key": "checkout-timeout", "area": "checkout",
+            "symptom": "Checkout requests time out under load", "cause": "Connection pool of 5 exhausted",
+            "fix": "Raised the pool to 20 and added a 2s acquire timeout", "resolved": true,
+            "related": ["decisions/database"]}),
+        json!({"category": "tools", "key": "ci", "kind": "ci", "name": "GitHub Actions",
+            "value": ".github/workflows/ci.yml", "notes": "Runs clippy and tests on every PR"}),
+        json!({"category": "tools", "key": "staging-url", "kind": "url", "name": "Staging",
+            "value": "https://staging.example.com"}),
+        json!({"category": "events", "key": "launch-review", "title": "Launch review",
+            "date": "2025-01-15", "time": "14:00", "location": "Room 3", "notes": "Toby and Raj attending"}),
+        json!({"category": "notes", "key": "retro-ideas", "topic": "retro",
+            "content": "Try smaller PRs; pair on the payment refactor"}),
+        json!({"category": "scratchpad", "key": "cache-hypothesis", "topic": "performance",
+            "content": "Product list may be slow because the cache key ignores locale", "source": "debugging"}),
+        json!({"category": "sessions", "key": "session-2025-01-06", "project": "acme-shop",
+            "branch": "fix/checkout-timeout", "goal": "Fix checkout timeouts", "status": "ended",
+            "files_touched": "src/db/pool.rs"}),
+        json!({"category": "errors", "key": "pool-timed-out", "signature": "PoolTimedOut",
+            "language": "rust", "cause": "All connections checked out", "fix": "Raise max_connections or shorten transactions",
+            "frequency": 3, "last_seen": "2025-01-05", "confidence": 0.9}),
+        json!({"category": "architecture", "key": "api-layers", "component": "api",
+            "pattern": "Handlers call services; services own transactions", "files": "src/handlers, src/services",
+            "constraints": "Handlers never touch the pool directly"}),
+        json!({"category": "snippets", "key": "pg-pool", "language": "rust", "purpose": "Build the Postgres pool",
+            "code": "PgPoolOptions::new().max_connections(20).connect(&url).await?", "imports": "sqlx::postgres::PgPoolOptions"}),
+        json!({"category": "tasks", "key": "rotate-jwt-secret", "title": "Rotate the JWT signing secret",
+            "status": "open", "due_date": "2025-01-20", "assigned_to": "Ann", "priority": "high",
+            "related": ["decisions/auth-method"]}),
+        json!({"category": "interactions", "key": "standup-2025-01-06", "date": "2025-01-06",
+            "participants": "Toby, Ann, Raj", "summary": "Checkout fix shipped; launch review next week",
+            "action_items": "Ann rotates the JWT secret", "sentiment": "positive", "source": "standup"}),
+    ]
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use crate::schema::PREDEFINED_SCHEMAS;
+    use std::collections::BTreeSet;
+
+    #[tokio::test]
+    async fn test_seed_demo_profile() {
+        let items = seed_items(SeedProfile::Demo);
+        assert_eq!(items, seed_items(SeedProfile::Demo));
+        let categories: BTreeSet<&str> = items
+            .iter()
+            .filter_map(|i| i["category"].as_str())
+            .collect();
+        for schema in PREDEFINED_SCHEMAS {
+            assert!(categories.contains(schema.name), "{}", schema.name);
+        }
+
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        backend
+            .put_item(json!({"category": "contacts", "key": "toby", "name": "Mine"}))
+            .await
+            .unwrap();
+        let report = seed(&backend, SeedProfile::Demo, false).await.unwrap();
+        assert_eq!(report.skipped, ["contacts/toby"]);
+        assert_eq!(report.written.len(), items.len() - 1);
+        let toby = backend.get_item("contacts", "toby").await.unwrap().unwrap();
+        assert_eq!(toby["name"], "Mine");
+
+        let forced = seed(&backend, SeedProfile::Demo, true).await.unwrap();
+        assert_eq!(forced.written.len(), items.len());
+        let toby = backend.get_item("contacts", "toby").await.unwrap().unwrap();
+        assert_eq!(toby["created_at"], SEED_TIMESTAMP);
+    }
+}
@@ -0,0 +1,250 @@
+//! Session lifecycle: one `sessions` item per working session.
+//!
+//! [`start_session`] creates the item with `status: "active"` and an empty
+//! `facts` list; [`append_to_session`] records notable facts on it as they
+//! come up; [`end_session`] has the LLM condense goal and facts into the
+//! item's `content`, marks it `ended`, and restarts its 7-day TTL so the
+//! summary outlives the session by a week. Without a key, append and end act
+//! on the most recently started active session. Both write their changes
+//! through [`MemoryBackend::update_item`]'s lock, so facts appended at the
+//! same time are all kept.
+
+use chrono::{DateTime, Local, Utc};
+use serde_json::{Map, Value, json};
+use tokio::sync::MutexGuard;
+
+use crate::backend::MemoryBackend;
+use crate::error::MemoryError;
+#[cfg(feature = "llm")]
+use crate::llm::LlmClient;
+#[cfg(feature = "llm")]
+use crate::schema::summarize_session;
+#[cfg(feature = "llm")]
+use crate::store::StoreError;
+use crate::store::build_item;
+use crate::ttl::is_expired;
+#[cfg(feature = "llm")]
+use crate::ttl::{SESSIONS_DEFAULT_TTL, compute_expires_at};
+
+/// Category session items are stored in.
+pub const SESSION_CATEGORY: &str = "sessions";
+
+/// `status` of a session that has been started and not ended.
+pub const SESSION_ACTIVE: &str = "active";
+
+/// `status` of an ended session.
+pub const SESSION_ENDED: &str = "ended";
+
+/// Attribute holding the facts appended to a session, as
+/// `{"at": RFC 3339, "text": ...}` objects.
+pub const FACTS_ATTRIBUTE: &str = "facts";
+
+/// Maximum number of sessions scanned for the active one.
+const SESSION_SCAN_LIMIT: usize = 10_000;
+
+/// Key of a session started at `now`, e.g. `session-20260314-093005`.
+pub fn session_key(now: DateTime<Utc>) -> String {
+    format!("session-{}", now.format("%Y%m%d-%H%M%S"))
+}
+
+/// Create an active session under `key` (default [`session_key`]) with the
+/// attributes of `details` (`project`, `branch`, `goal`, ...). Fails if that
+/// session is already active.
+pub async fn start_session(
+    backend: &MemoryBackend,
+    key: Option<&str>,
+    details: &Value,
+) -> Result<Value, MemoryError> {
+    let key = key.map_or_else(|| session_key(Utc::now()), String::from);
+    if let Some(existing) = backend.get_item(SESSION_CATEGORY, &key).await?
+        && is_active(&existing)
+    {
+        return Err(MemoryError::InvalidParams(format!(
+            "Session '{key}' is already active"
+        )));
+    }
+    let mut doc = json!({});
+    if let Some(details) = details.as_object() {
+        for (name, value) in details.iter().filter(|(_, v)| !v.is_null()) {
+            doc[name] = value.clone();
+        }
+    }
+    doc["status"] = json!(SESSION_ACTIVE);
+    doc[FACTS_ATTRIBUTE] = json!([]);
+    let mut item = build_item(SESSION_CATEGORY, &key, &doc, None);
+    item["last_active"] = item["created_at"].clone();
+    backend.put_item(item.clone()).await?;
+    Ok(item)
+}
+
+/// The most recently started active session, if any.
+pub async fn active_session(backend: &MemoryBackend) -> Result<Option<Value>, MemoryError> {
+    Ok(backend
+        .query(SESSION_CATEGORY, None, SESSION_SCAN_LIMIT)
+        .await?
+        .into_iter()
+        .filter(is_active)
+        .max_by(|a, b| a["created_at"].as_str().cmp(&b["created_at"].as_str())))
+}
+
+/// Record `fact` on the active session `key` (default [`active_session`]).
+pub async fn append_to_session(
+    backend: &MemoryBackend,
+    key: Option<&str>,
+    fact: &str,
+) -> Result<Value, MemoryError> {
+    let held = backend.lock_updates().await;
+    let item = load_active(backend, key).await?;
+    let now = Value::String(Utc::now().to_rfc3339());
+    let mut facts = item[FACTS_ATTRIBUTE]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    facts.push(json!({"at": now, "text": fact}));
+    let mut patch = Map::new();
+    patch.insert(FACTS_ATTRIBUTE.into(), Value::from(facts));
+    patch.insert("last_active".into(), now);
+    update_session(backend, &held, &item, &patch).await
+}
+
+/// Apply `patch` to the session `item` while holding the update lock.
+async fn update_session(
+    backend: &MemoryBackend,
+    held: &MutexGuard<'_, ()>,
+    item: &Value,
+    patch: &Map<String, Value>,
+) -> Result<Value, MemoryError> {
+    let key = item["key"].as_str().unwrap_or_default();
+    backend
+        .update_item_locked(held, SESSION_CATEGORY, key, patch)
+        .await?
+        .ok_or_else(|| MemoryError::InvalidParams(format!("No session '{key}'")))
+}
+
+/// End the active session `key` (default [`active_session`]): summarize it
+/// into `content` when it has facts, mark it ended, and expire it
+/// [`SESSIONS_DEFAULT_TTL`] from now.
+#[cfg(feature = "llm")]
+pub async fn end_session(
+    backend: &MemoryBackend,
+    llm: &dyn LlmClient,
+    key: Option<&str>,
+) -> Result<Value, StoreError> {
+    let item = load_active(backend, key).await?;
+    let mut patch = Map::new();
+    if item[FACTS_ATTRIBUTE]
+        .as_array()
+        .is_some_and(|facts| !facts.is_empty())
+    {
+        let summary = summarize_session(llm, &session_input(&item)).await?;
+        patch.insert("content".into(), Value::String(summary));
+    }
+    let now = Value::String(Utc::now().to_rfc3339());
+    patch.insert("status".into(), json!(SESSION_ENDED));
+    patch.insert("ended_at".into(), now.clone());
+    patch.insert("last_active".into(), now);
+    patch.insert(
+        "expires_at".into(),
+        Value::String(compute_expires_at(SESSIONS_DEFAULT_TTL)),
+    );
+    let held = backend.lock_updates().await;
+    Ok(update_session(backend, &held, &item, &patch).await?)
+}
+
+/// Render a session for the LLM: its details, then one `- HH:MM fact` line
+/// per fact.
+pub fn session_input(item: &Value) -> String {
+    let mut lines = Vec::new();
+    for name in ["goal", "project", "branch", "blockers", "content"] {
+        if let Some(value) = item[name].as_str() {
+            lines.push(format!("{name}: {value}"));
+        }
+    }
+    lines.push(String::new());
+    lines.push("Facts:".to_string());
+    for fact in item[FACTS_ATTRIBUTE].as_array().into_iter().flatten() {
+        let time = DateTime::parse_from_rfc3339(fact["at"].as_str().unwrap_or(""))
+            .map(|t| t.with_timezone(&Local).format("%H:%M").to_string())
+            .unwrap_or_else(|_| "--:--".to_string());
+        lines.push(format!("- {time} {}", fact["text"].as_str().unwrap_or("")));
+    }
+    lines.join("\n")
+}
+
+fn is_active(item: &Value) -> bool {
+    item["status"] == SESSION_ACTIVE && !is_expired(item)
+}
+
+async fn load_active(backend: &MemoryBackend, key: Option<&str>) -> Result<Value, MemoryError> {
+    let item = match key {
+        Some(key) => backend
+            .get_item(SESSION_CATEGORY, key)
+            .await?
+            .ok_or_else(|| MemoryError::InvalidParams(format!("No session '{key}'")))?,
+        None => active_session(backend)
+            .await?
+            .ok_or_else(|| MemoryError::InvalidParams("No active session".into()))?,
+    };
+    if !is_active(&item) {
+        return Err(MemoryError::InvalidParams(format!(
+            "Session '{}' is not active",
+            item["key"].as_str().unwrap_or_default()
+        )));
+    }
+    Ok(item)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+
+    #[tokio::test]
+    async fn test_session_lifecycle() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        let started = start_session(
+            &backend,
+            Some("auth-refactor"),
+            &json!({"project": "api", "goal": "split the auth module", "branch": null}),
+        )
+        .await
+        .unwrap();
+        assert_eq!(started["status"], SESSION_ACTIVE);
+        assert!(started.get("branch").is_none());
+        assert!(started["expires_at"].is_string());
+        assert!(
+            start_session(&backend, Some("auth-refactor"), &json!({}))
+                .await
+                .is_err()
+        );
+
+        append_to_session(&backend, None, "token checks moved to middleware")
+            .await
+            .unwrap();
+        let item = append_to_session(&backend, Some("auth-refactor"), "tests still red")
+            .await
+            .unwrap();
+        assert_eq!(item[FACTS_ATTRIBUTE].as_array().unwrap().len(), 2);
+        let input = session_input(&item);
+        assert!(input.starts_with("goal: split the auth module\nproject: api"));
+        assert!(input.contains(" token checks moved to middleware\n"));
+        assert_eq!(
+            active_session(&backend).await.unwrap().unwrap()["key"],
+            "auth-refactor"
+        );
+
+        #[cfg(feature = "llm")]
+        {
+            let llm = crate::llm::MockLlmClient::new(vec!["Moved token checks; tests red.".into()]);
+            let ended = end_session(&backend, &llm, None).await.unwrap();
+            assert_eq!(ended["status"], SESSION_ENDED);
+            assert_eq!(ended["content"], "Moved token checks; tests red.");
+            assert!(active_session(&backend).await.unwrap().is_none());
+            assert!(append_to_session(&backend, None, "late").await.is_err());
+        }
+    }
+}
@@ -0,0 +1,306 @@
+//! Related-memory suggestions.
+//!
+//! [`suggest_related`] ranks other items by how much they have in common
+//! with a target item, to pull adjacent context that no query asked for:
+//!
+//! - a reference either way, through `related` or a link ([`REFERENCE_WEIGHT`]);
+//! - each shared tag in a `tags` attribute ([`TAG_WEIGHT`]);
+//! - each other attribute holding the same short value, such as the same
+//!   `domain` or `team` ([`ATTRIBUTE_WEIGHT`]).
+//!
+//! The request also asked for embedding similarity "when available". It is
+//! never available: items are not embedded and no backend offers vector
+//! search, so there is no semantic similarity signal; two items about the
+//! same thing in different words are not suggested.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::append::CONTENT_ATTRIBUTE;
+use crate::backend::MemoryBackend;
+use crate::chunk::is_chunk;
+use crate::conflict::CONFLICTS_ATTRIBUTE;
+use crate::conflict::item_ref;
+use crate::error::MemoryError;
+use crate::issue::{RESOLVED_AT_ATTRIBUTE, RESOLVED_ATTRIBUTE};
+use crate::links::{normalize_item_ref, parse_item_ref};
+use crate::preferences::SCOPE_ATTRIBUTE;
+use crate::related::{RELATED_ATTRIBUTE, hydrate_related, related};
+use crate::review::REVIEW_COUNT_ATTRIBUTE;
+use crate::score::{IMPORTANCE_ATTRIBUTE, LAST_ACCESSED_ATTRIBUTE};
+use crate::session::FACTS_ATTRIBUTE;
+use crate::sort::UPDATED_AT_ATTRIBUTE;
+use crate::store::STRATEGY_ATTRIBUTE;
+use crate::ttl::{TTL_ATTRIBUTE, is_expired};
+
+/// Score for a reference between the two items, in either direction.
+pub const REFERENCE_WEIGHT: f64 = 3.0;
+
+/// Score per shared tag.
+pub const TAG_WEIGHT: f64 = 1.0;
+
+/// Score per attribute with the same value.
+pub const ATTRIBUTE_WEIGHT: f64 = 1.0;
+
+/// Attribute holding an item's tags: a list, or a comma-separated string.
+pub const TAGS_ATTRIBUTE: &str = "tags";
+
+/// Maximum number of items read per category when ranking candidates.
+pub const SIMILAR_SCAN_LIMIT: usize = 10_000;
+
+/// Longest string value compared as a shared attribute. Longer values are
+/// prose, which practically never matches exactly.
+const MAX_SHARED_VALUE_CHARS: usize = 80;
+
+/// Attributes that say nothing about what an item is about.
+const IGNORED_ATTRIBUTES: &[&str] = &[
+    "category",
+    "key",
+    CONTENT_ATTRIBUTE,
+    "created_at",
+    UPDATED_AT_ATTRIBUTE,
+    "expires_at",
+    TTL_ATTRIBUTE,
+    LAST_ACCESSED_ATTRIBUTE,
+    IMPORTANCE_ATTRIBUTE,
+    REVIEW_COUNT_ATTRIBUTE,
+    RESOLVED_ATTRIBUTE,
+    RESOLVED_AT_ATTRIBUTE,
+    SCOPE_ATTRIBUTE,
+    FACTS_ATTRIBUTE,
+    STRATEGY_ATTRIBUTE,
+    "chunk_count",
+    "source",
+    CONFLICTS_ATTRIBUTE,
+    RELATED_ATTRIBUTE,
+    TAGS_ATTRIBUTE,
+];
+
+/// A suggested item and why it was suggested.
+#[derive(Debug, Clone, Serialize)]
+pub struct Suggestion {
+    /// `category/key` of the suggested item.
+    pub reference: String,
+    pub score: f64,
+    /// Human-readable signals, e.g. `linked (decided-by)` or `domain: auth`.
+    pub reasons: Vec<String>,
+    pub item: Value,
+}
+
+/// Lowercased tags of an item.
+fn tags(item: &Value) -> HashSet<String> {
+    let raw: Vec<&str> = match &item[TAGS_ATTRIBUTE] {
+        Value::Array(tags) => tags.iter().filter_map(Value::as_str).collect(),
+        Value::String(tags) => tags.split(',').collect(),
+        _ => Vec::new(),
+    };
+    raw.into_iter()
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Comparable form of a short scalar attribute value.
+fn comparable(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.trim().is_empty() && s.chars().count() <= MAX_SHARED_VALUE_CHARS => {
+            Some(s.trim().to_lowercase())
+        }
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Reasons `candidate` resembles `target`, other than references.
+fn shared_signals(target: &Value, candidate: &Value) -> Vec<(f64, String)> {
+    let mut signals = Vec::new();
+    let mut shared_tags: Vec<String> = tags(target)
+        .intersection(&tags(candidate))
+        .cloned()
+        .collect();
+    shared_tags.sort();
+    signals.extend(
+        shared_tags
+            .into_iter()
+            .map(|tag| (TAG_WEIGHT, format!("tag: {tag}"))),
+    );
+    if let (Some(target), Some(candidate)) = (target.as_object(), candidate.as_object()) {
+        for (name, value) in target {
+            if IGNORED_ATTRIBUTES.contains(&name.as_str()) {
+                continue;
+            }
+            if let Some(value) = comparable(value)
+                && candidate.get(name).and_then(comparable).as_ref() == Some(&value)
+            {
+                signals.push((ATTRIBUTE_WEIGHT, format!("{name}: {value}")));
+            }
+        }
+    }
+    signals
+}
+
+/// Up to `limit` items most similar to `target` (`category/key`), best
+/// first. Candidates are the items in `categories` plus anything the target
+/// references or is linked with; chunks and (unless `include_expired`)
+/// expired items are skipped. Items with nothing in common are left out.
+pub async fn suggest_related(
+    backend: &MemoryBackend,
+    target: &str,
+    categories: &[String],
+    limit: usize,
+    include_expired: bool,
+) -> Result<Vec<Suggestion>, MemoryError> {
+    let target = normalize_item_ref(target)?;
+    let (category, key) = parse_item_ref(&target)?;
+    let target_item = backend
+        .get_item(&category, &key)
+        .await?
+        .ok_or_else(|| MemoryError::InvalidParams(format!("No memory found for {target}")))?;
+
+    // Why each reference is connected to the target.
+    let mut references: HashMap<String, String> = HashMap::new();
+    for reference in related(&target_item) {
+        references.insert(reference, "related".to_string());
+    }
+    for link in backend.get_links(&target).await? {
+        references.insert(
+            link.other_end(&target).to_string(),
+            format!("linked ({})", link.relation),
+        );
+    }
+
+    let mut candidates =
+        hydrate_related(backend, std::slice::from_ref(&target_item), include_expired).await?;
+    for category in categories {
+        candidates.extend(
+            backend
+                .query(category, None, SIMILAR_SCAN_LIMIT)
+                .await?
+                .into_iter()
+                .filter(|item| !is_chunk(item) && (include_expired || !is_expired(item))),
+        );
+    }
+
+    let mut seen = HashSet::from([target.clone()]);
+    let mut suggestions = Vec::new();
+    for item in candidates {
+        let Some(reference) = item_ref(&item) else {
+            continue;
+        };
+        if !seen.insert(reference.clone()) {
+            continue;
+        }
+        let mut signals = shared_signals(&target_item, &item);
+        let reason = references.get(&reference).cloned().or_else(|| {
+            related(&item)
+                .contains(&target)
+                .then(|| "related".to_string())
+        });
+        if let Some(reason) = reason {
+            signals.insert(0, (REFERENCE_WEIGHT, reason));
+        }
+        if signals.is_empty() {
+            continue;
+        }
+        suggestions.push(Suggestion {
+            reference,
+            score: signals.iter().map(|(weight, _)| weight).sum(),
+            reasons: signals.into_iter().map(|(_, reason)| reason).collect(),
+            item,
+        });
+    }
+    suggestions.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.reference.cmp(&b.reference))
+    });
+    suggestions.truncate(limit);
+    Ok(suggestions)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    #[test]
+    fn test_shared_signals() {
+        let prose = "a rationale long enough to count as prose rather than a label ".repeat(2);
+        let target = json!({
+            "category": "decisions",
+            "key": "auth-method",
+            "domain": "Auth",
+            "tags": ["security", "backend"],
+            "created_at": "2026-01-01T00:00:00Z",
+            "rationale": prose,
+        });
+        let candidate = json!({
+            "category": "decisions",
+            "key": "session-store",
+            "domain": "auth",
+            "tags": "Backend, storage",
+            "created_at": "2026-01-01T00:00:00Z",
+            "rationale": prose,
+        });
+        let reasons: Vec<String> = shared_signals(&target, &candidate)
+            .into_iter()
+            .map(|(_, reason)| reason)
+            .collect();
+        assert_eq!(reasons, vec!["tag: backend", "domain: auth"]);
+        assert!(shared_signals(&target, &json!({"domain": "billing"})).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_suggest_related_ranks_references_first() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for item in [
+            json!({"category": "decisions", "key": "auth-method", "domain": "auth", "team": "core"}),
+            json!({"category": "decisions", "key": "session-store", "domain": "auth", "team": "core"}),
+            json!({"category": "decisions", "key": "billing", "domain": "payments"}),
+            json!({"category": "contacts", "key": "toby", "related": ["decisions/auth-method"]}),
+            json!({"category": "misc", "key": "auth-notes"}),
+        ] {
+            backend.put_item(item).await.unwrap();
+        }
+        backend
+            .link_items("decisions/auth-method", "misc/auth-notes", "documented-in")
+            .await
+            .unwrap();
+
+        let categories = vec!["decisions".to_string(), "contacts".to_string()];
+        let suggestions =
+            suggest_related(&backend, "decisions/auth-method", &categories, 10, false)
+                .await
+                .unwrap();
+        let ranked: Vec<(&str, f64)> = suggestions
+            .iter()
+            .map(|s| (s.reference.as_str(), s.score))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                ("contacts/toby", 3.0),
+                ("misc/auth-notes", 3.0),
+                ("decisions/session-store", 2.0),
+            ]
+        );
+        assert_eq!(suggestions[1].reasons, vec!["linked (documented-in)"]);
+
+        let top = suggest_related(&backend, "decisions/auth-method", &categories, 1, false)
+            .await
+            .unwrap();
+        assert_eq!(top.len(), 1);
+        assert!(
+            suggest_related(&backend, "decisions/missing", &categories, 10, false)
+                .await
+                .is_err()
+        );
+    }
+}
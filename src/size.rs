@@ -0,0 +1,253 @@
+//! Item sizes: accounting for `fmemory stats --size` and the limits
+//! enforced on writes.
+//!
+//! The size of an item is the length of its compact JSON serialization,
+//! which tracks what the server stores closely enough to compare categories
+//! and spot oversized items. Page and index overhead is not counted.
+//!
+//! [`ItemSizeLimits`] bounds the items accepted by
+//! [`MemoryBackend::put_item`](crate::backend::MemoryBackend::put_item): an
+//! item over `warn` is logged, one over `max` is rejected with
+//! [`MemoryError::InvalidParams`](crate::error::MemoryError::InvalidParams).
+//! Limits come from `FMEMORY_ITEM_SIZE`.
+
+use serde::Serialize;
+use serde_json::Value;
+use tracing::warn;
+
+/// Environment variable overriding item size limits, as comma-separated
+/// `kind=size` entries with kind `warn` or `max` and a size such as `512B`,
+/// `64KiB`, or `2MiB`. `kind=off` removes the limit.
+pub const ITEM_SIZE_ENV: &str = "FMEMORY_ITEM_SIZE";
+
+/// Default size above which a write is logged at `warn`.
+pub const DEFAULT_WARN_ITEM_BYTES: usize = 256 * 1024;
+
+/// Default size above which a write is rejected.
+pub const DEFAULT_MAX_ITEM_BYTES: usize = 1024 * 1024;
+
+/// Number of largest items listed per category.
+pub const LARGEST_ITEMS: usize = 5;
+
+/// Serialized size of one item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ItemSize {
+    pub key: String,
+    pub bytes: usize,
+}
+
+/// Serialized size of a set of items.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SizeStats {
+    pub items: usize,
+    pub bytes: usize,
+    /// Up to [`LARGEST_ITEMS`] items, largest first.
+    pub largest: Vec<ItemSize>,
+}
+
+/// Size in bytes of `item` serialized as compact JSON.
+pub fn item_size(item: &Value) -> usize {
+    serde_json::to_vec(item).map_or(0, |bytes| bytes.len())
+}
+
+/// Total and largest serialized sizes of `items`. Chunks count like any
+/// other item.
+pub fn size_stats(items: &[Value]) -> SizeStats {
+    let mut sizes: Vec<ItemSize> = items
+        .iter()
+        .map(|item| ItemSize {
+            key: item["key"].as_str().unwrap_or_default().to_string(),
+            bytes: item_size(item),
+        })
+        .collect();
+    sizes.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.key.cmp(&b.key)));
+    let bytes = sizes.iter().map(|s| s.bytes).sum();
+    sizes.truncate(LARGEST_ITEMS);
+    SizeStats {
+        items: items.len(),
+        bytes,
+        largest: sizes,
+    }
+}
+
+/// Size thresholds for a single item; `None` disables one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemSizeLimits {
+    pub warn: Option<usize>,
+    pub max: Option<usize>,
+}
+
+impl Default for ItemSizeLimits {
+    fn default() -> Self {
+        Self {
+            warn: Some(DEFAULT_WARN_ITEM_BYTES),
+            max: Some(DEFAULT_MAX_ITEM_BYTES),
+        }
+    }
+}
+
+impl ItemSizeLimits {
+    /// No limits at all.
+    pub fn none() -> Self {
+        Self {
+            warn: None,
+            max: None,
+        }
+    }
+
+    /// Defaults plus overrides from `FMEMORY_ITEM_SIZE`.
+    pub fn from_env() -> Self {
+        match std::env::var(ITEM_SIZE_ENV) {
+            Ok(spec) => Self::parse(&spec),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Defaults plus the overrides in `spec`. Malformed entries are skipped
+    /// with a warning.
+    pub fn parse(spec: &str) -> Self {
+        let mut limits = Self::default();
+        for entry in spec.split(',').filter(|e| !e.trim().is_empty()) {
+            let Some((kind, value)) = entry.split_once('=') else {
+                warn!("Ignoring {ITEM_SIZE_ENV} entry: expected kind=size, got '{entry}'");
+                continue;
+            };
+            let limit = match value.trim() {
+                "off" => None,
+                value => match parse_size(value) {
+                    Ok(limit) => Some(limit),
+                    Err(e) => {
+                        warn!("Ignoring {ITEM_SIZE_ENV} entry '{entry}': {e}");
+                        continue;
+                    }
+                },
+            };
+            match kind.trim() {
+                "warn" => limits.warn = limit,
+                "max" => limits.max = limit,
+                other => warn!(
+                    "Ignoring {ITEM_SIZE_ENV} entry: unknown kind '{other}' (expected warn or max)"
+                ),
+            }
+        }
+        limits
+    }
+
+    /// Check `item` against the limits: `Err` with a message naming the
+    /// item when it is over `max`, a logged warning when over `warn`.
+    pub fn check(&self, item: &Value) -> Result<(), String> {
+        if self.warn.is_none() && self.max.is_none() {
+            return Ok(());
+        }
+        let bytes = item_size(item);
+        let address = format!(
+            "{}/{}",
+            item["category"].as_str().unwrap_or_default(),
+            item["key"].as_str().unwrap_or_default()
+        );
+        if let Some(max) = self.max
+            && bytes > max
+        {
+            return Err(format!(
+                "Item {address} is {}, over the {} item size limit ({ITEM_SIZE_ENV}). \
+                 Split long content into chunks or smaller items",
+                format_bytes(bytes),
+                format_bytes(max)
+            ));
+        }
+        if let Some(limit) = self.warn
+            && bytes > limit
+        {
+            warn!(
+                "Item {address} is {}, over the {} warning threshold; consider splitting it",
+                format_bytes(bytes),
+                format_bytes(limit)
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Parse a size such as `"512B"`, `"64KiB"`, or `"2MiB"`.
+pub fn parse_size(s: &str) -> Result<usize, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("Missing unit in '{s}'. Use B, KiB, or MiB"))?;
+    let (num_str, unit) = s.split_at(split);
+    let num: usize = num_str
+        .parse()
+        .map_err(|_| format!("Invalid size number: '{num_str}'"))?;
+    if num == 0 {
+        return Err("Size must be positive, got 0".into());
+    }
+    match unit {
+        "B" => Ok(num),
+        "KiB" => Ok(num * 1024),
+        "MiB" => Ok(num * 1024 * 1024),
+        _ => Err(format!("Unknown size unit '{unit}'. Use B, KiB, or MiB")),
+    }
+}
+
+/// `bytes` for display: `512 B`, `4.0 KiB`, `1.3 MiB`.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_size_stats() {
+        let items: Vec<Value> = (1..=7)
+            .map(|n| json!({"category": "notes", "key": format!("k{n}"), "content": "x".repeat(n * 10)}))
+            .collect();
+        let stats = size_stats(&items);
+        assert_eq!(stats.items, 7);
+        assert_eq!(stats.bytes, items.iter().map(item_size).sum::<usize>());
+        let keys: Vec<&str> = stats.largest.iter().map(|s| s.key.as_str()).collect();
+        assert_eq!(keys, ["k7", "k6", "k5", "k4", "k3"]);
+        assert_eq!(item_size(&json!({"a": 1})), r#"{"a":1}"#.len());
+        assert_eq!(size_stats(&[]), SizeStats::default());
+    }
+
+    #[test]
+    fn test_parse_item_size_limits() {
+        assert_eq!(parse_size("64KiB"), Ok(64 * 1024));
+        assert_eq!(parse_size(" 2MiB "), Ok(2 * 1024 * 1024));
+        assert!(parse_size("64").is_err());
+        assert!(parse_size("0B").is_err());
+        assert!(parse_size("1GB").is_err());
+        assert_eq!(
+            ItemSizeLimits::parse("warn=off, max=100B, bogus, max=big"),
+            ItemSizeLimits {
+                warn: None,
+                max: Some(100),
+            }
+        );
+        assert_eq!(ItemSizeLimits::parse(""), ItemSizeLimits::default());
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(4096), "4.0 KiB");
+        assert_eq!(format_bytes(1_363_149), "1.3 MiB");
+    }
+}
@@ -0,0 +1,181 @@
+//! Result ordering for category scans.
+//!
+//! Items are stored by `key`, so [`SortField::Key`] is applied by the backend
+//! (scanning the partition forwards or backwards). Secondary indexes only
+//! answer equality lookups, so every other field is sorted client-side over
+//! the whole category (up to [`SORT_SCAN_LIMIT`]) before the limit is applied.
+
+use serde_json::Value;
+
+use crate::backend::MemoryBackend;
+use crate::error::MemoryError;
+use crate::ttl::filter_expired;
+
+/// Attribute stamped with the RFC 3339 time of the item's last write.
+/// `created_at` survives merges; `updated_at` does not.
+pub const UPDATED_AT_ATTRIBUTE: &str = "updated_at";
+
+/// Maximum number of items read when sorting client-side.
+pub const SORT_SCAN_LIMIT: usize = 10_000;
+
+/// Field a category scan is ordered by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortField {
+    /// The sort key; the backend's native order.
+    #[default]
+    Key,
+    CreatedAt,
+    /// `updated_at`, falling back to `created_at` for items written before
+    /// it was stamped.
+    UpdatedAt,
+}
+
+impl SortField {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SortField::Key => "key",
+            SortField::CreatedAt => "created_at",
+            SortField::UpdatedAt => "updated_at",
+        }
+    }
+
+    /// Whether the backend can return items in this order.
+    pub fn is_server_side(self) -> bool {
+        self == SortField::Key
+    }
+
+    fn value(self, item: &Value) -> &str {
+        let attr = |name: &str| item[name].as_str();
+        match self {
+            SortField::Key => attr("key"),
+            SortField::CreatedAt => attr("created_at"),
+            SortField::UpdatedAt => attr(UPDATED_AT_ATTRIBUTE).or_else(|| attr("created_at")),
+        }
+        .unwrap_or_default()
+    }
+}
+
+impl std::str::FromStr for SortField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "key" => Ok(SortField::Key),
+            "created_at" => Ok(SortField::CreatedAt),
+            "updated_at" => Ok(SortField::UpdatedAt),
+            other => Err(format!(
+                "Unknown sort field '{other}' (expected created_at, key, or updated_at)"
+            )),
+        }
+    }
+}
+
+/// Sort `items` by `field`, ascending unless `descending`. Items missing the
+/// field sort first. Ties keep their key order.
+pub fn sort_items(items: &mut [Value], field: SortField, descending: bool) {
+    items.sort_by(|a, b| {
+        let ord = field.value(a).cmp(field.value(b));
+        if descending { ord.reverse() } else { ord }
+    });
+}
+
+/// Up to `limit` items of `category` (optionally under a key `prefix`) in
+/// `field` order. Expired items are dropped unless `include_expired`.
+pub async fn query_sorted(
+    backend: &MemoryBackend,
+    category: &str,
+    prefix: Option<&str>,
+    limit: usize,
+    field: SortField,
+    descending: bool,
+    include_expired: bool,
+) -> Result<Vec<Value>, MemoryError> {
+    let keep = |items: Vec<Value>| {
+        if include_expired {
+            items
+        } else {
+            filter_expired(items)
+        }
+    };
+    if field.is_server_side() {
+        let items = backend
+            .query_ordered(category, prefix, limit, descending)
+            .await?;
+        return Ok(keep(items));
+    }
+    let mut items = keep(backend.query(category, prefix, SORT_SCAN_LIMIT).await?);
+    sort_items(&mut items, field, descending);
+    items.truncate(limit);
+    Ok(items)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    async fn backend() -> MemoryBackend {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for (key, created, updated) in [
+            ("a", "2026-01-03T00:00:00+00:00", None),
+            (
+                "b",
+                "2026-01-01T00:00:00+00:00",
+                Some("2026-01-05T00:00:00+00:00"),
+            ),
+            ("c", "2026-01-02T00:00:00+00:00", None),
+        ] {
+            backend
+                .put_item(json!({
+                    "category": "notes",
+                    "key": key,
+                    "created_at": created,
+                    "updated_at": updated,
+                }))
+                .await
+                .unwrap();
+        }
+        backend
+    }
+
+    fn keys(items: &[Value]) -> Vec<&str> {
+        items.iter().filter_map(|i| i["key"].as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_sort_field() {
+        assert_eq!(
+            "created_at".parse::<SortField>().unwrap(),
+            SortField::CreatedAt
+        );
+        assert!("score".parse::<SortField>().is_err());
+        assert!(SortField::Key.is_server_side());
+        assert!(!SortField::UpdatedAt.is_server_side());
+    }
+
+    #[tokio::test]
+    async fn test_query_sorted() {
+        let backend = backend().await;
+        for (field, descending, expected) in [
+            (SortField::Key, false, vec!["a", "b"]),
+            (SortField::Key, true, vec!["c", "b"]),
+            (SortField::CreatedAt, false, vec!["b", "c"]),
+            (SortField::UpdatedAt, true, vec!["b", "a"]),
+        ] {
+            let items = query_sorted(&backend, "notes", None, 2, field, descending, false)
+                .await
+                .unwrap();
+            assert_eq!(
+                keys(&items),
+                expected,
+                "{} desc={descending}",
+                field.as_str()
+            );
+        }
+    }
+}
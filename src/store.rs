@@ -0,0 +1,1922 @@
+//! Embeddable high-level memory API.
+//!
+//! [`MemoryStore`] bundles a [`MemoryBackend`], its [`SchemaManager`] and an
+//! optional [`LlmClient`] behind the operations `fmemory` exposes on the
+//! command line: remember free text, recall with a natural language query,
+//! store structured items, and forget them. Applications that want persistent
+//! memory can depend on this crate and use the store directly instead of
+//! shelling out to the CLI. The natural language operations require the
+//! `llm` feature.
+//!
+//! ```no_run
+//! # #[cfg(feature = "llm")]
+//! # async fn demo() -> Result<(), ferridyn_memory::store::StoreError> {
+//! use std::sync::Arc;
+//! use ferridyn_memory::llm::AnthropicClient;
+//! use ferridyn_memory::store::MemoryStore;
+//!
+//! let store = MemoryStore::connect(Some("myapp"))
+//!     .await?
+//!     .with_llm(Arc::new(AnthropicClient::from_env()?));
+//! store.init().await?;
+//! store.remember("Toby's birthday is March 15").await?;
+//! let recall = store.recall("when is Toby's birthday?").await?;
+//! println!("{}", recall.answer.unwrap_or_default());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+
+use chrono::{Duration, NaiveDate};
+use futures::stream::{self, StreamExt};
+use serde_json::Value;
+use thiserror::Error;
+use tokio::sync::Mutex;
+#[cfg(feature = "llm")]
+use tracing::warn;
+
+use crate::backend::{
+    DEFAULT_REPLICA_MAX_LAG, MAX_CONCURRENT_CATEGORY_OPS, MemoryBackend, READ_SOCKET_ENV,
+    REPLICA_MAX_LAG_ENV,
+};
+#[cfg(feature = "llm")]
+use crate::budget::prompt_budget_from_env;
+use crate::budget::{overlap, rank_categories, terms};
+use crate::capability::probe_capabilities;
+#[cfg(feature = "llm")]
+use crate::chunk::collapse_chunks;
+use crate::chunk::{
+    CHUNK_SIZE_CHARS, delete_chunks, hydrate_chunks, is_chunk, needs_chunking, split_into_chunks,
+    store_chunks,
+};
+#[cfg(feature = "llm")]
+use crate::conflict::flag_conflicts;
+use crate::conflict::{CONFLICTS_ATTRIBUTE, item_ref};
+use crate::date_query::DateSpan;
+#[cfg(feature = "llm")]
+use crate::date_query::pre_resolve;
+use crate::error::MemoryError;
+use crate::interaction::INTERACTIONS_CATEGORY;
+#[cfg(feature = "llm")]
+use crate::interaction::{log_recall, recall_log_from_env};
+#[cfg(feature = "llm")]
+use crate::keys::{KeyStrategies, KeyStrategy};
+#[cfg(feature = "llm")]
+use crate::llm::LlmClient;
+use crate::llm::LlmError;
+use crate::locale::DateContext;
+use crate::mirror::MIRROR_SOCKET_ENV;
+use crate::quota::{Quota, Quotas};
+#[cfg(feature = "llm")]
+use crate::recall_cache::{RecallCache, RecallKey};
+use crate::related::{RELATED_ATTRIBUTE, RelatedBudget};
+#[cfg(feature = "llm")]
+use crate::related::{hydrate_related_within, link_related};
+use crate::review::REVIEW_COUNT_ATTRIBUTE;
+#[cfg(feature = "llm")]
+use crate::schema::{
+    Answer, FollowUp, SchemaSnapshot, answer_query_with_follow_up, parse_facts_batch,
+    parse_to_document, parse_to_document_with_category, resolve_query_with_budget,
+    summarize_category, summarize_text,
+};
+use crate::schema::{ResolvedQuery, SchemaManager};
+use crate::score::{LAST_ACCESSED_ATTRIBUTE, record_access};
+use crate::search::register_namespace;
+use crate::size::ItemSizeLimits;
+use crate::sort::UPDATED_AT_ATTRIBUTE;
+use crate::timeout::{Timeouts, parse_duration};
+#[cfg(feature = "llm")]
+use crate::ttl::take_ttl;
+use crate::ttl::{
+    INTERACTIONS_DEFAULT_TTL, SCRATCHPAD_DEFAULT_TTL, SESSIONS_DEFAULT_TTL, auto_ttl_from_date,
+    compute_expires_at, filter_expired, is_expired,
+};
+use crate::validation::WriteValidation;
+use crate::{
+    PartitionSchemaInfo, ensure_memories_table_via_server, namespace_of, resolve_socket_path,
+    resolve_table_name, validate_namespace,
+};
+
+#[cfg(feature = "llm")]
+/// Maximum number of facts parsed per LLM call by
+/// [`MemoryStore::remember_batch`].
+pub const REMEMBER_BATCH_SIZE: usize = 10;
+
+#[cfg(feature = "llm")]
+/// Default number of items fetched by [`MemoryStore::recall`].
+pub const DEFAULT_RECALL_LIMIT: usize = 20;
+
+/// Key of the item written by [`MemoryStore::summarize`]. It starts with
+/// `_`, which [`slugify`](crate::keys::slugify) never produces, so it does
+/// not replace a memory that happens to be keyed `overview`.
+pub const OVERVIEW_KEY: &str = "_overview";
+
+#[cfg(feature = "llm")]
+/// Maximum number of items [`MemoryStore::summarize`] reads from a category.
+pub const OVERVIEW_SCAN_LIMIT: usize = 10_000;
+
+/// Number of ranked categories [`broaden_across_categories`] tries, besides
+/// `notes`.
+pub const BROADEN_MAX_CATEGORIES: usize = 3;
+
+/// Maximum number of items [`broaden_across_categories`] reads per category.
+pub const BROADEN_SCAN_LIMIT: usize = 1000;
+
+/// Catch-all category, always tried when broadening.
+const NOTES_CATEGORY: &str = "notes";
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+/// Errors returned by [`MemoryStore`] operations.
+#[derive(Debug, Error)]
+pub enum StoreError {
+    /// Could not reach or prepare the ferridyn-server database.
+    #[error("{0}")]
+    Connect(String),
+
+    /// Backend, schema or index operation failed.
+    #[error(transparent)]
+    Memory(#[from] MemoryError),
+
+    /// LLM call failed.
+    #[error("LLM error: {0}")]
+    Llm(#[from] LlmError),
+
+    /// A natural language operation was attempted without an LLM client.
+    #[error("No LLM client configured; natural language operations need one")]
+    NoLlm,
+
+    /// The target category has no schema.
+    #[error("Unknown category '{category}'. Available: {available}")]
+    UnknownCategory { category: String, available: String },
+
+    /// Recall needs at least one schema to resolve a query against.
+    #[error("No schemas defined")]
+    NoSchemas,
+}
+
+// ============================================================================
+// Memory Store
+// ============================================================================
+
+#[cfg(feature = "llm")]
+/// Options for [`MemoryStore::remember_with`].
+#[derive(Debug, Clone, Default)]
+pub struct RememberOptions {
+    /// Target category. When `None` the LLM picks one from the defined schemas.
+    pub category: Option<String>,
+    /// Explicit key, overriding the one derived by the LLM. An explicit key
+    /// always replaces an existing item; generated keys follow the store's
+    /// [`CollisionPolicy`].
+    pub key: Option<String>,
+    /// Explicit time-to-live, overriding the category default.
+    pub ttl: Option<Duration>,
+}
+
+#[cfg(feature = "llm")]
+/// Result of [`MemoryStore::recall`].
+#[derive(Debug, Clone)]
+pub struct Recall {
+    /// Matching items, expired items and chunk fragments removed.
+    pub items: Vec<Value>,
+    /// Answer synthesized from `items`, or `None` if none were relevant.
+    pub answer: Option<String>,
+    /// Where to look next when the answer is partial.
+    pub follow_up: FollowUp,
+    /// Whether the plan matched nothing and the whole category was scanned.
+    pub fallback_used: bool,
+}
+
+#[cfg(feature = "llm")]
+/// Result of [`MemoryStore::resolve_recall`]: a query resolved and run,
+/// before any answer is synthesized.
+#[derive(Debug, Clone)]
+pub struct ResolvedRecall {
+    /// Plan the LLM resolved the query to.
+    pub plan: ResolvedQuery,
+    /// Whether the plan matched nothing and the whole category, or other
+    /// categories, were searched instead.
+    pub fallback: bool,
+    /// Matching items, expired items and chunk fragments removed, followed
+    /// by related items if enabled. Each is annotated with
+    /// [`STRATEGY_ATTRIBUTE`] and appears once.
+    pub items: Vec<Value>,
+}
+
+/// Attribute added to recall results, naming the strategy that produced the
+/// item: `index`, `scan`, or `exact` for the resolved plan, `fallback` for
+/// the category-wide scan run when the plan matched nothing, `broadened` for
+/// matches from another category ([`broaden_across_categories`]), and
+/// `related` for items added by `--related`. Never stored.
+pub const STRATEGY_ATTRIBUTE: &str = "_strategy";
+
+/// High-level memory API over a single namespace.
+#[derive(Clone)]
+pub struct MemoryStore {
+    backend: MemoryBackend,
+    schema_manager: SchemaManager,
+    #[cfg(feature = "llm")]
+    llm: Option<Arc<dyn LlmClient>>,
+    #[cfg(feature = "llm")]
+    prompt_budget: usize,
+    #[cfg(feature = "llm")]
+    key_strategies: KeyStrategies,
+    #[cfg(feature = "llm")]
+    collision_policy: CollisionPolicy,
+    /// Whether recalls are recorded in `interactions`.
+    #[cfg(feature = "llm")]
+    log_recall: bool,
+    quotas: Quotas,
+    include_expired: bool,
+    /// How far recall follows `related` references, if at all.
+    related: Option<RelatedBudget>,
+    #[cfg(feature = "llm")]
+    recall_cache: RecallCache,
+}
+
+impl MemoryStore {
+    /// Create a store over an existing backend. No LLM is configured.
+    pub fn new(backend: MemoryBackend) -> Self {
+        Self {
+            schema_manager: SchemaManager::new(backend.clone()),
+            backend,
+            #[cfg(feature = "llm")]
+            llm: None,
+            #[cfg(feature = "llm")]
+            prompt_budget: prompt_budget_from_env(),
+            #[cfg(feature = "llm")]
+            key_strategies: KeyStrategies::from_env(),
+            #[cfg(feature = "llm")]
+            collision_policy: CollisionPolicy::default(),
+            #[cfg(feature = "llm")]
+            log_recall: recall_log_from_env(),
+            quotas: Quotas::from_env(),
+            include_expired: false,
+            related: None,
+            #[cfg(feature = "llm")]
+            recall_cache: RecallCache::from_env(),
+        }
+    }
+
+    /// Connect to the running ferridyn-server and open `namespace`, which
+    /// must be a valid namespace ([`validate_namespace`]).
+    pub async fn connect(namespace: Option<&str>) -> Result<Self, StoreError> {
+        if let Some(namespace) = namespace {
+            validate_namespace(namespace).map_err(MemoryError::InvalidParams)?;
+        }
+        let backend = connect_backend(&resolve_table_name(namespace)).await?;
+        Ok(Self::new(backend))
+    }
+
+    #[cfg(feature = "llm")]
+    /// Use `llm` for natural language operations.
+    pub fn with_llm(mut self, llm: Arc<dyn LlmClient>) -> Self {
+        self.llm = Some(llm);
+        self
+    }
+
+    #[cfg(feature = "llm")]
+    /// Override the prompt budget used for query resolution.
+    pub fn with_prompt_budget(mut self, chars: usize) -> Self {
+        self.prompt_budget = chars;
+        self
+    }
+
+    #[cfg(feature = "llm")]
+    /// Choose how [`MemoryStore::remember`] keys items in `category` when no
+    /// key is given.
+    pub fn with_key_strategy(mut self, category: &str, strategy: KeyStrategy) -> Self {
+        self.key_strategies.set(category, strategy);
+        self
+    }
+
+    #[cfg(feature = "llm")]
+    /// Choose what [`MemoryStore::remember`] does when a generated key is
+    /// already taken by a different item.
+    pub fn with_collision_policy(mut self, policy: CollisionPolicy) -> Self {
+        self.collision_policy = policy;
+        self
+    }
+
+    #[cfg(feature = "llm")]
+    /// Record every recall in `interactions` (see [`crate::interaction`]),
+    /// overriding `FMEMORY_LOG_RECALL`.
+    pub fn with_recall_log(mut self, enabled: bool) -> Self {
+        self.log_recall = enabled;
+        self
+    }
+
+    /// Replace the per-category item caps (by default [`Quotas::from_env`]).
+    pub fn with_quotas(mut self, quotas: Quotas) -> Self {
+        self.quotas = quotas;
+        self
+    }
+
+    /// Cap `category` at `quota`, or remove its cap with `None`.
+    pub fn with_quota(mut self, category: &str, quota: Option<Quota>) -> Self {
+        match quota {
+            Some(quota) => self.quotas.set(category, quota),
+            None => self.quotas.remove(category),
+        }
+        self
+    }
+
+    #[cfg(feature = "llm")]
+    /// Cache recalls in `cache` (by default [`RecallCache::from_env`]), to
+    /// share it between stores.
+    pub fn with_recall_cache(mut self, cache: RecallCache) -> Self {
+        self.recall_cache = cache;
+        self
+    }
+
+    /// Return expired items from reads instead of filtering them out.
+    pub fn with_include_expired(mut self, include_expired: bool) -> Self {
+        self.include_expired = include_expired;
+        self
+    }
+
+    /// Append the items that recall results reference through their
+    /// `related` lists (see [`crate::related`]), within the default
+    /// [`RelatedBudget`].
+    pub fn with_related(mut self, include_related: bool) -> Self {
+        self.related = include_related.then(RelatedBudget::default);
+        self
+    }
+
+    /// Append related items, following references up to `budget.hops` away
+    /// and adding at most `budget.max_items`. Related items are part of the
+    /// context [`MemoryStore::recall`] answers from.
+    pub fn with_related_budget(mut self, budget: RelatedBudget) -> Self {
+        self.related = Some(budget);
+        self
+    }
+
+    /// Switch to another namespace on the same connection. Fails if
+    /// `namespace` is not a valid namespace ([`validate_namespace`]).
+    ///
+    /// The namespace's table must already exist (see [`MemoryStore::connect`]).
+    /// It is not added to the namespace registry, so searches across
+    /// namespaces miss it until it is connected to.
+    pub fn with_namespace(mut self, namespace: Option<&str>) -> Result<Self, StoreError> {
+        if let Some(namespace) = namespace {
+            validate_namespace(namespace).map_err(MemoryError::InvalidParams)?;
+        }
+        self.backend.table_name = resolve_table_name(namespace);
+        self.schema_manager = SchemaManager::new(self.backend.clone());
+        Ok(self)
+    }
+
+    /// The underlying backend, for operations the store does not wrap.
+    pub fn backend(&self) -> &MemoryBackend {
+        &self.backend
+    }
+
+    /// The schema manager for this namespace.
+    pub fn schema_manager(&self) -> &SchemaManager {
+        &self.schema_manager
+    }
+
+    /// Create the predefined schemas if the namespace has none yet.
+    ///
+    /// Returns `true` if schemas were created.
+    pub async fn init(&self) -> Result<bool, StoreError> {
+        let schemas = self.schema_manager.list_schemas().await.unwrap_or_default();
+        if !schemas.is_empty() {
+            return Ok(false);
+        }
+        self.backend.ensure_predefined_schemas().await?;
+        self.schema_manager.invalidate().await;
+        Ok(true)
+    }
+
+    #[cfg(feature = "llm")]
+    /// Parse free text into a structured memory and store it.
+    ///
+    /// Returns the stored item.
+    pub async fn remember(&self, text: &str) -> Result<Value, StoreError> {
+        self.remember_with(RememberOptions::default(), text).await
+    }
+
+    #[cfg(feature = "llm")]
+    /// Like [`MemoryStore::remember`], with an explicit category, key or TTL.
+    ///
+    /// Oversized text is summarized for parsing and the full text is stored
+    /// as chunks.
+    pub async fn remember_with(
+        &self,
+        options: RememberOptions,
+        text: &str,
+    ) -> Result<Value, StoreError> {
+        let llm = self.llm()?;
+        let generated_key = options.key.is_none();
+        let (parse_input, chunks) = summarize_if_oversized(llm, text).await?;
+        let (category, key, mut doc) = parse_memory(
+            llm,
+            &self.schema_manager.snapshot().await?,
+            &self.key_strategies,
+            options.category,
+            options.key,
+            &parse_input,
+        )
+        .await?;
+        // An explicit TTL wins over one stated in the text.
+        let ttl = options.ttl.or(take_ttl(&mut doc));
+        let mut item = build_item(&category, &key, &doc, ttl);
+        if !chunks.is_empty() {
+            item["content"] = Value::String(parse_input);
+            item["chunk_count"] = Value::from(chunks.len());
+        }
+        self.store_remembered(llm, item, &chunks, generated_key)
+            .await
+    }
+
+    #[cfg(feature = "llm")]
+    /// Remember several facts, parsing up to [`REMEMBER_BATCH_SIZE`] of them
+    /// per LLM call.
+    ///
+    /// `options.category` and `options.ttl` apply to every fact; keys are
+    /// always generated, so `options.key` is ignored. Oversized facts, and
+    /// facts a batch response leaves out, are remembered one at a time.
+    ///
+    /// Returns one result per fact, in order.
+    pub async fn remember_batch(
+        &self,
+        facts: &[String],
+        options: RememberOptions,
+    ) -> Result<Vec<Result<Value, StoreError>>, StoreError> {
+        let llm = self.llm()?;
+        let snapshot = self.schema_manager.snapshot().await?;
+        let schemas = match options.category {
+            Some(ref category) => match snapshot.get(category) {
+                Some(schema_info) => vec![schema_info.clone()],
+                None => return Err(unknown_category(&snapshot, category)),
+            },
+            None => snapshot.schemas.clone(),
+        };
+        let SchemaSnapshot { aliases, hints, .. } = snapshot;
+        let single = RememberOptions {
+            key: None,
+            ..options.clone()
+        };
+
+        let mut results = Vec::with_capacity(facts.len());
+        for batch in facts.chunks(REMEMBER_BATCH_SIZE) {
+            let parsed: Vec<usize> = (0..batch.len())
+                .filter(|&i| !needs_chunking(&batch[i]))
+                .collect();
+            let texts: Vec<String> = parsed.iter().map(|&i| batch[i].clone()).collect();
+            let mut docs: Vec<Option<Value>> = vec![None; batch.len()];
+            match parse_facts_batch(llm, &schemas, &aliases, &hints, &texts).await {
+                Ok(batch_docs) => {
+                    for (i, doc) in parsed.into_iter().zip(batch_docs) {
+                        docs[i] = doc;
+                    }
+                }
+                Err(e) => warn!("batch parse failed, remembering facts one at a time: {e}"),
+            }
+
+            for (fact, doc) in batch.iter().zip(docs) {
+                let result = match doc {
+                    Some(mut doc) => {
+                        let category = doc["category"].as_str().unwrap_or("notes").to_string();
+                        let parsed_key = doc["key"].as_str().unwrap_or("unknown").to_string();
+                        let key = self.key_strategies.key_for(&category, parsed_key);
+                        let ttl = options.ttl.or(take_ttl(&mut doc));
+                        let item = build_item(&category, &key, &doc, ttl);
+                        self.store_remembered(llm, item, &[], true).await
+                    }
+                    None => self.remember_with(single.clone(), fact).await,
+                };
+                results.push(result);
+            }
+        }
+        Ok(results)
+    }
+
+    #[cfg(feature = "llm")]
+    /// Store a newly remembered item and its chunks, then link its related
+    /// memories and flag conflicts with existing ones.
+    async fn store_remembered(
+        &self,
+        llm: &dyn LlmClient,
+        mut item: Value,
+        chunks: &[String],
+        generated_key: bool,
+    ) -> Result<Value, StoreError> {
+        if generated_key {
+            item = put_generated_item(&self.backend, item, self.collision_policy).await?;
+            store_chunks(&self.backend, &item, chunks).await?;
+            self.quotas
+                .enforce_after_write(&self.backend, &item)
+                .await?;
+        } else {
+            self.put_with_chunks(&item, chunks).await?;
+        }
+        link_related(&self.backend, &item).await?;
+        flag_conflicts(&self.backend, llm, &mut item).await;
+        Ok(item)
+    }
+
+    /// Store an item with caller-supplied attributes.
+    ///
+    /// `created_at` and the category's default `expires_at` are injected as
+    /// for parsed memories, and attribute aliases renamed. An oversized
+    /// `content` attribute is chunked.
+    pub async fn store_structured(
+        &self,
+        category: &str,
+        key: &str,
+        attributes: &Value,
+        ttl: Option<Duration>,
+    ) -> Result<Value, StoreError> {
+        let mut item = build_item(category, key, attributes, ttl);
+        let chunks = match item["content"].as_str() {
+            Some(content) if needs_chunking(content) => {
+                split_into_chunks(content, CHUNK_SIZE_CHARS)
+            }
+            _ => Vec::new(),
+        };
+        if !chunks.is_empty() {
+            item["content"] = Value::String(chunks[0].clone());
+            item["chunk_count"] = Value::from(chunks.len());
+        }
+        self.put_with_chunks(&item, &chunks).await?;
+        Ok(item)
+    }
+
+    /// Store an already built item followed by its chunks, then enforce the
+    /// category's quota.
+    ///
+    /// Returns the keys evicted to make room.
+    pub async fn put_with_chunks(
+        &self,
+        item: &Value,
+        chunks: &[String],
+    ) -> Result<Vec<String>, StoreError> {
+        self.backend.put_item(item.clone()).await?;
+        store_chunks(&self.backend, item, chunks).await?;
+        Ok(self.quotas.enforce_after_write(&self.backend, item).await?)
+    }
+
+    /// Fetch one item, with chunked content reassembled.
+    pub async fn get(&self, category: &str, key: &str) -> Result<Option<Value>, StoreError> {
+        let item = self
+            .backend
+            .get_item(category, key)
+            .await?
+            .filter(|i| self.include_expired || !is_expired(i));
+        match item {
+            Some(item) => {
+                record_access(&self.backend, std::slice::from_ref(&item)).await;
+                Ok(hydrate_chunks(&self.backend, vec![item])
+                    .await?
+                    .into_iter()
+                    .next())
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// List up to `limit` items in a category, with chunked content reassembled.
+    pub async fn list(&self, category: &str, limit: usize) -> Result<Vec<Value>, StoreError> {
+        let items = self.backend.query(category, None, limit).await?;
+        let items = self.filter_expired(items);
+        Ok(hydrate_chunks(&self.backend, items).await?)
+    }
+
+    #[cfg(feature = "llm")]
+    /// Answer a natural language question from stored memories.
+    pub async fn recall(&self, query: &str) -> Result<Recall, StoreError> {
+        let resolved = self.resolve_recall(query, DEFAULT_RECALL_LIMIT).await?;
+        let answer = self.answer(query, &resolved.items).await?;
+        let (answer, follow_up) = answer
+            .map(|a| (Some(a.text), a.follow_up))
+            .unwrap_or_default();
+        self.log_recall(query, &resolved, answer.as_deref()).await;
+        Ok(Recall {
+            items: resolved.items,
+            answer,
+            follow_up,
+            fallback_used: resolved.fallback,
+        })
+    }
+
+    #[cfg(feature = "llm")]
+    /// Resolve a natural language query to items without synthesizing an answer.
+    pub async fn recall_items(&self, query: &str, limit: usize) -> Result<Vec<Value>, StoreError> {
+        let resolved = self.resolve_recall(query, limit).await?;
+        self.log_recall(query, &resolved, None).await;
+        Ok(resolved.items)
+    }
+
+    #[cfg(feature = "llm")]
+    /// Resolve a natural language query to a plan and its items. Nothing is
+    /// logged; callers that synthesize an answer pass it to
+    /// [`MemoryStore::log_recall`] afterwards.
+    ///
+    /// The same query, asked again before anything is written to the
+    /// namespace, is answered from the [`RecallCache`].
+    pub async fn resolve_recall(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<ResolvedRecall, StoreError> {
+        let key = RecallKey::new(
+            &self.backend.table_name,
+            query,
+            limit,
+            self.include_expired,
+            self.related,
+        );
+        let versions = self.backend.data_versions();
+        if let Some(resolved) = self.recall_cache.get(&key, &versions) {
+            let found: Vec<Value> = resolved
+                .items
+                .iter()
+                .filter(|item| item[STRATEGY_ATTRIBUTE] != "related")
+                .cloned()
+                .collect();
+            record_access(&self.backend, &found).await;
+            return Ok(resolved);
+        }
+        let resolved = self.resolve_recall_uncached(query, limit).await?;
+        self.recall_cache.insert(key, versions, resolved.clone());
+        Ok(resolved)
+    }
+
+    #[cfg(feature = "llm")]
+    /// Synthesize the answer to `query` from the `items` it resolved to, or
+    /// `None` if there are none or none are relevant. Answers are cached
+    /// with the recall the items came from.
+    pub async fn answer(&self, query: &str, items: &[Value]) -> Result<Option<Answer>, StoreError> {
+        if items.is_empty() {
+            return Ok(None);
+        }
+        let table = &self.backend.table_name;
+        if let Some(answer) = self.recall_cache.answer(table, query, items) {
+            return Ok(answer);
+        }
+        let answer = answer_query_with_follow_up(self.llm()?, query, items).await?;
+        self.recall_cache
+            .insert_answer(table, query, items, answer.clone());
+        Ok(answer)
+    }
+
+    #[cfg(feature = "llm")]
+    async fn resolve_recall_uncached(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<ResolvedRecall, StoreError> {
+        let llm = self.llm()?;
+        let SchemaSnapshot {
+            schemas,
+            indexes,
+            aliases,
+            ..
+        } = self.schema_manager.snapshot().await?;
+        if schemas.is_empty() {
+            return Err(StoreError::NoSchemas);
+        }
+        let category_keys = fetch_category_keys(&self.backend, &schemas).await;
+        let dates = DateContext::from_env();
+        let resolved = match pre_resolve(query, &indexes, &dates, dates.today()) {
+            Some(resolved) => resolved,
+            None => {
+                resolve_query_with_budget(
+                    llm,
+                    &schemas,
+                    &aliases,
+                    &indexes,
+                    &category_keys,
+                    query,
+                    self.prompt_budget,
+                )
+                .await?
+            }
+        };
+
+        let (items, mut fallback) = execute_with_fallback(&self.backend, &resolved, limit).await?;
+        let mut items = self.filter_expired(items);
+        if items.is_empty() {
+            let broadened = broaden_across_categories(
+                &self.backend,
+                &schemas,
+                &category_keys,
+                query,
+                resolved_category(&resolved),
+                limit,
+            )
+            .await?;
+            items = self.filter_expired(broadened);
+            fallback = !items.is_empty();
+        }
+        let mut items = collapse_chunks(items);
+        record_access(&self.backend, &items).await;
+        if let Some(budget) = self.related {
+            let mut related =
+                hydrate_related_within(&self.backend, &items, budget, self.include_expired).await?;
+            annotate_strategy(&mut related, "related");
+            items.extend(related);
+        }
+        dedup_items(&mut items);
+        Ok(ResolvedRecall {
+            plan: resolved,
+            fallback,
+            items,
+        })
+    }
+
+    #[cfg(feature = "llm")]
+    /// Record a recall and its `answer` in `interactions` if recall logging
+    /// is enabled. A failed write is logged, never returned: the recall
+    /// itself succeeded.
+    pub async fn log_recall(&self, query: &str, resolved: &ResolvedRecall, answer: Option<&str>) {
+        if !self.log_recall {
+            return;
+        }
+        if let Err(e) = log_recall(
+            &self.backend,
+            query,
+            &resolved.plan,
+            resolved.fallback,
+            answer,
+            &resolved.items,
+        )
+        .await
+        {
+            warn!("Failed to log recall: {e}");
+        }
+    }
+
+    #[cfg(feature = "llm")]
+    /// Condense every item in `category` into a single
+    /// `{category}/`[`OVERVIEW_KEY`] item, replacing the previous overview.
+    ///
+    /// Returns the stored overview, or `None` if the category has nothing to
+    /// summarize.
+    pub async fn summarize(&self, category: &str) -> Result<Option<Value>, StoreError> {
+        let llm = self.llm()?;
+        let items = self
+            .backend
+            .query(category, None, OVERVIEW_SCAN_LIMIT)
+            .await?;
+        let items: Vec<Value> = collapse_chunks(self.filter_expired(items))
+            .into_iter()
+            .filter(|item| item["key"].as_str() != Some(OVERVIEW_KEY))
+            .collect();
+        if items.is_empty() {
+            return Ok(None);
+        }
+
+        let overview = summarize_category(llm, category, &items).await?;
+        let doc = serde_json::json!({
+            "content": overview,
+            "source": "summarize",
+            "item_count": items.len(),
+        });
+        let item = build_item(category, OVERVIEW_KEY, &doc, None);
+        self.put_with_chunks(&item, &[]).await?;
+        Ok(Some(item))
+    }
+
+    /// Delete an item and its chunks. Returns `false` if it did not exist.
+    pub async fn forget(&self, category: &str, key: &str) -> Result<bool, StoreError> {
+        let Some(item) = self.backend.get_item(category, key).await? else {
+            return Ok(false);
+        };
+        delete_chunks(&self.backend, &item).await?;
+        self.backend.delete_item(category, key).await?;
+        Ok(true)
+    }
+
+    #[cfg(feature = "llm")]
+    fn llm(&self) -> Result<&dyn LlmClient, StoreError> {
+        self.llm.as_deref().ok_or(StoreError::NoLlm)
+    }
+
+    fn filter_expired(&self, items: Vec<Value>) -> Vec<Value> {
+        if self.include_expired {
+            items
+        } else {
+            filter_expired(items)
+        }
+    }
+}
+
+// ============================================================================
+// Building Blocks
+// ============================================================================
+
+/// Connect to the running ferridyn-server and ensure `table_name` exists.
+pub async fn connect_backend(table_name: &str) -> Result<MemoryBackend, StoreError> {
+    let socket_path = resolve_socket_path();
+
+    if !socket_path.exists() {
+        return Err(StoreError::Connect(format!(
+            "ferridyn-server socket not found at {}. Start the server with: ferridyn-server",
+            socket_path.display()
+        )));
+    }
+
+    let mut client = ferridyn_server::FerridynClient::connect(&socket_path)
+        .await
+        .map_err(|e| {
+            StoreError::Connect(format!(
+                "Failed to connect to ferridyn-server at {}: {e}",
+                socket_path.display()
+            ))
+        })?;
+    ensure_memories_table_via_server(&mut client, table_name)
+        .await
+        .map_err(|e| StoreError::Connect(e.to_string()))?;
+    let namespace = namespace_of(table_name);
+    if namespace.is_some() {
+        ensure_memories_table_via_server(&mut client, &resolve_table_name(None))
+            .await
+            .map_err(|e| StoreError::Connect(e.to_string()))?;
+    }
+    let capabilities = probe_capabilities(&mut client, table_name).await;
+    let missing = capabilities.missing();
+    if !missing.is_empty() {
+        tracing::warn!(
+            "ferridyn-server lacks {}; upgrade it to use them",
+            missing.join(" and ")
+        );
+    }
+    let backend = MemoryBackend::server(Arc::new(Mutex::new(client)), table_name.to_string())
+        .with_capabilities(capabilities)
+        .with_socket_path(&socket_path)
+        .with_timeouts(Timeouts::from_env())
+        .with_item_size_limits(ItemSizeLimits::from_env())
+        .with_write_validation(WriteValidation::from_env());
+    if let Some(namespace) = namespace
+        && let Err(e) = register_namespace(&backend, &namespace).await
+    {
+        tracing::warn!("Failed to register namespace {namespace}: {e}");
+    }
+    let backend = with_read_replica_from_env(backend).await;
+    Ok(with_mirror_from_env(backend, table_name).await)
+}
+
+/// Copy writes of `backend` to the mirror named by
+/// `FERRIDYN_MEMORY_MIRROR_SOCKET`, if any. An unreachable mirror is skipped
+/// with a warning; `fmemory mirror-sync` catches it up later.
+async fn with_mirror_from_env(backend: MemoryBackend, table_name: &str) -> MemoryBackend {
+    let Ok(path) = std::env::var(MIRROR_SOCKET_ENV) else {
+        return backend;
+    };
+    let path = std::path::PathBuf::from(path);
+    let connected = async {
+        let mut client = ferridyn_server::FerridynClient::connect(&path).await?;
+        ensure_memories_table_via_server(&mut client, table_name).await?;
+        Ok::<_, ferridyn_server::error::ClientError>(client)
+    };
+    match connected.await {
+        Ok(client) => {
+            let mirror =
+                MemoryBackend::server(Arc::new(Mutex::new(client)), table_name.to_string())
+                    .with_socket_path(&path)
+                    .with_timeouts(Timeouts::from_env());
+            backend.with_mirror(mirror)
+        }
+        Err(e) => {
+            tracing::warn!(
+                "Mirror at {} unreachable, writes are not mirrored: {e}",
+                path.display()
+            );
+            backend
+        }
+    }
+}
+
+/// Route reads of `backend` to the replica named by
+/// `FERRIDYN_MEMORY_READ_SOCKET`, if any. An unreachable replica is skipped
+/// with a warning; everything then goes to the primary.
+async fn with_read_replica_from_env(backend: MemoryBackend) -> MemoryBackend {
+    let Ok(path) = std::env::var(READ_SOCKET_ENV) else {
+        return backend;
+    };
+    let path = std::path::PathBuf::from(path);
+    let max_lag = match std::env::var(REPLICA_MAX_LAG_ENV) {
+        Ok(value) if value.trim() == "0" => std::time::Duration::ZERO,
+        Ok(value) => parse_duration(&value).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring {REPLICA_MAX_LAG_ENV}: {e}");
+            DEFAULT_REPLICA_MAX_LAG
+        }),
+        Err(_) => DEFAULT_REPLICA_MAX_LAG,
+    };
+    match ferridyn_server::FerridynClient::connect(&path).await {
+        Ok(client) => backend.with_read_replica(Arc::new(Mutex::new(client)), &path, max_lag),
+        Err(e) => {
+            tracing::warn!(
+                "Read replica at {} unreachable, reading from the primary: {e}",
+                path.display()
+            );
+            backend
+        }
+    }
+}
+
+/// Execute a resolved query against the backend.
+pub async fn execute_resolved_query(
+    backend: &MemoryBackend,
+    resolved: &ResolvedQuery,
+    limit: usize,
+) -> Result<Vec<Value>, MemoryError> {
+    match resolved {
+        ResolvedQuery::IndexLookup {
+            index_name,
+            key_value,
+            ..
+        } => {
+            backend
+                .query_index(index_name, Value::String(key_value.clone()), Some(limit))
+                .await
+        }
+        ResolvedQuery::PartitionScan {
+            category,
+            key_prefix,
+        } => backend.query(category, key_prefix.as_deref(), limit).await,
+        ResolvedQuery::ExactLookup { category, key } => {
+            let item = backend.get_item(category, key).await?;
+            Ok(item.into_iter().collect())
+        }
+        ResolvedQuery::IndexRange {
+            index_name,
+            from,
+            to,
+            ..
+        } => {
+            let parse = |date: &str| {
+                NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .map_err(|e| MemoryError::InvalidParams(format!("invalid date '{date}': {e}")))
+            };
+            let span = DateSpan {
+                from: parse(from)?,
+                to: parse(to)?,
+            };
+            let mut items = Vec::new();
+            for day in span.days() {
+                if items.len() >= limit {
+                    break;
+                }
+                let key_value = Value::String(day.format("%Y-%m-%d").to_string());
+                let found = backend
+                    .query_index(index_name, key_value, Some(limit - items.len()))
+                    .await?;
+                items.extend(found);
+            }
+            Ok(items)
+        }
+    }
+}
+
+/// Execute a resolved query with broadening fallback.
+///
+/// If the initial query returns no results, falls back to scanning the entire
+/// category. Each item is annotated with the strategy that produced it
+/// ([`STRATEGY_ATTRIBUTE`]). Returns `(items, is_fallback)`.
+pub async fn execute_with_fallback(
+    backend: &MemoryBackend,
+    resolved: &ResolvedQuery,
+    limit: usize,
+) -> Result<(Vec<Value>, bool), MemoryError> {
+    let mut items = execute_resolved_query(backend, resolved, limit).await?;
+    if !items.is_empty() {
+        annotate_strategy(&mut items, resolved.strategy());
+        return Ok((items, false));
+    }
+
+    // Already a full category scan — no broader fallback possible.
+    if matches!(
+        resolved,
+        ResolvedQuery::PartitionScan {
+            key_prefix: None,
+            ..
+        }
+    ) {
+        return Ok((items, false));
+    }
+
+    let category = resolved_category(resolved);
+    let mut fallback_items = backend.query(category, None, limit).await?;
+    annotate_strategy(&mut fallback_items, "fallback");
+    let has_results = !fallback_items.is_empty();
+    Ok((fallback_items, has_results))
+}
+
+/// Look for `query` beyond the category it resolved to, once that category
+/// came up empty even after the category-wide fallback.
+///
+/// The [`BROADEN_MAX_CATEGORIES`] other categories most relevant to the
+/// query are tried, ranked by keyword overlap with their names, attributes,
+/// sample keys, and descriptions, followed by `notes`, where items often
+/// land when categorization varied. The first
+/// category with items sharing a keyword with the query wins: up to `limit`
+/// of them, most overlapping first, annotated `broadened`. `interactions`
+/// is skipped, as its recall logs repeat earlier queries.
+pub async fn broaden_across_categories(
+    backend: &MemoryBackend,
+    schemas: &[PartitionSchemaInfo],
+    category_keys: &[(String, Vec<String>)],
+    query: &str,
+    tried: &str,
+    limit: usize,
+) -> Result<Vec<Value>, MemoryError> {
+    let query_terms = terms(query);
+    if query_terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut candidates: Vec<&PartitionSchemaInfo> = rank_categories(schemas, category_keys, query)
+        .into_iter()
+        .filter(|s| s.prefix != tried && s.prefix != INTERACTIONS_CATEGORY)
+        .take(BROADEN_MAX_CATEGORIES)
+        .collect();
+    if let Some(notes) = schemas.iter().find(|s| s.prefix == NOTES_CATEGORY)
+        && notes.prefix != tried
+        && !candidates.iter().any(|s| s.prefix == NOTES_CATEGORY)
+    {
+        candidates.push(notes);
+    }
+
+    for schema in candidates {
+        let mut scored: Vec<(usize, Value)> = backend
+            .query(&schema.prefix, None, BROADEN_SCAN_LIMIT)
+            .await?
+            .into_iter()
+            .filter(|item| !is_chunk(item))
+            .map(|item| (overlap(&query_terms, &item_text(&item)), item))
+            .filter(|(score, _)| *score > 0)
+            .collect();
+        if scored.is_empty() {
+            continue;
+        }
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        let mut items: Vec<Value> = scored
+            .into_iter()
+            .take(limit)
+            .map(|(_, item)| item)
+            .collect();
+        annotate_strategy(&mut items, "broadened");
+        return Ok(items);
+    }
+    Ok(Vec::new())
+}
+
+/// The key and string values of `item`, for keyword matching.
+fn item_text(item: &Value) -> String {
+    let Some(obj) = item.as_object() else {
+        return String::new();
+    };
+    obj.iter()
+        .filter(|(name, _)| name.as_str() != "category")
+        .filter_map(|(_, value)| value.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Set [`STRATEGY_ATTRIBUTE`] on every item.
+pub fn annotate_strategy(items: &mut [Value], strategy: &str) {
+    for item in items.iter_mut().filter(|item| item.is_object()) {
+        item[STRATEGY_ATTRIBUTE] = Value::String(strategy.to_string());
+    }
+}
+
+/// Drop repeated `category/key` items, keeping the first of each.
+pub fn dedup_items(items: &mut Vec<Value>) {
+    let mut seen = HashSet::new();
+    items.retain(|item| item_ref(item).is_none_or(|id| seen.insert(id)));
+}
+
+/// Extract the category from any resolved query variant.
+fn resolved_category(resolved: &ResolvedQuery) -> &str {
+    match resolved {
+        ResolvedQuery::IndexLookup { category, .. }
+        | ResolvedQuery::PartitionScan { category, .. }
+        | ResolvedQuery::ExactLookup { category, .. }
+        | ResolvedQuery::IndexRange { category, .. } => category,
+    }
+}
+
+/// Fetch a sample of sort keys for each category (for query resolution context).
+pub async fn fetch_category_keys(
+    backend: &MemoryBackend,
+    schemas: &[PartitionSchemaInfo],
+) -> Vec<(String, Vec<String>)> {
+    // Owned prefixes keep the stream `Send` for callers in spawned tasks.
+    let prefixes: Vec<String> = schemas.iter().map(|s| s.prefix.clone()).collect();
+    stream::iter(prefixes)
+        .map(|prefix| async move {
+            let keys = backend
+                .list_sort_key_prefixes(&prefix, 20)
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                .collect();
+            (prefix, keys)
+        })
+        .buffered(MAX_CONCURRENT_CATEGORY_OPS)
+        .collect()
+        .await
+}
+
+#[cfg(feature = "llm")]
+/// Split oversized input into chunks and summarize it for parsing.
+///
+/// Returns `(parse_input, chunks)`; input under the chunking threshold is
+/// returned unchanged with no chunks.
+pub async fn summarize_if_oversized(
+    llm: &dyn LlmClient,
+    text: &str,
+) -> Result<(String, Vec<String>), LlmError> {
+    if !needs_chunking(text) {
+        return Ok((text.to_string(), Vec::new()));
+    }
+    let summary = summarize_text(llm, text).await?;
+    Ok((summary, split_into_chunks(text, CHUNK_SIZE_CHARS)))
+}
+
+#[cfg(feature = "llm")]
+/// Parse input into a structured document, picking a category if none is given.
+///
+/// Returns `(category, key, document)`. An explicit `key` overrides the parsed
+/// one; otherwise `key_strategies` decides between the parsed key and a ULID.
+pub async fn parse_memory(
+    llm: &dyn LlmClient,
+    snapshot: &SchemaSnapshot,
+    key_strategies: &KeyStrategies,
+    category: Option<String>,
+    key: Option<String>,
+    input_text: &str,
+) -> Result<(String, String, Value), StoreError> {
+    if let Some(cat) = category {
+        // Category provided: validate it has a schema.
+        let Some(schema_info) = snapshot.get(&cat) else {
+            return Err(unknown_category(snapshot, &cat));
+        };
+
+        let doc = parse_to_document(
+            llm,
+            &cat,
+            schema_info,
+            &snapshot.aliases,
+            snapshot.hints_of(&cat),
+            input_text,
+        )
+        .await?;
+        let parsed_key = doc["key"].as_str().unwrap_or("unknown").to_string();
+        let used_key = key.unwrap_or_else(|| key_strategies.key_for(&cat, parsed_key));
+        Ok((cat, used_key, doc))
+    } else {
+        // No category: let LLM pick from available schemas.
+        let doc = parse_to_document_with_category(
+            llm,
+            &snapshot.schemas,
+            &snapshot.aliases,
+            &snapshot.hints,
+            input_text,
+        )
+        .await?;
+        let chosen_cat = doc["category"].as_str().unwrap_or("notes").to_string();
+        let parsed_key = doc["key"].as_str().unwrap_or("unknown").to_string();
+        let used_key = key.unwrap_or_else(|| key_strategies.key_for(&chosen_cat, parsed_key));
+        Ok((chosen_cat, used_key, doc))
+    }
+}
+
+#[cfg(feature = "llm")]
+/// The error for a category without a schema, listing the defined ones.
+fn unknown_category(snapshot: &SchemaSnapshot, category: &str) -> StoreError {
+    StoreError::UnknownCategory {
+        category: category.to_string(),
+        available: snapshot.category_names(),
+    }
+}
+
+/// Build the item to store from a parsed document.
+///
+/// Injects `created_at` and an `expires_at` from the explicit TTL or the
+/// category default.
+pub fn build_item(category: &str, key: &str, doc: &Value, ttl: Option<Duration>) -> Value {
+    let mut final_item = serde_json::json!({
+        "category": category,
+        "key": key,
+    });
+    if let Some(obj) = doc.as_object() {
+        for (k, v) in obj {
+            if k == "key" || k == "category" {
+                continue;
+            }
+            final_item[k] = v.clone();
+        }
+    }
+    // Auto-inject created_at and updated_at timestamps.
+    let now = Value::String(chrono::Utc::now().to_rfc3339());
+    final_item["created_at"] = now.clone();
+    final_item[UPDATED_AT_ATTRIBUTE] = now;
+
+    // Auto-inject expires_at based on the explicit TTL or category defaults.
+    if let Some(duration) = ttl {
+        final_item["expires_at"] = Value::String(compute_expires_at(duration));
+    } else if category == "scratchpad" {
+        final_item["expires_at"] = Value::String(compute_expires_at(SCRATCHPAD_DEFAULT_TTL));
+    } else if category == "sessions" {
+        final_item["expires_at"] = Value::String(compute_expires_at(SESSIONS_DEFAULT_TTL));
+    } else if category == "interactions" {
+        final_item["expires_at"] = Value::String(compute_expires_at(INTERACTIONS_DEFAULT_TTL));
+    } else if category == "events"
+        && let Some(expires) = auto_ttl_from_date(&final_item, &DateContext::from_env())
+    {
+        final_item["expires_at"] = Value::String(expires);
+    }
+    final_item
+}
+
+/// Highest numeric suffix tried by [`CollisionPolicy::Suffix`].
+pub const MAX_KEY_SUFFIX: usize = 100;
+
+/// What to do when a generated key already holds an item with different
+/// content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CollisionPolicy {
+    /// Overwrite the existing item.
+    Replace,
+    /// Store under the first free `key-2`, `key-3`, ... instead.
+    #[default]
+    Suffix,
+    /// Merge into the existing item; new attributes win, `created_at` is kept.
+    Merge,
+}
+
+impl std::str::FromStr for CollisionPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "replace" => Ok(CollisionPolicy::Replace),
+            "suffix" => Ok(CollisionPolicy::Suffix),
+            "merge" => Ok(CollisionPolicy::Merge),
+            other => Err(format!(
+                "Unknown collision policy '{other}' (expected replace, suffix, or merge)"
+            )),
+        }
+    }
+}
+
+/// Store a new item whose key was generated (not chosen by the caller),
+/// adjusted by [`resolve_key_collision`], and return what was written. The
+/// chunks of an item it replaces are deleted; the caller stores its own.
+///
+/// The lookup and the write hold the backend's
+/// [`lock_updates`](MemoryBackend::lock_updates), so writers sharing this
+/// backend or its clones never both find a key free and overwrite each
+/// other. ferridyn-server has no conditional put, so a write from another
+/// process landing between the lookup and the write is still replaced.
+pub async fn put_generated_item(
+    backend: &MemoryBackend,
+    item: Value,
+    policy: CollisionPolicy,
+) -> Result<Value, MemoryError> {
+    let _held = backend.lock_updates().await;
+    let item = resolve_key_collision(backend, item, policy).await?;
+    if policy == CollisionPolicy::Replace
+        && let (Some(category), Some(key)) = (item["category"].as_str(), item["key"].as_str())
+        && let Some(existing) = backend.get_item(category, key).await?
+    {
+        delete_chunks(backend, &existing).await?;
+    }
+    backend.put_item(item.clone()).await?;
+    Ok(item)
+}
+
+/// Adjust a new item whose key was generated (not chosen by the caller) so
+/// storing it follows `policy` instead of silently replacing a different item.
+/// [`put_generated_item`] resolves and writes it in one step.
+///
+/// An existing item with the same attributes (ignoring timestamps) is simply
+/// refreshed. For [`CollisionPolicy::Merge`], chunks of the existing item are
+/// deleted when the new item brings its own `content`.
+pub async fn resolve_key_collision(
+    backend: &MemoryBackend,
+    mut item: Value,
+    policy: CollisionPolicy,
+) -> Result<Value, MemoryError> {
+    let (Some(category), Some(key)) = (item["category"].as_str(), item["key"].as_str()) else {
+        return Ok(item);
+    };
+    let (category, key) = (category.to_string(), key.to_string());
+    if policy == CollisionPolicy::Replace {
+        return Ok(item);
+    }
+    let Some(existing) = backend.get_item(&category, &key).await? else {
+        return Ok(item);
+    };
+    if same_content(&existing, &item) {
+        return Ok(item);
+    }
+
+    match policy {
+        CollisionPolicy::Replace => Ok(item),
+        CollisionPolicy::Merge => {
+            let mut merged = existing.clone();
+            if item.get("content").is_some() && existing.get("chunk_count").is_some() {
+                delete_chunks(backend, &existing).await?;
+                merged.as_object_mut().map(|m| m.remove("chunk_count"));
+            }
+            if let Some(obj) = item.as_object() {
+                for (k, v) in obj {
+                    if k != "created_at" && !v.is_null() {
+                        merged[k] = v.clone();
+                    }
+                }
+            }
+            Ok(merged)
+        }
+        CollisionPolicy::Suffix => {
+            for n in 2..=MAX_KEY_SUFFIX {
+                let candidate = format!("{key}-{n}");
+                let taken = backend.get_item(&category, &candidate).await?;
+                if taken.is_none_or(|other| same_content(&other, &item)) {
+                    item["key"] = Value::String(candidate);
+                    return Ok(item);
+                }
+            }
+            Err(MemoryError::InvalidParams(format!(
+                "No free key for {category}/{key} after {MAX_KEY_SUFFIX} suffixes"
+            )))
+        }
+    }
+}
+
+/// Whether two items carry the same attributes, ignoring key and timestamps.
+fn same_content(a: &Value, b: &Value) -> bool {
+    const IGNORED: &[&str] = &[
+        "key",
+        "created_at",
+        "expires_at",
+        UPDATED_AT_ATTRIBUTE,
+        LAST_ACCESSED_ATTRIBUTE,
+        REVIEW_COUNT_ATTRIBUTE,
+        CONFLICTS_ATTRIBUTE,
+        RELATED_ATTRIBUTE,
+    ];
+    fn attrs(v: &Value) -> Option<BTreeMap<&String, &Value>> {
+        v.as_object().map(|obj| {
+            obj.iter()
+                .filter(|(k, v)| !IGNORED.contains(&k.as_str()) && !v.is_null())
+                .collect()
+        })
+    }
+    attrs(a) == attrs(b)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "llm")]
+    use crate::llm::MockLlmClient;
+    use serde_json::json;
+
+    fn test_store() -> (tempfile::TempDir, MemoryStore) {
+        use crate::TABLE_NAME;
+        use ferridyn_core::api::FerridynDB;
+        use ferridyn_core::types::KeyType;
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = FerridynDB::create(dir.path().join("test.db")).unwrap();
+        db.create_table(TABLE_NAME)
+            .partition_key("category", KeyType::String)
+            .sort_key("key", KeyType::String)
+            .execute()
+            .unwrap();
+        let backend = MemoryBackend::direct(db, TABLE_NAME.to_string());
+        (dir, MemoryStore::new(backend))
+    }
+
+    #[test]
+    fn test_build_item_injects_timestamps() {
+        let doc = json!({"key": "ignored", "category": "ignored", "note": "hi"});
+        let item = build_item("scratchpad", "k", &doc, None);
+        assert_eq!(item["category"], "scratchpad");
+        assert_eq!(item["key"], "k");
+        assert_eq!(item["note"], "hi");
+        assert!(item["created_at"].is_string());
+        assert!(item["expires_at"].is_string());
+
+        let item = build_item("notes", "k", &doc, None);
+        assert!(item.get("expires_at").is_none());
+        let item = build_item("notes", "k", &doc, Some(Duration::hours(1)));
+        assert!(item["expires_at"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_store_get_and_forget() {
+        let (_dir, store) = test_store();
+        let content = "word ".repeat(1500);
+        store
+            .store_structured(
+                "project",
+                "design",
+                &json!({"content": content.clone()}),
+                None,
+            )
+            .await
+            .unwrap();
+
+        let item = store.get("project", "design").await.unwrap().unwrap();
+        assert!(item["chunk_count"].as_u64().unwrap() > 1);
+        assert!(item["content"].as_str().unwrap().len() > CHUNK_SIZE_CHARS);
+        assert_eq!(store.list("project", 10).await.unwrap().len(), 1);
+
+        assert!(store.forget("project", "design").await.unwrap());
+        assert!(!store.forget("project", "design").await.unwrap());
+        assert!(
+            store
+                .backend()
+                .query("project", None, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_put_enforces_category_quota() {
+        let (_dir, store) = test_store();
+        let store = store.with_quota("notes", Some(Quota::new(2)));
+        let mut evicted = Vec::new();
+        for (key, day) in [("a", 1), ("b", 2), ("c", 3)] {
+            let item = json!({
+                "category": "notes",
+                "key": key,
+                "created_at": format!("2026-01-0{day}T00:00:00Z"),
+            });
+            evicted.extend(store.put_with_chunks(&item, &[]).await.unwrap());
+        }
+        assert_eq!(evicted, vec!["a"]);
+        assert_eq!(store.list("notes", 10).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_expired_items_hidden_unless_requested() {
+        let (_dir, store) = test_store();
+        store
+            .store_structured("notes", "old", &json!({}), Some(Duration::seconds(-60)))
+            .await
+            .unwrap();
+
+        assert!(store.get("notes", "old").await.unwrap().is_none());
+        let store = store.with_include_expired(true);
+        assert!(store.get("notes", "old").await.unwrap().is_some());
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_natural_language_ops_require_llm() {
+        let (_dir, store) = test_store();
+        assert!(matches!(
+            store.remember("anything").await,
+            Err(StoreError::NoLlm)
+        ));
+        assert!(matches!(
+            store.recall("anything").await,
+            Err(StoreError::NoLlm)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_fallback_broadens_to_category() {
+        let (_dir, store) = test_store();
+        store
+            .store_structured("contacts", "toby", &json!({"name": "Toby"}), None)
+            .await
+            .unwrap();
+
+        let resolved = ResolvedQuery::ExactLookup {
+            category: "contacts".to_string(),
+            key: "missing".to_string(),
+        };
+        let (items, is_fallback) = execute_with_fallback(store.backend(), &resolved, 10)
+            .await
+            .unwrap();
+        assert!(is_fallback);
+        assert_eq!(items[0]["key"], "toby");
+        assert_eq!(items[0][STRATEGY_ATTRIBUTE], "fallback");
+
+        let resolved = ResolvedQuery::ExactLookup {
+            category: "contacts".to_string(),
+            key: "toby".to_string(),
+        };
+        let (mut items, is_fallback) = execute_with_fallback(store.backend(), &resolved, 10)
+            .await
+            .unwrap();
+        assert!(!is_fallback);
+        assert_eq!(items[0][STRATEGY_ATTRIBUTE], "exact");
+
+        items.push(json!({"category": "contacts", "key": "toby", STRATEGY_ATTRIBUTE: "related"}));
+        dedup_items(&mut items);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0][STRATEGY_ATTRIBUTE], "exact");
+    }
+
+    #[tokio::test]
+    async fn test_execute_index_range() {
+        let backend = MemoryBackend::in_memory(crate::TABLE_NAME.to_string());
+        backend.ensure_predefined_schemas().await.unwrap();
+        for (key, date) in [
+            ("standup", "2026-03-04"),
+            ("review", "2026-03-06"),
+            ("offsite", "2026-03-12"),
+        ] {
+            backend
+                .put_item(json!({"category": "events", "key": key, "title": key, "date": date}))
+                .await
+                .unwrap();
+        }
+        let resolved = ResolvedQuery::IndexRange {
+            category: "events".into(),
+            index_name: "events_date".into(),
+            from: "2026-03-02".into(),
+            to: "2026-03-08".into(),
+        };
+        let (items, is_fallback) = execute_with_fallback(&backend, &resolved, 10)
+            .await
+            .unwrap();
+        assert!(!is_fallback);
+        let keys: Vec<&str> = items.iter().filter_map(|i| i["key"].as_str()).collect();
+        assert_eq!(keys, ["standup", "review"]);
+        assert_eq!(items[0][STRATEGY_ATTRIBUTE], "range");
+
+        let (items, _) = execute_with_fallback(&backend, &resolved, 1).await.unwrap();
+        assert_eq!(items.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_broaden_across_categories() {
+        let backend = MemoryBackend::in_memory(crate::TABLE_NAME.to_string());
+        for item in [
+            json!({"category": "notes", "key": "standup", "content": "Standup moved to 10am"}),
+            json!({"category": "notes", "key": "lunch", "content": "Tacos on Friday"}),
+            json!({"category": "interactions", "key": "01", "summary": "Recall: standup time"}),
+            json!({"category": "contacts", "key": "toby", "name": "Toby"}),
+        ] {
+            backend.put_item(item).await.unwrap();
+        }
+        let schema = |prefix: &str| PartitionSchemaInfo {
+            prefix: prefix.into(),
+            description: String::new(),
+            attributes: Vec::new(),
+            validate: true,
+        };
+        let schemas = [
+            schema("events"),
+            schema("contacts"),
+            schema("interactions"),
+            schema("notes"),
+        ];
+
+        let items =
+            broaden_across_categories(&backend, &schemas, &[], "when is standup?", "events", 10)
+                .await
+                .unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["key"], "standup");
+        assert_eq!(items[0][STRATEGY_ATTRIBUTE], "broadened");
+
+        let none = broaden_across_categories(&backend, &schemas, &[], "standup", "notes", 10)
+            .await
+            .unwrap();
+        assert!(none.is_empty());
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_summarize_if_oversized() {
+        let mock = MockLlmClient::new(vec!["summary".to_string()]);
+        let (input, chunks) = summarize_if_oversized(&mock, "short").await.unwrap();
+        assert_eq!(input, "short");
+        assert!(chunks.is_empty());
+
+        let long = "word ".repeat(1500);
+        let (input, chunks) = summarize_if_oversized(&mock, &long).await.unwrap();
+        assert_eq!(input, "summary");
+        assert!(chunks.len() > 1);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_key_collision() {
+        let backend = MemoryBackend::in_memory(crate::TABLE_NAME.to_string());
+        backend
+            .put_item(
+                json!({"category": "contacts", "key": "toby", "email": "a@x", "created_at": "t0"}),
+            )
+            .await
+            .unwrap();
+        let new = |attrs: Value| {
+            let mut item = json!({"category": "contacts", "key": "toby", "created_at": "t1"});
+            item.as_object_mut()
+                .unwrap()
+                .extend(attrs.as_object().unwrap().clone());
+            item
+        };
+
+        // Same content: refreshed in place.
+        let same = resolve_key_collision(
+            &backend,
+            new(json!({"email": "a@x"})),
+            CollisionPolicy::Suffix,
+        )
+        .await
+        .unwrap();
+        assert_eq!(same["key"], "toby");
+
+        let differs = new(json!({"phone": "123"}));
+        let replaced = resolve_key_collision(&backend, differs.clone(), CollisionPolicy::Replace)
+            .await
+            .unwrap();
+        assert_eq!(replaced, differs);
+
+        let suffixed = resolve_key_collision(&backend, differs.clone(), CollisionPolicy::Suffix)
+            .await
+            .unwrap();
+        assert_eq!(suffixed["key"], "toby-2");
+        backend.put_item(suffixed).await.unwrap();
+        let next = resolve_key_collision(
+            &backend,
+            new(json!({"phone": "456"})),
+            CollisionPolicy::Suffix,
+        )
+        .await
+        .unwrap();
+        assert_eq!(next["key"], "toby-3");
+
+        let merged = resolve_key_collision(&backend, differs, CollisionPolicy::Merge)
+            .await
+            .unwrap();
+        assert_eq!(merged["key"], "toby");
+        assert_eq!(merged["email"], "a@x");
+        assert_eq!(merged["phone"], "123");
+        assert_eq!(merged["created_at"], "t0");
+    }
+
+    #[tokio::test]
+    async fn test_put_generated_item_claims_key_once() {
+        let backend = MemoryBackend::in_memory(crate::TABLE_NAME.to_string());
+        let writes = (0..4).map(|n| {
+            let item = json!({"category": "notes", "key": "idea", "content": format!("idea {n}")});
+            put_generated_item(&backend, item, CollisionPolicy::Suffix)
+        });
+        let mut keys: Vec<String> = futures::future::try_join_all(writes)
+            .await
+            .unwrap()
+            .iter()
+            .map(|item| item["key"].as_str().unwrap().to_string())
+            .collect();
+        keys.sort();
+        assert_eq!(keys, ["idea", "idea-2", "idea-3", "idea-4"]);
+        assert_eq!(backend.query("notes", None, 10).await.unwrap().len(), 4);
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_remember_takes_ttl_from_text() {
+        let mock = MockLlmClient::new(vec![
+            r#"{"key":"parking","content":"Level 3","ttl":"1w"}"#.into(),
+            r#"{"key":"gate","content":"Gate B","ttl":"1w"}"#.into(),
+            "[]".into(),
+        ]);
+        let store = MemoryStore::new(MemoryBackend::in_memory(crate::TABLE_NAME.to_string()))
+            .with_llm(Arc::new(mock));
+        store.init().await.unwrap();
+        let notes = || RememberOptions {
+            category: Some("notes".into()),
+            ..Default::default()
+        };
+
+        let item = store
+            .remember_with(notes(), "parked on level 3, remember it for a week")
+            .await
+            .unwrap();
+        assert!(item.get("ttl").is_none());
+        let expires =
+            chrono::DateTime::parse_from_rfc3339(item["expires_at"].as_str().unwrap()).unwrap();
+        let left = expires.with_timezone(&chrono::Utc) - chrono::Utc::now();
+        assert!(left > Duration::days(6) && left <= Duration::weeks(1));
+
+        // An explicit TTL wins.
+        let explicit = RememberOptions {
+            ttl: Some(Duration::hours(1)),
+            ..notes()
+        };
+        let item = store
+            .remember_with(explicit, "gate B, for a week")
+            .await
+            .unwrap();
+        let expires =
+            chrono::DateTime::parse_from_rfc3339(item["expires_at"].as_str().unwrap()).unwrap();
+        assert!(expires.with_timezone(&chrono::Utc) - chrono::Utc::now() <= Duration::hours(1));
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_remember_batch() {
+        let mock = MockLlmClient::new(vec![
+            r#"[
+                {"fact": 2, "category": "notes", "key": "parking", "content": "Level 3"},
+                {"fact": 1, "category": "contacts", "key": "toby", "name": "Toby"}
+            ]"#
+            .into(),
+            // Fact 3 is missing from the batch response and parsed on its own.
+            r#"{"category":"notes","key":"standup","content":"Standup at 9"}"#.into(),
+            "[]".into(),
+        ]);
+        let store = MemoryStore::new(MemoryBackend::in_memory(crate::TABLE_NAME.to_string()))
+            .with_llm(Arc::new(mock));
+        store.init().await.unwrap();
+        let facts: Vec<String> = ["Toby is on infra", "Parked on level 3", "Standup at 9"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        let results = store
+            .remember_batch(&facts, RememberOptions::default())
+            .await
+            .unwrap();
+        let items: Vec<Value> = results.into_iter().map(Result::unwrap).collect();
+        assert_eq!(items[0]["category"], "contacts");
+        assert_eq!(items[0]["key"], "toby");
+        assert_eq!(items[1]["key"], "parking");
+        assert_eq!(items[2]["key"], "standup");
+        assert!(store.get("notes", "parking").await.unwrap().is_some());
+
+        let unknown = RememberOptions {
+            category: Some("nope".into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            store.remember_batch(&facts, unknown).await,
+            Err(StoreError::UnknownCategory { .. })
+        ));
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_remember_uses_category_key_strategy() {
+        let mock = MockLlmClient::new(vec![
+            r#"{"key":"idea","content":"try sqlite"}"#.into(),
+            r#"{"key":"Toby","name":"Toby"}"#.into(),
+        ]);
+        let store = MemoryStore::new(MemoryBackend::in_memory(crate::TABLE_NAME.to_string()))
+            .with_llm(Arc::new(mock))
+            .with_key_strategy("scratchpad", KeyStrategy::Ulid);
+        store.init().await.unwrap();
+
+        let scratch = RememberOptions {
+            category: Some("scratchpad".into()),
+            ..Default::default()
+        };
+        let item = store.remember_with(scratch, "try sqlite").await.unwrap();
+        assert_eq!(item["key"].as_str().unwrap().len(), 26);
+
+        let contact = RememberOptions {
+            category: Some("contacts".into()),
+            ..Default::default()
+        };
+        let item = store.remember_with(contact, "Toby").await.unwrap();
+        assert_eq!(item["key"], "toby");
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_remember_links_and_recall_hydrates_related() {
+        let mock = MockLlmClient::new(vec![
+            r#"{"key":"auth-method","decision":"JWT","related":["contacts/toby"]}"#.into(),
+            r#"{"type":"exact","category":"contacts","key":"toby"}"#.into(),
+            r#"{"type":"exact","category":"contacts","key":"toby"}"#.into(),
+        ]);
+        let store = MemoryStore::new(MemoryBackend::in_memory(crate::TABLE_NAME.to_string()))
+            .with_llm(Arc::new(mock));
+        store.init().await.unwrap();
+        store
+            .store_structured("contacts", "toby", &json!({"name": "Toby"}), None)
+            .await
+            .unwrap();
+
+        let decision = RememberOptions {
+            category: Some("decisions".into()),
+            ..Default::default()
+        };
+        store
+            .remember_with(decision, "Toby picked JWT for auth")
+            .await
+            .unwrap();
+        let links = store.backend.get_links("contacts/toby").await.unwrap();
+        assert_eq!(links[0].source, "decisions/auth-method");
+
+        let items = store
+            .recall_items("what did Toby decide", 10)
+            .await
+            .unwrap();
+        assert_eq!(items.len(), 1);
+        let items = store
+            .with_related(true)
+            .recall_items("what did Toby decide", 10)
+            .await
+            .unwrap();
+        let keys: Vec<&str> = items.iter().filter_map(|i| i["key"].as_str()).collect();
+        assert_eq!(keys, vec!["toby", "auth-method"]);
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_recall_log_records_interaction() {
+        let mock = MockLlmClient::new(vec![
+            r#"{"type":"exact","category":"contacts","key":"toby"}"#.into(),
+            "Toby is the CTO.".into(),
+        ]);
+        let store = MemoryStore::new(MemoryBackend::in_memory(crate::TABLE_NAME.to_string()))
+            .with_llm(Arc::new(mock))
+            .with_recall_log(true);
+        store.init().await.unwrap();
+        store
+            .store_structured("contacts", "toby", &json!({"role": "CTO"}), None)
+            .await
+            .unwrap();
+
+        let recall = store.recall("who is the CTO?").await.unwrap();
+        assert_eq!(recall.answer.as_deref(), Some("Toby is the CTO."));
+        let logs = store.list("interactions", 10).await.unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0]["query"], "who is the CTO?");
+        assert_eq!(logs[0]["plan"]["type"], "exact");
+        assert_eq!(logs[0]["items"], json!(["contacts/toby"]));
+        assert_eq!(logs[0]["answer"], "Toby is the CTO.");
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_recall_cached_until_write() {
+        let resolve = r#"{"type":"exact","category":"contacts","key":"toby"}"#;
+        let mock = Arc::new(MockLlmClient::new(vec![
+            resolve.into(),
+            "Toby is the CTO.".into(),
+            resolve.into(),
+            "Toby is the CEO.".into(),
+        ]));
+        let store = MemoryStore::new(MemoryBackend::in_memory(crate::TABLE_NAME.to_string()))
+            .with_llm(mock.clone())
+            .with_recall_cache(RecallCache::default());
+        store.init().await.unwrap();
+        store
+            .store_structured("contacts", "toby", &json!({"role": "CTO"}), None)
+            .await
+            .unwrap();
+
+        let first = store.recall("who is the CTO?").await.unwrap();
+        let again = store.recall("Who is  the CTO?").await.unwrap();
+        assert_eq!(again.answer.as_deref(), Some("Toby is the CTO."));
+        assert_eq!(again.items, first.items);
+        assert_eq!(mock.requests.lock().unwrap().len(), 2);
+
+        // Access bookkeeping does not count as a write; a real write does.
+        store
+            .store_structured("contacts", "toby", &json!({"role": "CEO"}), None)
+            .await
+            .unwrap();
+        let fresh = store.recall("who is the CTO?").await.unwrap();
+        assert_eq!(fresh.answer.as_deref(), Some("Toby is the CEO."));
+        assert_eq!(fresh.items[0]["role"], "CEO");
+        assert_eq!(mock.requests.lock().unwrap().len(), 4);
+    }
+
+    #[cfg(feature = "llm")]
+    #[tokio::test]
+    async fn test_summarize_writes_overview() {
+        let mock = MockLlmClient::new(vec!["Uses Postgres; deploys weekly.".into()]);
+        let store = MemoryStore::new(MemoryBackend::in_memory(crate::TABLE_NAME.to_string()))
+            .with_llm(Arc::new(mock));
+        assert!(store.summarize("project").await.unwrap().is_none());
+
+        for (key, details) in [("db", "Postgres 16"), ("deploys", "Weekly on Fridays")] {
+            store
+                .store_structured("project", key, &json!({"details": details}), None)
+                .await
+                .unwrap();
+        }
+        let overview = store.summarize("project").await.unwrap().unwrap();
+        assert_eq!(overview["key"], OVERVIEW_KEY);
+        assert_eq!(overview["content"], "Uses Postgres; deploys weekly.");
+        assert_eq!(overview["item_count"], 2);
+        assert!(store.get("project", OVERVIEW_KEY).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,128 @@
+//! Tenant resolution for an MCP server shared by several clients.
+//!
+//! A [`TenantMap`] assigns each client token to a tenant, and each tenant
+//! owns the namespace of the same name. When a [`MemoryServer`] is built with
+//! one ([`MemoryServerBuilder::tenants`]) and served over rmcp's streamable
+//! HTTP transport ([`run_mcp_http_server`], `fmemory serve --listen ADDR
+//! --tenants FILE`), every tool call must carry `Authorization: Bearer <token>`;
+//! the call then runs in its tenant's namespace and may not select another.
+//!
+//! Tenants files hold one `tenant token` pair per line; blank lines and lines
+//! starting with `#` are ignored.
+//!
+//! [`MemoryServer`]: crate::mcp::MemoryServer
+//! [`MemoryServerBuilder::tenants`]: crate::mcp::MemoryServerBuilder::tenants
+//! [`run_mcp_http_server`]: crate::mcp::run_mcp_http_server
+
+use std::path::Path;
+
+use crate::http::tokens_match;
+
+/// Client tokens and the tenants they belong to.
+#[derive(Debug, Clone, Default)]
+pub struct TenantMap {
+    /// `(token, tenant)` pairs.
+    entries: Vec<(String, String)>,
+}
+
+impl TenantMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Grant `token` access as `tenant`. Fails if the tenant name is not a
+    /// valid namespace or the token is empty or already assigned.
+    pub fn insert(&mut self, token: &str, tenant: &str) -> Result<(), String> {
+        if tenant.is_empty()
+            || !tenant
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(format!(
+                "Invalid tenant '{tenant}': use letters, digits, '_' and '-'"
+            ));
+        }
+        if token.is_empty() {
+            return Err(format!("Empty token for tenant '{tenant}'"));
+        }
+        if self.entries.iter().any(|(t, _)| t == token) {
+            return Err(format!("Token of tenant '{tenant}' is already assigned"));
+        }
+        self.entries.push((token.to_string(), tenant.to_string()));
+        Ok(())
+    }
+
+    /// Parse the contents of a tenants file.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut map = Self::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [tenant, token] = fields[..] else {
+                return Err(format!(
+                    "Line {}: expected 'tenant token', got {} field(s)",
+                    number + 1,
+                    fields.len()
+                ));
+            };
+            map.insert(token, tenant)
+                .map_err(|e| format!("Line {}: {e}", number + 1))?;
+        }
+        Ok(map)
+    }
+
+    /// Read and parse a tenants file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        Self::parse(&text).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// The tenant owning `token`. Every entry is compared, in constant time
+    /// per token, so the lookup does not reveal which tokens exist.
+    pub fn resolve(&self, token: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .fold(None, |found, (candidate, tenant)| {
+                if tokens_match(token, candidate) {
+                    Some(tenant.as_str())
+                } else {
+                    found
+                }
+            })
+    }
+
+    /// The tenant named by an `Authorization: Bearer <token>` header value.
+    pub fn resolve_bearer(&self, authorization: &str) -> Option<&str> {
+        self.resolve(authorization.strip_prefix("Bearer ")?)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_resolve_tenants() {
+        let map = TenantMap::parse("# team tenants\nacme  s3cret\n\nglobex tok-2\n").unwrap();
+        assert_eq!(map.resolve("s3cret"), Some("acme"));
+        assert_eq!(map.resolve_bearer("Bearer tok-2"), Some("globex"));
+        assert_eq!(map.resolve("s3cre"), None);
+        assert_eq!(map.resolve_bearer("tok-2"), None);
+
+        assert!(TenantMap::parse("acme").unwrap_err().starts_with("Line 1:"));
+        assert!(TenantMap::parse("a/b tok").is_err());
+        assert!(TenantMap::parse("acme tok\nglobex tok").is_err());
+    }
+}
@@ -0,0 +1,217 @@
+//! Time limits on backend and LLM calls.
+//!
+//! A hung server socket or a stalled LLM request would otherwise block a
+//! command or MCP tool call forever. [`Timeouts`] bounds each backend read,
+//! backend write, and LLM completion; a call that runs over fails with
+//! [`MemoryError::Timeout`](crate::error::MemoryError::Timeout) or
+//! [`LlmError::Timeout`], which reach tool results and `--json` envelopes
+//! like any other error. Limits come from `FMEMORY_TIMEOUTS`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tracing::warn;
+
+use crate::llm::{Completion, LlmClient, LlmError, LlmFeature};
+
+/// Environment variable overriding timeouts, as comma-separated
+/// `kind=duration` entries with kind `read`, `write`, or `llm` and a duration
+/// such as `500ms`, `10s`, or `2m`. `kind=off` removes the limit.
+pub const TIMEOUTS_ENV: &str = "FMEMORY_TIMEOUTS";
+
+/// Default limit on a backend read (get, query, list, describe, delete).
+pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default limit on a backend write (put, schema and index changes).
+pub const DEFAULT_WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default limit on one LLM completion.
+pub const DEFAULT_LLM_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Per-operation time limits; `None` waits indefinitely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    pub read: Option<Duration>,
+    pub write: Option<Duration>,
+    pub llm: Option<Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            read: Some(DEFAULT_READ_TIMEOUT),
+            write: Some(DEFAULT_WRITE_TIMEOUT),
+            llm: Some(DEFAULT_LLM_TIMEOUT),
+        }
+    }
+}
+
+impl Timeouts {
+    /// No limits at all.
+    pub fn none() -> Self {
+        Self {
+            read: None,
+            write: None,
+            llm: None,
+        }
+    }
+
+    /// Defaults plus overrides from `FMEMORY_TIMEOUTS`.
+    pub fn from_env() -> Self {
+        match std::env::var(TIMEOUTS_ENV) {
+            Ok(spec) => Self::parse(&spec),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Defaults plus the overrides in `spec`. Malformed entries are skipped
+    /// with a warning.
+    pub fn parse(spec: &str) -> Self {
+        let mut timeouts = Self::default();
+        for entry in spec.split(',').filter(|e| !e.trim().is_empty()) {
+            let Some((kind, value)) = entry.split_once('=') else {
+                warn!("Ignoring {TIMEOUTS_ENV} entry: expected kind=duration, got '{entry}'");
+                continue;
+            };
+            let limit = match value.trim() {
+                "off" => None,
+                value => match parse_duration(value) {
+                    Ok(limit) => Some(limit),
+                    Err(e) => {
+                        warn!("Ignoring {TIMEOUTS_ENV} entry '{entry}': {e}");
+                        continue;
+                    }
+                },
+            };
+            match kind.trim() {
+                "read" => timeouts.read = limit,
+                "write" => timeouts.write = limit,
+                "llm" => timeouts.llm = limit,
+                other => warn!(
+                    "Ignoring {TIMEOUTS_ENV} entry: unknown kind '{other}' (expected read, write or llm)"
+                ),
+            }
+        }
+        timeouts
+    }
+}
+
+/// Parse a duration such as `"500ms"`, `"10s"`, or `"2m"`.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("Missing unit in '{s}'. Use ms, s, or m"))?;
+    let (num_str, unit) = s.split_at(split);
+    let num: u64 = num_str
+        .parse()
+        .map_err(|_| format!("Invalid duration number: '{num_str}'"))?;
+    if num == 0 {
+        return Err("Duration must be positive, got 0".into());
+    }
+    match unit {
+        "ms" => Ok(Duration::from_millis(num)),
+        "s" => Ok(Duration::from_secs(num)),
+        "m" => Ok(Duration::from_secs(num * 60)),
+        _ => Err(format!(
+            "Unknown duration unit '{unit}'. Use ms (milliseconds), s (seconds), or m (minutes)"
+        )),
+    }
+}
+
+/// LLM client wrapper failing completions that run longer than a limit.
+pub struct TimeoutLlmClient {
+    inner: Arc<dyn LlmClient>,
+    limit: Duration,
+}
+
+impl TimeoutLlmClient {
+    pub fn new(inner: Arc<dyn LlmClient>, limit: Duration) -> Self {
+        Self { inner, limit }
+    }
+
+    /// Wrap `inner` with the LLM limit of `timeouts`, or return it unchanged
+    /// when there is none.
+    pub fn wrap(inner: Arc<dyn LlmClient>, timeouts: &Timeouts) -> Arc<dyn LlmClient> {
+        match timeouts.llm {
+            Some(limit) => Arc::new(Self::new(inner, limit)),
+            None => inner,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmClient for TimeoutLlmClient {
+    async fn complete(&self, system: &str, user: &str) -> Result<Completion, LlmError> {
+        tokio::time::timeout(self.limit, self.inner.complete(system, user))
+            .await
+            .map_err(|_| LlmError::Timeout(self.limit))?
+    }
+
+    async fn complete_for(
+        &self,
+        feature: LlmFeature,
+        system: &str,
+        user: &str,
+    ) -> Result<Completion, LlmError> {
+        tokio::time::timeout(self.limit, self.inner.complete_for(feature, system, user))
+            .await
+            .map_err(|_| LlmError::Timeout(self.limit))?
+    }
+
+    fn model(&self) -> Option<&str> {
+        self.inner.model()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("10s"), Ok(Duration::from_secs(10)));
+        assert_eq!(parse_duration(" 2m "), Ok(Duration::from_secs(120)));
+        assert!(parse_duration("10").is_err());
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("3h").is_err());
+    }
+
+    #[test]
+    fn test_parse_timeouts() {
+        let timeouts = Timeouts::parse("read=5s, llm=off, bogus, write=fast, nap=1s");
+        assert_eq!(
+            timeouts,
+            Timeouts {
+                read: Some(Duration::from_secs(5)),
+                write: Some(DEFAULT_WRITE_TIMEOUT),
+                llm: None,
+            }
+        );
+        assert_eq!(Timeouts::parse(""), Timeouts::default());
+    }
+
+    /// LLM client that never answers.
+    struct HungLlmClient;
+
+    #[async_trait]
+    impl LlmClient for HungLlmClient {
+        async fn complete(&self, _system: &str, _user: &str) -> Result<Completion, LlmError> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_llm_timeout() {
+        let client = TimeoutLlmClient::new(Arc::new(HungLlmClient), Duration::from_secs(3));
+        let err = client.complete("system", "user").await.unwrap_err();
+        assert!(matches!(err, LlmError::Timeout(limit) if limit == Duration::from_secs(3)));
+        assert_eq!(err.to_string(), "LLM call timed out after 3s");
+    }
+}
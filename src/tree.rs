@@ -0,0 +1,156 @@
+//! Hierarchical view of a category's keys.
+//!
+//! Keys can nest with [`KEY_SEPARATOR`] (`async#tokio`, `async#pinning`).
+//! [`build_tree`] folds them into [`TreeNode`]s, one per segment, counting
+//! the items at or below each branch; [`render_tree`] draws that as an
+//! indented outline for `fmemory tree`.
+
+use serde::Serialize;
+
+use crate::backend::MemoryBackend;
+use crate::chunk::is_chunk;
+use crate::error::MemoryError;
+use crate::ttl::is_expired;
+
+/// Separator between the levels of a hierarchical key.
+pub const KEY_SEPARATOR: char = '#';
+
+/// Maximum number of items read when building a category's tree.
+pub const TREE_SCAN_LIMIT: usize = 10_000;
+
+/// One segment of the key hierarchy.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TreeNode {
+    /// The key segment, without its parents.
+    pub name: String,
+    /// Full key of the item stored at exactly this path, if there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Items at or below this node.
+    pub count: usize,
+    /// Child segments, sorted by name.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TreeNode>,
+}
+
+/// Fold `keys` into a forest of [`TreeNode`]s, sorted by name at every level.
+pub fn build_tree<'a>(keys: impl IntoIterator<Item = &'a str>) -> Vec<TreeNode> {
+    let mut roots: Vec<TreeNode> = Vec::new();
+    for key in keys {
+        let mut level = &mut roots;
+        let mut segments = key.split(KEY_SEPARATOR).peekable();
+        while let Some(segment) = segments.next() {
+            let index = match level.binary_search_by(|node| node.name.as_str().cmp(segment)) {
+                Ok(index) => index,
+                Err(index) => {
+                    let node = TreeNode {
+                        name: segment.to_string(),
+                        ..Default::default()
+                    };
+                    level.insert(index, node);
+                    index
+                }
+            };
+            let node = &mut level[index];
+            node.count += 1;
+            if segments.peek().is_none() {
+                node.key = Some(key.to_string());
+            }
+            level = &mut node.children;
+        }
+    }
+    roots
+}
+
+/// Indented outline of `nodes`, two spaces per level. Branches show how many
+/// items they hold; an item that is also a branch is marked with `*`.
+pub fn render_tree(nodes: &[TreeNode]) -> String {
+    fn render(nodes: &[TreeNode], depth: usize, out: &mut String) {
+        for node in nodes {
+            let indent = "  ".repeat(depth);
+            let marker = if node.key.is_some() && !node.children.is_empty() {
+                " *"
+            } else {
+                ""
+            };
+            if node.children.is_empty() {
+                out.push_str(&format!("{indent}{}\n", node.name));
+            } else {
+                out.push_str(&format!("{indent}{}{marker} ({})\n", node.name, node.count));
+            }
+            render(&node.children, depth + 1, out);
+        }
+    }
+    let mut out = String::new();
+    render(nodes, 0, &mut out);
+    out
+}
+
+/// The key tree of `category`. Chunks are left out, as are expired items
+/// unless `include_expired`.
+pub async fn key_tree(
+    backend: &MemoryBackend,
+    category: &str,
+    include_expired: bool,
+) -> Result<Vec<TreeNode>, MemoryError> {
+    let items = backend.query(category, None, TREE_SCAN_LIMIT).await?;
+    let keys = items
+        .iter()
+        .filter(|item| !is_chunk(item) && (include_expired || !is_expired(item)))
+        .filter_map(|item| item["key"].as_str());
+    Ok(build_tree(keys))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    #[test]
+    fn test_build_and_render_tree() {
+        let tree = build_tree([
+            "lifetimes",
+            "async#tokio#select",
+            "async#pinning",
+            "async",
+            "async#tokio#spawn",
+        ]);
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[0].name, "async");
+        assert_eq!(tree[0].count, 4);
+        assert_eq!(tree[0].key.as_deref(), Some("async"));
+        assert_eq!(tree[0].children[1].count, 2);
+        assert_eq!(
+            tree[0].children[1].children[0].key.as_deref(),
+            Some("async#tokio#select")
+        );
+        assert_eq!(
+            render_tree(&tree),
+            "async * (4)\n  pinning\n  tokio (2)\n    select\n    spawn\nlifetimes\n"
+        );
+        assert!(build_tree([]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_key_tree_skips_chunks_and_expired() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for item in [
+            json!({"category": "rust", "key": "async#tokio"}),
+            json!({"category": "rust", "key": "async#tokio#0", "chunk_of": "async#tokio"}),
+            json!({"category": "rust", "key": "async#old", "expires_at": "2020-01-01T00:00:00Z"}),
+        ] {
+            backend.put_item(item).await.unwrap();
+        }
+        let tree = key_tree(&backend, "rust", false).await.unwrap();
+        assert_eq!(tree[0].count, 1);
+        assert!(tree[0].children[0].children.is_empty());
+
+        let tree = key_tree(&backend, "rust", true).await.unwrap();
+        assert_eq!(tree[0].count, 2);
+    }
+}
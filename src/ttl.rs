@@ -4,9 +4,11 @@
 //! Items with an `expires_at` attribute (RFC 3339 timestamp) are filtered out
 //! on read when the timestamp is in the past.
 
-use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
+use chrono::{DateTime, Datelike, Duration, Utc};
 use serde_json::Value;
 
+use crate::locale::DateContext;
+
 /// Default TTL for scratchpad items: 24 hours.
 pub const SCRATCHPAD_DEFAULT_TTL: Duration = Duration::hours(24);
 
@@ -16,9 +18,15 @@ pub const SESSIONS_DEFAULT_TTL: Duration = Duration::days(7);
 /// Default TTL for interactions items: 90 days.
 pub const INTERACTIONS_DEFAULT_TTL: Duration = Duration::days(90);
 
+/// Attribute of a parsed document holding a retention hint from the input
+/// ("remember this for a week" → `"1w"`). Never stored: [`take_ttl`] turns it
+/// into the item's `expires_at`.
+pub const TTL_ATTRIBUTE: &str = "ttl";
+
 /// Parse a TTL duration string into a [`chrono::Duration`].
 ///
 /// Supported formats:
+/// - `"5m"`, `"30m"` — minutes
 /// - `"1h"`, `"24h"` — hours
 /// - `"1d"`, `"7d"`, `"30d"` — days
 /// - `"1w"`, `"2w"` — weeks (7 days each)
@@ -40,11 +48,12 @@ pub fn parse_ttl(s: &str) -> Result<Duration, String> {
     }
 
     match unit {
+        "m" => Ok(Duration::minutes(num)),
         "h" => Ok(Duration::hours(num)),
         "d" => Ok(Duration::days(num)),
         "w" => Ok(Duration::weeks(num)),
         _ => Err(format!(
-            "Unknown TTL unit '{unit}'. Use h (hours), d (days), or w (weeks)"
+            "Unknown TTL unit '{unit}'. Use m (minutes), h (hours), d (days), or w (weeks)"
         )),
     }
 }
@@ -76,19 +85,39 @@ pub fn filter_expired(items: Vec<Value>) -> Vec<Value> {
     items.into_iter().filter(|item| !is_expired(item)).collect()
 }
 
+/// Keep a parsed document's [`TTL_ATTRIBUTE`] only if it is a valid TTL,
+/// with spaces removed (`"1 w"` → `"1w"`). Anything else is dropped.
+pub fn normalize_ttl(doc: &mut Value) {
+    let Some(obj) = doc.as_object_mut() else {
+        return;
+    };
+    let ttl = match obj.remove(TTL_ATTRIBUTE) {
+        Some(Value::String(ttl)) => ttl.split_whitespace().collect::<String>(),
+        _ => return,
+    };
+    if parse_ttl(&ttl).is_ok() {
+        obj.insert(TTL_ATTRIBUTE.to_string(), Value::String(ttl));
+    }
+}
+
+/// Remove [`TTL_ATTRIBUTE`] from a parsed document, returning the duration
+/// it holds. `None` if it is missing or invalid.
+pub fn take_ttl(doc: &mut Value) -> Option<Duration> {
+    let ttl = doc.as_object_mut()?.remove(TTL_ATTRIBUTE)?;
+    parse_ttl(ttl.as_str()?).ok()
+}
+
 /// Auto-compute an `expires_at` for the `events` category based on the `date`
 /// attribute.
 ///
-/// If the item has a `date` attribute (ISO 8601 date string like "2026-02-10"),
-/// returns an `expires_at` set to the end of that day (23:59:59 UTC).
-/// Returns `None` if no date attribute is present or parsing fails.
-pub fn auto_ttl_from_date(item: &Value) -> Option<String> {
+/// If the item has a `date` attribute (ISO 8601 like "2026-02-10", or a
+/// numeric date in the order of `dates`' locale), returns an `expires_at` set
+/// to the end of that day (23:59:59 in `dates`' timezone, or UTC). Returns
+/// `None` if no date attribute is present or parsing fails.
+pub fn auto_ttl_from_date(item: &Value, dates: &DateContext) -> Option<String> {
     let date_str = item.get("date").and_then(|v| v.as_str())?;
-    let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d").ok()?;
-    let end_of_day = date
-        .and_time(NaiveTime::from_hms_opt(23, 59, 59)?)
-        .and_utc();
-    Some(end_of_day.to_rfc3339())
+    let date = dates.parse_date(date_str, dates.today().year())?;
+    Some(dates.end_of_day(date)?.to_rfc3339())
 }
 
 // ============================================================================
@@ -98,6 +127,7 @@ pub fn auto_ttl_from_date(item: &Value) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::NaiveDate;
     use serde_json::json;
 
     // --- parse_ttl ---
@@ -126,6 +156,12 @@ mod tests {
         assert_eq!(d, Duration::hours(1));
     }
 
+    #[test]
+    fn test_parse_ttl_minutes() {
+        let d = parse_ttl("30m").unwrap();
+        assert_eq!(d, Duration::minutes(30));
+    }
+
     #[test]
     fn test_parse_ttl_invalid_unit() {
         assert!(parse_ttl("5x").is_err());
@@ -210,7 +246,7 @@ mod tests {
     #[test]
     fn test_auto_ttl_from_date_valid() {
         let item = json!({"category": "events", "key": "meeting", "date": "2030-06-15"});
-        let expires = auto_ttl_from_date(&item).unwrap();
+        let expires = auto_ttl_from_date(&item, &DateContext::default()).unwrap();
         let parsed = DateTime::parse_from_rfc3339(&expires).unwrap();
         assert_eq!(
             parsed.date_naive(),
@@ -218,15 +254,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_auto_ttl_from_date_uses_locale_order() {
+        let item = json!({"category": "events", "key": "dentist", "date": "03/04/2030"});
+        let expires = auto_ttl_from_date(&item, &DateContext::for_locale("en-GB")).unwrap();
+        let parsed = DateTime::parse_from_rfc3339(&expires).unwrap();
+        assert_eq!(
+            parsed.date_naive(),
+            NaiveDate::from_ymd_opt(2030, 4, 3).unwrap()
+        );
+    }
+
     #[test]
     fn test_auto_ttl_from_date_no_date() {
         let item = json!({"category": "events", "key": "meeting", "content": "standup"});
-        assert!(auto_ttl_from_date(&item).is_none());
+        assert!(auto_ttl_from_date(&item, &DateContext::default()).is_none());
     }
 
     #[test]
     fn test_auto_ttl_from_date_invalid() {
         let item = json!({"category": "events", "key": "meeting", "date": "not-a-date"});
-        assert!(auto_ttl_from_date(&item).is_none());
+        assert!(auto_ttl_from_date(&item, &DateContext::default()).is_none());
+    }
+
+    // --- normalize_ttl / take_ttl ---
+
+    #[test]
+    fn test_normalize_ttl() {
+        let mut doc = json!({"ttl": " 1 w"});
+        normalize_ttl(&mut doc);
+        assert_eq!(doc["ttl"], "1w");
+
+        for bad in [json!("a week"), json!(7), json!(null)] {
+            let mut doc = json!({"ttl": bad});
+            normalize_ttl(&mut doc);
+            assert!(doc.get("ttl").is_none());
+        }
+    }
+
+    #[test]
+    fn test_take_ttl() {
+        let mut doc = json!({"ttl": "3d", "note": "x"});
+        assert_eq!(take_ttl(&mut doc), Some(Duration::days(3)));
+        assert_eq!(doc, json!({"note": "x"}));
+        assert_eq!(take_ttl(&mut doc), None);
+
+        let mut doc = json!({"ttl": "soon"});
+        assert_eq!(take_ttl(&mut doc), None);
+        assert!(doc.get("ttl").is_none());
     }
 }
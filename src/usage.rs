@@ -0,0 +1,438 @@
+//! LLM token usage tracking and cost reporting.
+//!
+//! [`TrackedLlmClient`] wraps any [`LlmClient`] and tallies token usage per
+//! [`LlmFeature`] into a shared [`UsageTracker`]. When a CLI command ends,
+//! successfully or not, and every [`USAGE_FLUSH_INTERVAL`] while the MCP
+//! server runs, the tally is persisted as items in the [`USAGE_CATEGORY`]
+//! partition (one item per feature, 90-day TTL like `interactions`, naming
+//! the model used), which `fmemory cost` aggregates into a spend report.
+//! Spend is estimated from [`MODEL_PRICES`]; tokens of models it does not
+//! list are reported without a cost.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::backend::MemoryBackend;
+use crate::error::MemoryError;
+use crate::llm::{Completion, LlmClient, LlmError, LlmFeature, Usage};
+use crate::logging::request_id;
+use crate::ttl::{INTERACTIONS_DEFAULT_TTL, compute_expires_at, filter_expired};
+
+/// Partition holding persisted usage records.
+pub const USAGE_CATEGORY: &str = "_llm_usage";
+
+/// Retention for usage records.
+pub const USAGE_RETENTION: Duration = INTERACTIONS_DEFAULT_TTL;
+
+/// How often a long-running process persists the usage recorded so far.
+pub const USAGE_FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Number of usage records read per request when loading a report.
+pub const USAGE_PAGE_SIZE: usize = 1_000;
+
+/// Input and output prices in USD per million tokens, by model name prefix
+/// (so dated snapshots such as `claude-haiku-4-5-20251001` match). The first
+/// matching prefix wins.
+pub const MODEL_PRICES: &[(&str, f64, f64)] = &[
+    ("claude-haiku-4-5", 1.0, 5.0),
+    ("claude-sonnet-4-5", 3.0, 15.0),
+    ("claude-opus-4-1", 15.0, 75.0),
+    ("gpt-4o-mini", 0.15, 0.6),
+    ("gpt-4o", 2.5, 10.0),
+];
+
+/// Input and output prices of `model` from [`MODEL_PRICES`], if listed.
+pub fn model_price(model: &str) -> Option<(f64, f64)> {
+    MODEL_PRICES
+        .iter()
+        .find(|(prefix, _, _)| model.starts_with(prefix))
+        .map(|(_, input, output)| (*input, *output))
+}
+
+/// Aggregated usage for one feature.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct FeatureUsage {
+    pub calls: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl FeatureUsage {
+    fn add(&mut self, other: FeatureUsage) {
+        self.calls += other.calls;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+    }
+
+    /// Spend in USD at `model`'s price, or `None` if it has none listed in
+    /// [`MODEL_PRICES`].
+    pub fn cost_usd(&self, model: &str) -> Option<f64> {
+        let (input, output) = model_price(model)?;
+        Some((self.input_tokens as f64 * input + self.output_tokens as f64 * output) / 1_000_000.0)
+    }
+}
+
+/// Usage of one feature over a report window, with its estimated spend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct FeatureCost {
+    #[serde(flatten)]
+    pub usage: FeatureUsage,
+    /// Spend of the calls made with a priced model.
+    pub cost_usd: f64,
+    /// Calls made with a model missing from [`MODEL_PRICES`], or recorded
+    /// without one; their tokens are not in `cost_usd`.
+    pub unpriced_calls: u64,
+}
+
+/// In-process tally of token usage per feature.
+#[derive(Debug, Default)]
+pub struct UsageTracker {
+    totals: Mutex<BTreeMap<LlmFeature, FeatureUsage>>,
+    /// Model of the client recording into this tracker, if known.
+    model: Mutex<Option<String>>,
+}
+
+impl UsageTracker {
+    /// Record usage as made with `model`, stored with each usage item so
+    /// reports can price it.
+    pub fn set_model(&self, model: Option<&str>) {
+        *self.model.lock().unwrap() = model.map(String::from);
+    }
+
+    /// Record one completion's usage against a feature.
+    pub fn record(&self, feature: LlmFeature, usage: Usage) {
+        self.totals
+            .lock()
+            .unwrap()
+            .entry(feature)
+            .or_default()
+            .add(FeatureUsage {
+                calls: 1,
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+            });
+    }
+
+    /// Snapshot of usage recorded so far.
+    pub fn totals(&self) -> BTreeMap<LlmFeature, FeatureUsage> {
+        self.totals.lock().unwrap().clone()
+    }
+
+    /// Build usage items for persistence, one per feature used by `command`.
+    pub fn usage_items(&self, command: &str) -> Vec<Value> {
+        usage_items(self.totals(), command, self.model().as_deref())
+    }
+
+    fn model(&self) -> Option<String> {
+        self.model.lock().unwrap().clone()
+    }
+
+    /// Persist the usage recorded since the last call for `command`, and
+    /// start a new tally. No-op if no LLM calls were made; usage that could
+    /// not be written is kept for the next call.
+    pub async fn persist(&self, backend: &MemoryBackend, command: &str) -> Result<(), MemoryError> {
+        let totals = std::mem::take(&mut *self.totals.lock().unwrap());
+        let items = usage_items(totals.clone(), command, self.model().as_deref());
+        for (item, feature) in items.into_iter().zip(totals.keys()) {
+            if let Err(e) = backend.put_system_item(item).await {
+                let mut current = self.totals.lock().unwrap();
+                for (feature, usage) in totals.range(feature..) {
+                    current.entry(*feature).or_default().add(*usage);
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Timestamp format leading usage record keys, so keys sort by time.
+const USAGE_KEY_STAMP: &str = "%Y%m%dT%H%M%S%.6fZ";
+
+/// Usage items for `totals`, one per feature used by `command` with `model`.
+fn usage_items(
+    totals: BTreeMap<LlmFeature, FeatureUsage>,
+    command: &str,
+    model: Option<&str>,
+) -> Vec<Value> {
+    let now = Utc::now();
+    let stamp = now.format(USAGE_KEY_STAMP);
+    let request_id = request_id();
+
+    totals
+        .into_iter()
+        .map(|(feature, usage)| {
+            serde_json::json!({
+                "category": USAGE_CATEGORY,
+                "key": format!("{stamp}-{request_id}-{}", feature.as_str()),
+                "command": command,
+                "request_id": request_id,
+                "feature": feature.as_str(),
+                "model": model,
+                "calls": usage.calls,
+                "input_tokens": usage.input_tokens,
+                "output_tokens": usage.output_tokens,
+                "created_at": now.to_rfc3339(),
+                "expires_at": compute_expires_at(USAGE_RETENTION),
+            })
+        })
+        .collect()
+}
+
+/// LLM client wrapper that records token usage per feature.
+pub struct TrackedLlmClient {
+    inner: Arc<dyn LlmClient>,
+    tracker: Arc<UsageTracker>,
+}
+
+impl TrackedLlmClient {
+    /// Record into `tracker`, which is told the model of `inner`.
+    pub fn new(inner: Arc<dyn LlmClient>, tracker: Arc<UsageTracker>) -> Self {
+        tracker.set_model(inner.model());
+        Self { inner, tracker }
+    }
+}
+
+#[async_trait]
+impl LlmClient for TrackedLlmClient {
+    async fn complete(&self, system: &str, user: &str) -> Result<Completion, LlmError> {
+        self.complete_for(LlmFeature::Other, system, user).await
+    }
+
+    async fn complete_for(
+        &self,
+        feature: LlmFeature,
+        system: &str,
+        user: &str,
+    ) -> Result<Completion, LlmError> {
+        let completion = self.inner.complete_for(feature, system, user).await?;
+        self.tracker.record(feature, completion.usage);
+        Ok(completion)
+    }
+
+    fn model(&self) -> Option<&str> {
+        self.inner.model()
+    }
+}
+
+/// Load persisted usage records, optionally limited to those newer than `since`.
+///
+/// Record keys start with their timestamp, so with `since` the scan starts
+/// at the cutoff; either way every record after it is read,
+/// [`USAGE_PAGE_SIZE`] at a time.
+pub async fn load_usage(
+    backend: &MemoryBackend,
+    since: Option<Duration>,
+) -> Result<Vec<Value>, MemoryError> {
+    let cutoff = since.map(|d| Utc::now() - d);
+    let mut after = cutoff.map(|t| t.format(USAGE_KEY_STAMP).to_string());
+    let mut items = Vec::new();
+    loop {
+        let page = backend
+            .query_after(USAGE_CATEGORY, after.as_deref(), USAGE_PAGE_SIZE)
+            .await?;
+        let full = page.len() == USAGE_PAGE_SIZE;
+        after = page
+            .last()
+            .and_then(|item| item["key"].as_str())
+            .map(String::from);
+        items.extend(page);
+        if !full || after.is_none() {
+            break;
+        }
+    }
+
+    Ok(filter_expired(items)
+        .into_iter()
+        .filter(|item| match cutoff {
+            Some(cutoff) => item["created_at"]
+                .as_str()
+                .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                .is_some_and(|t| t >= cutoff),
+            None => true,
+        })
+        .collect())
+}
+
+/// Aggregate usage records by feature name, pricing each by its model.
+pub fn summarize_usage(items: &[Value]) -> BTreeMap<String, FeatureCost> {
+    let mut summary: BTreeMap<String, FeatureCost> = BTreeMap::new();
+    for item in items {
+        let feature = item["feature"].as_str().unwrap_or("other").to_string();
+        let usage = FeatureUsage {
+            calls: item["calls"].as_u64().unwrap_or(0),
+            input_tokens: item["input_tokens"].as_u64().unwrap_or(0),
+            output_tokens: item["output_tokens"].as_u64().unwrap_or(0),
+        };
+        let entry = summary.entry(feature).or_default();
+        entry.usage.add(usage);
+        match item["model"]
+            .as_str()
+            .and_then(|model| usage.cost_usd(model))
+        {
+            Some(cost) => entry.cost_usd += cost,
+            None => entry.unpriced_calls += usage.calls,
+        }
+    }
+    summary
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::MockLlmClient;
+
+    #[tokio::test]
+    async fn test_tracked_client_records_per_feature() {
+        let tracker = Arc::new(UsageTracker::default());
+        let client = TrackedLlmClient::new(
+            Arc::new(MockLlmClient::new(vec!["a".into(), "b".into(), "c".into()])),
+            tracker.clone(),
+        );
+
+        client
+            .complete_for(LlmFeature::Resolve, "sys", "q")
+            .await
+            .unwrap();
+        client
+            .complete_for(LlmFeature::Resolve, "sys", "q")
+            .await
+            .unwrap();
+        client.complete("sys", "q").await.unwrap();
+
+        let totals = tracker.totals();
+        assert_eq!(totals[&LlmFeature::Resolve].calls, 2);
+        assert_eq!(totals[&LlmFeature::Other].calls, 1);
+    }
+
+    #[test]
+    fn test_usage_items_one_per_feature() {
+        let tracker = UsageTracker::default();
+        tracker.record(
+            LlmFeature::Parse,
+            Usage {
+                input_tokens: 100,
+                output_tokens: 20,
+            },
+        );
+        tracker.record(
+            LlmFeature::Answer,
+            Usage {
+                input_tokens: 50,
+                output_tokens: 10,
+            },
+        );
+
+        let items = tracker.usage_items("recall");
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|i| i["category"] == USAGE_CATEGORY));
+        assert!(items.iter().all(|i| i["command"] == "recall"));
+        assert!(items.iter().all(|i| i.get("expires_at").is_some()));
+        assert_ne!(items[0]["key"], items[1]["key"]);
+        assert!(items.iter().all(|i| i["model"].is_null()));
+
+        tracker.set_model(Some("gpt-4o-mini"));
+        tracker.record(LlmFeature::Parse, Usage::default());
+        assert_eq!(tracker.usage_items("recall")[0]["model"], "gpt-4o-mini");
+    }
+
+    #[test]
+    fn test_summarize_and_cost() {
+        let items = vec![
+            serde_json::json!({"feature": "parse", "model": "claude-haiku-4-5", "calls": 1, "input_tokens": 1_000_000, "output_tokens": 0}),
+            serde_json::json!({"feature": "parse", "model": "gpt-4o-mini-2024-07-18", "calls": 2, "input_tokens": 0, "output_tokens": 1_000_000}),
+            serde_json::json!({"feature": "parse", "model": "llama3.1", "calls": 4, "input_tokens": 500, "output_tokens": 50}),
+            serde_json::json!({"feature": "answer", "calls": 1, "input_tokens": 10, "output_tokens": 5}),
+        ];
+        let summary = summarize_usage(&items);
+        assert_eq!(summary.len(), 2);
+        let parse = summary["parse"];
+        assert_eq!(parse.usage.calls, 7);
+        assert_eq!(parse.usage.input_tokens, 1_000_500);
+        assert!((parse.cost_usd - 1.6).abs() < 1e-9);
+        assert_eq!(parse.unpriced_calls, 4);
+        // Records without a model are not priced.
+        assert_eq!(summary["answer"].cost_usd, 0.0);
+        assert_eq!(summary["answer"].unpriced_calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_load_usage_filters_by_since() {
+        use crate::TABLE_NAME;
+        use ferridyn_core::api::FerridynDB;
+        use ferridyn_core::types::KeyType;
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = FerridynDB::create(dir.path().join("test.db")).unwrap();
+        db.create_table(TABLE_NAME)
+            .partition_key("category", KeyType::String)
+            .sort_key("key", KeyType::String)
+            .execute()
+            .unwrap();
+        let backend = MemoryBackend::direct(db, TABLE_NAME.to_string());
+
+        let old = (Utc::now() - Duration::days(40)).to_rfc3339();
+        backend
+            .put_system_item(serde_json::json!({
+                "category": USAGE_CATEGORY,
+                "key": "old",
+                "feature": "parse",
+                "calls": 1,
+                "created_at": old,
+            }))
+            .await
+            .unwrap();
+
+        let tracker = UsageTracker::default();
+        tracker.record(LlmFeature::Answer, Usage::default());
+        tracker.persist(&backend, "recall").await.unwrap();
+        // The tally restarts, so flushing again records nothing twice.
+        assert!(tracker.totals().is_empty());
+        tracker.persist(&backend, "recall").await.unwrap();
+
+        assert_eq!(load_usage(&backend, None).await.unwrap().len(), 2);
+        let recent = load_usage(&backend, Some(Duration::days(30)))
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0]["feature"], "answer");
+    }
+
+    #[tokio::test]
+    async fn test_load_usage_reads_past_one_page() {
+        let backend = MemoryBackend::in_memory(crate::TABLE_NAME.to_string());
+        let now = Utc::now();
+        for n in 0..USAGE_PAGE_SIZE * 2 + 1 {
+            let at = now - Duration::days(20) + Duration::minutes(n as i64);
+            backend
+                .put_system_item(serde_json::json!({
+                    "category": USAGE_CATEGORY,
+                    "key": format!("{}-req-parse", at.format(USAGE_KEY_STAMP)),
+                    "feature": "parse",
+                    "calls": 1,
+                    "created_at": at.to_rfc3339(),
+                }))
+                .await
+                .unwrap();
+        }
+        let tracker = UsageTracker::default();
+        tracker.record(LlmFeature::Answer, Usage::default());
+        tracker.persist(&backend, "recall").await.unwrap();
+
+        let all = load_usage(&backend, None).await.unwrap();
+        assert_eq!(all.len(), USAGE_PAGE_SIZE * 2 + 2);
+        // The newest record, past the first pages, is part of a recent window.
+        let recent = load_usage(&backend, Some(Duration::days(7))).await.unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0]["feature"], "answer");
+    }
+}
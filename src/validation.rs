@@ -0,0 +1,283 @@
+//! Client-side validation of items against their partition schema.
+//!
+//! A schema defined with `validate: true` is enforced by ferridyn-server, but
+//! only there: the in-process backends and the lenient predefined schemas
+//! accept anything. [`WriteValidation`] makes
+//! [`MemoryBackend::put_item`](crate::backend::MemoryBackend::put_item) check
+//! items itself, against the schema listing it caches per table: declared
+//! types (`STRING`, `NUMBER`, `BOOLEAN`), required attributes, and, per
+//! [`UnknownAttributes`], attributes the schema does not declare. Failures are
+//! [`MemoryError::Schema`](crate::error::MemoryError::Schema), as from the
+//! server. Configured by `FMEMORY_VALIDATE` and `FMEMORY_UNKNOWN_ATTRIBUTES`;
+//! `fmemory --no-validate` bypasses it.
+
+use ferridyn_server::client::PartitionSchemaInfo;
+use serde_json::Value;
+use tracing::warn;
+
+use crate::append::CONTENT_ATTRIBUTE;
+use crate::conflict::CONFLICTS_ATTRIBUTE;
+use crate::error::MemoryError;
+use crate::issue::{RESOLVED_AT_ATTRIBUTE, RESOLVED_ATTRIBUTE};
+use crate::preferences::SCOPE_ATTRIBUTE;
+use crate::related::RELATED_ATTRIBUTE;
+use crate::review::REVIEW_COUNT_ATTRIBUTE;
+use crate::score::{IMPORTANCE_ATTRIBUTE, LAST_ACCESSED_ATTRIBUTE};
+use crate::session::FACTS_ATTRIBUTE;
+use crate::similar::TAGS_ATTRIBUTE;
+use crate::sort::UPDATED_AT_ATTRIBUTE;
+use crate::store::STRATEGY_ATTRIBUTE;
+use crate::ttl::TTL_ATTRIBUTE;
+
+/// Environment variable selecting which schemas are checked on write:
+/// `off`, `validating` (default), or `all`.
+pub const VALIDATE_ENV: &str = "FMEMORY_VALIDATE";
+
+/// Environment variable selecting how undeclared attributes are treated:
+/// `allow` (default), `warn`, or `reject`.
+pub const UNKNOWN_ATTRIBUTES_ENV: &str = "FMEMORY_UNKNOWN_ATTRIBUTES";
+
+/// Attributes maintained or written by this crate, accepted whatever the
+/// schema declares.
+pub const BOOKKEEPING_ATTRIBUTES: &[&str] = &[
+    "category",
+    "key",
+    CONTENT_ATTRIBUTE,
+    "created_at",
+    UPDATED_AT_ATTRIBUTE,
+    "expires_at",
+    TTL_ATTRIBUTE,
+    IMPORTANCE_ATTRIBUTE,
+    LAST_ACCESSED_ATTRIBUTE,
+    REVIEW_COUNT_ATTRIBUTE,
+    RELATED_ATTRIBUTE,
+    CONFLICTS_ATTRIBUTE,
+    TAGS_ATTRIBUTE,
+    RESOLVED_ATTRIBUTE,
+    RESOLVED_AT_ATTRIBUTE,
+    SCOPE_ATTRIBUTE,
+    FACTS_ATTRIBUTE,
+    "chunk_count",
+    STRATEGY_ATTRIBUTE,
+];
+
+/// Which schemas items are checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationScope {
+    /// No client-side checks; the server still enforces validating schemas.
+    Off,
+    /// Schemas defined with `validate: true`.
+    #[default]
+    Validating,
+    /// Every schema, lenient ones (the predefined categories) included.
+    All,
+}
+
+/// What happens to an attribute the schema does not declare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnknownAttributes {
+    #[default]
+    Allow,
+    /// Accept it, logged at `warn`.
+    Warn,
+    Reject,
+}
+
+/// Client-side checks made by `put_item`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WriteValidation {
+    pub scope: ValidationScope,
+    pub unknown: UnknownAttributes,
+}
+
+impl WriteValidation {
+    /// No client-side checks at all.
+    pub fn off() -> Self {
+        Self {
+            scope: ValidationScope::Off,
+            unknown: UnknownAttributes::Allow,
+        }
+    }
+
+    /// Defaults plus overrides from `FMEMORY_VALIDATE` and
+    /// `FMEMORY_UNKNOWN_ATTRIBUTES`. Unrecognized values are ignored with a
+    /// warning.
+    pub fn from_env() -> Self {
+        let mut validation = Self::default();
+        if let Ok(value) = std::env::var(VALIDATE_ENV) {
+            match value.trim() {
+                "off" => validation.scope = ValidationScope::Off,
+                "validating" => validation.scope = ValidationScope::Validating,
+                "all" => validation.scope = ValidationScope::All,
+                other => warn!("Ignoring {VALIDATE_ENV}={other}: expected off, validating, or all"),
+            }
+        }
+        if let Ok(value) = std::env::var(UNKNOWN_ATTRIBUTES_ENV) {
+            match value.trim() {
+                "allow" => validation.unknown = UnknownAttributes::Allow,
+                "warn" => validation.unknown = UnknownAttributes::Warn,
+                "reject" => validation.unknown = UnknownAttributes::Reject,
+                other => warn!(
+                    "Ignoring {UNKNOWN_ATTRIBUTES_ENV}={other}: expected allow, warn, or reject"
+                ),
+            }
+        }
+        validation
+    }
+
+    /// Whether items of `schema` are checked.
+    pub fn applies_to(&self, schema: &PartitionSchemaInfo) -> bool {
+        match self.scope {
+            ValidationScope::Off => false,
+            ValidationScope::Validating => schema.validate,
+            ValidationScope::All => true,
+        }
+    }
+
+    /// Check `doc` against `schema`, whatever the scope.
+    pub fn check(&self, schema: &PartitionSchemaInfo, doc: &Value) -> Result<(), MemoryError> {
+        check_attributes(schema, doc)?;
+        if self.unknown == UnknownAttributes::Allow {
+            return Ok(());
+        }
+        let Some(fields) = doc.as_object() else {
+            return Ok(());
+        };
+        let unknown: Vec<&str> = fields
+            .keys()
+            .map(String::as_str)
+            .filter(|name| {
+                !BOOKKEEPING_ATTRIBUTES.contains(name)
+                    && !schema.attributes.iter().any(|a| a.name == *name)
+            })
+            .collect();
+        if unknown.is_empty() {
+            return Ok(());
+        }
+        let message = format!(
+            "attribute(s) {} not declared by schema '{}'",
+            unknown.join(", "),
+            schema.prefix
+        );
+        match self.unknown {
+            UnknownAttributes::Reject => Err(MemoryError::Schema(message)),
+            _ => {
+                warn!("{message}");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Check required attributes and declared types, as a validating schema does.
+pub(crate) fn check_attributes(
+    schema: &PartitionSchemaInfo,
+    doc: &Value,
+) -> Result<(), MemoryError> {
+    for attr in &schema.attributes {
+        let value = &doc[&attr.name];
+        if value.is_null() {
+            if attr.required {
+                return Err(MemoryError::Schema(format!(
+                    "missing required attribute '{}' for schema '{}'",
+                    attr.name, schema.prefix
+                )));
+            }
+            continue;
+        }
+        let matches = match attr.attr_type.as_str() {
+            "STRING" => value.is_string(),
+            "NUMBER" => value.is_number(),
+            "BOOLEAN" => value.is_boolean(),
+            _ => true,
+        };
+        if !matches {
+            return Err(MemoryError::Schema(format!(
+                "attribute '{}' must be {} for schema '{}'",
+                attr.name, attr.attr_type, schema.prefix
+            )));
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryBackend;
+    use crate::{AttributeDefInput, TABLE_NAME};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_put_item_validates_against_schema() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        let attrs = [
+            AttributeDefInput {
+                name: "name".into(),
+                attr_type: "STRING".into(),
+                required: true,
+            },
+            AttributeDefInput {
+                name: "age".into(),
+                attr_type: "NUMBER".into(),
+                required: false,
+            },
+        ];
+        backend
+            .create_schema("people", None, &attrs, false)
+            .await
+            .unwrap();
+        let bad = json!({"category": "people", "key": "toby", "age": "forty"});
+
+        // A lenient schema is only checked with scope `all`.
+        backend.put_item(bad.clone()).await.unwrap();
+        let strict = backend.clone().with_write_validation(WriteValidation {
+            scope: ValidationScope::All,
+            unknown: UnknownAttributes::Reject,
+        });
+        let err = strict.put_item(bad.clone()).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("missing required attribute 'name'")
+        );
+        let err = strict
+            .put_item(json!({"category": "people", "key": "toby", "name": "Toby", "age": "forty"}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("'age' must be NUMBER"));
+        let err = strict
+            .put_item(json!({"category": "people", "key": "toby", "name": "Toby", "pet": "cat"}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("pet"));
+        strict
+            .put_item(
+                json!({"category": "people", "key": "toby", "name": "Toby", "age": 40,
+                "created_at": "2025-01-06T09:00:00+00:00", "tags": ["team"]}),
+            )
+            .await
+            .unwrap();
+        // Chunks carry none of the required attributes.
+        strict
+            .put_item(
+                json!({"category": "people", "key": "toby#2", "content": "more",
+                "chunk_of": "toby", "chunk_index": 2}),
+            )
+            .await
+            .unwrap();
+        strict
+            .put_item(json!({"category": "people", "key": "ann", "name": "Ann",
+                "content": "x", "ttl": "1w", "resolved": true, "scope": "work"}))
+            .await
+            .unwrap();
+        strict
+            .with_write_validation(WriteValidation::off())
+            .put_item(bad)
+            .await
+            .unwrap();
+    }
+}
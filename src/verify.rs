@@ -0,0 +1,165 @@
+//! Consistency checks of secondary indexes against their base partitions.
+//!
+//! Dropping schemas by hand or re-running `init --force` can leave an index
+//! out of step with the items it covers. [`verify_index`] scans the index's
+//! category, groups item keys by the indexed attribute, and queries the index
+//! for each value seen: keys the index does not return are *missing*, keys it
+//! returns that no longer carry that value (or no longer exist) are
+//! *orphaned*. Orphans under values no current item carries cannot be found
+//! this way, since an index can only be queried by value.
+//!
+//! [`rebuild_index`] drops and recreates an index, which repopulates it from
+//! the items in its category.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::IndexInfo;
+use crate::backend::MemoryBackend;
+use crate::error::MemoryError;
+
+/// Maximum number of items scanned per category, and returned per index
+/// lookup.
+pub const VERIFY_SCAN_LIMIT: usize = 100_000;
+
+/// Result of checking one index.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IndexReport {
+    pub index: String,
+    pub category: String,
+    pub attribute: String,
+    /// Items in the category carrying the indexed attribute.
+    pub checked: usize,
+    /// Keys of items the index does not return for their value.
+    pub missing: Vec<String>,
+    /// Keys the index returns for a value the item does not carry.
+    pub orphaned: Vec<String>,
+    /// Whether the index was rebuilt after the check.
+    pub rebuilt: bool,
+}
+
+impl IndexReport {
+    pub fn is_consistent(&self) -> bool {
+        self.missing.is_empty() && self.orphaned.is_empty()
+    }
+}
+
+/// Check `index` against the items of its category.
+pub async fn verify_index(
+    backend: &MemoryBackend,
+    index: &IndexInfo,
+) -> Result<IndexReport, MemoryError> {
+    let items = backend
+        .query(&index.partition_schema, None, VERIFY_SCAN_LIMIT)
+        .await?;
+    let mut expected: Vec<(Value, BTreeSet<String>)> = Vec::new();
+    let mut checked = 0;
+    for item in &items {
+        let (Some(key), value) = (item["key"].as_str(), &item[&index.index_key_name]) else {
+            continue;
+        };
+        if value.is_null() {
+            continue;
+        }
+        checked += 1;
+        match expected.iter_mut().find(|(v, _)| v == value) {
+            Some((_, keys)) => {
+                keys.insert(key.to_string());
+            }
+            None => expected.push((value.clone(), BTreeSet::from([key.to_string()]))),
+        }
+    }
+
+    let mut report = IndexReport {
+        index: index.name.clone(),
+        category: index.partition_schema.clone(),
+        attribute: index.index_key_name.clone(),
+        checked,
+        ..Default::default()
+    };
+    for (value, keys) in expected {
+        let indexed: BTreeSet<String> = backend
+            .query_index(&index.name, value, Some(VERIFY_SCAN_LIMIT))
+            .await?
+            .iter()
+            .filter_map(|item| item["key"].as_str().map(String::from))
+            .collect();
+        report.missing.extend(keys.difference(&indexed).cloned());
+        report.orphaned.extend(indexed.difference(&keys).cloned());
+    }
+    report.missing.sort();
+    report.orphaned.sort();
+    Ok(report)
+}
+
+/// Check every index, or only those on `category`.
+pub async fn verify_indexes(
+    backend: &MemoryBackend,
+    category: Option<&str>,
+) -> Result<Vec<IndexReport>, MemoryError> {
+    let indexes: BTreeMap<String, IndexInfo> = backend
+        .list_indexes()
+        .await?
+        .into_iter()
+        .filter(|idx| category.is_none_or(|c| idx.partition_schema == c))
+        .map(|idx| (idx.name.clone(), idx))
+        .collect();
+    let mut reports = Vec::with_capacity(indexes.len());
+    for index in indexes.values() {
+        reports.push(verify_index(backend, index).await?);
+    }
+    Ok(reports)
+}
+
+/// Drop `index` and create it again with the same definition.
+pub async fn rebuild_index(backend: &MemoryBackend, index: &IndexInfo) -> Result<(), MemoryError> {
+    backend.drop_index(&index.name).await?;
+    backend
+        .create_index(
+            &index.name,
+            &index.partition_schema,
+            &index.index_key_name,
+            &index.index_key_type,
+        )
+        .await
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_verify_and_rebuild_indexes() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        backend.ensure_predefined_schemas().await.unwrap();
+        for (key, role) in [("toby", "cto"), ("ann", "cto"), ("raj", "designer")] {
+            backend
+                .put_item(json!({"category": "contacts", "key": key, "name": key, "role": role}))
+                .await
+                .unwrap();
+        }
+
+        let reports = verify_indexes(&backend, Some("contacts")).await.unwrap();
+        let role = reports.iter().find(|r| r.attribute == "role").unwrap();
+        assert_eq!(role.index, "contacts_role");
+        assert_eq!(role.checked, 3);
+        assert!(reports.iter().all(IndexReport::is_consistent));
+        assert!(reports.iter().all(|r| r.category == "contacts"));
+
+        let index = backend.describe_index("contacts_role").await.unwrap();
+        rebuild_index(&backend, &index).await.unwrap();
+        let recreated = backend.describe_index("contacts_role").await.unwrap();
+        assert_eq!(recreated.index_key_name, "role");
+        let report = verify_index(&backend, &index).await.unwrap();
+        assert!(report.is_consistent());
+        assert_eq!(report.checked, 3);
+    }
+}
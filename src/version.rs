@@ -0,0 +1,48 @@
+//! Per-category data versions.
+//!
+//! [`DataVersions`] counts the writes made through a backend and its clones,
+//! per table and category, so results computed from the data can tell
+//! whether it changed since (see [`crate::recall_cache`]). Bookkeeping
+//! written by reads, such as access times and recall logs, is not counted.
+//! Writes made by other processes are not seen.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// Version key counting schema and index changes, which change how every
+/// category is queried.
+pub const SCHEMAS_VERSION_KEY: &str = "#schemas";
+
+/// Write counters, per table and category, shared between clones.
+#[derive(Debug, Clone, Default)]
+pub struct DataVersions {
+    counters: Arc<Mutex<HashMap<String, BTreeMap<String, u64>>>>,
+}
+
+impl DataVersions {
+    /// Count a write to `category` of `table`.
+    pub fn bump(&self, table: &str, category: &str) {
+        let mut counters = self.counters.lock().unwrap();
+        *counters
+            .entry(table.to_string())
+            .or_default()
+            .entry(category.to_string())
+            .or_default() += 1;
+    }
+
+    /// Writes counted for `category` of `table`.
+    pub fn version(&self, table: &str, category: &str) -> u64 {
+        let counters = self.counters.lock().unwrap();
+        counters
+            .get(table)
+            .and_then(|categories| categories.get(category))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Versions of every category of `table` written so far.
+    pub fn snapshot(&self, table: &str) -> BTreeMap<String, u64> {
+        let counters = self.counters.lock().unwrap();
+        counters.get(table).cloned().unwrap_or_default()
+    }
+}
@@ -0,0 +1,248 @@
+//! Weekly review reports.
+//!
+//! `fmemory review-week` collects what changed over the last [`REVIEW_WINDOW`]
+//! (decisions made, issues opened and resolved, new contacts) into a
+//! [`WeeklyReport`], then has the LLM write a short prose summary of it.
+//! Decisions, open issues and contacts count when created in the window;
+//! resolved issues count when last written (`updated_at`) in it.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::backend::MemoryBackend;
+use crate::chunk::is_chunk;
+use crate::error::MemoryError;
+use crate::sort::UPDATED_AT_ATTRIBUTE;
+use crate::ttl::filter_expired;
+
+/// How far back `review-week` looks.
+pub const REVIEW_WINDOW: Duration = Duration::days(7);
+
+/// Categories a weekly review reads.
+pub const REVIEW_CATEGORIES: &[&str] = &["decisions", "issues", "contacts"];
+
+/// Maximum number of items read per category.
+pub const REVIEW_SCAN_LIMIT: usize = 10_000;
+
+/// What changed between `since` and `until` (RFC 3339).
+#[derive(Debug, Clone, Serialize)]
+pub struct WeeklyReport {
+    pub since: String,
+    pub until: String,
+    pub decisions: Vec<Value>,
+    pub issues_opened: Vec<Value>,
+    pub issues_resolved: Vec<Value>,
+    pub contacts: Vec<Value>,
+    /// Prose synthesis, filled in by the caller.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+impl WeeklyReport {
+    /// Sort `items` into report sections, keeping those created (or, for
+    /// resolved issues, last written) in `[since, until]`. Chunks are skipped.
+    pub fn from_items(items: Vec<Value>, since: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        let mut report = WeeklyReport {
+            since: since.to_rfc3339(),
+            until: until.to_rfc3339(),
+            decisions: Vec::new(),
+            issues_opened: Vec::new(),
+            issues_resolved: Vec::new(),
+            contacts: Vec::new(),
+            summary: None,
+        };
+        let in_window = |item: &Value, attr: &str| {
+            item[attr]
+                .as_str()
+                .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                .is_some_and(|ts| ts >= since && ts <= until)
+        };
+        for item in items.into_iter().filter(|item| !is_chunk(item)) {
+            let created = in_window(&item, "created_at");
+            let section = match item["category"].as_str() {
+                Some("issues") if is_resolved(&item) => {
+                    let written = created || in_window(&item, UPDATED_AT_ATTRIBUTE);
+                    written.then_some(&mut report.issues_resolved)
+                }
+                Some("decisions") if created => Some(&mut report.decisions),
+                Some("issues") if created => Some(&mut report.issues_opened),
+                Some("contacts") if created => Some(&mut report.contacts),
+                _ => None,
+            };
+            if let Some(section) = section {
+                section.push(item);
+            }
+        }
+        for section in [
+            &mut report.decisions,
+            &mut report.issues_opened,
+            &mut report.issues_resolved,
+            &mut report.contacts,
+        ] {
+            section.sort_by(|a, b| {
+                let created_at =
+                    |item: &Value| item["created_at"].as_str().unwrap_or_default().to_string();
+                created_at(a).cmp(&created_at(b))
+            });
+        }
+        report
+    }
+
+    /// Whether nothing happened in the window.
+    pub fn is_empty(&self) -> bool {
+        self.sections().iter().all(|(_, items)| items.is_empty())
+    }
+
+    /// Section titles and items, in report order.
+    pub fn sections(&self) -> [(&'static str, &[Value]); 4] {
+        [
+            ("Decisions", &self.decisions),
+            ("Issues opened", &self.issues_opened),
+            ("Issues resolved", &self.issues_resolved),
+            ("New contacts", &self.contacts),
+        ]
+    }
+}
+
+/// `resolved` as stored by the LLM or importers: `true` or `"true"`.
+fn is_resolved(item: &Value) -> bool {
+    match &item["resolved"] {
+        Value::Bool(b) => *b,
+        Value::String(s) => s.eq_ignore_ascii_case("true"),
+        _ => false,
+    }
+}
+
+/// One-line label for a report entry: its title, symptom, or name, falling
+/// back to the key.
+pub fn headline(item: &Value) -> &str {
+    ["title", "decision", "symptom", "name"]
+        .iter()
+        .find_map(|attr| item[*attr].as_str().filter(|s| !s.is_empty()))
+        .or_else(|| item["key"].as_str())
+        .unwrap_or("?")
+}
+
+/// Read [`REVIEW_CATEGORIES`] and build the report for the window ending at
+/// `until`. Expired items are dropped unless `include_expired`.
+pub async fn gather_week(
+    backend: &MemoryBackend,
+    until: DateTime<Utc>,
+    include_expired: bool,
+) -> Result<WeeklyReport, MemoryError> {
+    let mut items = Vec::new();
+    for category in REVIEW_CATEGORIES {
+        items.extend(backend.query(category, None, REVIEW_SCAN_LIMIT).await?);
+    }
+    if !include_expired {
+        items = filter_expired(items);
+    }
+    Ok(WeeklyReport::from_items(
+        items,
+        until - REVIEW_WINDOW,
+        until,
+    ))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TABLE_NAME;
+    use serde_json::json;
+
+    fn item(category: &str, key: &str, days_ago: i64, extra: Value) -> Value {
+        let mut item = json!({
+            "category": category,
+            "key": key,
+            "created_at": (Utc::now() - Duration::days(days_ago)).to_rfc3339(),
+        });
+        if let Value::Object(extra) = extra {
+            item.as_object_mut().unwrap().extend(extra);
+        }
+        item
+    }
+
+    #[test]
+    fn test_report_sections() {
+        let now = Utc::now();
+        let items = vec![
+            item(
+                "decisions",
+                "use-postgres",
+                2,
+                json!({"title": "Use Postgres"}),
+            ),
+            item("decisions", "old-decision", 30, json!({})),
+            item("issues", "login-500", 1, json!({"resolved": false})),
+            item("issues", "flaky-ci", 3, json!({"resolved": true})),
+            item("issues", "slow-build", 4, json!({"resolved": "true"})),
+            item(
+                "issues",
+                "old-leak",
+                40,
+                json!({"resolved": true, "updated_at": (now - Duration::days(6)).to_rfc3339()}),
+            ),
+            item("contacts", "alice", 5, json!({"name": "Alice"})),
+            item(
+                "decisions",
+                "use-postgres#1",
+                2,
+                json!({"chunk_of": "use-postgres"}),
+            ),
+        ];
+        let report = WeeklyReport::from_items(items, now - REVIEW_WINDOW, now);
+        let keys = |section: &[Value]| -> Vec<String> {
+            section
+                .iter()
+                .filter_map(|i| i["key"].as_str().map(String::from))
+                .collect()
+        };
+        assert_eq!(keys(&report.decisions), vec!["use-postgres"]);
+        assert_eq!(keys(&report.issues_opened), vec!["login-500"]);
+        assert_eq!(
+            keys(&report.issues_resolved),
+            vec!["old-leak", "slow-build", "flaky-ci"]
+        );
+        assert_eq!(keys(&report.contacts), vec!["alice"]);
+        assert!(!report.is_empty());
+        assert_eq!(headline(&report.decisions[0]), "Use Postgres");
+        assert_eq!(headline(&report.issues_opened[0]), "login-500");
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert!(json.get("summary").is_none());
+        assert_eq!(json["issues_resolved"].as_array().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gather_week() {
+        let backend = MemoryBackend::in_memory(TABLE_NAME.to_string());
+        for entry in [
+            item("decisions", "recent", 1, json!({})),
+            item(
+                "contacts",
+                "expired",
+                1,
+                json!({"expires_at": "2020-01-01T00:00:00Z"}),
+            ),
+            item("notes", "ignored", 1, json!({})),
+        ] {
+            backend.put_item(entry).await.unwrap();
+        }
+        let report = gather_week(&backend, Utc::now(), false).await.unwrap();
+        assert_eq!(report.decisions.len(), 1);
+        assert!(report.contacts.is_empty());
+
+        let report = gather_week(&backend, Utc::now(), true).await.unwrap();
+        assert_eq!(report.contacts.len(), 1);
+
+        let empty = gather_week(&backend, Utc::now() - Duration::days(30), false)
+            .await
+            .unwrap();
+        assert!(empty.is_empty());
+    }
+}